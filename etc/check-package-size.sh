@@ -41,6 +41,7 @@ echo "in root: gitoxide CLI"
 (enter gix-features && indent cargo diet -n --package-size-limit 65KB)
 (enter gix-ref && indent cargo diet -n --package-size-limit 55KB)
 (enter gix-diff && indent cargo diet -n --package-size-limit 35KB)
+(enter gix-merge && indent cargo diet -n --package-size-limit 15KB)
 (enter gix-traverse && indent cargo diet -n --package-size-limit 15KB)
 (enter gix-url && indent cargo diet -n --package-size-limit 35KB)
 (enter gix-validate && indent cargo diet -n --package-size-limit 10KB)
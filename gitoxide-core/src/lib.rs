@@ -75,9 +75,13 @@ pub mod mailmap;
 #[cfg(feature = "organize")]
 pub mod organize;
 pub mod pack;
+#[cfg(feature = "progress-events")]
+pub mod progress;
 #[cfg(feature = "query")]
 pub mod query;
 pub mod repository;
+#[cfg(feature = "blocking-client")]
+pub mod serve;
 
 mod discover;
 pub use discover::discover;
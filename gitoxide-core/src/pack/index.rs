@@ -82,6 +82,8 @@ pub fn from_pack(
         iteration_mode: ctx.iteration_mode.into(),
         index_version: pack::index::Version::default(),
         object_hash: ctx.object_hash,
+        max_pack_size: None,
+        ..Default::default()
     };
     let out = ctx.out;
     let format = ctx.format;
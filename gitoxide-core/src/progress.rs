@@ -0,0 +1,226 @@
+//! Turn the progress of any long-running operation, like cloning, fetching, verifying packs or checking out, into a stream
+//! of structured events for consumption by applications like GUIs, which would otherwise have to parse terminal output.
+//!
+//! All operations report their progress through a [`prodash::tree::Item`], so all that's needed is to pass a child
+//! of a [`Root`] to them, and to stream its events with [`stream()`] or [`json_lines()`].
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::{atomic::Ordering, Weak},
+    time::{Duration, Instant},
+};
+
+use gix::progress::prodash::{
+    self,
+    messages::{MessageCopyState, MessageLevel},
+    progress::Key,
+    tree::Root,
+};
+
+/// An event describing a change to the progress tree.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    /// A task was added or changed its progress.
+    Progress {
+        /// The identifier of the task, which is unique for as long as the task exists.
+        id: usize,
+        /// The identifier of the task that contains this one, if it isn't a top-level task.
+        parent: Option<usize>,
+        /// The name of the task.
+        name: String,
+        /// The amount of progress made so far, or `None` if the task is only used to group other tasks.
+        current: Option<usize>,
+        /// The amount of progress at which the task is done, if known.
+        total: Option<usize>,
+        /// The unit of `current` and `total`, like `objects` or `B`, if known.
+        unit: Option<String>,
+        /// The amount of progress made per second since the last event of this task, if there was one.
+        rate: Option<f64>,
+    },
+    /// The task with `id` is complete and won't be mentioned again.
+    Done {
+        /// The identifier of the task.
+        id: usize,
+    },
+    /// A task sent a message.
+    Message {
+        /// The severity of the message, one of `info`, `failure` or `success`.
+        level: &'static str,
+        /// The name of the task sending the message.
+        origin: String,
+        /// The message itself.
+        message: String,
+    },
+}
+
+/// Configure how events are produced.
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// How long to wait between checking the progress tree for changes.
+    pub interval: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            interval: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Check `root` for changes periodically in a thread, and pass the resulting events to `emit` until it returns `false`
+/// or until `root` is dropped, at which point all remaining tasks are reported as done.
+/// Tasks are always reported as done before the tasks that contain them.
+///
+/// To receive events on a channel, use `move |event| sender.send(event).is_ok()` as `emit`.
+pub fn stream(
+    root: Weak<Root>,
+    options: Options,
+    mut emit: impl FnMut(Event) -> bool + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut state = State::default();
+        let mut snapshot = Vec::new();
+        let mut messages = Vec::new();
+        let mut events = Vec::new();
+        loop {
+            let Some(root) = root.upgrade() else {
+                let mut ids: Vec<_> = state.tasks.into_values().map(|task| task.id).collect();
+                ids.sort_unstable_by(|a, b| b.cmp(a));
+                for id in ids {
+                    if !emit(Event::Done { id }) {
+                        break;
+                    }
+                }
+                return;
+            };
+            root.sorted_snapshot(&mut snapshot);
+            state.messages = Some(root.copy_new_messages(&mut messages, state.messages));
+            drop(root);
+
+            state.update(&snapshot, &mut events);
+            events.extend(messages.drain(..).map(|message| Event::Message {
+                level: match message.level {
+                    MessageLevel::Info => "info",
+                    MessageLevel::Failure => "failure",
+                    MessageLevel::Success => "success",
+                },
+                origin: message.origin,
+                message: message.message,
+            }));
+            for event in events.drain(..) {
+                if !emit(event) {
+                    return;
+                }
+            }
+            std::thread::sleep(options.interval);
+        }
+    })
+}
+
+/// Like [`stream()`], but write each event as a single line of JSON to `out`, stopping on the first write error.
+pub fn json_lines(
+    root: Weak<Root>,
+    options: Options,
+    mut out: impl Write + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    stream(root, options, move |event| {
+        serde_json::to_writer(&mut out, &event)
+            .map_err(std::io::Error::from)
+            .and_then(|()| out.write_all(b"\n"))
+            .and_then(|()| out.flush())
+            .is_ok()
+    })
+}
+
+/// The last known state of a task.
+struct Task {
+    id: usize,
+    name: String,
+    current: Option<usize>,
+    total: Option<usize>,
+    seen_at: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    tasks: HashMap<Key, Task>,
+    next_id: usize,
+    messages: Option<MessageCopyState>,
+}
+
+impl State {
+    /// Compare `snapshot` to what we have seen before, and produce `events` for all tasks that were added, changed or removed.
+    fn update(&mut self, snapshot: &[(Key, prodash::progress::Task)], events: &mut Vec<Event>) {
+        let now = Instant::now();
+        let mut done: HashMap<Key, Task> = std::mem::take(&mut self.tasks);
+        // The ids of the most recent tasks at each level, which are parents to the tasks that follow.
+        let mut parents: Vec<(Key, usize)> = Vec::new();
+        for (key, task) in snapshot {
+            let level = key.level() as usize;
+            parents.truncate(level.saturating_sub(1));
+            let parent = parents
+                .last()
+                .filter(|(parent_key, _)| key.shares_parent_with(parent_key, parent_key.level()))
+                .map(|(_, id)| *id);
+            let current = task.progress.as_ref().map(|value| value.step.load(Ordering::SeqCst));
+            let total = task.progress.as_ref().and_then(|value| value.done_at);
+
+            let (id, rate) = match done.remove(key) {
+                Some(previous) if previous.name == task.name => {
+                    if previous.current == current && previous.total == total {
+                        parents.push((*key, previous.id));
+                        self.tasks.insert(*key, previous);
+                        continue;
+                    }
+                    let elapsed = now.duration_since(previous.seen_at).as_secs_f64();
+                    let rate = match (previous.current, current) {
+                        (Some(previous), Some(current)) if elapsed > 0.0 => {
+                            Some(current.saturating_sub(previous) as f64 / elapsed)
+                        }
+                        _ => None,
+                    };
+                    (previous.id, rate)
+                }
+                previous => {
+                    if let Some(previous) = previous {
+                        events.push(Event::Done { id: previous.id });
+                    }
+                    self.next_id += 1;
+                    (self.next_id, None)
+                }
+            };
+            parents.push((*key, id));
+            self.tasks.insert(
+                *key,
+                Task {
+                    id,
+                    name: task.name.clone(),
+                    current,
+                    total,
+                    seen_at: now,
+                },
+            );
+            events.push(Event::Progress {
+                id,
+                parent,
+                name: task.name.clone(),
+                current,
+                total,
+                unit: task.progress.as_ref().and_then(|value| {
+                    let unit = value.unit.as_ref()?;
+                    let mut buf = String::new();
+                    unit.as_display_value()
+                        .display_unit(&mut buf, current.unwrap_or_default())
+                        .ok()?;
+                    Some(buf)
+                }),
+                rate,
+            });
+        }
+        let mut done: Vec<_> = done.into_values().map(|task| task.id).collect();
+        done.sort_unstable_by(|a, b| b.cmp(a));
+        events.extend(done.into_iter().map(|id| Event::Done { id }));
+    }
+}
@@ -0,0 +1,381 @@
+use crate::OutputFormat;
+
+pub struct Options {
+    pub format: OutputFormat,
+    /// The amount of paths and objects to list in each ranking.
+    pub limit: usize,
+}
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+pub(crate) mod function {
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        io,
+        path::Path,
+    };
+
+    use anyhow::bail;
+    use bytesize::ByteSize;
+    use gix::{
+        bstr::{BString, ByteVec},
+        odb::{find::Header, HeaderExt},
+        ObjectId,
+    };
+
+    use super::Options;
+    use crate::OutputFormat;
+
+    /// The size of all versions of a path, along with the amount of versions.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    #[derive(Default, Debug, Clone)]
+    pub struct PathStats {
+        pub path: String,
+        /// The amount of distinct blobs seen at this path, where each is only attributed to the path it was first seen at.
+        pub versions: usize,
+        pub size: u64,
+        pub disk_size: u64,
+    }
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    #[derive(Debug, Clone)]
+    pub struct Object {
+        pub id: ObjectId,
+        /// The path at which the object was first seen.
+        pub path: String,
+        pub size: u64,
+        pub disk_size: u64,
+    }
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    #[derive(Default, Debug, Clone)]
+    pub struct KindStats {
+        pub count: usize,
+        pub size: u64,
+        pub disk_size: u64,
+    }
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    #[derive(Default, Debug, Clone)]
+    pub struct DeltaChains {
+        pub packed_objects: usize,
+        pub deltified_objects: usize,
+        pub longest: u32,
+        pub total_length: u64,
+        /// The amount of objects by the length of their delta chain.
+        pub histogram: BTreeMap<u32, usize>,
+    }
+
+    /// Everything that was added to the repository by the commits of one month.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    #[derive(Default, Debug, Clone)]
+    pub struct Growth {
+        /// The month in `YYYY-MM` format.
+        pub month: String,
+        pub commits: usize,
+        pub objects: usize,
+        pub size: u64,
+        pub disk_size: u64,
+    }
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    #[derive(Default, Debug, Clone)]
+    pub struct Report {
+        pub objects: BTreeMap<String, KindStats>,
+        pub delta_chains: DeltaChains,
+        /// Paths ordered by the size their versions take on disk, largest first.
+        pub paths: Vec<PathStats>,
+        /// Blobs ordered by the size they take on disk, largest first.
+        pub largest_blobs: Vec<Object>,
+        /// The growth of the repository per month, oldest first.
+        pub growth: Vec<Growth>,
+    }
+
+    /// Analyze which paths and objects reachable from any reference contribute most to the size of `repo`, how long
+    /// delta chains are, and how the repository grew over time.
+    ///
+    /// Each object is attributed to the commit and path it was first seen at when traversing commits oldest first.
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+    pub fn analyze(
+        mut repo: gix::Repository,
+        mut progress: impl gix::Progress,
+        mut out: impl io::Write,
+        mut err: impl io::Write,
+        Options { format, limit }: Options,
+    ) -> anyhow::Result<()> {
+        if format == OutputFormat::Human {
+            writeln!(err, "Sizes on disk are those of the compressed and possibly deltified objects in packs, or of loose objects").ok();
+        }
+        repo.object_cache_size_if_unset(4 * 1024 * 1024);
+
+        progress.set_name("collecting commits".into());
+        progress.init(None, gix::progress::count("commits"));
+        let mut tips = Vec::new();
+        let mut tags = Vec::new();
+        for reference in repo.references()?.all()? {
+            let mut reference = reference.map_err(|err| anyhow::anyhow!("{err}"))?;
+            if let Some(id) = reference.try_id() {
+                if repo.find_header(id)?.kind() == gix::object::Kind::Tag {
+                    tags.push(id.detach());
+                }
+            }
+            if let Ok(commit) = reference.peel_to_commit() {
+                tips.push(commit.id);
+            }
+        }
+        let mut commits = Vec::new();
+        for info in repo
+            .rev_walk(tips)
+            .sorting(gix::revision::walk::Sorting::ByCommitTimeNewestFirst)
+            .all()?
+        {
+            let info = info?;
+            commits.push((info.id, info.commit_time.unwrap_or_default()));
+            progress.inc();
+        }
+        commits.reverse();
+
+        progress.set_name("analyzing".into());
+        progress.init(Some(commits.len()), gix::progress::count("commits"));
+        let mut state = State {
+            disk_sizes: packed_disk_sizes(&repo)?,
+            ..Default::default()
+        };
+        for id in tags {
+            if state.seen.insert(id) {
+                state.account(&repo, id, None, None)?;
+            }
+        }
+        let mut growth = BTreeMap::<String, Growth>::new();
+        let mut trees = Vec::new();
+        for (id, time) in commits {
+            if gix::interrupt::is_triggered() {
+                bail!("Cancelled by user");
+            }
+            let month = gix::date::Time::new(time, 0).format(gix::date::time::format::SHORT)[..7].to_owned();
+            let growth = growth.entry(month).or_insert_with_key(|month| Growth {
+                month: month.clone(),
+                ..Default::default()
+            });
+            growth.commits += 1;
+            if state.seen.insert(id) {
+                state.account(&repo, id, None, Some(&mut *growth))?;
+            }
+
+            let tree_id = repo.find_commit(id)?.tree_id()?.detach();
+            if state.seen.insert(tree_id) {
+                trees.push((BString::default(), tree_id));
+            }
+            while let Some((path, tree_id)) = trees.pop() {
+                state.account(&repo, tree_id, None, Some(&mut *growth))?;
+                let tree = repo.find_tree(tree_id)?;
+                for entry in tree.decode()?.entries {
+                    if entry.mode.is_commit() || !state.seen.insert(entry.oid.to_owned()) {
+                        continue;
+                    }
+                    let mut entry_path = path.clone();
+                    if !entry_path.is_empty() {
+                        entry_path.push_byte(b'/');
+                    }
+                    entry_path.push_str(entry.filename);
+                    if entry.mode.is_tree() {
+                        trees.push((entry_path, entry.oid.to_owned()));
+                    } else {
+                        state.account(&repo, entry.oid.to_owned(), Some(entry_path), Some(&mut *growth))?;
+                    }
+                }
+            }
+            progress.inc();
+        }
+
+        let mut paths: Vec<_> = state.paths.into_values().collect();
+        paths.sort_by(|a, b| b.disk_size.cmp(&a.disk_size).then_with(|| a.path.cmp(&b.path)));
+        paths.truncate(limit);
+        let mut blobs = state.blobs;
+        blobs.sort_by(|a, b| b.disk_size.cmp(&a.disk_size).then_with(|| a.id.cmp(&b.id)));
+        blobs.truncate(limit);
+        let report = Report {
+            objects: state.kinds,
+            delta_chains: state.delta_chains,
+            paths,
+            largest_blobs: blobs,
+            growth: growth.into_values().collect(),
+        };
+
+        match format {
+            OutputFormat::Human => print(&report, out)?,
+            #[cfg(feature = "serde")]
+            OutputFormat::Json => serde_json::to_writer_pretty(&mut out, &report)?,
+        }
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct State {
+        /// The size of each packed object in its pack.
+        disk_sizes: HashMap<ObjectId, u64>,
+        seen: HashSet<ObjectId>,
+        kinds: BTreeMap<String, KindStats>,
+        delta_chains: DeltaChains,
+        paths: HashMap<BString, PathStats>,
+        blobs: Vec<Object>,
+    }
+
+    impl State {
+        /// Account for the object with `id`, which is a blob if it has a `path`, and add it to `growth` if set.
+        fn account(
+            &mut self,
+            repo: &gix::Repository,
+            id: ObjectId,
+            path: Option<BString>,
+            growth: Option<&mut Growth>,
+        ) -> anyhow::Result<()> {
+            let (kind, size) = match repo.objects.header(id)? {
+                Header::Loose { kind, size } => (kind, size),
+                Header::Packed(packed) => {
+                    let chains = &mut self.delta_chains;
+                    chains.packed_objects += 1;
+                    chains.deltified_objects += usize::from(packed.num_deltas > 0);
+                    chains.longest = chains.longest.max(packed.num_deltas);
+                    chains.total_length += u64::from(packed.num_deltas);
+                    *chains.histogram.entry(packed.num_deltas).or_default() += 1;
+                    (packed.kind, packed.object_size)
+                }
+            };
+            let disk_size = match self.disk_sizes.get(&id) {
+                Some(size) => *size,
+                None => loose_disk_size(repo.objects.store_ref().path(), &id),
+            };
+
+            let stats = self.kinds.entry(kind.to_string()).or_default();
+            stats.count += 1;
+            stats.size += size;
+            stats.disk_size += disk_size;
+            if let Some(growth) = growth {
+                growth.objects += 1;
+                growth.size += size;
+                growth.disk_size += disk_size;
+            }
+            if let Some(path) = path {
+                let stats = self.paths.entry(path.clone()).or_insert_with(|| PathStats {
+                    path: path.to_string(),
+                    ..Default::default()
+                });
+                stats.versions += 1;
+                stats.size += size;
+                stats.disk_size += disk_size;
+                self.blobs.push(Object {
+                    id,
+                    path: path.to_string(),
+                    size,
+                    disk_size,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    /// Return the size of all objects in all packs of `repo`, as the distance of their entry to the next one.
+    fn packed_disk_sizes(repo: &gix::Repository) -> anyhow::Result<HashMap<ObjectId, u64>> {
+        let mut sizes = HashMap::new();
+        let pack_dir = repo.objects.store_ref().path().join("pack");
+        let entries = match std::fs::read_dir(&pack_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(sizes),
+            Err(err) => return Err(err.into()),
+        };
+        for entry in entries {
+            let index_path = entry?.path();
+            if index_path.extension() != Some("idx".as_ref()) {
+                continue;
+            }
+            let Ok(data) = std::fs::metadata(index_path.with_extension("pack")) else {
+                continue;
+            };
+            let index = gix::odb::pack::index::File::at(&index_path, repo.object_hash())?;
+            let end_of_entries = data.len().saturating_sub(repo.object_hash().len_in_bytes() as u64);
+            let mut entries: Vec<_> = index.iter().map(|entry| (entry.pack_offset, entry.oid)).collect();
+            entries.sort_unstable();
+            let next_offsets = entries
+                .iter()
+                .skip(1)
+                .map(|(offset, _)| *offset)
+                .chain(Some(end_of_entries));
+            for ((offset, id), next_offset) in entries.iter().zip(next_offsets) {
+                sizes.insert(*id, next_offset.saturating_sub(*offset));
+            }
+        }
+        Ok(sizes)
+    }
+
+    /// Return the size of the loose object with `id` in the `objects_dir`, or 0 if it doesn't exist there.
+    fn loose_disk_size(objects_dir: &Path, id: &ObjectId) -> u64 {
+        let hex = id.to_hex().to_string();
+        std::fs::metadata(objects_dir.join(&hex[..2]).join(&hex[2..])).map_or(0, |m| m.len())
+    }
+
+    fn print(report: &Report, mut out: impl io::Write) -> io::Result<()> {
+        writeln!(out, "objects:")?;
+        for (kind, stats) in &report.objects {
+            writeln!(
+                out,
+                "  {kind:<6} {:>9} {:>10} ({} on disk)",
+                stats.count,
+                ByteSize(stats.size).to_string(),
+                ByteSize(stats.disk_size)
+            )?;
+        }
+
+        let chains = &report.delta_chains;
+        writeln!(
+            out,
+            "\ndelta chains: {} of {} packed objects are deltified, with an average length of {:.1} and the longest being {}",
+            chains.deltified_objects,
+            chains.packed_objects,
+            if chains.deltified_objects == 0 {
+                0.0
+            } else {
+                chains.total_length as f64 / chains.deltified_objects as f64
+            },
+            chains.longest
+        )?;
+
+        writeln!(out, "\npaths by size on disk:")?;
+        for path in &report.paths {
+            writeln!(
+                out,
+                "  {:>10} {:>10} {:>5} versions  {}",
+                ByteSize(path.disk_size).to_string(),
+                ByteSize(path.size).to_string(),
+                path.versions,
+                path.path
+            )?;
+        }
+
+        writeln!(out, "\nlargest blobs by size on disk:")?;
+        for blob in &report.largest_blobs {
+            writeln!(
+                out,
+                "  {:>10} {:>10}  {}  {}",
+                ByteSize(blob.disk_size).to_string(),
+                ByteSize(blob.size).to_string(),
+                blob.id,
+                blob.path
+            )?;
+        }
+
+        writeln!(out, "\ngrowth per month:")?;
+        for growth in &report.growth {
+            writeln!(
+                out,
+                "  {} {:>6} commits {:>8} objects {:>10} ({} on disk)",
+                growth.month,
+                growth.commits,
+                growth.objects,
+                ByteSize(growth.size).to_string(),
+                ByteSize(growth.disk_size)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::OutputFormat;
+
+pub struct Options {
+    pub format: OutputFormat,
+}
+
+pub(crate) mod function {
+    use std::{io, path::Path};
+
+    use anyhow::bail;
+    use gix::{
+        bstr::{BStr, BString},
+        filter::plumbing::{
+            eol::audit::{Change, LineEndings},
+            pipeline::audit_eol::Outcome,
+        },
+        index::entry::{Mode, Stage},
+    };
+
+    use crate::{repository::attributes::check_eol::Options, OutputFormat};
+
+    pub fn check_eol(
+        repo: gix::Repository,
+        pathspec: Vec<BString>,
+        mut out: impl io::Write,
+        mut err: impl io::Write,
+        Options { format }: Options,
+    ) -> anyhow::Result<()> {
+        if format != OutputFormat::Human {
+            bail!("JSON output isn't implemented yet");
+        }
+
+        let (mut pipeline, index) = repo.filter_pipeline(None)?;
+        let mut pathspec = repo.pathspec(
+            true,
+            pathspec.iter(),
+            true,
+            &index,
+            gix::worktree::stack::state::attributes::Source::WorktreeThenIdMapping.adjust_for_bare(repo.is_bare()),
+        )?;
+        let workdir = repo.work_dir();
+        let mut num_files = 0;
+        if let Some(entries) = pathspec.index_entries_with_paths(&index) {
+            for (path, entry) in entries {
+                if !matches!(entry.mode, Mode::FILE | Mode::FILE_EXECUTABLE) || entry.stage() != Stage::Unconflicted {
+                    continue;
+                }
+                let blob = repo.find_blob(entry.id)?;
+                let worktree = match workdir {
+                    Some(workdir) => match std::fs::read(workdir.join(gix::path::from_bstr(path))) {
+                        Ok(data) => Some(data),
+                        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                        Err(err) => return Err(err.into()),
+                    },
+                    None => None,
+                };
+                let outcome = pipeline.audit_eol(path, Some(&blob.data), worktree.as_deref())?;
+                if outcome.changes.is_empty() {
+                    continue;
+                }
+                num_files += 1;
+                print_outcome(&outcome, path, &mut out)?;
+            }
+        }
+
+        if num_files != 0 {
+            out.flush()?;
+            writeln!(err, "{num_files} file(s) would have their line endings changed").ok();
+            bail!("Line endings aren't normalized");
+        }
+        Ok(())
+    }
+
+    fn print_outcome(outcome: &Outcome, path: &BStr, mut out: impl io::Write) -> io::Result<()> {
+        for change in &outcome.changes {
+            match change {
+                Change::Renormalize => writeln!(out, "{path}: CRLF in the index would be renormalized to LF")?,
+                Change::ConvertOnAdd => {
+                    writeln!(out, "{path}: CRLF in the worktree would be converted to LF when added")?;
+                }
+                Change::ConvertOnCheckout { worktree, checkout } => writeln!(
+                    out,
+                    "{path}: {} in the worktree would become {} on checkout",
+                    line_endings(*worktree),
+                    line_endings(*checkout)
+                )?,
+            }
+        }
+        if outcome.sources.is_empty() {
+            writeln!(out, "\t(configured by core.autocrlf and core.eol)")?;
+        }
+        for source in &outcome.sources {
+            writeln!(
+                out,
+                "\t{}:{}:{}\t{}",
+                source.path.as_deref().map(Path::to_string_lossy).unwrap_or_default(),
+                source.line_number,
+                source.pattern,
+                source.assignment.as_ref()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn line_endings(line_endings: LineEndings) -> &'static str {
+        match line_endings {
+            LineEndings::None => "no line endings",
+            LineEndings::Lf => "LF",
+            LineEndings::CrLf => "CRLF",
+            LineEndings::Mixed => "mixed LF and CRLF",
+        }
+    }
+}
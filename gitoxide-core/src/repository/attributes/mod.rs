@@ -1,3 +1,6 @@
+pub mod check_eol;
+pub use check_eol::function::check_eol;
+
 pub mod query;
 pub use query::function::query;
 
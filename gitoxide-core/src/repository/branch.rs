@@ -0,0 +1,417 @@
+use std::collections::HashSet;
+
+use crate::OutputFormat;
+use anyhow::{anyhow, bail, Context};
+use gix::{
+    bstr::{BString, ByteSlice},
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    },
+};
+
+pub mod list {
+    pub struct Options {
+        pub all: bool,
+        pub contains: Option<String>,
+        pub merged: Option<String>,
+        /// If 1, also show the commit each branch points to, and if 2, also show upstream branches
+        /// along with how many commits the branch is ahead and behind of it.
+        pub verbose: u8,
+    }
+}
+
+pub fn list(
+    mut repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    format: OutputFormat,
+    list::Options {
+        all,
+        contains,
+        merged,
+        verbose,
+    }: list::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("JSON output isn't implemented yet");
+    }
+    repo.object_cache_size_if_unset(4 * 1024 * 1024);
+
+    let platform = repo.references()?;
+    let mut branches = platform
+        .local_branches()?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| anyhow::anyhow!(err))?;
+    if all {
+        branches.extend(
+            platform
+                .remote_branches()?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| anyhow::anyhow!(err))?,
+        );
+    }
+    if let Some(rev) = contains {
+        let commit = repo.rev_parse_single(format!("{rev}^{{commit}}").as_str())?;
+        branches = repo.references_containing(commit, branches)?;
+    }
+    if let Some(rev) = merged {
+        let commit = repo.rev_parse_single(format!("{rev}^{{commit}}").as_str())?;
+        branches = repo.references_merged_into(commit, branches)?;
+    }
+
+    let head_name = repo.head_name()?;
+    let name_width = branches
+        .iter()
+        .map(|branch| branch.name().shorten().chars().count())
+        .max()
+        .unwrap_or_default();
+    for mut branch in branches {
+        let marker = if head_name.as_ref().map(AsRef::as_ref) == Some(branch.name()) {
+            '*'
+        } else {
+            ' '
+        };
+        let name = branch.name().shorten().to_owned();
+        if verbose == 0 {
+            writeln!(out, "{marker} {name}")?;
+            continue;
+        }
+
+        let commit = branch.peel_to_id_in_place()?.object()?.try_into_commit()?;
+        let upstream = if verbose > 1 {
+            upstream_info(&repo, branch.name())?
+                .map(|info| format!("[{info}] "))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        writeln!(
+            out,
+            "{marker} {name:name_width$} {id} {upstream}{summary}",
+            id = commit.id().shorten_or_id(),
+            summary = commit.message()?.summary()
+        )?;
+    }
+    Ok(())
+}
+
+pub mod create {
+    pub struct Options {
+        /// Reset the branch to the start point if it already exists.
+        pub force: bool,
+    }
+}
+
+pub fn create(
+    repo: gix::Repository,
+    name: &str,
+    start_point: Option<&str>,
+    create::Options { force }: create::Options,
+) -> anyhow::Result<()> {
+    let start_point = start_point.unwrap_or("HEAD");
+    let id = repo.rev_parse_single(format!("{start_point}^{{commit}}").as_str())?;
+    let full_name = branch_name(name)?;
+    if !force && repo.try_find_reference(full_name.as_ref())?.is_some() {
+        bail!("a branch named '{name}' already exists");
+    }
+    if force && is_checked_out(&repo, full_name.as_ref())? {
+        bail!("cannot force update the branch '{name}' as it is checked out");
+    }
+    repo.reference(
+        full_name,
+        id,
+        if force {
+            PreviousValue::Any
+        } else {
+            PreviousValue::MustNotExist
+        },
+        format!("branch: Created from {start_point}"),
+    )?;
+    Ok(())
+}
+
+pub mod delete {
+    pub struct Options {
+        /// Delete branches even if they are not fully merged into their upstream branch or `HEAD`.
+        pub force: bool,
+    }
+}
+
+pub fn delete(
+    repo: gix::Repository,
+    names: &[String],
+    out: &mut dyn std::io::Write,
+    delete::Options { force }: delete::Options,
+) -> anyhow::Result<()> {
+    for name in names {
+        let full_name = branch_name(name)?;
+        let mut branch = repo
+            .try_find_reference(full_name.as_ref())?
+            .ok_or_else(|| anyhow!("branch '{name}' not found"))?;
+        if is_checked_out(&repo, branch.name())? {
+            bail!("cannot delete branch '{name}' as it is checked out");
+        }
+        let id = branch.peel_to_id_in_place()?.detach();
+        if !force {
+            let (merge_target, target_name) = match upstream_name(&repo, branch.name())? {
+                Some(upstream) => match repo.try_find_reference(upstream.as_ref())? {
+                    Some(mut upstream_ref) => (
+                        Some(upstream_ref.peel_to_id_in_place()?.detach()),
+                        upstream.as_bstr().to_owned(),
+                    ),
+                    None => (head_id(&repo)?, "HEAD".into()),
+                },
+                None => (head_id(&repo)?, "HEAD".into()),
+            };
+            let is_merged = match merge_target {
+                Some(target) => !repo.references_merged_into(target, Some(branch.clone()))?.is_empty(),
+                None => false,
+            };
+            if !is_merged {
+                bail!("the branch '{name}' is not fully merged into '{target_name}', use --force to delete it anyway");
+            }
+        }
+        repo.edit_reference(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::MustExistAndMatch(branch.target().into_owned()),
+                log: RefLog::AndReference,
+            },
+            name: branch.name().to_owned(),
+            deref: false,
+        })?;
+        edit_local_config(&repo, |config| {
+            config.remove_section("branch", Some(name.as_bytes().as_bstr()));
+            Ok(())
+        })?;
+        writeln!(
+            out,
+            "Deleted branch {name} (was {}).",
+            gix::prelude::ObjectIdExt::attach(id, &repo).shorten_or_id()
+        )?;
+    }
+    Ok(())
+}
+
+pub mod rename {
+    pub struct Options {
+        /// Overwrite the branch with the new name if it already exists.
+        pub force: bool,
+    }
+}
+
+/// Rename the branch `old`, or the current branch if `None`, to `new`, along with its configuration and reflog.
+pub fn rename(
+    repo: gix::Repository,
+    old: Option<&str>,
+    new: &str,
+    rename::Options { force }: rename::Options,
+) -> anyhow::Result<()> {
+    let old_name = match old {
+        Some(old) => branch_name(old)?,
+        None => current_branch(&repo)?,
+    };
+    let new_name = branch_name(new)?;
+    let branch = repo
+        .try_find_reference(old_name.as_ref())?
+        .ok_or_else(|| anyhow!("branch '{}' not found", old_name.shorten()))?;
+    if !force && repo.try_find_reference(new_name.as_ref()).ok().flatten().is_some() {
+        bail!("a branch named '{new}' already exists");
+    }
+
+    let message: BString = format!("Branch: renamed {} to {}", old_name.as_bstr(), new_name.as_bstr()).into();
+    let mut edits = vec![RefEdit {
+        change: Change::Rename {
+            expected: PreviousValue::MustExistAndMatch(branch.target().into_owned()),
+            new_name: new_name.clone(),
+            new_name_expected: if force {
+                PreviousValue::Any
+            } else {
+                PreviousValue::MustNotExist
+            },
+            message: message.clone(),
+        },
+        name: old_name.clone(),
+        deref: false,
+    }];
+    if is_checked_out(&repo, old_name.as_ref())? {
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message,
+                },
+                expected: PreviousValue::MustExistAndMatch(Target::Symbolic(old_name.clone())),
+                new: Target::Symbolic(new_name.clone()),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        });
+    }
+    repo.edit_references(edits)?;
+
+    edit_local_config(&repo, |config| {
+        let old = old_name.shorten();
+        if config.section("branch", Some(old)).is_ok() {
+            if force {
+                config.remove_section("branch", Some(new_name.shorten()));
+            }
+            config.rename_section(
+                "branch",
+                Some(old),
+                "branch",
+                Some(new_name.shorten().to_owned().into()),
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Set the upstream of `branch`, or the current branch if `None`, to the remote tracking branch or local branch `upstream`.
+pub fn set_upstream(repo: gix::Repository, branch: Option<&str>, upstream: &str) -> anyhow::Result<()> {
+    let branch = match branch {
+        Some(name) => branch_name(name)?,
+        None => current_branch(&repo)?,
+    };
+    if repo.try_find_reference(branch.as_ref())?.is_none() {
+        bail!("branch '{}' not found", branch.shorten());
+    }
+
+    let (remote, merge) = match repo.try_find_reference(format!("refs/heads/{upstream}").as_str())? {
+        Some(local) => (BString::from("."), local.name().as_bstr().to_owned()),
+        None => {
+            let remote_branch = repo
+                .find_reference(format!("refs/remotes/{upstream}").as_str())
+                .with_context(|| format!("the requested upstream branch '{upstream}' does not exist"))?;
+            let (remote, name) = repo
+                .remote_names()
+                .into_iter()
+                .filter_map(|remote| {
+                    let name = remote_branch
+                        .name()
+                        .shorten()
+                        .strip_prefix(remote.as_bytes())?
+                        .strip_prefix(b"/")?;
+                    Some((remote.into_owned(), name.as_bstr().to_owned()))
+                })
+                .max_by_key(|(remote, _)| remote.len())
+                .ok_or_else(|| anyhow!("no configured remote matches the upstream branch '{upstream}'"))?;
+            (remote, format!("refs/heads/{name}").into())
+        }
+    };
+    edit_local_config(&repo, |config| {
+        let mut section = config.section_mut_or_create_new("branch", Some(branch.shorten()))?;
+        section.set("remote".try_into()?, remote.as_ref());
+        section.set("merge".try_into()?, merge.as_ref());
+        Ok(())
+    })
+}
+
+/// Remove the upstream configuration of `branch`, or the current branch if `None`.
+pub fn unset_upstream(repo: gix::Repository, branch: Option<&str>) -> anyhow::Result<()> {
+    let branch = match branch {
+        Some(name) => branch_name(name)?,
+        None => current_branch(&repo)?,
+    };
+    edit_local_config(&repo, |config| {
+        let Ok(mut section) = config.section_mut("branch", Some(branch.shorten())) else {
+            bail!("branch '{}' has no upstream information", branch.shorten());
+        };
+        let had_upstream = section.remove("remote").is_some() | section.remove("merge").is_some();
+        let is_empty = section.num_values() == 0;
+        if !had_upstream {
+            bail!("branch '{}' has no upstream information", branch.shorten());
+        }
+        if is_empty {
+            config.remove_section("branch", Some(branch.shorten()));
+        }
+        Ok(())
+    })
+}
+
+fn branch_name(name: &str) -> anyhow::Result<FullName> {
+    let full_name: FullName = format!("refs/heads/{name}")
+        .try_into()
+        .with_context(|| format!("'{name}' is not a valid branch name"))?;
+    Ok(full_name)
+}
+
+fn current_branch(repo: &gix::Repository) -> anyhow::Result<FullName> {
+    repo.head_name()?
+        .ok_or_else(|| anyhow!("HEAD is detached and doesn't point to a branch"))
+}
+
+fn is_checked_out(repo: &gix::Repository, name: &gix::refs::FullNameRef) -> anyhow::Result<bool> {
+    Ok(repo.head_name()?.as_ref().map(AsRef::as_ref) == Some(name))
+}
+
+fn head_id(repo: &gix::Repository) -> anyhow::Result<Option<gix::ObjectId>> {
+    Ok(repo.head()?.id().map(gix::Id::detach))
+}
+
+/// Return the full name of the upstream branch of `branch`, which is either a remote tracking branch or a local branch.
+fn upstream_name(repo: &gix::Repository, branch: &gix::refs::FullNameRef) -> anyhow::Result<Option<FullName>> {
+    let is_local = repo
+        .config_snapshot()
+        .string(format!("branch.{}.remote", branch.shorten()).as_str())
+        .is_some_and(|remote| remote.as_ref() == ".");
+    Ok(if is_local {
+        repo.branch_remote_ref_name(branch, gix::remote::Direction::Fetch)
+            .transpose()?
+            .map(std::borrow::Cow::into_owned)
+    } else {
+        repo.branch_remote_tracking_ref_name(branch, gix::remote::Direction::Fetch)
+            .transpose()?
+            .map(std::borrow::Cow::into_owned)
+    })
+}
+
+/// Describe the upstream of `branch` like `origin/main: ahead 1, behind 2`, or `None` if there is no upstream.
+fn upstream_info(repo: &gix::Repository, branch: &gix::refs::FullNameRef) -> anyhow::Result<Option<String>> {
+    let Some(upstream) = upstream_name(repo, branch)? else {
+        return Ok(None);
+    };
+    let short_name = upstream.shorten().to_owned();
+    let Some(mut upstream_ref) = repo.try_find_reference(upstream.as_ref())? else {
+        return Ok(Some(format!("{short_name}: gone")));
+    };
+    let upstream_id = upstream_ref.peel_to_id_in_place()?.detach();
+    let branch_id = repo.find_reference(branch)?.peel_to_id_in_place()?.detach();
+    let (ahead, behind) = ahead_behind(repo, branch_id, upstream_id)?;
+    Ok(Some(match (ahead, behind) {
+        (0, 0) => short_name.to_string(),
+        (ahead, 0) => format!("{short_name}: ahead {ahead}"),
+        (0, behind) => format!("{short_name}: behind {behind}"),
+        (ahead, behind) => format!("{short_name}: ahead {ahead}, behind {behind}"),
+    }))
+}
+
+/// Return the amount of commits reachable only from `ours`, and only from `theirs`, respectively.
+fn ahead_behind(repo: &gix::Repository, ours: gix::ObjectId, theirs: gix::ObjectId) -> anyhow::Result<(usize, usize)> {
+    if ours == theirs {
+        return Ok((0, 0));
+    }
+    let ancestors = |tip: gix::ObjectId| -> anyhow::Result<HashSet<gix::ObjectId>> {
+        repo.rev_walk(Some(tip))
+            .all()?
+            .map(|info| info.map(|info| info.id).map_err(Into::into))
+            .collect()
+    };
+    let ours = ancestors(ours)?;
+    let theirs = ancestors(theirs)?;
+    Ok((ours.difference(&theirs).count(), theirs.difference(&ours).count()))
+}
+
+/// Load the repository-local configuration file, let `edit` change it, and write it back.
+pub(crate) fn edit_local_config(
+    repo: &gix::Repository,
+    edit: impl FnOnce(&mut gix::config::File<'static>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let path = repo.common_dir().join("config");
+    let mut config = gix::config::File::from_path_no_includes(path.clone(), gix::config::Source::Local)?;
+    edit(&mut config)?;
+    let mut buf = Vec::new();
+    config.write_to(&mut buf)?;
+    std::fs::write(&path, buf).with_context(|| format!("Could not write configuration to '{}'", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,436 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::atomic::AtomicBool,
+};
+
+use anyhow::{anyhow, bail, Context};
+use gix::{
+    bstr::{BStr, BString, ByteSlice},
+    index::entry::{Flags, Mode, Stage, Stat},
+    prelude::ObjectIdExt,
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    },
+    worktree::stack::state::attributes::Source,
+};
+
+pub mod switch {
+    pub struct Options {
+        /// Create a branch of the given name pointing to the target, and switch to it.
+        pub new_branch: Option<String>,
+        /// If `true`, reset [`new_branch`](Self::new_branch) if it already exists.
+        pub force_new_branch: bool,
+        /// Switch to the target commit with a detached `HEAD`, even if it is a branch.
+        pub detach: bool,
+        /// Discard local modifications and overwrite untracked files that are in the way.
+        pub force: bool,
+    }
+}
+
+/// Switch to the branch `target`, or to the commit it names if `detach` is set, updating the index and
+/// the worktree only where they differ between the current `HEAD` and the new commit.
+///
+/// Local modifications are carried over, unless they are in files that differ between both commits, which
+/// makes the operation fail unless `force` is set.
+pub fn switch(
+    repo: gix::Repository,
+    target: Option<&str>,
+    out: &mut dyn std::io::Write,
+    should_interrupt: &AtomicBool,
+    switch::Options {
+        new_branch,
+        force_new_branch,
+        detach,
+        force,
+    }: switch::Options,
+) -> anyhow::Result<()> {
+    let previous = describe_head(&repo)?;
+    let (new_head, message) = match new_branch {
+        Some(name) => {
+            let start_point = target.unwrap_or("HEAD");
+            let commit = repo.rev_parse_single(format!("{start_point}^{{commit}}").as_str())?;
+            let branch: FullName = format!("refs/heads/{name}")
+                .try_into()
+                .with_context(|| format!("'{name}' is not a valid branch name"))?;
+            if !force_new_branch && repo.try_find_reference(branch.as_ref())?.is_some() {
+                bail!("a branch named '{name}' already exists");
+            }
+            update_worktree(&repo, commit.detach(), force, should_interrupt)?;
+            repo.reference(
+                branch.clone(),
+                commit,
+                PreviousValue::Any,
+                format!("branch: Created from {start_point}"),
+            )?;
+            (Target::Symbolic(branch), format!("Switched to a new branch '{name}'"))
+        }
+        None => {
+            let target = target.ok_or_else(|| anyhow!("Need a branch or revision to switch to"))?;
+            let branch = (!detach)
+                .then(|| repo.try_find_reference(format!("refs/heads/{target}").as_str()))
+                .transpose()?
+                .flatten();
+            match branch {
+                Some(mut branch) => {
+                    let commit = branch.peel_to_id_in_place()?.detach();
+                    update_worktree(&repo, commit, force, should_interrupt)?;
+                    let message = if repo.head_name()?.as_ref() == Some(&branch.inner.name) {
+                        format!("Already on '{target}'")
+                    } else {
+                        format!("Switched to branch '{target}'")
+                    };
+                    (Target::Symbolic(branch.inner.name), message)
+                }
+                None if detach => {
+                    let commit = repo.rev_parse_single(format!("{target}^{{commit}}").as_str())?;
+                    update_worktree(&repo, commit.detach(), force, should_interrupt)?;
+                    let commit = commit.object()?.into_commit();
+                    let message = format!(
+                        "HEAD is now at {} {}",
+                        commit.id().shorten_or_id(),
+                        commit.message()?.summary()
+                    );
+                    (Target::Object(commit.id), message)
+                }
+                None => bail!("a branch is expected, got '{target}' - use --detach to switch to a commit"),
+            }
+        }
+    };
+
+    let to = match &new_head {
+        Target::Symbolic(name) => name.shorten().to_owned(),
+        Target::Object(id) => id.to_hex().to_string().into(),
+    };
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: format!("checkout: moving from {previous} to {to}").into(),
+            },
+            expected: PreviousValue::Any,
+            new: new_head,
+        },
+        name: "HEAD".try_into()?,
+        deref: false,
+    })?;
+    writeln!(out, "{message}")?;
+    Ok(())
+}
+
+/// Like `git checkout`, switch to `target` which is either a branch or a revision to detach `HEAD` at
+/// if `paths` is empty. Otherwise, restore `paths` from the tree of `target`, or from the index if there is
+/// no `target`, in both the index and the worktree, overwriting local modifications.
+pub fn checkout(
+    repo: gix::Repository,
+    target: Option<&str>,
+    paths: Vec<BString>,
+    out: &mut dyn std::io::Write,
+    should_interrupt: &AtomicBool,
+    options: switch::Options,
+) -> anyhow::Result<()> {
+    if paths.is_empty() {
+        let detach = options.detach
+            || target.map_or(Ok(false), |target| {
+                repo.try_find_reference(format!("refs/heads/{target}").as_str())
+                    .map(|branch| branch.is_none())
+            })?;
+        return switch(
+            repo,
+            target,
+            out,
+            should_interrupt,
+            switch::Options { detach, ..options },
+        );
+    }
+    if options.new_branch.is_some() || options.detach {
+        bail!("Cannot create branches or detach HEAD while checking out paths");
+    }
+    restore_paths(repo, target, paths, should_interrupt)
+}
+
+fn restore_paths(
+    repo: gix::Repository,
+    source: Option<&str>,
+    patterns: Vec<BString>,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let workdir = repo
+        .work_dir()
+        .ok_or_else(|| anyhow!("Cannot checkout paths in a bare repository"))?
+        .to_owned();
+    let mut index = repo.open_index()?;
+    let source_index = match source {
+        Some(rev) => {
+            let tree = repo.rev_parse_single(format!("{rev}^{{tree}}").as_str())?;
+            Some(repo.index_from_tree(&tree)?)
+        }
+        None => None,
+    };
+    let mut pathspec = repo.pathspec(true, patterns.iter(), true, &index, Source::IdMapping)?;
+    let mut to_checkout = gix::index::State::new(repo.object_hash());
+    for entry in source_index.as_deref().unwrap_or(&index).entries() {
+        let path = entry.path_in(source_index.as_deref().unwrap_or(&index).path_backing());
+        if entry.stage() != Stage::Unconflicted || !pathspec.is_included(path, Some(false)) {
+            continue;
+        }
+        to_checkout.dangerously_push_entry(Stat::default(), entry.id, Flags::empty(), entry.mode, path);
+    }
+    drop(pathspec);
+    if to_checkout.entries().is_empty() {
+        bail!(
+            "pathspec '{}' did not match any file(s) known to git",
+            patterns
+                .iter()
+                .map(|pattern| pattern.to_str_lossy())
+                .collect::<Vec<_>>()
+                .join("', '")
+        );
+    }
+
+    let mut opts = repo.checkout_options(Source::IdMapping)?;
+    opts.overwrite_existing = true;
+    checkout_entries(&repo, &mut to_checkout, &workdir, should_interrupt, opts)?;
+
+    let mut added = false;
+    for entry in to_checkout.entries() {
+        let path = entry.path(&to_checkout);
+        match index.entry_mut_by_path_and_stage(path, Stage::Unconflicted) {
+            Some(existing) => {
+                existing.stat = entry.stat;
+                existing.id = entry.id;
+                existing.mode = entry.mode;
+            }
+            None => {
+                index.dangerously_push_entry(entry.stat, entry.id, Flags::empty(), entry.mode, path);
+                added = true;
+            }
+        }
+    }
+    if added {
+        index.sort_entries();
+    }
+    write_index_without_tree_cache(&mut index)
+}
+
+/// Make the index and the worktree match the tree of `new_commit`, only touching paths that differ between the tree of
+/// the current `HEAD` and the new tree, unless `force` is set, which resets all paths to the new tree.
+pub(crate) fn update_worktree(
+    repo: &gix::Repository,
+    new_commit: gix::ObjectId,
+    force: bool,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let workdir = repo
+        .work_dir()
+        .ok_or_else(|| anyhow!("Cannot switch branches in a bare repository"))?
+        .to_owned();
+    let index = repo.index_or_empty()?;
+    let old = match repo.head()?.id() {
+        Some(id) => entries_by_path(&*repo.index_from_tree(&id.object()?.peel_to_tree()?.id)?),
+        None => BTreeMap::new(),
+    };
+    let new_tree = new_commit.attach(repo).object()?.peel_to_tree()?.id;
+    let new = entries_by_path(&*repo.index_from_tree(&new_tree)?);
+    let changed: BTreeSet<&BStr> = old
+        .iter()
+        .filter(|(path, entry)| new.get(*path) != Some(entry))
+        .map(|(path, _)| path.as_ref())
+        .chain(
+            new.iter()
+                .filter(|(path, entry)| old.get(*path) != Some(entry))
+                .map(|(path, _)| path.as_ref()),
+        )
+        .collect();
+
+    if !force {
+        let modified = locally_modified_paths(repo)?;
+        let mut would_be_overwritten = Vec::new();
+        let mut untracked_in_the_way = Vec::new();
+        for path in &changed {
+            let in_index = index
+                .entry_by_path_and_stage(path, Stage::Unconflicted)
+                .map(|entry| (entry.id, entry.mode));
+            if in_index.as_ref() != old.get(*path) || modified.contains(*path) {
+                would_be_overwritten.push(*path);
+            } else if in_index.is_none()
+                && new.contains_key(*path)
+                && workdir.join(gix::path::from_bstr(*path)).symlink_metadata().is_ok()
+            {
+                untracked_in_the_way.push(*path);
+            }
+        }
+        if !would_be_overwritten.is_empty() {
+            bail!(
+                "Your local changes to the following files would be overwritten by checkout:\n\t{}\nPlease commit or stash them, or use --force to discard them.",
+                join_paths(&would_be_overwritten)
+            );
+        }
+        if !untracked_in_the_way.is_empty() {
+            bail!(
+                "The following untracked working tree files would be overwritten by checkout:\n\t{}\nPlease move or remove them, or use --force to overwrite them.",
+                join_paths(&untracked_in_the_way)
+            );
+        }
+    }
+
+    let removed: BTreeSet<&BStr> = if force {
+        index
+            .entries()
+            .iter()
+            .map(|entry| entry.path(&index))
+            .chain(old.keys().map(AsRef::as_ref))
+            .filter(|path| !new.contains_key(*path))
+            .collect()
+    } else {
+        changed
+            .iter()
+            .copied()
+            .filter(|path| !new.contains_key(*path))
+            .collect()
+    };
+    for path in removed {
+        remove_from_worktree(&workdir, path)?;
+    }
+
+    let mut to_checkout = gix::index::State::new(repo.object_hash());
+    for (path, (id, mode)) in &new {
+        if force || changed.contains(path.as_bstr()) {
+            to_checkout.dangerously_push_entry(Stat::default(), *id, Flags::empty(), *mode, path.as_ref());
+        }
+    }
+    let mut opts = repo.checkout_options(Source::IdMapping)?;
+    opts.overwrite_existing = true;
+    checkout_entries(repo, &mut to_checkout, &workdir, should_interrupt, opts)?;
+
+    let mut state = gix::index::State::new(repo.object_hash());
+    for (path, (id, mode)) in &new {
+        if let Some(entry) = to_checkout.entry_by_path_and_stage(path.as_ref(), Stage::Unconflicted) {
+            state.dangerously_push_entry(entry.stat, entry.id, Flags::empty(), entry.mode, path.as_ref());
+        } else if let Some(entry) = index.entry_by_path_and_stage(path.as_ref(), Stage::Unconflicted) {
+            state.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path.as_ref());
+        } else if !old.contains_key(path) {
+            state.dangerously_push_entry(Stat::default(), *id, Flags::empty(), *mode, path.as_ref());
+        }
+    }
+    if !force {
+        // Keep newly added files around, just like `git` does.
+        for entry in index.entries() {
+            let path = entry.path(&index);
+            if entry.stage() == Stage::Unconflicted && !old.contains_key(path) && !new.contains_key(path) {
+                state.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+            }
+        }
+    }
+    state.sort_entries();
+    let mut index = gix::index::File::from_state(state, repo.index_path());
+    write_index_without_tree_cache(&mut index)
+}
+
+pub(crate) fn checkout_entries(
+    repo: &gix::Repository,
+    to_checkout: &mut gix::index::State,
+    workdir: &Path,
+    should_interrupt: &AtomicBool,
+    opts: gix::worktree::state::checkout::Options,
+) -> anyhow::Result<()> {
+    to_checkout.sort_entries();
+    let outcome = gix::worktree::state::checkout(
+        to_checkout,
+        workdir,
+        repo.objects.clone().into_arc()?,
+        &gix::progress::Discard,
+        &gix::progress::Discard,
+        should_interrupt,
+        opts,
+    )?;
+    if should_interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+        bail!("Interrupted");
+    }
+    if let Some(err) = outcome.errors.first() {
+        bail!("Failed to checkout '{}': {}", err.path, err.error);
+    }
+    Ok(())
+}
+
+/// Return all paths that have changes in the worktree compared to the index.
+fn locally_modified_paths(repo: &gix::Repository) -> anyhow::Result<BTreeSet<BString>> {
+    let mut paths = BTreeSet::new();
+    for item in repo
+        .status(gix::progress::Discard)?
+        .index_worktree_rewrites(None)
+        .index_worktree_submodules(gix::status::Submodule::AsConfigured { check_dirty: true })
+        .index_worktree_options_mut(|opts| {
+            opts.dirwalk_options = None;
+        })
+        .into_index_worktree_iter(Vec::new())?
+    {
+        let item = item?;
+        if item.summary().is_some() {
+            paths.insert(item.rela_path().to_owned());
+        }
+    }
+    Ok(paths)
+}
+
+fn entries_by_path(index: &gix::index::State) -> BTreeMap<BString, (gix::ObjectId, Mode)> {
+    index
+        .entries()
+        .iter()
+        .map(|entry| (entry.path(index).to_owned(), (entry.id, entry.mode)))
+        .collect()
+}
+
+/// Remove the file at `rela_path` and all of its then empty parent directories.
+pub(crate) fn remove_from_worktree(workdir: &Path, rela_path: &BStr) -> anyhow::Result<()> {
+    let path = workdir.join(gix::path::from_bstr(rela_path));
+    let res = match path.symlink_metadata() {
+        Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(&path),
+        Ok(meta) if meta.is_symlink() => gix::fs::symlink::remove(&path),
+        Ok(_) => std::fs::remove_file(&path),
+        Err(err) => Err(err),
+    };
+    match res {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err).with_context(|| format!("Could not remove '{}'", path.display())),
+    }
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+    Ok(())
+}
+
+/// As entries have changed, the tree-cache extension would be outdated and must not be written.
+pub(crate) fn write_index_without_tree_cache(index: &mut gix::index::File) -> anyhow::Result<()> {
+    index.write(gix::index::write::Options {
+        extensions: gix::index::write::Extensions::Given {
+            tree_cache: false,
+            end_of_index_entry: true,
+        },
+        ..Default::default()
+    })?;
+    Ok(())
+}
+
+fn describe_head(repo: &gix::Repository) -> anyhow::Result<String> {
+    let head = repo.head()?;
+    Ok(match head.referent_name() {
+        Some(name) => name.shorten().to_string(),
+        None => head.id().map_or_else(|| "HEAD".into(), |id| id.to_hex().to_string()),
+    })
+}
+
+fn join_paths(paths: &[&BStr]) -> String {
+    paths
+        .iter()
+        .map(|path| path.to_str_lossy())
+        .collect::<Vec<_>>()
+        .join("\n\t")
+}
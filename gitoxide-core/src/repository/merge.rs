@@ -0,0 +1,241 @@
+use std::{collections::BTreeSet, sync::atomic::AtomicBool};
+
+use anyhow::{anyhow, bail};
+use gix::{
+    bstr::{BStr, ByteSlice},
+    index::entry::{Flags, Stage, Stat},
+    merge::tree::{Conflict, ConflictKind, Entry},
+    prelude::ObjectIdExt,
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    },
+};
+
+use crate::repository::checkout::{update_worktree, write_index_without_tree_cache};
+
+pub struct Options {
+    /// Refuse to do anything unless the merge can be resolved as fast-forward.
+    pub ff_only: bool,
+    /// Create a merge commit even if the merge could be resolved as fast-forward.
+    pub no_ff: bool,
+    /// Abort the merge in progress, resetting the index and the worktree to `HEAD`.
+    pub abort: bool,
+    /// The message of the merge commit, instead of the default one.
+    pub message: Option<String>,
+}
+
+/// Merge `rev` into `HEAD`, fast-forwarding if possible unless `no_ff` is set, or abort the merge in progress.
+///
+/// If the merge has conflicts, the index contains all sides of each conflicting path, the worktree contains the
+/// conflicting files with conflict markers, and `MERGE_HEAD` and `MERGE_MSG` are written so that the merge can be
+/// concluded with a commit.
+pub fn merge(
+    repo: gix::Repository,
+    rev: Option<&str>,
+    out: &mut dyn std::io::Write,
+    should_interrupt: &AtomicBool,
+    Options {
+        ff_only,
+        no_ff,
+        abort,
+        message,
+    }: Options,
+) -> anyhow::Result<()> {
+    let merge_head_path = repo.git_dir().join("MERGE_HEAD");
+    if abort {
+        if !merge_head_path.is_file() {
+            bail!("There is no merge to abort (MERGE_HEAD missing).");
+        }
+        let head = repo.head_id()?.detach();
+        update_worktree(&repo, head, true, should_interrupt)?;
+        return remove_merge_state(&repo);
+    }
+    if merge_head_path.is_file() {
+        bail!("You have not concluded your merge (MERGE_HEAD exists). Please commit your changes before you merge.");
+    }
+
+    let rev = rev.ok_or_else(|| anyhow!("Need a revision to merge"))?;
+    let theirs = repo.rev_parse_single(format!("{rev}^{{commit}}").as_str())?.detach();
+    let Some(head) = repo.head()?.id().map(gix::Id::detach) else {
+        update_worktree(&repo, theirs, false, should_interrupt)?;
+        update_head(&repo, None, theirs, format!("merge {rev}: Fast-forward"))?;
+        return Ok(());
+    };
+    let base = match repo.merge_base(head, theirs) {
+        Ok(base) => Some(base.detach()),
+        Err(gix::repository::merge_base::Error::NotFound { .. }) => None,
+        Err(err) => return Err(err.into()),
+    };
+    if base == Some(theirs) {
+        writeln!(out, "Already up to date.")?;
+        return Ok(());
+    }
+    if base == Some(head) && !no_ff {
+        update_worktree(&repo, theirs, false, should_interrupt)?;
+        write_orig_head(&repo, head)?;
+        update_head(&repo, Some(head), theirs, format!("merge {rev}: Fast-forward"))?;
+        writeln!(
+            out,
+            "Updating {}..{}\nFast-forward",
+            head.attach(&repo).shorten_or_id(),
+            theirs.attach(&repo).shorten_or_id()
+        )?;
+        return Ok(());
+    }
+    if ff_only {
+        bail!("Not possible to fast-forward, aborting.");
+    }
+
+    let outcome = repo.merge_trees(
+        base,
+        head,
+        theirs,
+        gix::merge::blob::Labels {
+            ours: "HEAD".into(),
+            theirs: rev.into(),
+        },
+    )?;
+    let message = match message {
+        Some(message) => message,
+        None => default_message(&repo, rev)?,
+    };
+    update_worktree(&repo, outcome.tree, false, should_interrupt)?;
+    write_orig_head(&repo, head)?;
+
+    if outcome.has_conflicts() {
+        write_conflicts_to_index(&repo, &outcome.conflicts)?;
+        let mut merge_msg = format!("{message}\n\n# Conflicts:\n");
+        merge_msg.extend(
+            outcome
+                .conflicts
+                .iter()
+                .map(|conflict| format!("#\t{}\n", conflict.path)),
+        );
+        std::fs::write(&merge_head_path, format!("{theirs}\n"))?;
+        std::fs::write(repo.git_dir().join("MERGE_MSG"), merge_msg)?;
+        std::fs::write(repo.git_dir().join("MERGE_MODE"), if no_ff { "no-ff" } else { "" })?;
+        for conflict in &outcome.conflicts {
+            writeln!(out, "{}", describe_conflict(conflict, rev))?;
+        }
+        bail!("Automatic merge failed; fix conflicts and then commit the result.");
+    }
+
+    let commit = repo.commit("HEAD", message, outcome.tree, [head, theirs])?;
+    writeln!(
+        out,
+        "Merge made by a three-way merge, creating {}.",
+        commit.shorten_or_id()
+    )?;
+    Ok(())
+}
+
+/// Point what `HEAD` refers to to `new`, expecting it to be at `previous`.
+fn update_head(
+    repo: &gix::Repository,
+    previous: Option<gix::ObjectId>,
+    new: gix::ObjectId,
+    message: String,
+) -> anyhow::Result<()> {
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: message.into(),
+            },
+            expected: match previous {
+                Some(id) => PreviousValue::MustExistAndMatch(Target::Object(id)),
+                None => PreviousValue::MustNotExist,
+            },
+            new: Target::Object(new),
+        },
+        name: "HEAD".try_into()?,
+        deref: true,
+    })?;
+    Ok(())
+}
+
+fn write_orig_head(repo: &gix::Repository, head: gix::ObjectId) -> anyhow::Result<()> {
+    std::fs::write(repo.git_dir().join("ORIG_HEAD"), format!("{head}\n"))?;
+    Ok(())
+}
+
+fn remove_merge_state(repo: &gix::Repository) -> anyhow::Result<()> {
+    for name in ["MERGE_HEAD", "MERGE_MSG", "MERGE_MODE"] {
+        match std::fs::remove_file(repo.git_dir().join(name)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Produce the message `git` would use when merging `rev` into the current branch.
+fn default_message(repo: &gix::Repository, rev: &str) -> anyhow::Result<String> {
+    let mut message = if repo.try_find_reference(format!("refs/heads/{rev}").as_str())?.is_some() {
+        format!("Merge branch '{rev}'")
+    } else if repo
+        .try_find_reference(format!("refs/remotes/{rev}").as_str())?
+        .is_some()
+    {
+        format!("Merge remote-tracking branch '{rev}'")
+    } else {
+        format!("Merge commit '{rev}'")
+    };
+    if let Some(head_name) = repo.head_name()? {
+        let branch = head_name.shorten();
+        if branch != "main" && branch != "master" {
+            message = format!("{message} into {branch}");
+        }
+    }
+    Ok(message)
+}
+
+/// Replace the unconflicted entries of all conflicting paths with one entry for each side that has the path.
+fn write_conflicts_to_index(repo: &gix::Repository, conflicts: &[Conflict]) -> anyhow::Result<()> {
+    let mut index = repo.open_index()?;
+    let paths: BTreeSet<&BStr> = conflicts.iter().map(|conflict| conflict.path.as_bstr()).collect();
+    index.remove_entries(|_, path, entry| entry.stage() == Stage::Unconflicted && paths.contains(path));
+    for conflict in conflicts {
+        for (stage, entry) in [
+            (Stage::Base, conflict.base),
+            (Stage::Ours, conflict.ours),
+            (Stage::Theirs, conflict.theirs),
+        ] {
+            let Some(Entry { mode, id }) = entry else { continue };
+            index.dangerously_push_entry(
+                Stat::default(),
+                id,
+                Flags::from_bits_retain((stage as u32) << 12),
+                mode.into(),
+                conflict.path.as_ref(),
+            );
+        }
+    }
+    index.sort_entries();
+    write_index_without_tree_cache(&mut index)
+}
+
+pub(crate) fn describe_conflict(conflict: &Conflict, rev: &str) -> String {
+    let path = &conflict.path;
+    match conflict.kind {
+        ConflictKind::Content => format!("CONFLICT (content): Merge conflict in {path}"),
+        ConflictKind::Unmergeable => format!("CONFLICT (content): Cannot merge {path} line by line"),
+        ConflictKind::Mode => format!("CONFLICT (mode): Both sides changed the mode of {path} differently"),
+        ConflictKind::ModifyDelete => {
+            let (deleted_in, modified_in) = if conflict.ours.is_none() {
+                ("HEAD", rev)
+            } else {
+                (rev, "HEAD")
+            };
+            format!(
+                "CONFLICT (modify/delete): {path} deleted in {deleted_in} and modified in {modified_in}. Version {modified_in} of {path} left in tree."
+            )
+        }
+        ConflictKind::FileDirectory => {
+            format!("CONFLICT (file/directory): {path} is a file on one side and a directory on the other")
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use crate::OutputFormat;
+
+pub struct Options {
+    pub format: OutputFormat,
+    /// Don't change the local repository or the push remote, but otherwise try to be as accurate as possible.
+    pub dry_run: bool,
+    /// The name or url of the remote to mirror, or the remote of the current branch if `None`.
+    pub remote: Option<String>,
+    /// Delete all local references that don't exist on the remote anymore.
+    pub prune: bool,
+    /// The name or url of the remote to push all local references to after fetching, deleting those that don't
+    /// exist locally.
+    pub push_to: Option<String>,
+    /// If set, limit the rate at which data is sent or received to the given amount of bytes per second.
+    pub max_bytes_per_second: Option<u64>,
+}
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+pub(crate) mod function {
+    use std::collections::BTreeSet;
+
+    use anyhow::{anyhow, bail, Context};
+    use gix::{
+        bstr::{BString, ByteSlice},
+        protocol::transport::client::{
+            throttle::{Throttle, Throttled},
+            MessageKind, Transport, TransportWithoutIO, WriteMode,
+        },
+        refs::transaction::{Change, PreviousValue, RefEdit, RefLog},
+        remote::{fetch::Status, Direction},
+        ObjectId,
+    };
+
+    use super::Options;
+    use crate::{repository::remote::by_name_or_url, OutputFormat};
+
+    /// The refspec to fetch all references of the remote into the same location, overwriting local changes.
+    const MIRROR_REFSPEC: &str = "+refs/*:refs/*";
+
+    /// Make all references of `repo` match those of the remote like `git fetch --mirror` would, optionally deleting
+    /// those that don't exist on the remote anymore, and push all references to another remote like `git push --mirror`.
+    ///
+    /// References are only changed after all objects were received, so an interrupted mirror operation leaves `repo`
+    /// untouched and can be resumed by running it again, which only transfers what's still missing.
+    pub fn mirror<P>(
+        repo: gix::Repository,
+        mut progress: P,
+        mut out: impl std::io::Write,
+        err: impl std::io::Write,
+        Options {
+            format,
+            dry_run,
+            remote,
+            prune,
+            push_to,
+            max_bytes_per_second,
+        }: Options,
+    ) -> anyhow::Result<()>
+    where
+        P: gix::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        if format != OutputFormat::Human {
+            bail!("JSON output isn't yet supported for mirroring.");
+        }
+        let throttle = Throttle::new(max_bytes_per_second, max_bytes_per_second);
+
+        let mut remote = by_name_or_url(&repo, remote.as_deref())?;
+        remote.replace_refspecs(Some(MIRROR_REFSPEC), Direction::Fetch)?;
+        remote = remote.with_fetch_tags(gix::remote::fetch::Tags::None);
+        let transport = connect(&remote, Direction::Fetch, throttle.clone())?;
+        let res = remote
+            .to_connection_with_transport(transport)
+            .prepare_fetch(&mut progress, Default::default())?
+            .with_dry_run(dry_run)
+            .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+
+        let ref_specs = remote.refspecs(Direction::Fetch);
+        let remote_names: BTreeSet<BString> = res
+            .ref_map
+            .remote_refs
+            .iter()
+            .map(|r| r.unpack().0.to_owned())
+            .collect();
+        let (negotiate, update_refs) = match res.status {
+            Status::NoPackReceived {
+                update_refs, negotiate, ..
+            } => (negotiate.unwrap_or_default(), update_refs),
+            Status::Change {
+                update_refs, negotiate, ..
+            } => (negotiate, update_refs),
+        };
+        crate::repository::fetch::function::print_updates(
+            &repo,
+            &negotiate,
+            update_refs,
+            ref_specs,
+            res.ref_map,
+            &mut out,
+            err,
+        )?;
+
+        if prune {
+            let stale: Vec<_> = mirrored_refs(&repo)?
+                .into_iter()
+                .filter(|(name, _)| !remote_names.contains(name.as_bstr()))
+                .collect();
+            for (name, _) in &stale {
+                writeln!(
+                    out,
+                    " * [{}] {}",
+                    if dry_run { "would prune" } else { "pruned" },
+                    name.as_bstr()
+                )?;
+            }
+            if !dry_run && !stale.is_empty() {
+                repo.edit_references(stale.into_iter().map(|(name, id)| RefEdit {
+                    change: Change::Delete {
+                        expected: PreviousValue::MustExistAndMatch(gix::refs::Target::Object(id)),
+                        log: RefLog::AndReference,
+                    },
+                    name,
+                    deref: false,
+                }))?;
+            }
+        }
+
+        if let Some(push_to) = push_to {
+            push_mirror(&repo, &push_to, throttle, &mut progress, &mut out, dry_run)?;
+        }
+        if dry_run {
+            writeln!(out, "DRY-RUN: No ref was updated and no pack was transferred.").ok();
+        }
+        Ok(())
+    }
+
+    /// Create a transport to `remote` for `direction` whose transfer rate is limited by `throttle`.
+    fn connect(
+        remote: &gix::Remote<'_>,
+        direction: Direction,
+        throttle: Throttle,
+    ) -> anyhow::Result<Throttled<Box<dyn Transport + Send>>> {
+        let repo = remote.repo();
+        let (url, version) = remote.sanitized_url_and_version(direction)?;
+        let ssh = if url.scheme == gix::url::Scheme::Ssh {
+            repo.ssh_connect_options()?
+        } else {
+            Default::default()
+        };
+        let transport = gix::protocol::transport::connect(
+            url,
+            gix::protocol::transport::client::connect::Options {
+                version,
+                ssh,
+                trace: repo
+                    .config_snapshot()
+                    .boolean("gitoxide.tracePacket")
+                    .unwrap_or_default(),
+            },
+        )?;
+        Ok(throttle.wrap(transport))
+    }
+
+    /// Return the names and targets of all references that are subject to mirroring, which excludes symbolic and
+    /// worktree-private references as well as those outside of `refs/`.
+    fn mirrored_refs(repo: &gix::Repository) -> anyhow::Result<Vec<(gix::refs::FullName, ObjectId)>> {
+        let mut out = Vec::new();
+        for reference in repo.references()?.prefixed("refs/")? {
+            let reference = reference.map_err(|err| anyhow!("{err}"))?.detach();
+            if reference.name.category().is_some_and(|c| c.is_worktree_private()) {
+                continue;
+            }
+            if let Some(id) = reference.target.try_id() {
+                out.push((reference.name.clone(), id.to_owned()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// A reference update to send to the remote.
+    struct Command {
+        name: gix::refs::FullName,
+        /// The value on the remote, or null if it doesn't exist there.
+        old: ObjectId,
+        /// The value to set, or null to delete the reference.
+        new: ObjectId,
+    }
+
+    /// Make the references of the remote `name_or_url` match all of our mirrored references, like `git push --mirror`.
+    fn push_mirror(
+        repo: &gix::Repository,
+        name_or_url: &str,
+        throttle: Throttle,
+        mut progress: &mut dyn gix::DynNestedProgress,
+        mut out: impl std::io::Write,
+        dry_run: bool,
+    ) -> anyhow::Result<()> {
+        let remote = by_name_or_url(repo, Some(name_or_url))?;
+        let url = remote
+            .url(Direction::Push)
+            .context("Remote didn't have a URL to push to")?
+            .to_owned();
+        let transport = connect(&remote, Direction::Push, throttle.clone())?;
+        let mut connection = remote.to_connection_with_transport(transport);
+        let authenticate = connection.configured_credentials(url.clone())?;
+        let transport = connection.transport_mut();
+        let handshake = gix::protocol::handshake(
+            &mut *transport,
+            gix::protocol::transport::Service::ReceivePack,
+            authenticate,
+            Vec::new(),
+            &mut progress,
+        )?;
+        let remote_refs = handshake.refs.ok_or_else(|| {
+            anyhow!("The remote didn't advertise its references, which protocol V2 doesn't support for pushing")
+        })?;
+
+        let null = repo.object_hash().null();
+        let mut remote_ids = Vec::new();
+        let mut commands = Vec::new();
+        let local = mirrored_refs(repo)?;
+        for r in &remote_refs {
+            let (name, Some(id), _) = r.unpack() else { continue };
+            if !name.starts_with(b"refs/") {
+                continue;
+            }
+            remote_ids.push(id.to_owned());
+            if !local.iter().any(|(local_name, _)| local_name.as_bstr() == name) {
+                commands.push(Command {
+                    name: name.try_into()?,
+                    old: id.to_owned(),
+                    new: null,
+                });
+            }
+        }
+        for (name, id) in local {
+            let old = remote_refs
+                .iter()
+                .find_map(|r| match r.unpack() {
+                    (remote_name, Some(remote_id), _) if remote_name == name.as_bstr() => Some(remote_id.to_owned()),
+                    _ => None,
+                })
+                .unwrap_or(null);
+            if old != id {
+                commands.push(Command { name, old, new: id });
+            }
+        }
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+
+        writeln!(out, "To {}", url.to_bstring())?;
+        if commands.is_empty() {
+            writeln!(out, "Everything up-to-date")?;
+        }
+        let capabilities = &handshake.capabilities;
+        if commands.iter().any(|c| c.new.is_null()) && !capabilities.contains("delete-refs") {
+            bail!("The remote doesn't support deleting references");
+        }
+        if commands.is_empty() || dry_run {
+            for command in &commands {
+                writeln!(out, " * [would {}] {}", describe(command), command.name)?;
+            }
+            if transport.connection_persists_across_multiple_requests() {
+                // Tell the server we are done without pushing anything.
+                transport
+                    .request(WriteMode::Binary, MessageKind::Flush, false)?
+                    .into_read()?;
+            }
+            return Ok(());
+        }
+
+        let mut requested = vec!["report-status".to_string()];
+        requested.retain(|name| capabilities.contains(name));
+        if capabilities.contains("ofs-delta") {
+            requested.push("ofs-delta".into());
+        }
+        requested.push(format!("agent={}", gix::protocol::agent(gix::env::agent())));
+        let report_status = capabilities.contains("report-status");
+
+        let mut writer = transport.request(WriteMode::OneLfTerminatedLinePerWriteCall, MessageKind::Flush, false)?;
+        for (index, command) in commands.iter().enumerate() {
+            let mut line = format!("{} {} {}", command.old, command.new, command.name);
+            if index == 0 {
+                line.push('\0');
+                line.push_str(&requested.join(" "));
+            }
+            std::io::Write::write_all(&mut writer, line.as_bytes())?;
+        }
+        writer.write_message(MessageKind::Flush)?;
+        let (mut pack_writer, mut reader) = writer.into_parts();
+        if commands.iter().any(|c| !c.new.is_null()) {
+            let wants: Vec<_> = commands.iter().map(|c| c.new).filter(|id| !id.is_null()).collect();
+            let common: Vec<_> = remote_ids.into_iter().filter(|id| repo.has_object(id)).collect();
+            let objects = crate::serve::upload_pack::objects_to_send(repo, &wants, &common, false)?;
+            progress.info(format!("Sending {} objects", objects.len()));
+            crate::serve::upload_pack::write_pack(repo, objects, &mut pack_writer, &gix::interrupt::IS_INTERRUPTED)?;
+        }
+        std::io::Write::flush(&mut pack_writer)?;
+        // The response can only be read once the request is complete.
+        drop(pack_writer);
+
+        let mut statuses = Vec::new();
+        if report_status {
+            let mut line = String::new();
+            loop {
+                line.clear();
+                if reader.readline_str(&mut line)? == 0 {
+                    break;
+                }
+                let line = line.trim_end();
+                if let Some(status) = line.strip_prefix("unpack ") {
+                    if status != "ok" {
+                        bail!("The remote failed to unpack the objects we sent: {status}");
+                    }
+                } else if let Some(name) = line.strip_prefix("ok ") {
+                    statuses.push((BString::from(name), None));
+                } else if let Some(rejected) = line.strip_prefix("ng ") {
+                    let (name, reason) = rejected.split_once(' ').unwrap_or((rejected, "rejected"));
+                    statuses.push((name.into(), Some(reason.to_owned())));
+                }
+            }
+        }
+
+        let mut num_rejected = 0;
+        for command in &commands {
+            let reason = statuses
+                .iter()
+                .find(|(name, _)| name.as_bstr() == command.name.as_bstr())
+                .and_then(|(_, reason)| reason.as_deref());
+            match reason {
+                Some(reason) => {
+                    num_rejected += 1;
+                    writeln!(out, " ! [rejected] {} ({reason})", command.name)?;
+                }
+                None => writeln!(out, " * [{}] {}", describe(command), command.name)?,
+            }
+        }
+        if num_rejected != 0 {
+            bail!("{num_rejected} reference update(s) were rejected by the remote");
+        }
+        Ok(())
+    }
+
+    fn describe(command: &Command) -> String {
+        if command.new.is_null() {
+            "deleted".into()
+        } else if command.old.is_null() {
+            "new".into()
+        } else {
+            format!(
+                "{}...{}",
+                command.old.to_hex_with_len(7),
+                command.new.to_hex_with_len(7)
+            )
+        }
+    }
+}
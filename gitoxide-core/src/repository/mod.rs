@@ -17,6 +17,8 @@ pub enum PathsOrPatterns {
     Patterns(Vec<BString>),
 }
 
+pub mod analyze;
+pub use analyze::function::analyze;
 #[cfg(feature = "archive")]
 pub mod archive;
 pub mod commit;
@@ -38,19 +40,33 @@ pub mod fetch;
 pub use clone::function::clone;
 #[cfg(feature = "blocking-client")]
 pub use fetch::function::fetch;
+#[cfg(feature = "blocking-client")]
+pub mod mirror;
+#[cfg(feature = "blocking-client")]
+pub use mirror::function::mirror;
+#[cfg(feature = "blocking-client")]
+pub mod remote_helper;
+#[cfg(feature = "blocking-client")]
+pub use remote_helper::function::remote_helper;
 
+pub mod branch;
+pub mod checkout;
 pub mod commitgraph;
 mod fsck;
 pub use fsck::function as fsck;
 pub mod index;
 pub mod mailmap;
+pub mod merge;
 mod merge_base;
 pub use merge_base::merge_base;
 pub mod odb;
 pub mod remote;
 pub mod revision;
+pub mod show;
+pub mod stash;
 pub mod status;
 pub mod submodule;
+pub mod tag;
 pub mod tree;
 pub mod verify;
 pub mod worktree;
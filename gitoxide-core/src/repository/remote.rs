@@ -1,3 +1,16 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, bail};
+use gix::{
+    bstr::{BString, ByteSlice},
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    },
+};
+
+use crate::repository::branch::edit_local_config;
+
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 mod refs_impl {
     use anyhow::bail;
@@ -336,3 +349,463 @@ pub(crate) fn by_name_or_url<'repo>(
 ) -> anyhow::Result<gix::Remote<'repo>> {
     repo.find_fetch_remote(name_or_url.map(Into::into)).map_err(Into::into)
 }
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+mod show_impl {
+    use std::collections::BTreeSet;
+
+    use gix::{bstr::ByteSlice, protocol::handshake};
+
+    use super::{by_name_or_url, tracking_refs};
+
+    pub mod show {
+        pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+    }
+
+    pub mod prune {
+        pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+        pub struct Options {
+            /// Only print which remote tracking branches would be removed.
+            pub dry_run: bool,
+        }
+    }
+
+    /// Show information about the remote `name_or_url`, or the remote of the current branch if `None`, like `git remote show`.
+    ///
+    /// This connects to the remote to learn which of its branches are tracked, which are new, and which of our
+    /// remote tracking branches are stale as they don't exist on the remote anymore.
+    #[gix::protocol::maybe_async::maybe_async]
+    pub async fn show_fn(
+        repo: gix::Repository,
+        name_or_url: Option<&str>,
+        mut progress: impl gix::Progress,
+        mut out: impl std::io::Write,
+    ) -> anyhow::Result<()> {
+        let remote = by_name_or_url(&repo, name_or_url)?;
+        let map = ref_map(&remote, &mut progress).await?;
+        let name = remote
+            .name()
+            .map_or_else(|| "(anonymous)".into(), |name| name.as_bstr().to_owned());
+        writeln!(out, "* remote {name}")?;
+        let url_or_none = |direction| {
+            remote
+                .url(direction)
+                .map_or_else(|| "(no URL)".into(), gix::Url::to_bstring)
+        };
+        writeln!(out, "  Fetch URL: {}", url_or_none(gix::remote::Direction::Fetch))?;
+        writeln!(out, "  Push  URL: {}", url_or_none(gix::remote::Direction::Push))?;
+        let head_branch = map.remote_refs.iter().find_map(|r| match r {
+            handshake::Ref::Symbolic {
+                full_ref_name, target, ..
+            }
+            | handshake::Ref::Unborn { full_ref_name, target }
+                if full_ref_name == "HEAD" =>
+            {
+                Some(target.strip_prefix(b"refs/heads/").unwrap_or(target).as_bstr())
+            }
+            _ => None,
+        });
+        match head_branch {
+            Some(branch) => writeln!(out, "  HEAD branch: {branch}")?,
+            None => writeln!(out, "  HEAD branch: (unknown)")?,
+        }
+
+        let mut branches = Vec::new();
+        for mapping in &map.mappings {
+            let (gix::remote::fetch::Source::Ref(remote_ref), Some(local)) = (&mapping.remote, &mapping.local) else {
+                continue;
+            };
+            let Some(branch) = remote_ref.unpack().0.strip_prefix(b"refs/heads/") else {
+                continue;
+            };
+            let status = if repo.try_find_reference(local.as_bstr())?.is_some() {
+                "tracked".to_owned()
+            } else {
+                let local = local.strip_prefix(b"refs/").unwrap_or(local).as_bstr();
+                format!(
+                    "new (next fetch will store in {})",
+                    local.rsplit_once_str("/").map_or(local, |t| t.0.as_bstr())
+                )
+            };
+            branches.push((branch.as_bstr().to_owned(), status));
+        }
+        let remote_names: BTreeSet<_> = map.remote_refs.iter().map(|r| r.unpack().0).collect();
+        for (local, remote_name) in tracking_refs(&repo, remote.refspecs(gix::remote::Direction::Fetch))? {
+            if local.target.try_id().is_some() && !remote_names.contains(remote_name.as_bstr()) {
+                let branch = remote_name.strip_prefix(b"refs/heads/").unwrap_or(&remote_name);
+                branches.push((
+                    branch.as_bstr().to_owned(),
+                    "stale (use 'gix remote prune' to remove)".into(),
+                ));
+            }
+        }
+        branches.sort();
+        branches.dedup_by(|a, b| a.0 == b.0);
+        if !branches.is_empty() {
+            let width = branches.iter().map(|(name, _)| name.len()).max().unwrap_or_default();
+            writeln!(out, "  Remote branches:")?;
+            for (name, status) in branches {
+                writeln!(out, "    {:width$} {status}", name.to_str_lossy())?;
+            }
+        }
+
+        if let Some(name) = remote.name() {
+            let config = repo.config_snapshot();
+            let mut pulls = Vec::new();
+            for section in config.plumbing().sections_by_name("branch").into_iter().flatten() {
+                let (Some(branch), Some(merge)) = (section.header().subsection_name(), section.value("merge")) else {
+                    continue;
+                };
+                if section.value("remote").as_deref() == Some(name.as_bstr()) {
+                    let merge = merge
+                        .strip_prefix(b"refs/heads/")
+                        .unwrap_or(&merge)
+                        .as_bstr()
+                        .to_owned();
+                    pulls.push((branch.to_owned(), merge));
+                }
+            }
+            if !pulls.is_empty() {
+                pulls.sort();
+                let width = pulls.iter().map(|(branch, _)| branch.len()).max().unwrap_or_default();
+                writeln!(out, "  Local branches configured for pulling:")?;
+                for (branch, merge) in pulls {
+                    writeln!(out, "    {:width$} merges with remote {merge}", branch.to_str_lossy())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete all remote tracking branches of the remote `name_or_url`, or the remote of the current branch if `None`,
+    /// whose counterpart doesn't exist on the remote anymore.
+    #[gix::protocol::maybe_async::maybe_async]
+    pub async fn prune_fn(
+        repo: gix::Repository,
+        name_or_url: Option<&str>,
+        mut progress: impl gix::Progress,
+        mut out: impl std::io::Write,
+        prune::Options { dry_run }: prune::Options,
+    ) -> anyhow::Result<()> {
+        use gix::refs::transaction::{Change, PreviousValue, RefEdit, RefLog};
+
+        let remote = by_name_or_url(&repo, name_or_url)?;
+        let map = ref_map(&remote, &mut progress).await?;
+        let remote_names: BTreeSet<_> = map.remote_refs.iter().map(|r| r.unpack().0).collect();
+        let stale: Vec<_> = tracking_refs(&repo, remote.refspecs(gix::remote::Direction::Fetch))?
+            .into_iter()
+            .filter(|(local, remote_name)| {
+                local.target.try_id().is_some() && !remote_names.contains(remote_name.as_bstr())
+            })
+            .map(|(local, _)| local)
+            .collect();
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(name) = remote.name() {
+            writeln!(out, "Pruning {}", name.as_bstr())?;
+        }
+        if let Some(url) = remote.url(gix::remote::Direction::Fetch) {
+            writeln!(out, "URL: {}", url.to_bstring())?;
+        }
+        for local in &stale {
+            writeln!(
+                out,
+                " * [{}] {}",
+                if dry_run { "would prune" } else { "pruned" },
+                local.name.shorten()
+            )?;
+        }
+        if !dry_run {
+            repo.edit_references(stale.into_iter().map(|local| RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(local.target.clone()),
+                    log: RefLog::AndReference,
+                },
+                name: local.name.clone(),
+                deref: false,
+            }))?;
+        }
+        Ok(())
+    }
+
+    /// Obtain all refs advertised by `remote` without filtering them by prefix, so `HEAD` is always included.
+    #[gix::protocol::maybe_async::maybe_async]
+    async fn ref_map(
+        remote: &gix::Remote<'_>,
+        progress: &mut impl gix::Progress,
+    ) -> anyhow::Result<gix::remote::fetch::RefMap> {
+        use anyhow::Context;
+        progress.info(format!(
+            "Connecting to {:?}",
+            remote
+                .url(gix::remote::Direction::Fetch)
+                .context("Remote didn't have a URL to connect to")?
+                .to_bstring()
+        ));
+        Ok(remote
+            .connect(gix::remote::Direction::Fetch)
+            .await?
+            .ref_map(
+                progress,
+                gix::remote::ref_map::Options {
+                    prefix_from_spec_as_filter_on_remote: false,
+                    ..Default::default()
+                },
+            )
+            .await?)
+    }
+}
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use show_impl::{prune, prune_fn as prune, show, show_fn as show};
+
+pub mod add {
+    pub struct Options {
+        /// The branches to track instead of all branches.
+        pub track: Vec<String>,
+        /// If `Some(true)`, fetch all tags, and if `Some(false)`, fetch no tags at all.
+        pub tags: Option<bool>,
+    }
+}
+
+/// Add a new remote `name` that fetches from `url`, like `git remote add`.
+pub fn add(
+    repo: gix::Repository,
+    name: &str,
+    url: &str,
+    add::Options { track, tags }: add::Options,
+) -> anyhow::Result<()> {
+    if repo.try_find_remote_without_url_rewrite(name).is_some() {
+        bail!("remote '{name}' already exists");
+    }
+    let specs: Vec<String> = if track.is_empty() {
+        vec![format!("+refs/heads/*:refs/remotes/{name}/*")]
+    } else {
+        track
+            .iter()
+            .map(|branch| format!("+refs/heads/{branch}:refs/remotes/{name}/{branch}"))
+            .collect()
+    };
+    let mut remote = repo
+        .remote_at_without_url_rewrite(url)?
+        .with_refspecs(specs.iter().map(String::as_str), gix::remote::Direction::Fetch)?
+        .with_fetch_tags(match tags {
+            None => gix::remote::fetch::Tags::Included,
+            Some(true) => gix::remote::fetch::Tags::All,
+            Some(false) => gix::remote::fetch::Tags::None,
+        });
+    edit_local_config(&repo, |config| {
+        remote.save_as_to(name, config)?;
+        Ok(())
+    })
+}
+
+/// Remove the remote `name` along with its remote tracking branches and all configuration referring to it.
+pub fn remove(repo: gix::Repository, name: &str) -> anyhow::Result<()> {
+    let remote = find_named(&repo, name)?;
+    let tracking: Vec<_> = tracking_refs(&repo, remote.refspecs(gix::remote::Direction::Fetch))?
+        .into_iter()
+        .map(|(local, _)| local)
+        .collect();
+    repo.edit_references(tracking.into_iter().map(|local| RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::MustExistAndMatch(local.target.clone()),
+            log: RefLog::AndReference,
+        },
+        name: local.name.clone(),
+        deref: false,
+    }))?;
+    edit_local_config(&repo, |config| {
+        while config.remove_section("remote", Some(name.into())).is_some() {}
+        for id in branch_sections_with_remote(config, name) {
+            let mut section = config.section_mut_by_id(id).expect("just queried");
+            section.remove("remote");
+            section.remove("merge");
+            if section.num_values() == 0 {
+                config.remove_section_by_id(id);
+            }
+        }
+        if config.string("remote.pushDefault").as_deref() == Some(name.into()) {
+            let mut section = config.section_mut("remote", None)?;
+            while section.remove("pushDefault").is_some() {}
+            if section.num_values() == 0 {
+                config.remove_section("remote", None);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Rename the remote `old` to `new`, along with its default refspecs, remote tracking branches and configuration referring to it.
+pub fn rename(repo: gix::Repository, old: &str, new: &str) -> anyhow::Result<()> {
+    let remote = find_named(&repo, old)?;
+    gix::remote::name::validated(new)?;
+    if repo.try_find_remote_without_url_rewrite(new).is_some() {
+        bail!("remote '{new}' already exists");
+    }
+    let old_prefix = format!("refs/remotes/{old}/");
+    let new_prefix = format!("refs/remotes/{new}/");
+    let tracking: Vec<_> = tracking_refs(&repo, remote.refspecs(gix::remote::Direction::Fetch))?
+        .into_iter()
+        .filter_map(|(local, _)| {
+            let new_name = format!(
+                "{new_prefix}{}",
+                local.name.as_bstr().strip_prefix(old_prefix.as_bytes())?.as_bstr()
+            );
+            Some((local, new_name))
+        })
+        .collect();
+
+    let message: BString = format!("remote: renamed {old} to {new}").into();
+    let mut edits = Vec::new();
+    for (local, new_name) in tracking {
+        let new_name: FullName = new_name.try_into()?;
+        let target = match local.target.clone() {
+            Target::Symbolic(target) => match target.as_bstr().strip_prefix(old_prefix.as_bytes()) {
+                Some(rest) => Target::Symbolic(format!("{new_prefix}{}", rest.as_bstr()).try_into()?),
+                None => Target::Symbolic(target),
+            },
+            target @ Target::Object(_) => target,
+        };
+        edits.push(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::MustExistAndMatch(local.target.clone()),
+                log: RefLog::AndReference,
+            },
+            name: local.name.clone(),
+            deref: false,
+        });
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: message.clone(),
+                },
+                expected: PreviousValue::MustNotExist,
+                new: target,
+            },
+            name: new_name,
+            deref: false,
+        });
+    }
+    repo.edit_references(edits)?;
+
+    edit_local_config(&repo, |config| {
+        let id = config
+            .sections_and_ids_by_name("remote")
+            .into_iter()
+            .flatten()
+            .filter_map(|(section, id)| (section.header().subsection_name() == Some(old.into())).then_some(id))
+            .last()
+            .expect("the remote exists");
+        config.rename_section("remote", Some(old.into()), "remote", Some(Cow::Owned(new.into())))?;
+        // The name lookup table isn't updated on rename, so access the section by id.
+        let mut section = config.section_mut_by_id(id).expect("just renamed");
+        let specs: Vec<BString> = section.values("fetch").into_iter().map(Cow::into_owned).collect();
+        while section.remove("fetch").is_some() {}
+        for spec in specs {
+            let spec = spec.replace(old_prefix.as_bytes(), new_prefix.as_bytes());
+            section.push("fetch".try_into()?, Some(spec.as_bstr()));
+        }
+        for id in branch_sections_with_remote(config, old) {
+            let mut section = config.section_mut_by_id(id).expect("just queried");
+            section.set("remote".try_into()?, new.into());
+        }
+        if config.string("remote.pushDefault").as_deref() == Some(old.into()) {
+            config.set_raw_value_by("remote", None, "pushDefault", new)?;
+        }
+        Ok(())
+    })
+}
+
+pub mod set_url {
+    pub struct Options {
+        /// Change the push URL instead of the fetch URL.
+        pub push: bool,
+        /// Add the URL instead of replacing the existing one.
+        pub add: bool,
+    }
+}
+
+/// Set the fetch or push URL of the remote `name` to `url`.
+pub fn set_url(
+    repo: gix::Repository,
+    name: &str,
+    url: &str,
+    set_url::Options { push, add }: set_url::Options,
+) -> anyhow::Result<()> {
+    find_named(&repo, name)?;
+    gix::url::parse(url.into())?;
+    edit_local_config(&repo, |config| {
+        let mut section = config.section_mut("remote", Some(name.into()))?;
+        let key = if push { "pushurl" } else { "url" };
+        if add {
+            section.push(key.try_into()?, Some(url.into()));
+        } else {
+            section.set(key.try_into()?, url.into());
+        }
+        Ok(())
+    })
+}
+
+/// Return all references that are destinations of the given fetch `specs`, along with the name of the remote reference
+/// they are fetched from.
+///
+/// This is done by matching all references against the reversed `specs`.
+pub(crate) fn tracking_refs(
+    repo: &gix::Repository,
+    specs: &[gix::refspec::RefSpec],
+) -> anyhow::Result<Vec<(gix::refs::Reference, BString)>> {
+    let reversed: Vec<BString> = specs
+        .iter()
+        .filter_map(|spec| {
+            let spec = spec.to_ref();
+            Some(format!("{}:{}", spec.destination()?, spec.source()?).into())
+        })
+        .collect();
+    let reversed = reversed
+        .iter()
+        .map(|spec| gix::refspec::parse(spec.as_bstr(), gix::refspec::parse::Operation::Fetch))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs = repo
+        .references()?
+        .all()?
+        .map(|r| r.map(gix::Reference::detach))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| anyhow!(err))?;
+    let null = repo.object_hash().null();
+    let items = refs.iter().map(|r| gix::refspec::match_group::Item {
+        full_ref_name: r.name.as_bstr(),
+        target: r.target.try_id().unwrap_or(&null),
+        object: None,
+    });
+    let outcome = gix::refspec::MatchGroup::from_fetch_specs(reversed).match_remotes(items);
+    Ok(outcome
+        .mappings
+        .into_iter()
+        .filter_map(|mapping| Some((refs[mapping.item_index?].clone(), mapping.rhs?.into_owned())))
+        .collect())
+}
+
+fn find_named<'repo>(repo: &'repo gix::Repository, name: &str) -> anyhow::Result<gix::Remote<'repo>> {
+    let remote = repo
+        .try_find_remote_without_url_rewrite(name)
+        .ok_or_else(|| anyhow!("no such remote: '{name}'"))??;
+    if remote.name().is_none() {
+        bail!("no such remote: '{name}'");
+    }
+    Ok(remote)
+}
+
+/// Return the ids of all `branch` sections in `config` that refer to `remote`.
+fn branch_sections_with_remote(config: &gix::config::File<'static>, remote: &str) -> Vec<gix::config::file::SectionId> {
+    config
+        .sections_and_ids_by_name("branch")
+        .into_iter()
+        .flatten()
+        .filter_map(|(section, id)| (section.value("remote").as_deref() == Some(remote.into())).then_some(id))
+        .collect()
+}
@@ -0,0 +1,393 @@
+use std::path::PathBuf;
+
+/// The capabilities we advertise to `git`, which will then hand us batches of `fetch` and `push` commands.
+const CAPABILITIES: &[&str] = &["fetch", "push"];
+
+pub struct Options {
+    /// The path to the bundle file which serves as remote, as passed as `<url>` by `git`.
+    pub bundle_path: PathBuf,
+}
+
+pub(crate) mod function {
+    use std::{
+        collections::BTreeMap,
+        io::{BufRead, Write},
+        path::{Path, PathBuf},
+        sync::atomic::AtomicBool,
+    };
+
+    use anyhow::{anyhow, bail, Context};
+    use gix::{
+        bstr::{BStr, BString, ByteSlice},
+        protocol::transport::remote_helper::{
+            self, Capability, Delegate, DelegateError, Fetch, ListedRef, PushStatus, Value,
+        },
+        ObjectId,
+    };
+
+    use super::{Options, CAPABILITIES};
+
+    /// Act as remote helper for `git` by reading its commands from `input` and writing responses to `output`,
+    /// using the bundle file in `options` as remote.
+    ///
+    /// Objects are fetched into `repo` by indexing the pack contained in the bundle, and pushing rewrites the
+    /// bundle so it contains all previous and pushed references along with all objects reachable from them.
+    pub fn remote_helper(
+        repo: gix::Repository,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+        progress: &mut dyn gix::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        Options { bundle_path }: Options,
+    ) -> anyhow::Result<()> {
+        let mut delegate = BundleRemote {
+            repo: &repo,
+            path: bundle_path,
+            progress,
+            should_interrupt,
+        };
+        remote_helper::serve(input, output, &mut delegate)?;
+        Ok(())
+    }
+
+    /// A remote which is a bundle file, as created by `git bundle create`.
+    struct BundleRemote<'a> {
+        repo: &'a gix::Repository,
+        path: PathBuf,
+        progress: &'a mut dyn gix::DynNestedProgress,
+        should_interrupt: &'a AtomicBool,
+    }
+
+    impl Delegate for BundleRemote<'_> {
+        fn capabilities(&mut self) -> Vec<Capability> {
+            CAPABILITIES
+                .iter()
+                .map(|name| remote_helper::decode::capability(name.as_bytes().as_bstr()))
+                .collect()
+        }
+
+        fn list(&mut self, for_push: bool) -> Result<Vec<ListedRef>, DelegateError> {
+            let header = if for_push && !self.path.exists() {
+                // Pushing creates the bundle.
+                Header::default()
+            } else {
+                Header::from_path(&self.path)?.0
+            };
+            let mut out: Vec<_> = header
+                .refs
+                .iter()
+                .filter(|(name, _)| name != "HEAD")
+                .map(|(name, id)| ListedRef {
+                    value: Value::Object(*id),
+                    name: name.clone(),
+                    attributes: Vec::new(),
+                })
+                .collect();
+            if let Some(id) = header.head() {
+                out.push(ListedRef {
+                    value: match header.head_branch() {
+                        Some(branch) => Value::Symbolic(branch.into()),
+                        None => Value::Object(id),
+                    },
+                    name: "HEAD".into(),
+                    attributes: Vec::new(),
+                });
+            }
+            Ok(out)
+        }
+
+        fn fetch(&mut self, fetches: &[Fetch]) -> Result<(), DelegateError> {
+            if fetches.iter().all(|fetch| self.repo.has_object(fetch.id)) {
+                return Ok(());
+            }
+            let (header, mut pack) = Header::from_path(&self.path)?;
+            if let Some(missing) = header.prerequisites.iter().find(|id| !self.repo.has_object(id)) {
+                return Err(anyhow!(
+                    "The bundle at '{}' requires commit {missing} which doesn't exist locally",
+                    self.path.display()
+                )
+                .into());
+            }
+            let outcome = gix::odb::pack::Bundle::write_to_directory(
+                &mut pack,
+                Some(&self.repo.objects.store_ref().path().join("pack")),
+                &mut *self.progress,
+                self.should_interrupt,
+                Some(self.repo.objects.clone()),
+                gix::odb::pack::bundle::write::Options {
+                    object_hash: self.repo.object_hash(),
+                    ..Default::default()
+                },
+            )?;
+            if let Some(keep_path) = outcome.keep_path {
+                // `git` will protect the pack by updating its references once we are done.
+                std::fs::remove_file(keep_path)?;
+            }
+            Ok(())
+        }
+
+        fn push(&mut self, specs: &[BString]) -> Result<Vec<PushStatus>, DelegateError> {
+            let previous = if self.path.exists() {
+                Header::from_path(&self.path)?.0
+            } else {
+                Header::default()
+            };
+            let mut head_branch = previous.head_branch().map(ToOwned::to_owned);
+            let mut refs: BTreeMap<_, _> = previous
+                .refs
+                .iter()
+                .filter(|(name, _)| name != "HEAD")
+                .cloned()
+                .collect();
+
+            let mut statuses = Vec::with_capacity(specs.len());
+            for spec in specs {
+                let (source, destination, force) = parse_push_spec(spec.as_ref())?;
+                let status = match self.update(&mut refs, source, destination, force) {
+                    Ok(()) => PushStatus::Ok {
+                        destination: destination.into(),
+                    },
+                    Err(reason) => PushStatus::Error {
+                        destination: destination.into(),
+                        reason: Some(reason.into()),
+                    },
+                };
+                if head_branch.is_none() && destination.starts_with(b"refs/heads/") && refs.contains_key(destination) {
+                    head_branch = Some(destination.into());
+                }
+                statuses.push(status);
+            }
+
+            if !statuses.iter().any(|status| matches!(status, PushStatus::Ok { .. })) {
+                return Ok(statuses);
+            }
+            let mut new_refs: Vec<_> = refs.into_iter().collect();
+            if let Some(id) = head_branch.and_then(|branch| new_refs.iter().find(|(name, _)| *name == branch)) {
+                new_refs.insert(0, ("HEAD".into(), id.1));
+            }
+            self.write_bundle(Header {
+                prerequisites: Vec::new(),
+                refs: new_refs,
+            })?;
+            Ok(statuses)
+        }
+    }
+
+    impl BundleRemote<'_> {
+        /// Update `destination` in `refs` to the object `source` resolves to, or delete it if `source` is empty.
+        ///
+        /// Return the reason for not doing so as error.
+        fn update(
+            &self,
+            refs: &mut BTreeMap<BString, ObjectId>,
+            source: &BStr,
+            destination: &BStr,
+            force: bool,
+        ) -> Result<(), &'static str> {
+            if source.is_empty() {
+                return refs.remove(destination).map(|_| ()).ok_or("no such ref");
+            }
+            let new = self
+                .repo
+                .rev_parse_single(source)
+                .map_err(|_| "src refspec does not match any")?
+                .detach();
+            if let Some(previous) = refs.get(destination).filter(|previous| !force && **previous != new) {
+                if !self.repo.has_object(previous) {
+                    return Err("fetch first");
+                }
+                if self
+                    .repo
+                    .merge_base(*previous, new)
+                    .map_or(true, |base| base != *previous)
+                {
+                    return Err("non-fast forward");
+                }
+            }
+            refs.insert(destination.into(), new);
+            Ok(())
+        }
+
+        /// Replace our bundle file with one containing all refs in `header` and all objects reachable from them.
+        fn write_bundle(&self, header: Header) -> anyhow::Result<()> {
+            let tips: Vec<_> = header.refs.iter().map(|(_, id)| *id).collect();
+            let mut file =
+                gix::lock::File::acquire_to_update_resource(&self.path, gix::lock::acquire::Fail::Immediately, None)?;
+            header.write_to(&mut file)?;
+            write_pack(self.repo, &tips, &mut file, self.should_interrupt)?;
+            file.commit().map_err(|err| err.error)?;
+            Ok(())
+        }
+    }
+
+    /// Write a pack with all objects reachable from `tips` to `out`, without deltas against objects outside of it.
+    fn write_pack(
+        repo: &gix::Repository,
+        tips: &[ObjectId],
+        out: &mut dyn Write,
+        should_interrupt: &AtomicBool,
+    ) -> anyhow::Result<()> {
+        use gix::{odb::pack, parallel::InOrderIter, prelude::Finalize};
+
+        // Annotated tags and non-commit tips are expanded on their own, while commits are expanded along with their history.
+        let mut input = Vec::new();
+        let mut commits = Vec::new();
+        for tip in tips {
+            let mut object = repo.find_object(*tip)?;
+            while object.kind == gix::object::Kind::Tag {
+                input.push(object.id);
+                object = repo.find_object(object.to_tag_ref().target())?;
+            }
+            match object.kind {
+                gix::object::Kind::Commit => commits.push(object.id),
+                _ => input.push(object.id),
+            }
+        }
+        for info in repo.rev_walk(commits).all()? {
+            input.push(info?.id);
+        }
+
+        // Open the object database once more as we need to be its sole owner to share it across threads.
+        let mut handle = gix::open_opts(repo.git_dir(), repo.open_options().clone())?
+            .into_sync()
+            .objects
+            .into_shared_arc()
+            .to_cache_arc();
+        handle.prevent_pack_unload();
+        handle.ignore_replacements = true;
+        let (counts, _) = pack::data::output::count::objects_unthreaded(
+            &handle,
+            &mut input.into_iter().map(Ok),
+            &gix::progress::Discard,
+            should_interrupt,
+            pack::data::output::count::objects::ObjectExpansion::TreeContents,
+        )?;
+        let num_entries = counts.len();
+        let mut entries = InOrderIter::from(pack::data::output::entry::iter_from_counts(
+            counts,
+            handle,
+            Box::new(gix::progress::Discard),
+            pack::data::output::entry::iter_from_counts::Options {
+                allow_thin_pack: false,
+                ..Default::default()
+            },
+        ));
+        let mut bytes = pack::data::output::bytes::FromEntriesIter::new(
+            entries.by_ref(),
+            out,
+            num_entries as u32,
+            pack::data::Version::default(),
+            repo.object_hash(),
+        );
+        for written in bytes.by_ref() {
+            written?;
+        }
+        entries.inner.finalize()?;
+        Ok(())
+    }
+
+    /// Parse `[+]<src>:<dst>` into `(src, dst, force)`, with `src` being empty for deletions.
+    fn parse_push_spec(spec: &BStr) -> anyhow::Result<(&BStr, &BStr, bool)> {
+        let (force, spec) = match spec.strip_prefix(b"+") {
+            Some(spec) => (true, spec.as_bstr()),
+            None => (false, spec),
+        };
+        let Some(colon) = spec.find_byte(b':') else {
+            bail!("Push refspec {spec:?} must name source and destination")
+        };
+        Ok((spec[..colon].as_bstr(), spec[colon + 1..].as_bstr(), force))
+    }
+
+    /// The header of a bundle file, which precedes the pack with all of its objects.
+    #[derive(Default)]
+    struct Header {
+        /// Commits which the pack uses as bases, and which must exist in the receiving repository.
+        prerequisites: Vec<ObjectId>,
+        /// All references in the order they are listed in the bundle, possibly including `HEAD`.
+        refs: Vec<(BString, ObjectId)>,
+    }
+
+    impl Header {
+        /// Open the bundle at `path` and parse its header, returning it along with a reader positioned at the start of the pack.
+        fn from_path(path: &Path) -> anyhow::Result<(Self, std::io::BufReader<std::fs::File>)> {
+            let mut read = std::io::BufReader::new(
+                std::fs::File::open(path).with_context(|| format!("Could not open bundle at '{}'", path.display()))?,
+            );
+            let header =
+                Self::from_read(&mut read).with_context(|| format!("Could not read bundle at '{}'", path.display()))?;
+            Ok((header, read))
+        }
+
+        /// Parse a version 2 or 3 bundle header from `read`, including the empty line that terminates it.
+        fn from_read(read: &mut dyn BufRead) -> anyhow::Result<Self> {
+            let mut line = Vec::new();
+            let mut next_line = |line: &mut Vec<u8>| -> anyhow::Result<()> {
+                line.clear();
+                if read.read_until(b'\n', line)? == 0 {
+                    bail!("Unexpected end of bundle header");
+                }
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                }
+                Ok(())
+            };
+
+            next_line(&mut line)?;
+            let is_v3 = match line.as_slice() {
+                b"# v2 git bundle" => false,
+                b"# v3 git bundle" => true,
+                _ => bail!("Not a version 2 or 3 bundle, its signature was {:?}", line.as_bstr()),
+            };
+            let mut out = Header::default();
+            loop {
+                next_line(&mut line)?;
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(capability) = line.strip_prefix(b"@").filter(|_| is_v3) {
+                    match capability {
+                        b"object-format=sha1" => {}
+                        _ => bail!("Unsupported bundle capability {:?}", capability.as_bstr()),
+                    }
+                } else if let Some(prerequisite) = line.strip_prefix(b"-") {
+                    let hex = prerequisite.split_str(" ").next().unwrap_or_default();
+                    out.prerequisites.push(ObjectId::from_hex(hex)?);
+                } else {
+                    let (hex, name) = line
+                        .split_once_str(" ")
+                        .ok_or_else(|| anyhow!("Expected '<id> <ref>', got {:?}", line.as_bstr()))?;
+                    out.refs.push((name.into(), ObjectId::from_hex(hex)?));
+                }
+            }
+            Ok(out)
+        }
+
+        /// Write this header as version 2 bundle header to `out`, followed by the empty line after which the pack starts.
+        fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+            out.write_all(b"# v2 git bundle\n")?;
+            for id in &self.prerequisites {
+                writeln!(out, "-{id}")?;
+            }
+            for (name, id) in &self.refs {
+                write!(out, "{id} ")?;
+                out.write_all(name)?;
+                out.write_all(b"\n")?;
+            }
+            out.write_all(b"\n")
+        }
+
+        /// Return the object `HEAD` points to, if the bundle contains it.
+        fn head(&self) -> Option<ObjectId> {
+            self.refs.iter().find(|(name, _)| name == "HEAD").map(|(_, id)| *id)
+        }
+
+        /// Return the first branch pointing to the same object as `HEAD`, as the bundle doesn't say which one it is.
+        fn head_branch(&self) -> Option<&BStr> {
+            let head = self.head()?;
+            self.refs
+                .iter()
+                .find(|(name, id)| *id == head && name.starts_with(b"refs/heads/"))
+                .map(|(name, _)| name.as_ref())
+        }
+    }
+}
@@ -6,5 +6,8 @@ pub use explain::explain;
 pub mod resolve;
 pub use resolve::function::resolve;
 
+pub mod rev_parse;
+pub use rev_parse::function::rev_parse;
+
 mod previous_branches;
 pub use previous_branches::previous_branches;
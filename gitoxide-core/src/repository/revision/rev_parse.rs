@@ -0,0 +1,168 @@
+pub struct Options {
+    /// Require exactly one spec which resolves to a single object.
+    pub verify: bool,
+    /// Print the short name of the reference each spec refers to, instead of the object.
+    pub abbrev_ref: bool,
+    /// Print the full name of the reference each spec refers to, instead of the object.
+    pub symbolic_full_name: bool,
+    /// Print the absolute path to the root of the worktree.
+    pub show_toplevel: bool,
+    /// Print the path to the `.git` directory.
+    pub git_dir: bool,
+}
+
+pub(crate) mod function {
+    use std::ffi::OsString;
+
+    use anyhow::{anyhow, bail};
+    use gix::bstr::{BStr, ByteSlice};
+    use gix::revision::plumbing::Spec;
+
+    use super::Options;
+
+    /// Resolve all `specs` and print the objects they refer to, one per line, just like `git rev-parse` does.
+    ///
+    /// Objects that are excluded, like the start of a range `a..b`, are prefixed with `^`.
+    pub fn rev_parse(
+        repo: gix::Repository,
+        specs: Vec<OsString>,
+        out: &mut dyn std::io::Write,
+        Options {
+            verify,
+            abbrev_ref,
+            symbolic_full_name,
+            show_toplevel,
+            git_dir,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        if git_dir {
+            writeln!(out, "{}", display_git_dir(&repo)?)?;
+        }
+        if show_toplevel {
+            let workdir = repo
+                .work_dir()
+                .ok_or_else(|| anyhow!("this operation must be run in a work tree"))?;
+            writeln!(out, "{}", gix::path::realpath(workdir)?.display())?;
+        }
+
+        if verify {
+            let [spec] = specs.as_slice() else {
+                bail!("Needed a single revision");
+            };
+            let id = repo
+                .rev_parse(gix::path::os_str_into_bstr(spec)?)
+                .ok()
+                .and_then(|spec| spec.single())
+                .ok_or_else(|| anyhow!("Needed a single revision"))?;
+            writeln!(out, "{id}")?;
+            return Ok(());
+        }
+
+        for spec in specs {
+            let spec = gix::path::os_str_into_bstr(&spec)?;
+            let parsed = repo.rev_parse(spec)?;
+            if abbrev_ref || symbolic_full_name {
+                if let Some(name) = reference_name(&repo, spec, parsed)? {
+                    if abbrev_ref {
+                        writeln!(out, "{}", name.as_ref().shorten())?;
+                    } else {
+                        writeln!(out, "{}", name.as_bstr())?;
+                    }
+                }
+                continue;
+            }
+            match parsed.detach() {
+                Spec::Include(id) => writeln!(out, "{id}")?,
+                Spec::Exclude(id) => writeln!(out, "^{id}")?,
+                Spec::Range { from, to } => writeln!(out, "{to}\n^{from}")?,
+                Spec::Merge { theirs, ours } => {
+                    writeln!(out, "{ours}\n{theirs}")?;
+                    match repo.merge_base(theirs, ours) {
+                        Ok(base) => writeln!(out, "^{base}")?,
+                        Err(gix::repository::merge_base::Error::NotFound { .. }) => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Spec::IncludeOnlyParents(id) => {
+                    for parent in repo.find_commit(id)?.parent_ids() {
+                        writeln!(out, "{parent}")?;
+                    }
+                }
+                Spec::ExcludeParents(id) => {
+                    writeln!(out, "{id}")?;
+                    for parent in repo.find_commit(id)?.parent_ids() {
+                        writeln!(out, "^{parent}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the name of the reference `spec` refers to if it names the object of a reference, like `HEAD`, `main`,
+    /// `@{-1}` or `main@{upstream}`, but not `HEAD~1`. Symbolic references are followed, so `HEAD` yields the current branch.
+    fn reference_name(
+        repo: &gix::Repository,
+        spec: &BStr,
+        parsed: gix::revision::Spec<'_>,
+    ) -> anyhow::Result<Option<gix::refs::FullName>> {
+        if let Some(nth) = spec
+            .strip_prefix(b"@{-")
+            .and_then(|rest| rest.strip_suffix(b"}"))
+            .and_then(|nth| nth.to_str().ok()?.parse::<usize>().ok())
+        {
+            let branches = repo.head()?.prior_checked_out_branches()?.unwrap_or_default();
+            let Some((name, _)) = nth.checked_sub(1).and_then(|nth| branches.iter().rev().nth(nth)) else {
+                return Ok(None);
+            };
+            return Ok(repo
+                .try_find_reference(name.as_bstr())?
+                .map(|reference| reference.inner.name));
+        }
+        for (suffix, direction) in [
+            (&b"@{u}"[..], gix::remote::Direction::Fetch),
+            (b"@{upstream}", gix::remote::Direction::Fetch),
+            (b"@{push}", gix::remote::Direction::Push),
+        ] {
+            let Some(branch) = spec.strip_suffix(suffix) else {
+                continue;
+            };
+            let branch = if branch.is_empty() {
+                repo.head_name()?
+                    .ok_or_else(|| anyhow!("HEAD does not point to a branch"))?
+            } else {
+                repo.find_reference(branch.as_bstr())?.inner.name
+            };
+            return Ok(Some(
+                repo.branch_remote_tracking_ref_name(branch.as_ref(), direction)
+                    .ok_or_else(|| anyhow!("no upstream configured for branch '{}'", branch.as_ref().shorten()))??
+                    .into_owned(),
+            ));
+        }
+
+        let Some(id) = parsed.single().map(gix::Id::detach) else {
+            return Ok(None);
+        };
+        let (Some(mut reference), None) = parsed.into_references() else {
+            return Ok(None);
+        };
+        while let Some(referent) = reference.follow() {
+            reference = referent?;
+        }
+        Ok((reference.target().try_id() == Some(id.as_ref())).then_some(reference.inner.name))
+    }
+
+    /// Print the git directory relative to the current working directory if it's the `.git` directory within it,
+    /// or as absolute path otherwise.
+    fn display_git_dir(repo: &gix::Repository) -> anyhow::Result<String> {
+        let cwd = gix::path::realpath(std::env::current_dir()?)?;
+        let git_dir = gix::path::realpath(repo.git_dir())?;
+        Ok(if git_dir == cwd.join(gix::discover::DOT_GIT_DIR) {
+            gix::discover::DOT_GIT_DIR.into()
+        } else if git_dir == cwd {
+            ".".into()
+        } else {
+            git_dir.display().to_string()
+        })
+    }
+}
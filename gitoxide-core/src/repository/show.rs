@@ -0,0 +1,448 @@
+use anyhow::bail;
+use gix::{
+    bstr::{BString, ByteSlice},
+    diff::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm},
+    object::tree::diff::{change::EventDetached, Action},
+    objs::tree::EntryMode,
+    prelude::ObjectIdExt,
+    ObjectId,
+};
+
+pub struct Options {
+    /// Print commits as a single line with their abbreviated id and their subject.
+    pub oneline: bool,
+    /// Don't print the changes introduced by commits.
+    pub no_patch: bool,
+    /// Print a summary of changed files and lines instead of the patch.
+    pub stat: bool,
+}
+
+/// Show the object `spec` resolves to in a way that is appropriate for its kind, similar to `git show`.
+///
+/// Commits are shown with their message and the changes they introduce compared to their first parent, unless they are
+/// merge commits. Tags are shown with their message, followed by the object they point to. Trees are listed
+/// by name and blobs are printed as they are.
+pub fn show(repo: gix::Repository, spec: &str, out: &mut dyn std::io::Write, options: Options) -> anyhow::Result<()> {
+    let mut object = repo.rev_parse_single(spec)?.object()?;
+    loop {
+        match object.kind {
+            gix::object::Kind::Commit => return show_commit(&repo, object.into_commit(), out, &options),
+            gix::object::Kind::Tag => {
+                let tag = object.to_tag_ref();
+                writeln!(out, "tag {}", tag.name)?;
+                if let Some(tagger) = tag.tagger {
+                    writeln!(out, "Tagger: {} <{}>", tagger.name, tagger.email)?;
+                    writeln!(out, "Date:   {}", tagger.time.format(gix::date::time::format::DEFAULT))?;
+                }
+                writeln!(out, "\n{}", tag.message)?;
+                let target = tag.target();
+                object = repo.find_object(target)?;
+            }
+            gix::object::Kind::Tree => {
+                writeln!(out, "tree {spec}\n")?;
+                for entry in object.into_tree().iter() {
+                    let entry = entry?;
+                    let suffix = if entry.mode().is_tree() { "/" } else { "" };
+                    writeln!(out, "{}{suffix}", entry.filename())?;
+                }
+                return Ok(());
+            }
+            gix::object::Kind::Blob => {
+                out.write_all(&object.data)?;
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn show_commit(
+    repo: &gix::Repository,
+    commit: gix::Commit<'_>,
+    out: &mut dyn std::io::Write,
+    Options {
+        oneline,
+        no_patch,
+        stat,
+    }: &Options,
+) -> anyhow::Result<()> {
+    let message = commit.message_raw_sloppy();
+    let parents: Vec<_> = commit.parent_ids().collect();
+    if *oneline {
+        let subject = message.lines().next().unwrap_or_default();
+        writeln!(out, "{} {}", commit.id().shorten_or_id(), subject.as_bstr())?;
+    } else {
+        writeln!(out, "commit {}", commit.id)?;
+        if parents.len() > 1 {
+            let parents: Vec<_> = parents.iter().map(|id| id.shorten_or_id().to_string()).collect();
+            writeln!(out, "Merge: {}", parents.join(" "))?;
+        }
+        let author = commit.author()?;
+        writeln!(out, "Author: {} <{}>", author.name, author.email)?;
+        writeln!(
+            out,
+            "Date:   {}\n",
+            author.time.format(gix::date::time::format::DEFAULT)
+        )?;
+        for line in message.trim_end().lines() {
+            writeln!(out, "    {}", line.as_bstr())?;
+        }
+    }
+    if *no_patch {
+        return Ok(());
+    }
+    if parents.len() > 1 && !*stat {
+        // Like `git`, show the separator of the combined diff, which is empty unless conflicts were resolved.
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    let changes = changes(repo, parents.first().map(|id| id.detach()), commit.tree_id()?.detach())?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+    if !*oneline {
+        writeln!(out)?;
+    }
+    if *stat {
+        write_stat(repo, &changes, out)
+    } else {
+        for change in &changes {
+            write_patch(repo, change, out)?;
+        }
+        Ok(())
+    }
+}
+
+/// One side of a changed file.
+struct Side {
+    path: BString,
+    mode: EntryMode,
+    id: ObjectId,
+}
+
+/// A change to a single file, which may also be a rename if the paths of both sides differ.
+struct FileChange {
+    before: Option<Side>,
+    after: Option<Side>,
+    /// The similarity of both sides in percent, if this is a rename.
+    similarity: Option<u32>,
+}
+
+impl FileChange {
+    /// The path to sort by, which is the destination of renames.
+    fn path(&self) -> &BString {
+        &self
+            .after
+            .as_ref()
+            .or(self.before.as_ref())
+            .expect("a change has at least one side")
+            .path
+    }
+}
+
+/// Return all changes to files needed to turn the tree of `parent` into `tree`, with rewrites tracked as configured.
+fn changes(repo: &gix::Repository, parent: Option<ObjectId>, tree: ObjectId) -> anyhow::Result<Vec<FileChange>> {
+    let from = match parent {
+        Some(parent) => parent.attach(repo).object()?.peel_to_tree()?,
+        None => repo.empty_tree(),
+    };
+    let to = repo.find_tree(tree)?;
+    let mut changes = Vec::new();
+    from.changes()?.track_path().for_each_to_obtain_tree(&to, |change| {
+        let change = change.detach();
+        let side = |path: &BString, mode: EntryMode, id: ObjectId| {
+            (!mode.is_tree()).then(|| Side {
+                path: path.clone(),
+                mode,
+                id,
+            })
+        };
+        let (before, after, similarity) = match change.event {
+            EventDetached::Addition { entry_mode, id } => (None, side(&change.location, entry_mode, id), None),
+            EventDetached::Deletion { entry_mode, id } => (side(&change.location, entry_mode, id), None, None),
+            EventDetached::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => (
+                side(&change.location, previous_entry_mode, previous_id),
+                side(&change.location, entry_mode, id),
+                None,
+            ),
+            EventDetached::Rewrite {
+                source_location,
+                source_entry_mode,
+                source_id,
+                diff,
+                entry_mode,
+                id,
+                copy: _,
+            } => (
+                side(&source_location, source_entry_mode, source_id),
+                side(&change.location, entry_mode, id),
+                Some(diff.map_or(100, |diff| (diff.similarity * 100.0) as u32)),
+            ),
+        };
+        if before.is_some() || after.is_some() {
+            changes.push(FileChange {
+                before,
+                after,
+                similarity,
+            });
+        }
+        Ok::<_, std::convert::Infallible>(Action::Continue)
+    })?;
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(changes)
+}
+
+/// Return the content of `side` as it would be diffed, with submodules represented by the commit they point to.
+fn content(repo: &gix::Repository, side: Option<&Side>) -> anyhow::Result<Vec<u8>> {
+    Ok(match side {
+        None => Vec::new(),
+        Some(side) if side.mode.is_commit() => format!("Subproject commit {}\n", side.id).into_bytes(),
+        Some(side) => repo.find_object(side.id)?.detach().data,
+    })
+}
+
+fn write_patch(repo: &gix::Repository, change: &FileChange, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    let (before_path, after_path) = match (&change.before, &change.after) {
+        (Some(before), Some(after)) => (&before.path, &after.path),
+        (Some(side), None) | (None, Some(side)) => (&side.path, &side.path),
+        (None, None) => bail!("BUG: a change has at least one side"),
+    };
+    writeln!(out, "diff --git a/{before_path} b/{after_path}")?;
+    let mode = |side: &Side| format!("{:o}", side.mode.kind() as u32);
+    let short_id = |side: Option<&Side>| match side {
+        Some(side) => side.id.attach(repo).shorten_or_id().to_string(),
+        None => repo.object_hash().null().to_hex_with_len(7).to_string(),
+    };
+    let index_mode = match (&change.before, &change.after) {
+        (None, Some(after)) => {
+            writeln!(out, "new file mode {}", mode(after))?;
+            None
+        }
+        (Some(before), None) => {
+            writeln!(out, "deleted file mode {}", mode(before))?;
+            None
+        }
+        (Some(before), Some(after)) => {
+            if let Some(similarity) = change.similarity {
+                writeln!(out, "similarity index {similarity}%")?;
+                writeln!(out, "rename from {before_path}\nrename to {after_path}")?;
+            }
+            if before.mode != after.mode {
+                writeln!(out, "old mode {}\nnew mode {}", mode(before), mode(after))?;
+                None
+            } else {
+                Some(mode(after))
+            }
+        }
+        (None, None) => unreachable!("checked above"),
+    };
+    let (before, after) = (change.before.as_ref(), change.after.as_ref());
+    if before.map(|side| side.id) == after.map(|side| side.id) {
+        return Ok(());
+    }
+    write!(out, "index {}..{}", short_id(before), short_id(after))?;
+    match index_mode {
+        Some(mode) => writeln!(out, " {mode}")?,
+        None => writeln!(out)?,
+    }
+
+    let (old, new) = (content(repo, before)?, content(repo, after)?);
+    let (old_name, new_name) = (
+        before.map_or_else(|| "/dev/null".into(), |_| format!("a/{before_path}")),
+        after.map_or_else(|| "/dev/null".into(), |_| format!("b/{after_path}")),
+    );
+    if gix::merge::blob::is_binary(&old) || gix::merge::blob::is_binary(&new) {
+        writeln!(out, "Binary files {old_name} and {new_name} differ")?;
+        return Ok(());
+    }
+    writeln!(out, "--- {old_name}\n+++ {new_name}")?;
+    write_hunks(&old, &new, out)
+}
+
+/// The amount of unchanged lines to show around each change.
+const CONTEXT_LINES: u32 = 3;
+
+/// Write the unified diff of `old` and `new`, merging changes into one hunk if their context would overlap.
+fn write_hunks(old: &[u8], new: &[u8], out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let mut hunks: Vec<Vec<(std::ops::Range<u32>, std::ops::Range<u32>)>> = Vec::new();
+    gix::diff::blob::diff(
+        Algorithm::Histogram,
+        &input,
+        |before: std::ops::Range<u32>, after| match hunks.last_mut() {
+            Some(hunk) if before.start - hunk.last().expect("hunks are never empty").0.end <= 2 * CONTEXT_LINES => {
+                hunk.push((before, after));
+            }
+            _ => hunks.push(vec![(before, after)]),
+        },
+    );
+
+    let write_line = |out: &mut dyn std::io::Write, prefix: char, token: gix::diff::blob::intern::Token| {
+        let line = input.interner[token];
+        write!(out, "{prefix}")?;
+        out.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            writeln!(out, "\n\\ No newline at end of file")?;
+        }
+        std::io::Result::Ok(())
+    };
+    let range = |start: u32, len: u32| match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    };
+    for hunk in hunks {
+        let (first_before, first_after) = hunk.first().cloned().expect("hunks are never empty");
+        let (last_before, last_after) = hunk.last().cloned().expect("hunks are never empty");
+        let leading = first_before.start.min(CONTEXT_LINES);
+        let trailing = (input.before.len() as u32 - last_before.end).min(CONTEXT_LINES);
+        let (before_start, after_start) = (first_before.start - leading, first_after.start - leading);
+        let before_len = last_before.end + trailing - before_start;
+        let after_len = last_after.end + trailing - after_start;
+        writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(before_start, before_len),
+            range(after_start, after_len)
+        )?;
+
+        let mut pos = before_start;
+        for (before, after) in &hunk {
+            for &token in &input.before[pos as usize..before.start as usize] {
+                write_line(out, ' ', token)?;
+            }
+            for &token in &input.before[before.start as usize..before.end as usize] {
+                write_line(out, '-', token)?;
+            }
+            for &token in &input.after[after.start as usize..after.end as usize] {
+                write_line(out, '+', token)?;
+            }
+            pos = before.end;
+        }
+        for &token in &input.before[pos as usize..(last_before.end + trailing) as usize] {
+            write_line(out, ' ', token)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the name of each changed file along with the amount of changed lines, followed by a summary.
+fn write_stat(repo: &gix::Repository, changes: &[FileChange], out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    enum Count {
+        Lines { insertions: u32, removals: u32 },
+        Binary { before: usize, after: usize },
+    }
+    let mut files = Vec::with_capacity(changes.len());
+    for change in changes {
+        let name = match (&change.before, &change.after) {
+            (Some(before), Some(after)) if before.path != after.path => format!("{} => {}", before.path, after.path),
+            (_, Some(side)) | (Some(side), None) => side.path.to_string(),
+            (None, None) => bail!("BUG: a change has at least one side"),
+        };
+        let (old, new) = (
+            content(repo, change.before.as_ref())?,
+            content(repo, change.after.as_ref())?,
+        );
+        let count = if gix::merge::blob::is_binary(&old) || gix::merge::blob::is_binary(&new) {
+            Count::Binary {
+                before: old.len(),
+                after: new.len(),
+            }
+        } else {
+            let input = InternedInput::new(byte_lines_with_terminator(&old), byte_lines_with_terminator(&new));
+            let counter =
+                gix::diff::blob::diff(Algorithm::Histogram, &input, gix::diff::blob::sink::Counter::default());
+            Count::Lines {
+                insertions: counter.insertions,
+                removals: counter.removals,
+            }
+        };
+        files.push((name, count));
+    }
+
+    let name_width = files
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or_default();
+    let max_changes = files
+        .iter()
+        .map(|(_, count)| match count {
+            Count::Lines { insertions, removals } => insertions + removals,
+            Count::Binary { .. } => 0,
+        })
+        .max()
+        .unwrap_or_default();
+    let has_binary = files.iter().any(|(_, count)| matches!(count, Count::Binary { .. }));
+    let count_width = max_changes.to_string().len().max(if has_binary { 3 } else { 0 });
+    let bar_width = 79usize.saturating_sub(name_width + count_width + 5).max(6) as u32;
+    // Scale `n` to fit into the bar while keeping non-zero counts visible, exactly like `git` does.
+    let scale = |n: u32| {
+        if n == 0 {
+            0
+        } else {
+            1 + n * (bar_width - 1) / max_changes
+        }
+    };
+    let bar = |insertions: u32, removals: u32| {
+        if max_changes <= bar_width {
+            return (insertions, removals);
+        }
+        let mut total = scale(insertions + removals);
+        if total < 2 && insertions != 0 && removals != 0 {
+            total = 2;
+        }
+        if insertions < removals {
+            let insertions = scale(insertions);
+            (insertions, total - insertions)
+        } else {
+            let removals = scale(removals);
+            (total - removals, removals)
+        }
+    };
+    let (mut total_insertions, mut total_removals) = (0, 0);
+    for (name, count) in &files {
+        match count {
+            Count::Lines { insertions, removals } => {
+                total_insertions += insertions;
+                total_removals += removals;
+                let (plus, minus) = bar(*insertions, *removals);
+                let bar = format!("{}{}", "+".repeat(plus as usize), "-".repeat(minus as usize));
+                let line = format!(" {name:<name_width$} | {:>count_width$} {bar}", insertions + removals);
+                writeln!(out, "{}", line.trim_end())?;
+            }
+            Count::Binary { before, after } => {
+                writeln!(out, " {name:<name_width$} | Bin {before} -> {after} bytes")?;
+            }
+        }
+    }
+
+    let plural = |n: u32, singular: &str, plural: &str| if n == 1 { singular.to_owned() } else { plural.to_owned() };
+    write!(
+        out,
+        " {} {} changed",
+        files.len(),
+        plural(files.len() as u32, "file", "files")
+    )?;
+    if total_insertions > 0 {
+        write!(
+            out,
+            ", {total_insertions} {}(+)",
+            plural(total_insertions, "insertion", "insertions")
+        )?;
+    }
+    if total_removals > 0 {
+        write!(
+            out,
+            ", {total_removals} {}(-)",
+            plural(total_removals, "deletion", "deletions")
+        )?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use std::{collections::BTreeMap, sync::atomic::AtomicBool};
+
+use anyhow::{anyhow, bail, Context};
+use gix::{
+    bstr::{BString, ByteSlice},
+    index::entry::Mode,
+    prelude::ObjectIdExt,
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    },
+    ObjectId,
+};
+
+use crate::repository::{
+    checkout::{remove_from_worktree, update_worktree},
+    merge::describe_conflict,
+};
+
+const STASH_REF: &str = "refs/stash";
+
+pub mod push {
+    pub struct Options {
+        /// Also stash untracked files and remove them from the worktree.
+        pub include_untracked: bool,
+        /// The message to describe the stash entry with, instead of the summary of `HEAD`.
+        pub message: Option<String>,
+    }
+}
+
+/// Save the local modifications of the index and the worktree, along with untracked files if `include_untracked` is set,
+/// as a new stash entry and reset the index and the worktree to `HEAD`.
+///
+/// Just like `git stash`, the stash entry is a commit of the worktree whose parents are `HEAD`, a commit of the index and
+/// optionally a commit of all untracked files.
+pub fn push(
+    repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    should_interrupt: &AtomicBool,
+    push::Options {
+        include_untracked,
+        message,
+    }: push::Options,
+) -> anyhow::Result<()> {
+    let workdir = repo
+        .work_dir()
+        .ok_or_else(|| anyhow!("Cannot stash changes in a bare repository"))?
+        .to_owned();
+    let Some(id) = repo.stash_create_opts(
+        message.as_deref().map(Into::into),
+        gix::stash::create::Options { include_untracked },
+    )?
+    else {
+        writeln!(out, "No local changes to save")?;
+        return Ok(());
+    };
+    let stash = id.attach(&repo).object()?.into_commit();
+    let message: BString = stash.message_raw()?.trim_end().into();
+    repo.stash_store(id, message.clone())?;
+
+    let parents: Vec<_> = stash.parent_ids().map(gix::Id::detach).collect();
+    update_worktree(&repo, parents[0], true, should_interrupt)?;
+    if let Some(untracked) = parents.get(2) {
+        for path in entries_by_path(&repo, *untracked)?.keys() {
+            remove_from_worktree(&workdir, path.as_ref())?;
+        }
+    }
+    writeln!(out, "Saved working directory and index state {message}")?;
+    Ok(())
+}
+
+/// List all stash entries, newest first.
+pub fn list(repo: gix::Repository, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    for (index, line) in entries(&repo)?.iter().enumerate() {
+        writeln!(out, "stash@{{{index}}}: {}", line.message)?;
+    }
+    Ok(())
+}
+
+/// Show the status of each path that the `stash` entry changed compared to the commit it was created on.
+pub fn show(repo: gix::Repository, stash: Option<&str>, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    let (_, id) = resolve(&repo, stash)?;
+    let commit = id.attach(&repo).object()?.into_commit();
+    let base = commit
+        .parent_ids()
+        .next()
+        .ok_or_else(|| anyhow!("{id} is not a stash commit as it has no parent"))?;
+    let before = entries_by_path(&repo, base.detach())?;
+    let after = entries_by_path(&repo, id)?;
+    let paths: std::collections::BTreeSet<&BString> = before.keys().chain(after.keys()).collect();
+    for path in paths {
+        let status = match (before.get(path), after.get(path)) {
+            (None, Some(_)) => 'A',
+            (Some(_), None) => 'D',
+            (Some(before), Some(after)) if before != after => 'M',
+            _ => continue,
+        };
+        writeln!(out, "{status}\t{path}")?;
+    }
+    Ok(())
+}
+
+/// Apply the changes of the `stash` entry, defaulting to the newest one, to the worktree on top of `HEAD`, and restore its
+/// untracked files.
+///
+/// Files that didn't exist in `HEAD` are added to the index, all other changes remain unstaged. If the changes conflict
+/// with `HEAD`, the conflicts are listed and nothing is changed.
+pub fn apply(repo: gix::Repository, stash: Option<&str>, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    let (index, id) = resolve(&repo, stash)?;
+    if !apply_entry(&repo, index, id, out)? {
+        bail!("Could not apply the stash entry without conflicts, nothing was changed");
+    }
+    Ok(())
+}
+
+/// Like [`apply()`], but drop the stash entry afterwards unless there were conflicts.
+pub fn pop(repo: gix::Repository, stash: Option<&str>, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    let (index, id) = resolve(&repo, stash)?;
+    if !apply_entry(&repo, index, id, out)? {
+        bail!("Could not apply the stash entry without conflicts. The stash entry is kept in case you need it again.");
+    }
+    drop_entry(&repo, index)?;
+    writeln!(out, "Dropped stash@{{{index}}} ({id})")?;
+    Ok(())
+}
+
+/// Remove the `stash` entry, defaulting to the newest one.
+pub fn drop(repo: gix::Repository, stash: Option<&str>, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    let (index, id) = resolve(&repo, stash)?;
+    drop_entry(&repo, index)?;
+    writeln!(out, "Dropped stash@{{{index}}} ({id})")?;
+    Ok(())
+}
+
+/// Return `true` if the stash entry at `index` with commit `id` could be applied without conflicts.
+fn apply_entry(
+    repo: &gix::Repository,
+    index: usize,
+    id: ObjectId,
+    out: &mut dyn std::io::Write,
+) -> anyhow::Result<bool> {
+    match repo.stash_apply(id) {
+        Ok(()) => Ok(true),
+        Err(gix::stash::Error::Conflict { conflicts }) => {
+            let label = format!("stash@{{{index}}}");
+            for conflict in &conflicts {
+                writeln!(out, "{}", describe_conflict(conflict, &label))?;
+            }
+            Ok(false)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Remove the stash entry at `index` from the reflog of the stash reference, and point the reference to the newest remaining
+/// entry, or delete it if there is none.
+fn drop_entry(repo: &gix::Repository, index: usize) -> anyhow::Result<()> {
+    let mut lines = entries(repo)?;
+    lines.remove(index);
+    lines.reverse();
+    // Keep the chain of previous values intact, just like `git reflog delete --rewrite` does.
+    let newer = lines.len() - index;
+    if newer < lines.len() {
+        lines[newer].previous_oid = match newer.checked_sub(1) {
+            Some(older) => lines[older].new_oid,
+            None => repo.object_hash().null(),
+        };
+    }
+
+    let Some(newest) = lines.last() else {
+        repo.edit_reference(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name: STASH_REF.try_into()?,
+            deref: false,
+        })?;
+        return Ok(());
+    };
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: true,
+                message: newest.message.clone(),
+            },
+            expected: PreviousValue::Any,
+            new: Target::Object(newest.new_oid),
+        },
+        name: STASH_REF.try_into()?,
+        deref: false,
+    })?;
+    // The reference update added a line to the reflog, which is replaced by the remaining entries.
+    let mut buf = Vec::new();
+    for line in &lines {
+        line.write_to(&mut buf)?;
+    }
+    let log_path = repo.common_dir().join("logs").join(STASH_REF);
+    std::fs::write(&log_path, buf).with_context(|| format!("Could not write reflog at '{}'", log_path.display()))?;
+    Ok(())
+}
+
+/// Return all stash entries, newest first.
+fn entries(repo: &gix::Repository) -> anyhow::Result<Vec<gix::refs::log::Line>> {
+    let Some(stash) = repo.try_find_reference(STASH_REF)? else {
+        return Ok(Vec::new());
+    };
+    let mut platform = stash.log_iter();
+    let Some(lines) = platform.all()? else {
+        return Ok(Vec::new());
+    };
+    let mut lines = lines
+        .map(|line| line.map(|line| line.to_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    lines.reverse();
+    Ok(lines)
+}
+
+/// Parse `stash`, which is `stash@{<n>}` or `<n>`, and return the index of the entry along with the id of its commit.
+fn resolve(repo: &gix::Repository, stash: Option<&str>) -> anyhow::Result<(usize, ObjectId)> {
+    let index = match stash {
+        None => 0,
+        Some(stash) => stash
+            .strip_prefix("stash@{")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .unwrap_or(stash)
+            .parse()
+            .with_context(|| format!("'{stash}' is not a stash-like reference"))?,
+    };
+    let entries = entries(repo)?;
+    if entries.is_empty() {
+        bail!("No stash entries found.");
+    }
+    let line = entries
+        .get(index)
+        .ok_or_else(|| anyhow!("stash@{{{index}}} is not a valid reference"))?;
+    Ok((index, line.new_oid))
+}
+
+fn entries_by_path(repo: &gix::Repository, id: ObjectId) -> anyhow::Result<BTreeMap<BString, (ObjectId, Mode)>> {
+    let index = repo.index_from_tree(&id.attach(repo).object()?.peel_to_tree()?.id)?;
+    Ok(index
+        .entries()
+        .iter()
+        .map(|entry| (entry.path(&index).to_owned(), (entry.id, entry.mode)))
+        .collect())
+}
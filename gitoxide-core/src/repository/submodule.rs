@@ -1,7 +1,14 @@
-use anyhow::bail;
-use gix::{commit::describe::SelectRef, prelude::ObjectIdExt, Repository, Submodule};
+use std::{ffi::OsString, path::Path};
 
-use crate::OutputFormat;
+use anyhow::{anyhow, bail, Context};
+use gix::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    commit::describe::SelectRef,
+    prelude::ObjectIdExt,
+    Repository, Submodule,
+};
+
+use crate::{repository::branch::edit_local_config, OutputFormat};
 
 pub fn list(
     repo: Repository,
@@ -74,3 +81,640 @@ fn submodule_short_hash(id: Option<gix::ObjectId>, repo: Option<&Repository>) ->
         |id| repo.map_or_else(|| id.to_string(), |repo| id.attach(repo).shorten_or_id().to_string()),
     )
 }
+
+pub mod status {
+    pub struct Options {
+        /// Also print the status of submodules of submodules.
+        pub recursive: bool,
+    }
+}
+
+/// Print the commit checked out in each submodule matching `paths`, or all submodules if empty, like `git submodule status`.
+///
+/// Each line is prefixed with `-` if the submodule isn't initialized, and with `+` if the checked out commit
+/// doesn't match the one recorded in the index of the superproject.
+pub fn status(
+    repo: Repository,
+    paths: &[BString],
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+    status::Options { recursive }: status::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is supported for now")
+    }
+    print_status(&repo, paths, "".into(), recursive, &mut out)
+}
+
+fn print_status(
+    repo: &Repository,
+    paths: &[BString],
+    prefix: &BStr,
+    recursive: bool,
+    out: &mut impl std::io::Write,
+) -> anyhow::Result<()> {
+    let Some(submodules) = submodules_by_path(repo)? else {
+        return Ok(());
+    };
+    for sm in submodules {
+        let path = sm.path()?.into_owned();
+        if !matches_paths(paths, path.as_ref()) {
+            continue;
+        }
+        let display_path = display_path(prefix, path.as_ref());
+        let index_id = sm.index_id()?;
+        let sm_repo = sm
+            .open()?
+            .filter(|_| is_initialized(repo, sm.name()) && sm.state().is_ok_and(|s| s.worktree_checkout));
+        let Some(sm_repo) = sm_repo else {
+            let id = index_id.unwrap_or_else(|| repo.object_hash().null());
+            writeln!(out, "-{id} {display_path}")?;
+            continue;
+        };
+        let head_id = sm_repo.head_id().ok().map(gix::Id::detach);
+        let (marker, id) = match head_id {
+            Some(head_id) if Some(head_id) == index_id => (' ', head_id),
+            Some(head_id) => ('+', head_id),
+            None => ('-', index_id.unwrap_or_else(|| repo.object_hash().null())),
+        };
+        write!(out, "{marker}{id} {display_path}")?;
+        if let Some(name) = head_id.and_then(|id| describe(&sm_repo, id)) {
+            write!(out, " ({name})")?;
+        }
+        writeln!(out)?;
+        if recursive {
+            print_status(&sm_repo, &[], display_path.as_ref(), recursive, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the URL and update strategy of each submodule matching `paths`, or all submodules if empty, from `.gitmodules`
+/// into the local configuration, like `git submodule init`.
+///
+/// Relative URLs are resolved against the URL of the default remote of the superproject, or its worktree if there is none.
+pub fn init(repo: Repository, paths: &[BString], mut out: impl std::io::Write) -> anyhow::Result<()> {
+    init_submodules(&repo, paths, &mut out)
+}
+
+fn init_submodules(repo: &Repository, paths: &[BString], out: &mut impl std::io::Write) -> anyhow::Result<()> {
+    let (Some(submodules), Some(modules)) = (submodules_by_path(repo)?, repo.open_modules_file()?) else {
+        return Ok(());
+    };
+    let mut to_init = Vec::new();
+    for sm in submodules {
+        let path = sm.path()?.into_owned();
+        if !matches_paths(paths, path.as_ref()) || is_initialized(repo, sm.name()) {
+            continue;
+        }
+        // Without any paths, only initialize what is configured to be active, or everything if nothing is.
+        let config = repo.config_snapshot();
+        let has_active_config = config.plumbing().string("submodule.active").is_some()
+            || config
+                .plumbing()
+                .string(format!("submodule.{}.active", sm.name()))
+                .is_some();
+        if paths.is_empty() && has_active_config && !sm.is_active()? {
+            continue;
+        }
+        let url = modules
+            .config()
+            .string(format!("submodule.{}.url", sm.name()))
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| anyhow!("No url found for submodule path '{path}' in .gitmodules"))?
+            .into_owned();
+        let url = resolve_relative_url(repo, url)?;
+        // Commands can only be configured locally, so `.gitmodules` can't make us run anything.
+        let update = modules
+            .config()
+            .string(format!("submodule.{}.update", sm.name()))
+            .map(std::borrow::Cow::into_owned)
+            .filter(|update| {
+                !matches!(
+                    update.as_bstr().try_into(),
+                    Ok(gix::submodule::config::Update::Command(_)) | Err(())
+                )
+            });
+        to_init.push((sm.name().to_owned(), path, url, update));
+    }
+    if to_init.is_empty() {
+        return Ok(());
+    }
+    edit_local_config(repo, |config| {
+        for (name, _, url, update) in &to_init {
+            let mut section = config.section_mut_or_create_new("submodule", Some(name.as_ref()))?;
+            section.set("active".try_into()?, "true".into());
+            section.set("url".try_into()?, url.as_ref());
+            if let Some(update) = update {
+                section.set("update".try_into()?, update.as_ref());
+            }
+        }
+        Ok(())
+    })?;
+    for (name, path, url, _) in to_init {
+        writeln!(out, "Submodule '{name}' ({url}) registered for path '{path}'")?;
+    }
+    Ok(())
+}
+
+/// Run `command` with a shell in each checked out submodule, like `git submodule foreach`.
+///
+/// The command has access to the `name`, `sm_path`, `displaypath`, `sha1` and `toplevel` environment variables.
+pub fn foreach(
+    repo: Repository,
+    command: Vec<OsString>,
+    recursive: bool,
+    mut out: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let mut joined = OsString::new();
+    for (idx, arg) in command.iter().enumerate() {
+        if idx != 0 {
+            joined.push(" ");
+        }
+        joined.push(arg);
+    }
+    run_in_each(&repo, &joined, "".into(), recursive, &mut out)
+}
+
+fn run_in_each(
+    repo: &Repository,
+    command: &std::ffi::OsStr,
+    prefix: &BStr,
+    recursive: bool,
+    out: &mut impl std::io::Write,
+) -> anyhow::Result<()> {
+    let Some(submodules) = submodules_by_path(repo)? else {
+        return Ok(());
+    };
+    let toplevel = gix::path::realpath(
+        repo.work_dir()
+            .ok_or_else(|| anyhow!("Cannot run commands in submodules of a bare repository"))?,
+    )?;
+    for sm in submodules {
+        let Some(sm_repo) = sm.open()?.filter(|_| sm.state().is_ok_and(|s| s.worktree_checkout)) else {
+            continue;
+        };
+        let Some(work_dir) = sm_repo.work_dir().map(ToOwned::to_owned) else {
+            continue;
+        };
+        let path = sm.path()?.into_owned();
+        let display_path = display_path(prefix, path.as_ref());
+        writeln!(out, "Entering '{display_path}'")?;
+        out.flush()?;
+        let sha1 = sm_repo
+            .head_id()
+            .map_or_else(|_| repo.object_hash().null(), gix::Id::detach);
+        let mut cmd: std::process::Command = gix::command::prepare(command)
+            .with_shell()
+            .env("name", gix::path::from_bstr(sm.name()).as_os_str())
+            .env("sm_path", gix::path::from_bstr(path.as_bstr()).as_os_str())
+            .env("displaypath", gix::path::from_bstr(display_path.as_bstr()).as_os_str())
+            .env("sha1", sha1.to_string())
+            .env("toplevel", toplevel.as_os_str())
+            .stdin(std::process::Stdio::inherit())
+            .stdout(std::process::Stdio::inherit())
+            .into();
+        let status = cmd
+            .current_dir(&work_dir)
+            .status()
+            .with_context(|| format!("Could not run command in '{display_path}'"))?;
+        if !status.success() {
+            bail!("Stopping at '{display_path}'; script returned non-zero status.");
+        }
+        if recursive {
+            run_in_each(&sm_repo, command, display_path.as_ref(), recursive, out)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "blocking-client")]
+pub mod update {
+    pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+    pub struct Options {
+        /// Initialize submodules that aren't initialized yet before updating them.
+        pub init: bool,
+        /// Also update submodules of submodules, initializing them as needed.
+        pub recursive: bool,
+        /// The amount of submodules to fetch in parallel, or `None` to use `submodule.fetchJobs` or the amount of cores.
+        pub jobs: Option<usize>,
+    }
+}
+
+/// Check out the commit recorded in the index of the superproject in each initialized submodule matching `paths`,
+/// or all submodules if empty, cloning or fetching them as needed, like `git submodule update`.
+///
+/// Submodules are fetched in parallel, and their progress is aggregated under `progress`.
+#[cfg(feature = "blocking-client")]
+pub fn update<P>(
+    repo: Repository,
+    paths: &[BString],
+    mut progress: P,
+    mut out: impl std::io::Write,
+    should_interrupt: &std::sync::atomic::AtomicBool,
+    options: update::Options,
+) -> anyhow::Result<()>
+where
+    P: gix::NestedProgress,
+    P::SubProgress: 'static,
+    <P::SubProgress as gix::NestedProgress>::SubProgress: 'static,
+{
+    update_submodules(
+        repo,
+        paths,
+        "".into(),
+        &mut progress,
+        &mut out,
+        should_interrupt,
+        &options,
+    )
+}
+
+#[cfg(feature = "blocking-client")]
+fn update_submodules<P>(
+    mut repo: Repository,
+    paths: &[BString],
+    prefix: &BStr,
+    progress: &mut P,
+    out: &mut impl std::io::Write,
+    should_interrupt: &std::sync::atomic::AtomicBool,
+    options: &update::Options,
+) -> anyhow::Result<()>
+where
+    P: gix::NestedProgress,
+    P::SubProgress: 'static,
+    <P::SubProgress as gix::NestedProgress>::SubProgress: 'static,
+{
+    use gix::{Count, Progress};
+
+    if options.init {
+        init_submodules(&repo, paths, out)?;
+        // Pick up the configuration we just wrote.
+        repo = gix::open_opts(repo.git_dir(), repo.open_options().clone())?;
+    }
+    let Some(submodules) = submodules_by_path(&repo)? else {
+        return Ok(());
+    };
+
+    let mut plans = Vec::new();
+    for sm in submodules {
+        let path = sm.path()?.into_owned();
+        if !matches_paths(paths, path.as_ref()) || !is_initialized(&repo, sm.name()) {
+            continue;
+        }
+        if matches!(sm.update()?, Some(gix::submodule::config::Update::None)) {
+            continue;
+        }
+        if let Some(
+            update @ (gix::submodule::config::Update::Rebase
+            | gix::submodule::config::Update::Merge
+            | gix::submodule::config::Update::Command(_)),
+        ) = sm.update()?
+        {
+            bail!(
+                "Submodule path '{}' uses the unsupported update strategy {update:?}",
+                display_path(prefix, path.as_ref())
+            );
+        }
+        let Some(target) = sm.index_id()? else {
+            continue;
+        };
+        let sm_repo = if sm.state()?.repository_exists {
+            sm.open()?
+        } else {
+            None
+        };
+        let fetch = match sm_repo {
+            None => Some(update_impl::Fetch::Clone { url: sm.url()? }),
+            Some(sm_repo) => (!sm_repo.has_object(target)).then_some(update_impl::Fetch::Fetch),
+        };
+        plans.push(update_impl::Plan {
+            name: sm.name().to_owned(),
+            display_path: display_path(prefix, path.as_ref()),
+            git_dir: sm.git_dir_try_old_form()?,
+            work_dir: sm.work_dir()?,
+            target,
+            fetch,
+        });
+    }
+
+    let jobs: Vec<_> = plans.iter().filter(|plan| plan.fetch.is_some()).collect();
+    if !jobs.is_empty() {
+        let num_threads = options
+            .jobs
+            .or_else(|| {
+                repo.config_snapshot()
+                    .integer("submodule.fetchJobs")
+                    .and_then(|jobs| usize::try_from(jobs).ok())
+                    .filter(|jobs| *jobs > 0)
+            })
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, Into::into))
+            .min(jobs.len())
+            .max(1);
+        progress.init(Some(jobs.len()), gix::progress::count("submodules"));
+        progress.set_name(format!("fetch submodules with {num_threads} thread(s)"));
+        let children: Vec<_> = jobs
+            .iter()
+            .map(|plan| progress.add_child(plan.display_path.to_string()))
+            .collect();
+        let open_options = repo.open_options().clone();
+        let work = std::sync::Mutex::new(jobs.into_iter().zip(children).collect::<Vec<_>>());
+        let results = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..num_threads)
+                .map(|_| {
+                    scope.spawn(|| -> anyhow::Result<()> {
+                        loop {
+                            let Some((plan, mut child)) = work.lock().expect("not poisoned").pop() else {
+                                return Ok(());
+                            };
+                            plan.fetch(&mut child, open_options.clone(), should_interrupt)?;
+                            progress.inc();
+                        }
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().expect("no panic"))
+                .collect::<Vec<_>>()
+        });
+        results.into_iter().collect::<anyhow::Result<()>>()?;
+        progress.show_throughput(std::time::Instant::now());
+    }
+
+    for plan in plans {
+        let sm_repo = plan.checkout(&repo, should_interrupt)?;
+        if let Some(sm_repo) = sm_repo {
+            writeln!(
+                out,
+                "Submodule path '{}': checked out '{}'",
+                plan.display_path, plan.target
+            )?;
+            if options.recursive {
+                let recursive = update::Options {
+                    init: true,
+                    recursive: true,
+                    jobs: options.jobs,
+                };
+                update_submodules(
+                    sm_repo,
+                    &[],
+                    plan.display_path.as_ref(),
+                    progress,
+                    out,
+                    should_interrupt,
+                    &recursive,
+                )?;
+            }
+        } else if options.recursive {
+            let sm_repo = gix::open_opts(&plan.git_dir, repo.open_options().clone())?;
+            update_submodules(
+                sm_repo,
+                &[],
+                plan.display_path.as_ref(),
+                progress,
+                out,
+                should_interrupt,
+                options,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "blocking-client")]
+mod update_impl {
+    use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+    use anyhow::{anyhow, bail};
+    use gix::{
+        bstr::BString,
+        refs::{
+            transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+            Target,
+        },
+    };
+
+    use crate::repository::{branch::edit_local_config, checkout::update_worktree};
+
+    pub(super) enum Fetch {
+        /// The submodule repository doesn't exist yet and needs to be cloned from `url`.
+        Clone { url: gix::Url },
+        /// The submodule repository exists, but lacks the commit to check out.
+        Fetch,
+    }
+
+    pub(super) struct Plan {
+        pub name: BString,
+        pub display_path: BString,
+        pub git_dir: PathBuf,
+        pub work_dir: PathBuf,
+        pub target: gix::ObjectId,
+        pub fetch: Option<Fetch>,
+    }
+
+    impl Plan {
+        /// Clone or fetch the submodule repository so it contains our target commit.
+        pub fn fetch<P>(
+            &self,
+            progress: &mut P,
+            open_options: gix::open::Options,
+            should_interrupt: &AtomicBool,
+        ) -> anyhow::Result<()>
+        where
+            P: gix::NestedProgress,
+            P::SubProgress: 'static,
+        {
+            match &self.fetch {
+                None => return Ok(()),
+                Some(Fetch::Clone { url }) => {
+                    std::fs::create_dir_all(&self.git_dir)?;
+                    gix::clone::PrepareFetch::new(
+                        url.clone(),
+                        &self.git_dir,
+                        gix::create::Kind::Bare,
+                        gix::create::Options::default(),
+                        open_options,
+                    )?
+                    .fetch_only(progress, should_interrupt)?;
+                }
+                Some(Fetch::Fetch) => {
+                    let repo = gix::open_opts(&self.git_dir, open_options)?;
+                    let remote = repo
+                        .find_default_remote(gix::remote::Direction::Fetch)
+                        .ok_or_else(|| anyhow!("Submodule '{}' has no remote to fetch from", self.name))??;
+                    remote
+                        .connect(gix::remote::Direction::Fetch)?
+                        .prepare_fetch(&mut *progress, Default::default())?
+                        .receive(&mut *progress, should_interrupt)?;
+                    if !repo.has_object(self.target) {
+                        bail!(
+                            "Fetched in submodule path '{}', but it did not contain {}",
+                            self.display_path,
+                            self.target
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Check out our target commit with a detached `HEAD`, and return the submodule repository if something changed.
+        pub fn checkout(
+            &self,
+            superproject: &gix::Repository,
+            should_interrupt: &AtomicBool,
+        ) -> anyhow::Result<Option<gix::Repository>> {
+            let is_fresh_clone = matches!(self.fetch, Some(Fetch::Clone { .. }));
+            if is_fresh_clone {
+                self.connect_worktree()?;
+            }
+            let repo = gix::open_opts(&self.git_dir, superproject.open_options().clone())?;
+            let head_id = repo.head_id().ok().map(gix::Id::detach);
+            if head_id == Some(self.target) && repo.head()?.is_detached() {
+                return Ok(None);
+            }
+            std::fs::create_dir_all(&self.work_dir)?;
+            // A fresh clone has nothing checked out even though its `HEAD` may already point to our target.
+            update_worktree(&repo, self.target, is_fresh_clone, should_interrupt)?;
+            repo.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: format!("submodule update: checkout {}", self.target).into(),
+                    },
+                    expected: PreviousValue::Any,
+                    new: Target::Object(self.target),
+                },
+                name: "HEAD".try_into()?,
+                deref: false,
+            })?;
+            Ok(Some(repo))
+        }
+
+        /// Turn the freshly cloned bare repository into one with a worktree at our work dir.
+        fn connect_worktree(&self) -> anyhow::Result<()> {
+            std::fs::create_dir_all(&self.work_dir)?;
+            let work_dir = gix::path::realpath(&self.work_dir)?;
+            let git_dir = gix::path::realpath(&self.git_dir)?;
+            let repo = gix::open_opts(&git_dir, gix::open::Options::isolated())?;
+            edit_local_config(&repo, |config| {
+                let mut core = config.section_mut_or_create_new("core", None)?;
+                core.set("bare".try_into()?, "false".into());
+                core.set(
+                    "worktree".try_into()?,
+                    gix::path::into_bstr(relative_path(&git_dir, &work_dir)).as_ref(),
+                );
+                Ok(())
+            })?;
+            std::fs::write(
+                work_dir.join(gix::discover::DOT_GIT_DIR),
+                format!("gitdir: {}\n", relative_path(&work_dir, &git_dir).display()),
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Return the path to `to` relative to the directory `from`, assuming both are absolute and normalized.
+    fn relative_path(from: &std::path::Path, to: &std::path::Path) -> PathBuf {
+        let common = from
+            .components()
+            .zip(to.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut out: PathBuf = from.components().skip(common).map(|_| "..").collect();
+        out.extend(to.components().skip(common));
+        out
+    }
+}
+
+/// Return all submodules of `repo` in the order of their paths, like they appear in the index.
+fn submodules_by_path(repo: &Repository) -> anyhow::Result<Option<Vec<Submodule<'_>>>> {
+    Ok(repo.submodules()?.map(|submodules| {
+        let mut submodules: Vec<_> = submodules.collect();
+        submodules.sort_by_cached_key(|sm| sm.path().ok().map(std::borrow::Cow::into_owned));
+        submodules
+    }))
+}
+
+fn is_initialized(repo: &Repository, name: &BStr) -> bool {
+    repo.config_snapshot()
+        .plumbing()
+        .string(format!("submodule.{name}.url"))
+        .is_some_and(|url| !url.is_empty())
+}
+
+fn matches_paths(paths: &[BString], path: &BStr) -> bool {
+    paths.is_empty()
+        || paths.iter().any(|wanted| {
+            let wanted = wanted.trim_end_with(|c| c == '/');
+            path == wanted || path.starts_with(wanted) && path.get(wanted.len()) == Some(&b'/')
+        })
+}
+
+fn display_path(prefix: &BStr, path: &BStr) -> BString {
+    if prefix.is_empty() {
+        path.to_owned()
+    } else {
+        let mut out = prefix.to_owned();
+        out.push_byte(b'/');
+        out.push_str(path);
+        out
+    }
+}
+
+fn describe(repo: &Repository, id: gix::ObjectId) -> Option<String> {
+    id.attach(repo)
+        .object()
+        .ok()?
+        .try_into_commit()
+        .ok()?
+        .describe()
+        .names(SelectRef::AllRefs)
+        .try_resolve()
+        .ok()??
+        .format()
+        .ok()
+        .map(|format| format.to_string())
+}
+
+/// Resolve `url` relative to the URL of the default remote of `repo`, or its worktree, if it starts with `./` or `../`.
+fn resolve_relative_url(repo: &Repository, url: BString) -> anyhow::Result<BString> {
+    if !(url.starts_with(b"./") || url.starts_with(b"../")) {
+        return Ok(url);
+    }
+    let mut base: BString = match repo
+        .find_default_remote(gix::remote::Direction::Fetch)
+        .transpose()?
+        .and_then(|remote| remote.url(gix::remote::Direction::Fetch).map(gix::Url::to_bstring))
+    {
+        Some(base) => base,
+        None => gix::path::into_bstr(
+            repo.work_dir()
+                .map_or_else(|| repo.git_dir().to_owned(), Path::to_owned),
+        )
+        .into_owned(),
+    };
+    while base.ends_with(b"/") {
+        base.pop();
+    }
+    let mut rest = url.as_bstr();
+    loop {
+        if let Some(stripped) = rest.strip_prefix(b"./") {
+            rest = stripped.as_bstr();
+        } else if let Some(stripped) = rest.strip_prefix(b"../") {
+            rest = stripped.as_bstr();
+            match base.rfind_byte(b'/') {
+                Some(pos) => base.truncate(pos),
+                None => bail!("Cannot strip one component off url '{base}'"),
+            }
+        } else {
+            break;
+        }
+    }
+    base.push_byte(b'/');
+    base.push_str(rest);
+    Ok(base)
+}
@@ -0,0 +1,374 @@
+use std::{
+    cmp::Ordering,
+    io::{Read, Write},
+    process::Stdio,
+};
+
+use anyhow::{anyhow, bail, Context};
+use gix::{bstr::ByteSlice, refs::transaction::PreviousValue};
+
+use crate::OutputFormat;
+
+pub mod list {
+    pub struct Options {
+        /// Only list tags whose short name matches any of these patterns, or all tags if empty.
+        pub patterns: Vec<String>,
+        /// A sort key like `refname`, `version:refname` or `creatordate`, prefixed with `-` to reverse the order.
+        pub sort: Option<String>,
+    }
+}
+
+pub fn list(
+    repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    format: OutputFormat,
+    list::Options { patterns, sort }: list::Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("JSON output isn't implemented yet");
+    }
+    let sort = SortKey::parse(sort.as_deref().unwrap_or("refname"))?;
+
+    let platform = repo.references()?;
+    let mut tags = Vec::new();
+    for tag in platform.tags()? {
+        let mut tag = tag.map_err(|err| anyhow!(err))?;
+        let name = tag.name().shorten().to_owned();
+        if !patterns.is_empty()
+            && !patterns
+                .iter()
+                .any(|pattern| gix::glob::wildmatch(pattern.as_bytes().as_bstr(), name.as_ref(), Default::default()))
+        {
+            continue;
+        }
+        let time = match sort.field {
+            SortField::CreatorDate => creator_date(&mut tag)?,
+            SortField::RefName | SortField::Version => None,
+        };
+        tags.push((name, time));
+    }
+
+    tags.sort_by(|(a_name, a_time), (b_name, b_time)| {
+        let ordering = match sort.field {
+            SortField::RefName => a_name.cmp(b_name),
+            SortField::Version => compare_versions(a_name.as_ref(), b_name.as_ref()),
+            SortField::CreatorDate => a_time.cmp(b_time).then_with(|| a_name.cmp(b_name)),
+        };
+        if sort.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    for (name, _) in tags {
+        writeln!(out, "{name}")?;
+    }
+    Ok(())
+}
+
+pub mod create {
+    use std::path::PathBuf;
+
+    pub struct Options {
+        /// The tag message, which makes this an annotated tag.
+        pub message: Option<String>,
+        /// Read the tag message from this file, which makes this an annotated tag.
+        pub file: Option<PathBuf>,
+        /// Create an annotated tag, and obtain the message from an editor if it isn't provided otherwise.
+        pub annotate: bool,
+        /// Create a signed annotated tag, and obtain the message from an editor if it isn't provided otherwise.
+        pub sign: bool,
+        /// The key to sign with instead of `user.signingKey` or the committer identity.
+        pub local_user: Option<String>,
+        /// Replace an existing tag of the same name.
+        pub force: bool,
+    }
+}
+
+pub fn create(
+    repo: gix::Repository,
+    name: &str,
+    target: Option<&str>,
+    create::Options {
+        message,
+        file,
+        annotate,
+        sign,
+        local_user,
+        force,
+    }: create::Options,
+) -> anyhow::Result<()> {
+    let target = repo.rev_parse_single(target.unwrap_or("HEAD"))?.object()?;
+    let constraint = if force {
+        PreviousValue::Any
+    } else {
+        if repo.try_find_reference(format!("refs/tags/{name}").as_str())?.is_some() {
+            bail!("tag '{name}' already exists");
+        }
+        PreviousValue::MustNotExist
+    };
+
+    let message = match (message, file) {
+        (Some(message), _) => Some(message),
+        (None, Some(path)) if path.as_os_str() == "-" => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            Some(buf)
+        }
+        (None, Some(path)) => Some(
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Could not read tag message from '{}'", path.display()))?,
+        ),
+        (None, None) if annotate || sign || local_user.is_some() => Some(edit_message(&repo, name)?),
+        (None, None) => None,
+    };
+    let Some(message) = message else {
+        repo.tag_reference(name, target.id, constraint)?;
+        return Ok(());
+    };
+    let message = cleanup_message(&message);
+    if message.is_empty() {
+        bail!("Refusing to create a tag with an empty message");
+    }
+
+    let tagger = repo
+        .committer()
+        .ok_or_else(|| anyhow!("A committer identity is needed to create annotated tags"))??
+        .to_owned();
+    if !(sign || local_user.is_some()) {
+        repo.tag(name, target.id, target.kind, Some(tagger.to_ref()), message, constraint)?;
+        return Ok(());
+    }
+
+    let mut tag = gix::objs::Tag {
+        target: target.id,
+        target_kind: target.kind,
+        name: name.into(),
+        tagger: Some(tagger),
+        message: message.into(),
+        pgp_signature: None,
+    };
+    let mut payload = Vec::new();
+    gix::objs::WriteTo::write_to(&tag, &mut payload)?;
+    let signature = sign_payload(&repo, local_user, &payload)?;
+    // The signature directly follows the message, which is serialized with a separating newline.
+    tag.message.pop();
+    tag.pgp_signature = Some(signature.into());
+    let tag_id = repo.write_object(&tag)?;
+    repo.tag_reference(name, tag_id, constraint)?;
+    Ok(())
+}
+
+pub fn delete(repo: gix::Repository, names: &[String], out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    use gix::refs::transaction::{Change, RefEdit, RefLog};
+    for name in names {
+        let reference = repo
+            .find_reference(format!("refs/tags/{name}").as_str())
+            .with_context(|| format!("tag '{name}' not found"))?;
+        let id = reference.id();
+        repo.edit_reference(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::MustExistAndMatch(reference.target().into_owned()),
+                log: RefLog::AndReference,
+            },
+            name: reference.name().to_owned(),
+            deref: false,
+        })?;
+        writeln!(out, "Deleted tag '{name}' (was {})", id.shorten_or_id())?;
+    }
+    Ok(())
+}
+
+/// Note that like [`commit::verify()`](super::commit::verify()), this only focusses on the gist of what `git` does.
+pub fn verify(repo: gix::Repository, names: &[String]) -> anyhow::Result<()> {
+    for name in names {
+        let tag = repo
+            .find_reference(format!("refs/tags/{name}").as_str())
+            .with_context(|| format!("tag '{name}' not found"))?
+            .id()
+            .object()?
+            .try_into_tag()
+            .map_err(|_| anyhow!("Tag '{name}' isn't an annotated tag and can't be verified"))?;
+        let signature = tag
+            .decode()?
+            .pgp_signature
+            .ok_or_else(|| anyhow!("Tag '{name}' is not signed"))?;
+        let signed_data = &tag.data[..tag.data.len() - signature.len()];
+
+        let mut signature_storage = tempfile::NamedTempFile::new()?;
+        signature_storage.write_all(signature.as_ref())?;
+        let signed_storage = signature_storage.into_temp_path();
+
+        let mut cmd: std::process::Command = gix::command::prepare(gpg_program(&repo)).into();
+        cmd.args(["--keyid-format=long", "--status-fd=1", "--verify"])
+            .arg(&signed_storage)
+            .arg("-")
+            .stdin(Stdio::piped());
+        gix::trace::debug!("About to execute {cmd:?}");
+        let mut child = cmd.spawn()?;
+        child.stdin.take().expect("configured").write_all(signed_data)?;
+
+        if !child.wait()?.success() {
+            bail!("Could not verify tag '{name}' as command {cmd:?} failed");
+        }
+    }
+    Ok(())
+}
+
+fn gpg_program(repo: &gix::Repository) -> String {
+    repo.config_snapshot()
+        .string("gpg.program")
+        .map_or_else(|| "gpg".into(), |program| program.to_string())
+}
+
+/// Sign `payload` with a detached, ascii-armored signature, just like `git` does.
+fn sign_payload(repo: &gix::Repository, key: Option<String>, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let key = match key.or_else(|| {
+        repo.config_snapshot()
+            .string("user.signingKey")
+            .map(|key| key.to_string())
+    }) {
+        Some(key) => key,
+        None => {
+            let committer = repo
+                .committer()
+                .ok_or_else(|| anyhow!("A committer identity is needed to find the signing key"))??;
+            format!("{} <{}>", committer.name, committer.email)
+        }
+    };
+    let mut cmd: std::process::Command = gix::command::prepare(gpg_program(repo)).into();
+    cmd.arg("-bsau").arg(key).stdin(Stdio::piped()).stdout(Stdio::piped());
+    gix::trace::debug!("About to execute {cmd:?}");
+    let mut child = cmd.spawn()?;
+    child.stdin.take().expect("configured").write_all(payload)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!("Command {cmd:?} failed to sign the tag");
+    }
+    Ok(output.stdout)
+}
+
+/// Let the user write the tag message in their editor, similar to `git tag -a` without `-m`.
+fn edit_message(repo: &gix::Repository, name: &str) -> anyhow::Result<String> {
+    let editor = std::env::var_os("GIT_EDITOR")
+        .or_else(|| {
+            repo.config_snapshot()
+                .trusted_program("core.editor")
+                .map(std::borrow::Cow::into_owned)
+        })
+        .or_else(|| std::env::var_os("VISUAL"))
+        .or_else(|| std::env::var_os("EDITOR"))
+        .unwrap_or_else(|| "vi".into());
+
+    let path = repo.git_dir().join("TAG_EDITMSG");
+    std::fs::write(
+        &path,
+        format!("\n#\n# Write a message for tag:\n#   {name}\n# Lines starting with '#' will be ignored.\n"),
+    )?;
+    let mut cmd: std::process::Command = gix::command::prepare(editor).arg(&path).with_shell().into();
+    gix::trace::debug!("About to execute {cmd:?}");
+    if !cmd.status()?.success() {
+        bail!("Editor {cmd:?} failed");
+    }
+    let message = std::fs::read_to_string(&path)?;
+    Ok(message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Strip trailing whitespace from all lines as well as leading and trailing empty lines, and end the message with a newline.
+fn cleanup_message(message: &str) -> String {
+    let lines: Vec<_> = message.lines().map(str::trim_end).collect();
+    let lines = match lines.iter().position(|line| !line.is_empty()) {
+        Some(start) => {
+            let end = lines
+                .iter()
+                .rposition(|line| !line.is_empty())
+                .expect("non-empty exists");
+            &lines[start..=end]
+        }
+        None => return String::new(),
+    };
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Return the time of the tagger of annotated tags, or the committer time of the commit a lightweight tag points to.
+fn creator_date(tag: &mut gix::Reference<'_>) -> anyhow::Result<Option<gix::date::SecondsSinceUnixEpoch>> {
+    let object = tag.id().object()?;
+    Ok(match object.kind {
+        gix::object::Kind::Tag => object.into_tag().tagger()?.map(|tagger| tagger.time.seconds),
+        gix::object::Kind::Commit => Some(object.into_commit().time()?.seconds),
+        _ => None,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum SortField {
+    RefName,
+    Version,
+    CreatorDate,
+}
+
+struct SortKey {
+    field: SortField,
+    reverse: bool,
+}
+
+impl SortKey {
+    fn parse(key: &str) -> anyhow::Result<Self> {
+        let (reverse, key) = key.strip_prefix('-').map_or((false, key), |key| (true, key));
+        let field = match key {
+            "refname" => SortField::RefName,
+            "version:refname" | "v:refname" => SortField::Version,
+            "creatordate" | "taggerdate" => SortField::CreatorDate,
+            _ => bail!("Unsupported sort key '{key}', use one of 'refname', 'version:refname' or 'creatordate'"),
+        };
+        Ok(SortKey { field, reverse })
+    }
+}
+
+/// Compare `a` and `b` so that sequences of digits are ordered by their numerical value, like `v1.2 < v1.10`.
+fn compare_versions(a: &gix::bstr::BStr, b: &gix::bstr::BStr) -> Ordering {
+    fn chunks(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+        let mut rest = input;
+        std::iter::from_fn(move || {
+            let first = *rest.first()?;
+            let len = rest
+                .iter()
+                .position(|b| b.is_ascii_digit() != first.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (chunk, tail) = rest.split_at(len);
+            rest = tail;
+            Some(chunk)
+        })
+    }
+    let mut a_chunks = chunks(a);
+    let mut b_chunks = chunks(b);
+    loop {
+        match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ordering = if a[0].is_ascii_digit() && b[0].is_ascii_digit() {
+                    let (a, b) = (trim_leading_zeros(a), trim_leading_zeros(b));
+                    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+                } else {
+                    a.cmp(b)
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits.iter().position(|b| *b != b'0').unwrap_or(digits.len());
+    &digits[start..]
+}
@@ -1,10 +1,26 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
 use crate::OutputFormat;
-use anyhow::bail;
+use anyhow::{anyhow, bail, Context};
+use gix::{prelude::ObjectIdExt, refs::FullName, worktree::Proxy};
+
+use crate::repository::checkout::checkout_entries;
 
-pub fn list(repo: gix::Repository, out: &mut dyn std::io::Write, format: OutputFormat) -> anyhow::Result<()> {
+pub fn list(
+    repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    format: OutputFormat,
+    porcelain: bool,
+) -> anyhow::Result<()> {
     if format != OutputFormat::Human {
         bail!("JSON output isn't implemented yet");
     }
+    if porcelain {
+        return list_porcelain(&repo, out);
+    }
 
     if let Some(worktree) = repo.worktree() {
         writeln!(
@@ -26,3 +42,425 @@ pub fn list(repo: gix::Repository, out: &mut dyn std::io::Write, format: OutputF
     }
     Ok(())
 }
+
+/// List the main worktree and all linked worktrees just like `git worktree list --porcelain` does.
+fn list_porcelain(repo: &gix::Repository, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+    fn write_head(repo: &gix::Repository, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+        let head = repo.head()?;
+        let id = head.id().map_or_else(|| repo.object_hash().null(), gix::Id::detach);
+        writeln!(out, "HEAD {id}")?;
+        match head.referent_name() {
+            Some(name) => writeln!(out, "branch {}", name.as_bstr())?,
+            None => writeln!(out, "detached")?,
+        }
+        Ok(())
+    }
+
+    let main = repo.main_repo()?;
+    match main.work_dir() {
+        Some(base) => {
+            writeln!(out, "worktree {}", gix::path::realpath(base)?.display())?;
+            write_head(&main, out)?;
+        }
+        None => writeln!(out, "worktree {}\nbare", gix::path::realpath(main.git_dir())?.display())?,
+    }
+    writeln!(out)?;
+
+    for proxy in repo.worktrees()? {
+        let base = proxy.base()?;
+        writeln!(out, "worktree {}", base.display())?;
+        let lock_reason = proxy.lock_reason();
+        let is_prunable = !base.is_dir();
+        write_head(&proxy.into_repo_with_possibly_inaccessible_worktree()?, out)?;
+        match lock_reason {
+            Some(reason) if reason.is_empty() => writeln!(out, "locked")?,
+            Some(reason) => writeln!(out, "locked {reason}")?,
+            None if is_prunable => writeln!(out, "prunable gitdir file points to non-existent location")?,
+            None => {}
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+pub mod add {
+    pub struct Options {
+        /// Create a new branch with this name at the commit to check out, and check it out instead.
+        pub new_branch: Option<String>,
+        /// Detach `HEAD` in the new worktree even if a branch is checked out.
+        pub detach: bool,
+        /// Check out the branch even if it is already checked out in another worktree.
+        pub force: bool,
+        /// Keep the new worktree locked, along with an optional reason.
+        pub lock: Option<Option<String>>,
+    }
+}
+
+/// Create a new worktree at `path` and check out `commit_ish` into it, just like `git worktree add` does.
+///
+/// If `commit_ish` is a branch, it's checked out unless `detach` is set. Without `commit_ish`, a new branch named
+/// after the last component of `path` is created at `HEAD`, or checked out if it already exists.
+pub fn add(
+    repo: gix::Repository,
+    path: &Path,
+    commit_ish: Option<&str>,
+    out: &mut dyn std::io::Write,
+    should_interrupt: &AtomicBool,
+    add::Options {
+        new_branch,
+        detach,
+        force,
+        lock,
+    }: add::Options,
+) -> anyhow::Result<()> {
+    if path.exists() && path.read_dir().map_or(true, |mut entries| entries.next().is_some()) {
+        bail!("'{}' already exists", path.display());
+    }
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Could not derive a worktree name from '{}'", path.display()))?;
+
+    let (head, commit, preparing) = match (new_branch, commit_ish) {
+        (Some(new_branch), commit_ish) => {
+            let start_point = commit_ish.unwrap_or("HEAD");
+            let commit = repo
+                .rev_parse_single(format!("{start_point}^{{commit}}").as_str())?
+                .detach();
+            let branch = branch_name(&new_branch)?;
+            if repo.try_find_reference(branch.as_ref())?.is_some() {
+                bail!("a branch named '{new_branch}' already exists");
+            }
+            repo.reference(
+                branch.clone(),
+                commit,
+                gix::refs::transaction::PreviousValue::MustNotExist,
+                format!("branch: Created from {start_point}"),
+            )?;
+            (
+                gix::refs::Target::Symbolic(branch),
+                commit,
+                format!("new branch '{new_branch}'"),
+            )
+        }
+        (None, Some(commit_ish)) => {
+            let branch = (!detach)
+                .then(|| repo.try_find_reference(format!("refs/heads/{commit_ish}").as_str()))
+                .transpose()?
+                .flatten();
+            match branch {
+                Some(mut branch) => {
+                    let commit = branch.peel_to_id_in_place()?.detach();
+                    (
+                        gix::refs::Target::Symbolic(branch.inner.name),
+                        commit,
+                        format!("checking out '{commit_ish}'"),
+                    )
+                }
+                None => {
+                    let commit = repo.rev_parse_single(format!("{commit_ish}^{{commit}}").as_str())?;
+                    let preparing = format!("detached HEAD {}", commit.shorten_or_id());
+                    (gix::refs::Target::Object(commit.detach()), commit.detach(), preparing)
+                }
+            }
+        }
+        (None, None) => {
+            let commit = repo.head_commit()?.id;
+            if detach {
+                let preparing = format!("detached HEAD {}", commit.attach(&repo).shorten_or_id());
+                (gix::refs::Target::Object(commit), commit, preparing)
+            } else {
+                let branch = branch_name(name)?;
+                match repo.try_find_reference(branch.as_ref())? {
+                    Some(mut existing) => (
+                        gix::refs::Target::Symbolic(branch),
+                        existing.peel_to_id_in_place()?.detach(),
+                        format!("checking out '{name}'"),
+                    ),
+                    None => {
+                        repo.reference(
+                            branch.clone(),
+                            commit,
+                            gix::refs::transaction::PreviousValue::MustNotExist,
+                            "branch: Created from HEAD",
+                        )?;
+                        (
+                            gix::refs::Target::Symbolic(branch),
+                            commit,
+                            format!("new branch '{name}'"),
+                        )
+                    }
+                }
+            }
+        }
+    };
+    if let gix::refs::Target::Symbolic(branch) = &head {
+        if let Some(base) = worktree_with_branch_checked_out(&repo, branch)?.filter(|_| !force) {
+            bail!(
+                "'{}' is already used by worktree at '{}'",
+                branch.shorten(),
+                base.display()
+            );
+        }
+    }
+    writeln!(out, "Preparing worktree ({preparing})")?;
+
+    std::fs::create_dir_all(path)?;
+    let base = gix::path::realpath(path)?;
+    let worktrees = repo.common_dir().join("worktrees");
+    let mut id = name.to_owned();
+    let mut counter = 0;
+    while worktrees.join(&id).exists() {
+        counter += 1;
+        id = format!("{name}{counter}");
+    }
+    let private_git_dir = worktrees.join(&id);
+    std::fs::create_dir_all(&private_git_dir)?;
+    let private_git_dir = gix::path::realpath(&private_git_dir)?;
+    std::fs::write(
+        private_git_dir.join("gitdir"),
+        format!("{}\n", base.join(gix::discover::DOT_GIT_DIR).display()),
+    )?;
+    std::fs::write(private_git_dir.join("commondir"), "../..\n")?;
+    std::fs::write(
+        private_git_dir.join("HEAD"),
+        match &head {
+            gix::refs::Target::Symbolic(name) => format!("ref: {}\n", name.as_bstr()),
+            gix::refs::Target::Object(id) => format!("{id}\n"),
+        },
+    )?;
+    std::fs::write(
+        base.join(gix::discover::DOT_GIT_DIR),
+        format!("gitdir: {}\n", private_git_dir.display()),
+    )?;
+
+    let worktree_repo = gix::open_opts(&base, repo.open_options().clone())?;
+    let mut index = worktree_repo.index_from_tree(&commit.attach(&worktree_repo).object()?.peel_to_tree()?.id)?;
+    let opts = worktree_repo.checkout_options(gix::worktree::stack::state::attributes::Source::IdMapping)?;
+    checkout_entries(&worktree_repo, &mut index, &base, should_interrupt, opts)?;
+    index.write(Default::default())?;
+    if let Some(reason) = lock {
+        write_lock(&private_git_dir, reason.as_deref())?;
+    }
+
+    let commit = worktree_repo.find_commit(commit)?;
+    writeln!(
+        out,
+        "HEAD is now at {} {}",
+        commit.id().shorten_or_id(),
+        commit.message()?.summary()
+    )?;
+    Ok(())
+}
+
+/// Lock the linked `worktree` so it can't be moved, removed or pruned, with an optional `reason`.
+pub fn lock(repo: gix::Repository, worktree: &Path, reason: Option<&str>) -> anyhow::Result<()> {
+    let proxy = find_linked_worktree(&repo, worktree, "locked or unlocked")?;
+    if let Some(existing) = proxy.lock_reason() {
+        if existing.is_empty() {
+            bail!("'{}' is already locked", worktree.display());
+        }
+        bail!("'{}' is already locked, reason: {existing}", worktree.display());
+    }
+    write_lock(proxy.git_dir(), reason)
+}
+
+/// Unlock the linked `worktree`.
+pub fn unlock(repo: gix::Repository, worktree: &Path) -> anyhow::Result<()> {
+    let proxy = find_linked_worktree(&repo, worktree, "locked or unlocked")?;
+    if !proxy.is_locked() {
+        bail!("'{}' is not locked", worktree.display());
+    }
+    std::fs::remove_file(proxy.git_dir().join("locked"))?;
+    Ok(())
+}
+
+/// Move the linked `worktree` to `new_path`, or into it if it's an existing directory.
+pub fn move_to(repo: gix::Repository, worktree: &Path, new_path: &Path) -> anyhow::Result<()> {
+    let proxy = find_linked_worktree(&repo, worktree, "moved")?;
+    bail_if_locked(&proxy, "move")?;
+    let base = proxy.base()?;
+    let destination = if new_path.is_dir() {
+        new_path.join(base.file_name().ok_or_else(|| anyhow!("worktree has no name"))?)
+    } else {
+        new_path.to_owned()
+    };
+    if destination.exists() {
+        bail!("target '{}' already exists", destination.display());
+    }
+    std::fs::rename(&base, &destination)
+        .with_context(|| format!("failed to move '{}' to '{}'", base.display(), destination.display()))?;
+    let destination = gix::path::realpath(destination)?;
+    std::fs::write(
+        proxy.git_dir().join("gitdir"),
+        format!("{}\n", destination.join(gix::discover::DOT_GIT_DIR).display()),
+    )?;
+    Ok(())
+}
+
+/// Remove the linked `worktree` along with its administrative files.
+///
+/// If it has local modifications or untracked files, `force` needs to be at least 1, and if it's locked, it needs to be 2.
+pub fn remove(repo: gix::Repository, worktree: &Path, force: u8) -> anyhow::Result<()> {
+    let proxy = find_linked_worktree(&repo, worktree, "removed")?;
+    if force < 2 {
+        bail_if_locked(&proxy, "remove")?;
+    }
+    let base = proxy.base()?;
+    let private_git_dir = proxy.git_dir().to_owned();
+    if base.is_dir() {
+        if force == 0 && has_changes(&proxy.into_repo()?)? {
+            bail!(
+                "'{}' contains modified or untracked files, use --force to delete it",
+                worktree.display()
+            );
+        }
+        std::fs::remove_dir_all(&base).with_context(|| format!("failed to delete '{}'", base.display()))?;
+    }
+    std::fs::remove_dir_all(&private_git_dir)
+        .with_context(|| format!("failed to delete '{}'", private_git_dir.display()))?;
+    Ok(())
+}
+
+pub mod prune {
+    pub struct Options {
+        /// Only print what would be removed.
+        pub dry_run: bool,
+        /// Print what is removed.
+        pub verbose: bool,
+    }
+}
+
+/// Remove the administrative files of all linked worktrees that aren't locked and whose worktree doesn't exist anymore.
+pub fn prune(
+    repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    prune::Options { dry_run, verbose }: prune::Options,
+) -> anyhow::Result<()> {
+    let worktrees = repo.common_dir().join("worktrees");
+    let mut private_git_dirs = match std::fs::read_dir(&worktrees) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    private_git_dirs.sort();
+    for private_git_dir in private_git_dirs {
+        if !private_git_dir.is_dir() || private_git_dir.join("locked").is_file() {
+            continue;
+        }
+        let gitdir = private_git_dir.join("gitdir");
+        let reason = match gix::discover::path::from_plain_file(&gitdir) {
+            None => "gitdir file does not exist",
+            Some(Err(_)) => "unable to read gitdir file",
+            Some(Ok(dot_git)) if !dot_git.exists() => "gitdir file points to non-existent location",
+            Some(Ok(_)) => continue,
+        };
+        if verbose || dry_run {
+            let id = private_git_dir.file_name().expect("worktree dirs have a name");
+            writeln!(out, "Removing worktrees/{}: {reason}", Path::new(id).display())?;
+        }
+        if !dry_run {
+            std::fs::remove_dir_all(&private_git_dir)?;
+        }
+    }
+    if !dry_run && std::fs::remove_dir(&worktrees).is_err() {
+        // It's fine for other worktrees to remain.
+    }
+    Ok(())
+}
+
+fn branch_name(name: &str) -> anyhow::Result<FullName> {
+    format!("refs/heads/{name}")
+        .try_into()
+        .with_context(|| format!("'{name}' is not a valid branch name"))
+}
+
+fn write_lock(private_git_dir: &Path, reason: Option<&str>) -> anyhow::Result<()> {
+    let reason = reason.map(|reason| format!("{reason}\n")).unwrap_or_default();
+    std::fs::write(private_git_dir.join("locked"), reason)?;
+    Ok(())
+}
+
+fn bail_if_locked(proxy: &Proxy<'_>, operation: &str) -> anyhow::Result<()> {
+    match proxy.lock_reason() {
+        None => Ok(()),
+        Some(reason) if reason.is_empty() => bail!("cannot {operation} a locked working tree"),
+        Some(reason) => bail!("cannot {operation} a locked working tree, lock reason: {reason}"),
+    }
+}
+
+/// Find the linked worktree identified by `worktree`, which is either its path or its name.
+fn find_linked_worktree<'repo>(
+    repo: &'repo gix::Repository,
+    worktree: &Path,
+    operation: &str,
+) -> anyhow::Result<Proxy<'repo>> {
+    let wanted = gix::path::realpath(worktree).ok();
+    let main = repo.main_repo()?;
+    if let Some(main_base) = main.work_dir() {
+        if wanted.is_some() && wanted == gix::path::realpath(main_base).ok() {
+            bail!(
+                "'{}' is a main working tree and cannot be {operation}",
+                worktree.display()
+            );
+        }
+    }
+    for proxy in repo.worktrees()? {
+        let matches_path = wanted.is_some() && proxy.base().ok() == wanted;
+        if matches_path || gix::path::os_str_into_bstr(worktree.as_os_str()).ok() == Some(proxy.id()) {
+            return Ok(proxy);
+        }
+    }
+    bail!("'{}' is not a working tree", worktree.display())
+}
+
+/// Return the base of the worktree that has `branch` checked out, if there is one.
+fn worktree_with_branch_checked_out(repo: &gix::Repository, branch: &FullName) -> anyhow::Result<Option<PathBuf>> {
+    let main = repo.main_repo()?;
+    if let Some(base) = main.work_dir() {
+        if main.head_name()?.as_ref() == Some(branch) {
+            return Ok(Some(gix::path::realpath(base)?));
+        }
+    }
+    for proxy in repo.worktrees()? {
+        let base = proxy.base()?;
+        if proxy
+            .into_repo_with_possibly_inaccessible_worktree()?
+            .head_name()?
+            .as_ref()
+            == Some(branch)
+        {
+            return Ok(Some(base));
+        }
+    }
+    Ok(None)
+}
+
+/// Return `true` if the index differs from `HEAD`, or if the worktree differs from the index or has untracked files.
+fn has_changes(repo: &gix::Repository) -> anyhow::Result<bool> {
+    let index = repo.index_or_empty()?;
+    let head_index = match repo.head()?.id() {
+        Some(id) => repo.index_from_tree(&id.object()?.peel_to_tree()?.id)?.into(),
+        None => gix::index::State::new(repo.object_hash()),
+    };
+    let entries = |index: &gix::index::State| {
+        index
+            .entries()
+            .iter()
+            .map(|entry| (entry.path(index).to_owned(), entry.id, entry.mode))
+            .collect::<Vec<_>>()
+    };
+    if entries(&index) != entries(&head_index) {
+        return Ok(true);
+    }
+    let has_worktree_changes = repo
+        .status(gix::progress::Discard)?
+        .index_worktree_rewrites(None)
+        .untracked_files(gix::status::UntrackedFiles::Files)
+        .into_index_worktree_iter(Vec::new())?
+        .next()
+        .is_some();
+    Ok(has_worktree_changes)
+}
@@ -0,0 +1,373 @@
+//! Serve repositories over the smart HTTP protocol like `git http-backend` does.
+//!
+//! [`handle()`] doesn't depend on any particular HTTP implementation, which makes it easy to embed into web frameworks,
+//! while [`tcp()`] is a minimal HTTP/1.1 server built on top of it.
+use std::{
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    net::TcpStream,
+    sync::atomic::AtomicBool,
+};
+
+use anyhow::{anyhow, bail, Context};
+use gix::{protocol::transport::packetline, NestedProgress, Progress};
+
+use super::{open_repository, parse_version, receive_pack, upload_pack, Options, Service};
+
+/// The address to listen on if none is given.
+pub const DEFAULT_LISTEN_ADDRESS: &str = "0.0.0.0:8080";
+
+/// An HTTP request as far as the smart HTTP protocol is concerned.
+pub struct Request<'a, R> {
+    /// The method of the request, like `GET` or `POST`.
+    pub method: &'a str,
+    /// The percent-decoded path of the request relative to the location repositories are served from,
+    /// like `/project.git/info/refs`.
+    pub path: &'a str,
+    /// The query of the request without the leading `?`, like `service=git-upload-pack`.
+    pub query: Option<&'a str>,
+    /// The value of the `Content-Encoding` header, if present.
+    pub content_encoding: Option<&'a str>,
+    /// The value of the `Git-Protocol` header, like `version=2`, if present.
+    pub git_protocol: Option<&'a str>,
+    /// The body of the request, with its transfer encoding already removed.
+    pub body: R,
+}
+
+/// The head of the response to a [`Request`], with the body being written separately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code, like `200`.
+    pub status: u16,
+    /// The headers to send, as pairs of name and value.
+    pub headers: Vec<(&'static str, String)>,
+}
+
+/// The endpoints of the smart HTTP protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endpoint {
+    /// `GET <repo>/info/refs?service=<service>`, which advertises references and capabilities.
+    InfoRefs,
+    /// `POST <repo>/<service>`, which runs the service without advertisement.
+    Service,
+}
+
+/// Handle `request` by serving the repository it refers to below [`Options::base_path`], and write the response
+/// to the writer returned by `respond`, which is called with the response head before anything is written.
+///
+/// Only the smart HTTP protocol is supported, with `GET <repo>/info/refs?service=<service>` advertising references and
+/// `POST <repo>/<service>` running `git-upload-pack` or `git-receive-pack`, the latter only if enabled in `options`.
+/// Request bodies may be compressed with `gzip`, and the protocol version is taken from the `Git-Protocol` header.
+/// Errors are returned after responding with an appropriate status, or in-band if the response was already started.
+pub fn handle<W: Write>(
+    request: Request<'_, impl Read>,
+    respond: impl FnOnce(Response) -> std::io::Result<W>,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let (endpoint, service, path) = match route(&request) {
+        Ok(route) => route,
+        Err((status, message)) => return respond_with_error(respond, status, message),
+    };
+    let version = match parse_version(
+        request
+            .git_protocol
+            .into_iter()
+            .flat_map(|params| params.split(':'))
+            .map(str::as_bytes),
+    ) {
+        Ok(version) => version,
+        Err(err) => return respond_with_error(respond, 400, err.to_string()),
+    };
+    let git_request = super::Request {
+        service,
+        path: path.into(),
+        version,
+    };
+    progress.info(format!("{} {} {}", request.method, service.as_str(), git_request.path));
+    if service == Service::ReceivePack && !options.enable_receive_pack {
+        return respond_with_error(respond, 403, format!("The '{}' service is disabled", service.as_str()));
+    }
+    let repo = match open_repository(&git_request, options) {
+        Ok(repo) => repo,
+        Err(err) => {
+            progress.fail(format!("{err:#}"));
+            // Don't reveal more than `git http-backend` would.
+            return respond_with_error(respond, 404, "Repository not found".into());
+        }
+    };
+
+    let announce_version = version == 1;
+    match endpoint {
+        Endpoint::InfoRefs => {
+            let mut out = respond(ok(format!("application/x-{}-advertisement", service.as_str())))?;
+            match service {
+                Service::UploadPack if version == 2 => upload_pack::advertise_v2(&repo, &mut out),
+                _ => {
+                    packetline::encode::text_to_write(format!("# service={}", service.as_str()).as_bytes(), &mut out)?;
+                    packetline::encode::flush_to_write(&mut out)?;
+                    match service {
+                        Service::UploadPack => upload_pack::advertise_v1(&repo, announce_version, &mut out),
+                        Service::ReceivePack => receive_pack::advertise(&repo, announce_version, &mut out),
+                    }
+                }
+            }
+        }
+        Endpoint::Service => {
+            let body: Box<dyn Read + '_> = match request.content_encoding {
+                None | Some("identity") => Box::new(request.body),
+                Some("gzip" | "x-gzip") => Box::new(flate2::read::GzDecoder::new(request.body)),
+                Some(encoding) => {
+                    return respond_with_error(respond, 415, format!("Unsupported content encoding '{encoding}'"))
+                }
+            };
+            let read = BufReader::new(body);
+            let out = respond(ok(format!("application/x-{}-result", service.as_str())))?;
+            match service {
+                Service::UploadPack if version == 2 => {
+                    upload_pack::handle_commands_v2(&repo, read, out, should_interrupt)
+                }
+                Service::UploadPack => upload_pack::negotiate_v1(&repo, read, out, true, should_interrupt),
+                Service::ReceivePack => receive_pack::receive(&repo, read, out, progress, should_interrupt),
+            }
+        }
+    }
+}
+
+/// Determine the endpoint, service and repository path of `request`, or the status and message to respond with.
+fn route<'a, R>(request: &Request<'a, R>) -> Result<(Endpoint, Service, &'a str), (u16, String)> {
+    if let Some(path) = request.path.strip_suffix("/info/refs") {
+        if request.method != "GET" {
+            return Err((405, format!("Method '{}' isn't allowed here", request.method)));
+        }
+        let service = request
+            .query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .find_map(|param| param.strip_prefix("service="))
+            .ok_or_else(|| (403, "The dumb HTTP protocol isn't supported".to_string()))?;
+        let service =
+            Service::from_bytes(service.as_bytes()).ok_or_else(|| (403, format!("Unsupported service '{service}'")))?;
+        return Ok((Endpoint::InfoRefs, service, path));
+    }
+    let (path, service) = request
+        .path
+        .rsplit_once('/')
+        .and_then(|(path, service)| Some((path, Service::from_bytes(service.as_bytes())?)))
+        .ok_or_else(|| (404, "Not found".to_string()))?;
+    if request.method != "POST" {
+        return Err((405, format!("Method '{}' isn't allowed here", request.method)));
+    }
+    Ok((Endpoint::Service, service, path))
+}
+
+/// The head of a successful response with a body of `content_type`, which must not be cached.
+fn ok(content_type: String) -> Response {
+    Response {
+        status: 200,
+        headers: vec![
+            ("Content-Type", content_type),
+            ("Cache-Control", "no-cache, max-age=0, must-revalidate".into()),
+            ("Pragma", "no-cache".into()),
+            ("Expires", "Fri, 01 Jan 1980 00:00:00 GMT".into()),
+        ],
+    }
+}
+
+/// Respond with `status` and `message` as plain text, and return `message` as error.
+fn respond_with_error<W: Write>(
+    respond: impl FnOnce(Response) -> std::io::Result<W>,
+    status: u16,
+    message: String,
+) -> anyhow::Result<()> {
+    let mut out = respond(Response {
+        status,
+        headers: vec![("Content-Type", "text/plain; charset=utf-8".into())],
+    })?;
+    writeln!(out, "{message}")?;
+    out.flush()?;
+    Err(anyhow!(message))
+}
+
+/// Accept HTTP connections on `address` until interrupted, and serve a single request on each of them on its own thread.
+pub fn tcp<P>(address: &str, progress: P, should_interrupt: &'static AtomicBool, options: Options) -> anyhow::Result<()>
+where
+    P: NestedProgress,
+    P::SubProgress: 'static,
+    <P::SubProgress as NestedProgress>::SubProgress: 'static,
+{
+    super::listen(address, progress, should_interrupt, options, serve_connection)
+}
+
+/// Read an HTTP/1.1 request from `stream`, handle it and close the connection.
+fn serve_connection(
+    stream: TcpStream,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let mut read = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    read.read_line(&mut line)?;
+    let mut request_line = line.trim_end().splitn(3, ' ');
+    let (Some(method), Some(target), Some(_version)) = (request_line.next(), request_line.next(), request_line.next())
+    else {
+        bail!("Malformed request line: '{}'", line.trim_end());
+    };
+    let (method, target) = (method.to_owned(), target.to_owned());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if read.read_line(&mut line)? == 0 {
+            bail!("Connection closed while reading headers");
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Malformed header: '{header}'"))?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+    }
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, value)| value.as_str());
+
+    let mut out = BufWriter::new(&stream);
+    if header("expect").is_some_and(|expect| expect.eq_ignore_ascii_case("100-continue")) {
+        out.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+        out.flush()?;
+    }
+    let body: Box<dyn Read> =
+        if header("transfer-encoding").is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked")) {
+            Box::new(Chunked {
+                inner: read,
+                remaining: 0,
+                state: ChunkState::First,
+            })
+        } else {
+            let len = header("content-length")
+                .map(|len| len.parse::<u64>().context("Invalid Content-Length"))
+                .transpose()?
+                .unwrap_or(0);
+            Box::new(read.take(len))
+        };
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target.as_str(), None),
+    };
+    let path = percent_decode(path)?;
+    let out = &mut out;
+    handle(
+        Request {
+            method: &method,
+            path: &path,
+            query,
+            content_encoding: header("content-encoding"),
+            git_protocol: header("git-protocol"),
+            body,
+        },
+        move |response| {
+            write!(out, "HTTP/1.1 {} {}\r\n", response.status, reason(response.status))?;
+            for (name, value) in &response.headers {
+                write!(out, "{name}: {value}\r\n")?;
+            }
+            // Without a length, the end of the body is signalled by closing the connection.
+            out.write_all(b"Connection: close\r\n\r\n")?;
+            Ok(out)
+        },
+        progress,
+        should_interrupt,
+        options,
+    )
+}
+
+/// Decode all `%XX` escapes in `path`.
+fn percent_decode(path: &str) -> anyhow::Result<String> {
+    let mut out = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next().unwrap_or_default(), bytes.next().unwrap_or_default()];
+            let decoded = std::str::from_utf8(&hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| anyhow!("Invalid escape in path '{path}'"))?;
+            out.push(decoded);
+        } else {
+            out.push(byte);
+        }
+    }
+    String::from_utf8(out).map_err(|_| anyhow!("Path '{path}' isn't valid UTF-8"))
+}
+
+/// The reason phrase for the status codes we use.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        415 => "Unsupported Media Type",
+        _ => "Unknown",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkState {
+    /// The size of the first chunk is next.
+    First,
+    /// The data of a chunk is next, or the line ending after it and the size of the next chunk if it was fully read.
+    Data,
+    /// The last chunk was read.
+    Done,
+}
+
+/// A reader for bodies with `chunked` transfer encoding.
+struct Chunked<R> {
+    inner: R,
+    /// The amount of bytes left in the current chunk.
+    remaining: u64,
+    state: ChunkState,
+}
+
+impl<R: BufRead> Read for Chunked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 {
+            let mut line = String::new();
+            match self.state {
+                ChunkState::Done => return Ok(0),
+                ChunkState::Data => {
+                    self.inner.read_line(&mut line)?;
+                    line.clear();
+                }
+                ChunkState::First => self.state = ChunkState::Data,
+            }
+            self.inner.read_line(&mut line)?;
+            let size = line.split(';').next().unwrap_or_default().trim();
+            self.remaining = u64::from_str_radix(size, 16).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid chunk size '{size}'"))
+            })?;
+            if self.remaining == 0 {
+                // Skip trailers until the empty line that ends the body.
+                loop {
+                    line.clear();
+                    if self.inner.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                        break;
+                    }
+                }
+                self.state = ChunkState::Done;
+                return Ok(0);
+            }
+        }
+        let len = self.remaining.min(buf.len() as u64) as usize;
+        let read = self.inner.read(&mut buf[..len])?;
+        if read == 0 && len != 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
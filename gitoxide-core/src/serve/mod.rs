@@ -0,0 +1,395 @@
+//! Serve repositories to `git` clients like `git daemon` does, either over TCP or standard input and output,
+//! or over the smart HTTP protocol like `git http-backend` does.
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context};
+use gix::{
+    bstr::{BStr, BString, ByteSlice},
+    prelude::ReferenceExt,
+    protocol::transport::packetline::{self, PacketLineRef, StreamingPeekableIter},
+    NestedProgress, ObjectId, Progress,
+};
+
+pub mod http;
+mod proc_receive;
+mod receive_pack;
+pub(crate) mod upload_pack;
+
+/// The address to listen on if none is given, which uses the default port of the `git://` protocol.
+pub const DEFAULT_LISTEN_ADDRESS: &str = "0.0.0.0:9418";
+
+/// The name of the file that has to exist in a repository for it to be served, unless [`Options::export_all`] is set.
+pub const EXPORT_OK_FILE: &str = "git-daemon-export-ok";
+
+pub struct Options {
+    /// The directory in which to look for repositories, with all requested paths being relative to it.
+    pub base_path: PathBuf,
+    /// If `true`, serve all repositories, not only those containing a `git-daemon-export-ok` file.
+    pub export_all: bool,
+    /// If `true`, allow clients to push to the repositories we serve.
+    pub enable_receive_pack: bool,
+}
+
+/// Accept connections on `address` until interrupted, and serve each of them on its own thread.
+pub fn tcp<P>(address: &str, progress: P, should_interrupt: &'static AtomicBool, options: Options) -> anyhow::Result<()>
+where
+    P: NestedProgress,
+    P::SubProgress: 'static,
+    <P::SubProgress as NestedProgress>::SubProgress: 'static,
+{
+    listen(
+        address,
+        progress,
+        should_interrupt,
+        options,
+        |stream, progress, should_interrupt, options| {
+            let read = stream.try_clone()?;
+            serve_request(BufReader::new(read), &stream, progress, should_interrupt, options)
+        },
+    )
+}
+
+/// Accept connections on `address` until interrupted, and pass each of them to `serve_connection` on its own thread.
+fn listen<P>(
+    address: &str,
+    mut progress: P,
+    should_interrupt: &'static AtomicBool,
+    options: Options,
+    serve_connection: fn(TcpStream, &mut dyn gix::DynNestedProgress, &AtomicBool, &Options) -> anyhow::Result<()>,
+) -> anyhow::Result<()>
+where
+    P: NestedProgress,
+    P::SubProgress: 'static,
+    <P::SubProgress as NestedProgress>::SubProgress: 'static,
+{
+    let listener = TcpListener::bind(address).with_context(|| format!("Could not listen on '{address}'"))?;
+    // Poll so we can notice being interrupted.
+    listener.set_nonblocking(true)?;
+    progress.info(format!("Listening on {}", listener.local_addr()?));
+    let options = Arc::new(options);
+    while !should_interrupt.load(Ordering::Relaxed) {
+        let (stream, peer) = match listener.accept() {
+            Ok(connection) => connection,
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        stream.set_nonblocking(false)?;
+        let mut progress = progress.add_child(peer.to_string());
+        let options = Arc::clone(&options);
+        std::thread::spawn(
+            move || match serve_connection(stream, &mut progress, should_interrupt, &options) {
+                Ok(()) => progress.done("connection closed".into()),
+                Err(err) => progress.fail(format!("{err:#}")),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Serve a single connection over standard input and `out`, like `git daemon --inetd`.
+///
+/// If `SSH_ORIGINAL_COMMAND` is set, like it is for forced commands of `ssh`, the service and repository are taken
+/// from it instead of being read from standard input, and the protocol version is taken from `GIT_PROTOCOL`.
+pub fn stdio<P>(out: impl Write, mut progress: P, should_interrupt: &AtomicBool, options: Options) -> anyhow::Result<()>
+where
+    P: NestedProgress + 'static,
+    P::SubProgress: 'static,
+{
+    let stdin = std::io::stdin().lock();
+    match std::env::var_os("SSH_ORIGINAL_COMMAND") {
+        Some(command) => {
+            let request = Request::from_ssh_command(
+                gix::path::os_str_into_bstr(&command)?,
+                std::env::var_os("GIT_PROTOCOL")
+                    .as_deref()
+                    .map(gix::path::os_str_into_bstr)
+                    .transpose()?,
+            )?;
+            serve(request, stdin, out, &mut progress, should_interrupt, &options)
+        }
+        None => serve_request(stdin, out, &mut progress, should_interrupt, &options),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    UploadPack,
+    ReceivePack,
+}
+
+impl Service {
+    fn from_bytes(name: &[u8]) -> Option<Self> {
+        Some(match name {
+            b"git-upload-pack" => Service::UploadPack,
+            b"git-receive-pack" => Service::ReceivePack,
+            _ => return None,
+        })
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Service::UploadPack => "git-upload-pack",
+            Service::ReceivePack => "git-receive-pack",
+        }
+    }
+}
+
+struct Request {
+    service: Service,
+    path: BString,
+    /// The protocol version requested by the client, with `0` if none was requested.
+    version: u8,
+}
+
+impl Request {
+    /// Parse the initial packet line of the `git://` protocol, like `git-upload-pack /project.git\0host=example.com\0\0version=2\0`.
+    fn from_daemon_line(line: &BStr) -> anyhow::Result<Self> {
+        let mut fields = line.split_str(b"\0");
+        let command = fields.next().unwrap_or_default().trim_end();
+        let (service, path) = command
+            .split_once_str(b" ")
+            .ok_or_else(|| anyhow!("Malformed request: '{}'", command.as_bstr()))?;
+        Ok(Request {
+            service: Service::from_bytes(service).ok_or_else(|| anyhow!("Unknown service: '{}'", service.as_bstr()))?,
+            path: path.into(),
+            version: parse_version(fields)?,
+        })
+    }
+
+    /// Parse a command like `git-upload-pack '/project.git'`, as sent by `git` over `ssh`.
+    fn from_ssh_command(command: &BStr, git_protocol: Option<&BStr>) -> anyhow::Result<Self> {
+        let (service, path) = command
+            .split_once_str(b" ")
+            .ok_or_else(|| anyhow!("Malformed command: '{command}'"))?;
+        let service = Service::from_bytes(service).ok_or_else(|| anyhow!("Refusing to run '{command}'"))?;
+        let path = path.trim();
+        let path = match path.strip_prefix(b"'").and_then(|path| path.strip_suffix(b"'")) {
+            Some(quoted) => quoted.replace(b"'\\''", b"'").into(),
+            None => path.into(),
+        };
+        Ok(Request {
+            service,
+            path,
+            version: parse_version(git_protocol.into_iter().flat_map(|params| params.split_str(b":")))?,
+        })
+    }
+}
+
+/// Find the last `version=<n>` among the extra parameters `fields`, or return `0` if there is none.
+fn parse_version<'a>(fields: impl Iterator<Item = &'a [u8]>) -> anyhow::Result<u8> {
+    fields
+        .filter_map(|field| field.strip_prefix(b"version="))
+        .last()
+        .map_or(Ok(0), |version| match version {
+            b"0" => Ok(0),
+            b"1" => Ok(1),
+            b"2" => Ok(2),
+            _ => Err(anyhow!("Unsupported protocol version '{}'", version.as_bstr())),
+        })
+}
+
+/// Read the request of a `git://` connection from `read` and serve it.
+fn serve_request(
+    mut read: impl BufRead,
+    mut out: impl Write,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let request = {
+        let mut lines = StreamingPeekableIter::new(&mut read, &[PacketLineRef::Flush], false);
+        let line = lines
+            .read_line()
+            .ok_or_else(|| anyhow!("Connection closed before receiving a request"))???;
+        match line.as_text() {
+            Some(text) => Request::from_daemon_line(text.as_bstr()),
+            None => Err(anyhow!("Expected the request as first line, got {line:?}")),
+        }
+    };
+    match request {
+        Ok(request) => serve(request, read, out, progress, should_interrupt, options),
+        Err(err) => {
+            packetline::encode::error_to_write(err.to_string().as_bytes(), &mut out)?;
+            Err(err)
+        }
+    }
+}
+
+fn serve(
+    request: Request,
+    read: impl BufRead,
+    mut out: impl Write,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+    options: &Options,
+) -> anyhow::Result<()> {
+    progress.info(format!("{} {}", request.service.as_str(), request.path));
+    let repo = match open_repository(&request, options) {
+        Ok(repo) => repo,
+        Err(err) => {
+            // Let the client know, but don't reveal more than `git daemon` would.
+            packetline::encode::error_to_write(
+                format!("access denied or repository not exported: {}", request.path).as_bytes(),
+                &mut out,
+            )?;
+            out.flush()?;
+            return Err(err);
+        }
+    };
+    match request.service {
+        Service::UploadPack if request.version == 2 => upload_pack::serve_v2(&repo, read, out, should_interrupt),
+        Service::UploadPack => upload_pack::serve_v1(&repo, read, out, request.version == 1, should_interrupt),
+        Service::ReceivePack => receive_pack::serve(&repo, read, out, request.version == 1, progress, should_interrupt),
+    }
+}
+
+/// Open the repository requested by `request` below our base path, applying our export policy.
+fn open_repository(request: &Request, options: &Options) -> anyhow::Result<gix::Repository> {
+    if request.service == Service::ReceivePack && !options.enable_receive_pack {
+        bail!("The '{}' service is disabled", request.service.as_str());
+    }
+    let path = gix::path::try_from_bstr(request.path.as_bstr())?;
+    let path = path.strip_prefix("/").unwrap_or(&path);
+    if path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        bail!("Refusing to serve non-normalized path '{}'", request.path);
+    }
+    // Try the same locations as `git daemon`.
+    let path = options.base_path.join(path);
+    let git_dir = ["/.git", "", ".git/.git", ".git"]
+        .iter()
+        .map(|suffix| {
+            let mut candidate = path.clone().into_os_string();
+            candidate.push(suffix);
+            PathBuf::from(candidate)
+        })
+        .find(|candidate| candidate.is_dir())
+        .ok_or_else(|| anyhow!("No repository at '{}'", path.display()))?;
+    let repo = gix::open(&git_dir)?;
+    if !options.export_all && !is_exported(repo.git_dir()) {
+        bail!("Repository at '{}' is not exported", git_dir.display());
+    }
+    Ok(repo)
+}
+
+fn is_exported(git_dir: &Path) -> bool {
+    git_dir.join(EXPORT_OK_FILE).is_file()
+}
+
+/// A reference as advertised to clients.
+struct Ref {
+    name: BString,
+    /// The object the reference points to, or `None` if it is unborn.
+    id: Option<ObjectId>,
+    /// The object at the end of the chain of tags `id` points to, if it is a tag.
+    peeled: Option<ObjectId>,
+    /// The name of the reference a symbolic reference points to.
+    symref_target: Option<BString>,
+}
+
+/// Collect all references of `repo` in the order we advertise them, starting with `HEAD` if `include_head` is set.
+fn refs(repo: &gix::Repository, include_head: bool) -> anyhow::Result<Vec<Ref>> {
+    let mut out = Vec::new();
+    if include_head {
+        let (id, symref_target) = match repo.head()?.kind {
+            gix::head::Kind::Symbolic(reference) => {
+                let target = reference.name.as_bstr().to_owned();
+                (Some(reference.attach(repo).follow_to_object()?.detach()), Some(target))
+            }
+            gix::head::Kind::Unborn(name) => (None, Some(name.as_bstr().to_owned())),
+            gix::head::Kind::Detached { target, .. } => (Some(target), None),
+        };
+        out.push(Ref {
+            name: "HEAD".into(),
+            id,
+            peeled: id.map(|id| peel_tags(repo, id)).transpose()?.flatten(),
+            symref_target,
+        });
+    }
+    for reference in repo.references()?.all()? {
+        let mut reference = reference.map_err(|err| anyhow!("{err}"))?;
+        let symref_target = reference.target().try_name().map(|name| name.as_bstr().to_owned());
+        let id = reference.follow_to_object()?.detach();
+        out.push(Ref {
+            name: reference.name().as_bstr().to_owned(),
+            id: Some(id),
+            peeled: peel_tags(repo, id)?,
+            symref_target,
+        });
+    }
+    Ok(out)
+}
+
+/// Return the object `id` ultimately points to if it is a tag, or `None` otherwise.
+fn peel_tags(repo: &gix::Repository, id: ObjectId) -> anyhow::Result<Option<ObjectId>> {
+    let object = repo.find_object(id)?;
+    Ok(if object.kind == gix::object::Kind::Tag {
+        Some(object.peel_tags_to_end()?.id)
+    } else {
+        None
+    })
+}
+
+/// Write the reference advertisement of protocol V0 and V1 with `capabilities` on the first line, and the
+/// peeled value of tags if `with_peeled` is set.
+fn advertise_refs(
+    refs: &[Ref],
+    capabilities: &str,
+    announce_version: bool,
+    with_peeled: bool,
+    object_hash: gix::hash::Kind,
+    out: impl Write,
+) -> std::io::Result<()> {
+    let mut lines = Vec::with_capacity(refs.len() + 1);
+    let mut capabilities = Some(capabilities);
+    for r in refs {
+        let Some(id) = r.id else { continue };
+        let mut line = format!("{id} {}", r.name);
+        if let Some(capabilities) = capabilities.take() {
+            line.push('\0');
+            line.push_str(capabilities);
+        }
+        lines.push(line);
+        if let Some(peeled) = r.peeled.filter(|_| with_peeled) {
+            lines.push(format!("{peeled} {}^{{}}", r.name));
+        }
+    }
+    if let Some(capabilities) = capabilities {
+        lines.push(format!("{} capabilities^{{}}\0{capabilities}", object_hash.null()));
+    }
+
+    // With many references, writing all lines at once is much faster than writing them one by one.
+    let mut out = packetline::VectoredWriter::new(out);
+    if announce_version {
+        out.text(b"version 1")?;
+    }
+    for line in &lines {
+        out.text(line.as_bytes())?;
+    }
+    out.flush_line()?;
+    out.flush()
+}
+
+/// The value of the `object-format` capability we advertise, like `sha1`.
+fn object_format(repo: &gix::Repository) -> String {
+    repo.object_hash().to_string().to_ascii_lowercase()
+}
+
+/// The value of the `agent` capability we advertise.
+fn agent() -> String {
+    gix::protocol::agent(gix::env::agent())
+}
@@ -0,0 +1,234 @@
+//! Let the `proc-receive` hook handle pushes to references matching `receive.procReceiveRefs`, like `git receive-pack` does
+//! to implement features like creating merge requests by pushing to `refs/for/<branch>`.
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::{anyhow, bail};
+use gix::{
+    bstr::{BStr, BString, ByteSlice},
+    protocol::transport::packetline::{self, PacketLineRef, StreamingPeekableIter},
+    ObjectId,
+};
+
+/// A `receive.procReceiveRefs` entry like `ad:refs/heads` or `!:refs/heads`, which determines which commands are
+/// handled by the `proc-receive` hook.
+pub(super) struct RefPattern {
+    prefix: BString,
+    create: bool,
+    modify: bool,
+    delete: bool,
+    negative: bool,
+}
+
+impl RefPattern {
+    /// Parse all `receive.procReceiveRefs` values in the configuration of `repo`.
+    pub(super) fn from_config(repo: &gix::Repository) -> Vec<RefPattern> {
+        repo.config_snapshot()
+            .plumbing()
+            .strings("receive.procReceiveRefs")
+            .unwrap_or_default()
+            .iter()
+            .map(|value| RefPattern::parse(value.as_bstr()))
+            .collect()
+    }
+
+    fn parse(value: &BStr) -> Self {
+        let (modifiers, prefix) = value.split_once_str(b":").unwrap_or((b"", value));
+        let any_action = !modifiers.iter().any(|modifier| b"amd".contains(modifier));
+        RefPattern {
+            prefix: prefix.trim_end_with(|c| c == '/').into(),
+            create: any_action || modifiers.contains(&b'a'),
+            modify: any_action || modifiers.contains(&b'm'),
+            delete: any_action || modifiers.contains(&b'd'),
+            negative: modifiers.contains(&b'!'),
+        }
+    }
+}
+
+/// Return `true` if the update of `name` from `old` to `new` should be handled by the hook according to `patterns`,
+/// with the first pattern that applies to the kind of change deciding.
+pub(super) fn matches(patterns: &[RefPattern], name: &BStr, old: &ObjectId, new: &ObjectId) -> bool {
+    patterns
+        .iter()
+        .filter(|pattern| match (old.is_null(), new.is_null()) {
+            (true, _) => pattern.create,
+            (false, true) => pattern.delete,
+            (false, false) => pattern.modify,
+        })
+        .find_map(|pattern| {
+            let is_match = name
+                .strip_prefix(pattern.prefix.as_slice())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(b"/"));
+            (is_match != pattern.negative).then_some(true)
+        })
+        .unwrap_or(false)
+}
+
+/// What the hook reported for a command it handled successfully, which may differ from what the client requested.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct Report {
+    /// The name of the reference that was actually updated.
+    pub refname: Option<BString>,
+    /// The value the reference actually had before.
+    pub old: Option<ObjectId>,
+    /// The value the reference actually has now.
+    pub new: Option<ObjectId>,
+    /// If `true`, the update was forced.
+    pub forced_update: bool,
+}
+
+impl Report {
+    /// Write this report as `option` lines of the `report-status-v2` protocol.
+    pub(super) fn write_options(&self, mut out: impl Write) -> std::io::Result<()> {
+        if let Some(refname) = &self.refname {
+            packetline::encode::text_to_write(format!("option refname {refname}").as_bytes(), &mut out)?;
+        }
+        if let Some(old) = &self.old {
+            packetline::encode::text_to_write(format!("option old-oid {old}").as_bytes(), &mut out)?;
+        }
+        if let Some(new) = &self.new {
+            packetline::encode::text_to_write(format!("option new-oid {new}").as_bytes(), &mut out)?;
+        }
+        if self.forced_update {
+            packetline::encode::text_to_write(b"option forced-update", &mut out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the `proc-receive` hook of `repo` with `commands` of the form `(old, new, name)`, and return the status for each
+/// of them, which is one or more reports if the hook handled the command successfully, or the reason for the failure.
+///
+/// The hook is told that all commands are applied atomically, as that's what we do with all other commands.
+pub(super) fn run(
+    repo: &gix::Repository,
+    commands: &[(ObjectId, ObjectId, &BStr)],
+) -> Vec<Result<Vec<Report>, String>> {
+    let hook = match repo.hook_path("proc-receive") {
+        Ok(Some(hook)) => hook,
+        Ok(None) => {
+            return commands
+                .iter()
+                .map(|_| Err("fail to run proc-receive hook".into()))
+                .collect()
+        }
+        Err(err) => return commands.iter().map(|_| Err(err.to_string())).collect(),
+    };
+    match communicate(repo, &hook, commands) {
+        Ok(mut statuses) => {
+            for status in statuses.iter_mut() {
+                if matches!(status, Ok(reports) if reports.is_empty()) {
+                    *status = Err("proc-receive failed to report status".into());
+                }
+            }
+            statuses
+        }
+        Err(err) => commands.iter().map(|_| Err(format!("{err:#}"))).collect(),
+    }
+}
+
+fn communicate(
+    repo: &gix::Repository,
+    hook: &std::path::Path,
+    commands: &[(ObjectId, ObjectId, &BStr)],
+) -> anyhow::Result<Vec<Result<Vec<Report>, String>>> {
+    let git_dir = gix::path::realpath(repo.git_dir())?;
+    let mut cmd: Command = gix::command::prepare(gix::path::realpath(hook)?)
+        .with_context(gix::command::Context {
+            git_dir: Some(git_dir.clone()),
+            ..Default::default()
+        })
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .into();
+    let mut child = cmd.current_dir(&git_dir).spawn()?;
+    let mut stdin = child.stdin.take().expect("configured");
+    let stdout = child.stdout.take().expect("configured");
+
+    packetline::encode::text_to_write(b"version=1\0atomic", &mut stdin)?;
+    packetline::encode::flush_to_write(&mut stdin)?;
+
+    let mut lines = StreamingPeekableIter::new(std::io::BufReader::new(stdout), &[PacketLineRef::Flush], false);
+    let mut hook_version = None;
+    while let Some(line) = lines.read_line() {
+        let line = line??;
+        let line = line
+            .as_text()
+            .ok_or_else(|| anyhow!("proc-receive sent {line:?} instead of its version"))?
+            .as_bstr();
+        let version = line
+            .split_once_str(b"\0")
+            .map_or(line.as_bytes(), |(version, _caps)| version);
+        hook_version.get_or_insert_with(|| version.to_owned());
+    }
+    match hook_version.as_deref() {
+        Some(b"version=1") => {}
+        Some(version) => bail!("proc-receive uses unsupported protocol '{}'", version.as_bstr()),
+        None => bail!("proc-receive didn't send its protocol version"),
+    }
+
+    for (old, new, name) in commands {
+        packetline::encode::text_to_write(format!("{old} {new} {name}").as_bytes(), &mut stdin)?;
+    }
+    packetline::encode::flush_to_write(&mut stdin)?;
+    stdin.flush()?;
+    drop(stdin);
+
+    let mut statuses: Vec<Result<Vec<Report>, String>> = commands.iter().map(|_| Ok(Vec::new())).collect();
+    let mut last_ok: Option<usize> = None;
+    lines.reset();
+    while let Some(line) = lines.read_line() {
+        let line = line??;
+        let line = line
+            .as_text()
+            .ok_or_else(|| anyhow!("proc-receive sent {line:?} instead of a report"))?
+            .as_bstr();
+        let (kind, rest) = line.split_once_str(b" ").unwrap_or((line, b""));
+        if kind == b"option" {
+            let idx = last_ok.ok_or_else(|| anyhow!("proc-receive sent '{line}' without preceding 'ok'"))?;
+            let Ok(reports) = &mut statuses[idx] else {
+                unreachable!("options are only accepted after 'ok'")
+            };
+            let report = reports.last_mut().expect("'ok' creates a report");
+            let (key, value) = rest.split_once_str(b" ").unwrap_or((rest, b""));
+            match key {
+                b"refname" => report.refname = Some(value.into()),
+                b"old-oid" => report.old = Some(ObjectId::from_hex(value)?),
+                b"new-oid" => report.new = Some(ObjectId::from_hex(value)?),
+                b"forced-update" => report.forced_update = true,
+                _ => bail!("proc-receive sent unknown option '{line}'"),
+            }
+            continue;
+        }
+        let (name, reason) = rest.split_once_str(b" ").unwrap_or((rest, b""));
+        let idx = commands
+            .iter()
+            .position(|(_, _, command_name)| *command_name == name)
+            .ok_or_else(|| anyhow!("proc-receive reported status on unknown ref '{}'", name.as_bstr()))?;
+        last_ok = None;
+        match kind {
+            b"ok" => {
+                if let Ok(reports) = &mut statuses[idx] {
+                    reports.push(Report::default());
+                    last_ok = Some(idx);
+                }
+            }
+            b"ng" => {
+                statuses[idx] = Err(if reason.is_empty() {
+                    "failed".into()
+                } else {
+                    reason.to_str_lossy().into_owned()
+                });
+            }
+            _ => bail!("proc-receive sent malformed report '{line}'"),
+        }
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("proc-receive hook failed with {status}");
+    }
+    Ok(statuses)
+}
@@ -0,0 +1,278 @@
+use std::{
+    io::{BufRead, Read, Write},
+    sync::atomic::AtomicBool,
+};
+
+use anyhow::{anyhow, bail};
+use gix::{
+    bstr::{BString, ByteSlice},
+    protocol::transport::packetline::{self, PacketLineRef, StreamingPeekableIter},
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    },
+    ObjectId, Progress,
+};
+
+use super::proc_receive;
+
+/// The status of a command, which is `Ok` with the reports of the `proc-receive` hook if it handled it, or `Ok` without
+/// reports if it was applied by us.
+type Status = Result<Vec<proc_receive::Report>, String>;
+
+/// A single ref update requested by the client.
+struct Command {
+    old: ObjectId,
+    new: ObjectId,
+    name: FullName,
+}
+
+/// Serve `receive-pack` using protocol V0, or V1 if `announce_version` is set.
+///
+/// All ref updates are applied in a single transaction, so either all of them succeed or none does.
+/// Updates to references matching `receive.procReceiveRefs` are handed to the `proc-receive` hook instead.
+pub(super) fn serve(
+    repo: &gix::Repository,
+    read: impl BufRead,
+    mut out: impl Write,
+    announce_version: bool,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    advertise(repo, announce_version, &mut out)?;
+    receive(repo, read, out, progress, should_interrupt)
+}
+
+/// Advertise our references and capabilities using protocol V0, or V1 if `announce_version` is set.
+pub(super) fn advertise(repo: &gix::Repository, announce_version: bool, out: impl Write) -> anyhow::Result<()> {
+    let capabilities = format!(
+        "report-status report-status-v2 delete-refs atomic ofs-delta object-format={} agent={}",
+        super::object_format(repo),
+        super::agent()
+    );
+    let refs = super::refs(repo, false)?;
+    super::advertise_refs(&refs, &capabilities, announce_version, false, repo.object_hash(), out)?;
+    Ok(())
+}
+
+/// Read the ref updates and the pack sent by the client from `read`, apply them and report their status to `out`.
+pub(super) fn receive(
+    repo: &gix::Repository,
+    mut read: impl BufRead,
+    mut out: impl Write,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let mut commands = Vec::new();
+    let mut report_status = false;
+    let mut report_status_v2 = false;
+    {
+        let mut lines = StreamingPeekableIter::new(&mut read, &[PacketLineRef::Flush], false);
+        while let Some(line) = lines.read_line() {
+            let line = line??;
+            let line = line
+                .as_text()
+                .ok_or_else(|| anyhow!("Expected a command, got {line:?}"))?
+                .as_bstr();
+            let (command, client_capabilities) = line.split_once_str(b"\0").unwrap_or((line, &[]));
+            if commands.is_empty() {
+                for capability in client_capabilities.split_str(b" ") {
+                    match capability {
+                        b"report-status" => report_status = true,
+                        b"report-status-v2" => report_status_v2 = true,
+                        _ => {}
+                    }
+                }
+            }
+            let mut fields = command.splitn_str(3, b" ");
+            let (Some(old), Some(new), Some(name)) = (fields.next(), fields.next(), fields.next()) else {
+                bail!("Malformed command: '{}'", command.as_bstr());
+            };
+            commands.push(Command {
+                old: ObjectId::from_hex(old)?,
+                new: ObjectId::from_hex(new)?,
+                name: name.as_bstr().try_into()?,
+            });
+        }
+        if lines.stopped_at().is_none() || commands.is_empty() {
+            // The client only wanted to see our references.
+            return Ok(());
+        }
+    }
+
+    let unpacked = if commands.iter().any(|command| !command.new.is_null()) {
+        receive_pack(repo, &mut read, progress, should_interrupt)
+    } else {
+        Ok(None)
+    };
+    let statuses: Vec<Status> = match &unpacked {
+        Ok(_) => update_refs(repo, &commands),
+        Err(_) => commands.iter().map(|_| Err("unpacker error".into())).collect(),
+    };
+    if let Ok(Some(keep_path)) = &unpacked {
+        // The pack is now protected by our references, if they could be updated.
+        std::fs::remove_file(keep_path)?;
+    }
+
+    if report_status || report_status_v2 {
+        let unpack_status = match &unpacked {
+            Ok(_) => "unpack ok".to_string(),
+            Err(err) => format!("unpack {err:#}"),
+        };
+        packetline::encode::text_to_write(unpack_status.as_bytes(), &mut out)?;
+        for (command, status) in commands.iter().zip(&statuses) {
+            match status {
+                Ok(reports) if report_status_v2 && !reports.is_empty() => {
+                    for report in reports {
+                        packetline::encode::text_to_write(format!("ok {}", command.name).as_bytes(), &mut out)?;
+                        report.write_options(&mut out)?;
+                    }
+                }
+                Ok(_) => {
+                    packetline::encode::text_to_write(format!("ok {}", command.name).as_bytes(), &mut out)?;
+                }
+                Err(reason) => {
+                    packetline::encode::text_to_write(format!("ng {} {reason}", command.name).as_bytes(), &mut out)?;
+                }
+            }
+        }
+        packetline::encode::flush_to_write(&mut out)?;
+        out.flush()?;
+    }
+    for (command, status) in commands.iter().zip(statuses) {
+        match status {
+            Ok(_) => progress.info(format!("{} {} -> {}", command.name, command.old, command.new)),
+            Err(reason) => progress.fail(format!("{}: {reason}", command.name)),
+        }
+    }
+    unpacked.map(|_| ())
+}
+
+/// Read the pack sent by the client from `read` and write it into the object database of `repo` unless it's larger
+/// than `receive.maxInputSize`, returning the path to the `.keep` file protecting it from being garbage-collected, if one was written.
+fn receive_pack(
+    repo: &gix::Repository,
+    read: &mut dyn BufRead,
+    progress: &mut dyn gix::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<Option<std::path::PathBuf>> {
+    let mut header = [0u8; 12];
+    read.read_exact(&mut header)?;
+    let (_version, num_objects) = gix::odb::pack::data::header::decode(&header)?;
+    if num_objects == 0 {
+        // Clients send empty packs when all objects are known to exist, which we don't want to keep.
+        let mut trailer = vec![0; repo.object_hash().len_in_bytes()];
+        read.read_exact(&mut trailer)?;
+        return Ok(None);
+    }
+    let mut pack = std::io::BufReader::new(std::io::Cursor::new(header).chain(read));
+    let outcome = gix::odb::pack::Bundle::write_to_directory(
+        &mut pack,
+        Some(&repo.objects.store_ref().path().join("pack")),
+        progress,
+        should_interrupt,
+        Some(repo.objects.clone()),
+        gix::odb::pack::bundle::write::Options {
+            object_hash: repo.object_hash(),
+            max_pack_size: repo
+                .config_snapshot()
+                .integer("receive.maxInputSize")
+                .and_then(|limit| u64::try_from(limit).ok())
+                .filter(|limit| *limit != 0),
+            memory_limit: repo
+                .config_snapshot()
+                .integer("gitoxide.objects.resolveMemoryLimit")
+                .and_then(|limit| usize::try_from(limit).ok())
+                .filter(|limit| *limit != 0),
+            ..Default::default()
+        },
+    )?;
+    Ok(outcome.keep_path)
+}
+
+/// Apply `commands` atomically, and return the status of each of them.
+///
+/// Commands matching `receive.procReceiveRefs` are passed to the `proc-receive` hook, and if it fails any of them
+/// the remaining commands aren't applied.
+fn update_refs(repo: &gix::Repository, commands: &[Command]) -> Vec<Status> {
+    let checked_out = repo
+        .head_name()
+        .ok()
+        .flatten()
+        .filter(|_| !repo.is_bare() && repo.worktree().is_some());
+    let mut statuses: Vec<Status> = commands
+        .iter()
+        .map(|command| {
+            if checked_out.as_ref() == Some(&command.name) {
+                Err("branch is currently checked out".into())
+            } else if !command.new.is_null() && !repo.has_object(command.new) {
+                Err("missing necessary objects".into())
+            } else {
+                Ok(Vec::new())
+            }
+        })
+        .collect();
+    if statuses.iter().any(Result::is_err) {
+        return fail_atomically(statuses);
+    }
+
+    let patterns = proc_receive::RefPattern::from_config(repo);
+    let (hook_commands, commands): (Vec<_>, Vec<_>) = commands
+        .iter()
+        .enumerate()
+        .partition(|(_, command)| proc_receive::matches(&patterns, command.name.as_bstr(), &command.old, &command.new));
+    if !hook_commands.is_empty() {
+        let hook_input: Vec<_> = hook_commands
+            .iter()
+            .map(|(_, command)| (command.old, command.new, command.name.as_bstr()))
+            .collect();
+        for ((idx, _), status) in hook_commands.iter().zip(proc_receive::run(repo, &hook_input)) {
+            statuses[*idx] = status;
+        }
+        if statuses.iter().any(Result::is_err) {
+            return fail_atomically(statuses);
+        }
+    }
+
+    let edits = commands.iter().map(|(_, command)| {
+        let expected = if command.old.is_null() {
+            PreviousValue::MustNotExist
+        } else {
+            PreviousValue::MustExistAndMatch(Target::Object(command.old))
+        };
+        RefEdit {
+            change: if command.new.is_null() {
+                Change::Delete {
+                    expected,
+                    log: RefLog::AndReference,
+                }
+            } else {
+                Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: BString::from("push"),
+                    },
+                    expected,
+                    new: Target::Object(command.new),
+                }
+            },
+            name: command.name.clone(),
+            deref: false,
+        }
+    });
+    if let Err(err) = repo.edit_references(edits) {
+        for (idx, _) in &commands {
+            statuses[*idx] = Err(err.to_string());
+        }
+    }
+    statuses
+}
+
+/// Fail all successful commands in `statuses` as some of them failed.
+fn fail_atomically(mut statuses: Vec<Status>) -> Vec<Status> {
+    for status in statuses.iter_mut().filter(|status| status.is_ok()) {
+        *status = Err("atomic push failed".into());
+    }
+    statuses
+}
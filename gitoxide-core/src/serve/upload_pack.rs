@@ -0,0 +1,480 @@
+use std::{
+    io::{BufRead, Write},
+    sync::atomic::AtomicBool,
+};
+
+use anyhow::{anyhow, bail};
+use gix::{
+    bstr::{BStr, BString, ByteSlice},
+    hashtable::HashSet,
+    odb::pack,
+    parallel::InOrderIter,
+    prelude::Finalize,
+    protocol::transport::packetline::{self, Channel, PacketLineRef, StreamingPeekableIter},
+    ObjectId,
+};
+
+/// The most data a single packet line on a side-band can carry.
+const MAX_BAND_DATA_LEN: usize = 65515;
+
+/// Serve `upload-pack` using protocol V0, or V1 if `announce_version` is set, which both use the same negotiation.
+pub(super) fn serve_v1(
+    repo: &gix::Repository,
+    read: impl BufRead,
+    mut out: impl Write,
+    announce_version: bool,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    advertise_v1(repo, announce_version, &mut out)?;
+    negotiate_v1(repo, read, out, false, should_interrupt)
+}
+
+/// Advertise our references and capabilities using protocol V0, or V1 if `announce_version` is set.
+pub(super) fn advertise_v1(repo: &gix::Repository, announce_version: bool, out: impl Write) -> anyhow::Result<()> {
+    let refs = super::refs(repo, true)?;
+    let symref = refs
+        .first()
+        .filter(|head| head.name == "HEAD")
+        .and_then(|head| head.symref_target.as_ref())
+        .map(|target| format!(" symref=HEAD:{target}"))
+        .unwrap_or_default();
+    let capabilities = format!(
+        "side-band-64k ofs-delta include-tag no-progress object-format={} agent={}{symref}",
+        super::object_format(repo),
+        super::agent()
+    );
+    super::advertise_refs(&refs, &capabilities, announce_version, true, repo.object_hash(), out)?;
+    Ok(())
+}
+
+/// Read the objects the client wants and has from `read`, and send the pack with the objects it needs to `out`.
+///
+/// If `stateless` is set, like it is for the smart HTTP protocol, only a single round of negotiation is handled
+/// unless the client is done, as the next round will be sent with a new request.
+/// As we don't support `multi_ack`, the first common object we see ends the negotiation.
+pub(super) fn negotiate_v1(
+    repo: &gix::Repository,
+    mut read: impl BufRead,
+    mut out: impl Write,
+    stateless: bool,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let mut lines = StreamingPeekableIter::new(&mut read, &[PacketLineRef::Flush], false);
+    let mut wants = Vec::new();
+    let mut client_capabilities = BString::default();
+    while let Some(line) = lines.read_line() {
+        let line = text(&line??)?;
+        let Some(want) = line.strip_prefix(b"want ") else {
+            bail!("Unsupported request: '{line}'");
+        };
+        let (id, capabilities) = want.split_once_str(b" ").unwrap_or((want, &[]));
+        if wants.is_empty() {
+            client_capabilities = capabilities.into();
+        }
+        wants.push(ObjectId::from_hex(id)?);
+    }
+    if lines.stopped_at().is_none() || wants.is_empty() {
+        // The client only wanted to see our references.
+        return Ok(());
+    }
+    let has_capability = |name: &str| client_capabilities.split_str(b" ").any(|cap| cap == name.as_bytes());
+
+    let mut common = Vec::new();
+    'negotiation: loop {
+        lines.reset();
+        while let Some(line) = lines.read_line() {
+            let line = text(&line??)?;
+            if line == "done" {
+                break 'negotiation;
+            }
+            let Some(id) = line.strip_prefix(b"have ") else {
+                bail!("Unsupported request during negotiation: '{line}'");
+            };
+            let id = ObjectId::from_hex(id)?;
+            if repo.has_object(id) {
+                if common.is_empty() {
+                    packetline::encode::text_to_write(format!("ACK {id}").as_bytes(), &mut out)?;
+                }
+                common.push(id);
+            }
+        }
+        if lines.stopped_at().is_none() {
+            bail!("Connection closed during negotiation");
+        }
+        if common.is_empty() {
+            packetline::encode::text_to_write(b"NAK", &mut out)?;
+        }
+        out.flush()?;
+        if stateless {
+            return Ok(());
+        }
+    }
+    if common.is_empty() {
+        packetline::encode::text_to_write(b"NAK", &mut out)?;
+    }
+
+    let objects = objects_to_send(repo, &wants, &common, has_capability("include-tag"))?;
+    if has_capability("side-band-64k") || has_capability("side-band") {
+        let mut band = Sideband { out: &mut out };
+        if !has_capability("no-progress") {
+            band.progress(&format!("Counting objects: {}, done.\n", objects.len()))?;
+        }
+        write_pack(repo, objects, &mut band, should_interrupt)?;
+        packetline::encode::flush_to_write(&mut out)?;
+    } else {
+        write_pack(repo, objects, &mut out, should_interrupt)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Serve `upload-pack` using protocol V2, handling commands until the client closes the connection.
+pub(super) fn serve_v2(
+    repo: &gix::Repository,
+    read: impl BufRead,
+    mut out: impl Write,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    advertise_v2(repo, &mut out)?;
+    handle_commands_v2(repo, read, out, should_interrupt)
+}
+
+/// Advertise our capabilities using protocol V2.
+pub(super) fn advertise_v2(repo: &gix::Repository, mut out: impl Write) -> anyhow::Result<()> {
+    for line in [
+        "version 2".to_string(),
+        format!("agent={}", super::agent()),
+        "ls-refs=unborn".into(),
+        "fetch".into(),
+        "server-option".into(),
+        format!("object-format={}", super::object_format(repo)),
+    ] {
+        packetline::encode::text_to_write(line.as_bytes(), &mut out)?;
+    }
+    packetline::encode::flush_to_write(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Handle the commands of protocol V2 read from `read` until the client closes the connection or sends a lone flush packet.
+pub(super) fn handle_commands_v2(
+    repo: &gix::Repository,
+    read: impl BufRead,
+    mut out: impl Write,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let mut lines = StreamingPeekableIter::new(read, &[PacketLineRef::Flush], false);
+    loop {
+        let mut command = None;
+        let mut arguments = Vec::new();
+        let mut in_arguments = false;
+        while let Some(line) = lines.read_line() {
+            let line = match line {
+                // Clients may just close the connection instead of ending the session with a flush packet.
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof && command.is_none() => return Ok(()),
+                line => line??,
+            };
+            match line {
+                PacketLineRef::Delimiter => in_arguments = true,
+                line => {
+                    let line = text(&line)?;
+                    if in_arguments {
+                        arguments.push(line.to_owned());
+                    } else if let Some(name) = line.strip_prefix(b"command=") {
+                        command = Some(name.as_bstr().to_owned());
+                    }
+                }
+            }
+        }
+        if lines.stopped_at().is_none() {
+            return Ok(());
+        }
+        lines.reset();
+        match command.as_ref().map(|name| name.as_bytes()) {
+            // A lone flush packet ends the session.
+            None => return Ok(()),
+            Some(b"ls-refs") => ls_refs(repo, &arguments, &mut out)?,
+            Some(b"fetch") => fetch(repo, &arguments, &mut out, should_interrupt)?,
+            Some(name) => {
+                let message = format!("unknown command '{}'", name.as_bstr());
+                packetline::encode::error_to_write(message.as_bytes(), &mut out)?;
+                bail!(message)
+            }
+        }
+        out.flush()?;
+    }
+}
+
+fn ls_refs(repo: &gix::Repository, arguments: &[BString], out: impl Write) -> anyhow::Result<()> {
+    let has_argument = |name: &str| arguments.iter().any(|arg| arg == name);
+    let (symrefs, peel, unborn) = (has_argument("symrefs"), has_argument("peel"), has_argument("unborn"));
+    let prefixes: Vec<_> = arguments
+        .iter()
+        .filter_map(|arg| arg.strip_prefix(b"ref-prefix "))
+        .collect();
+    let mut lines = Vec::new();
+    for r in super::refs(repo, true)? {
+        if !prefixes.is_empty() && !prefixes.iter().any(|prefix| r.name.starts_with(prefix)) {
+            continue;
+        }
+        let value = match r.id {
+            Some(id) => id.to_string(),
+            None if unborn => "unborn".into(),
+            None => continue,
+        };
+        let symref_target = r
+            .symref_target
+            .filter(|_| symrefs)
+            .map(|target| format!(" symref-target:{target}"))
+            .unwrap_or_default();
+        let peeled = r
+            .peeled
+            .filter(|_| peel)
+            .map(|peeled| format!(" peeled:{peeled}"))
+            .unwrap_or_default();
+        lines.push(format!("{value} {}{symref_target}{peeled}", r.name));
+    }
+
+    let mut out = packetline::VectoredWriter::new(out);
+    for line in &lines {
+        out.text(line.as_bytes())?;
+    }
+    out.flush_line()?;
+    out.write_queued()?;
+    Ok(())
+}
+
+fn fetch(
+    repo: &gix::Repository,
+    arguments: &[BString],
+    mut out: impl Write,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let mut wants = Vec::new();
+    let mut common = Vec::new();
+    let (mut done, mut no_progress, mut include_tag) = (false, false, false);
+    for arg in arguments {
+        if let Some(id) = arg.strip_prefix(b"want ") {
+            wants.push(ObjectId::from_hex(id)?);
+        } else if let Some(id) = arg.strip_prefix(b"have ") {
+            let id = ObjectId::from_hex(id)?;
+            if repo.has_object(id) {
+                common.push(id);
+            }
+        } else {
+            match arg.as_bytes() {
+                b"done" => done = true,
+                b"no-progress" => no_progress = true,
+                b"include-tag" => include_tag = true,
+                b"thin-pack" | b"ofs-delta" => {}
+                _ => {
+                    let message = format!("unsupported fetch argument '{arg}'");
+                    packetline::encode::error_to_write(message.as_bytes(), &mut out)?;
+                    bail!(message)
+                }
+            }
+        }
+    }
+
+    if !done {
+        packetline::encode::text_to_write(b"acknowledgments", &mut out)?;
+        if common.is_empty() {
+            packetline::encode::text_to_write(b"NAK", &mut out)?;
+        }
+        for id in &common {
+            packetline::encode::text_to_write(format!("ACK {id}").as_bytes(), &mut out)?;
+        }
+        if common.is_empty() {
+            // Let the client send more `have` lines.
+            packetline::encode::flush_to_write(&mut out)?;
+            return Ok(());
+        }
+        packetline::encode::text_to_write(b"ready", &mut out)?;
+        packetline::encode::delim_to_write(&mut out)?;
+    }
+
+    let objects = objects_to_send(repo, &wants, &common, include_tag)?;
+    packetline::encode::text_to_write(b"packfile", &mut out)?;
+    let mut band = Sideband { out: &mut out };
+    if !no_progress {
+        band.progress(&format!("Counting objects: {}, done.\n", objects.len()))?;
+    }
+    write_pack(repo, objects, &mut band, should_interrupt)?;
+    packetline::encode::flush_to_write(&mut out)?;
+    Ok(())
+}
+
+/// Return the text of `line`, without its trailing newline.
+fn text<'a>(line: &PacketLineRef<'a>) -> anyhow::Result<&'a BStr> {
+    line.as_text()
+        .map(|text| text.as_bstr())
+        .ok_or_else(|| anyhow!("Expected a line of text, got {line:?}"))
+}
+
+/// Collect all objects reachable from `wants` that aren't reachable from `common`, as far as we can tell cheaply.
+///
+/// We don't traverse the history of `common` objects, so we may send more than strictly needed if they aren't
+/// on the ancestry path of `wants`.
+pub(crate) fn objects_to_send(
+    repo: &gix::Repository,
+    wants: &[ObjectId],
+    common: &[ObjectId],
+    include_tag: bool,
+) -> anyhow::Result<Vec<ObjectId>> {
+    let mut seen = HashSet::default();
+    let mut out = Vec::new();
+    for id in common {
+        if let Ok(commit) = repo.find_commit(*id) {
+            add_tree(repo, commit.tree_id()?.detach(), &mut seen, None)?;
+        }
+    }
+
+    let mut tips = Vec::new();
+    for want in wants {
+        let mut object = repo.find_object(*want)?;
+        loop {
+            match object.kind {
+                gix::object::Kind::Tag => {
+                    if seen.insert(object.id) {
+                        out.push(object.id);
+                    }
+                    object = repo.find_object(object.to_tag_ref().target())?;
+                }
+                gix::object::Kind::Commit => {
+                    tips.push(object.id);
+                    break;
+                }
+                gix::object::Kind::Tree => {
+                    add_tree(repo, object.id, &mut seen, Some(&mut out))?;
+                    break;
+                }
+                gix::object::Kind::Blob => {
+                    if seen.insert(object.id) {
+                        out.push(object.id);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    for info in repo.rev_walk(tips).with_pruned(common.iter().copied()).all()? {
+        let info = info?;
+        if !seen.insert(info.id) {
+            continue;
+        }
+        out.push(info.id);
+        add_tree(repo, info.object()?.tree_id()?.detach(), &mut seen, Some(&mut out))?;
+    }
+
+    if include_tag {
+        let sent: HashSet<_> = out.iter().copied().collect();
+        for tag in repo.references()?.tags()? {
+            let mut tag = tag.map_err(|err| anyhow!("{err}"))?;
+            let id = tag.follow_to_object()?.detach();
+            let object = repo.find_object(id)?;
+            if object.kind == gix::object::Kind::Tag && sent.contains(&object.to_tag_ref().target()) && seen.insert(id)
+            {
+                out.push(id);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Mark all objects in the tree `id` as seen, and add those we didn't see yet to `out` if set.
+fn add_tree(
+    repo: &gix::Repository,
+    id: ObjectId,
+    seen: &mut HashSet<ObjectId>,
+    mut out: Option<&mut Vec<ObjectId>>,
+) -> anyhow::Result<()> {
+    if !seen.insert(id) {
+        return Ok(());
+    }
+    if let Some(out) = out.as_mut() {
+        out.push(id);
+    }
+    let tree = repo.find_tree(id)?;
+    for entry in tree.decode()?.entries.iter() {
+        let id = entry.oid.to_owned();
+        if entry.mode.is_tree() {
+            add_tree(repo, id, seen, out.as_deref_mut())?;
+        } else if !entry.mode.is_commit() && seen.insert(id) {
+            if let Some(out) = out.as_mut() {
+                out.push(id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a pack with all `objects` to `out`.
+pub(crate) fn write_pack(
+    repo: &gix::Repository,
+    objects: Vec<ObjectId>,
+    out: &mut dyn Write,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    // Open the object database once more as we need to be its sole owner to share it across threads.
+    let mut handle = gix::open_opts(repo.git_dir(), repo.open_options().clone())?
+        .into_sync()
+        .objects
+        .into_shared_arc()
+        .to_cache_arc();
+    handle.prevent_pack_unload();
+    handle.ignore_replacements = true;
+    let (counts, _) = pack::data::output::count::objects_unthreaded(
+        &handle,
+        &mut objects.into_iter().map(Ok),
+        &gix::progress::Discard,
+        should_interrupt,
+        pack::data::output::count::objects::ObjectExpansion::AsIs,
+    )?;
+    let num_entries = counts.len();
+    let mut entries = InOrderIter::from(pack::data::output::entry::iter_from_counts(
+        counts,
+        handle,
+        Box::new(gix::progress::Discard),
+        pack::data::output::entry::iter_from_counts::Options {
+            // The client may not have the bases of thin packs as we don't negotiate precisely.
+            allow_thin_pack: false,
+            ..Default::default()
+        },
+    ));
+    let mut bytes = pack::data::output::bytes::FromEntriesIter::new(
+        entries.by_ref(),
+        out,
+        num_entries as u32,
+        pack::data::Version::default(),
+        repo.object_hash(),
+    );
+    for written in bytes.by_ref() {
+        written?;
+    }
+    entries.inner.finalize()?;
+    Ok(())
+}
+
+/// A writer that sends everything on the data side-band.
+struct Sideband<W> {
+    out: W,
+}
+
+impl<W: Write> Sideband<W> {
+    fn progress(&mut self, message: &str) -> std::io::Result<()> {
+        packetline::encode::band_to_write(Channel::Progress, message.as_bytes(), &mut self.out).map(|_| ())
+    }
+}
+
+impl<W: Write> Write for Sideband<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(MAX_BAND_DATA_LEN);
+        packetline::encode::band_to_write(Channel::Data, &buf[..len], &mut self.out)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -20,6 +20,11 @@ mod error {
         DuplicateChunk { kind: crate::Id },
         #[error("The table of contents would be {expected} bytes, but got only {actual}")]
         TocTooSmall { actual: usize, expected: usize },
+        #[error("The chunk offset {offset} points into the table of contents which ends at {toc_end}")]
+        ChunkOverlapsToc {
+            offset: crate::file::Offset,
+            toc_end: crate::file::Offset,
+        },
         #[error("Empty chunk indices are not allowed as the point of chunked files is to have chunks.")]
         Empty,
     }
@@ -38,8 +43,8 @@ impl file::Index {
 
         let data_len: u64 = data.len() as u64;
         let mut chunks = Vec::with_capacity(num_chunks as usize);
-        let mut toc_entry = &data[toc_offset..];
-        let expected_min_size = (num_chunks as usize + 1) * file::Index::ENTRY_SIZE;
+        let mut toc_entry = data.get(toc_offset..).unwrap_or_default();
+        let expected_min_size = file::Index::size_for_entries(num_chunks as usize);
         if toc_entry.len() < expected_min_size {
             return Err(Error::TocTooSmall {
                 expected: expected_min_size,
@@ -47,6 +52,7 @@ impl file::Index {
             });
         }
 
+        let toc_end = (toc_offset + expected_min_size) as u64;
         for _ in 0..num_chunks {
             let (kind, offset) = toc_entry.split_at(4);
             let kind = to_kind(kind);
@@ -64,6 +70,9 @@ impl file::Index {
                     file_length: data_len,
                 });
             }
+            if offset < toc_end {
+                return Err(Error::ChunkOverlapsToc { offset, toc_end });
+            }
             toc_entry = &toc_entry[file::Index::ENTRY_SIZE..];
             let next_offset = be_u64(&toc_entry[4..]);
             if next_offset > data_len {
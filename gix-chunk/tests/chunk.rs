@@ -0,0 +1,41 @@
+mod decode {
+    use gix_chunk::file::{decode::Error, Index};
+
+    /// Produce a table of contents at the beginning of a file for chunks of `kinds_and_offsets`, followed by the sentinel
+    /// pointing to `end`, and pad the file so it is `end` bytes long.
+    fn toc_with(kinds_and_offsets: &[(gix_chunk::Id, u64)], end: u64) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for (kind, offset) in kinds_and_offsets
+            .iter()
+            .copied()
+            .chain(Some((gix_chunk::SENTINEL, end)))
+        {
+            buf.extend_from_slice(&kind);
+            buf.extend_from_slice(&offset.to_be_bytes());
+        }
+        buf.resize(end as usize, 0);
+        buf
+    }
+
+    #[test]
+    fn chunks_may_start_right_after_the_table_of_contents() {
+        let toc_end = Index::size_for_entries(1) as u64;
+        let data = toc_with(&[(*b"DATA", toc_end)], toc_end + 8);
+        let index = Index::from_bytes(&data, 0, 1).expect("valid");
+        assert_eq!(index.offset_by_id(*b"DATA").expect("present"), toc_end..toc_end + 8);
+    }
+
+    #[test]
+    fn chunk_offsets_pointing_into_the_table_of_contents_are_rejected() {
+        let toc_end = Index::size_for_entries(1) as u64;
+        assert_eq!(toc_end, 24, "one chunk entry and the sentinel");
+        let data = toc_with(&[(*b"DATA", 4)], toc_end + 8);
+        assert!(
+            matches!(
+                Index::from_bytes(&data, 0, 1),
+                Err(Error::ChunkOverlapsToc { offset: 4, toc_end: 24 })
+            ),
+            "the chunk would overlap the table of contents"
+        );
+    }
+}
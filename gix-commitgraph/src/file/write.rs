@@ -0,0 +1,263 @@
+//! Write new commit-graph files.
+use std::io::Write;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    file::{
+        self, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
+        EXTENDED_EDGES_LIST_CHUNK_ID, EXTENDED_EDGES_MASK, FAN_LEN, HEADER_LEN, LAST_EXTENDED_EDGE_MASK, NO_PARENT,
+        OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    },
+    File, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+
+/// The error returned by [`File::write_to()`] and [`File::write_layer_to()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Parent {parent} of commit {id} is not part of the commits to write")]
+    MissingParent { id: ObjectId, parent: ObjectId },
+    #[error("Cannot write {count} commits as a commit-graph can hold no more than {MAX_COMMITS}")]
+    TooManyCommits { count: usize },
+    #[error("Cannot write a commit-graph file on top of {count} base graphs as there can be no more than 255")]
+    TooManyBaseGraphs { count: usize },
+}
+
+/// A commit to write into a commit-graph file with [`File::write_to()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The id of the commit itself.
+    pub id: ObjectId,
+    /// The id of the tree the commit points to.
+    pub tree: ObjectId,
+    /// The ids of all parents of the commit, in order.
+    pub parents: Vec<ObjectId>,
+    /// The committer timestamp of the commit in seconds since the unix epoch.
+    pub commit_time: u64,
+}
+
+/// The result of [`File::write_to()`] and [`File::write_layer_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The checksum of the written file, which is also its trailer.
+    pub checksum: ObjectId,
+    /// The amount of distinct commits written, which doesn't include commits of base graphs.
+    pub num_commits: u32,
+}
+
+impl File {
+    /// Write a new commit-graph file with all `commits` to `out`, with each one of their parents being present in `commits` as well.
+    ///
+    /// Generation numbers are computed as topological levels, and commit times that don't fit into 34 bits are truncated
+    /// just like `git` does.
+    pub fn write_to(
+        commits: Vec<Commit>,
+        out: &mut dyn std::io::Write,
+        object_hash: gix_hash::Kind,
+    ) -> Result<Outcome, Error> {
+        Self::write_layer_to(commits, &[], out, object_hash)
+    }
+
+    /// Write a new commit-graph file to `out` which is a layer of a split commit-graph on top of the `base` files
+    /// of its chain, with the lowest one first.
+    ///
+    /// `commits` that are already contained in `base` are skipped, and each one of their parents must be present in either
+    /// `commits` or `base`.
+    pub fn write_layer_to(
+        mut commits: Vec<Commit>,
+        base: &[File],
+        out: &mut dyn std::io::Write,
+        object_hash: gix_hash::Kind,
+    ) -> Result<Outcome, Error> {
+        let num_base_graphs: u8 = base
+            .len()
+            .try_into()
+            .map_err(|_| Error::TooManyBaseGraphs { count: base.len() })?;
+        commits.retain(|commit| base_position(base, &commit.id).is_none());
+        commits.sort_by_key(|c| c.id);
+        commits.dedup_by(|a, b| a.id == b.id);
+        let num_base_commits: u32 = base.iter().map(File::num_commits).sum();
+        if u64::from(num_base_commits) + commits.len() as u64 > u64::from(MAX_COMMITS) {
+            return Err(Error::TooManyCommits {
+                count: num_base_commits as usize + commits.len(),
+            });
+        }
+
+        let parent_positions = commits
+            .iter()
+            .map(|commit| {
+                commit
+                    .parents
+                    .iter()
+                    .map(|parent| {
+                        commits
+                            .binary_search_by(|c| c.id.cmp(parent))
+                            .ok()
+                            .map(|pos| num_base_commits + pos as u32)
+                            .or_else(|| base_position(base, parent))
+                            .ok_or(Error::MissingParent {
+                                id: commit.id,
+                                parent: *parent,
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let generations = generations(&parent_positions, base, num_base_commits);
+        let num_extra_edges: usize = parent_positions
+            .iter()
+            .filter(|parents| parents.len() > 2)
+            .map(|parents| parents.len() - 1)
+            .sum();
+
+        let hash_len = object_hash.len_in_bytes();
+        let mut cf = gix_chunk::file::Index::for_writing();
+        cf.plan_chunk(OID_FAN_CHUNK_ID, (FAN_LEN * 4) as u64);
+        cf.plan_chunk(OID_LOOKUP_CHUNK_ID, (commits.len() * hash_len) as u64);
+        cf.plan_chunk(
+            COMMIT_DATA_CHUNK_ID,
+            (commits.len() * (hash_len + COMMIT_DATA_ENTRY_SIZE_SANS_HASH)) as u64,
+        );
+        if num_extra_edges != 0 {
+            cf.plan_chunk(EXTENDED_EDGES_LIST_CHUNK_ID, (num_extra_edges * 4) as u64);
+        }
+        if !base.is_empty() {
+            cf.plan_chunk(BASE_GRAPHS_LIST_CHUNK_ID, (base.len() * hash_len) as u64);
+        }
+
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&[1 /* version */, object_hash as u8])?;
+        out.write_all(&[
+            cf.num_chunks().try_into().expect("BUG: wrote more than 256 chunks"),
+            num_base_graphs,
+        ])?;
+
+        let mut chunk_write = cf.into_write(&mut out, HEADER_LEN)?;
+        while let Some(chunk_to_write) = chunk_write.next_chunk() {
+            match chunk_to_write {
+                OID_FAN_CHUNK_ID => {
+                    let mut fan = [0u32; FAN_LEN];
+                    for commit in &commits {
+                        fan[commit.id.first_byte() as usize] += 1;
+                    }
+                    let mut count = 0;
+                    for entry in fan {
+                        count += entry;
+                        chunk_write.write_all(&count.to_be_bytes())?;
+                    }
+                }
+                OID_LOOKUP_CHUNK_ID => {
+                    for commit in &commits {
+                        chunk_write.write_all(commit.id.as_slice())?;
+                    }
+                }
+                COMMIT_DATA_CHUNK_ID => {
+                    let mut extra_edge_index = 0;
+                    for ((commit, parents), generation) in commits.iter().zip(&parent_positions).zip(&generations) {
+                        chunk_write.write_all(commit.tree.as_slice())?;
+                        let (parent1, parent2) = match parents.as_slice() {
+                            [] => (NO_PARENT, NO_PARENT),
+                            [first] => (*first, NO_PARENT),
+                            [first, second] => (*first, *second),
+                            [first, rest @ ..] => {
+                                let edge = EXTENDED_EDGES_MASK | extra_edge_index;
+                                extra_edge_index += rest.len() as u32;
+                                (*first, edge)
+                            }
+                        };
+                        chunk_write.write_all(&parent1.to_be_bytes())?;
+                        chunk_write.write_all(&parent2.to_be_bytes())?;
+                        let generation_and_time_high = (generation << 2) | ((commit.commit_time >> 32) & 0x3) as u32;
+                        chunk_write.write_all(&generation_and_time_high.to_be_bytes())?;
+                        chunk_write.write_all(&(commit.commit_time as u32).to_be_bytes())?;
+                    }
+                }
+                EXTENDED_EDGES_LIST_CHUNK_ID => {
+                    for parents in parent_positions.iter().filter(|parents| parents.len() > 2) {
+                        let (last, rest) = parents[1..].split_last().expect("more than one");
+                        for parent in rest {
+                            chunk_write.write_all(&parent.to_be_bytes())?;
+                        }
+                        chunk_write.write_all(&(last | LAST_EXTENDED_EDGE_MASK).to_be_bytes())?;
+                    }
+                }
+                BASE_GRAPHS_LIST_CHUNK_ID => {
+                    for file in base {
+                        chunk_write.write_all(file.checksum().as_bytes())?;
+                    }
+                }
+                unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
+            }
+        }
+
+        let checksum: ObjectId = out.hash.digest().into();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(Outcome {
+            checksum,
+            num_commits: commits.len() as u32,
+        })
+    }
+}
+
+/// Return the graph position of `id` within the `base` files of a chain, with the lowest one first.
+fn base_position(base: &[File], id: &gix_hash::oid) -> Option<u32> {
+    let mut file_start = 0;
+    for file in base {
+        if let Some(pos) = file.lookup(id) {
+            return Some(file_start + pos.0);
+        }
+        file_start += file.num_commits();
+    }
+    None
+}
+
+/// Return the generation of the commit at graph position `pos` within the `base` files of a chain.
+fn base_generation(base: &[File], mut pos: u32) -> u32 {
+    for file in base {
+        match pos.checked_sub(file.num_commits()) {
+            Some(remaining) => pos = remaining,
+            None => return file.commit_at(file::Position(pos)).generation(),
+        }
+    }
+    unreachable!("BUG: parent positions below the amount of base commits are in a base graph")
+}
+
+/// Compute the topological level of each commit, given the graph positions of its parents, without recursion.
+///
+/// Commits are at positions starting at `num_base_commits`, and parents at lower positions are in `base`.
+fn generations(parent_positions: &[Vec<u32>], base: &[File], num_base_commits: u32) -> Vec<u32> {
+    let mut generations = vec![0u32; parent_positions.len()];
+    let mut stack = Vec::new();
+    for start in 0..parent_positions.len() {
+        if generations[start] != 0 {
+            continue;
+        }
+        stack.push(start);
+        while let Some(&pos) = stack.last() {
+            let mut max_parent_generation = 0;
+            let mut all_parents_known = true;
+            for &parent in &parent_positions[pos] {
+                let Some(parent) = parent.checked_sub(num_base_commits) else {
+                    max_parent_generation = max_parent_generation.max(base_generation(base, parent));
+                    continue;
+                };
+                match generations[parent as usize] {
+                    0 => {
+                        all_parents_known = false;
+                        stack.push(parent as usize);
+                    }
+                    generation => max_parent_generation = max_parent_generation.max(generation),
+                }
+            }
+            if all_parents_known {
+                generations[pos] = (max_parent_generation + 1).min(GENERATION_NUMBER_MAX);
+                stack.pop();
+            }
+        }
+    }
+    generations
+}
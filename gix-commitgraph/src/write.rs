@@ -0,0 +1,214 @@
+//! Write split commit-graphs, which consist of a chain of commit-graph files.
+use std::{collections::HashSet, io::Write, path::Path, time::SystemTime};
+
+use gix_lock::tempfile::{AutoRemove, ContainingDirectory};
+
+use crate::{file, File, Graph};
+
+/// Options for use in [`Graph::write_chain()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Merge the new layer with the layer below it as long as that one has no more than `size_multiple` times
+    /// the commits of the new layer, just like `git commit-graph write --split --size-multiple=<n>`.
+    ///
+    /// This keeps the chain short as larger layers are at the bottom, while only small layers are rewritten
+    /// most of the time. It's 2 by default, and 0 prevents merging unless the new layer has more than `max_commits`.
+    pub size_multiple: u32,
+    /// If the new layer would contain more than this amount of commits, merge it with layers below it until it doesn't,
+    /// or until it's the only layer, like `git commit-graph write --split --max-commits=<n>`.
+    pub max_commits: Option<u32>,
+    /// Delete files of the chain which are no longer used if they were last modified at or before this time,
+    /// or delete all of them if `None`, like `git commit-graph write --split --expire-time=<datetime>`.
+    ///
+    /// Keeping unused files around for a while allows concurrent readers of the previous chain to continue.
+    pub expire_time: Option<SystemTime>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            size_multiple: 2,
+            max_commits: None,
+            expire_time: None,
+        }
+    }
+}
+
+/// The error returned by [`Graph::write_chain()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not lock the commit-graph chain")]
+    LockAcquire(#[from] gix_lock::acquire::Error),
+    #[error("Could not load the existing commit-graph chain")]
+    Init(#[from] crate::init::Error),
+    #[error("Could not read the parents of a commit in a layer to merge")]
+    Parents(#[from] file::commit::Error),
+    #[error(transparent)]
+    Write(#[from] file::write::Error),
+    #[error("Could not write commit-graph file or chain")]
+    Io(#[from] std::io::Error),
+    #[error("Could not move the commit-graph chain into place")]
+    LockCommit(#[from] gix_lock::commit::Error<gix_lock::File>),
+}
+
+/// The result of [`Graph::write_chain()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The newly written file at the top of the chain, or `None` if all commits were already contained in the chain.
+    pub layer: Option<file::write::Outcome>,
+    /// The amount of existing layers whose commits were merged into the new layer, replacing them.
+    pub num_merged_layers: usize,
+    /// The amount of files in the chain after writing.
+    pub num_layers: usize,
+    /// The amount of files of previous chains which were deleted.
+    pub num_expired_files: usize,
+}
+
+impl Graph {
+    /// Add all `commits` that aren't yet contained in the commit-graph chain of the `.git/objects/info` directory at `info_dir`
+    /// as new layer on top of it, configured by `options`, and return information about what was written.
+    ///
+    /// Each parent of `commits` must be contained in the chain or in `commits`. Existing layers are merged with the new one
+    /// according to `options`, which keeps writing cheap even in large repositories as usually only a small layer is written.
+    /// Nothing is written if all `commits` are already contained in the chain.
+    ///
+    /// A monolithic `commit-graph` file in `info_dir` is removed as it would take precedence over the chain.
+    /// Files that were part of previous chains are deleted according to [`Options::expire_time`].
+    pub fn write_chain(
+        info_dir: &Path,
+        commits: Vec<file::write::Commit>,
+        object_hash: gix_hash::Kind,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let commit_graphs_dir = info_dir.join("commit-graphs");
+        std::fs::create_dir_all(&commit_graphs_dir)?;
+        let chain_path = commit_graphs_dir.join("commit-graph-chain");
+        let mut chain =
+            gix_lock::File::acquire_to_update_resource(&chain_path, gix_lock::acquire::Fail::Immediately, None)?;
+        let graph = if chain_path.is_file() {
+            Graph::from_commit_graphs_dir(&commit_graphs_dir)?
+        } else {
+            Graph { files: Vec::new() }
+        };
+
+        let mut commits: Vec<_> = commits
+            .into_iter()
+            .filter(|commit| graph.lookup(commit.id).is_none())
+            .collect();
+        commits.sort_by_key(|c| c.id);
+        commits.dedup_by(|a, b| a.id == b.id);
+        if commits.is_empty() {
+            return Ok(Outcome {
+                layer: None,
+                num_merged_layers: 0,
+                num_layers: graph.files.len(),
+                num_expired_files: 0,
+            });
+        }
+
+        let num_base_graphs = num_base_graphs_after_merge(&graph.files, commits.len() as u64, options);
+        for file in &graph.files[num_base_graphs..] {
+            for commit in file.iter_commits() {
+                commits.push(file::write::Commit {
+                    id: commit.id().to_owned(),
+                    tree: commit.root_tree_id().to_owned(),
+                    parents: commit
+                        .iter_parents()
+                        .map(|parent| parent.map(|pos| graph.id_at(pos).to_owned()))
+                        .collect::<Result<_, _>>()?,
+                    commit_time: commit.committer_timestamp(),
+                });
+            }
+        }
+        let num_merged_layers = graph.files.len() - num_base_graphs;
+        let mut files = graph.files;
+        // Release merged files early as they may be deleted later.
+        files.truncate(num_base_graphs);
+
+        let mut layer_file =
+            gix_lock::tempfile::new(&commit_graphs_dir, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+        let layer = File::write_layer_to(commits, &files, &mut layer_file, object_hash)?;
+        layer_file
+            .persist(commit_graphs_dir.join(graph_file_name(&layer.checksum)))
+            .map_err(|err| err.error)?;
+
+        let mut chain_file_names: HashSet<_> = files.iter().map(|file| graph_file_name(file.checksum())).collect();
+        chain_file_names.insert(graph_file_name(&layer.checksum));
+        for checksum in files.iter().map(File::checksum).chain(Some(layer.checksum.as_ref())) {
+            writeln!(chain, "{checksum}")?;
+        }
+        chain.commit()?;
+        let num_layers = files.len() + 1;
+        drop(files);
+
+        match std::fs::remove_file(info_dir.join("commit-graph")) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+        let num_expired_files = expire_files(
+            &commit_graphs_dir,
+            &chain_file_names,
+            options.expire_time.unwrap_or_else(SystemTime::now),
+        )?;
+        Ok(Outcome {
+            layer: Some(layer),
+            num_merged_layers,
+            num_layers,
+            num_expired_files,
+        })
+    }
+}
+
+/// Return the amount of `files` at the bottom of the chain to keep, with all others being merged into a new layer with
+/// `num_new_commits`, just like `git` does.
+fn num_base_graphs_after_merge(files: &[File], mut num_new_commits: u64, options: Options) -> usize {
+    let mut num_base_graphs = files.len();
+    while let Some(top) = num_base_graphs.checked_sub(1).map(|idx| &files[idx]) {
+        let top_commits = u64::from(top.num_commits());
+        let is_too_small = top_commits <= u64::from(options.size_multiple) * num_new_commits;
+        let is_new_layer_too_large = options
+            .max_commits
+            .map_or(false, |max_commits| num_new_commits > u64::from(max_commits));
+        if !(is_too_small || is_new_layer_too_large) {
+            break;
+        }
+        num_new_commits += top_commits;
+        num_base_graphs -= 1;
+    }
+    num_base_graphs
+}
+
+fn graph_file_name(checksum: &gix_hash::oid) -> String {
+    format!("graph-{}.graph", checksum.to_hex())
+}
+
+/// Delete all graph files in `commit_graphs_dir` that aren't in `chain_file_names` and were modified at or before `expire_time`,
+/// and return how many were deleted.
+fn expire_files(
+    commit_graphs_dir: &Path,
+    chain_file_names: &HashSet<String>,
+    expire_time: SystemTime,
+) -> std::io::Result<usize> {
+    let mut num_expired_files = 0;
+    for entry in std::fs::read_dir(commit_graphs_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let is_graph_file = name.starts_with("graph-") && path.extension().map_or(false, |ext| ext == "graph");
+        if !is_graph_file || chain_file_names.contains(name) {
+            continue;
+        }
+        match entry.metadata().and_then(|meta| meta.modified()) {
+            Ok(modified) if modified <= expire_time => {}
+            _ => continue,
+        }
+        // Like `git`, ignore files that can't be deleted, for instance because they are still in use on Windows.
+        if std::fs::remove_file(&path).is_ok() {
+            num_expired_files += 1;
+        }
+    }
+    Ok(num_expired_files)
+}
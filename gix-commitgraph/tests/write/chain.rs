@@ -0,0 +1,176 @@
+use std::time::{Duration, SystemTime};
+
+use gix_commitgraph::{write::Options, Graph};
+
+use crate::{graph_and_expected, write::commits_of};
+
+/// Return a git-generated graph with 7 commits, along with the commits of the first two generations, which are all
+/// commits except for the two merges.
+fn octopus_graph() -> (Graph, Vec<gix_commitgraph::file::write::Commit>) {
+    let (graph, _) = graph_and_expected(
+        "octopus_merges.sh",
+        &[
+            "root",
+            "parent1",
+            "parent2",
+            "parent3",
+            "parent4",
+            "three_parents",
+            "four_parents",
+        ],
+    );
+    let without_merges = commits_of(&graph)
+        .into_iter()
+        .filter(|commit| graph.commit_by_id(commit.id).expect("present").generation() <= 2)
+        .collect();
+    (graph, without_merges)
+}
+
+/// Assert that `actual` contains exactly the commits of `expected`, with the same parents and generations.
+fn assert_same_commits(actual: &Graph, expected: &Graph) {
+    actual
+        .verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))
+        .expect("graph is valid");
+    assert_eq!(actual.num_commits(), expected.num_commits());
+    for expected_commit in expected.iter_commits() {
+        let commit = actual.commit_by_id(expected_commit.id()).expect("present");
+        assert_eq!(
+            commit.generation(),
+            expected_commit.generation(),
+            "generations match git"
+        );
+        assert_eq!(commit.committer_timestamp(), expected_commit.committer_timestamp());
+        assert_eq!(commit.root_tree_id(), expected_commit.root_tree_id());
+        let parent_ids = |graph: &Graph, commit: gix_commitgraph::file::Commit<'_>| {
+            commit
+                .iter_parents()
+                .map(|pos| graph.id_at(pos.expect("valid parent")).to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parent_ids(actual, commit), parent_ids(expected, expected_commit));
+    }
+}
+
+fn chain_len(info_dir: &std::path::Path) -> usize {
+    std::fs::read_to_string(info_dir.join("commit-graphs").join("commit-graph-chain"))
+        .expect("chain exists")
+        .lines()
+        .count()
+}
+
+fn num_graph_files(info_dir: &std::path::Path) -> usize {
+    std::fs::read_dir(info_dir.join("commit-graphs"))
+        .expect("directory exists")
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .expect("readable")
+                .path()
+                .extension()
+                .map_or(false, |ext| ext == "graph")
+        })
+        .count()
+}
+
+#[test]
+fn layers_are_added_without_merging_if_the_size_multiple_allows_it() -> gix_testtools::Result {
+    let (expected, without_merges) = octopus_graph();
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let info_dir = tmp.path();
+    let options = Options {
+        size_multiple: 0,
+        ..Default::default()
+    };
+
+    let outcome = Graph::write_chain(info_dir, without_merges, gix_hash::Kind::Sha1, options)?;
+    assert_eq!(outcome.layer.expect("written").num_commits, 5);
+    assert_eq!(outcome.num_layers, 1);
+
+    let outcome = Graph::write_chain(info_dir, commits_of(&expected), gix_hash::Kind::Sha1, options)?;
+    assert_eq!(
+        outcome.layer.expect("written").num_commits,
+        2,
+        "only commits that aren't in the chain are written"
+    );
+    assert_eq!(outcome.num_merged_layers, 0);
+    assert_eq!(outcome.num_layers, 2);
+    assert_eq!(chain_len(info_dir), 2);
+
+    let actual = Graph::from_info_dir(info_dir)?;
+    assert_same_commits(&actual, &expected);
+    drop(actual);
+
+    let outcome = Graph::write_chain(info_dir, commits_of(&expected), gix_hash::Kind::Sha1, options)?;
+    assert_eq!(outcome.layer, None, "there is nothing to do if all commits are known");
+    assert_eq!(outcome.num_layers, 2);
+    Ok(())
+}
+
+#[test]
+fn small_layers_are_merged_and_replaced_files_expire() -> gix_testtools::Result {
+    let (expected, without_merges) = octopus_graph();
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let info_dir = tmp.path();
+    std::fs::write(
+        info_dir.join("commit-graph"),
+        b"a monolithic graph that would take precedence",
+    )?;
+
+    Graph::write_chain(info_dir, without_merges, gix_hash::Kind::Sha1, Default::default())?;
+    assert!(
+        !info_dir.join("commit-graph").exists(),
+        "the monolithic graph is removed once the chain is written"
+    );
+    let keep_replaced_files = Options {
+        size_multiple: 3,
+        expire_time: Some(SystemTime::now() - Duration::from_secs(60 * 60)),
+        ..Default::default()
+    };
+    let outcome = Graph::write_chain(
+        info_dir,
+        commits_of(&expected),
+        gix_hash::Kind::Sha1,
+        keep_replaced_files,
+    )?;
+    assert_eq!(
+        outcome.layer.expect("written").num_commits,
+        7,
+        "5 commits aren't more than 3 times the 2 new commits, so they are merged"
+    );
+    assert_eq!(outcome.num_merged_layers, 1);
+    assert_eq!(outcome.num_layers, 1);
+    assert_eq!(outcome.num_expired_files, 0, "the replaced file is too new to expire");
+    assert_eq!(chain_len(info_dir), 1);
+    assert_eq!(num_graph_files(info_dir), 2);
+    assert_same_commits(&Graph::from_info_dir(info_dir)?, &expected);
+    Ok(())
+}
+
+#[test]
+fn max_commits_forces_merges_and_replaced_files_expire_by_default() -> gix_testtools::Result {
+    let (expected, without_merges) = octopus_graph();
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let info_dir = tmp.path();
+
+    let no_merges = Options {
+        size_multiple: 0,
+        ..Default::default()
+    };
+    Graph::write_chain(info_dir, without_merges, gix_hash::Kind::Sha1, no_merges)?;
+    let outcome = Graph::write_chain(
+        info_dir,
+        commits_of(&expected),
+        gix_hash::Kind::Sha1,
+        Options {
+            max_commits: Some(1),
+            ..no_merges
+        },
+    )?;
+    assert_eq!(outcome.layer.expect("written").num_commits, 7);
+    assert_eq!(outcome.num_merged_layers, 1);
+    assert_eq!(outcome.num_layers, 1);
+    assert_eq!(outcome.num_expired_files, 1);
+    assert_eq!(num_graph_files(info_dir), 1);
+    assert_same_commits(&Graph::from_info_dir(info_dir)?, &expected);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use gix_commitgraph::{file::write, File, Graph};
+
+mod chain;
+
+use crate::{check_common, graph_and_expected};
+
+/// Return all commits of `graph` in a form suitable for writing them.
+fn commits_of(graph: &Graph) -> Vec<write::Commit> {
+    graph
+        .iter_commits()
+        .map(|commit| write::Commit {
+            id: commit.id().to_owned(),
+            tree: commit.root_tree_id().to_owned(),
+            parents: commit
+                .iter_parents()
+                .map(|pos| graph.id_at(pos.expect("valid parent")).to_owned())
+                .collect(),
+            commit_time: commit.committer_timestamp(),
+        })
+        .collect()
+}
+
+/// Write all commits of the git-generated `graph` into a new commit-graph file and return it as graph.
+fn rewrite(graph: &Graph) -> (Graph, gix_testtools::tempfile::TempDir) {
+    let commits = commits_of(graph);
+    let num_commits = commits.len();
+
+    let tmp = gix_testtools::tempfile::tempdir().expect("can create tempdir");
+    let mut out = Vec::new();
+    let outcome = File::write_to(commits, &mut out, gix_hash::Kind::Sha1).expect("all parents are present");
+    assert_eq!(outcome.num_commits as usize, num_commits);
+    assert_eq!(
+        out[out.len() - 20..],
+        *outcome.checksum.as_slice(),
+        "the checksum is the trailer"
+    );
+    std::fs::write(tmp.path().join("commit-graph"), out).expect("can write file");
+    (Graph::from_info_dir(tmp.path()).expect("written graph is valid"), tmp)
+}
+
+#[test]
+fn roundtrip_with_generations_and_extra_edges() {
+    for (script, refs) in [
+        ("single_parent.sh", &["parent", "child"][..]),
+        ("two_parents.sh", &["parent1", "parent2", "child"]),
+        (
+            "octopus_merges.sh",
+            &[
+                "root",
+                "parent1",
+                "parent2",
+                "parent3",
+                "parent4",
+                "three_parents",
+                "four_parents",
+            ],
+        ),
+    ] {
+        let (expected_graph, expected) = graph_and_expected(script, refs);
+        let (actual, _tmp) = rewrite(&expected_graph);
+        check_common(&actual, &expected);
+        for info in expected.values() {
+            assert_eq!(
+                actual.commit_at(info.pos()).generation(),
+                expected_graph.commit_at(info.pos()).generation(),
+                "{script}: generations match what git computes"
+            );
+        }
+    }
+}
+
+#[test]
+fn missing_parents_are_an_error() {
+    let id = gix_hash::ObjectId::from_hex(b"0000000000000000000000000000000000000001").expect("valid");
+    let parent = gix_hash::Kind::Sha1.null();
+    let err = File::write_to(
+        vec![write::Commit {
+            id,
+            tree: id,
+            parents: vec![parent],
+            commit_time: 0,
+        }],
+        &mut Vec::new(),
+        gix_hash::Kind::Sha1,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, write::Error::MissingParent { id: actual_id, parent: actual_parent } if actual_id == id && actual_parent == parent)
+    );
+}
@@ -165,19 +165,21 @@ pub(crate) fn git_with_metadata(
 
     {
         // Fast-path: avoid doing the complete search if HEAD is already not there.
-        // TODO(reftable): use a ref-store to lookup HEAD if ref-tables should be supported, or detect ref-tables beforehand.
         if !dot_git.join("HEAD").exists() {
             return Err(crate::is_git::Error::MissingHead);
         }
-        // We expect to be able to parse any ref-hash, so we shouldn't have to know the repos hash here.
-        // With ref-table, the has is probably stored as part of the ref-db itself, so we can handle it from there.
-        // In other words, it's important not to fail on detached heads here because we guessed the hash kind wrongly.
-        let refs = gix_ref::file::Store::at(dot_git.as_ref().into(), Default::default());
-        let head = refs.find_loose("HEAD")?;
-        if head.name.as_bstr() != "HEAD" {
-            return Err(crate::is_git::Error::MisplacedHead {
-                name: head.name.into_inner(),
-            });
+        // With a reftable, HEAD is a placeholder pointing to the invalid `refs/heads/.invalid` to keep older tools away,
+        // while the actual HEAD is stored in the reftable which is validated when opening the repository.
+        if !dot_git.join("reftable").is_dir() {
+            // We expect to be able to parse any ref-hash, so we shouldn't have to know the repos hash here.
+            // In other words, it's important not to fail on detached heads here because we guessed the hash kind wrongly.
+            let refs = gix_ref::file::Store::at(dot_git.as_ref().into(), Default::default());
+            let head = refs.find_loose("HEAD")?;
+            if head.name.as_bstr() != "HEAD" {
+                return Err(crate::is_git::Error::MisplacedHead {
+                    name: head.name.into_inner(),
+                });
+            }
         }
     }
 
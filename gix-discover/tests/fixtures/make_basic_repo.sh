@@ -92,3 +92,17 @@ mkdir $worktree && touch $worktree/file
   git add file
   git commit -m "make sure na index exists"
 )
+
+git init -q --separate-git-dir=separate-git-dir.git worktree-with-separate-git-dir
+(cd worktree-with-separate-git-dir
+  touch this
+  git add this
+  git commit -q -m c1
+  mkdir subdir
+)
+
+git init -q --separate-git-dir=relative-separate-git-dir.git worktree-with-relative-gitfile
+(cd worktree-with-relative-gitfile
+  echo "gitdir: ../relative-separate-git-dir.git" > .git
+  mkdir subdir
+)
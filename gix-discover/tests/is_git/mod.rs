@@ -106,3 +106,25 @@ fn split_worktree_using_configuration() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+fn reftable_repo_with_placeholder_head() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    for name in ["objects", "refs"] {
+        std::fs::create_dir(dir.path().join(name))?;
+    }
+    std::fs::write(dir.path().join("HEAD"), "ref: refs/heads/.invalid\n")?;
+    assert!(
+        gix_discover::is_git(dir.path()).is_err(),
+        "without reftable, the placeholder is an invalid reference"
+    );
+
+    std::fs::create_dir(dir.path().join("reftable"))?;
+    let kind = gix_discover::is_git(dir.path())?;
+    assert_eq!(
+        kind,
+        gix_discover::repository::Kind::PossiblyBare,
+        "the actual HEAD is in the reftable, which isn't checked here"
+    );
+    Ok(())
+}
@@ -235,6 +235,45 @@ fn from_existing_worktree() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn from_worktree_with_separate_git_dir() -> crate::Result {
+    let top_level_repo = repo_path()?;
+    for (worktree_name, expected_git_dir) in [
+        ("worktree-with-separate-git-dir", "separate-git-dir.git"),
+        ("worktree-with-relative-gitfile", "relative-separate-git-dir.git"),
+    ] {
+        let expected_worktree = top_level_repo.join(worktree_name);
+        for discover_path in [expected_worktree.clone(), expected_worktree.join("subdir")] {
+            let (path, trust) = gix_discover::upwards(&discover_path)?;
+            assert!(
+                matches!(path, gix_discover::repository::Path::LinkedWorkTree { .. }),
+                "the .git file is followed to the git dir, which is the common dir as well"
+            );
+            assert_eq!(trust, expected_trust());
+            let (git_dir, worktree) = path.into_repository_and_work_tree_directories();
+            assert_eq!(
+                gix_path::realpath(&git_dir)?,
+                gix_path::realpath(top_level_repo.join(expected_git_dir))?,
+                "relative paths in the .git file are relative to the directory containing it"
+            );
+            assert_eq!(
+                worktree.expect("worktree is set"),
+                expected_worktree,
+                "the worktree is the .git file's directory"
+            );
+        }
+        match gix_discover::is_git(&expected_worktree.join(".git"))? {
+            gix_discover::repository::Kind::Submodule { git_dir } => assert_eq!(
+                gix_path::realpath(git_dir)?,
+                gix_path::realpath(top_level_repo.join(expected_git_dir))?,
+                "without a commondir file, the git dir is the common dir"
+            ),
+            kind => unreachable!("unexpected kind: {kind:?}"),
+        }
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "macos")]
 #[test]
 fn cross_fs() -> crate::Result {
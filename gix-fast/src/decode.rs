@@ -0,0 +1,485 @@
+use std::io::{BufRead, Read};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{Blob, Command, Commit, CommitIsh, DataRef, FileChange, Mark, Reset, Tag};
+
+/// The error returned by the [`Parser`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("An IO error occurred while reading the stream")]
+    Io(#[from] std::io::Error),
+    #[error("Line {line_number}: unknown or unsupported command {line:?}")]
+    UnknownCommand { line_number: usize, line: BString },
+    #[error("Line {line_number}: {message}, got {line:?}")]
+    Malformed {
+        line_number: usize,
+        message: &'static str,
+        line: BString,
+    },
+    #[error("Line {line_number}: could not unquote path in {line:?}")]
+    Unquote {
+        line_number: usize,
+        line: BString,
+        source: gix_quote::ansi_c::undo::Error,
+    },
+    #[error("Line {line_number}: expected {expected:?}, but the stream ended")]
+    UnexpectedEof { line_number: usize, expected: &'static str },
+}
+
+/// A parser which reads [commands][Command] from a fast-import stream, one at a time, as an iterator.
+///
+/// Comment lines starting with `#` are skipped.
+pub struct Parser<R> {
+    input: R,
+    /// The current line, without its trailing newline.
+    line: Vec<u8>,
+    /// If `true`, `line` was read but not yet consumed.
+    line_pending: bool,
+    line_number: usize,
+    done: bool,
+}
+
+/// Lifecycle
+impl<R> Parser<R>
+where
+    R: BufRead,
+{
+    /// Create a new instance to read commands from `input`.
+    pub fn new(input: R) -> Self {
+        Parser {
+            input,
+            line: Vec::new(),
+            line_pending: false,
+            line_number: 0,
+            done: false,
+        }
+    }
+
+    /// Return the underlying reader, positioned right after the last parsed command.
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+}
+
+impl<R> Iterator for Parser<R>
+where
+    R: BufRead,
+{
+    type Item = Result<Command, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.parse_command().transpose();
+        if matches!(res, None | Some(Err(_) | Ok(Command::Done))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+impl<R> Parser<R>
+where
+    R: BufRead,
+{
+    fn parse_command(&mut self) -> Result<Option<Command>, Error> {
+        loop {
+            if !self.next_line()? {
+                return Ok(None);
+            }
+            if !self.line.is_empty() {
+                break;
+            }
+        }
+        let line = self.line.as_bstr();
+        let (command, args) = split_once_space(line);
+        Ok(Some(match (command.as_bytes(), args) {
+            (b"blob", None) => Command::Blob(self.blob()?),
+            (b"commit", Some(ref_name)) => {
+                let ref_name = ref_name.to_owned();
+                Command::Commit(self.commit(ref_name)?)
+            }
+            (b"reset", Some(ref_name)) => {
+                let ref_name = ref_name.to_owned();
+                let from = self.optional_from()?;
+                self.skip_empty_line()?;
+                Command::Reset(Reset { ref_name, from })
+            }
+            (b"tag", Some(name)) => {
+                let name = name.to_owned();
+                Command::Tag(self.tag(name)?)
+            }
+            (b"checkpoint", None) => Command::Checkpoint,
+            (b"done", None) => Command::Done,
+            (b"progress", message) => Command::Progress(message.unwrap_or_default().to_owned()),
+            (b"feature", Some(feature)) => match feature.find_byte(b'=') {
+                Some(pos) => Command::Feature {
+                    name: feature[..pos].into(),
+                    value: Some(feature[pos + 1..].into()),
+                },
+                None => Command::Feature {
+                    name: feature.to_owned(),
+                    value: None,
+                },
+            },
+            (b"option", Some(option)) => Command::Option(option.to_owned()),
+            _ => return Err(self.unknown_command()),
+        }))
+    }
+
+    fn blob(&mut self) -> Result<Blob, Error> {
+        let mark = self.optional_mark()?;
+        let original_oid = self.optional_value(b"original-oid ")?;
+        let data = self.data()?;
+        Ok(Blob {
+            mark,
+            original_oid,
+            data,
+        })
+    }
+
+    fn commit(&mut self, ref_name: BString) -> Result<Commit, Error> {
+        let mark = self.optional_mark()?;
+        let original_oid = self.optional_value(b"original-oid ")?;
+        let author = self
+            .optional_value(b"author ")?
+            .map(|value| self.signature(value.as_ref()))
+            .transpose()?;
+        let committer = match self.optional_value(b"committer ")? {
+            Some(value) => self.signature(value.as_ref())?,
+            None => return Err(self.malformed("expected 'committer' line")),
+        };
+        let encoding = self.optional_value(b"encoding ")?;
+        let message = self.data()?;
+        let from = self.optional_from()?;
+        let mut merge = Vec::new();
+        while let Some(parent) = self.optional_value(b"merge ")? {
+            merge.push(commit_ish(parent.as_ref()));
+        }
+        let mut changes = Vec::new();
+        while self.next_line()? {
+            if self.line.is_empty() {
+                break;
+            }
+            match self.file_change()? {
+                Some(change) => changes.push(change),
+                None => {
+                    self.line_pending = true;
+                    break;
+                }
+            }
+        }
+        Ok(Commit {
+            ref_name,
+            mark,
+            original_oid,
+            author,
+            committer,
+            encoding,
+            message,
+            from,
+            merge,
+            changes,
+        })
+    }
+
+    fn tag(&mut self, name: BString) -> Result<Tag, Error> {
+        let mark = self.optional_mark()?;
+        let from = match self.optional_from()? {
+            Some(from) => from,
+            None => return Err(self.malformed("expected 'from' line")),
+        };
+        let original_oid = self.optional_value(b"original-oid ")?;
+        let tagger = self
+            .optional_value(b"tagger ")?
+            .map(|value| self.signature(value.as_ref()))
+            .transpose()?;
+        let message = self.data()?;
+        Ok(Tag {
+            name,
+            mark,
+            from,
+            original_oid,
+            tagger,
+            message,
+        })
+    }
+
+    /// Parse the current line as file change, or return `None` if it's not one.
+    fn file_change(&mut self) -> Result<Option<FileChange>, Error> {
+        let line = self.line.as_bstr();
+        Ok(Some(if let Some(args) = line.strip_prefix(b"M ") {
+            let mut tokens = args.splitn(3, |b| *b == b' ');
+            let (mode, data, path) = match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some(mode), Some(data), Some(path)) => (mode, data, path),
+                _ => return Err(self.malformed("expected 'M <mode> <dataref> <path>'")),
+            };
+            let mode = match mode {
+                b"100644" | b"644" => gix_object::tree::EntryKind::Blob,
+                b"100755" | b"755" => gix_object::tree::EntryKind::BlobExecutable,
+                b"120000" => gix_object::tree::EntryKind::Link,
+                b"160000" => gix_object::tree::EntryKind::Commit,
+                b"040000" | b"40000" => gix_object::tree::EntryKind::Tree,
+                _ => return Err(self.malformed("invalid file mode")),
+            };
+            let path = self.path(path.as_bstr())?.0;
+            let data = data.to_owned();
+            let data = self.data_ref(&data)?;
+            FileChange::Modify { mode, data, path }
+        } else if let Some(path) = line.strip_prefix(b"D ") {
+            FileChange::Delete {
+                path: self.path(path.as_bstr())?.0,
+            }
+        } else if line == "deleteall" {
+            FileChange::DeleteAll
+        } else if let Some(args) = line.strip_prefix(b"N ") {
+            let (data, commit) = match split_once_space(args.as_bstr()) {
+                (data, Some(commit)) => (data.to_owned(), commit_ish(commit)),
+                _ => return Err(self.malformed("expected 'N <dataref> <commit-ish>'")),
+            };
+            FileChange::NoteModify {
+                data: self.data_ref(&data)?,
+                commit,
+            }
+        } else if let Some((is_copy, args)) = line
+            .strip_prefix(b"C ")
+            .map(|args| (true, args))
+            .or_else(|| line.strip_prefix(b"R ").map(|args| (false, args)))
+        {
+            let (source, consumed) = self.path_with_spaces_quoted(args.as_bstr())?;
+            let destination = match args.get(consumed..).and_then(|rest| rest.strip_prefix(b" ")) {
+                Some(destination) => self.path(destination.as_bstr())?.0,
+                None => return Err(self.malformed("expected '<source> <destination>'")),
+            };
+            if is_copy {
+                FileChange::Copy { source, destination }
+            } else {
+                FileChange::Rename { source, destination }
+            }
+        } else {
+            return Ok(None);
+        }))
+    }
+
+    fn data_ref(&mut self, data: &[u8]) -> Result<DataRef, Error> {
+        Ok(if data == b"inline" {
+            DataRef::Inline(self.data()?)
+        } else if let Some(mark) = data.strip_prefix(b":") {
+            DataRef::Mark(self.mark(mark)?)
+        } else {
+            DataRef::Id(gix_hash::ObjectId::from_hex(data).map_err(|_| self.malformed("expected mark or object id"))?)
+        })
+    }
+
+    /// Parse `path` which extends to the end of the line, unquoting it if necessary.
+    fn path(&self, path: &BStr) -> Result<(BString, usize), Error> {
+        if path.starts_with(b"\"") {
+            let (path, consumed) = gix_quote::ansi_c::undo(path).map_err(|source| Error::Unquote {
+                line_number: self.line_number,
+                line: self.line.clone().into(),
+                source,
+            })?;
+            Ok((path.into_owned(), consumed))
+        } else {
+            Ok((path.to_owned(), path.len()))
+        }
+    }
+
+    /// Like [`path()`](Self::path()), but an unquoted path ends at the first space.
+    fn path_with_spaces_quoted(&self, args: &BStr) -> Result<(BString, usize), Error> {
+        if args.starts_with(b"\"") {
+            self.path(args)
+        } else {
+            self.path(split_once_space(args).0)
+        }
+    }
+
+    fn signature(&self, value: &BStr) -> Result<gix_actor::Signature, Error> {
+        let (lt, gt) = match (value.find_byte(b'<'), value.find_byte(b'>')) {
+            (Some(lt), Some(gt)) if lt < gt => (lt, gt),
+            _ => return Err(self.malformed("expected '[<name> ]<<email>> <when>'")),
+        };
+        let time = parse_raw_time(value[gt + 1..].trim().as_bstr())
+            .ok_or_else(|| self.malformed("expected time in the raw format '<seconds> <+-hhmm>'"))?;
+        Ok(gix_actor::Signature {
+            name: value[..lt].trim().into(),
+            email: value[lt + 1..gt].into(),
+            time,
+        })
+    }
+
+    fn optional_mark(&mut self) -> Result<Option<Mark>, Error> {
+        match self.optional_value(b"mark :")? {
+            Some(mark) => Ok(Some(self.mark(&mark)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn mark(&self, mark: &[u8]) -> Result<Mark, Error> {
+        mark.to_str()
+            .ok()
+            .and_then(|mark| mark.parse().ok())
+            .map(Mark)
+            .ok_or_else(|| self.malformed("expected mark ':<number>'"))
+    }
+
+    fn optional_from(&mut self) -> Result<Option<CommitIsh>, Error> {
+        Ok(self.optional_value(b"from ")?.map(|from| commit_ish(from.as_ref())))
+    }
+
+    /// Return the value of the next line if it starts with `prefix`, or leave the line for the next call if not.
+    fn optional_value(&mut self, prefix: &[u8]) -> Result<Option<BString>, Error> {
+        if !self.next_line()? {
+            return Ok(None);
+        }
+        match self.line.strip_prefix(prefix) {
+            Some(value) => Ok(Some(value.into())),
+            None => {
+                self.line_pending = true;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Parse a `data` command with either an exact byte count or a delimiter.
+    fn data(&mut self) -> Result<BString, Error> {
+        if !self.next_line()? {
+            return Err(Error::UnexpectedEof {
+                line_number: self.line_number,
+                expected: "data",
+            });
+        }
+        let spec = match self.line.strip_prefix(b"data ") {
+            Some(spec) => spec,
+            None => return Err(self.malformed("expected 'data' command")),
+        };
+        let data = if let Some(delimiter) = spec.strip_prefix(b"<<") {
+            let delimiter = delimiter.to_owned();
+            let mut data = BString::default();
+            loop {
+                if !self.next_line()? {
+                    return Err(Error::UnexpectedEof {
+                        line_number: self.line_number,
+                        expected: "data delimiter",
+                    });
+                }
+                if self.line == delimiter {
+                    break;
+                }
+                data.extend_from_slice(&self.line);
+                data.push(b'\n');
+            }
+            data
+        } else {
+            let len: u64 = spec
+                .to_str()
+                .ok()
+                .and_then(|len| len.parse().ok())
+                .ok_or_else(|| self.malformed("expected byte count"))?;
+            let mut data = Vec::new();
+            (&mut self.input).take(len).read_to_end(&mut data)?;
+            if (data.len() as u64) < len {
+                return Err(Error::UnexpectedEof {
+                    line_number: self.line_number,
+                    expected: "data bytes",
+                });
+            }
+            self.line_number += data.iter().filter(|b| **b == b'\n').count();
+            if self.input.fill_buf()?.first() == Some(&b'\n') {
+                self.input.consume(1);
+                self.line_number += 1;
+            }
+            data.into()
+        };
+        Ok(data)
+    }
+
+    fn skip_empty_line(&mut self) -> Result<(), Error> {
+        if self.next_line()? && !self.line.is_empty() {
+            self.line_pending = true;
+        }
+        Ok(())
+    }
+
+    /// Make the next non-comment line available in `self.line`, or return `false` if the end of the stream was reached.
+    fn next_line(&mut self) -> Result<bool, Error> {
+        if self.line_pending {
+            self.line_pending = false;
+            return Ok(true);
+        }
+        loop {
+            self.line.clear();
+            if self.input.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(false);
+            }
+            self.line_number += 1;
+            if self.line.last() == Some(&b'\n') {
+                self.line.pop();
+            }
+            if !self.line.starts_with(b"#") {
+                return Ok(true);
+            }
+        }
+    }
+
+    fn malformed(&self, message: &'static str) -> Error {
+        Error::Malformed {
+            line_number: self.line_number,
+            message,
+            line: self.line.clone().into(),
+        }
+    }
+
+    fn unknown_command(&self) -> Error {
+        Error::UnknownCommand {
+            line_number: self.line_number,
+            line: self.line.clone().into(),
+        }
+    }
+}
+
+fn commit_ish(value: &BStr) -> CommitIsh {
+    if let Some(mark) = value
+        .strip_prefix(b":")
+        .and_then(|mark| mark.to_str().ok())
+        .and_then(|mark| mark.parse().ok())
+    {
+        return CommitIsh::Mark(Mark(mark));
+    }
+    match gix_hash::ObjectId::from_hex(value) {
+        Ok(id) => CommitIsh::Id(id),
+        Err(_) => CommitIsh::Ref(value.to_owned()),
+    }
+}
+
+/// Parse `<seconds> <+-hhmm>`.
+fn parse_raw_time(input: &BStr) -> Option<gix_date::Time> {
+    let (seconds, offset) = split_once_space(input);
+    let seconds: gix_date::SecondsSinceUnixEpoch = seconds.to_str().ok()?.parse().ok()?;
+    let offset = offset?;
+    if offset.len() != 5 {
+        return None;
+    }
+    let sign = match offset[0] {
+        b'-' => gix_date::time::Sign::Minus,
+        b'+' => gix_date::time::Sign::Plus,
+        _ => return None,
+    };
+    let hours: i32 = offset[1..3].to_str().ok()?.parse().ok()?;
+    let minutes: i32 = offset[3..5].to_str().ok()?.parse().ok()?;
+    let mut offset = hours * 3600 + minutes * 60;
+    if sign == gix_date::time::Sign::Minus {
+        offset = -offset;
+    }
+    Some(gix_date::Time { seconds, offset, sign })
+}
+
+fn split_once_space(line: &BStr) -> (&BStr, Option<&BStr>) {
+    match line.find_byte(b' ') {
+        Some(pos) => (line[..pos].as_bstr(), Some(line[pos + 1..].as_bstr())),
+        None => (line, None),
+    }
+}
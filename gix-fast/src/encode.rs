@@ -0,0 +1,231 @@
+use std::{borrow::Cow, io::Write};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{Blob, Command, Commit, CommitIsh, DataRef, FileChange, Mark, Reset, Tag};
+
+/// Output
+impl Command {
+    /// Serialize this command to `out` in the format understood by `git fast-import`.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            Command::Blob(blob) => blob.write_to(out),
+            Command::Commit(commit) => commit.write_to(out),
+            Command::Reset(reset) => reset.write_to(out),
+            Command::Tag(tag) => tag.write_to(out),
+            Command::Checkpoint => out.write_all(b"checkpoint\n\n"),
+            Command::Progress(message) => line(out, b"progress ", message.as_ref()),
+            Command::Done => out.write_all(b"done\n"),
+            Command::Feature { name, value } => {
+                out.write_all(b"feature ")?;
+                out.write_all(name)?;
+                if let Some(value) = value {
+                    out.write_all(b"=")?;
+                    out.write_all(value)?;
+                }
+                out.write_all(b"\n")
+            }
+            Command::Option(option) => line(out, b"option ", option.as_ref()),
+        }
+    }
+}
+
+/// Output
+impl Blob {
+    /// Serialize this instance to `out` as `blob` command.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        out.write_all(b"blob\n")?;
+        write_mark(out, self.mark)?;
+        optional_line(out, b"original-oid ", self.original_oid.as_ref())?;
+        write_data(out, self.data.as_ref())
+    }
+}
+
+/// Output
+impl Commit {
+    /// Serialize this instance to `out` as `commit` command.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        line(out, b"commit ", self.ref_name.as_ref())?;
+        write_mark(out, self.mark)?;
+        optional_line(out, b"original-oid ", self.original_oid.as_ref())?;
+        if let Some(author) = &self.author {
+            write_signature(out, b"author ", author)?;
+        }
+        write_signature(out, b"committer ", &self.committer)?;
+        optional_line(out, b"encoding ", self.encoding.as_ref())?;
+        write_data(out, self.message.as_ref())?;
+        if let Some(from) = &self.from {
+            write_commit_ish(out, b"from ", from)?;
+        }
+        for parent in &self.merge {
+            write_commit_ish(out, b"merge ", parent)?;
+        }
+        for change in &self.changes {
+            change.write_to(out)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+/// Output
+impl Reset {
+    /// Serialize this instance to `out` as `reset` command.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        line(out, b"reset ", self.ref_name.as_ref())?;
+        if let Some(from) = &self.from {
+            write_commit_ish(out, b"from ", from)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+/// Output
+impl Tag {
+    /// Serialize this instance to `out` as `tag` command.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        line(out, b"tag ", self.name.as_ref())?;
+        write_mark(out, self.mark)?;
+        write_commit_ish(out, b"from ", &self.from)?;
+        optional_line(out, b"original-oid ", self.original_oid.as_ref())?;
+        if let Some(tagger) = &self.tagger {
+            write_signature(out, b"tagger ", tagger)?;
+        }
+        write_data(out, self.message.as_ref())
+    }
+}
+
+/// Output
+impl FileChange {
+    /// Serialize this instance to `out` as a single file change line, followed by inline data if present.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            FileChange::Modify { mode, data, path } => {
+                out.write_all(b"M ")?;
+                out.write_all(match mode {
+                    gix_object::tree::EntryKind::Tree => b"040000".as_bstr(),
+                    _ => mode.as_octal_str(),
+                })?;
+                out.write_all(b" ")?;
+                write_data_ref(out, data)?;
+                out.write_all(b" ")?;
+                out.write_all(&quote_path(path.as_ref(), false))?;
+                out.write_all(b"\n")?;
+                write_inline_data(out, data)
+            }
+            FileChange::Delete { path } => line(out, b"D ", quote_path(path.as_ref(), false).as_ref()),
+            FileChange::Copy { source, destination } => write_source_and_destination(out, b"C ", source, destination),
+            FileChange::Rename { source, destination } => write_source_and_destination(out, b"R ", source, destination),
+            FileChange::DeleteAll => out.write_all(b"deleteall\n"),
+            FileChange::NoteModify { data, commit } => {
+                out.write_all(b"N ")?;
+                write_data_ref(out, data)?;
+                out.write_all(b" ")?;
+                write_commit_ish_value(out, commit)?;
+                out.write_all(b"\n")?;
+                write_inline_data(out, data)
+            }
+        }
+    }
+}
+
+fn write_source_and_destination(
+    out: &mut dyn Write,
+    prefix: &[u8],
+    source: &BString,
+    destination: &BString,
+) -> std::io::Result<()> {
+    out.write_all(prefix)?;
+    out.write_all(&quote_path(source.as_ref(), true))?;
+    out.write_all(b" ")?;
+    out.write_all(&quote_path(destination.as_ref(), false))?;
+    out.write_all(b"\n")
+}
+
+fn write_data_ref(out: &mut dyn Write, data: &DataRef) -> std::io::Result<()> {
+    match data {
+        DataRef::Mark(mark) => write_mark_value(out, *mark),
+        DataRef::Id(id) => id.write_hex_to(out),
+        DataRef::Inline(_) => out.write_all(b"inline"),
+    }
+}
+
+fn write_inline_data(out: &mut dyn Write, data: &DataRef) -> std::io::Result<()> {
+    match data {
+        DataRef::Inline(data) => write_data(out, data.as_ref()),
+        DataRef::Mark(_) | DataRef::Id(_) => Ok(()),
+    }
+}
+
+fn write_commit_ish(out: &mut dyn Write, prefix: &[u8], commit: &CommitIsh) -> std::io::Result<()> {
+    out.write_all(prefix)?;
+    write_commit_ish_value(out, commit)?;
+    out.write_all(b"\n")
+}
+
+fn write_commit_ish_value(out: &mut dyn Write, commit: &CommitIsh) -> std::io::Result<()> {
+    match commit {
+        CommitIsh::Mark(mark) => write_mark_value(out, *mark),
+        CommitIsh::Id(id) => id.write_hex_to(out),
+        CommitIsh::Ref(name) => out.write_all(name),
+    }
+}
+
+fn write_mark(out: &mut dyn Write, mark: Option<Mark>) -> std::io::Result<()> {
+    if let Some(mark) = mark {
+        out.write_all(b"mark ")?;
+        write_mark_value(out, mark)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn write_mark_value(out: &mut dyn Write, mark: Mark) -> std::io::Result<()> {
+    write!(out, ":{}", mark.0)
+}
+
+fn write_signature(out: &mut dyn Write, prefix: &[u8], signature: &gix_actor::Signature) -> std::io::Result<()> {
+    out.write_all(prefix)?;
+    signature.write_to(out)?;
+    out.write_all(b"\n")
+}
+
+/// Write `data` with its exact length, followed by the optional newline for readability.
+fn write_data(out: &mut dyn Write, data: &BStr) -> std::io::Result<()> {
+    writeln!(out, "data {}", data.len())?;
+    out.write_all(data)?;
+    out.write_all(b"\n")
+}
+
+fn optional_line(out: &mut dyn Write, prefix: &[u8], value: Option<&BString>) -> std::io::Result<()> {
+    match value {
+        Some(value) => line(out, prefix, value.as_ref()),
+        None => Ok(()),
+    }
+}
+
+fn line(out: &mut dyn Write, prefix: &[u8], value: &BStr) -> std::io::Result<()> {
+    out.write_all(prefix)?;
+    out.write_all(value)?;
+    out.write_all(b"\n")
+}
+
+/// Quote `path` C-style if it would otherwise be ambiguous, which includes paths with spaces if `quote_spaces` is `true`.
+fn quote_path(path: &BStr, quote_spaces: bool) -> Cow<'_, BStr> {
+    let needs_quoting = path.starts_with(b"\"") || path.contains(&b'\n') || (quote_spaces && path.contains(&b' '));
+    if !needs_quoting {
+        return path.into();
+    }
+    let mut out = BString::from("\"");
+    for byte in path.iter().copied() {
+        match byte {
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'"' | b'\\' => {
+                out.push(b'\\');
+                out.push(byte);
+            }
+            _ => out.push(byte),
+        }
+    }
+    out.push(b'"');
+    out.into()
+}
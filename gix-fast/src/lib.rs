@@ -0,0 +1,188 @@
+//! Parse and produce the [fast-import stream format](https://git-scm.com/docs/git-fast-import) as it's emitted by
+//! `git fast-export` and consumed by `git fast-import`, to interoperate with migration tools and to filter repositories.
+//!
+//! Use a [`decode::Parser`] to read [commands][Command] from a stream, and [`Command::write_to()`] to produce one.
+//! [Marks files](marks) as written with `--export-marks` can be read and written as well.
+//! ## Feature Flags
+#![cfg_attr(
+    all(doc, feature = "document-features"),
+    doc = ::document_features::document_features!()
+)]
+#![cfg_attr(all(doc, feature = "document-features"), feature(doc_cfg, doc_auto_cfg))]
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use bstr::BString;
+
+/// A number to refer to an object created earlier in the stream, written as `:<number>`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mark(pub u64);
+
+/// A way to refer to a commit, as used in `from` and `merge`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommitIsh {
+    /// A commit previously created in the stream.
+    Mark(Mark),
+    /// A commit that exists in the object database.
+    Id(gix_hash::ObjectId),
+    /// A ref, or any other revision specification understood by the importer, like `refs/heads/main^0`.
+    Ref(BString),
+}
+
+/// A way to refer to the data of a blob or note, as used in [file changes][FileChange].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataRef {
+    /// A blob previously created in the stream.
+    Mark(Mark),
+    /// A blob that exists in the object database, or a commit if the mode is that of a submodule.
+    Id(gix_hash::ObjectId),
+    /// The data follows right after the file change, and is stored here.
+    Inline(BString),
+}
+
+/// A change to the tree of a [`Commit`], relative to its first parent.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileChange {
+    /// Add or change the file at `path`, i.e. `M <mode> <dataref> <path>`.
+    Modify {
+        /// The kind of entry to create.
+        mode: gix_object::tree::EntryKind,
+        /// The data of the file.
+        data: DataRef,
+        /// The path of the file, relative to the root of the repository.
+        path: BString,
+    },
+    /// Remove the file or directory at `path`, i.e. `D <path>`.
+    Delete {
+        /// The path to remove.
+        path: BString,
+    },
+    /// Copy the file or directory at `source` to `destination`, i.e. `C <source> <destination>`.
+    Copy {
+        /// The path to copy.
+        source: BString,
+        /// The path to copy to.
+        destination: BString,
+    },
+    /// Move the file or directory at `source` to `destination`, i.e. `R <source> <destination>`.
+    Rename {
+        /// The path to move.
+        source: BString,
+        /// The path to move to.
+        destination: BString,
+    },
+    /// Remove all files, i.e. `deleteall`, which is typically followed by all files of the commit.
+    DeleteAll,
+    /// Attach the note stored in `data` to `commit`, i.e. `N <dataref> <commit-ish>`.
+    NoteModify {
+        /// The content of the note.
+        data: DataRef,
+        /// The commit to annotate.
+        commit: CommitIsh,
+    },
+}
+
+/// A blob to create, as introduced by the `blob` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Blob {
+    /// The mark to refer to this blob later in the stream.
+    pub mark: Option<Mark>,
+    /// The id of the blob in the original repository, which is informational only.
+    pub original_oid: Option<BString>,
+    /// The content of the blob.
+    pub data: BString,
+}
+
+/// A commit to create on a branch, as introduced by the `commit` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Commit {
+    /// The full name of the ref to update with the new commit, like `refs/heads/main`.
+    pub ref_name: BString,
+    /// The mark to refer to this commit later in the stream.
+    pub mark: Option<Mark>,
+    /// The id of the commit in the original repository, which is informational only.
+    pub original_oid: Option<BString>,
+    /// The author of the commit, which is the same as the committer if unset.
+    pub author: Option<gix_actor::Signature>,
+    /// The committer of the commit.
+    pub committer: gix_actor::Signature,
+    /// The encoding of the message, if it's not UTF-8.
+    pub encoding: Option<BString>,
+    /// The commit message.
+    pub message: BString,
+    /// The first parent of the commit, which otherwise is the current tip of `ref_name` if it exists.
+    pub from: Option<CommitIsh>,
+    /// All additional parents of the commit.
+    pub merge: Vec<CommitIsh>,
+    /// All changes to apply to the tree of the first parent to obtain the tree of this commit.
+    pub changes: Vec<FileChange>,
+}
+
+/// The creation or reset of a ref, as introduced by the `reset` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reset {
+    /// The full name of the ref to reset, like `refs/heads/main`.
+    pub ref_name: BString,
+    /// The commit to point the ref to, or `None` to have the next commit to the ref start a new history.
+    pub from: Option<CommitIsh>,
+}
+
+/// An annotated tag to create, as introduced by the `tag` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tag {
+    /// The name of the tag without the `refs/tags/` prefix.
+    pub name: BString,
+    /// The mark to refer to this tag later in the stream.
+    pub mark: Option<Mark>,
+    /// The object to tag.
+    pub from: CommitIsh,
+    /// The id of the tag in the original repository, which is informational only.
+    pub original_oid: Option<BString>,
+    /// The creator of the tag, which may be unset for tags of very old repositories.
+    pub tagger: Option<gix_actor::Signature>,
+    /// The tag message.
+    pub message: BString,
+}
+
+/// A single command of a fast-import stream.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    /// Create a blob.
+    Blob(Blob),
+    /// Create a commit.
+    Commit(Commit),
+    /// Create or reset a ref.
+    Reset(Reset),
+    /// Create an annotated tag.
+    Tag(Tag),
+    /// Ask the importer to write out everything it has so far.
+    Checkpoint,
+    /// Ask the importer to print the given message when it reaches this point in the stream.
+    Progress(BString),
+    /// Indicate the end of the stream, which is only required if the `done` feature was requested.
+    Done,
+    /// Require a feature of the importer, i.e. `feature <name>[=<value>]`.
+    Feature {
+        /// The name of the feature, like `done` or `export-marks`.
+        name: BString,
+        /// The argument of the feature, if present.
+        value: Option<BString>,
+    },
+    /// Set an importer option, like `quiet`, without the `--` prefix.
+    Option(BString),
+}
+
+///
+pub mod decode;
+mod encode;
+///
+pub mod marks;
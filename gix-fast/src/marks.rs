@@ -0,0 +1,47 @@
+//! Read and write marks files as used by `--import-marks` and `--export-marks`, with one `:<mark> <id>` line per object.
+use std::io::Write;
+
+use bstr::{BString, ByteSlice};
+
+use crate::Mark;
+
+/// The error returned by [`parse()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number}: expected ':<mark> <id>', got {line:?}")]
+    Malformed { line_number: usize, line: BString },
+}
+
+/// Parse all lines of the marks file in `buf`, returning each mark along with the object it refers to.
+pub fn parse(buf: &[u8]) -> impl Iterator<Item = Result<(Mark, gix_hash::ObjectId), Error>> + '_ {
+    buf.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line_index, line)| {
+            parse_line(line).ok_or_else(|| Error::Malformed {
+                line_number: line_index + 1,
+                line: line.into(),
+            })
+        })
+}
+
+fn parse_line(line: &[u8]) -> Option<(Mark, gix_hash::ObjectId)> {
+    let (mark, id) = line.strip_prefix(b":")?.split_once_str(" ")?;
+    let mark = mark.to_str().ok()?.parse().ok()?;
+    let id = gix_hash::ObjectId::from_hex(id).ok()?;
+    Some((Mark(mark), id))
+}
+
+/// Write all `marks` to `out` in the format understood by [`parse()`].
+pub fn write_to(
+    marks: impl IntoIterator<Item = (Mark, gix_hash::ObjectId)>,
+    out: &mut dyn Write,
+) -> std::io::Result<()> {
+    for (mark, id) in marks {
+        write!(out, ":{} ", mark.0)?;
+        id.write_hex_to(&mut *out)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
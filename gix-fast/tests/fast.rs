@@ -0,0 +1,294 @@
+use bstr::ByteSlice;
+use gix_fast::{decode::Parser, Blob, Command, Commit, CommitIsh, DataRef, FileChange, Mark, Reset, Tag};
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn hex_to_id(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
+}
+
+fn signature(name: &str, email: &str, seconds: i64, offset: i32) -> gix_actor::Signature {
+    gix_actor::Signature {
+        name: name.into(),
+        email: email.into(),
+        time: gix_date::Time::new(seconds, offset),
+    }
+}
+
+fn parse(stream: &str) -> std::result::Result<Vec<Command>, gix_fast::decode::Error> {
+    Parser::new(stream.as_bytes()).collect()
+}
+
+mod decode {
+    use super::*;
+
+    #[test]
+    fn stream_as_produced_by_fast_export() -> crate::Result {
+        let stream = "feature done\n\
+            # a comment\n\
+            blob\n\
+            mark :1\n\
+            data 6\n\
+            hello\n\
+            \n\
+            reset refs/heads/main\n\
+            commit refs/heads/main\n\
+            mark :2\n\
+            original-oid 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3\n\
+            author A U Thor <author@example.com> 1112911993 -0700\n\
+            committer C O Mitter <committer@example.com> 1112912053 +0130\n\
+            data 8\n\
+            initial\n\
+            M 100644 :1 file\n\
+            M 644 inline \"with \\\"quotes\\\"\\n\"\n\
+            data <<EOF\n\
+            line one\n\
+            EOF\n\
+            \n\
+            commit refs/heads/main\n\
+            committer <nobody@example.com> 1112912053 +0000\n\
+            data 0\n\
+            from :2\n\
+            merge 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3\n\
+            merge refs/heads/other^0\n\
+            D file\n\
+            R \"a b\" c d\n\
+            C src dst with spaces\n\
+            deleteall\n\
+            N :1 :2\n\
+            tag v1.0\n\
+            from :2\n\
+            tagger T Agger <tagger@example.com> 1112912053 +0000\n\
+            data 5\n\
+            v1.0\n\
+            \n\
+            reset refs/heads/orphan\n\
+            progress all done\n\
+            checkpoint\n\
+            done\n\
+            blob\n";
+        let commands = parse(stream)?;
+        assert_eq!(
+            commands,
+            vec![
+                Command::Feature {
+                    name: "done".into(),
+                    value: None
+                },
+                Command::Blob(Blob {
+                    mark: Some(Mark(1)),
+                    original_oid: None,
+                    data: "hello\n".into()
+                }),
+                Command::Reset(Reset {
+                    ref_name: "refs/heads/main".into(),
+                    from: None
+                }),
+                Command::Commit(Commit {
+                    ref_name: "refs/heads/main".into(),
+                    mark: Some(Mark(2)),
+                    original_oid: Some("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3".into()),
+                    author: Some(signature("A U Thor", "author@example.com", 1112911993, -7 * 3600)),
+                    committer: signature("C O Mitter", "committer@example.com", 1112912053, 5400),
+                    encoding: None,
+                    message: "initial\n".into(),
+                    from: None,
+                    merge: vec![],
+                    changes: vec![
+                        FileChange::Modify {
+                            mode: gix_object::tree::EntryKind::Blob,
+                            data: DataRef::Mark(Mark(1)),
+                            path: "file".into()
+                        },
+                        FileChange::Modify {
+                            mode: gix_object::tree::EntryKind::Blob,
+                            data: DataRef::Inline("line one\n".into()),
+                            path: "with \"quotes\"\n".into()
+                        }
+                    ]
+                }),
+                Command::Commit(Commit {
+                    ref_name: "refs/heads/main".into(),
+                    mark: None,
+                    original_oid: None,
+                    author: None,
+                    committer: signature("", "nobody@example.com", 1112912053, 0),
+                    encoding: None,
+                    message: "".into(),
+                    from: Some(CommitIsh::Mark(Mark(2))),
+                    merge: vec![
+                        CommitIsh::Id(hex_to_id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3")),
+                        CommitIsh::Ref("refs/heads/other^0".into())
+                    ],
+                    changes: vec![
+                        FileChange::Delete { path: "file".into() },
+                        FileChange::Rename {
+                            source: "a b".into(),
+                            destination: "c d".into()
+                        },
+                        FileChange::Copy {
+                            source: "src".into(),
+                            destination: "dst with spaces".into()
+                        },
+                        FileChange::DeleteAll,
+                        FileChange::NoteModify {
+                            data: DataRef::Mark(Mark(1)),
+                            commit: CommitIsh::Mark(Mark(2))
+                        }
+                    ]
+                }),
+                Command::Tag(Tag {
+                    name: "v1.0".into(),
+                    mark: None,
+                    from: CommitIsh::Mark(Mark(2)),
+                    original_oid: None,
+                    tagger: Some(signature("T Agger", "tagger@example.com", 1112912053, 0)),
+                    message: "v1.0\n".into()
+                }),
+                Command::Reset(Reset {
+                    ref_name: "refs/heads/orphan".into(),
+                    from: None
+                }),
+                Command::Progress("all done".into()),
+                Command::Checkpoint,
+                Command::Done,
+            ],
+            "everything after 'done' is ignored"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors_contain_the_line_number() {
+        let err = parse("blob\ndata 1\na\ncommit refs/heads/main\ndata 0\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 5: expected 'committer' line, got \"data 0\"");
+        let err = parse("\nunknown\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 2: unknown or unsupported command \"unknown\"");
+        let err = parse("blob\ndata 10\nshort").unwrap_err();
+        assert_eq!(err.to_string(), "Line 2: expected \"data bytes\", but the stream ended");
+    }
+}
+
+mod encode {
+    use super::*;
+
+    #[test]
+    fn round_trip() -> crate::Result {
+        let commands = vec![
+            Command::Feature {
+                name: "export-marks".into(),
+                value: Some("marks".into()),
+            },
+            Command::Option("quiet".into()),
+            Command::Blob(Blob {
+                mark: Some(Mark(1)),
+                original_oid: None,
+                data: "binary\0data without trailing newline".into(),
+            }),
+            Command::Commit(Commit {
+                ref_name: "refs/heads/main".into(),
+                mark: Some(Mark(2)),
+                original_oid: Some("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3".into()),
+                author: Some(signature("A U Thor", "author@example.com", 1112911993, -7 * 3600)),
+                committer: signature("C O Mitter", "committer@example.com", 1112912053, 5400),
+                encoding: Some("ISO-8859-1".into()),
+                message: "subject\n\nbody\n".into(),
+                from: Some(CommitIsh::Id(hex_to_id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3"))),
+                merge: vec![CommitIsh::Mark(Mark(3)), CommitIsh::Ref("refs/heads/other".into())],
+                changes: vec![
+                    FileChange::Modify {
+                        mode: gix_object::tree::EntryKind::BlobExecutable,
+                        data: DataRef::Mark(Mark(1)),
+                        path: "\"starts with quote".into(),
+                    },
+                    FileChange::Modify {
+                        mode: gix_object::tree::EntryKind::Commit,
+                        data: DataRef::Id(hex_to_id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3")),
+                        path: "submodule".into(),
+                    },
+                    FileChange::Modify {
+                        mode: gix_object::tree::EntryKind::Link,
+                        data: DataRef::Inline("target".into()),
+                        path: "dir/link".into(),
+                    },
+                    FileChange::Rename {
+                        source: "with space".into(),
+                        destination: "new\nline".into(),
+                    },
+                    FileChange::Copy {
+                        source: "a".into(),
+                        destination: "b c".into(),
+                    },
+                    FileChange::Delete { path: "gone".into() },
+                    FileChange::NoteModify {
+                        data: DataRef::Inline("note".into()),
+                        commit: CommitIsh::Mark(Mark(2)),
+                    },
+                ],
+            }),
+            Command::Tag(Tag {
+                name: "v1.0".into(),
+                mark: Some(Mark(4)),
+                from: CommitIsh::Mark(Mark(2)),
+                original_oid: None,
+                tagger: None,
+                message: "".into(),
+            }),
+            Command::Reset(Reset {
+                ref_name: "refs/tags/lightweight".into(),
+                from: Some(CommitIsh::Mark(Mark(2))),
+            }),
+            Command::Checkpoint,
+            Command::Progress("halfway".into()),
+            Command::Done,
+        ];
+        let mut out = Vec::new();
+        for command in &commands {
+            command.write_to(&mut out)?;
+        }
+        assert_eq!(parse(out.to_str()?)?, commands);
+        Ok(())
+    }
+
+    #[test]
+    fn paths_are_quoted_only_if_needed() -> crate::Result {
+        let mut out = Vec::new();
+        FileChange::Rename {
+            source: "a b".into(),
+            destination: "c d".into(),
+        }
+        .write_to(&mut out)?;
+        FileChange::Delete { path: "\"x\\y".into() }.write_to(&mut out)?;
+        assert_eq!(out.as_bstr(), "R \"a b\" c d\nD \"\\\"x\\\\y\"\n");
+        Ok(())
+    }
+}
+
+mod marks {
+    use super::*;
+
+    #[test]
+    fn round_trip() -> crate::Result {
+        let input = ":1 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3\n\n:42 1f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3\n";
+        let marks = gix_fast::marks::parse(input.as_bytes()).collect::<std::result::Result<Vec<_>, _>>()?;
+        assert_eq!(
+            marks,
+            vec![
+                (Mark(1), hex_to_id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3")),
+                (Mark(42), hex_to_id("1f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3"))
+            ]
+        );
+        let mut out = Vec::new();
+        gix_fast::marks::write_to(marks, &mut out)?;
+        assert_eq!(out.as_bstr(), input.replace("\n\n", "\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_lines_are_errors() {
+        let err = gix_fast::marks::parse(b":1 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3\n1 abc")
+            .find_map(std::result::Result::err)
+            .expect("second line fails");
+        assert_eq!(err.to_string(), "Line 2: expected ':<mark> <id>', got \"1 abc\"");
+    }
+}
@@ -0,0 +1,118 @@
+use crate::eol::Stats;
+
+/// The line endings used in a buffer.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LineEndings {
+    /// There are no line endings at all.
+    None,
+    /// All lines end with `\n`.
+    Lf,
+    /// All lines end with `\r\n`.
+    CrLf,
+    /// Lines end with both `\n` and `\r\n`.
+    Mixed,
+}
+
+impl From<Stats> for LineEndings {
+    fn from(stats: Stats) -> Self {
+        match (stats.lone_lf, stats.crlf) {
+            (0, 0) => LineEndings::None,
+            (_, 0) => LineEndings::Lf,
+            (0, _) => LineEndings::CrLf,
+            _ => LineEndings::Mixed,
+        }
+    }
+}
+
+/// A change to line endings that `git` would make the next time it touches a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Change {
+    /// The object in the index contains `\r\n` line endings which would be normalized to `\n` if the file was added again,
+    /// as with `git add --renormalize`.
+    Renormalize,
+    /// The file in the worktree contains `\r\n` line endings which would be normalized to `\n` when adding it,
+    /// and the result differs from the object in the index, if there is one.
+    ConvertOnAdd,
+    /// The file in the worktree has other line endings than it would have after checking out the object in the index.
+    ConvertOnCheckout {
+        /// The line endings of the file in the worktree.
+        worktree: LineEndings,
+        /// The line endings the file would have after checkout.
+        checkout: LineEndings,
+    },
+}
+
+pub(crate) mod function {
+    use super::{Change, LineEndings};
+    use crate::eol::{self, AttributesDigest, Configuration, Stats};
+
+    /// Determine which line ending [changes](Change) would be made to a file whose end-of-line conversion is configured
+    /// by `digest` and `config`, given the object stored for it in the index as `index_object` and its worktree
+    /// content as `worktree`, if these are available.
+    ///
+    /// `buf` is used as scratch space. An empty list means the file is normalized and its line endings remain as they are.
+    pub fn audit(
+        digest: AttributesDigest,
+        config: Configuration,
+        index_object: Option<&[u8]>,
+        worktree: Option<&[u8]>,
+        buf: &mut Vec<u8>,
+    ) -> Result<Vec<Change>, std::collections::TryReserveError> {
+        let mut changes = Vec::new();
+        if digest == AttributesDigest::Binary {
+            return Ok(changes);
+        }
+        let options = eol::convert_to_git::Options {
+            round_trip_check: None,
+            config,
+        };
+
+        if let Some(index_object) = index_object {
+            if to_git(index_object, digest, buf, &mut |_| Ok(None), options)? {
+                changes.push(Change::Renormalize);
+            }
+        }
+
+        if let Some(worktree) = worktree {
+            let mut index_object_fn = |out: &mut Vec<u8>| {
+                Ok(index_object.map(|index_object| {
+                    out.clear();
+                    out.extend_from_slice(index_object);
+                }))
+            };
+            if to_git(worktree, digest, buf, &mut index_object_fn, options)? && index_object != Some(buf.as_slice()) {
+                changes.push(Change::ConvertOnAdd);
+            }
+
+            if let Some(index_object) = index_object {
+                let checkout = if eol::convert_to_worktree(index_object, digest, buf, config)? {
+                    buf.as_slice()
+                } else {
+                    index_object
+                };
+                let checkout = LineEndings::from(Stats::from_bytes(checkout));
+                let worktree = LineEndings::from(Stats::from_bytes(worktree));
+                if checkout != worktree && checkout != LineEndings::None && worktree != LineEndings::None {
+                    changes.push(Change::ConvertOnCheckout { worktree, checkout });
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    fn to_git(
+        src: &[u8],
+        digest: AttributesDigest,
+        buf: &mut Vec<u8>,
+        index_object: &mut eol::convert_to_git::IndexObjectFn<'_>,
+        options: eol::convert_to_git::Options<'_>,
+    ) -> Result<bool, std::collections::TryReserveError> {
+        match eol::convert_to_git(src, digest, buf, index_object, options) {
+            Ok(changed) => Ok(changed),
+            Err(eol::convert_to_git::Error::OutOfMemory(err)) => Err(err),
+            Err(eol::convert_to_git::Error::RoundTrip { .. } | eol::convert_to_git::Error::FetchObjectFromIndex(_)) => {
+                unreachable!("round-trip checks are disabled and the index object is provided infallibly")
+            }
+        }
+    }
+}
@@ -5,6 +5,10 @@ pub use convert_to_git::function::convert_to_git;
 mod convert_to_worktree;
 pub use convert_to_worktree::convert_to_worktree;
 
+///
+pub mod audit;
+pub use audit::function::audit;
+
 mod utils;
 
 /// The kind of end of lines to set.
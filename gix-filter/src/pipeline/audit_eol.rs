@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use bstr::{BStr, BString};
+use gix_attributes::StateRef;
+
+use crate::{
+    eol,
+    pipeline::{convert::configuration, util::Configuration},
+    Pipeline,
+};
+
+/// The error returned by [Pipeline::audit_eol()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Configuration(#[from] configuration::Error),
+    #[error("Could not allocate buffer")]
+    OutOfMemory(#[from] std::collections::TryReserveError),
+}
+
+/// An attribute that affects end-of-line conversion, along with the place it was defined at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    /// The attribute as it was assigned, like `text=auto` or `-crlf`.
+    pub assignment: gix_attributes::Assignment,
+    /// The pattern that matched the path, like `*.txt`.
+    pub pattern: BString,
+    /// The file the pattern was read from, or `None` if it was provided by other means.
+    pub path: Option<PathBuf>,
+    /// The line at which the pattern was found in its `path`.
+    pub line_number: usize,
+}
+
+/// The outcome of [Pipeline::audit_eol()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// What the `text`, `crlf` and `eol` attributes say about end-of-line conversion, or `None` if none of them is set
+    /// and configuration like `core.autocrlf` decides.
+    pub attributes_digest: Option<eol::AttributesDigest>,
+    /// The end-of-line conversion that is actually performed, which takes configuration into account.
+    pub digest: eol::AttributesDigest,
+    /// The `crlf`, `eol` and `text` attributes that were set for the path, in that order.
+    pub sources: Vec<Source>,
+    /// The changes to line endings that would be made the next time the file is added or checked out.
+    pub changes: Vec<eol::audit::Change>,
+}
+
+/// Access
+impl Pipeline {
+    /// Determine which changes to line endings would be made to the file at `rela_path` the next time it's added or checked out,
+    /// given the object stored for it in the index as `index_object` and its content in the worktree as `worktree`,
+    /// if these are available. `attributes` is used to obtain the attributes at `rela_path`.
+    ///
+    /// The returned [Outcome] also tells which attributes caused the conversion and where they are defined.
+    /// Note that only end-of-line conversions are considered, and filter drivers or working-tree encodings are ignored.
+    pub fn audit_eol(
+        &mut self,
+        rela_path: &BStr,
+        attributes: &mut dyn FnMut(&BStr, &mut gix_attributes::search::Outcome),
+        index_object: Option<&[u8]>,
+        worktree: Option<&[u8]>,
+    ) -> Result<Outcome, Error> {
+        let Configuration {
+            attr_digest, digest, ..
+        } = Configuration::at_path(
+            rela_path,
+            &self.options.drivers,
+            &mut self.attrs,
+            attributes,
+            self.options.eol_config,
+        )?;
+
+        const EOL_ATTRS: [&str; 3] = ["crlf", "eol", "text"];
+        let sources = self
+            .attrs
+            .iter_selected()
+            .filter(|m| {
+                EOL_ATTRS.contains(&m.assignment.name.as_str()) && !matches!(m.assignment.state, StateRef::Unspecified)
+            })
+            .map(|m| Source {
+                assignment: m.assignment.to_owned(),
+                pattern: m.pattern.to_string().into(),
+                path: m.location.source.map(ToOwned::to_owned),
+                line_number: m.location.sequence_number,
+            })
+            .collect();
+
+        self.bufs.clear();
+        let changes = eol::audit(
+            digest,
+            self.options.eol_config,
+            index_object,
+            worktree,
+            &mut self.bufs.dest,
+        )?;
+        Ok(Outcome {
+            attributes_digest: attr_digest,
+            digest,
+            sources,
+            changes,
+        })
+    }
+}
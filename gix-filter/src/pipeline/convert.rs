@@ -80,7 +80,7 @@ impl Pipeline {
         let Configuration {
             driver,
             digest,
-            _attr_digest: _,
+            attr_digest: _,
             encoding,
             apply_ident_filter,
         } = Configuration::at_path(
@@ -182,7 +182,7 @@ impl Pipeline {
         let Configuration {
             driver,
             digest,
-            _attr_digest: _,
+            attr_digest: _,
             encoding,
             apply_ident_filter,
         } = Configuration::at_path(
@@ -109,6 +109,8 @@ impl Pipeline {
     }
 }
 
+///
+pub mod audit_eol;
 ///
 pub mod convert;
 
@@ -14,7 +14,7 @@ use crate::{
 pub(crate) struct Configuration<'a> {
     pub(crate) driver: Option<&'a Driver>,
     /// What attributes say about CRLF handling.
-    pub(crate) _attr_digest: Option<eol::AttributesDigest>,
+    pub(crate) attr_digest: Option<eol::AttributesDigest>,
     /// The final digest that includes configuration values
     pub(crate) digest: eol::AttributesDigest,
     pub(crate) encoding: Option<&'static encoding_rs::Encoding>,
@@ -128,7 +128,7 @@ impl<'driver> Configuration<'driver> {
 
         Ok(Configuration {
             driver,
-            _attr_digest: attr_digest,
+            attr_digest,
             digest: digest.expect("always set by now"),
             encoding,
             apply_ident_filter,
@@ -0,0 +1,146 @@
+use gix_filter::{
+    eol,
+    eol::{
+        audit::{Change, LineEndings},
+        AttributesDigest, Configuration, Mode,
+    },
+};
+
+fn crlf() -> Configuration {
+    Configuration {
+        auto_crlf: eol::AutoCrlf::Disabled,
+        eol: Some(Mode::CrLf),
+    }
+}
+
+#[test]
+fn binary_files_are_never_changed() -> crate::Result {
+    let mut buf = Vec::new();
+    let changes = eol::audit(
+        AttributesDigest::Binary,
+        crlf(),
+        Some(b"a\r\nb\r\n"),
+        Some(b"a\nb\r\n"),
+        &mut buf,
+    )?;
+    assert_eq!(changes, Vec::new());
+    Ok(())
+}
+
+#[test]
+fn normalized_files_are_not_changed() -> crate::Result {
+    let mut buf = Vec::new();
+    let changes = eol::audit(
+        AttributesDigest::Text,
+        crlf(),
+        Some(b"a\nb\n"),
+        Some(b"a\r\nb\r\n"),
+        &mut buf,
+    )?;
+    assert_eq!(changes, Vec::new(), "the worktree has the line endings of a checkout");
+
+    let changes = eol::audit(AttributesDigest::Text, crlf(), Some(b"a\nb\n"), None, &mut buf)?;
+    assert_eq!(changes, Vec::new(), "the index alone can be checked");
+
+    let changes = eol::audit(AttributesDigest::TextInput, crlf(), None, Some(b"a\nb\n"), &mut buf)?;
+    assert_eq!(changes, Vec::new(), "the worktree alone can be checked");
+    Ok(())
+}
+
+#[test]
+fn crlf_in_index_would_be_renormalized() -> crate::Result {
+    let mut buf = Vec::new();
+    let changes = eol::audit(
+        AttributesDigest::Text,
+        crlf(),
+        Some(b"a\r\nb\r\n"),
+        Some(b"a\r\nb\r\n"),
+        &mut buf,
+    )?;
+    assert_eq!(
+        changes,
+        [Change::Renormalize, Change::ConvertOnAdd],
+        "the file in the worktree is converted, too, as the index doesn't contain lone LFs"
+    );
+
+    let changes = eol::audit(
+        AttributesDigest::TextAuto,
+        Configuration {
+            auto_crlf: eol::AutoCrlf::Input,
+            eol: None,
+        },
+        Some(b"a\r\nb\r\n"),
+        Some(b"a\r\nb\r\n"),
+        &mut buf,
+    )?;
+    assert_eq!(
+        changes,
+        [Change::Renormalize],
+        "with 'auto', files that already have CRLF in the index are left alone when added"
+    );
+    Ok(())
+}
+
+#[test]
+fn mismatching_line_endings_in_worktree_would_change_on_checkout() -> crate::Result {
+    let mut buf = Vec::new();
+    let changes = eol::audit(
+        AttributesDigest::TextInput,
+        crlf(),
+        Some(b"a\nb\n"),
+        Some(b"a\r\nb\n"),
+        &mut buf,
+    )?;
+    assert_eq!(
+        changes,
+        [Change::ConvertOnCheckout {
+            worktree: LineEndings::Mixed,
+            checkout: LineEndings::Lf,
+        }],
+        "adding the file would yield the object in the index"
+    );
+
+    let changes = eol::audit(
+        AttributesDigest::TextInput,
+        crlf(),
+        Some(b"a\nb\n"),
+        Some(b"a\r\nc\n"),
+        &mut buf,
+    )?;
+    assert_eq!(
+        changes,
+        [
+            Change::ConvertOnAdd,
+            Change::ConvertOnCheckout {
+                worktree: LineEndings::Mixed,
+                checkout: LineEndings::Lf,
+            }
+        ],
+        "the line endings of changed files are normalized"
+    );
+
+    let changes = eol::audit(
+        AttributesDigest::TextCrlf,
+        crlf(),
+        Some(b"a\nb\n"),
+        Some(b"a\nb\n"),
+        &mut buf,
+    )?;
+    assert_eq!(
+        changes,
+        [Change::ConvertOnCheckout {
+            worktree: LineEndings::Lf,
+            checkout: LineEndings::CrLf,
+        }]
+    );
+
+    let changes = eol::audit(
+        AttributesDigest::TextCrlf,
+        crlf(),
+        Some(b"a\nb\n"),
+        Some(b"ab"),
+        &mut buf,
+    )?;
+    assert_eq!(changes, Vec::new(), "files without line endings can't mismatch");
+    Ok(())
+}
@@ -21,5 +21,6 @@ mod stats {
     }
 }
 
+mod audit;
 pub(crate) mod convert_to_git;
 mod convert_to_worktree;
@@ -0,0 +1,86 @@
+use gix_filter::{
+    eol::{
+        audit::{Change, LineEndings},
+        AttributesDigest,
+    },
+    pipeline::CrlfRoundTripCheck,
+};
+
+use crate::pipeline::pipeline;
+
+#[test]
+fn attributes_and_their_sources_are_reported() -> gix_testtools::Result {
+    let (mut cache, mut pipe) = pipeline("all-filters", || {
+        (vec![], Vec::new(), CrlfRoundTripCheck::Skip, Default::default())
+    })?;
+
+    let out = pipe.audit_eol(
+        "any.txt".into(),
+        &mut |path, attrs| {
+            cache
+                .at_entry(path, None, &gix_object::find::Never)
+                .expect("cannot fail")
+                .matching_attributes(attrs);
+        },
+        Some(b"a\r\nb\r\n"),
+        Some(b"a\r\nb\r\n"),
+    )?;
+    assert_eq!(out.attributes_digest, Some(AttributesDigest::TextAutoCrlf));
+    assert_eq!(out.digest, AttributesDigest::TextAutoCrlf);
+    assert_eq!(out.changes, [Change::Renormalize]);
+
+    let sources: Vec<_> = out
+        .sources
+        .iter()
+        .map(|s| {
+            (
+                s.assignment.as_ref().to_string(),
+                s.pattern.to_string(),
+                s.path
+                    .as_deref()
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().into_owned()),
+                s.line_number,
+            )
+        })
+        .collect();
+    let source = |assignment: &str| {
+        (
+            assignment.to_string(),
+            "*".to_string(),
+            Some(".gitattributes".to_string()),
+            1,
+        )
+    };
+    assert_eq!(sources, [source("eol=crlf"), source("text=auto")]);
+    Ok(())
+}
+
+#[test]
+fn configuration_is_used_without_attributes() -> gix_testtools::Result {
+    let (_cache, mut pipe) = pipeline("no-filters", || {
+        (
+            vec![],
+            Vec::new(),
+            CrlfRoundTripCheck::Skip,
+            gix_filter::eol::Configuration {
+                auto_crlf: gix_filter::eol::AutoCrlf::Input,
+                eol: None,
+            },
+        )
+    })?;
+
+    let out = pipe.audit_eol("any.txt".into(), &mut |_, _| {}, Some(b"a\nb\n"), Some(b"a\r\nb\r\n"))?;
+    assert_eq!(out.attributes_digest, None);
+    assert_eq!(out.digest, AttributesDigest::TextAutoInput);
+    assert!(out.sources.is_empty());
+    assert_eq!(
+        out.changes,
+        [Change::ConvertOnCheckout {
+            worktree: LineEndings::CrLf,
+            checkout: LineEndings::Lf,
+        }],
+        "the worktree would be normalized when checked out"
+    );
+    Ok(())
+}
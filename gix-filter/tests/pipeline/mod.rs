@@ -2,6 +2,7 @@ use bstr::ByteSlice;
 use gix_attributes::glob::pattern::Case;
 use gix_filter::eol;
 
+mod audit_eol;
 mod convert_to_git;
 mod convert_to_worktree;
 
@@ -42,7 +42,8 @@ impl Default for Capabilities {
 impl Capabilities {
     /// try to determine all values in this context by probing them in the given `git_dir`, which
     /// should be on the file system the git repository is located on.
-    /// `git_dir` is a typical git repository, expected to be populated with the typical files like `config`.
+    /// `git_dir` may be any writable directory, like the `.git` directory of a repository or the root of its worktree,
+    /// as all probes are performed on temporary files which are removed afterwards.
     ///
     /// All errors are ignored and interpreted on top of the default for the platform the binary is compiled for.
     pub fn probe(git_dir: &Path) -> Self {
@@ -77,14 +78,26 @@ impl Capabilities {
         Ok(false)
     }
 
-    fn probe_ignore_case(git_dir: &Path) -> std::io::Result<bool> {
-        std::fs::metadata(git_dir.join("cOnFiG")).map(|_| true).or_else(|err| {
-            if err.kind() == std::io::ErrorKind::NotFound {
-                Ok(false)
-            } else {
-                Err(err)
-            }
-        })
+    fn probe_ignore_case(root: &Path) -> std::io::Result<bool> {
+        let rand = fastrand::usize(..);
+        let lowercase = root.join(format!("_test_ignore_case{rand}"));
+        std::fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&lowercase)?;
+        let res = root
+            .join(format!("_TEST_IGNORE_CASE{rand}"))
+            .symlink_metadata()
+            .map(|_| true)
+            .or_else(|err| {
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    Ok(false)
+                } else {
+                    Err(err)
+                }
+            });
+        std::fs::remove_file(lowercase)?;
+        res
     }
 
     fn probe_precompose_unicode(root: &Path) -> std::io::Result<bool> {
@@ -0,0 +1,223 @@
+//! Assure written files are persisted to storage, as configured by `core.fsync` and `core.fsyncMethod`.
+use std::path::Path;
+
+/// The way written data is flushed to storage, as configured by `core.fsyncMethod`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    /// Flush data and metadata of each file all the way to storage, including the write cache of the disk where the
+    /// platform supports it.
+    #[default]
+    Fsync,
+    /// Only make the operating system write out its cached data of each file, without flushing the write cache of the disk.
+    ///
+    /// This is faster, but data may still be lost on power failure.
+    /// Where this isn't supported, data is flushed to storage.
+    WriteoutOnly,
+    /// Use [`WriteoutOnly`](Method::WriteoutOnly) for each loose object and flush the write cache of the disk only once
+    /// at the end of a batch of objects, which is much faster when writing many objects on some filesystems.
+    /// All other files are handled like with [`Fsync`](Method::Fsync).
+    Batch,
+}
+
+/// A kind of file that may be synced, as named in `core.fsync`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    /// Each loose object.
+    LooseObject,
+    /// Pack data files.
+    Pack,
+    /// Pack indices, reverse indices and other metadata files of packs.
+    PackMetadata,
+    /// The commit-graph file.
+    CommitGraph,
+    /// The index file.
+    Index,
+    /// Loose references, `packed-refs` and reference logs.
+    Reference,
+}
+
+impl Component {
+    /// All components, in the order they are documented by `git`.
+    pub const ALL: [Component; 6] = [
+        Component::LooseObject,
+        Component::Pack,
+        Component::PackMetadata,
+        Component::CommitGraph,
+        Component::Index,
+        Component::Reference,
+    ];
+
+    /// Return the name of this component as used in `core.fsync`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Component::LooseObject => "loose-object",
+            Component::Pack => "pack",
+            Component::PackMetadata => "pack-metadata",
+            Component::CommitGraph => "commit-graph",
+            Component::Index => "index",
+            Component::Reference => "reference",
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+/// A set of [components](Component) whose files should be synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Components(u8);
+
+impl Components {
+    /// No component is synced.
+    pub const NONE: Components = Components(0);
+
+    /// Return a set with all components.
+    pub fn all() -> Self {
+        Component::ALL.into_iter().collect()
+    }
+
+    /// Return `true` if `component` is contained in this set.
+    pub fn contains(&self, component: Component) -> bool {
+        self.0 & component.bit() != 0
+    }
+
+    /// Add `component` to this set.
+    pub fn insert(&mut self, component: Component) {
+        self.0 |= component.bit();
+    }
+
+    /// Remove `component` from this set.
+    pub fn remove(&mut self, component: Component) {
+        self.0 &= !component.bit();
+    }
+
+    /// Return all components in this set.
+    pub fn iter(&self) -> impl Iterator<Item = Component> + '_ {
+        Component::ALL.into_iter().filter(|c| self.contains(*c))
+    }
+}
+
+impl Default for Components {
+    /// Like `git`, sync everything that is written only rarely and usually in bulk, that is packs and their metadata,
+    /// and the commit-graph.
+    fn default() -> Self {
+        [Component::Pack, Component::PackMetadata, Component::CommitGraph]
+            .into_iter()
+            .collect()
+    }
+}
+
+impl FromIterator<Component> for Components {
+    fn from_iter<T: IntoIterator<Item = Component>>(iter: T) -> Self {
+        let mut out = Components::NONE;
+        for component in iter {
+            out.insert(component);
+        }
+        out
+    }
+}
+
+/// Which files to sync and how, as configured by `core.fsync` and `core.fsyncMethod`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Config {
+    /// How to sync files.
+    pub method: Method,
+    /// The kinds of files to sync.
+    pub components: Components,
+}
+
+impl Config {
+    /// Return the method to use when writing files of `component`, or `None` if these shouldn't be synced.
+    pub fn method_for(&self, component: Component) -> Option<Method> {
+        self.components.contains(component).then_some(self.method)
+    }
+}
+
+/// Flush `file` to storage using `method`.
+///
+/// [`Batch`](Method::Batch) is the same as [`Fsync`](Method::Fsync) here, as only writers of loose objects can
+/// take advantage of it by calling [`writeout()`] for each file and [`flush_batch()`] once at the end.
+pub fn file(file: &std::fs::File, method: Method) -> std::io::Result<()> {
+    match method {
+        Method::Fsync | Method::Batch => file.sync_all(),
+        Method::WriteoutOnly => writeout(file),
+    }
+}
+
+/// Flush the entries of `directory` to storage using `method`, which is needed for files that were created, renamed or
+/// deleted within it to survive power loss.
+///
+/// This does nothing on platforms that don't support syncing directories, like Windows.
+pub fn directory(directory: &Path, method: Method) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        file(&std::fs::File::open(directory)?, method)
+    }
+    #[cfg(not(unix))]
+    {
+        _ = (directory, method);
+        Ok(())
+    }
+}
+
+/// Make the operating system write out the cached data of `file`, without necessarily flushing the write cache of the disk,
+/// or flush it to storage if that's not supported on this platform.
+pub fn writeout(file: &std::fs::File) -> std::io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use std::os::unix::io::AsRawFd;
+        // SAFETY: the file descriptor is valid for as long as `file` is borrowed, and no memory is passed.
+        #[allow(unsafe_code)]
+        let res = unsafe {
+            libc::sync_file_range(
+                file.as_raw_fd(),
+                0,
+                0,
+                libc::SYNC_FILE_RANGE_WAIT_BEFORE | libc::SYNC_FILE_RANGE_WRITE | libc::SYNC_FILE_RANGE_WAIT_AFTER,
+            )
+        };
+        match res {
+            0 => Ok(()),
+            _ => {
+                let err = std::io::Error::last_os_error();
+                match err.raw_os_error() {
+                    // The filesystem doesn't support it.
+                    Some(libc::ENOSYS | libc::EINVAL) => file.sync_data(),
+                    _ => Err(err),
+                }
+            }
+        }
+    }
+    #[cfg(target_vendor = "apple")]
+    {
+        use std::os::unix::io::AsRawFd;
+        // A plain `fsync()` doesn't flush the write cache of the disk here, unlike `File::sync_all()`
+        // which uses `F_FULLFSYNC`.
+        // SAFETY: the file descriptor is valid for as long as `file` is borrowed, and no memory is passed.
+        #[allow(unsafe_code)]
+        let res = unsafe { libc::fsync(file.as_raw_fd()) };
+        match res {
+            0 => Ok(()),
+            _ => Err(std::io::Error::last_os_error()),
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+    {
+        file.sync_data()
+    }
+}
+
+/// End a batch of files that were only [written out](writeout()) by flushing the write cache of the disk, which holds
+/// files in the `directory` that is to contain them.
+///
+/// This is done by syncing a new temporary file in `directory`, which relies on the filesystem to flush the write
+/// cache of the disk in the process.
+pub fn flush_batch(directory: &Path) -> std::io::Result<()> {
+    let path = directory.join(format!("bulk_fsync_{:016x}", fastrand::u64(..)));
+    let file = std::fs::OpenOptions::new().write(true).create_new(true).open(&path)?;
+    let res = file.sync_all();
+    drop(file);
+    std::fs::remove_file(&path).ok();
+    res
+}
@@ -1,6 +1,6 @@
 //! A crate with file-system specific utilities.
 #![deny(rust_2018_idioms, missing_docs)]
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 
 use std::path::PathBuf;
 
@@ -45,6 +45,9 @@ pub use read_dir::function::read_dir;
 ///
 pub mod dir;
 
+///
+pub mod fsync;
+
 /// Like [`std::env::current_dir()`], but it will `precompose_unicode` if that value is true, if the current directory
 /// is valid unicode and if there are decomposed unicode codepoints.
 ///
@@ -21,6 +21,25 @@ fn probe() {
     }
 }
 
+#[test]
+fn probe_without_git_configuration_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let with_config = tempfile::tempdir().unwrap();
+    std::fs::File::create(with_config.path().join("config")).unwrap();
+
+    let caps = gix_fs::Capabilities::probe(dir.path());
+    assert_eq!(
+        caps,
+        gix_fs::Capabilities::probe(with_config.path()),
+        "probing doesn't rely on the presence of any file, so worktrees can be probed as well"
+    );
+    assert_eq!(
+        std::fs::read_dir(dir.path()).unwrap().count(),
+        0,
+        "there should be no left-over files after probing"
+    );
+}
+
 #[test]
 fn parallel_probe() {
     let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use gix_fs::fsync::{Component, Components, Config, Method};
+
+#[test]
+fn components() {
+    let default = Components::default();
+    assert_eq!(
+        default.iter().collect::<Vec<_>>(),
+        [Component::Pack, Component::PackMetadata, Component::CommitGraph],
+        "like git, only what is written rarely is synced by default"
+    );
+    assert_eq!(Components::all().iter().collect::<Vec<_>>(), Component::ALL);
+    assert_eq!(Components::NONE.iter().count(), 0);
+
+    let mut components = Components::NONE;
+    components.insert(Component::Reference);
+    components.insert(Component::Index);
+    components.remove(Component::Index);
+    assert!(components.contains(Component::Reference));
+    assert!(!components.contains(Component::Index));
+}
+
+#[test]
+fn config_method_for() {
+    let config = Config {
+        method: Method::Batch,
+        components: [Component::LooseObject].into_iter().collect(),
+    };
+    assert_eq!(config.method_for(Component::LooseObject), Some(Method::Batch));
+    assert_eq!(config.method_for(Component::Reference), None);
+    assert_eq!(Config::default().method_for(Component::Pack), Some(Method::Fsync));
+}
+
+#[test]
+fn all_methods_work_and_batches_leave_no_trace() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("file");
+    let mut file = std::fs::File::create(&path)?;
+    file.write_all(b"content")?;
+    for method in [Method::Fsync, Method::WriteoutOnly, Method::Batch] {
+        gix_fs::fsync::file(&file, method)?;
+    }
+    gix_fs::fsync::writeout(&file)?;
+    drop(file);
+
+    gix_fs::fsync::flush_batch(dir.path())?;
+    let entries: Vec<_> = std::fs::read_dir(dir.path())?
+        .map(|e| e.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(entries, ["file"], "the file used to flush the batch is removed");
+    assert_eq!(std::fs::read(&path)?, b"content");
+    Ok(())
+}
+
+#[test]
+fn directories_can_be_synced_with_all_methods() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(dir.path().join("file"), b"content")?;
+    for method in [Method::Fsync, Method::WriteoutOnly, Method::Batch] {
+        gix_fs::fsync::directory(dir.path(), method)?;
+    }
+    assert!(
+        gix_fs::fsync::directory(&dir.path().join("missing"), Method::Fsync).is_err() || cfg!(not(unix)),
+        "directories must exist"
+    );
+    Ok(())
+}
@@ -68,6 +68,65 @@ where
         Ok(())
     }
 
+    /// Mark `oid` as known to be connected, so that it and all objects reachable from it are skipped by
+    /// [`check_reachable()`](Self::check_reachable()).
+    ///
+    /// Return `true` if `oid` wasn't seen before.
+    pub fn mark_connected(&mut self, oid: ObjectId) -> bool {
+        self.seen.insert(oid)
+    }
+
+    /// Run the connectivity check on the object `oid` of `kind` and everything reachable from it.
+    ///
+    /// ### Algorithm
+    ///
+    /// Follow the parents and the tree of commits, the targets of tags and the entries of trees, and verify all of
+    /// these objects exist in the ODB.
+    /// Objects previously encountered by this instance or [marked as connected](Self::mark_connected()) are skipped
+    /// along with all objects reachable from them.
+    /// Any missing object, including `oid` itself, results in a call to the `missing_cb` along with the kind it is
+    /// expected to have.
+    /// Objects that exist but can't be decoded cause an error to be returned.
+    pub fn check_reachable(
+        &mut self,
+        oid: &ObjectId,
+        kind: Kind,
+    ) -> Result<(), gix_object::find::existing_object::Error> {
+        use gix_object::find::existing_object::Error;
+        let mut objects = vec![(*oid, kind)];
+        while let Some((oid, kind)) = objects.pop() {
+            if !self.seen.insert(oid) {
+                continue;
+            }
+            let res = match kind {
+                Kind::Commit => self.db.find_commit(&oid, &mut self.buf).map(|commit| {
+                    objects.extend(commit.parents().map(|id| (id, Kind::Commit)));
+                    objects.push((commit.tree(), Kind::Tree));
+                }),
+                Kind::Tag => self
+                    .db
+                    .find_tag(&oid, &mut self.buf)
+                    .map(|tag| objects.push((tag.target(), tag.target_kind))),
+                Kind::Tree => {
+                    let mut tree_ids = VecDeque::new();
+                    self.check_tree(&oid, &mut tree_ids);
+                    objects.extend(tree_ids.into_iter().map(|id| (id, Kind::Tree)));
+                    Ok(())
+                }
+                Kind::Blob => {
+                    check_blob(&self.db, &oid, &mut self.missing_cb);
+                    Ok(())
+                }
+            };
+            match res {
+                Ok(()) => {}
+                Err(Error::NotFound { .. }) => (self.missing_cb)(&oid, kind),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
     /// Blobs are checked right away, trees are stored in `tree_ids` for the parent to iterate them, and only
     /// if they have not been `seen` yet.
     fn check_tree(&mut self, oid: &ObjectId, tree_ids: &mut VecDeque<ObjectId>) {
@@ -82,3 +82,81 @@ fn missing_trees() {
     );
     assert_eq!(check_missing("treeless", all_commits()), expected);
 }
+
+mod check_reachable {
+    use gix_fsck::Connectivity;
+    use gix_hash::ObjectId;
+    use gix_hashtable::HashMap;
+    use gix_object::Kind;
+
+    use super::{all_commits, hex_to_objects};
+
+    fn check_missing(
+        repo_name: &str,
+        connected: impl IntoIterator<Item = ObjectId>,
+        tips: impl IntoIterator<Item = (ObjectId, Kind)>,
+    ) -> HashMap<ObjectId, Kind> {
+        let fixture_path = gix_testtools::scripted_fixture_read_only("make_test_repos.sh")
+            .expect("fixture path")
+            .join(repo_name)
+            .join(".git")
+            .join("objects");
+        let db = gix_odb::at(fixture_path).expect("valid odb");
+
+        let mut missing: HashMap<ObjectId, Kind> = HashMap::default();
+        let mut check = Connectivity::new(db, |oid: &ObjectId, kind: Kind| {
+            missing.try_insert(*oid, kind).expect("no duplicate oid");
+        });
+        for id in connected {
+            check.mark_connected(id);
+        }
+        for (tip, kind) in tips {
+            check.check_reachable(&tip, kind).expect("no decode errors");
+        }
+        missing
+    }
+
+    fn tip() -> (ObjectId, Kind) {
+        (all_commits()[0], Kind::Commit)
+    }
+
+    #[test]
+    fn parents_are_followed() {
+        assert_eq!(check_missing("base", None, Some(tip())), HashMap::default());
+        assert_eq!(
+            check_missing("blobless", None, Some(tip())),
+            super::check_missing("blobless", all_commits()),
+            "the tip reaches all missing objects through its parents"
+        );
+    }
+
+    #[test]
+    fn connected_objects_are_skipped() {
+        let missing_blobs = hex_to_objects(
+            [
+                "4cdeaab5b01f9a9fbbb2fb6c08404cf12b7bdab1",
+                "c18147dc648481eeb65dc5e66628429a64843327",
+            ],
+            Kind::Blob,
+        );
+        assert_eq!(check_missing("blobless", None, Some(tip())), missing_blobs);
+        assert_eq!(
+            check_missing("blobless", missing_blobs.keys().copied(), Some(tip())),
+            HashMap::default()
+        );
+        assert_eq!(
+            check_missing("blobless", all_commits().iter().copied(), Some(tip())),
+            HashMap::default(),
+            "nothing is reachable from connected commits"
+        );
+    }
+
+    #[test]
+    fn missing_tips_are_reported_with_their_expected_kind() {
+        let missing = gix_hash::Kind::Sha1.null();
+        assert_eq!(
+            check_missing("base", None, [(missing, Kind::Tag)]),
+            [(missing, Kind::Tag)].into_iter().collect()
+        );
+    }
+}
@@ -7,6 +7,10 @@
 use gix_hash::ObjectId;
 pub use hashbrown::{hash_map, hash_set, raw, Equivalent};
 
+///
+pub mod set;
+pub use set::ObjectIdSet;
+
 /// thread-safe types
 pub mod sync {
     /// A map for associating data with object ids in a thread-safe fashion. It should scale well up to 256 threads.
@@ -0,0 +1,157 @@
+use gix_hash::{oid, ObjectId};
+
+use crate::HashSet;
+
+/// The amount of `u64` words needed to have one bit for each possible value of the first two bytes of an object id.
+const PREFIX_WORDS: usize = (1 << 16) / 64;
+
+/// A set of object ids optimized for the large amounts of ids that are kept while traversing commit graphs
+/// or during negotiation, like *seen* or *common* sets.
+///
+/// Next to a [`HashSet`] with the ids themselves, it keeps a bitmap with one bit for each possible value of the
+/// first two bytes of an object id. It's used to answer most lookups of ids that aren't contained without touching
+/// the hash table, which is the common case when traversing into unknown territory.
+/// The bitmap takes `8KiB` and is only allocated once the first id is inserted.
+///
+/// Note that removing ids won't clear their bits in the bitmap, which keeps lookups correct but may make them slower.
+#[derive(Default, Clone)]
+pub struct ObjectIdSet {
+    /// One bit for each possible value of the first two bytes of an object id, set if an id with these bytes was inserted.
+    prefixes: Vec<u64>,
+    ids: HashSet<ObjectId>,
+}
+
+/// Lifecycle
+impl ObjectIdSet {
+    /// Create a new empty instance which doesn't allocate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new empty instance which can hold at least `capacity` ids without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ObjectIdSet {
+            prefixes: Vec::new(),
+            ids: HashSet::with_capacity_and_hasher(capacity, Default::default()),
+        }
+    }
+}
+
+/// Access and modification
+impl ObjectIdSet {
+    /// Insert `id` and return `true` if it wasn't contained yet.
+    pub fn insert(&mut self, id: ObjectId) -> bool {
+        if self.prefixes.is_empty() {
+            self.prefixes = vec![0; PREFIX_WORDS];
+        }
+        let (word, bit) = prefix_position(&id);
+        self.prefixes[word] |= bit;
+        self.ids.insert(id)
+    }
+
+    /// Insert all `ids` at once, reserving space for them upfront, and return the amount of ids that weren't contained yet.
+    pub fn insert_many(&mut self, ids: impl IntoIterator<Item = ObjectId>) -> usize {
+        let ids = ids.into_iter();
+        self.reserve(ids.size_hint().0);
+        ids.fold(0, |count, id| count + usize::from(self.insert(id)))
+    }
+
+    /// Return `true` if `id` is contained in this set.
+    pub fn contains(&self, id: &oid) -> bool {
+        if self.prefixes.is_empty() {
+            return false;
+        }
+        let (word, bit) = prefix_position(id);
+        self.prefixes[word] & bit != 0 && self.ids.contains(id)
+    }
+
+    /// Remove `id` and return `true` if it was contained.
+    pub fn remove(&mut self, id: &oid) -> bool {
+        self.ids.remove(id)
+    }
+
+    /// Remove all ids, but keep the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.prefixes.iter_mut().for_each(|word| *word = 0);
+        self.ids.clear();
+    }
+
+    /// Reserve space for at least `additional` ids to be inserted without reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.ids.reserve(additional);
+    }
+
+    /// Return the amount of contained ids.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Return `true` if there is no id in this set.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Return an iterator over all contained ids in no particular order.
+    pub fn iter(&self) -> crate::hash_set::Iter<'_, ObjectId> {
+        self.ids.iter()
+    }
+
+    /// Return the amount of bytes allocated on the heap to store the ids and the bitmap, which includes
+    /// unused capacity.
+    pub fn heap_size_in_bytes(&self) -> usize {
+        self.prefixes.capacity() * std::mem::size_of::<u64>() + self.ids.raw_table().allocation_info().1.size()
+    }
+}
+
+/// Return the index of the word and the bit within it which represent the first two bytes of `id`.
+fn prefix_position(id: &oid) -> (usize, u64) {
+    let bytes = id.as_bytes();
+    let prefix = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+    (prefix / 64, 1 << (prefix % 64))
+}
+
+impl std::fmt::Debug for ObjectIdSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.ids.iter()).finish()
+    }
+}
+
+impl PartialEq for ObjectIdSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.ids == other.ids
+    }
+}
+
+impl Eq for ObjectIdSet {}
+
+impl Extend<ObjectId> for ObjectIdSet {
+    fn extend<T: IntoIterator<Item = ObjectId>>(&mut self, iter: T) {
+        self.insert_many(iter);
+    }
+}
+
+impl FromIterator<ObjectId> for ObjectIdSet {
+    fn from_iter<T: IntoIterator<Item = ObjectId>>(iter: T) -> Self {
+        let mut set = ObjectIdSet::new();
+        set.insert_many(iter);
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a ObjectIdSet {
+    type Item = &'a ObjectId;
+    type IntoIter = crate::hash_set::Iter<'a, ObjectId>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ids.iter()
+    }
+}
+
+impl IntoIterator for ObjectIdSet {
+    type Item = ObjectId;
+    type IntoIter = crate::hash_set::IntoIter<ObjectId>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ids.into_iter()
+    }
+}
@@ -19,3 +19,69 @@ mod hash {
         }
     }
 }
+
+mod object_id_set {
+    use gix_hash::ObjectId;
+    use gix_hashtable::ObjectIdSet;
+
+    fn id(prefix: u8, last: u8) -> ObjectId {
+        let mut bytes = [0; 20];
+        bytes[0] = prefix;
+        bytes[19] = last;
+        ObjectId::from(bytes)
+    }
+
+    #[test]
+    fn insert_contains_and_remove() {
+        let mut set = ObjectIdSet::new();
+        assert!(!set.contains(&id(1, 0)), "empty sets contain nothing");
+        assert!(set.insert(id(1, 0)));
+        assert!(!set.insert(id(1, 0)), "inserting twice is detected");
+        assert!(set.contains(&id(1, 0)));
+        assert!(!set.contains(&id(1, 1)), "the same prefix isn't enough");
+        assert!(!set.contains(&id(2, 0)));
+        assert_eq!(set.len(), 1);
+
+        assert!(set.remove(&id(1, 0)));
+        assert!(!set.remove(&id(1, 0)));
+        assert!(
+            !set.contains(&id(1, 0)),
+            "removed ids aren't found even though their prefix remains marked"
+        );
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn insert_many_counts_new_ids_only() {
+        let mut set = ObjectIdSet::new();
+        assert_eq!(set.insert_many((0..10).map(|n| id(n, n))), 10);
+        assert_eq!(set.insert_many((5..15).map(|n| id(n, n))), 5);
+        assert_eq!(set.len(), 15);
+        assert!((0..15).all(|n| set.contains(&id(n, n))));
+
+        let collected: ObjectIdSet = (0..15).map(|n| id(n, n)).collect();
+        assert_eq!(collected, set);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains(&id(0, 0)));
+    }
+
+    #[test]
+    fn heap_size_in_bytes() {
+        let mut set = ObjectIdSet::new();
+        assert_eq!(set.heap_size_in_bytes(), 0, "nothing is allocated upfront");
+
+        set.insert(id(0, 0));
+        let bitmap_size = 8 * 1024;
+        let size = set.heap_size_in_bytes();
+        assert!(size > bitmap_size + 20, "bitmap and at least one id: {size}");
+
+        set.insert_many((0..=255).map(|n| id(n, 1)));
+        let grown = set.heap_size_in_bytes();
+        assert!(grown >= bitmap_size + 257 * 20, "all ids are accounted for: {grown}");
+
+        set.clear();
+        assert_eq!(set.heap_size_in_bytes(), grown, "clearing keeps the memory for reuse");
+    }
+}
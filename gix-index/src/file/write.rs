@@ -48,7 +48,12 @@ impl File {
         );
         let (version, digest) = self.write_to(&mut lock, options)?;
         match lock.into_inner() {
-            Ok(lock) => lock.commit()?,
+            Ok(mut lock) => {
+                if let Some(method) = options.fsync {
+                    lock.with_mut(|file| gix_fs::fsync::file(file, method))?;
+                }
+                lock.commit()?
+            }
             Err(err) => return Err(err.into_error().into()),
         };
         self.state.version = version;
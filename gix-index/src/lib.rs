@@ -32,6 +32,9 @@ pub mod init;
 ///
 pub mod decode;
 
+///
+pub mod sparse;
+
 ///
 pub mod verify;
 
@@ -0,0 +1,459 @@
+use std::collections::BTreeSet;
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+use gix_object::{tree, FindExt};
+
+use crate::{
+    entry::{Flags, Mode, Stage, Stat},
+    Entry, PathStorage, State,
+};
+
+/// The flags of entries which are outside of the sparse-checkout cone, including sparse directories.
+const OUTSIDE_OF_CONE: Flags = Flags::EXTENDED.union(Flags::SKIP_WORKTREE);
+
+/// The directories included by a _cone mode_ sparse-checkout, as stored in `.git/info/sparse-checkout`
+/// when `core.sparseCheckoutCone` is enabled.
+///
+/// All files at the root of the repository are always included, along with all files in directories that are
+/// included recursively, and the files directly within their parent directories.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// Directories that are included with all of their content, without trailing slash.
+    recursive: BTreeSet<BString>,
+    /// Directories of which only the files directly within them are included, without trailing slash.
+    parents: BTreeSet<BString>,
+}
+
+///
+pub mod cone {
+    use bstr::BString;
+
+    /// The error returned by [`Cone::from_bytes()`](super::Cone::from_bytes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cone patterns must start with '/*' and '!/*/'")]
+        MissingRootPatterns,
+        #[error("Line {line_number} is not a cone-mode pattern: {line:?}")]
+        InvalidPattern { line_number: usize, line: BString },
+    }
+}
+
+impl Cone {
+    /// Parse the patterns of a sparse-checkout file in cone mode from `input`.
+    ///
+    /// Fail if any of the patterns isn't one that `git sparse-checkout set --cone` would write, which means the patterns
+    /// should be interpreted in non-cone mode.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, cone::Error> {
+        let mut patterns = input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim_end()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with(b"#"));
+        if patterns.next().map(|(_, line)| line) != Some(b"/*".as_slice())
+            || patterns.next().map(|(_, line)| line) != Some(b"!/*/".as_slice())
+        {
+            return Err(cone::Error::MissingRootPatterns);
+        }
+
+        let mut included = BTreeSet::new();
+        let mut parents = BTreeSet::new();
+        for (line_number, line) in patterns {
+            let (negated, directory) = match line.strip_prefix(b"!") {
+                Some(pattern) => (true, pattern.strip_prefix(b"/").and_then(|p| p.strip_suffix(b"/*/"))),
+                None => (false, line.strip_prefix(b"/").and_then(|p| p.strip_suffix(b"/"))),
+            };
+            let directory = match directory {
+                Some(directory) if !directory.is_empty() && !directory.contains(&b'*') => unescape(directory),
+                _ => {
+                    return Err(cone::Error::InvalidPattern {
+                        line_number,
+                        line: line.into(),
+                    })
+                }
+            };
+            if negated {
+                parents.insert(directory);
+            } else {
+                included.insert(directory);
+            }
+        }
+        let recursive = included.difference(&parents).cloned().collect();
+        let parents = parents.intersection(&included).cloned().collect();
+        Ok(Cone { recursive, parents })
+    }
+
+    /// Create an instance that includes all `directories` recursively, along with the files in their parent directories,
+    /// similar to `git sparse-checkout set --cone <directories>`.
+    pub fn from_directories(directories: impl IntoIterator<Item = impl AsRef<BStr>>) -> Self {
+        let mut cone = Cone::default();
+        for directory in directories {
+            let directory = directory.as_ref().trim_with(|c| c == '/');
+            if directory.is_empty() {
+                continue;
+            }
+            cone.parents
+                .extend(ancestors(directory.as_bstr()).map(ToOwned::to_owned));
+            cone.recursive.insert(directory.into());
+        }
+        let recursive = &cone.recursive;
+        cone.parents.retain(|parent| !recursive.contains(parent));
+        cone
+    }
+
+    /// Return `true` if `directory`, a slash-separated path relative to the root of the repository without trailing slash,
+    /// is entirely outside of the cone so none of its files are checked out, making it suitable for a sparse directory entry.
+    pub fn is_excluded_directory(&self, directory: &BStr) -> bool {
+        if directory.is_empty() || self.parents.contains(directory) || self.is_recursively_included(directory) {
+            return false;
+        }
+        let mut prefix = BString::from(directory);
+        prefix.push(b'/');
+        let leads_into_cone = |set: &BTreeSet<BString>| {
+            set.range(prefix.clone()..)
+                .next()
+                .map_or(false, |dir| dir.starts_with(&prefix))
+        };
+        !(leads_into_cone(&self.recursive) || leads_into_cone(&self.parents))
+    }
+
+    /// Return `true` if the file at `path`, relative to the root of the repository, is checked out.
+    pub fn contains_file(&self, path: &BStr) -> bool {
+        match path.rfind_byte(b'/') {
+            None => true,
+            Some(pos) => {
+                let directory = path[..pos].as_bstr();
+                self.parents.contains(directory) || self.is_recursively_included(directory)
+            }
+        }
+    }
+
+    /// Write our patterns to `out` in the format used by `git sparse-checkout` in cone mode.
+    pub fn write_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+        out.write_all(b"/*\n!/*/\n")?;
+        // Both sets are sorted, so parents are always written before the directories within them.
+        let mut parents = self.parents.iter().peekable();
+        for recursive in &self.recursive {
+            while let Some(parent) = parents.next_if(|parent| *parent < recursive) {
+                writeln!(out, "/{parent}/\n!/{parent}/*/")?;
+            }
+            writeln!(out, "/{recursive}/")?;
+        }
+        for parent in parents {
+            writeln!(out, "/{parent}/\n!/{parent}/*/")?;
+        }
+        Ok(())
+    }
+
+    fn is_recursively_included(&self, directory: &BStr) -> bool {
+        self.recursive.contains(directory) || ancestors(directory).any(|dir| self.recursive.contains(dir))
+    }
+}
+
+/// Return all ancestors of `path`, starting with the top-most one.
+fn ancestors(path: &BStr) -> impl Iterator<Item = &BStr> {
+    path.find_iter(b"/").map(move |pos| path[..pos].as_bstr())
+}
+
+fn unescape(pattern: &[u8]) -> BString {
+    let mut out = BString::default();
+    let mut bytes = pattern.iter();
+    while let Some(&b) = bytes.next() {
+        if b == b'\\' {
+            if let Some(&escaped) = bytes.next() {
+                out.push(escaped);
+            }
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+///
+pub mod expand {
+    use bstr::BString;
+
+    /// The error returned by [`State::expand_sparse_directories()`](crate::State::expand_sparse_directories())
+    /// and [`State::expand_sparse_directories_leading_to()`](crate::State::expand_sparse_directories_leading_to()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not find the tree of the sparse directory '{path}'")]
+        FindTree {
+            source: gix_object::find::existing_iter::Error,
+            path: BString,
+        },
+        #[error("Could not decode the tree of the sparse directory '{path}'")]
+        DecodeTree {
+            source: gix_object::decode::Error,
+            path: BString,
+        },
+    }
+}
+
+/// Sparse directories
+impl State {
+    /// Replace all sparse directory entries with the entries of their trees, recursively, so that the index is a full index
+    /// which tracks files only. Return the amount of sparse directories that were expanded.
+    ///
+    /// Sub-trees are looked up in `objects`. All new entries are marked as outside of the cone.
+    /// Note that the tree-cache extension is removed if a directory was expanded, as it doesn't match the entries anymore.
+    pub fn expand_sparse_directories(&mut self, objects: &dyn gix_object::Find) -> Result<usize, expand::Error> {
+        let _span = gix_features::trace::coarse!("gix_index::State::expand_sparse_directories()");
+        if !self.entries.iter().any(|e| e.mode.is_sparse()) {
+            self.is_sparse = false;
+            return Ok(0);
+        }
+        let mut buf = Vec::new();
+        let mut expanded = 0;
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for entry in std::mem::take(&mut self.entries) {
+            if entry.mode.is_sparse() {
+                let directory = entry.path_in(&self.path_backing).to_owned();
+                expanded += expand_tree(
+                    directory.as_ref(),
+                    entry.id,
+                    true,
+                    objects,
+                    &mut buf,
+                    &mut entries,
+                    &mut self.path_backing,
+                )?;
+            } else {
+                entries.push(entry);
+            }
+        }
+        self.entries = entries;
+        self.is_sparse = false;
+        self.tree = None;
+        Ok(expanded)
+    }
+
+    /// Expand only the sparse directories that contain `path`, a slash-separated path relative to the root of the repository,
+    /// or which are `path` itself, so that entries at and below `path` can be looked up and changed.
+    /// Return the amount of sparse directories that were expanded.
+    ///
+    /// Each sparse directory on the way is expanded by one level only, so sibling directories remain sparse and the amount
+    /// of entries grows with the depth of `path`, not with the size of the directories containing it.
+    /// Sub-trees are looked up in `objects`. All new entries are marked as outside of the cone.
+    /// Note that the tree-cache extension is removed if a directory was expanded, as it doesn't match the entries anymore.
+    pub fn expand_sparse_directories_leading_to(
+        &mut self,
+        path: &BStr,
+        objects: &dyn gix_object::Find,
+    ) -> Result<usize, expand::Error> {
+        if !self.is_sparse {
+            return Ok(0);
+        }
+        let path = path.trim_with(|c| c == '/');
+        let mut buf = Vec::new();
+        let mut expanded = 0;
+        let directories = path
+            .find_iter(b"/")
+            .map(|pos| &path[..=pos])
+            .chain(Some(path))
+            .filter(|directory| !directory.is_empty());
+        for directory in directories {
+            let mut directory = BString::from(directory);
+            if !directory.ends_with(b"/") {
+                directory.push(b'/');
+            }
+            let Some(idx) = self
+                .entry_index_by_path_and_stage(directory.as_bstr(), Stage::Unconflicted)
+                .filter(|idx| self.entries[*idx].mode.is_sparse())
+            else {
+                continue;
+            };
+            let id = self.entries[idx].id;
+            let mut entries = Vec::new();
+            expanded += expand_tree(
+                directory.as_bstr(),
+                id,
+                false,
+                objects,
+                &mut buf,
+                &mut entries,
+                &mut self.path_backing,
+            )?;
+            self.entries.splice(idx..=idx, entries);
+        }
+        if expanded != 0 {
+            self.tree = None;
+            self.is_sparse = self.entries.iter().any(|e| e.mode.is_sparse());
+        }
+        Ok(expanded)
+    }
+
+    /// Replace the entries of all directories for which `is_excluded_directory(directory)` returns `true` with a single
+    /// sparse directory entry, and return the amount of sparse directories that were created. Afterwards the index is sparse.
+    ///
+    /// `directory` is relative to the root of the repository and has no trailing slash.
+    /// The top-most excluded directories are collapsed, but only if all entries within them are unconflicted, marked
+    /// as outside of the cone and aren't submodules.
+    /// `write_tree` is called to write the tree of each collapsed directory and its sub-trees, returning their ids.
+    /// Note that the tree-cache extension is removed if a directory was collapsed, as it doesn't match the entries anymore.
+    pub fn collapse_sparse_directories<E>(
+        &mut self,
+        is_excluded_directory: &mut dyn FnMut(&BStr) -> bool,
+        mut write_tree: impl FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+    ) -> Result<usize, E> {
+        let _span = gix_features::trace::coarse!("gix_index::State::collapse_sparse_directories()");
+        let can_collapse = |e: &Entry| {
+            e.stage() == Stage::Unconflicted
+                && e.flags.contains(Flags::SKIP_WORKTREE)
+                && !e.mode.is_submodule()
+                && e.mode.to_tree_entry_mode().is_some()
+        };
+
+        let mut collapsed = 0;
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            let path = self.entries[idx].path_in(&self.path_backing);
+            let mut collapsed_until = None;
+            for pos in path.find_iter(b"/").filter(|pos| pos + 1 != path.len()) {
+                let prefix = &path[..=pos];
+                // Directories that were seen before weren't eligible for collapsing.
+                let is_first_in_directory =
+                    idx == 0 || !self.entries[idx - 1].path_in(&self.path_backing).starts_with(prefix);
+                if !is_first_in_directory || !is_excluded_directory(path[..pos].as_bstr()) {
+                    continue;
+                }
+                let end = idx
+                    + self.entries[idx..]
+                        .iter()
+                        .take_while(|e| e.path_in(&self.path_backing).starts_with(prefix))
+                        .count();
+                if self.entries[idx..end].iter().all(can_collapse) {
+                    let id = write_directory(
+                        &self.entries[idx..end],
+                        &self.path_backing,
+                        prefix.len(),
+                        &mut write_tree,
+                    )?;
+                    collapsed_until = Some((end, prefix.to_owned(), id));
+                    break;
+                }
+            }
+
+            match collapsed_until {
+                Some((end, directory, id)) => {
+                    let path_start = self.path_backing.len();
+                    self.path_backing.extend_from_slice(&directory);
+                    entries.push(Entry {
+                        stat: Stat::default(),
+                        id,
+                        flags: OUTSIDE_OF_CONE,
+                        mode: Mode::DIR,
+                        path: path_start..self.path_backing.len(),
+                    });
+                    collapsed += 1;
+                    idx = end;
+                }
+                None => {
+                    entries.push(self.entries[idx].clone());
+                    idx += 1;
+                }
+            }
+        }
+        self.entries = entries;
+        self.is_sparse = true;
+        if collapsed != 0 {
+            self.tree = None;
+        }
+        Ok(collapsed)
+    }
+}
+
+/// Add the entries of the tree with `id` at `directory` (with trailing slash) to `out`, turning sub-trees
+/// into sparse directories unless `recursive` is `true`. Return the amount of expanded trees.
+fn expand_tree(
+    directory: &BStr,
+    id: ObjectId,
+    recursive: bool,
+    objects: &dyn gix_object::Find,
+    buf: &mut Vec<u8>,
+    out: &mut Vec<Entry>,
+    path_backing: &mut PathStorage,
+) -> Result<usize, expand::Error> {
+    let tree_entries = objects
+        .find_tree_iter(&id, buf)
+        .map_err(|source| expand::Error::FindTree {
+            source,
+            path: directory.to_owned(),
+        })?
+        .map(|entry| entry.map(|entry| (entry.mode, BString::from(entry.filename), entry.oid.to_owned())))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| expand::Error::DecodeTree {
+            source,
+            path: directory.to_owned(),
+        })?;
+
+    let mut expanded = 1;
+    for (mode, filename, id) in tree_entries {
+        let mut path = BString::from(directory);
+        path.push_str(&filename);
+        if mode.is_tree() {
+            path.push(b'/');
+            if recursive {
+                expanded += expand_tree(path.as_bstr(), id, recursive, objects, buf, out, path_backing)?;
+                continue;
+            }
+        }
+        let path_start = path_backing.len();
+        path_backing.extend_from_slice(&path);
+        out.push(Entry {
+            stat: Stat::default(),
+            id,
+            flags: OUTSIDE_OF_CONE,
+            mode: Mode::from(mode),
+            path: path_start..path_backing.len(),
+        });
+    }
+    Ok(expanded)
+}
+
+/// Write the tree of `entries`, all of which are in the same directory whose path with trailing slash is `prefix_len` bytes long,
+/// and return its id.
+fn write_directory<E>(
+    entries: &[Entry],
+    path_backing: &PathStorage,
+    prefix_len: usize,
+    write_tree: &mut dyn FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+) -> Result<ObjectId, E> {
+    let mut tree = gix_object::Tree::empty();
+    let mut idx = 0;
+    while idx < entries.len() {
+        let entry = &entries[idx];
+        let rela_path = &entry.path_in(path_backing)[prefix_len..];
+        match rela_path.find_byte(b'/') {
+            Some(pos) if pos + 1 != rela_path.len() => {
+                let sub_directory = &rela_path[..=pos];
+                let end = idx
+                    + entries[idx..]
+                        .iter()
+                        .take_while(|e| e.path_in(path_backing)[prefix_len..].starts_with(sub_directory))
+                        .count();
+                let id = write_directory(&entries[idx..end], path_backing, prefix_len + pos + 1, write_tree)?;
+                tree.entries.push(tree::Entry {
+                    mode: tree::EntryKind::Tree.into(),
+                    filename: rela_path[..pos].into(),
+                    oid: id,
+                });
+                idx = end;
+            }
+            sparse_directory_or_file => {
+                tree.entries.push(tree::Entry {
+                    mode: entry.mode.to_tree_entry_mode().expect("checked by caller"),
+                    filename: rela_path[..sparse_directory_or_file.unwrap_or(rela_path.len())].into(),
+                    oid: entry.id,
+                });
+                idx += 1;
+            }
+        }
+    }
+    tree.entries.sort();
+    write_tree(&tree)
+}
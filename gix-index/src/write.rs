@@ -56,6 +56,11 @@ pub struct Options {
     /// via [`File::write()`](crate::File::write()) and [`File::write_to()`](crate::File::write_to()).
     /// Note that
     pub skip_hash: bool,
+    /// How to sync the index file before moving it into place when it is written with [`File::write()`](crate::File::write()),
+    /// if at all.
+    ///
+    /// This value is typically controlled by the `index` component of `core.fsync` and by `core.fsyncMethod`.
+    pub fsync: Option<gix_fs::fsync::Method>,
 }
 
 impl State {
@@ -66,6 +71,7 @@ impl State {
         Options {
             extensions,
             skip_hash: _,
+            fsync: _,
         }: Options,
     ) -> std::io::Result<Version> {
         let _span = gix_features::trace::detail!("gix_index::State::write()");
@@ -48,6 +48,7 @@ fn skip_hash() -> crate::Result {
     expected.write(Options {
         extensions: Default::default(),
         skip_hash: false,
+        fsync: None,
     })?;
 
     let actual = gix_index::File::at(
@@ -65,6 +66,7 @@ fn skip_hash() -> crate::Result {
     expected.write(Options {
         extensions: Default::default(),
         skip_hash: true,
+        fsync: None,
     })?;
 
     let actual = gix_index::File::at(
@@ -78,6 +80,31 @@ fn skip_hash() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn fsync() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let path = tmp.path().join("index");
+    let mut expected = Loose("conflicting-file").open();
+    expected.set_path(&path);
+    for method in [
+        gix_fs::fsync::Method::Fsync,
+        gix_fs::fsync::Method::WriteoutOnly,
+        gix_fs::fsync::Method::Batch,
+    ] {
+        expected.write(Options {
+            fsync: Some(method),
+            ..Default::default()
+        })?;
+        let actual = gix_index::File::at(&path, gix_hash::Kind::Sha1, false, Default::default())?;
+        assert_eq!(
+            actual.checksum(),
+            expected.checksum(),
+            "syncing doesn't affect the content"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn roundtrips_sparse_index() -> crate::Result {
     // NOTE: I initially tried putting these fixtures into the main roundtrip test above,
@@ -295,6 +322,7 @@ fn only_tree_ext() -> Options {
             tree_cache: true,
         },
         skip_hash: false,
+        fsync: None,
     }
 }
 
@@ -302,5 +330,6 @@ fn options_with(extensions: write::Extensions) -> Options {
     Options {
         extensions,
         skip_hash: false,
+        fsync: None,
     }
 }
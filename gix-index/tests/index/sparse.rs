@@ -0,0 +1,156 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_index::{
+    entry::{Flags, Mode},
+    sparse::Cone,
+};
+
+use crate::index::{fixture_index_path, Fixture};
+
+fn odb(fixture: &str) -> crate::Result<gix_odb::Handle> {
+    let git_dir = fixture_index_path(fixture)
+        .parent()
+        .expect("index is in git-dir")
+        .to_owned();
+    Ok(gix_odb::at(git_dir.join("objects"))?)
+}
+
+fn cone(fixture: &str) -> crate::Result<Cone> {
+    let git_dir = fixture_index_path(fixture)
+        .parent()
+        .expect("index is in git-dir")
+        .to_owned();
+    Ok(Cone::from_bytes(&std::fs::read(git_dir.join("info/sparse-checkout"))?)?)
+}
+
+fn entries(state: &gix_index::State) -> Vec<(BString, Mode, gix_hash::ObjectId, bool)> {
+    state
+        .entries()
+        .iter()
+        .map(|e| {
+            (
+                e.path(state).to_owned(),
+                e.mode,
+                e.id,
+                e.flags.contains(Flags::SKIP_WORKTREE),
+            )
+        })
+        .collect()
+}
+
+fn paths(state: &gix_index::State) -> Vec<&BStr> {
+    state.entries().iter().map(|e| e.path(state)).collect()
+}
+
+#[test]
+fn cone_patterns_as_written_by_git() -> crate::Result {
+    let cone = cone("v3_sparse_index")?;
+    assert_eq!(cone, Cone::from_directories(["c1/c2"]));
+
+    assert!(
+        !cone.is_excluded_directory("c1".into()),
+        "parent directories are partially included"
+    );
+    assert!(!cone.is_excluded_directory("c1/c2".into()));
+    assert!(!cone.is_excluded_directory("c1/c2/deeper".into()));
+    assert!(cone.is_excluded_directory("c1/c3".into()));
+    assert!(cone.is_excluded_directory("d".into()));
+
+    assert!(cone.contains_file("a".into()), "files at the root are always included");
+    assert!(
+        cone.contains_file("c1/a".into()),
+        "files in parent directories are included"
+    );
+    assert!(cone.contains_file("c1/c2/deeper/a".into()));
+    assert!(!cone.contains_file("c1/c3/a".into()));
+    assert!(!cone.contains_file("d/a".into()));
+
+    let mut buf = Vec::new();
+    cone.write_to(&mut buf)?;
+    assert_eq!(buf.as_bstr(), "/*\n!/*/\n/c1/\n!/c1/*/\n/c1/c2/\n");
+    assert_eq!(Cone::from_bytes(&buf)?, cone, "round-trips");
+
+    assert!(
+        Cone::from_bytes(b"/*\n!/*/\n*.txt\n").is_err(),
+        "non-cone patterns can't be used"
+    );
+    assert!(Cone::from_bytes(b"/c1/\n").is_err(), "the root patterns are required");
+    Ok(())
+}
+
+#[test]
+fn expanding_all_sparse_directories_yields_a_full_index() -> crate::Result {
+    let mut sparse = Fixture::Generated("v3_sparse_index").open();
+    let full = Fixture::Generated("v3_skip_worktree").open();
+
+    let expanded = sparse.expand_sparse_directories(&odb("v3_sparse_index")?)?;
+    assert_eq!(expanded, 3, "c1/c3, d and d/c4, which is contained in d");
+    assert!(!sparse.is_sparse());
+    assert_eq!(entries(&sparse), entries(&full));
+    assert!(sparse.verify_entries().is_ok());
+    Ok(())
+}
+
+#[test]
+fn expanding_sparse_directories_on_demand_only_expands_what_leads_to_a_path() -> crate::Result {
+    let mut index = Fixture::Generated("v3_sparse_index").open();
+    let odb = odb("v3_sparse_index")?;
+    assert_eq!(
+        index.expand_sparse_directories_leading_to("c1/c2/a".into(), &odb)?,
+        0,
+        "nothing to do for paths inside of the cone"
+    );
+
+    let expanded = index.expand_sparse_directories_leading_to("d/c4/c5".into(), &odb)?;
+    assert_eq!(expanded, 2, "d/ and d/c4/");
+    assert!(index.is_sparse(), "c1/c3 is still a sparse directory");
+    assert_eq!(
+        paths(&index),
+        ["a", "b", "c1/a", "c1/b", "c1/c2/a", "c1/c2/b", "c1/c3/", "d/a", "d/b", "d/c4/a", "d/c4/b", "d/c4/c5"]
+    );
+    let c5 = index.entry_by_path("d/c4/c5".into()).expect("present after expansion");
+    assert_eq!(c5.mode, Mode::FILE);
+    assert!(c5.flags.contains(Flags::SKIP_WORKTREE));
+
+    assert_eq!(index.expand_sparse_directories_leading_to("c1/c3".into(), &odb)?, 1);
+    assert!(!index.is_sparse(), "there is no sparse directory left");
+    assert!(index.verify_entries().is_ok());
+    Ok(())
+}
+
+#[test]
+fn collapsing_excluded_directories_yields_a_sparse_index() -> crate::Result {
+    let mut full = Fixture::Generated("v3_skip_worktree").open();
+    let sparse = Fixture::Generated("v3_sparse_index").open();
+    let cone = cone("v3_skip_worktree")?;
+    let odb = odb("v3_skip_worktree")?;
+
+    let mut written = Vec::new();
+    let collapsed = full.collapse_sparse_directories(&mut |dir| cone.is_excluded_directory(dir), |tree| {
+        let mut buf = Vec::new();
+        gix_object::WriteTo::write_to(tree, &mut buf)?;
+        let id = gix_object::compute_hash(gix_hash::Kind::Sha1, gix_object::Kind::Tree, &buf);
+        written.push(id);
+        Ok::<_, std::io::Error>(id)
+    })?;
+    assert_eq!(collapsed, 2, "c1/c3 and d");
+    assert_eq!(written.len(), 3, "d/c4 had to be written as well");
+    for id in written {
+        assert!(
+            gix_object::Exists::exists(&odb, &id),
+            "the trees are those of the checked-out commit"
+        );
+    }
+    assert!(full.is_sparse());
+    assert_eq!(entries(&full), entries(&sparse));
+    assert!(full.verify_entries().is_ok());
+
+    let entries_before = entries(&full);
+    assert_eq!(
+        full.collapse_sparse_directories(&mut |_| true, |_| -> Result<_, std::io::Error> {
+            unreachable!("directories with entries inside of the cone can't be collapsed")
+        })?,
+        0
+    );
+    assert_eq!(entries(&full), entries_before);
+    Ok(())
+}
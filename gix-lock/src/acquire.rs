@@ -17,6 +17,56 @@ pub enum Fail {
     /// Retry after failure with exponentially longer sleep times to block the current thread.
     /// Fail once the given duration is exceeded, similar to [Fail::Immediately]
     AfterDurationWithBackoff(Duration),
+    /// Retry after failure as configured by the given policy, blocking the current thread in between attempts.
+    WithBackoff(Backoff),
+}
+
+/// A policy to control how long to wait for a lock that is held elsewhere, for use with [`Fail::WithBackoff`].
+///
+/// It applies to each lock individually, so a transaction that locks multiple resources may wait for each of them.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Backoff {
+    /// The longest time to wait for the lock, after which obtaining it fails.
+    pub timeout: Duration,
+    /// The most attempts to make to obtain the lock, including the first one, or `None` to only be limited by the `timeout`.
+    pub max_attempts: Option<usize>,
+    /// The time to wait after the first failed attempt, which grows quadratically with each subsequent attempt.
+    pub initial_wait: Duration,
+    /// The longest time to wait between two attempts.
+    pub max_wait: Duration,
+    /// If `true`, randomize each wait by up to 25% so competing processes don't retry in lock-step.
+    pub jitter: bool,
+}
+
+impl Backoff {
+    /// Create a policy that fails after `timeout`, waiting just like [`Fail::AfterDurationWithBackoff`] in between attempts.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Backoff {
+            timeout,
+            max_attempts: None,
+            initial_wait: Duration::from_millis(1),
+            max_wait: Duration::from_secs(1),
+            jitter: true,
+        }
+    }
+
+    /// Return an iterator over the times to wait after each failed attempt.
+    fn waits(&self) -> impl Iterator<Item = Duration> {
+        let Backoff {
+            initial_wait,
+            max_wait,
+            jitter,
+            ..
+        } = *self;
+        backoff::Exponential::default().map(move |multiplier| {
+            let wait = initial_wait.saturating_mul(multiplier.as_millis() as u32).min(max_wait);
+            if jitter {
+                wait.mul_f64(fastrand::f64() * 0.5 + 0.75)
+            } else {
+                wait
+            }
+        })
+    }
 }
 
 impl fmt::Display for Fail {
@@ -26,6 +76,15 @@ impl fmt::Display for Fail {
             Fail::AfterDurationWithBackoff(duration) => {
                 write!(f, "after {:.02}s", duration.as_secs_f32())
             }
+            Fail::WithBackoff(Backoff {
+                timeout, max_attempts, ..
+            }) => {
+                write!(f, "with backoff (timeout {:.02}s", timeout.as_secs_f32())?;
+                if let Some(max_attempts) = max_attempts {
+                    write!(f, ", at most {max_attempts} attempt(s)")?;
+                }
+                f.write_str(")")
+            }
         }
     }
 }
@@ -180,21 +239,31 @@ fn lock_with_mode<T>(
                 attempts += 1;
                 match try_lock(&lock_path, directory, cleanup.clone()) {
                     Ok(v) => return Ok((lock_path, v)),
-                    #[cfg(windows)]
-                    Err(err) if err.kind() == AlreadyExists || err.kind() == PermissionDenied => {
-                        std::thread::sleep(wait);
-                        continue;
-                    }
-                    #[cfg(not(windows))]
-                    Err(err) if err.kind() == AlreadyExists => {
-                        std::thread::sleep(wait);
-                        continue;
-                    }
+                    Err(err) if is_locked(&err) => std::thread::sleep(wait),
                     Err(err) => return Err(Error::from(err)),
                 }
             }
             try_lock(&lock_path, directory, cleanup)
         }
+        Fail::WithBackoff(policy) => {
+            let mut waits = policy.waits();
+            let mut elapsed = Duration::default();
+            loop {
+                match try_lock(&lock_path, directory, cleanup.clone()) {
+                    Err(err) if is_locked(&err) => {
+                        let remaining = policy.timeout.saturating_sub(elapsed);
+                        if remaining.is_zero() || policy.max_attempts.map_or(false, |max| attempts >= max) {
+                            break Err(err);
+                        }
+                        let wait = waits.next().expect("infinite").min(remaining);
+                        std::thread::sleep(wait);
+                        elapsed += wait;
+                        attempts += 1;
+                    }
+                    res => break res,
+                }
+            }
+        }
     }
     .map(|v| (lock_path, v))
     .map_err(|err| match err.kind() {
@@ -207,6 +276,19 @@ fn lock_with_mode<T>(
     })
 }
 
+/// Return `true` if `err` indicates that the lock is held elsewhere.
+fn is_locked(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind::*;
+    #[cfg(windows)]
+    {
+        err.kind() == AlreadyExists || err.kind() == PermissionDenied
+    }
+    #[cfg(not(windows))]
+    {
+        err.kind() == AlreadyExists
+    }
+}
+
 fn add_lock_suffix(resource_path: &Path) -> PathBuf {
     resource_path.with_extension(resource_path.extension().map_or_else(
         || DOT_LOCK_SUFFIX.chars().skip(1).collect(),
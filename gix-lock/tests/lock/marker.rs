@@ -1,7 +1,7 @@
 mod acquire {
     use std::time::{Duration, Instant};
 
-    use gix_lock::acquire::Fail;
+    use gix_lock::acquire::{Backoff, Fail};
 
     #[test]
     fn fail_mode_immediately_produces_a_descriptive_error() -> crate::Result {
@@ -41,6 +41,59 @@ mod acquire {
         assert!(err_str.contains("the-resource.lock"), "it mentions the lockfile itself");
         Ok(())
     }
+
+    #[test]
+    fn fail_mode_with_backoff_respects_max_attempts_and_timeout() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let resource = dir.path().join("the-resource");
+        let _guard = gix_lock::Marker::acquire_to_hold_resource(&resource, Fail::Immediately, None)?;
+
+        let policy = Backoff {
+            max_attempts: Some(3),
+            initial_wait: Duration::from_millis(5),
+            jitter: false,
+            ..Backoff::with_timeout(Duration::from_secs(10))
+        };
+        let start = Instant::now();
+        let err_str = gix_lock::Marker::acquire_to_hold_resource(&resource, Fail::WithBackoff(policy), None)
+            .expect_err("the lock is taken")
+            .to_string();
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(5 + 20),
+            "it waited twice, quadratically longer the second time"
+        );
+        assert!(elapsed < Duration::from_secs(10), "it stopped before the timeout");
+        assert!(
+            err_str.contains(
+                "could not be obtained with backoff (timeout 10.00s, at most 3 attempt(s)) after 3 attempt(s)"
+            ),
+            "{err_str}"
+        );
+
+        let time_to_wait = Duration::from_millis(50);
+        let policy = Backoff {
+            max_wait: Duration::from_millis(10),
+            ..Backoff::with_timeout(time_to_wait)
+        };
+        let start = Instant::now();
+        let err = gix_lock::Marker::acquire_to_hold_resource(&resource, Fail::WithBackoff(policy), None)
+            .expect_err("the lock is still taken");
+        assert!(
+            start.elapsed() >= time_to_wait,
+            "it should never wait less than the given wait time"
+        );
+        match err {
+            gix_lock::acquire::Error::PermanentlyLocked { attempts, .. } => {
+                assert!(
+                    attempts > 4,
+                    "waits are capped, so there are more attempts, got {attempts}"
+                );
+            }
+            err => unreachable!("unexpected error: {err}"),
+        }
+        Ok(())
+    }
 }
 mod commit {
     use gix_lock::acquire::Fail;
@@ -0,0 +1,142 @@
+use std::ops::Range;
+
+use bstr::BStr;
+use gix_diff::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm};
+
+/// The labels to put next to conflict markers.
+#[derive(Debug, Clone, Copy)]
+pub struct Labels<'a> {
+    /// The label of our side, like `HEAD`.
+    pub ours: &'a BStr,
+    /// The label of their side, like the name of the branch being merged.
+    pub theirs: &'a BStr,
+}
+
+/// Whether or not a merge could be performed without conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    /// All changes could be combined.
+    Complete,
+    /// At least one region was changed differently by both sides and is surrounded by conflict markers.
+    Conflict,
+}
+
+/// Return `true` if `data` looks like binary data, which can't be merged line by line.
+pub fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(8000)].contains(&0)
+}
+
+/// Merge the lines of `ours` and `theirs`, both derived from `base`, and return the merged content along with
+/// whether conflicts were encountered.
+///
+/// Regions which were changed by both sides, even if they are only adjacent, are conflicting unless both sides made the
+/// same change. Conflicting regions are surrounded by conflict markers with the given `labels`, just like `git merge` does.
+pub fn text(base: &[u8], ours: &[u8], theirs: &[u8], labels: Labels<'_>) -> (Vec<u8>, Resolution) {
+    let base_lines = lines(base);
+    let ours_lines = lines(ours);
+    let theirs_lines = lines(theirs);
+    let ours_hunks = hunks(base, ours);
+    let theirs_hunks = hunks(base, theirs);
+
+    let mut out = Vec::with_capacity(ours.len().max(theirs.len()));
+    let mut resolution = Resolution::Complete;
+    let (mut ours_idx, mut theirs_idx) = (0, 0);
+    let mut base_pos = 0;
+    loop {
+        let next_ours = ours_hunks.get(ours_idx);
+        let next_theirs = theirs_hunks.get(theirs_idx);
+        let start = match (next_ours, next_theirs) {
+            (None, None) => break,
+            (Some(hunk), None) | (None, Some(hunk)) => hunk.before.start,
+            (Some(a), Some(b)) => a.before.start.min(b.before.start),
+        };
+        extend(&mut out, &base_lines[base_pos..start]);
+
+        // Collect all hunks of both sides that overlap or touch, forming a region of the base that they all change.
+        let (ours_first, theirs_first) = (ours_idx, theirs_idx);
+        let mut end = start;
+        loop {
+            if let Some(hunk) = ours_hunks.get(ours_idx).filter(|hunk| hunk.before.start <= end) {
+                end = end.max(hunk.before.end);
+                ours_idx += 1;
+            } else if let Some(hunk) = theirs_hunks.get(theirs_idx).filter(|hunk| hunk.before.start <= end) {
+                end = end.max(hunk.before.end);
+                theirs_idx += 1;
+            } else {
+                break;
+            }
+        }
+        base_pos = end;
+
+        let ours_region = side_region(&ours_hunks[ours_first..ours_idx], start..end);
+        let theirs_region = side_region(&theirs_hunks[theirs_first..theirs_idx], start..end);
+        match (ours_region, theirs_region) {
+            (Some(region), None) => extend(&mut out, &ours_lines[region]),
+            (None, Some(region)) => extend(&mut out, &theirs_lines[region]),
+            (Some(ours_region), Some(theirs_region)) => {
+                let (ours_region, theirs_region) = (&ours_lines[ours_region], &theirs_lines[theirs_region]);
+                if ours_region == theirs_region {
+                    extend(&mut out, ours_region);
+                } else {
+                    resolution = Resolution::Conflict;
+                    out.extend_from_slice(b"<<<<<<< ");
+                    out.extend_from_slice(labels.ours);
+                    out.push(b'\n');
+                    extend_with_newline(&mut out, ours_region);
+                    out.extend_from_slice(b"=======\n");
+                    extend_with_newline(&mut out, theirs_region);
+                    out.extend_from_slice(b">>>>>>> ");
+                    out.extend_from_slice(labels.theirs);
+                    out.push(b'\n');
+                }
+            }
+            (None, None) => unreachable!("BUG: each region contains at least one hunk"),
+        }
+    }
+    extend(&mut out, &base_lines[base_pos..]);
+    (out, resolution)
+}
+
+/// Given all `hunks` of one side within the `base` region, return the corresponding region of that side, or `None` if
+/// it didn't change anything there.
+fn side_region(hunks: &[Hunk], base: Range<usize>) -> Option<Range<usize>> {
+    let (first, last) = (hunks.first()?, hunks.last()?);
+    let start = first.after.start - (first.before.start - base.start);
+    let end = last.after.end + (base.end - last.before.end);
+    Some(start..end)
+}
+
+struct Hunk {
+    before: Range<usize>,
+    after: Range<usize>,
+}
+
+fn hunks(before: &[u8], after: &[u8]) -> Vec<Hunk> {
+    let input = InternedInput::new(byte_lines_with_terminator(before), byte_lines_with_terminator(after));
+    let mut out = Vec::new();
+    gix_diff::blob::diff(Algorithm::Histogram, &input, |before: Range<u32>, after: Range<u32>| {
+        out.push(Hunk {
+            before: before.start as usize..before.end as usize,
+            after: after.start as usize..after.end as usize,
+        });
+    });
+    out
+}
+
+/// Split `data` into lines including their terminator, just like the diff does.
+fn lines(data: &[u8]) -> Vec<&[u8]> {
+    data.split_inclusive(|b| *b == b'\n').collect()
+}
+
+fn extend(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        out.extend_from_slice(line);
+    }
+}
+
+fn extend_with_newline(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    extend(out, lines);
+    if matches!(out.last(), Some(b) if *b != b'\n') {
+        out.push(b'\n');
+    }
+}
@@ -0,0 +1,12 @@
+//! Three-way merges of blobs and trees, as needed to combine the changes of two sides since their merge base.
+//!
+//! Use [`blob::text()`] to merge the content of text files, and [`tree()`] to merge entire trees.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+///
+pub mod blob;
+
+///
+pub mod tree;
+pub use tree::function::tree;
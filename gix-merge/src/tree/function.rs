@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_object::{tree::EntryKind, FindExt};
+
+use crate::{
+    blob::{self, Labels, Resolution},
+    tree::{Conflict, ConflictKind, Entry, Error, Outcome},
+};
+
+/// Merge the changes from the tree `base` to the tree `ours` with the changes from `base` to the tree `theirs`,
+/// and write the merged tree along with all merged blobs using `write`. All objects are read from `objects`.
+///
+/// `write` stores each merged blob and tree and returns its id. Written objects don't have to be found in `objects`.
+///
+/// If `base` is `None`, the sides are merged as if they started out empty, which is what `git` does for unrelated histories.
+/// Changes to different files are combined, and so are changes to different regions of the same text file.
+/// All other changes conflict, and conflicting text regions are surrounded by conflict markers using `labels`.
+///
+/// Note that renames aren't detected, and that a single `base` has to be chosen by the caller if there are multiple merge bases.
+pub fn tree<E>(
+    base: Option<&gix_hash::oid>,
+    ours: &gix_hash::oid,
+    theirs: &gix_hash::oid,
+    labels: Labels<'_>,
+    objects: &impl gix_object::Find,
+    mut write: impl FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+) -> Result<Outcome, Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    let mut write = |object: &dyn gix_object::WriteTo| write(object).map_err(|err| Error::WriteObject(err.into()));
+    let base = base
+        .map(|id| entries_by_path(id, objects))
+        .transpose()?
+        .unwrap_or_default();
+    let theirs = entries_by_path(theirs, objects)?;
+    let mut buf = Vec::new();
+    let mut editor = gix_object::tree::Editor::new(objects.find_tree(ours, &mut buf)?.into(), objects, ours.kind());
+    let ours = entries_by_path(ours, objects)?;
+
+    let mut conflicts = Vec::new();
+    let mut merged = BTreeMap::new();
+    let paths: BTreeSet<&BString> = base.keys().chain(ours.keys()).chain(theirs.keys()).collect();
+    for path in paths {
+        let (b, o, t) = (base.get(path), ours.get(path), theirs.get(path));
+        let mut conflict = |kind| {
+            conflicts.push(Conflict {
+                path: path.clone(),
+                kind,
+                base: b.copied(),
+                ours: o.copied(),
+                theirs: t.copied(),
+            });
+        };
+        let result = if o == t || t == b {
+            o.copied()
+        } else if o == b {
+            t.copied()
+        } else {
+            match (b, o, t) {
+                (_, Some(o), Some(t)) if is_file(o) && is_file(t) && b.map_or(true, is_file) => {
+                    let base_mode = b.map(|b| b.mode);
+                    let mode = if o.mode == t.mode || base_mode == Some(t.mode) {
+                        o.mode
+                    } else if base_mode == Some(o.mode) {
+                        t.mode
+                    } else {
+                        conflict(ConflictKind::Mode);
+                        o.mode
+                    };
+                    let id = if o.id == t.id || b.map(|b| b.id) == Some(t.id) {
+                        o.id
+                    } else if b.map(|b| b.id) == Some(o.id) {
+                        t.id
+                    } else {
+                        let base_data = match b {
+                            Some(b) => objects.find_blob(&b.id, &mut buf)?.data.to_owned(),
+                            None => Vec::new(),
+                        };
+                        let ours_data = objects.find_blob(&o.id, &mut buf)?.data.to_owned();
+                        let theirs_data = objects.find_blob(&t.id, &mut buf)?.data.to_owned();
+                        if [&base_data, &ours_data, &theirs_data]
+                            .iter()
+                            .any(|data| blob::is_binary(data))
+                        {
+                            conflict(ConflictKind::Unmergeable);
+                            o.id
+                        } else {
+                            let (data, resolution) = blob::text(&base_data, &ours_data, &theirs_data, labels);
+                            if resolution == Resolution::Conflict {
+                                conflict(ConflictKind::Content);
+                            }
+                            write(&gix_object::BlobRef { data: &data })?
+                        }
+                    };
+                    Some(Entry { mode, id })
+                }
+                (_, Some(o), Some(_)) => {
+                    conflict(ConflictKind::Unmergeable);
+                    Some(*o)
+                }
+                (Some(_), modified @ Some(_), None) | (Some(_), None, modified @ Some(_)) => {
+                    conflict(ConflictKind::ModifyDelete);
+                    modified.copied()
+                }
+                _ => unreachable!("BUG: unchanged sides were handled before"),
+            }
+        };
+        if let Some(entry) = result {
+            merged.insert(path.as_bstr(), entry);
+        }
+    }
+
+    let directories: BTreeSet<&BStr> = merged
+        .keys()
+        .flat_map(|path| {
+            path.rfind_byte(b'/')
+                .map(|pos| path[..pos].as_bstr())
+                .into_iter()
+                .flat_map(|dir| {
+                    std::iter::successors(Some(dir), |dir| dir.rfind_byte(b'/').map(|pos| dir[..pos].as_bstr()))
+                })
+        })
+        .collect();
+    for dir in &directories {
+        if merged.remove(dir).is_some() {
+            let path: BString = (*dir).to_owned();
+            conflicts.push(Conflict {
+                kind: ConflictKind::FileDirectory,
+                base: base.get(&path).copied(),
+                ours: ours.get(&path).copied(),
+                theirs: theirs.get(&path).copied(),
+                path,
+            });
+        }
+    }
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    for path in ours.keys().filter(|path| !merged.contains_key(path.as_bstr())) {
+        editor.remove(path.split(|b| *b == b'/'))?;
+    }
+    for (path, entry) in merged
+        .iter()
+        .filter(|(path, entry)| ours.get(path.as_bytes()) != Some(entry))
+    {
+        editor.upsert(path.split(|b| *b == b'/'), entry.mode.kind(), entry.id)?;
+    }
+    let tree = editor.write(|tree| write(tree))?;
+    Ok(Outcome { tree, conflicts })
+}
+
+/// Return all non-tree entries of the tree `id`, recursively, by their path, reading all trees from `objects`.
+pub fn entries_by_path(id: &gix_hash::oid, objects: &impl gix_object::Find) -> Result<BTreeMap<BString, Entry>, Error> {
+    let mut buf = Vec::new();
+    let root = objects.find_tree_iter(id, &mut buf)?;
+    let mut recorder = gix_traverse::tree::Recorder::default();
+    gix_traverse::tree::breadthfirst(
+        root,
+        gix_traverse::tree::breadthfirst::State::default(),
+        objects,
+        &mut recorder,
+    )?;
+    Ok(recorder
+        .records
+        .into_iter()
+        .filter(|entry| !entry.mode.is_tree())
+        .map(|entry| {
+            (
+                entry.filepath,
+                Entry {
+                    mode: entry.mode,
+                    id: entry.oid,
+                },
+            )
+        })
+        .collect())
+}
+
+fn is_file(entry: &Entry) -> bool {
+    matches!(entry.mode.kind(), EntryKind::Blob | EntryKind::BlobExecutable)
+}
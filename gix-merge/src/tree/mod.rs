@@ -0,0 +1,88 @@
+use bstr::BString;
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+/// An entry of a tree as seen by the merge, identified by its path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// The mode of the entry, which is never a tree.
+    pub mode: EntryMode,
+    /// The id of the object the entry points to.
+    pub id: ObjectId,
+}
+
+/// The kind of conflict that was encountered at a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+    /// Both sides changed the content of a text file in overlapping regions, or added it with different content.
+    ///
+    /// The merged tree contains the file with conflict markers.
+    Content,
+    /// Both sides changed an entry which can't be merged line by line, like binary files, symbolic links or submodules.
+    ///
+    /// The merged tree contains our version.
+    Unmergeable,
+    /// Both sides changed the executable bit differently.
+    ///
+    /// The merged tree contains our mode.
+    Mode,
+    /// One side deleted the entry while the other side modified it.
+    ///
+    /// The merged tree contains the modified version.
+    ModifyDelete,
+    /// One side has a file where the other side has a directory.
+    ///
+    /// The merged tree contains the directory, the file is only available in the conflict.
+    FileDirectory,
+}
+
+/// A conflict at a single path that couldn't be resolved automatically.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// The path relative to the root of the tree.
+    pub path: BString,
+    /// The kind of conflict.
+    pub kind: ConflictKind,
+    /// The entry in the merge base, if there was one.
+    pub base: Option<Entry>,
+    /// The entry on our side, if there was one.
+    pub ours: Option<Entry>,
+    /// The entry on their side, if there was one.
+    pub theirs: Option<Entry>,
+}
+
+/// The outcome of [`tree()`](crate::tree()).
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The merged tree, which contains conflicting files as described by their [kind](ConflictKind), i.e. the way they
+    /// would be checked out.
+    pub tree: ObjectId,
+    /// All conflicts, sorted by path, which is empty if the merge was clean.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Outcome {
+    /// Return `true` if there was at least one conflict.
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// The error returned by [`tree()`](crate::tree()) and [`entries_by_path()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    FindTree(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    Traverse(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    EditTree(#[from] gix_object::tree::editor::Error),
+    #[error("Could not write a merged object")]
+    WriteObject(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+pub(crate) mod function;
+pub use function::entries_by_path;
@@ -0,0 +1,50 @@
+use gix_merge::blob::{is_binary, text, Resolution};
+
+use crate::labels;
+
+#[test]
+fn changes_to_different_regions_are_combined() {
+    let (merged, resolution) = text(b"a\nb\nc\nd\ne\n", b"A\nb\nc\nd\ne\n", b"a\nb\nc\nd\nE\n", labels());
+    assert_eq!(resolution, Resolution::Complete);
+    assert_eq!(merged.as_slice(), b"A\nb\nc\nd\nE\n");
+}
+
+#[test]
+fn identical_changes_are_taken_once() {
+    let (merged, resolution) = text(b"a\nb\n", b"a\nB\nnew\n", b"a\nB\nnew\n", labels());
+    assert_eq!(resolution, Resolution::Complete);
+    assert_eq!(merged.as_slice(), b"a\nB\nnew\n");
+}
+
+#[test]
+fn changes_to_the_same_region_conflict() {
+    let (merged, resolution) = text(b"a\nb\nc\n", b"a\nours\nc\n", b"a\ntheirs\nc", labels());
+    assert_eq!(resolution, Resolution::Conflict);
+    assert_eq!(
+        merged.as_slice(),
+        b"a\n<<<<<<< ours\nours\nc\n=======\ntheirs\nc\n>>>>>>> theirs\n",
+        "their last line lacks a newline and thus differs, and it is added before the marker"
+    );
+}
+
+#[test]
+fn adjacent_changes_conflict() {
+    let (_, resolution) = text(b"a\nb\n", b"A\nb\n", b"a\nB\n", labels());
+    assert_eq!(resolution, Resolution::Conflict, "just like in git");
+}
+
+#[test]
+fn different_additions_without_base_conflict() {
+    let (merged, resolution) = text(b"", b"ours\n", b"theirs\n", labels());
+    assert_eq!(resolution, Resolution::Conflict);
+    assert_eq!(
+        merged.as_slice(),
+        b"<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n"
+    );
+}
+
+#[test]
+fn binary_detection() {
+    assert!(!is_binary(b"text\n"));
+    assert!(is_binary(b"te\0xt"));
+}
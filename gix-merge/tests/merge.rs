@@ -0,0 +1,90 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use gix_hash::{oid, ObjectId};
+use gix_merge::blob::Labels;
+use gix_object::{tree::EntryKind, WriteTo};
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+mod blob;
+mod tree;
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ours: "ours".into(),
+        theirs: "theirs".into(),
+    }
+}
+
+/// An object database in memory.
+#[derive(Default)]
+struct Objects(RefCell<HashMap<ObjectId, (gix_object::Kind, Vec<u8>)>>);
+
+impl gix_object::Find for Objects {
+    fn try_find<'a>(
+        &self,
+        id: &oid,
+        buffer: &'a mut Vec<u8>,
+    ) -> std::result::Result<Option<gix_object::Data<'a>>, gix_object::find::Error> {
+        Ok(self.0.borrow().get(id).map(|(kind, data)| {
+            buffer.clear();
+            buffer.extend_from_slice(data);
+            gix_object::Data {
+                kind: *kind,
+                data: buffer.as_slice(),
+            }
+        }))
+    }
+}
+
+impl Objects {
+    fn write(&self, object: &dyn WriteTo) -> std::result::Result<ObjectId, std::io::Error> {
+        let mut data = Vec::new();
+        object.write_to(&mut data)?;
+        let id = gix_object::compute_hash(gix_hash::Kind::Sha1, object.kind(), &data);
+        self.0.borrow_mut().insert(id, (object.kind(), data));
+        Ok(id)
+    }
+
+    /// Write a tree with `files`, each of which is a path and its content, along with their kind.
+    fn tree_with_kinds(&self, files: &[(&str, &str, EntryKind)]) -> Result<ObjectId> {
+        let mut editor = gix_object::tree::Editor::new(Default::default(), self, gix_hash::Kind::Sha1);
+        for (path, content, kind) in files {
+            let blob = self.write(&gix_object::BlobRef {
+                data: content.as_bytes(),
+            })?;
+            editor.upsert(path.split('/'), *kind, blob)?;
+        }
+        Ok(editor.write(|tree| self.write(tree))?)
+    }
+
+    /// Write a tree with `files`, each of which is a path and its content.
+    fn tree(&self, files: &[(&str, &str)]) -> Result<ObjectId> {
+        self.tree_with_kinds(
+            &files
+                .iter()
+                .map(|(path, content)| (*path, *content, EntryKind::Blob))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn merge(&self, base: Option<ObjectId>, ours: ObjectId, theirs: ObjectId) -> Result<gix_merge::tree::Outcome> {
+        Ok(gix_merge::tree(
+            base.as_deref(),
+            &ours,
+            &theirs,
+            labels(),
+            self,
+            |object| self.write(object),
+        )?)
+    }
+
+    /// Return the content of the file at `path` in `tree`.
+    fn content(&self, tree: ObjectId, path: &str) -> Result<String> {
+        let entries = gix_merge::tree::entries_by_path(&tree, self)?;
+        let entry = entries.get(path.as_bytes()).expect("path exists in merged tree");
+        let mut buf = Vec::new();
+        let data = gix_object::FindExt::find_blob(self, &entry.id, &mut buf)?.data;
+        Ok(String::from_utf8(data.to_owned())?)
+    }
+}
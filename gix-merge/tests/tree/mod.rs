@@ -0,0 +1,137 @@
+use gix_merge::tree::ConflictKind;
+use gix_object::tree::EntryKind;
+
+use crate::Objects;
+
+fn conflicts(outcome: &gix_merge::tree::Outcome) -> Vec<(String, ConflictKind)> {
+    outcome
+        .conflicts
+        .iter()
+        .map(|conflict| (conflict.path.to_string(), conflict.kind))
+        .collect()
+}
+
+#[test]
+fn changes_to_different_files_and_regions_are_combined() -> crate::Result {
+    let odb = Objects::default();
+    let base = odb.tree(&[("a", "1\n2\n3\n4\n5\n"), ("dir/b", "b\n"), ("c", "c\n")])?;
+    let ours = odb.tree(&[("a", "one\n2\n3\n4\n5\n"), ("dir/b", "ours\n"), ("c", "c\n")])?;
+    let theirs = odb.tree(&[("a", "1\n2\n3\n4\nfive\n"), ("dir/b", "b\n"), ("new", "new\n")])?;
+
+    let outcome = odb.merge(Some(base), ours, theirs)?;
+    assert!(!outcome.has_conflicts());
+    assert_eq!(odb.content(outcome.tree, "a")?, "one\n2\n3\n4\nfive\n");
+    assert_eq!(odb.content(outcome.tree, "dir/b")?, "ours\n");
+    assert_eq!(odb.content(outcome.tree, "new")?, "new\n");
+    assert!(
+        !gix_merge::tree::entries_by_path(&outcome.tree, &odb)?.contains_key(b"c".as_slice()),
+        "their deletion is applied"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicting_changes_are_reported_and_marked() -> crate::Result {
+    let odb = Objects::default();
+    let base = odb.tree(&[("a", "a\nb\nc\n"), ("deleted", "d\n")])?;
+    let ours = odb.tree(&[("a", "a\nours\nc\n"), ("deleted", "modified\n")])?;
+    let theirs = odb.tree(&[("a", "a\ntheirs\nc\n")])?;
+
+    let outcome = odb.merge(Some(base), ours, theirs)?;
+    assert_eq!(
+        conflicts(&outcome),
+        [
+            ("a".to_string(), ConflictKind::Content),
+            ("deleted".into(), ConflictKind::ModifyDelete)
+        ]
+    );
+    assert_eq!(
+        odb.content(outcome.tree, "a")?,
+        "a\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\nc\n"
+    );
+    assert_eq!(
+        odb.content(outcome.tree, "deleted")?,
+        "modified\n",
+        "the modified side is kept"
+    );
+    let deleted = &outcome.conflicts[1];
+    assert!(deleted.base.is_some() && deleted.ours.is_some() && deleted.theirs.is_none());
+    Ok(())
+}
+
+#[test]
+fn without_base_identical_additions_merge_cleanly() -> crate::Result {
+    let odb = Objects::default();
+    let ours = odb.tree(&[("same", "same\n"), ("ours", "ours\n")])?;
+    let theirs = odb.tree(&[("same", "same\n"), ("theirs", "theirs\n")])?;
+
+    let outcome = odb.merge(None, ours, theirs)?;
+    assert!(!outcome.has_conflicts());
+    assert_eq!(
+        gix_merge::tree::entries_by_path(&outcome.tree, &odb)?
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["ours", "same", "theirs"]
+    );
+    Ok(())
+}
+
+#[test]
+fn binary_files_and_modes_conflict_if_changed_differently() -> crate::Result {
+    let odb = Objects::default();
+    let base = odb.tree_with_kinds(&[("binary", "\0base", EntryKind::Blob), ("mode", "m\n", EntryKind::Blob)])?;
+    let ours = odb.tree_with_kinds(&[
+        ("binary", "\0ours", EntryKind::Blob),
+        ("mode", "ours\n", EntryKind::BlobExecutable),
+    ])?;
+    let theirs = odb.tree_with_kinds(&[
+        ("binary", "\0theirs", EntryKind::Blob),
+        ("mode", "m\n", EntryKind::Link),
+    ])?;
+
+    let outcome = odb.merge(Some(base), ours, theirs)?;
+    assert_eq!(
+        conflicts(&outcome),
+        [
+            ("binary".to_string(), ConflictKind::Unmergeable),
+            ("mode".into(), ConflictKind::Unmergeable)
+        ],
+        "symbolic links can't be merged with files"
+    );
+    assert_eq!(odb.content(outcome.tree, "binary")?, "\0ours", "our version is kept");
+
+    let theirs = odb.tree_with_kinds(&[("binary", "\0base", EntryKind::Blob), ("mode", "m\n", EntryKind::Blob)])?;
+    let ours = odb.tree_with_kinds(&[
+        ("binary", "\0base", EntryKind::Blob),
+        ("mode", "m\n", EntryKind::BlobExecutable),
+    ])?;
+    let outcome = odb.merge(Some(base), ours, theirs)?;
+    assert!(!outcome.has_conflicts(), "only one side changed the mode");
+    assert_eq!(
+        gix_merge::tree::entries_by_path(&outcome.tree, &odb)?[b"mode".as_slice()]
+            .mode
+            .kind(),
+        EntryKind::BlobExecutable
+    );
+    Ok(())
+}
+
+#[test]
+fn files_and_directories_at_the_same_path_conflict() -> crate::Result {
+    let odb = Objects::default();
+    let base = odb.tree(&[("a", "a\n")])?;
+    let ours = odb.tree(&[("a", "a\n"), ("path", "file\n")])?;
+    let theirs = odb.tree(&[("a", "a\n"), ("path/file", "in directory\n")])?;
+
+    let outcome = odb.merge(Some(base), ours, theirs)?;
+    assert_eq!(conflicts(&outcome), [("path".to_string(), ConflictKind::FileDirectory)]);
+    assert_eq!(
+        odb.content(outcome.tree, "path/file")?,
+        "in directory\n",
+        "the directory is kept"
+    );
+    let conflict = &outcome.conflicts[0];
+    assert!(conflict.ours.is_some() && conflict.theirs.is_none());
+    Ok(())
+}
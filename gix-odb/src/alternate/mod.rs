@@ -43,9 +43,44 @@ pub enum Error {
 /// if there are no alternates).
 /// It is an error once a repository is seen again as it would lead to a cycle.
 pub fn resolve(objects_directory: PathBuf, current_dir: &std::path::Path) -> Result<Vec<PathBuf>, Error> {
-    let mut dirs = vec![(0, objects_directory.clone())];
+    resolve_with_additional(objects_directory, &[], current_dir)
+}
+
+/// Like [`resolve()`], but also use the `additional` object directories as alternates, along with their own alternates.
+/// Relative `additional` paths are resolved relative to `current_dir`, and these directories are ignored if they were
+/// seen already, as is the case if they are also listed in `info/alternates`.
+///
+/// This is used to handle the `GIT_ALTERNATE_OBJECT_DIRECTORIES` environment variable, whose value can be parsed with
+/// [`parse::path_list()`].
+pub fn resolve_with_additional(
+    objects_directory: PathBuf,
+    additional: &[PathBuf],
+    current_dir: &std::path::Path,
+) -> Result<Vec<PathBuf>, Error> {
     let mut out = Vec::new();
     let mut seen = vec![gix_path::realpath_opts(&objects_directory, current_dir, MAX_SYMLINKS)?];
+    resolve_into(objects_directory, current_dir, &mut seen, &mut out)?;
+    for dir in additional {
+        let dir = current_dir.join(dir);
+        let dir_canonicalized = gix_path::realpath_opts(&dir, current_dir, MAX_SYMLINKS)?;
+        if seen.contains(&dir_canonicalized) {
+            continue;
+        }
+        seen.push(dir_canonicalized);
+        out.push(dir.clone());
+        resolve_into(dir, current_dir, &mut seen, &mut out)?;
+    }
+    Ok(out)
+}
+
+/// Put all alternates of `objects_directory` into `out`, recursively.
+fn resolve_into(
+    objects_directory: PathBuf,
+    current_dir: &std::path::Path,
+    seen: &mut Vec<PathBuf>,
+    out: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let mut dirs = vec![(0, objects_directory.clone())];
     while let Some((depth, dir)) = dirs.pop() {
         match fs::read(dir.join("info").join("alternates")) {
             Ok(input) => {
@@ -53,7 +88,7 @@ pub fn resolve(objects_directory: PathBuf, current_dir: &std::path::Path) -> Res
                     let path = objects_directory.join(path);
                     let path_canonicalized = gix_path::realpath_opts(&path, current_dir, MAX_SYMLINKS)?;
                     if seen.contains(&path_canonicalized) {
-                        return Err(Error::Cycle(seen));
+                        return Err(Error::Cycle(std::mem::take(seen)));
                     }
                     seen.push(path_canonicalized);
                     dirs.push((depth + 1, path));
@@ -66,5 +101,5 @@ pub fn resolve(objects_directory: PathBuf, current_dir: &std::path::Path) -> Res
             out.push(dir);
         }
     }
-    Ok(out)
+    Ok(())
 }
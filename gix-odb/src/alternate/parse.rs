@@ -12,6 +12,34 @@ pub enum Error {
     Unquote(#[from] gix_quote::ansi_c::undo::Error),
 }
 
+/// Parse `input` as list of paths separated by the platform-specific path-list separator, `;` on Windows and `:` everywhere
+/// else, as used in the `GIT_ALTERNATE_OBJECT_DIRECTORIES` environment variable.
+///
+/// Paths starting with `"` are ANSI-C unquoted, which allows them to contain the separator. Empty paths are skipped.
+pub fn path_list(mut input: &[u8]) -> Result<Vec<PathBuf>, Error> {
+    let separator = if cfg!(windows) { b';' } else { b':' };
+    let mut out = Vec::new();
+    while !input.is_empty() {
+        let (path, rest) = if input.starts_with(b"\"") {
+            let (path, consumed) = gix_quote::ansi_c::undo(input.as_bstr())?;
+            (path, &input[consumed..])
+        } else {
+            let end = input.find_byte(separator).unwrap_or(input.len());
+            (Cow::Borrowed(input[..end].as_bstr()), &input[end..])
+        };
+        input = rest.strip_prefix(&[separator]).unwrap_or(rest);
+        if path.is_empty() {
+            continue;
+        }
+        out.push(
+            gix_path::try_from_bstr(path.as_ref())
+                .map_err(|_| Error::PathConversion(path.to_vec()))?
+                .into_owned(),
+        );
+    }
+    Ok(out)
+}
+
 pub(crate) fn content(input: &[u8]) -> Result<Vec<PathBuf>, Error> {
     let mut out = Vec::new();
     for line in input.split(|b| *b == b'\n') {
@@ -0,0 +1,291 @@
+//! Write large amounts of objects directly into packs, bypassing loose object storage.
+//!
+//! This is most useful for tools that convert repositories or import history, which create millions of objects
+//! that would otherwise each end up in their own loose object file.
+use std::{io::Write as _, path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_hash::ObjectId;
+use gix_object::Kind;
+
+/// Options for use in [`Writer::new()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The amount of threads to use at most when hashing batches of objects or compressing objects. If `None`, all logical cores are used.
+    pub thread_limit: Option<usize>,
+    /// Once the uncompressed size of all buffered objects exceeds this amount of bytes, they are written into a new pack automatically.
+    pub flush_threshold_in_bytes: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            thread_limit: None,
+            flush_threshold_in_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// A writer which buffers objects in memory, and writes them into a new pack along with its index once
+/// enough objects were collected, or when [flushed](Writer::flush()) explicitly.
+///
+/// Objects are deduplicated among those that are buffered, but it's up to the caller to avoid writing objects that
+/// already exist in an object database. Using an instance as [custom write policy](crate::store::WritePolicy::Custom) of
+/// a [store handle](crate::store::Handle) does exactly that.
+///
+/// Note that buffered objects are discarded when the instance is dropped, so [`flush()`](Writer::flush()) must be called
+/// once all objects were written.
+pub struct Writer {
+    pack_dir: PathBuf,
+    object_hash: gix_hash::Kind,
+    options: Options,
+    state: parking_lot::Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    objects: Vec<(ObjectId, Kind, Vec<u8>)>,
+    seen: gix_hashtable::HashSet,
+    buffered_bytes: usize,
+    outcomes: Vec<gix_pack::bundle::write::Outcome>,
+}
+
+/// The error returned by [`Writer::flush()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not compress objects or write the pack data")]
+    Io(#[from] std::io::Error),
+    #[error("Could not write pack and index into the pack directory")]
+    BundleWrite(#[from] gix_pack::bundle::write::Error),
+    #[error("Cannot write more than {} objects into a single pack", u32::MAX)]
+    TooManyObjects,
+}
+
+/// Lifecycle
+impl Writer {
+    /// Create a new instance to write packs into the `pack` directory within `objects_dir`, with objects hashed
+    /// with `object_hash`.
+    pub fn new(objects_dir: impl Into<PathBuf>, object_hash: gix_hash::Kind, options: Options) -> Self {
+        Writer {
+            pack_dir: objects_dir.into().join("pack"),
+            object_hash,
+            options,
+            state: Default::default(),
+        }
+    }
+}
+
+/// Access
+impl Writer {
+    /// Return the amount of objects that are buffered and not yet written into a pack.
+    pub fn num_buffered_objects(&self) -> usize {
+        self.state.lock().objects.len()
+    }
+
+    /// Take the result of all packs written so far, in the order they were written.
+    ///
+    /// Each of these has a `keep_path`, which should be deleted once references point to the newly written objects.
+    pub fn take_written_packs(&self) -> Vec<gix_pack::bundle::write::Outcome> {
+        std::mem::take(&mut self.state.lock().outcomes)
+    }
+}
+
+/// Writing
+impl Writer {
+    /// Add all `objects` at once, hashing them in parallel, and return their ids in order.
+    ///
+    /// This is faster than writing them one by one if many objects are available.
+    pub fn write_many(&self, objects: Vec<(Kind, Vec<u8>)>) -> Result<Vec<ObjectId>, Error> {
+        let object_hash = self.object_hash;
+        let chunk_size = 1000;
+        let mut chunks = Vec::new();
+        let mut objects = objects.into_iter().peekable();
+        while objects.peek().is_some() {
+            chunks.push(objects.by_ref().take(chunk_size).collect::<Vec<_>>());
+        }
+        let num_chunks = chunks.len();
+        let mut hashed = gix_features::parallel::in_parallel_if(
+            || num_chunks > 1,
+            chunks.into_iter().enumerate(),
+            self.options.thread_limit,
+            |_| (),
+            move |(chunk_index, chunk), _state| {
+                let hashed: Vec<_> = chunk
+                    .into_iter()
+                    .map(|(kind, data)| (gix_object::compute_hash(object_hash, kind, &data), kind, data))
+                    .collect();
+                (chunk_index, hashed)
+            },
+            CollectHashed::default(),
+        )
+        .unwrap_or_else(|err| match err {});
+        hashed.sort_by_key(|(chunk_index, _)| *chunk_index);
+
+        let mut ids = Vec::new();
+        for (id, kind, data) in hashed.into_iter().flat_map(|(_, hashed)| hashed) {
+            ids.push(id);
+            self.add(id, kind, data)?;
+        }
+        Ok(ids)
+    }
+
+    /// Write all buffered objects into a new pack and index in the pack directory, and return information about it,
+    /// or `None` if there was nothing to write.
+    ///
+    /// The pack data is compressed using multiple threads.
+    pub fn flush(&self) -> Result<Option<gix_pack::bundle::write::Outcome>, Error> {
+        let mut state = self.state.lock();
+        let outcome = self.write_pack(&mut state)?;
+        if let Some(outcome) = &outcome {
+            state.outcomes.push(outcome.clone());
+        }
+        Ok(outcome)
+    }
+
+    fn add(&self, id: ObjectId, kind: Kind, data: Vec<u8>) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        if !state.seen.insert(id) {
+            return Ok(());
+        }
+        state.buffered_bytes += data.len();
+        state.objects.push((id, kind, data));
+        if state.buffered_bytes >= self.options.flush_threshold_in_bytes {
+            if let Some(outcome) = self.write_pack(&mut state)? {
+                state.outcomes.push(outcome);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_pack(&self, state: &mut State) -> Result<Option<gix_pack::bundle::write::Outcome>, Error> {
+        if state.objects.is_empty() {
+            return Ok(None);
+        }
+        let objects = std::mem::take(&mut state.objects);
+        state.seen.clear();
+        state.buffered_bytes = 0;
+        let num_objects = u32::try_from(objects.len()).map_err(|_| Error::TooManyObjects)?;
+
+        let mut pack = gix_features::hash::Write::new(Vec::new(), self.object_hash);
+        pack.write_all(&gix_pack::data::header::encode(
+            gix_pack::data::Version::V2,
+            num_objects,
+        ))?;
+        let chunk_size = 100;
+        let mut chunks = Vec::new();
+        let mut objects = objects.into_iter().peekable();
+        while objects.peek().is_some() {
+            chunks.push(objects.by_ref().take(chunk_size).collect::<Vec<_>>());
+        }
+        let num_chunks = chunks.len();
+        pack = gix_features::parallel::in_parallel_if(
+            || num_chunks > 1,
+            chunks.into_iter(),
+            self.options.thread_limit,
+            |_| (),
+            |chunk, _state| encode_entries(chunk),
+            AppendEntries { pack },
+        )?;
+        let trailer = pack.hash.digest();
+        let mut pack = pack.inner;
+        pack.extend_from_slice(&trailer);
+
+        let outcome = gix_pack::Bundle::write_to_directory(
+            &mut pack.as_slice(),
+            Some(&self.pack_dir),
+            &mut gix_features::progress::Discard,
+            &AtomicBool::default(),
+            None::<gix_object::find::Never>,
+            gix_pack::bundle::write::Options {
+                thread_limit: self.options.thread_limit,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                index_version: Default::default(),
+                object_hash: self.object_hash,
+                max_pack_size: None,
+                ..Default::default()
+            },
+        )?;
+        Ok(Some(outcome))
+    }
+}
+
+/// Encode all objects in `chunk` as undeltified pack entries.
+fn encode_entries(chunk: Vec<(ObjectId, Kind, Vec<u8>)>) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for (_id, kind, data) in chunk {
+        let header = match kind {
+            Kind::Blob => gix_pack::data::entry::Header::Blob,
+            Kind::Tree => gix_pack::data::entry::Header::Tree,
+            Kind::Commit => gix_pack::data::entry::Header::Commit,
+            Kind::Tag => gix_pack::data::entry::Header::Tag,
+        };
+        header.write_to(data.len() as u64, &mut out)?;
+        let mut deflate = gix_features::zlib::stream::deflate::Write::new(out);
+        deflate.write_all(&data)?;
+        deflate.flush()?;
+        out = deflate.into_inner();
+    }
+    Ok(out)
+}
+
+struct AppendEntries {
+    pack: gix_features::hash::Write<Vec<u8>>,
+}
+
+impl gix_features::parallel::Reduce for AppendEntries {
+    type Input = std::io::Result<Vec<u8>>;
+    type FeedProduce = ();
+    type Output = gix_features::hash::Write<Vec<u8>>;
+    type Error = Error;
+
+    fn feed(&mut self, item: Self::Input) -> Result<Self::FeedProduce, Self::Error> {
+        self.pack.write_all(&item?)?;
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.pack)
+    }
+}
+
+type HashedChunk = (usize, Vec<(ObjectId, Kind, Vec<u8>)>);
+
+#[derive(Default)]
+struct CollectHashed {
+    chunks: Vec<HashedChunk>,
+}
+
+impl gix_features::parallel::Reduce for CollectHashed {
+    type Input = HashedChunk;
+    type FeedProduce = ();
+    type Output = Vec<HashedChunk>;
+    type Error = std::convert::Infallible;
+
+    fn feed(&mut self, item: Self::Input) -> Result<Self::FeedProduce, Self::Error> {
+        self.chunks.push(item);
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.chunks)
+    }
+}
+
+impl crate::Write for Writer {
+    fn write_buf(&self, kind: Kind, from: &[u8]) -> Result<ObjectId, crate::write::Error> {
+        let id = gix_object::compute_hash(self.object_hash, kind, from);
+        self.add(id, kind, from.to_owned())?;
+        Ok(id)
+    }
+
+    fn write_stream(
+        &self,
+        kind: Kind,
+        _size: u64,
+        from: &mut dyn std::io::Read,
+    ) -> Result<ObjectId, crate::write::Error> {
+        let mut buf = Vec::new();
+        from.read_to_end(&mut buf)?;
+        self.write_buf(kind, &buf)
+    }
+}
@@ -136,7 +136,7 @@ mod impls {
     use std::{cell::RefCell, io::Read, ops::DerefMut};
 
     use gix_hash::{oid, ObjectId};
-    use gix_object::{Data, Kind};
+    use gix_object::{Data, Kind, WriteTo};
     use gix_pack::cache::Object;
 
     use crate::{find::Header, pack::data::entry::Location, Cache};
@@ -145,6 +145,14 @@ mod impls {
     where
         S: crate::Write,
     {
+        fn write(&self, object: &dyn WriteTo) -> Result<ObjectId, crate::write::Error> {
+            self.inner.write(object)
+        }
+
+        fn write_buf(&self, kind: Kind, from: &[u8]) -> Result<ObjectId, crate::write::Error> {
+            self.inner.write_buf(kind, from)
+        }
+
         fn write_stream(&self, kind: Kind, size: u64, from: &mut dyn Read) -> Result<ObjectId, crate::write::Error> {
             self.inner.write_stream(kind, size, from)
         }
@@ -69,6 +69,9 @@ pub fn sink(object_hash: gix_hash::Kind) -> Sink {
 ///
 pub mod memory;
 
+///
+pub mod bulk;
+
 mod sink;
 
 ///
@@ -117,6 +120,9 @@ pub struct Store {
     /// Keeping it here helps to assure consistency even while a process changes its CWD.
     pub(crate) current_dir: PathBuf,
 
+    /// Object directories to use as alternates in addition to the ones configured in the `info/alternates` file.
+    pub(crate) alternates: Vec<PathBuf>,
+
     /// A set of replacements that given a source OID return a destination OID. The vector is sorted.
     pub(crate) replacements: Vec<(gix_hash::ObjectId, gix_hash::ObjectId)>,
 
@@ -140,6 +146,10 @@ pub struct Store {
     use_multi_pack_index: bool,
     /// The hash kind to use for some operations
     object_hash: gix_hash::Kind,
+    /// How to sync newly written loose objects, if at all.
+    fsync_loose_objects: Option<gix_fs::fsync::Method>,
+    /// The limits to enforce when decoding objects.
+    limits: gix_pack::data::decode::Limits,
 }
 
 /// Create a new cached handle to the object store with support for additional options.
@@ -9,7 +9,7 @@ use std::sync::Arc;
 /// An object database to read from any implementation but write to memory.
 /// Previously written objects can be returned from memory upon query, which makes the view of objects consistent.
 /// In-Memory objects can be disabled by [taking out its storage](Proxy::take_object_memory). From there in-memory
-/// object can also be persisted one by one.
+/// object can also be persisted one by one, or all at once with [`persist_object_memory()`](Proxy::persist_object_memory()).
 ///
 /// It's possible to turn off the memory by removing it from the instance.
 pub struct Proxy<T> {
@@ -120,6 +120,34 @@ impl<T> Proxy<T> {
     }
 }
 
+/// Persistence
+impl<T> Proxy<T>
+where
+    T: crate::Write,
+{
+    /// Write all objects currently held in memory into the inner object database and remove them from memory,
+    /// returning the amount of objects that were written.
+    ///
+    /// If writing fails, all objects remain in memory, even though some of them may have been written already.
+    /// Note that new objects will still be written to memory after this call.
+    pub fn persist_object_memory(&self) -> Result<usize, crate::write::Error> {
+        let Some(map) = self.memory.as_ref() else {
+            return Ok(0);
+        };
+        let mut map = map.borrow_mut();
+        for (id, (kind, data)) in map.iter() {
+            let written_id = self.inner.write_buf(*kind, data)?;
+            debug_assert_eq!(
+                *id, written_id,
+                "BUG: object hash mismatch between memory and inner database"
+            );
+        }
+        let num_objects = map.len();
+        map.clear();
+        Ok(num_objects)
+    }
+}
+
 impl<T> Clone for Proxy<T>
 where
     T: Clone,
@@ -204,8 +232,22 @@ where
 
 impl<T> crate::Write for Proxy<T>
 where
-    T: crate::Write,
+    T: crate::Write + gix_object::Exists,
 {
+    /// Note that objects that already exist in memory or in the inner database aren't written again.
+    /// Without memory, the inner database is responsible for handling existing objects.
+    fn write_buf(&self, kind: gix_object::Kind, from: &[u8]) -> Result<gix_hash::ObjectId, crate::write::Error> {
+        let Some(map) = self.memory.as_ref() else {
+            return self.inner.write_buf(kind, from);
+        };
+
+        let id = gix_object::compute_hash(self.object_hash, kind, from);
+        if !map.borrow().contains_key(&id) && !self.inner.exists(&id) {
+            map.borrow_mut().insert(id, (kind, from.to_owned()));
+        }
+        Ok(id)
+    }
+
     fn write_stream(
         &self,
         kind: gix_object::Kind,
@@ -7,6 +7,12 @@ impl Store {
         &self.path
     }
 
+    /// Object directories used as alternates in addition to the ones listed in the `info/alternates` file, as passed
+    /// when [opening the store](crate::Store::at_opts()).
+    pub fn alternates(&self) -> &[std::path::PathBuf] {
+        &self.alternates
+    }
+
     /// The kind of object hash to assume when dealing with pack indices and pack data files.
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.object_hash
@@ -17,6 +23,16 @@ impl Store {
         self.use_multi_pack_index
     }
 
+    /// How newly written loose objects are synced, if at all.
+    pub fn fsync_loose_objects(&self) -> Option<gix_fs::fsync::Method> {
+        self.fsync_loose_objects
+    }
+
+    /// The limits enforced when decoding objects.
+    pub fn limits(&self) -> gix_pack::data::decode::Limits {
+        self.limits
+    }
+
     /// An iterator over replacements from object-ids `X` to `X-replaced` as `(X, X-replaced)`, sorted by the original id `X`.
     pub fn replacements(&self) -> impl Iterator<Item = (gix_hash::ObjectId, gix_hash::ObjectId)> + '_ {
         self.replacements.iter().copied()
@@ -119,6 +119,24 @@ pub(crate) mod index_lookup {
             }
         }
 
+        /// Return the path to the pack data file containing `object_id`, or `None` if it isn't contained in this index.
+        pub(crate) fn pack_path_for(&self, object_id: &oid) -> Option<std::path::PathBuf> {
+            match &self.file {
+                handle::SingleOrMultiIndex::Single { index, .. } => {
+                    index.lookup(object_id).map(|_| index.path().with_extension("pack"))
+                }
+                handle::SingleOrMultiIndex::Multi { index, .. } => index.lookup(object_id).map(|entry_index| {
+                    let (pack_index, _) = index.pack_id_and_pack_offset_at_index(entry_index);
+                    index
+                        .path()
+                        .parent()
+                        .expect("multi-index lives in a directory")
+                        .join(&index.index_names()[pack_index as usize])
+                        .with_extension("pack")
+                }),
+            }
+        }
+
         /// Return true if the given object id exists in this index
         pub(crate) fn oid_at_index(&self, entry_index: u32) -> &gix_hash::oid {
             match &self.file {
@@ -255,6 +273,7 @@ impl super::Store {
             store: self.clone(),
             refresh: RefreshMode::default(),
             ignore_replacements: false,
+            write_policy: Default::default(),
             token: Some(token),
             inflate: RefCell::new(Default::default()),
             snapshot: RefCell::new(self.collect_snapshot()),
@@ -272,6 +291,7 @@ impl super::Store {
             store: self.clone(),
             refresh: Default::default(),
             ignore_replacements: false,
+            write_policy: Default::default(),
             token: Some(token),
             inflate: RefCell::new(Default::default()),
             snapshot: RefCell::new(self.collect_snapshot()),
@@ -352,6 +372,9 @@ impl TryFrom<&super::Store> for super::Store {
                 object_hash: Default::default(),
                 use_multi_pack_index: false,
                 current_dir: s.current_dir.clone().into(),
+                alternates: s.alternates.clone(),
+                fsync_loose_objects: s.fsync_loose_objects,
+                limits: s.limits,
             },
         )
     }
@@ -364,6 +387,7 @@ impl super::Handle<Rc<super::Store>> {
         let mut cache = store.to_handle_arc();
         cache.refresh = self.refresh;
         cache.max_recursion_depth = self.max_recursion_depth;
+        cache.write_policy = self.write_policy.clone();
         Ok(cache)
     }
 }
@@ -384,6 +408,7 @@ where
             store: self.store.clone(),
             refresh: self.refresh,
             ignore_replacements: self.ignore_replacements,
+            write_policy: self.write_policy.clone(),
             token: {
                 let token = self.store.register_handle();
                 match self.token.as_ref().expect("token is always set here ") {
@@ -19,6 +19,15 @@ pub struct Options {
     /// The current directory of the process at the time of instantiation.
     /// If unset, it will be retrieved using `gix_fs::current_dir(false)`.
     pub current_dir: Option<std::path::PathBuf>,
+    /// Additional object directories to use as alternates along with the ones listed in the `info/alternates` file,
+    /// typically obtained from `GIT_ALTERNATE_OBJECT_DIRECTORIES` and parsed with [`crate::alternate::parse::path_list()`].
+    /// Relative paths are resolved relative to `current_dir`.
+    pub alternates: Vec<std::path::PathBuf>,
+    /// How to sync newly written loose objects, if at all, typically obtained from `core.fsync` and `core.fsyncMethod`.
+    pub fsync_loose_objects: Option<gix_fs::fsync::Method>,
+    /// Limits to enforce when decoding objects from packs, and whose [maximum object size](gix_pack::data::decode::Limits::max_object_size)
+    /// also applies to loose objects, to bound the resources used by objects from untrusted sources.
+    pub limits: gix_pack::data::decode::Limits,
 }
 
 impl Default for Options {
@@ -28,6 +37,9 @@ impl Default for Options {
             object_hash: Default::default(),
             use_multi_pack_index: true,
             current_dir: None,
+            alternates: Vec::new(),
+            fsync_loose_objects: None,
+            limits: Default::default(),
         }
     }
 }
@@ -77,6 +89,9 @@ impl Store {
             object_hash,
             use_multi_pack_index,
             current_dir,
+            alternates,
+            fsync_loose_objects,
+            limits,
         }: Options,
     ) -> std::io::Result<Self> {
         let _span = gix_features::trace::detail!("gix_odb::Store::at()");
@@ -96,8 +111,9 @@ impl Store {
         let slot_count = match slots {
             Slots::Given(n) => n as usize,
             Slots::AsNeededByDiskState { multiplier, minimum } => {
-                let mut db_paths = crate::alternate::resolve(objects_dir.clone(), &current_dir)
-                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+                let mut db_paths =
+                    crate::alternate::resolve_with_additional(objects_dir.clone(), &alternates, &current_dir)
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
                 db_paths.insert(0, objects_dir.clone());
                 let num_slots = super::Store::collect_indices_and_mtime_sorted_by_size(db_paths, None, None)
                     .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
@@ -120,10 +136,13 @@ impl Store {
             write: Default::default(),
             replacements,
             path: objects_dir,
+            alternates,
             files: Vec::from_iter(std::iter::repeat_with(MutableIndexAndPack::default).take(slot_count)),
             index: ArcSwap::new(Arc::new(SlotMapIndex::default())),
             use_multi_pack_index,
             object_hash,
+            fsync_loose_objects,
+            limits,
             num_handles_stable: Default::default(),
             num_handles_unstable: Default::default(),
             num_disk_state_consolidation: Default::default(),
@@ -215,7 +215,11 @@ impl super::Store {
         self.num_disk_state_consolidation.fetch_add(1, Ordering::Relaxed);
 
         let db_paths: Vec<_> = std::iter::once(objects_directory.to_owned())
-            .chain(crate::alternate::resolve(objects_directory.clone(), &self.current_dir)?)
+            .chain(crate::alternate::resolve_with_additional(
+                objects_directory.clone(),
+                &self.alternates,
+                &self.current_dir,
+            )?)
             .collect();
 
         // turn db paths into loose object databases. Reuse what's there, but only if it is in the right order.
@@ -229,7 +233,11 @@ impl super::Store {
             Arc::new(
                 db_paths
                     .iter()
-                    .map(|path| crate::loose::Store::at(path, self.object_hash))
+                    .map(|path| {
+                        crate::loose::Store::at(path, self.object_hash)
+                            .with_fsync(self.fsync_loose_objects)
+                            .with_max_object_size(self.limits.max_object_size)
+                    })
                     .collect::<Vec<_>>(),
             )
         } else {
@@ -23,6 +23,8 @@ where
 
     /// If true, replacements will not be performed even if these are available.
     pub ignore_replacements: bool,
+    /// Decide where objects are placed when writing them through this handle.
+    pub write_policy: WritePolicy,
 
     pub(crate) token: Option<handle::Mode>,
     snapshot: RefCell<load_index::Snapshot>,
@@ -43,6 +45,26 @@ pub enum RefreshMode {
     Never,
 }
 
+/// Decide where newly written objects are placed.
+#[derive(Default, Clone)]
+pub enum WritePolicy {
+    /// Write objects as loose objects into the object directory the store was opened with, just like `git` does.
+    #[default]
+    PrimaryLooseDb,
+    /// Pass all objects to the given implementation, which may for instance collect them into a pack,
+    /// or place them into an object database other than the one the store was opened with.
+    Custom(std::sync::Arc<dyn crate::Write + Send + Sync>),
+}
+
+impl std::fmt::Debug for WritePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WritePolicy::PrimaryLooseDb => f.write_str("PrimaryLooseDb"),
+            WritePolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
 impl RefreshMode {
     /// Set this refresh mode to never refresh.
     pub fn never(&mut self) {
@@ -1,7 +1,7 @@
 use std::{io::Read, ops::Deref};
 
 use gix_hash::ObjectId;
-use gix_object::Kind;
+use gix_object::{Kind, WriteTo};
 
 use crate::store;
 
@@ -28,7 +28,26 @@ impl<S> crate::Write for store::Handle<S>
 where
     S: Deref<Target = dynamic::Store> + Clone,
 {
+    fn write(&self, object: &dyn WriteTo) -> Result<ObjectId, crate::write::Error> {
+        let mut buf = Vec::with_capacity(2048);
+        object.write_to(&mut buf)?;
+        self.write_buf(object.kind(), &buf)
+    }
+
+    /// Note that the object won't be written if it already exists in any of our object databases, including alternates.
+    /// Instead, the loose object or the pack containing it is [freshened](Self::freshen_without_refresh()).
+    fn write_buf(&self, kind: Kind, from: &[u8]) -> Result<ObjectId, crate::write::Error> {
+        let id = gix_object::compute_hash(self.store.object_hash, kind, from);
+        if self.freshen_without_refresh(&id) {
+            return Ok(id);
+        }
+        self.write_stream(kind, from.len() as u64, &mut &*from)
+    }
+
     fn write_stream(&self, kind: Kind, size: u64, from: &mut dyn Read) -> Result<ObjectId, crate::write::Error> {
+        if let store::WritePolicy::Custom(out) = &self.write_policy {
+            return out.write_stream(kind, size, from);
+        }
         let mut snapshot = self.snapshot.borrow_mut();
         Ok(match snapshot.loose_dbs.first() {
             Some(ldb) => ldb.write_stream(kind, size, from)?,
@@ -44,3 +63,42 @@ where
         })
     }
 }
+
+impl<S> store::Handle<S>
+where
+    S: Deref<Target = dynamic::Store> + Clone,
+{
+    /// Like [`contains()`](gix_pack::Find::contains()), but loads all indices we know without ever refreshing the
+    /// on-disk state, as this would be too costly to do for every object that is about to be written.
+    ///
+    /// If the object exists, the modification time of its loose object or of the pack containing it is set to now.
+    /// This is what `git` does to prevent a concurrent prune from deleting an unreachable object that is about to
+    /// become reachable again, as pruning only affects objects that are older than its grace period.
+    /// If the file can't be freshened, `false` is returned so the object is written anew.
+    fn freshen_without_refresh(&self, id: &gix_hash::oid) -> bool {
+        let mut snapshot = self.snapshot.borrow_mut();
+        loop {
+            let path = snapshot
+                .indices
+                .iter()
+                .find_map(|index| index.pack_path_for(id))
+                .or_else(|| {
+                    snapshot
+                        .loose_dbs
+                        .iter()
+                        .find(|ldb| ldb.contains(id))
+                        .map(|ldb| ldb.object_path(id))
+                });
+            if let Some(path) = path {
+                return filetime::set_file_mtime(path, filetime::FileTime::now()).is_ok();
+            }
+            match self.store.load_one_index(store::RefreshMode::Never, snapshot.marker) {
+                Ok(Some(new_snapshot)) => {
+                    *snapshot = new_snapshot;
+                    self.clear_cache();
+                }
+                Ok(None) | Err(_) => return false,
+            }
+        }
+    }
+}
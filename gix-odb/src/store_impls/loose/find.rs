@@ -19,6 +19,8 @@ pub enum Error {
     Decode(#[from] gix_object::decode::LooseHeaderDecodeError),
     #[error("Cannot store {size} in memory as it's not representable")]
     OutOfMemory { size: u64 },
+    #[error("Object at '{path}' has {size} bytes and exceeds the configured maximum of {max} bytes")]
+    ObjectTooLarge { size: u64, max: u64, path: PathBuf },
     #[error("Could not {action} data at '{path}'")]
     Io {
         source: std::io::Error,
@@ -218,6 +220,9 @@ impl Store {
         let decompressed_start = bytes_read;
         let (kind, size, header_size) =
             gix_object::decode::loose_header(&buf[decompressed_start..decompressed_start + consumed_out])?;
+        if let Some(max) = self.max_object_size.filter(|max| size > *max) {
+            return Err(Error::ObjectTooLarge { size, max, path });
+        }
 
         if status == zlib::Status::StreamEnd {
             let decompressed_body_bytes_sans_header =
@@ -12,6 +12,10 @@ pub struct Store {
     pub(crate) path: PathBuf,
     /// The kind of hash we should assume during iteration and when writing new objects.
     pub(crate) object_hash: gix_hash::Kind,
+    /// How to sync newly written objects, if at all.
+    pub(crate) fsync: Option<gix_fs::fsync::Method>,
+    /// The size of the largest object we are willing to read into memory, if limited.
+    pub(crate) max_object_size: Option<u64>,
 }
 
 /// Initialization
@@ -26,9 +30,26 @@ impl Store {
         Store {
             path: objects_directory.into(),
             object_hash,
+            fsync: None,
+            max_object_size: None,
         }
     }
 
+    /// Sync each newly written object to storage using `method`, or don't sync them if `None`.
+    ///
+    /// With [`Batch`](gix_fs::fsync::Method::Batch), objects are only written out and become durable once
+    /// [`flush_batch()`](Store::flush_batch()) is called, or once any other file on the same filesystem is synced.
+    pub fn with_fsync(mut self, method: Option<gix_fs::fsync::Method>) -> Self {
+        self.fsync = method;
+        self
+    }
+
+    /// Refuse to read objects larger than `max_object_size` bytes into memory, or read objects of any size if `None`.
+    pub fn with_max_object_size(mut self, max_object_size: Option<u64>) -> Self {
+        self.max_object_size = max_object_size;
+        self
+    }
+
     /// Return the path to our `objects` directory.
     pub fn path(&self) -> &Path {
         &self.path
@@ -38,6 +59,16 @@ impl Store {
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.object_hash
     }
+
+    /// Return how newly written objects are synced, if at all.
+    pub fn fsync(&self) -> Option<gix_fs::fsync::Method> {
+        self.fsync
+    }
+
+    /// Return the size of the largest object we read into memory, if limited.
+    pub fn max_object_size(&self) -> Option<u64> {
+        self.max_object_size
+    }
 }
 
 fn hash_path(id: &gix_hash::oid, mut root: PathBuf) -> PathBuf {
@@ -105,6 +105,14 @@ impl Store {
     }
 }
 
+impl Store {
+    /// Make all objects written so far durable, which is only needed if objects are synced with
+    /// [`Batch`](gix_fs::fsync::Method::Batch).
+    pub fn flush_batch(&self) -> io::Result<()> {
+        gix_fs::fsync::flush_batch(&self.path)
+    }
+}
+
 impl Store {
     fn dest(&self) -> Result<hash::Write<CompressedTempfile>, Error> {
         #[cfg_attr(not(unix), allow(unused_mut))]
@@ -141,6 +149,17 @@ impl Store {
             }
         }
         let file = file.into_inner();
+        if let Some(method) = self.fsync {
+            match method {
+                gix_fs::fsync::Method::Batch => gix_fs::fsync::writeout(file.as_file()),
+                method => gix_fs::fsync::file(file.as_file(), method),
+            }
+            .map_err(|err| Error::Io {
+                source: err,
+                message: "sync object file in",
+                path: self.path.to_owned(),
+            })?;
+        }
         let res = file.persist(&object_path);
         // On windows, we assume that such errors are due to its special filesystem semantics,
         // on any other platform that would be a legitimate error though.
@@ -100,3 +100,62 @@ fn no_alternate_in_first_objects_dir() -> crate::Result {
     assert!(alternate::resolve(tmp.path().to_owned(), &std::env::current_dir()?)?.is_empty());
     Ok(())
 }
+
+#[test]
+fn additional_alternates_are_resolved_along_with_their_own_alternates() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (additional, additional_alternate) = alternate(tmp.path().join("b"), tmp.path().join("c"))?;
+    let (from, to) = alternate(tmp.path().join("a"), tmp.path().join("d"))?;
+
+    let alternates = alternate::resolve_with_additional(
+        from.clone(),
+        &[additional.clone(), to.clone(), from],
+        &std::env::current_dir()?,
+    )?;
+    assert_eq!(
+        alternates,
+        vec![to, additional, additional_alternate],
+        "additional alternates come last, and those that were already seen are ignored"
+    );
+    Ok(())
+}
+
+#[test]
+fn additional_alternates_are_relative_to_the_current_dir() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let from = tmp.path().join("a");
+    std::fs::create_dir(&from)?;
+    let alternates = alternate::resolve_with_additional(from, &[PathBuf::from("b")], tmp.path())?;
+    assert_eq!(alternates, vec![tmp.path().join("b")]);
+    Ok(())
+}
+
+mod path_list {
+    use std::path::PathBuf;
+
+    use gix_odb::alternate::parse::path_list;
+
+    #[test]
+    fn empty_and_empty_entries() -> crate::Result {
+        assert!(path_list(b"")?.is_empty());
+        let sep = if cfg!(windows) { ";" } else { ":" };
+        assert!(path_list(format!("{sep}{sep}").as_bytes())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn plain_and_quoted_paths_separated_by_the_platform_separator() -> crate::Result {
+        let sep = if cfg!(windows) { ";" } else { ":" };
+        let input = format!("a{sep}\"b{sep}\\tc\"{sep}{sep}d");
+        assert_eq!(
+            path_list(input.as_bytes())?,
+            vec![
+                PathBuf::from("a"),
+                PathBuf::from(format!("b{sep}\tc")),
+                PathBuf::from("d")
+            ],
+            "quoted paths may contain the separator"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use gix_object::{Exists, FindExt, Kind};
+use gix_odb::Write;
+
+#[test]
+fn objects_are_written_into_a_pack_once_flushed() -> crate::Result {
+    let objects_dir = gix_testtools::tempfile::tempdir()?;
+    std::fs::create_dir(objects_dir.path().join("pack"))?;
+    let writer = gix_odb::bulk::Writer::new(objects_dir.path(), gix_hash::Kind::Sha1, Default::default());
+
+    let hello = writer.write_buf(Kind::Blob, b"hello world")?;
+    assert_eq!(hello, crate::hex_to_id("95d09f2b10159347eece71399a7e2e907ea3df4f"));
+    assert_eq!(
+        writer.write_buf(Kind::Blob, b"hello world")?,
+        hello,
+        "the same object is only buffered once"
+    );
+    let ids = writer.write_many(
+        (0..2500)
+            .map(|i| (Kind::Blob, format!("blob {i}").into_bytes()))
+            .collect(),
+    )?;
+    assert_eq!(ids.len(), 2500, "ids are returned in order of their objects");
+    assert_eq!(writer.num_buffered_objects(), 2501);
+
+    let outcome = writer.flush()?.expect("objects were buffered");
+    assert_eq!(outcome.index.num_objects, 2501);
+    assert!(
+        outcome.keep_path.as_ref().is_some_and(|p| p.is_file()),
+        "the pack is protected until refs point to it"
+    );
+    assert_eq!(writer.num_buffered_objects(), 0);
+    assert!(writer.flush()?.is_none(), "there is nothing left to write");
+    assert_eq!(writer.take_written_packs().len(), 1);
+
+    let handle = gix_odb::at(objects_dir.path())?;
+    let mut buf = Vec::new();
+    assert_eq!(handle.find_blob(&hello, &mut buf)?.data, b"hello world");
+    assert_eq!(handle.find_blob(&ids[1234], &mut buf)?.data, b"blob 1234");
+    let loose = gix_odb::loose::Store::at(objects_dir.path(), gix_hash::Kind::Sha1);
+    assert_eq!(loose.iter().count(), 0, "no loose object was written");
+    Ok(())
+}
+
+#[test]
+fn exceeding_the_threshold_flushes_automatically() -> crate::Result {
+    let objects_dir = gix_testtools::tempfile::tempdir()?;
+    std::fs::create_dir(objects_dir.path().join("pack"))?;
+    let writer = std::sync::Arc::new(gix_odb::bulk::Writer::new(
+        objects_dir.path(),
+        gix_hash::Kind::Sha1,
+        gix_odb::bulk::Options {
+            flush_threshold_in_bytes: 10,
+            ..Default::default()
+        },
+    ));
+    let mut handle = gix_odb::at(objects_dir.path())?;
+    handle.write_policy = gix_odb::store::WritePolicy::Custom(writer.clone());
+
+    let first = handle.write_buf(Kind::Blob, b"short")?;
+    assert_eq!(writer.num_buffered_objects(), 1);
+    let second = handle.write_buf(Kind::Blob, b"long enough")?;
+    assert_eq!(writer.num_buffered_objects(), 0, "the threshold was exceeded");
+    assert_eq!(writer.take_written_packs().len(), 1);
+
+    assert!(handle.exists(&first) && handle.exists(&second));
+    Ok(())
+}
@@ -101,6 +101,31 @@ fn with_memory() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn persist_object_memory() -> crate::Result {
+    let (odb, _tmp) = db_rw()?;
+    let existing = hex_to_id("21d3ba9a26b790a4858d67754ae05d04dfce4d0c");
+    let blob_id = odb.write_buf(gix_object::Kind::Blob, b"speculative")?;
+    assert!(odb.exists(&blob_id), "the object is visible through the proxy…");
+    assert!(
+        !(*odb).exists(&blob_id),
+        "…but isn't written to the inner object database"
+    );
+    assert!(odb.exists(&existing));
+
+    assert_eq!(odb.persist_object_memory()?, 1, "one object was written");
+    assert_eq!(odb.num_objects_in_memory(), 0, "memory is cleared");
+    assert!((*odb).exists(&blob_id), "now it's written to the inner object database");
+    assert!(odb.exists(&blob_id), "and still visible through the proxy");
+
+    let blob_id = odb.write_buf(gix_object::Kind::Blob, b"dropped")?;
+    assert_eq!(odb.num_objects_in_memory(), 1, "memory is still used for new objects");
+    odb.reset_object_memory();
+    assert!(!odb.exists(&blob_id), "dropped objects are gone");
+    assert_eq!(odb.persist_object_memory()?, 0, "nothing to write");
+    Ok(())
+}
+
 fn db() -> crate::Result<gix_odb::memory::Proxy<gix_odb::Handle>> {
     let odb = gix_odb::at(
         gix_testtools::scripted_fixture_read_only_standalone("repo_with_loose_objects.sh")?.join(".git/objects"),
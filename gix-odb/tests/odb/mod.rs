@@ -16,6 +16,7 @@ fn db_small_packs() -> gix_odb::Handle {
 }
 
 pub mod alternate;
+pub mod bulk;
 pub mod find;
 pub mod header;
 pub mod memory;
@@ -221,6 +221,102 @@ fn write() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn write_skips_objects_that_already_exist_in_packs() -> crate::Result {
+    let objects_dir = gix_testtools::tempfile::tempdir()?;
+    let pack_dir = objects_dir.path().join("pack");
+    std::fs::create_dir(&pack_dir)?;
+    gix_testtools::copy_recursively_into_existing_dir(fixture_path_standalone("objects/pack"), &pack_dir)?;
+    let handle = gix_odb::at(objects_dir.path())?;
+
+    let id = handle.iter()?.next().expect("at least one packed object")?;
+    let mut buf = Vec::new();
+    let object = handle.find(&id, &mut buf)?;
+    let (kind, data) = (object.kind, object.data.to_owned());
+
+    assert_eq!(handle.write_buf(kind, &data)?, id);
+    let loose = gix_odb::loose::Store::at(objects_dir.path(), gix_hash::Kind::Sha1);
+    assert_eq!(
+        loose.iter().count(),
+        0,
+        "the object exists in a pack, so it's not written as loose object"
+    );
+
+    let new_id = handle.write_buf(gix_object::Kind::Blob, b"definitely not in any pack")?;
+    assert!(loose.contains(&new_id), "new objects are still written");
+    Ok(())
+}
+
+#[test]
+fn write_freshens_objects_that_already_exist() -> crate::Result {
+    let objects_dir = gix_testtools::tempfile::tempdir()?;
+    let pack_dir = objects_dir.path().join("pack");
+    std::fs::create_dir(&pack_dir)?;
+    gix_testtools::copy_recursively_into_existing_dir(fixture_path_standalone("objects/pack"), &pack_dir)?;
+    let handle = gix_odb::at(objects_dir.path())?;
+
+    let past = filetime::FileTime::from_unix_time(1000, 0);
+    let mtime = |path: &std::path::Path| -> std::io::Result<filetime::FileTime> {
+        Ok(filetime::FileTime::from_last_modification_time(&std::fs::metadata(path)?))
+    };
+    let pack_paths: Vec<_> = std::fs::read_dir(&pack_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|path| path.extension().map_or(false, |ext| ext == "pack"))
+        .collect();
+    for path in &pack_paths {
+        filetime::set_file_mtime(path, past)?;
+    }
+
+    let id = handle.iter()?.next().expect("at least one packed object")?;
+    let mut buf = Vec::new();
+    let object = handle.find(&id, &mut buf)?;
+    let (kind, data) = (object.kind, object.data.to_owned());
+    assert_eq!(handle.write_buf(kind, &data)?, id);
+
+    let num_freshened_packs = pack_paths
+        .iter()
+        .map(|path| mtime(path))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|time| *time != past)
+        .count();
+    assert_eq!(num_freshened_packs, 1, "only the pack containing the object is freshened");
+
+    let loose = gix_odb::loose::Store::at(objects_dir.path(), gix_hash::Kind::Sha1);
+    let new_id = handle.write_buf(gix_object::Kind::Blob, b"definitely not in any pack")?;
+    let loose_path = loose.object_path(&new_id);
+    filetime::set_file_mtime(&loose_path, past)?;
+    assert_eq!(handle.write_buf(gix_object::Kind::Blob, b"definitely not in any pack")?, new_id);
+    assert_ne!(mtime(&loose_path)?, past, "existing loose objects are freshened as well");
+    Ok(())
+}
+
+#[test]
+fn write_with_custom_policy() -> crate::Result {
+    let dir = gix_testtools::tempfile::tempdir()?;
+    let other_dir = gix_testtools::tempfile::tempdir()?;
+    let mut handle = gix_odb::at(dir.path())?;
+    let other = std::sync::Arc::new(gix_odb::loose::Store::at(other_dir.path(), gix_hash::Kind::Sha1));
+    handle.write_policy = store::WritePolicy::Custom(other.clone());
+
+    let written_id = handle.write_buf(gix_object::Kind::Blob, b"hello world")?;
+    assert_eq!(written_id, hex_to_id("95d09f2b10159347eece71399a7e2e907ea3df4f"));
+    assert!(
+        other.contains(&written_id),
+        "the object was written into the custom destination…"
+    );
+    assert!(!handle.exists(&written_id), "…and not into the store itself");
+
+    let clone = handle.clone();
+    assert!(
+        matches!(clone.write_policy, store::WritePolicy::Custom(_)),
+        "the policy is inherited by clones"
+    );
+    Ok(())
+}
+
 #[test]
 fn alternate_dbs_query() -> crate::Result {
     let dir = gix_testtools::scripted_fixture_read_only_standalone("make_alternates_odb.sh")?;
@@ -74,6 +74,32 @@ mod write {
         Ok(())
     }
 
+    #[test]
+    fn objects_can_be_synced_with_all_methods() -> crate::Result {
+        let mut buf = Vec::new();
+        for method in [
+            gix_fs::fsync::Method::Fsync,
+            gix_fs::fsync::Method::WriteoutOnly,
+            gix_fs::fsync::Method::Batch,
+        ] {
+            let dir = gix_testtools::tempfile::tempdir()?;
+            let db = loose::Store::at(dir.path(), gix_hash::Kind::Sha1).with_fsync(Some(method));
+            assert_eq!(db.fsync(), Some(method));
+            for oid in object_ids() {
+                let obj = locate_oid(oid, &mut buf);
+                assert_eq!(db.write_buf(obj.kind, obj.data)?, oid);
+                assert!(db.contains(&oid));
+            }
+            db.flush_batch()?;
+            assert_eq!(
+                std::fs::read_dir(dir.path())?.count(),
+                object_ids().len(),
+                "only the fan-out directories remain, one per object in this case"
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     #[cfg(unix)]
     fn it_writes_objects_with_similar_permissions() -> crate::Result {
@@ -350,6 +376,27 @@ cjHJZXWmV4CcRfmLsXzU8s2cR9A0DBvOxhPD1TlKC2JhBFXigjuL9U4Rbq9tdegB
         Ok(())
     }
 
+    #[test]
+    fn objects_larger_than_the_configured_maximum_are_not_read() {
+        let mut buf = Vec::new();
+        let id = hex_to_id("37d4e6c5c48ba0d245164c4e10d5f41140cab980");
+        let db = ldb().with_max_object_size(Some(8));
+        assert!(matches!(
+            db.try_find(&id, &mut buf),
+            Err(loose::find::Error::ObjectTooLarge { size: 9, max: 8, .. })
+        ));
+        let db = db.with_max_object_size(Some(9));
+        assert_eq!(
+            db.try_find(&id, &mut buf)
+                .expect("no error")
+                .expect("present")
+                .data
+                .len(),
+            9,
+            "objects of the maximum size are fine"
+        );
+    }
+
     fn try_locate<'a>(hex: &str, buf: &'a mut Vec<u8>) -> Option<gix_object::Data<'a>> {
         ldb().try_find(&hex_to_id(hex), buf).ok().flatten()
     }
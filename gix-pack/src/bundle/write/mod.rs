@@ -16,7 +16,7 @@ pub use error::Error;
 use gix_features::progress::prodash::DynNestedProgress;
 
 mod types;
-use types::{LockWriter, PassThrough};
+use types::{LimitedRead, LockWriter, PassThrough};
 pub use types::{Options, Outcome};
 
 use crate::bundle::write::types::SharedTempFile;
@@ -70,9 +70,13 @@ impl crate::Bundle {
         let _span = gix_features::trace::coarse!("gix_pack::Bundle::write_to_directory()");
         let mut read_progress = progress.add_child_with_id("read pack".into(), ProgressId::ReadPackBytes.into());
         read_progress.init(None, progress::bytes());
-        let pack = progress::Read {
-            inner: pack,
-            progress: progress::ThroughputOnDrop::new(read_progress),
+        let pack = LimitedRead {
+            inner: progress::Read {
+                inner: pack,
+                progress: progress::ThroughputOnDrop::new(read_progress),
+            },
+            limit: options.max_pack_size,
+            bytes_read: 0,
         };
 
         let object_hash = options.object_hash;
@@ -180,9 +184,13 @@ impl crate::Bundle {
         let _span = gix_features::trace::coarse!("gix_pack::Bundle::write_to_directory_eagerly()");
         let mut read_progress = progress.add_child_with_id("read pack".into(), ProgressId::ReadPackBytes.into()); /* Bundle Write Read pack Bytes*/
         read_progress.init(pack_size.map(|s| s as usize), progress::bytes());
-        let pack = progress::Read {
-            inner: pack,
-            progress: progress::ThroughputOnDrop::new(read_progress),
+        let pack = LimitedRead {
+            inner: progress::Read {
+                inner: pack,
+                progress: progress::ThroughputOnDrop::new(read_progress),
+            },
+            limit: options.max_pack_size,
+            bytes_read: 0,
         };
 
         let data_file = Arc::new(parking_lot::Mutex::new(io::BufWriter::new(match directory.as_ref() {
@@ -266,9 +274,13 @@ impl crate::Bundle {
         progress: &mut dyn DynNestedProgress,
         Options {
             thread_limit,
+            memory_limit,
             iteration_mode: _,
             index_version: index_kind,
             object_hash,
+            max_pack_size: _,
+            fsync_pack,
+            fsync_pack_metadata,
         }: Options,
         data_file: SharedTempFile,
         mut pack_entries_iter: Box<dyn Iterator<Item = Result<data::input::Entry, data::input::Error>> + 'a>,
@@ -292,6 +304,7 @@ impl crate::Bundle {
                     },
                     &mut pack_entries_iter,
                     thread_limit,
+                    memory_limit,
                     &mut indexing_progress,
                     &mut index_file,
                     should_interrupt,
@@ -317,16 +330,25 @@ impl crate::Bundle {
                     } else {
                         let keep_path = data_path.with_extension("keep");
 
-                        std::fs::write(&keep_path, b"")?;
-                        Arc::try_unwrap(data_file)
+                        let keep_file = std::fs::File::create(&keep_path)?;
+                        if let Some(method) = fsync_pack_metadata {
+                            gix_fs::fsync::file(&keep_file, method)?;
+                        }
+                        let mut data_file = Arc::try_unwrap(data_file)
                             .expect("only one handle left after pack was consumed")
                             .into_inner()
                             .into_inner()
-                            .map_err(|err| Error::from(err.into_error()))?
-                            .persist(&data_path)?;
+                            .map_err(|err| Error::from(err.into_error()))?;
+                        if let Some(method) = fsync_pack {
+                            data_file.with_mut(|file| gix_fs::fsync::file(file.as_file(), method))??;
+                        }
+                        data_file.persist(&data_path)?;
                         Some(keep_path)
                     };
                     if !index_path.is_file() {
+                        if let Some(method) = fsync_pack_metadata {
+                            index_file.with_mut(|file| gix_fs::fsync::file(file.as_file(), method))??;
+                        }
                         index_file
                             .persist(&index_path)
                             .map_err(|err| {
@@ -348,6 +370,7 @@ impl crate::Bundle {
                     move || new_pack_file_resolver(data_file),
                     &mut pack_entries_iter,
                     thread_limit,
+                    memory_limit,
                     &mut indexing_progress,
                     &mut io::sink(),
                     should_interrupt,
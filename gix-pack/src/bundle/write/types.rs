@@ -8,12 +8,26 @@ use gix_tempfile::handle::Writable;
 pub struct Options {
     /// The amount of threads to use at most when resolving the pack. If `None`, all logical cores are used.
     pub thread_limit: Option<usize>,
+    /// The amount of bytes that decompressed objects may use while waiting for their deltas to be resolved, to protect
+    /// against packs crafted to use excessive amounts of memory. If `None`, there is no limit.
+    ///
+    /// Once the limit is reached, resolution continues more slowly, but with much less memory.
+    pub memory_limit: Option<usize>,
     /// Determine how much processing to spend on protecting against corruption or recovering from errors.
     pub iteration_mode: crate::data::input::Mode,
     /// The version of pack index to write, should be [`crate::index::Version::default()`]
     pub index_version: crate::index::Version,
     /// The kind of hash to use when writing the bundle.
     pub object_hash: gix_hash::Kind,
+    /// If set, fail with an IO error of kind [`Other`](io::ErrorKind::Other) once more than this amount of bytes were read
+    /// from the pack, to protect against packs that are larger than permitted.
+    pub max_pack_size: Option<u64>,
+    /// How to sync the pack data file before moving it into place, if at all, typically obtained from the `pack` component
+    /// of `core.fsync` and from `core.fsyncMethod`.
+    pub fsync_pack: Option<gix_fs::fsync::Method>,
+    /// How to sync the pack index and the `.keep` file before moving them into place, if at all, typically obtained from
+    /// the `pack-metadata` component of `core.fsync` and from `core.fsyncMethod`.
+    pub fsync_pack_metadata: Option<gix_fs::fsync::Method>,
 }
 
 impl Default for Options {
@@ -21,9 +35,13 @@ impl Default for Options {
     fn default() -> Self {
         Options {
             thread_limit: None,
+            memory_limit: None,
             iteration_mode: crate::data::input::Mode::Verify,
             index_version: Default::default(),
             object_hash: Default::default(),
+            max_pack_size: None,
+            fsync_pack: None,
+            fsync_pack_metadata: None,
         }
     }
 }
@@ -95,6 +113,30 @@ where
     }
 }
 
+/// A reader that fails once more than `limit` bytes were read from `inner`.
+pub(crate) struct LimitedRead<R> {
+    pub inner: R,
+    pub limit: Option<u64>,
+    pub bytes_read: u64,
+}
+
+impl<R> io::Read for LimitedRead<R>
+where
+    R: io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        self.bytes_read += bytes_read as u64;
+        match self.limit {
+            Some(limit) if self.bytes_read > limit => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("The pack exceeded the maximum size of {limit} bytes"),
+            )),
+            _ => Ok(bytes_read),
+        }
+    }
+}
+
 pub(crate) struct LockWriter {
     pub writer: SharedTempFile,
 }
@@ -70,6 +70,13 @@ pub struct Options<'a, 's> {
     /// specifies what kind of hashes we expect to be stored in oid-delta entries, which is viable to decoding them
     /// with the correct size.
     pub object_hash: gix_hash::Kind,
+    /// If `Some`, the amount of bytes that decompressed objects may use across all threads while they are held in memory
+    /// until all deltas based on them are resolved.
+    /// Once it would be exceeded, the deltas of an object are resolved depth-first right away, which needs much less memory
+    /// on packs with many wide delta trees, but can't be parallelized.
+    ///
+    /// If `None`, there is no limit.
+    pub memory_limit: Option<usize>,
 }
 
 /// The outcome of [`Tree::traverse()`]
@@ -78,6 +85,11 @@ pub struct Outcome<T> {
     pub roots: Vec<Item<T>>,
     /// The items that children to a root object, i.e. delta objects.
     pub children: Vec<Item<T>>,
+    /// The highest amount of bytes used by decompressed objects held in memory until their deltas were resolved.
+    pub peak_memory: usize,
+    /// The amount of objects whose deltas were resolved depth-first as holding them would have exceeded
+    /// [the memory limit](Options::memory_limit).
+    pub bases_resolved_depth_first: usize,
 }
 
 impl<T> Tree<T>
@@ -113,6 +125,7 @@ where
             size_progress,
             should_interrupt,
             object_hash,
+            memory_limit,
         }: Options<'_, '_>,
     ) -> Result<Outcome<T>, Error>
     where
@@ -133,6 +146,8 @@ where
         let size_counter = size_progress.counter();
         let object_progress = OwnShared::new(Mutable::new(object_progress));
 
+        let memory = resolve::Memory::new(memory_limit);
+        let memory = &memory;
         let start = std::time::Instant::now();
         let (mut root_items, mut child_items_vec) = self.take_root_and_child();
         let child_items = ItemSliceSync::new(&mut child_items_vec);
@@ -152,6 +167,7 @@ where
                         resolve: resolve.clone(),
                         modify_base: inspect_object.clone(),
                         child_items,
+                        memory,
                     }
                 }
             },
@@ -185,6 +201,8 @@ where
         Ok(Outcome {
             roots: root_items,
             children: child_items_vec,
+            peak_memory: memory.peak.load(Ordering::Relaxed),
+            bases_resolved_depth_first: memory.bases_resolved_depth_first.load(Ordering::Relaxed),
         })
     }
 }
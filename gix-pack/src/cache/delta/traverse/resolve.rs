@@ -1,6 +1,6 @@
 use std::{
     collections::BTreeMap,
-    sync::atomic::{AtomicBool, AtomicIsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering},
 };
 
 use gix_features::{progress::Progress, threading, zlib};
@@ -81,6 +81,48 @@ mod root {
     }
 }
 
+/// Keeps track of the memory used by decompressed objects that are held until the deltas based on them are resolved,
+/// across all threads.
+pub(super) struct Memory {
+    limit: Option<usize>,
+    held: AtomicUsize,
+    pub peak: AtomicUsize,
+    pub bases_resolved_depth_first: AtomicUsize,
+}
+
+impl Memory {
+    pub fn new(limit: Option<usize>) -> Self {
+        Memory {
+            limit,
+            held: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            bases_resolved_depth_first: AtomicUsize::new(0),
+        }
+    }
+
+    /// Return `true` if a base of `size` bytes can be held without exceeding the limit, and account for it if so.
+    fn try_hold(&self, size: usize) -> bool {
+        let limit = self.limit;
+        match self.held.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |held| {
+            let held = held.saturating_add(size);
+            limit.map_or(true, |limit| held <= limit).then_some(held)
+        }) {
+            Ok(previous) => {
+                self.peak.fetch_max(previous + size, Ordering::Relaxed);
+                true
+            }
+            Err(_) => {
+                self.bases_resolved_depth_first.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    fn release(&self, size: usize) {
+        self.held.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
 pub(super) struct State<'items, F, MBFN, T: Send> {
     pub delta_bytes: Vec<u8>,
     pub fully_resolved_delta_bytes: Vec<u8>,
@@ -88,6 +130,7 @@ pub(super) struct State<'items, F, MBFN, T: Send> {
     pub resolve: F,
     pub modify_base: MBFN,
     pub child_items: &'items ItemSliceSync<'items, Item<T>>,
+    pub memory: &'items Memory,
 }
 
 /// SAFETY: `item.children` must uniquely reference elements in child_items that no other currently alive
@@ -109,6 +152,7 @@ pub(super) unsafe fn deltas<T, F, MBFN, E, R>(
         resolve,
         modify_base,
         child_items,
+        memory,
     }: &mut State<'_, F, MBFN, T>,
     resolve_data: &R,
     hash_len: usize,
@@ -122,7 +166,7 @@ where
     MBFN: FnMut(&mut T, &dyn Progress, Context<'_>) -> Result<(), E> + Send + Clone,
     E: std::error::Error + Send + Sync + 'static,
 {
-    let mut decompressed_bytes_by_pack_offset = BTreeMap::new();
+    let mut decompressed_bytes_by_pack_offset = BTreeMap::<u64, (data::Entry, u64, Vec<u8>)>::new();
     let mut inflate = zlib::Inflate::default();
     let mut decompress_from_resolver = |slice: EntryRange, out: &mut Vec<u8>| -> Result<(data::Entry, u64), Error> {
         let bytes = resolve(slice.clone(), resolve_data).ok_or(Error::ResolveFailed {
@@ -151,9 +195,11 @@ where
             let (a, b) = decompress_from_resolver(base.entry_slice(), &mut buf)?;
             (a, b, buf)
         } else {
-            decompressed_bytes_by_pack_offset
+            let resolved = decompressed_bytes_by_pack_offset
                 .remove(&base.offset())
-                .expect("we store the resolved delta buffer when done")
+                .expect("we store the resolved delta buffer when done");
+            memory.release(resolved.2.len());
+            resolved
         };
 
         // anything done here must be repeated further down for leaf-nodes.
@@ -177,28 +223,30 @@ where
 
         for mut child in base.into_child_iter() {
             let (mut child_entry, entry_end) = decompress_from_resolver(child.entry_slice(), delta_bytes)?;
-            let (base_size, consumed) = data::delta::decode_header_size(delta_bytes);
-            let mut header_ofs = consumed;
-            assert_eq!(
-                base_bytes.len(),
-                base_size as usize,
-                "recorded base size in delta does match the actual one"
-            );
-            let (result_size, consumed) = data::delta::decode_header_size(&delta_bytes[consumed..]);
-            header_ofs += consumed;
-
-            fully_resolved_delta_bytes.resize(result_size as usize, 0);
-            data::delta::apply(&base_bytes, fully_resolved_delta_bytes, &delta_bytes[header_ofs..]);
+            apply_delta(&base_bytes, delta_bytes, fully_resolved_delta_bytes);
 
             // FIXME: this actually invalidates the "pack_offset()" computation, which is not obvious to consumers
             //        at all
             child_entry.header = base_entry.header; // assign the actual object type, instead of 'delta'
             if child.has_children() {
-                decompressed_bytes_by_pack_offset.insert(
-                    child.offset(),
-                    (child_entry, entry_end, std::mem::take(fully_resolved_delta_bytes)),
-                );
-                nodes.push((level + 1, child));
+                let child_bytes = std::mem::take(fully_resolved_delta_bytes);
+                if memory.try_hold(child_bytes.len()) {
+                    decompressed_bytes_by_pack_offset.insert(child.offset(), (child_entry, entry_end, child_bytes));
+                    nodes.push((level + 1, child));
+                } else {
+                    deltas_depth_first(
+                        level + 1,
+                        child,
+                        (child_entry, entry_end, child_bytes),
+                        &mut decompress_from_resolver,
+                        delta_bytes,
+                        fully_resolved_delta_bytes,
+                        modify_base,
+                        &**progress,
+                        (&objects, &size),
+                        should_interrupt,
+                    )?;
+                }
             } else {
                 modify_base(
                     child.data(),
@@ -240,6 +288,7 @@ where
                     resolve_data,
                     modify_base.clone(),
                     hash_len,
+                    memory,
                     threads_left,
                     should_interrupt,
                 );
@@ -265,6 +314,7 @@ fn deltas_mt<T, F, MBFN, E, R>(
     resolve_data: &R,
     modify_base: MBFN,
     hash_len: usize,
+    memory: &Memory,
     threads_left: &AtomicIsize,
     should_interrupt: &AtomicBool,
 ) -> Result<(), Error>
@@ -324,9 +374,11 @@ where
                                     let (a, b) = decompress_from_resolver(base.entry_slice(), &mut buf)?;
                                     (a, b, buf)
                                 } else {
-                                    threading::lock(decompressed_bytes_by_pack_offset)
+                                    let resolved = threading::lock(decompressed_bytes_by_pack_offset)
                                         .remove(&base.offset())
-                                        .expect("we store the resolved delta buffer when done")
+                                        .expect("we store the resolved delta buffer when done");
+                                    memory.release(resolved.2.len());
+                                    resolved
                                 };
 
                                 // anything done here must be repeated further down for leaf-nodes.
@@ -351,33 +403,31 @@ where
                                 for mut child in base.into_child_iter() {
                                     let (mut child_entry, entry_end) =
                                         decompress_from_resolver(child.entry_slice(), &mut delta_bytes)?;
-                                    let (base_size, consumed) = data::delta::decode_header_size(&delta_bytes);
-                                    let mut header_ofs = consumed;
-                                    assert_eq!(
-                                        base_bytes.len(),
-                                        base_size as usize,
-                                        "recorded base size in delta does match the actual one"
-                                    );
-                                    let (result_size, consumed) =
-                                        data::delta::decode_header_size(&delta_bytes[consumed..]);
-                                    header_ofs += consumed;
-
-                                    fully_resolved_delta_bytes.resize(result_size as usize, 0);
-                                    data::delta::apply(
-                                        &base_bytes,
-                                        &mut fully_resolved_delta_bytes,
-                                        &delta_bytes[header_ofs..],
-                                    );
+                                    apply_delta(&base_bytes, &delta_bytes, &mut fully_resolved_delta_bytes);
 
                                     // FIXME: this actually invalidates the "pack_offset()" computation, which is not obvious to consumers
                                     //        at all
                                     child_entry.header = base_entry.header; // assign the actual object type, instead of 'delta'
                                     if child.has_children() {
-                                        threading::lock(decompressed_bytes_by_pack_offset).insert(
-                                            child.offset(),
-                                            (child_entry, entry_end, std::mem::take(&mut fully_resolved_delta_bytes)),
-                                        );
-                                        threading::lock(nodes).push((level + 1, child));
+                                        let child_bytes = std::mem::take(&mut fully_resolved_delta_bytes);
+                                        if memory.try_hold(child_bytes.len()) {
+                                            threading::lock(decompressed_bytes_by_pack_offset)
+                                                .insert(child.offset(), (child_entry, entry_end, child_bytes));
+                                            threading::lock(nodes).push((level + 1, child));
+                                        } else {
+                                            deltas_depth_first(
+                                                level + 1,
+                                                child,
+                                                (child_entry, entry_end, child_bytes),
+                                                &mut decompress_from_resolver,
+                                                &mut delta_bytes,
+                                                &mut fully_resolved_delta_bytes,
+                                                &mut modify_base,
+                                                progress,
+                                                (objects, size),
+                                                should_interrupt,
+                                            )?;
+                                        }
                                     } else {
                                         modify_base(
                                             child.data(),
@@ -452,6 +502,93 @@ where
     })
 }
 
+/// Decompress the pack entry in the given range into the given buffer, and return its header along with the offset at which it ends.
+type DecompressFn<'a> = dyn FnMut(EntryRange, &mut Vec<u8>) -> Result<(data::Entry, u64), Error> + 'a;
+
+/// Resolve all deltas below `node` and pass them to `modify_base`, along with `node` itself whose decompressed object is `resolved`.
+///
+/// This is done depth-first so only the objects along the current delta chain are held in memory, instead of all
+/// objects whose deltas are yet to be resolved. It's slower as it can't be parallelized, and is used only once
+/// the memory limit is reached.
+#[allow(clippy::too_many_arguments)]
+fn deltas_depth_first<T, MBFN, E>(
+    level: u16,
+    mut node: root::Node<'_, T>,
+    (entry, entry_end, bytes): (data::Entry, u64, Vec<u8>),
+    decompress_from_resolver: &mut DecompressFn<'_>,
+    delta_bytes: &mut Vec<u8>,
+    fully_resolved_delta_bytes: &mut Vec<u8>,
+    modify_base: &mut MBFN,
+    progress: &dyn Progress,
+    (objects, size): (&gix_features::progress::StepShared, &gix_features::progress::StepShared),
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error>
+where
+    T: Send,
+    MBFN: FnMut(&mut T, &dyn Progress, Context<'_>) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut inspect = |data: &mut T, entry: &data::Entry, entry_end: u64, decompressed: &[u8], level: u16| {
+        modify_base(
+            data,
+            progress,
+            Context {
+                entry,
+                entry_end,
+                decompressed,
+                level,
+            },
+        )
+        .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)?;
+        objects.fetch_add(1, Ordering::Relaxed);
+        size.fetch_add(decompressed.len(), Ordering::Relaxed);
+        Ok::<_, Error>(())
+    };
+
+    inspect(node.data(), &entry, entry_end, &bytes, level)?;
+    let mut chain = vec![(level, entry, bytes, node.into_child_iter())];
+    while let Some((level, base_entry, base_bytes, children)) = chain.last_mut() {
+        let Some(mut child) = children.next() else {
+            chain.pop();
+            continue;
+        };
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        let level = *level + 1;
+        let (mut child_entry, entry_end) = decompress_from_resolver(child.entry_slice(), delta_bytes)?;
+        apply_delta(base_bytes, delta_bytes, fully_resolved_delta_bytes);
+        child_entry.header = base_entry.header; // assign the actual object type, instead of 'delta'
+
+        inspect(child.data(), &child_entry, entry_end, fully_resolved_delta_bytes, level)?;
+        if child.has_children() {
+            chain.push((
+                level,
+                child_entry,
+                std::mem::take(fully_resolved_delta_bytes),
+                child.into_child_iter(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Apply the delta in `delta_bytes` to `base_bytes` and write the result into `out`.
+fn apply_delta(base_bytes: &[u8], delta_bytes: &[u8], out: &mut Vec<u8>) {
+    let (base_size, consumed) = data::delta::decode_header_size(delta_bytes);
+    let mut header_ofs = consumed;
+    assert_eq!(
+        base_bytes.len(),
+        base_size as usize,
+        "recorded base size in delta does match the actual one"
+    );
+    let (result_size, consumed) = data::delta::decode_header_size(&delta_bytes[consumed..]);
+    header_ofs += consumed;
+
+    out.resize(result_size as usize, 0);
+    data::delta::apply(base_bytes, out, &delta_bytes[header_ofs..]);
+}
+
 fn decompress_all_at_once_with(
     inflate: &mut zlib::Inflate,
     b: &[u8],
@@ -169,6 +169,7 @@ impl File {
         use crate::data::entry::Header::*;
         match entry.header {
             Tree | Blob | Commit | Tag => {
+                self.limits.check_object_size(entry.decompressed_size)?;
                 let size: usize = entry.decompressed_size.try_into().map_err(|_| Error::OutOfMemory)?;
                 if let Some(additional) = size.checked_sub(out.len()) {
                     out.try_reserve(additional)?;
@@ -219,6 +220,12 @@ impl File {
                 }
                 break;
             }
+            if let Some(max) = self.limits.max_delta_chain_length {
+                if chain.len() >= max {
+                    return Err(Error::DeltaChainTooLong { max });
+                }
+            }
+            self.limits.check_object_size(cursor.decompressed_size)?;
             // This is a pessimistic guess, as worst possible compression should not be bigger than the data itself.
             // TODO: is this assumption actually true?
             total_delta_data_size += cursor.decompressed_size;
@@ -310,6 +317,8 @@ impl File {
                 instructions = &mut instructions[delta.decompressed_size..];
             }
 
+            self.limits.check_object_size(biggest_result_size)?;
+
             // Now we can produce a buffer like this
             // [<biggest-result-buffer, possibly filled with resolved base object data>]<biggest-result-buffer><delta-1..delta-n>
             // from [<possibly resolved base object>]<delta-1..delta-n>...
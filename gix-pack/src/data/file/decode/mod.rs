@@ -19,6 +19,32 @@ pub enum Error {
     EntryType(#[from] crate::data::entry::decode::Error),
     #[error("Entry too large to fit in memory")]
     OutOfMemory,
+    #[error("The delta chain is longer than the configured maximum of {max} deltas")]
+    DeltaChainTooLong { max: usize },
+    #[error("An object or delta of {size} bytes exceeds the configured maximum of {max} bytes")]
+    ObjectTooLarge { size: u64, max: u64 },
+}
+
+/// Limits to bound the resources used when decoding entries of a pack that may come from an untrusted source,
+/// as set with [`File::set_limits()`][crate::data::File::set_limits()].
+///
+/// By default, there are no limits.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The maximum amount of deltas to resolve to decode a single object, or `None` for no limit.
+    pub max_delta_chain_length: Option<usize>,
+    /// The maximum size in bytes of each decompressed object, base object or delta to keep in memory while decoding,
+    /// or `None` for no limit.
+    pub max_object_size: Option<u64>,
+}
+
+impl Limits {
+    pub(crate) fn check_object_size(&self, size: u64) -> Result<(), Error> {
+        match self.max_object_size {
+            Some(max) if size > max => Err(Error::ObjectTooLarge { size, max }),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl From<TryReserveError> for Error {
@@ -36,6 +36,7 @@ impl data::File {
             num_objects,
             hash_len,
             object_hash,
+            limits: Default::default(),
         })
     }
 }
@@ -77,6 +77,7 @@ pub struct File {
     /// based on their configuration.
     hash_len: usize,
     object_hash: gix_hash::Kind,
+    limits: decode::Limits,
 }
 
 /// Information about the pack data file itself
@@ -97,6 +98,14 @@ impl File {
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.object_hash
     }
+    /// The limits enforced when decoding entries.
+    pub fn limits(&self) -> decode::Limits {
+        self.limits
+    }
+    /// Enforce `limits` when [decoding entries](File::decode_entry()) to bound the resources needed for malicious packs.
+    pub fn set_limits(&mut self, limits: decode::Limits) {
+        self.limits = limits;
+    }
     /// The position of the byte one past the last pack entry, or in other terms, the first byte of the trailing hash.
     pub fn pack_end(&self) -> usize {
         self.data.len() - self.hash_len
@@ -164,6 +164,7 @@ impl index::File {
                         thread_limit,
                         should_interrupt,
                         object_hash: self.object_hash,
+                        memory_limit: None,
                     },
                 )?);
                 outcome.pack_size = pack.data_len() as u64;
@@ -199,7 +200,7 @@ impl From<crate::index::Entry> for Entry {
     }
 }
 
-fn digest_statistics(traverse::Outcome { roots, children }: traverse::Outcome<Entry>) -> index::traverse::Statistics {
+fn digest_statistics(traverse::Outcome { roots, children, .. }: traverse::Outcome<Entry>) -> index::traverse::Statistics {
     let mut res = index::traverse::Statistics::default();
     let average = &mut res.average;
     for item in roots.iter().chain(children.iter()) {
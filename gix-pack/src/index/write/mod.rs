@@ -68,11 +68,13 @@ impl crate::index::File {
     ///
     /// * `kind` is the version of pack index to produce, use [`crate::index::Version::default()`] if in doubt.
     /// * `tread_limit` is used for a parallel tree traversal for obtaining object hashes with optimal performance.
+    /// * `memory_limit` is the amount of bytes that objects may use while waiting for their deltas to be resolved,
+    ///   see [`Options::memory_limit`](crate::cache::delta::traverse::Options::memory_limit) for details.
     /// * `root_progress` is the top-level progress to stay informed about the progress of this potentially long-running
-    ///    computation.
+    ///   computation.
     /// * `object_hash` defines what kind of object hash we write into the index file.
     /// * `pack_version` is the version of the underlying pack for which `entries` are read. It's used in case none of these objects are provided
-    ///    to compute a pack-hash.
+    ///   to compute a pack-hash.
     ///
     /// # Remarks
     ///
@@ -87,6 +89,7 @@ impl crate::index::File {
         make_resolver: F,
         entries: &mut dyn Iterator<Item = Result<crate::data::input::Entry, crate::data::input::Error>>,
         thread_limit: Option<usize>,
+        memory_limit: Option<usize>,
         root_progress: &mut dyn DynNestedProgress,
         out: &mut dyn io::Write,
         should_interrupt: &AtomicBool,
@@ -182,7 +185,12 @@ impl crate::index::File {
 
         let (resolver, pack) = make_resolver()?;
         let sorted_pack_offsets_by_oid = {
-            let traverse::Outcome { roots, children } = tree.traverse(
+            let traverse::Outcome {
+                roots,
+                children,
+                peak_memory,
+                bases_resolved_depth_first,
+            } = tree.traverse(
                 resolver,
                 &pack,
                 pack_entries_end,
@@ -205,8 +213,15 @@ impl crate::index::File {
                     thread_limit,
                     should_interrupt,
                     object_hash,
+                    memory_limit,
                 },
             )?;
+            if memory_limit.is_some() {
+                root_progress.info(format!(
+                    "Resolved deltas with up to {peak_memory} bytes of base objects in memory, and {bases_resolved_depth_first} \
+                     objects resolved depth-first to stay within the limit"
+                ));
+            }
             root_progress.inc();
 
             let mut items = roots;
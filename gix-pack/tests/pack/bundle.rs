@@ -117,7 +117,7 @@ mod write_to_directory {
 
     #[test]
     fn without_providing_one() -> Result<(), Box<dyn std::error::Error>> {
-        let res = write_pack(None::<&Path>, SMALL_PACK)?;
+        let res = write_pack(None::<&Path>, SMALL_PACK, None, None)?;
         assert_eq!(res, expected_outcome()?);
         assert_eq!(
             res.index.index_hash,
@@ -130,7 +130,7 @@ mod write_to_directory {
     #[test]
     fn given_a_directory() -> Result<(), Box<dyn std::error::Error>> {
         let dir = TempDir::new()?;
-        let mut res = write_pack(Some(&dir), SMALL_PACK)?;
+        let mut res = write_pack(Some(&dir), SMALL_PACK, None, Some(gix_fs::fsync::Method::Fsync))?;
         let (index_path, data_path, keep_path) = (res.index_path.take(), res.data_path.take(), res.keep_path.take());
         assert_eq!(res, expected_outcome()?);
         let mut sorted_entries = fs::read_dir(&dir)?.filter_map(Result::ok).collect::<Vec<_>>();
@@ -154,6 +154,26 @@ mod write_to_directory {
         Ok(())
     }
 
+    #[test]
+    fn packs_larger_than_the_maximum_size_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+        let pack_size = fs::metadata(fixture_path(SMALL_PACK))?.len();
+        let res = write_pack(None::<&Path>, SMALL_PACK, Some(pack_size), None)?;
+        assert_eq!(res, expected_outcome()?, "packs of exactly the maximum size are fine");
+
+        let dir = TempDir::new()?;
+        let err = write_pack(Some(&dir), SMALL_PACK, Some(pack_size - 1), None).unwrap_err();
+        let source = std::iter::successors(Some(err.as_ref() as &dyn std::error::Error), |err| err.source())
+            .last()
+            .expect("at least one error");
+        assert_eq!(
+            source.to_string(),
+            format!("The pack exceeded the maximum size of {} bytes", pack_size - 1),
+            "the pack is rejected once it exceeds the limit"
+        );
+        assert_eq!(fs::read_dir(&dir)?.count(), 0, "no file is left behind");
+        Ok(())
+    }
+
     fn file_name(entry: &fs::DirEntry) -> String {
         entry.path().file_name().unwrap().to_str().unwrap().to_owned()
     }
@@ -161,6 +181,8 @@ mod write_to_directory {
     fn write_pack(
         directory: Option<impl AsRef<Path>>,
         pack_file: &str,
+        max_pack_size: Option<u64>,
+        fsync: Option<gix_fs::fsync::Method>,
     ) -> Result<pack::bundle::write::Outcome, Box<dyn std::error::Error>> {
         let pack_file = fs::File::open(fixture_path(pack_file))?;
         static SHOULD_INTERRUPT: AtomicBool = AtomicBool::new(false);
@@ -173,9 +195,13 @@ mod write_to_directory {
             None::<gix_object::find::Never>,
             pack::bundle::write::Options {
                 thread_limit: None,
+                memory_limit: None,
                 iteration_mode: pack::data::input::Mode::Verify,
                 index_version: pack::index::Version::V2,
                 object_hash: gix_hash::Kind::Sha1,
+                max_pack_size,
+                fsync_pack: fsync,
+                fsync_pack_metadata: fsync,
             },
         )
         .map_err(Into::into)
@@ -44,7 +44,10 @@ mod method {
 /// All hardcoded offsets are obtained via `git pack-verify --verbose  tests/fixtures/packs/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.idx`
 mod decode_entry {
     use bstr::ByteSlice;
-    use gix_pack::{cache, data::decode::entry::ResolvedBase};
+    use gix_pack::{
+        cache,
+        data::decode::{entry::ResolvedBase, Error, Limits},
+    };
 
     use crate::{
         fixture_path, fixup,
@@ -96,13 +99,76 @@ mod decode_entry {
         );
     }
 
+    #[test]
+    fn limits() {
+        let no_limits = Limits::default();
+        assert!(decode_entry_at_offset_with_limits(3033, no_limits).is_ok());
+
+        let err = decode_entry_at_offset_with_limits(
+            3033,
+            Limits {
+                max_delta_chain_length: Some(1),
+                ..no_limits
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::DeltaChainTooLong { max: 1 }));
+        assert!(
+            decode_entry_at_offset_with_limits(
+                3569,
+                Limits {
+                    max_delta_chain_length: Some(1),
+                    ..no_limits
+                },
+            )
+            .is_ok(),
+            "a single delta is within the limit"
+        );
+
+        let err = decode_entry_at_offset_with_limits(
+            1968,
+            Limits {
+                max_object_size: Some(186),
+                ..no_limits
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::ObjectTooLarge { size: 187, max: 186 }));
+        assert!(decode_entry_at_offset_with_limits(
+            1968,
+            Limits {
+                max_object_size: Some(187),
+                ..no_limits
+            }
+        )
+        .is_ok());
+
+        let err = decode_entry_at_offset_with_limits(
+            3569,
+            Limits {
+                max_object_size: Some(1162),
+                ..no_limits
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, Error::ObjectTooLarge { max: 1162, .. }),
+            "bases, deltas and results of deltified objects are all checked"
+        );
+    }
+
     fn decode_entry_at_offset(offset: u64) -> Vec<u8> {
+        decode_entry_at_offset_with_limits(offset, Limits::default()).expect("valid offset provides valid entry")
+    }
+
+    fn decode_entry_at_offset_with_limits(offset: u64, limits: Limits) -> Result<Vec<u8>, Error> {
         #[allow(clippy::ptr_arg)]
         fn resolve_with_panic(_oid: &gix_hash::oid, _out: &mut Vec<u8>) -> Option<ResolvedBase> {
             panic!("should not want to resolve an id here")
         }
 
-        let p = pack_at(SMALL_PACK);
+        let mut p = pack_at(SMALL_PACK);
+        p.set_limits(limits);
         let entry = p.entry(offset).expect("valid object type");
         let mut buf = Vec::new();
         p.decode_entry(
@@ -111,9 +177,8 @@ mod decode_entry {
             &mut Default::default(),
             &resolve_with_panic,
             &mut cache::Never,
-        )
-        .expect("valid offset provides valid entry");
-        buf
+        )?;
+        Ok(buf)
     }
 }
 
@@ -132,6 +132,7 @@ mod version {
                 compressed: &input::EntryDataMode,
                 index_path: &&str,
                 data_path: &&str,
+                memory_limit: Option<usize>,
             ) -> Result<(), Box<dyn std::error::Error>> {
                 let mut pack_iter = pack::data::input::BytesToEntriesIter::new_from_header(
                     io::BufReader::new(fs::File::open(fixture_path(data_path))?),
@@ -153,6 +154,7 @@ mod version {
                     },
                     &mut pack_iter,
                     None,
+                    memory_limit,
                     &mut progress::Discard,
                     &mut actual,
                     &AtomicBool::new(false),
@@ -217,7 +219,10 @@ mod version {
             for mode in &[input::Mode::AsIs, input::Mode::Verify, input::Mode::Restore] {
                 for compressed in &[input::EntryDataMode::Crc32, input::EntryDataMode::KeepAndCrc32] {
                     for (index_path, data_path) in V2_PACKS_AND_INDICES {
-                        assert_index_write(mode, compressed, index_path, data_path)?;
+                        // A limit of 0 forces all deltas to be resolved depth-first, which must yield the same result.
+                        for memory_limit in [None, Some(0)] {
+                            assert_index_write(mode, compressed, index_path, data_path, memory_limit)?;
+                        }
                     }
                 }
             }
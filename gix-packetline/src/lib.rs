@@ -1,6 +1,7 @@
 //! Read and write the git packet line wire format without copying it.
 //!
-//! For reading the packet line format use the [`StreamingPeekableIter`], and for writing the [`Writer`].
+//! For reading the packet line format use the [`StreamingPeekableIter`], and for writing the [`Writer`],
+//! or the `VectoredWriter` to write many lines at once.
 //! ## Feature Flags
 #![cfg_attr(
     all(doc, all(doc, feature = "document-features")),
@@ -40,6 +41,8 @@ mod write;
 pub use write::async_io::Writer;
 #[cfg(feature = "blocking-io")]
 pub use write::blocking_io::Writer;
+#[cfg(feature = "blocking-io")]
+pub use write::vectored::VectoredWriter;
 
 /// A borrowed packet line as it refers to a slice of data by reference.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
@@ -91,6 +94,9 @@ pub struct StreamingPeekableIter<T> {
     delimiters: &'static [PacketLineRef<'static>],
     is_done: bool,
     stopped_at: Option<PacketLineRef<'static>>,
+    max_lines: Option<usize>,
+    #[cfg_attr(all(not(feature = "async-io"), not(feature = "blocking-io")), allow(dead_code))]
+    num_lines_read: usize,
     #[cfg_attr(all(not(feature = "async-io"), not(feature = "blocking-io")), allow(dead_code))]
     trace: bool,
 }
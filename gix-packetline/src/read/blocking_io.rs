@@ -115,6 +115,9 @@ where
             self.peek_buf.clear();
             Some(Ok(Ok(crate::decode(&self.buf).expect("only valid data in peek buf"))))
         } else {
+            if let Err(err) = self.count_line() {
+                return Some(Err(err));
+            }
             if self.buf.len() != MAX_LINE_LEN {
                 self.buf.resize(MAX_LINE_LEN, 0);
             }
@@ -141,6 +144,9 @@ where
             return None;
         }
         if self.peek_buf.is_empty() {
+            if let Err(err) = self.count_line() {
+                return Some(Err(err));
+            }
             self.peek_buf.resize(MAX_LINE_LEN, 0);
             let (is_done, stopped_at, res) = Self::read_line_inner_exhaustive(
                 &mut self.read,
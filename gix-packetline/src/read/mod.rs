@@ -38,8 +38,24 @@ mod error {
     }
 
     impl std::error::Error for Error {}
+
+    /// The error returned once more than the [maximum amount of lines](super::StreamingPeekableIter::set_max_lines())
+    /// would be read, as wrapped into an `std::io::Error` in [`read_line(…)`][super::StreamingPeekableIter::read_line()].
+    #[derive(Debug)]
+    pub struct LineLimitExceeded {
+        /// The maximum amount of lines that could be read.
+        pub max_lines: usize,
+    }
+
+    impl Display for LineLimitExceeded {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Refusing to read more than {} packet lines", self.max_lines)
+        }
+    }
+
+    impl std::error::Error for LineLimitExceeded {}
 }
-pub use error::Error;
+pub use error::{Error, LineLimitExceeded};
 
 impl<T> StreamingPeekableIter<T> {
     /// Return a new instance from `read` which will stop decoding packet lines when receiving one of the given `delimiters`.
@@ -54,10 +70,40 @@ impl<T> StreamingPeekableIter<T> {
             fail_on_err_lines: false,
             is_done: false,
             stopped_at: None,
+            max_lines: None,
+            num_lines_read: 0,
             trace,
         }
     }
 
+    /// Fail with [`LineLimitExceeded`] once more than `max_lines` packet lines would be read from the underlying reader,
+    /// or read any amount of lines if `None`.
+    ///
+    /// Delimiters and peeked lines count as well, and the count is only reset when [replacing](StreamingPeekableIter::replace())
+    /// the reader. This is useful to bound the resources used when reading from untrusted remotes.
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>) {
+        self.max_lines = max_lines;
+    }
+
+    /// Account for reading another line from the underlying reader, or mark the iteration as done and fail
+    /// if that would exceed the maximum amount of lines.
+    #[cfg(any(feature = "blocking-io", feature = "async-io"))]
+    fn count_line(&mut self) -> std::io::Result<()> {
+        match self.max_lines {
+            Some(max_lines) if self.num_lines_read >= max_lines => {
+                self.is_done = true;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    LineLimitExceeded { max_lines },
+                ))
+            }
+            _ => {
+                self.num_lines_read += 1;
+                Ok(())
+            }
+        }
+    }
+
     /// Modify the peek buffer, overwriting the byte at `position` with the given byte to `replace_with` while truncating
     /// it to contain only bytes until the newly replaced `position`.
     ///
@@ -107,6 +153,7 @@ impl<T> StreamingPeekableIter<T> {
     pub fn replace(&mut self, read: T) -> T {
         let prev = std::mem::replace(&mut self.read, read);
         self.reset();
+        self.num_lines_read = 0;
         self.fail_on_err_lines = false;
         prev
     }
@@ -6,6 +6,9 @@ pub(crate) mod async_io;
 #[cfg(feature = "blocking-io")]
 pub(crate) mod blocking_io;
 
+#[cfg(feature = "blocking-io")]
+pub(crate) mod vectored;
+
 /// Common methods
 impl<T> Writer<T> {
     /// As [`enable_text_mode()`][Writer::enable_text_mode()], but suitable for chaining.
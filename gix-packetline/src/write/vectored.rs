@@ -0,0 +1,185 @@
+use std::io;
+
+use crate::{
+    encode::{u16_to_hex, Error},
+    Channel, DELIMITER_LINE, ERR_PREFIX, FLUSH_LINE, MAX_DATA_LEN, RESPONSE_END_LINE, U16_HEX_BYTES,
+};
+
+/// The maximum amount of slices to pass to a single `write_vectored()` call, which is also the amount of queued slices
+/// at which they are written automatically. It's the smallest `IOV_MAX` of all common platforms.
+const MAX_SLICES: usize = 1024;
+
+/// The side-band channel bytes, to be able to borrow them.
+const CHANNELS: &[u8] = &[Channel::Data as u8, Channel::Progress as u8, Channel::Error as u8];
+
+/// A part of a packet line, either owned if it's the length of the line, or borrowed.
+enum Part<'a> {
+    Header([u8; U16_HEX_BYTES]),
+    Borrowed(&'a [u8]),
+}
+
+impl Part<'_> {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Part::Header(buf) => buf,
+            Part::Borrowed(data) => data,
+        }
+    }
+}
+
+/// A writer of packet lines which, unlike the functions in [`encode`](crate::encode), doesn't write each packet line by itself,
+/// but queues them to write many of them at once with [`write_vectored()`](io::Write::write_vectored()).
+///
+/// The data of each line is borrowed instead of copied, which makes this useful to write a large amount of small lines,
+/// like references in an advertisement, or side-band frames.
+///
+/// Note that queued lines are only written once enough of them were queued, or when calling [`write_queued()`](Self::write_queued()),
+/// [`flush()`](Self::flush()) or [`into_inner()`](Self::into_inner()), and are lost when dropping the writer.
+pub struct VectoredWriter<'a, T> {
+    /// the `Write` implementation to which to write packet lines
+    inner: T,
+    parts: Vec<Part<'a>>,
+}
+
+impl<'a, T: io::Write> VectoredWriter<'a, T> {
+    /// Create a new instance which writes packet lines to `write`.
+    pub fn new(write: T) -> Self {
+        VectoredWriter {
+            inner: write,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Queue a `data` message, like [`data_to_write()`](crate::encode::data_to_write()).
+    pub fn data(&mut self, data: &'a [u8]) -> io::Result<()> {
+        self.queue_line(&[], data, &[])
+    }
+
+    /// Queue a `text` message, which is assured to end in a newline, like [`text_to_write()`](crate::encode::text_to_write()).
+    pub fn text(&mut self, text: &'a [u8]) -> io::Result<()> {
+        self.queue_line(&[], text, b"\n")
+    }
+
+    /// Queue `data` of `kind` using side-band encoding, like [`band_to_write()`](crate::encode::band_to_write()).
+    pub fn band(&mut self, kind: Channel, data: &'a [u8]) -> io::Result<()> {
+        let channel = kind as usize - 1;
+        self.queue_line(&CHANNELS[channel..][..1], data, &[])
+    }
+
+    /// Queue an error `message`, like [`error_to_write()`](crate::encode::error_to_write()).
+    pub fn error(&mut self, message: &'a [u8]) -> io::Result<()> {
+        self.queue_line(ERR_PREFIX, message, &[])
+    }
+
+    /// Queue a flush line, like [`flush_to_write()`](crate::encode::flush_to_write()).
+    pub fn flush_line(&mut self) -> io::Result<()> {
+        self.queue(Part::Borrowed(FLUSH_LINE))
+    }
+
+    /// Queue a delimiter line, like [`delim_to_write()`](crate::encode::delim_to_write()).
+    pub fn delim(&mut self) -> io::Result<()> {
+        self.queue(Part::Borrowed(DELIMITER_LINE))
+    }
+
+    /// Queue a response-end line, like [`response_end_to_write()`](crate::encode::response_end_to_write()).
+    pub fn response_end(&mut self) -> io::Result<()> {
+        self.queue(Part::Borrowed(RESPONSE_END_LINE))
+    }
+
+    /// Write all queued lines to the inner writer, without flushing it.
+    pub fn write_queued(&mut self) -> io::Result<()> {
+        let mut slices = Vec::with_capacity(self.parts.len().min(MAX_SLICES));
+        let (mut part, mut skip) = (0, 0);
+        while part < self.parts.len() {
+            slices.clear();
+            slices.extend(
+                self.parts[part..]
+                    .iter()
+                    .take(MAX_SLICES)
+                    .enumerate()
+                    .map(|(index, p)| io::IoSlice::new(&p.as_slice()[if index == 0 { skip } else { 0 }..])),
+            );
+            let mut written = match self.inner.write_vectored(&slices) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write all queued packet lines",
+                    ))
+                }
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            // Skip over what was written, which may end in the middle of a part.
+            while written != 0 {
+                let remaining = self.parts[part].as_slice().len() - skip;
+                if written >= remaining {
+                    written -= remaining;
+                    part += 1;
+                    skip = 0;
+                } else {
+                    skip += written;
+                    written = 0;
+                }
+            }
+        }
+        drop(slices);
+        self.parts.clear();
+        Ok(())
+    }
+
+    /// Write all queued lines and flush the inner writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_queued()?;
+        self.inner.flush()
+    }
+
+    /// Write all queued lines and return the inner writer, without flushing it.
+    pub fn into_inner(mut self) -> io::Result<T> {
+        self.write_queued()?;
+        Ok(self.inner)
+    }
+
+    fn queue_line(&mut self, prefix: &'static [u8], data: &'a [u8], suffix: &'static [u8]) -> io::Result<()> {
+        let data_len = prefix.len() + data.len() + suffix.len();
+        if data_len > MAX_DATA_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                Error::DataLengthLimitExceeded {
+                    length_in_bytes: data_len,
+                },
+            ));
+        }
+        if data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::Other, Error::DataIsEmpty));
+        }
+        self.parts
+            .push(Part::Header(u16_to_hex((data_len + U16_HEX_BYTES) as u16)));
+        for part in [prefix, data, suffix] {
+            if !part.is_empty() {
+                self.parts.push(Part::Borrowed(part));
+            }
+        }
+        self.write_if_full()
+    }
+
+    fn queue(&mut self, part: Part<'a>) -> io::Result<()> {
+        self.parts.push(part);
+        self.write_if_full()
+    }
+
+    fn write_if_full(&mut self) -> io::Result<()> {
+        if self.parts.len() >= MAX_SLICES {
+            self.write_queued()?;
+        }
+        Ok(())
+    }
+}
+
+/// Non-IO methods
+impl<T> VectoredWriter<'_, T> {
+    /// Return a mutable reference to the inner writer, which shouldn't be written to while lines are queued.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
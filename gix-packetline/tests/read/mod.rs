@@ -222,6 +222,43 @@ pub mod streaming_peek_iter {
         Ok(())
     }
 
+    #[maybe_async::test(feature = "blocking-io", async(feature = "async-io", async_std::test))]
+    async fn max_lines() -> crate::Result {
+        let input = b"0005a0005b0005c0000";
+        let mut rd = gix_packetline::StreamingPeekableIter::new(&input[..], &[PacketLineRef::Flush], false);
+        rd.set_max_lines(Some(2));
+        let res = rd.read_line().await;
+        assert_eq!(res.expect("line")??, PacketLineRef::Data(b"a"));
+        let res = rd.peek_line().await;
+        assert_eq!(res.expect("line")??, PacketLineRef::Data(b"b"));
+        let res = rd.read_line().await;
+        assert_eq!(
+            res.expect("line")??,
+            PacketLineRef::Data(b"b"),
+            "peeked lines are only counted once"
+        );
+        let err = rd.peek_line().await.expect("line").unwrap_err();
+        assert_eq!(err.to_string(), "Refusing to read more than 2 packet lines");
+        assert_eq!(
+            err.into_inner()
+                .expect("inner")
+                .downcast_ref::<gix_packetline::read::LineLimitExceeded>()
+                .expect("typed error")
+                .max_lines,
+            2
+        );
+        let res = rd.read_line().await;
+        assert!(res.is_none(), "iteration is done after exceeding the limit");
+
+        rd.replace(&input[..]);
+        let res = exhaust(&mut rd).await;
+        assert_eq!(
+            res, 3,
+            "replacing the reader resets the count, so two lines are read before failing again"
+        );
+        Ok(())
+    }
+
     #[maybe_async::maybe_async]
     async fn exhaust(rd: &mut gix_packetline::StreamingPeekableIter<&[u8]>) -> i32 {
         let mut count = 0;
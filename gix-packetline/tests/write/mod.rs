@@ -1,3 +1,6 @@
+#[cfg(feature = "blocking-io")]
+mod vectored;
+
 #[cfg(feature = "blocking-io")]
 use std::io::Write;
 
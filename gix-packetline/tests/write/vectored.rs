@@ -0,0 +1,136 @@
+use std::io;
+
+use bstr::ByteSlice;
+use gix_packetline::{encode, Channel, VectoredWriter};
+
+/// Queue the same lines as [`encode_lines()`], with the line number in each of them.
+fn queue_lines<'a, T: io::Write>(lines: &'a [String], w: &mut VectoredWriter<'a, T>) -> io::Result<()> {
+    for (index, line) in lines.iter().enumerate() {
+        match index % 7 {
+            0 => w.text(line.as_bytes()),
+            1 => w.data(line.as_bytes()),
+            2 => w.band(Channel::Data, line.as_bytes()),
+            3 => w.band(Channel::Progress, line.as_bytes()),
+            4 => w.error(line.as_bytes()),
+            5 => w.delim(),
+            _ => w.response_end(),
+        }?;
+    }
+    w.flush_line()
+}
+
+fn encode_lines(lines: &[String], mut out: impl io::Write) -> io::Result<()> {
+    for (index, line) in lines.iter().enumerate() {
+        match index % 7 {
+            0 => encode::text_to_write(line.as_bytes(), &mut out),
+            1 => encode::data_to_write(line.as_bytes(), &mut out),
+            2 => encode::band_to_write(Channel::Data, line.as_bytes(), &mut out),
+            3 => encode::band_to_write(Channel::Progress, line.as_bytes(), &mut out),
+            4 => encode::error_to_write(line.as_bytes(), &mut out),
+            5 => encode::delim_to_write(&mut out),
+            _ => encode::response_end_to_write(&mut out),
+        }?;
+    }
+    encode::flush_to_write(&mut out).map(|_| ())
+}
+
+fn lines(count: usize) -> Vec<String> {
+    (0..count).map(|index| format!("line {index}")).collect()
+}
+
+/// A writer which writes at most `max` bytes at a time, and is interrupted every other call.
+struct Trickle {
+    out: Vec<u8>,
+    max: usize,
+    calls: usize,
+}
+
+impl io::Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.calls += 1;
+        if self.calls % 2 == 0 {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        let len = buf.len().min(self.max);
+        self.out.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn output_is_the_same_as_the_one_of_the_encoder() -> crate::Result {
+    for count in [0, 1, 7, 2000] {
+        let lines = lines(count);
+        let mut expected = Vec::new();
+        encode_lines(&lines, &mut expected)?;
+
+        let mut w = VectoredWriter::new(Vec::new());
+        queue_lines(&lines, &mut w)?;
+        assert_eq!(w.into_inner()?.as_bstr(), expected.as_bstr(), "{count} lines");
+    }
+    Ok(())
+}
+
+#[test]
+fn lines_are_written_in_batches_once_enough_are_queued() -> crate::Result {
+    let lines = lines(2000);
+    let mut w = VectoredWriter::new(Vec::new());
+    w.text(lines[0].as_bytes())?;
+    assert!(w.inner_mut().is_empty(), "a single line is only queued");
+    for line in &lines[1..] {
+        w.text(line.as_bytes())?;
+    }
+    assert!(
+        !w.inner_mut().is_empty(),
+        "many lines are written before being asked to"
+    );
+    let out = w.into_inner()?;
+    assert_eq!(out.lines().count(), 2000, "all lines are written");
+    Ok(())
+}
+
+#[test]
+fn partial_and_interrupted_writes_are_retried() -> crate::Result {
+    let lines = lines(1500);
+    let mut expected = Vec::new();
+    encode_lines(&lines, &mut expected)?;
+
+    let mut w = VectoredWriter::new(Trickle {
+        out: Vec::new(),
+        max: 3,
+        calls: 0,
+    });
+    queue_lines(&lines, &mut w)?;
+    w.flush()?;
+    assert_eq!(w.into_inner()?.out.as_bstr(), expected.as_bstr());
+    Ok(())
+}
+
+#[test]
+fn writers_not_accepting_data_cause_an_error() -> crate::Result {
+    let mut buf = [0u8; 6];
+    let mut w = VectoredWriter::new(&mut buf[..]);
+    w.text(b"hello")?;
+    let err = w.write_queued().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    Ok(())
+}
+
+#[test]
+fn invalid_lines_are_rejected_without_being_queued() -> crate::Result {
+    let mut w = VectoredWriter::new(Vec::new());
+    crate::assert_err_display(w.data(&[]), "Empty lines are invalid");
+    let data = vec![0; 65516];
+    w.data(&data)?;
+    crate::assert_err_display(w.text(&data), "Cannot encode more than 65516 bytes, got 65517");
+    crate::assert_err_display(
+        w.band(Channel::Data, &data),
+        "Cannot encode more than 65516 bytes, got 65517",
+    );
+    assert_eq!(w.into_inner()?.len(), 65520, "only the valid line was written");
+    Ok(())
+}
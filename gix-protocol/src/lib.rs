@@ -40,6 +40,9 @@ mod fetch_fn;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub use fetch_fn::{fetch, FetchConnection};
 
+///
+pub mod push;
+
 mod remote_progress;
 pub use remote_progress::RemoteProgress;
 
@@ -56,7 +59,7 @@ pub use handshake::function::handshake;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub mod ls_refs;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
-pub use ls_refs::function::ls_refs;
+pub use ls_refs::function::{ls_refs, ls_refs_streaming};
 
 mod util;
 pub use util::agent;
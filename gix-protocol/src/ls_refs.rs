@@ -37,6 +37,17 @@ pub enum Action {
     Skip,
 }
 
+/// What to do after receiving a reference in [`ls_refs_streaming()`][crate::ls_refs_streaming()].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Flow {
+    /// Receive the next reference.
+    Continue,
+    /// Don't parse any of the remaining references as all needed ones were received.
+    ///
+    /// The remaining lines of the response are still read so the connection can be used for more commands.
+    Stop,
+}
+
 pub(crate) mod function {
     use std::borrow::Cow;
 
@@ -45,9 +56,12 @@ pub(crate) mod function {
     use gix_transport::client::{Capabilities, Transport, TransportV2Ext};
     use maybe_async::maybe_async;
 
-    use super::{Action, Error};
+    use super::{Action, Error, Flow};
     use crate::{
-        handshake::{refs::from_v2_refs, Ref},
+        handshake::{
+            refs::{parse, shared::parse_v2},
+            Ref,
+        },
         indicate_end_of_interaction, Command,
     };
 
@@ -57,7 +71,7 @@ pub(crate) mod function {
     /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
     #[maybe_async]
     pub async fn ls_refs(
-        mut transport: impl Transport,
+        transport: impl Transport,
         capabilities: &Capabilities,
         prepare_ls_refs: impl FnOnce(
             &Capabilities,
@@ -67,6 +81,33 @@ pub(crate) mod function {
         progress: &mut impl Progress,
         trace: bool,
     ) -> Result<Vec<Ref>, Error> {
+        let mut refs = Vec::new();
+        ls_refs_streaming(transport, capabilities, prepare_ls_refs, progress, trace, |r| {
+            refs.push(r);
+            Ok(Flow::Continue)
+        })
+        .await?;
+        Ok(refs)
+    }
+
+    /// Like [`ls_refs()`][crate::ls_refs()], but instead of collecting all references, pass each of them to `on_ref` as
+    /// soon as it was received, which is useful for repositories with a lot of references.
+    ///
+    /// If `on_ref` returns [`Flow::Stop`], the remaining references are skipped without being parsed, and if it returns
+    /// an error, the same is done before returning it.
+    #[maybe_async]
+    pub async fn ls_refs_streaming(
+        mut transport: impl Transport,
+        capabilities: &Capabilities,
+        prepare_ls_refs: impl FnOnce(
+            &Capabilities,
+            &mut Vec<BString>,
+            &mut Vec<(&str, Option<Cow<'static, str>>)>,
+        ) -> std::io::Result<Action>,
+        progress: &mut impl Progress,
+        trace: bool,
+        mut on_ref: impl FnMut(Ref) -> std::io::Result<Flow>,
+    ) -> Result<(), Error> {
         let _span = gix_features::trace::detail!("gix_protocol::ls_refs()", capabilities = ?capabilities);
         let ls_refs = Command::LsRefs;
         let mut ls_features = ls_refs.default_features(gix_transport::Protocol::V2, capabilities);
@@ -78,8 +119,8 @@ pub(crate) mod function {
         {
             ls_args.push("unborn".into());
         }
-        let refs = match prepare_ls_refs(capabilities, &mut ls_args, &mut ls_features) {
-            Ok(Action::Skip) => Vec::new(),
+        match prepare_ls_refs(capabilities, &mut ls_args, &mut ls_features) {
+            Ok(Action::Skip) => {}
             Ok(Action::Continue) => {
                 ls_refs.validate_argument_prefixes_or_panic(
                     gix_transport::Protocol::V2,
@@ -102,13 +143,40 @@ pub(crate) mod function {
                         trace,
                     )
                     .await?;
-                from_v2_refs(&mut remote_refs).await?
+                let mut res = Ok(());
+                while let Some(line) = remote_refs
+                    .readline()
+                    .await
+                    .transpose()?
+                    .transpose()
+                    .map_err(parse::Error::from)?
+                    .and_then(|l| l.as_bstr())
+                {
+                    match on_ref(parse_v2(line)?) {
+                        Ok(Flow::Continue) => {}
+                        Ok(Flow::Stop) => break,
+                        Err(err) => {
+                            res = Err(err.into());
+                            break;
+                        }
+                    }
+                }
+                // Consume the rest of the response to keep the connection usable.
+                while remote_refs
+                    .readline()
+                    .await
+                    .transpose()?
+                    .transpose()
+                    .map_err(parse::Error::from)?
+                    .is_some()
+                {}
+                return res;
             }
             Err(err) => {
                 indicate_end_of_interaction(transport, trace).await?;
                 return Err(err.into());
             }
-        };
-        Ok(refs)
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,228 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+use gix_transport::client::Capabilities;
+
+/// A single reference update to send to `git-receive-pack`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// The object the reference points to on the remote, or the null id if it is to be created.
+    pub old_id: ObjectId,
+    /// The object the reference should point to, or the null id if it is to be deleted.
+    pub new_id: ObjectId,
+    /// The full name of the reference on the remote, like `refs/heads/main`.
+    pub name: BString,
+}
+
+impl Command {
+    /// Return this command as `<old-id> <new-id> <name>`, without trailing newline.
+    pub fn to_line(&self) -> BString {
+        let mut out = BString::from(self.old_id.to_string());
+        out.push_byte(b' ');
+        out.push_str(self.new_id.to_string());
+        out.push_byte(b' ');
+        out.push_str(&self.name);
+        out
+    }
+}
+
+/// The error returned by [`Arguments`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The server does not support push options, which is required to send {option:?}")]
+    PushOptionsUnsupported { option: BString },
+    #[error("The server does not support signed pushes as it didn't advertise a 'push-cert' nonce")]
+    SignedPushUnsupported,
+    #[error("Push option {option:?} must not contain newlines or NUL bytes")]
+    InvalidPushOption { option: BString },
+    #[error("Signing the push certificate failed")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// The arguments to send to `git-receive-pack`, validated against the capabilities it advertised.
+#[derive(Debug, Clone)]
+pub struct Arguments {
+    commands: Vec<Command>,
+    features: Vec<BString>,
+    push_options: Vec<BString>,
+    supports_push_options: bool,
+    nonce: Option<BString>,
+}
+
+/// The lines of a push request to send to `git-receive-pack`, each of which is to be written as packet line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// The lines with the reference updates, or the lines of the signed push certificate that contains them,
+    /// to be followed by a flush packet.
+    pub commands: Vec<BString>,
+    /// The lines with push options, to be followed by a flush packet unless empty, in which case nothing is to be sent.
+    pub push_options: Vec<BString>,
+}
+
+/// Lifecycle
+impl Arguments {
+    /// Create a new instance based on the `capabilities` advertised by `git-receive-pack`.
+    pub fn new(capabilities: &Capabilities) -> Self {
+        Arguments {
+            commands: Vec::new(),
+            features: Vec::new(),
+            push_options: Vec::new(),
+            supports_push_options: capabilities.contains("push-options"),
+            nonce: capabilities
+                .capability("push-cert")
+                .and_then(|c| c.value().map(ToOwned::to_owned)),
+        }
+    }
+}
+
+/// Access
+impl Arguments {
+    /// Return `true` if the server supports push options.
+    pub fn can_use_push_options(&self) -> bool {
+        self.supports_push_options
+    }
+
+    /// Return `true` if the server supports signed pushes, i.e. if it sent a nonce for us to sign.
+    pub fn can_use_signed_push(&self) -> bool {
+        self.nonce.is_some()
+    }
+
+    /// Return the nonce the server advertised with the `push-cert` capability, to be included in the push certificate.
+    pub fn nonce(&self) -> Option<&BStr> {
+        self.nonce.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Add the `feature`, like `report-status` or `atomic`, to the capabilities sent along with the first command.
+    pub fn use_feature(&mut self, feature: impl Into<BString>) {
+        let feature = feature.into();
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+    }
+
+    /// Add `command` to the reference updates to send.
+    pub fn command(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+
+    /// Add `option` to be sent as push option, like `git push --push-option=<option>`.
+    ///
+    /// Fails if the server doesn't support push options, or if `option` contains newlines or NUL bytes.
+    pub fn push_option(&mut self, option: impl Into<BString>) -> Result<(), Error> {
+        let option = option.into();
+        if !self.supports_push_options {
+            return Err(Error::PushOptionsUnsupported { option });
+        }
+        if option.contains(&b'\n') || option.contains(&b'\0') {
+            return Err(Error::InvalidPushOption { option });
+        }
+        self.use_feature("push-options");
+        self.push_options.push(option);
+        Ok(())
+    }
+}
+
+/// Output
+impl Arguments {
+    /// Produce the request with unsigned commands.
+    pub fn into_request(self) -> Request {
+        let features = self.features.join(&b' ');
+        let commands = self
+            .commands
+            .iter()
+            .enumerate()
+            .map(|(idx, command)| {
+                let mut line = command.to_line();
+                if idx == 0 {
+                    line.push_byte(0);
+                    line.extend_from_slice(&features);
+                }
+                line
+            })
+            .collect();
+        Request {
+            commands,
+            push_options: self.push_options,
+        }
+    }
+
+    /// Produce the request with all commands sent as part of a push certificate, which is signed by passing its
+    /// payload to `sign()`, which returns the signature to append to it, like `git push --signed`.
+    ///
+    /// `pusher` is the identity of the signer along with the time of signing, like `Name <email> 1700000000 +0100`,
+    /// and `pushee` is the URL of the remote without any credentials. If not set, it is omitted from the certificate.
+    ///
+    /// Fails if the server doesn't support signed pushes.
+    pub fn into_signed_request<E>(
+        mut self,
+        pusher: &BStr,
+        pushee: Option<&BStr>,
+        sign: impl FnOnce(&BStr) -> Result<BString, E>,
+    ) -> Result<Request, Error>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let nonce = self.nonce.take().ok_or(Error::SignedPushUnsupported)?;
+        let mut certificate = Certificate {
+            pusher: pusher.to_owned(),
+            pushee: pushee.map(ToOwned::to_owned),
+            nonce,
+            push_options: self.push_options.clone(),
+            commands: std::mem::take(&mut self.commands),
+        }
+        .to_payload();
+        let signature = sign(certificate.as_ref()).map_err(|err| Error::Sign(err.into()))?;
+        certificate.extend_from_slice(&signature);
+
+        let mut first_line = BString::from("push-cert\0");
+        first_line.extend_from_slice(&self.features.join(&b' '));
+        let mut commands = vec![first_line];
+        commands.extend(certificate.lines_with_terminator().map(BString::from));
+        commands.push("push-cert-end\n".into());
+        Ok(Request {
+            commands,
+            push_options: self.push_options,
+        })
+    }
+}
+
+/// The unsigned portion of a push certificate, as described in `git help gitprotocol-pack`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    /// The identity of the signer and the time of signing, like `Name <email> 1700000000 +0100`.
+    pub pusher: BString,
+    /// The URL of the remote without any credentials, if known.
+    pub pushee: Option<BString>,
+    /// The nonce advertised by the server.
+    pub nonce: BString,
+    /// The push options sent alongside the push.
+    pub push_options: Vec<BString>,
+    /// The reference updates to perform.
+    pub commands: Vec<Command>,
+}
+
+impl Certificate {
+    /// Return the payload to sign, which ends with the last command line.
+    pub fn to_payload(&self) -> BString {
+        let mut out = BString::from("certificate version 0.1\n");
+        for (field, value) in [("pusher", Some(&self.pusher)), ("pushee", self.pushee.as_ref())]
+            .into_iter()
+            .chain(std::iter::once(("nonce", Some(&self.nonce))))
+            .chain(self.push_options.iter().map(|option| ("push-option", Some(option))))
+        {
+            if let Some(value) = value {
+                out.push_str(field);
+                out.push_byte(b' ');
+                out.push_str(value);
+                out.push_byte(b'\n');
+            }
+        }
+        out.push_byte(b'\n');
+        for command in &self.commands {
+            out.push_str(command.to_line());
+            out.push_byte(b'\n');
+        }
+        out
+    }
+}
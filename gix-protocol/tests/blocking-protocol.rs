@@ -6,4 +6,5 @@ pub fn fixture_bytes(path: &str) -> Vec<u8> {
 }
 
 mod fetch;
+mod push;
 mod remote_progress;
@@ -202,3 +202,63 @@ async fn ref_in_want() -> crate::Result {
 
     Ok(())
 }
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ls_refs_streaming_stops_early_and_keeps_the_connection_usable() -> crate::Result {
+    use gix_transport::client::TransportV2Ext;
+
+    let mut transport = transport(
+        Vec::new(),
+        "v2/clone.response",
+        Protocol::V2,
+        gix_transport::client::git::ConnectMode::Daemon,
+    );
+    let outcome = gix_protocol::handshake(
+        &mut transport,
+        gix_transport::Service::UploadPack,
+        helper_unused,
+        Vec::new(),
+        &mut progress::Discard,
+    )
+    .await?;
+
+    let mut refs = Vec::new();
+    gix_protocol::ls_refs_streaming(
+        &mut transport,
+        &outcome.capabilities,
+        |_capabilities, _arguments, _features| Ok(ls_refs::Action::Continue),
+        &mut progress::Discard,
+        false,
+        |r| {
+            refs.push(r);
+            Ok(ls_refs::Flow::Stop)
+        },
+    )
+    .await?;
+    assert_eq!(
+        refs,
+        vec![handshake::Ref::Symbolic {
+            full_ref_name: "HEAD".into(),
+            target: "refs/heads/master".into(),
+            tag: None,
+            object: oid("808e50d724f604f69ab93c6da2919c014667bedb"),
+        }],
+        "only the first of two references was received"
+    );
+
+    let mut response = transport
+        .invoke(
+            "fetch",
+            Vec::<(&str, Option<&str>)>::new().into_iter(),
+            None::<std::iter::Empty<bstr::BString>>,
+            false,
+        )
+        .await?;
+    let line = response.readline().await.expect("line")??;
+    assert_eq!(
+        line.as_bstr(),
+        Some(b"packfile\n".as_bstr()),
+        "the remaining references were skipped"
+    );
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use bstr::{BString, ByteSlice};
+use gix_protocol::push::{Arguments, Command, Error};
+use gix_transport::client::Capabilities;
+
+fn capabilities(caps: &str) -> Capabilities {
+    let mut line = BString::from("0000000000000000000000000000000000000000 capabilities^{}\0");
+    line.extend_from_slice(caps.as_bytes());
+    Capabilities::from_bytes(&line).expect("valid").0
+}
+
+fn command(name: &str) -> Command {
+    Command {
+        old_id: gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+        new_id: gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1),
+        name: name.into(),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("signing failed")]
+struct SignError;
+
+#[test]
+fn unsigned_with_push_options() -> crate::Result {
+    let mut args = Arguments::new(&capabilities("report-status push-options"));
+    assert!(args.can_use_push_options());
+    assert!(!args.can_use_signed_push());
+    args.use_feature("report-status");
+    args.command(command("refs/heads/main"));
+    args.command(command("refs/heads/other"));
+    args.push_option("ci.skip")?;
+    assert!(matches!(args.push_option("a\nb"), Err(Error::InvalidPushOption { .. })));
+
+    let request = args.into_request();
+    assert_eq!(
+        request.commands,
+        [
+            "0000000000000000000000000000000000000000 4b825dc642cb6eb9a060e54bf8d69288fbee4904 refs/heads/main\0report-status push-options",
+            "0000000000000000000000000000000000000000 4b825dc642cb6eb9a060e54bf8d69288fbee4904 refs/heads/other"
+        ]
+    );
+    assert_eq!(request.push_options, ["ci.skip"]);
+    Ok(())
+}
+
+#[test]
+fn push_options_and_signing_require_server_support() {
+    let mut args = Arguments::new(&capabilities("report-status"));
+    assert!(matches!(
+        args.push_option("ci.skip"),
+        Err(Error::PushOptionsUnsupported { .. })
+    ));
+    assert!(matches!(
+        args.into_signed_request("pusher".into(), None, |_| Ok::<_, SignError>(BString::default())),
+        Err(Error::SignedPushUnsupported)
+    ));
+}
+
+#[test]
+fn signed_with_push_options() -> crate::Result {
+    let mut args = Arguments::new(&capabilities("report-status push-options push-cert=1700000000-abcdef"));
+    assert_eq!(args.nonce().expect("present"), "1700000000-abcdef");
+    args.use_feature("report-status");
+    args.command(command("refs/heads/main"));
+    args.push_option("topic=x")?;
+
+    let mut payload = None;
+    let request = args.into_signed_request(
+        "A U Thor <author@example.com> 1700000000 +0100".into(),
+        Some("https://example.com/repo".into()),
+        |data| {
+            payload = Some(data.to_owned());
+            Ok::<_, SignError>("-----BEGIN PGP SIGNATURE-----\nsig\n-----END PGP SIGNATURE-----\n".into())
+        },
+    )?;
+    let payload = payload.expect("sign was called");
+    assert_eq!(
+        payload.to_str()?,
+        "certificate version 0.1
+pusher A U Thor <author@example.com> 1700000000 +0100
+pushee https://example.com/repo
+nonce 1700000000-abcdef
+push-option topic=x
+
+0000000000000000000000000000000000000000 4b825dc642cb6eb9a060e54bf8d69288fbee4904 refs/heads/main
+"
+    );
+    assert_eq!(
+        request.commands,
+        [
+            "push-cert\0report-status push-options",
+            "certificate version 0.1\n",
+            "pusher A U Thor <author@example.com> 1700000000 +0100\n",
+            "pushee https://example.com/repo\n",
+            "nonce 1700000000-abcdef\n",
+            "push-option topic=x\n",
+            "\n",
+            "0000000000000000000000000000000000000000 4b825dc642cb6eb9a060e54bf8d69288fbee4904 refs/heads/main\n",
+            "-----BEGIN PGP SIGNATURE-----\n",
+            "sig\n",
+            "-----END PGP SIGNATURE-----\n",
+            "push-cert-end\n",
+        ]
+    );
+    assert_eq!(request.push_options, ["topic=x"]);
+
+    let args = Arguments::new(&capabilities("push-cert=nonce"));
+    assert!(matches!(
+        args.into_signed_request("pusher".into(), None, |_| Err(SignError)),
+        Err(Error::Sign(_))
+    ));
+    Ok(())
+}
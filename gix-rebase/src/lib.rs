@@ -1,2 +1,6 @@
-#![deny(rust_2018_idioms)]
+//! Data structures and parsers for rebases, like the [list of instructions](todo::List) that drives an interactive rebase.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+///
+pub mod todo;
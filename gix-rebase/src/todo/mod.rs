@@ -0,0 +1,184 @@
+//! Parse and serialize the `git-rebase-todo` file which lists the instructions of an interactive rebase, one per line.
+use bstr::BString;
+use gix_hash::Prefix;
+
+///
+pub mod parse;
+mod write;
+
+/// The parsed `git-rebase-todo` file, with instructions to perform in order.
+///
+/// Comments and empty lines are retained so that a list can be written back without losing information.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct List {
+    /// All lines of the file, in order.
+    pub lines: Vec<Line>,
+}
+
+/// A single line in a [`List`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Line {
+    /// An instruction to perform.
+    Instruction(Instruction),
+    /// A line that is ignored, either because it's empty or because it starts with the comment character.
+    ///
+    /// It's stored verbatim, including the comment character.
+    Comment(BString),
+}
+
+/// What to do with the message of a commit that is folded into its predecessor with a [`fixup`](Instruction::Fixup).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum FixupMessage {
+    /// Keep the message of the commit that is fixed up and discard the one of the fixup commit.
+    #[default]
+    Discard,
+    /// Use the message of the fixup commit instead of the one of the commit that is fixed up, like `fixup -C`.
+    Use,
+    /// Like [`Use`](FixupMessage::Use), but let the user edit the message, like `fixup -c`.
+    Edit,
+}
+
+/// An instruction of an interactive rebase, see the
+/// [git documentation](https://git-scm.com/docs/git-rebase#_interactive_mode) for details.
+///
+/// The `summary` of instructions which refer to a commit is typically the first line of its message, and for informational purposes only.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// `pick <commit>`: apply `commit` as is.
+    Pick {
+        /// The commit to apply.
+        commit: Prefix,
+        /// Text after the commit.
+        summary: BString,
+    },
+    /// `reword <commit>`: apply `commit`, but let the user edit its message.
+    Reword {
+        /// The commit to apply.
+        commit: Prefix,
+        /// Text after the commit.
+        summary: BString,
+    },
+    /// `edit <commit>`: apply `commit`, but stop to let the user amend it.
+    Edit {
+        /// The commit to apply.
+        commit: Prefix,
+        /// Text after the commit.
+        summary: BString,
+    },
+    /// `squash <commit>`: fold `commit` into the previous commit and let the user edit the combined message.
+    Squash {
+        /// The commit to fold into its predecessor.
+        commit: Prefix,
+        /// Text after the commit.
+        summary: BString,
+    },
+    /// `fixup [-C | -c] <commit>`: fold `commit` into the previous commit and deal with its message according to `message`.
+    Fixup {
+        /// The commit to fold into its predecessor.
+        commit: Prefix,
+        /// What to do with the message of `commit`.
+        message: FixupMessage,
+        /// Text after the commit.
+        summary: BString,
+    },
+    /// `drop <commit>`: don't apply `commit`.
+    Drop {
+        /// The commit to leave out.
+        commit: Prefix,
+        /// Text after the commit.
+        summary: BString,
+    },
+    /// `exec <command>`: run `command` with the shell, and stop if it fails.
+    Exec {
+        /// The command to run, which is everything after the instruction.
+        command: BString,
+    },
+    /// `break`: stop to let the user inspect the state, and continue later.
+    Break,
+    /// `label <label>`: remember the current commit as `label`.
+    Label {
+        /// The name of the label.
+        name: BString,
+    },
+    /// `reset <label>`: make the commit remembered as `label`, or the commit it names, the current commit.
+    Reset {
+        /// The name of the label.
+        name: BString,
+    },
+    /// `merge [-C <commit> | -c <commit>] <label> [# <oneline>]`: merge the commits known as `labels` into the current commit.
+    Merge {
+        /// The original merge commit whose message to use, if present.
+        commit: Option<Prefix>,
+        /// If `true`, the user should edit the message, which is the case with `-c <commit>` or if there is no `commit`.
+        edit_message: bool,
+        /// The labels of the commits to merge, more than one for octopus merges.
+        labels: Vec<BString>,
+        /// The text after `#`, typically the first line of the message of the original merge.
+        summary: BString,
+    },
+    /// `update-ref <ref>`: remember to set `ref` to the current commit when the rebase is finished.
+    UpdateRef {
+        /// The full name of the reference to update.
+        name: BString,
+    },
+    /// `noop`: do nothing, typically used to keep the list from being empty.
+    Noop,
+}
+
+impl Instruction {
+    /// Return the commit this instruction applies, folds or drops, or the commit whose message is used for merges.
+    pub fn commit(&self) -> Option<&Prefix> {
+        match self {
+            Instruction::Pick { commit, .. }
+            | Instruction::Reword { commit, .. }
+            | Instruction::Edit { commit, .. }
+            | Instruction::Squash { commit, .. }
+            | Instruction::Fixup { commit, .. }
+            | Instruction::Drop { commit, .. } => Some(commit),
+            Instruction::Merge { commit, .. } => commit.as_ref(),
+            Instruction::Exec { .. }
+            | Instruction::Break
+            | Instruction::Label { .. }
+            | Instruction::Reset { .. }
+            | Instruction::UpdateRef { .. }
+            | Instruction::Noop => None,
+        }
+    }
+
+    /// Return the name of this instruction as it appears in the todo list.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Instruction::Pick { .. } => "pick",
+            Instruction::Reword { .. } => "reword",
+            Instruction::Edit { .. } => "edit",
+            Instruction::Squash { .. } => "squash",
+            Instruction::Fixup { .. } => "fixup",
+            Instruction::Drop { .. } => "drop",
+            Instruction::Exec { .. } => "exec",
+            Instruction::Break => "break",
+            Instruction::Label { .. } => "label",
+            Instruction::Reset { .. } => "reset",
+            Instruction::Merge { .. } => "merge",
+            Instruction::UpdateRef { .. } => "update-ref",
+            Instruction::Noop => "noop",
+        }
+    }
+}
+
+impl List {
+    /// Return an iterator over all instructions, skipping comments.
+    pub fn instructions(&self) -> impl Iterator<Item = &Instruction> + '_ {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Instruction(instruction) => Some(instruction),
+            Line::Comment(_) => None,
+        })
+    }
+}
+
+impl FromIterator<Instruction> for List {
+    fn from_iter<T: IntoIterator<Item = Instruction>>(iter: T) -> Self {
+        List {
+            lines: iter.into_iter().map(Line::Instruction).collect(),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use bstr::{BStr, ByteSlice};
+use gix_hash::Prefix;
+
+use crate::todo::{FixupMessage, Instruction, Line, List};
+
+mod error {
+    use bstr::BString;
+
+    /// The error returned by [`List::from_bytes()`](crate::todo::List::from_bytes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Line {line_number}: unknown command {command:?}")]
+        UnknownCommand { line_number: usize, command: BString },
+        #[error("Line {line_number}: '{command}' does not accept arguments")]
+        UnexpectedArguments { line_number: usize, command: &'static str },
+        #[error("Line {line_number}: missing arguments for '{command}'")]
+        MissingArguments { line_number: usize, command: &'static str },
+        #[error("Line {line_number}: {commit:?} is not a valid commit")]
+        InvalidCommit {
+            line_number: usize,
+            commit: BString,
+            source: gix_hash::prefix::from_hex::Error,
+        },
+    }
+}
+pub use error::Error;
+
+impl List {
+    /// Parse `input` as the contents of a `git-rebase-todo` file, with `comment_char` being the value of `core.commentChar`,
+    /// typically `#`.
+    ///
+    /// Commands may be abbreviated to their first character, like `p` for `pick`, and commits are identified by their
+    /// possibly abbreviated hash.
+    pub fn from_bytes(input: &[u8], comment_char: u8) -> Result<Self, Error> {
+        let mut lines = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            let trimmed = line.trim_start();
+            lines.push(if trimmed.is_empty() || trimmed[0] == comment_char {
+                Line::Comment(line.into())
+            } else {
+                Line::Instruction(parse_instruction(trimmed.trim_end().as_bstr(), idx + 1)?)
+            });
+        }
+        Ok(List { lines })
+    }
+}
+
+fn parse_instruction(line: &BStr, line_number: usize) -> Result<Instruction, Error> {
+    let (command, args) = split_token(line);
+    let command = match command.as_bytes() {
+        b"pick" | b"p" => "pick",
+        b"reword" | b"r" => "reword",
+        b"edit" | b"e" => "edit",
+        b"squash" | b"s" => "squash",
+        b"fixup" | b"f" => "fixup",
+        b"drop" | b"d" => "drop",
+        b"exec" | b"x" => "exec",
+        b"break" | b"b" => "break",
+        b"label" | b"l" => "label",
+        b"reset" | b"t" => "reset",
+        b"merge" | b"m" => "merge",
+        b"update-ref" | b"u" => "update-ref",
+        b"noop" => "noop",
+        _ => {
+            return Err(Error::UnknownCommand {
+                line_number,
+                command: command.into(),
+            })
+        }
+    };
+    match command {
+        "break" | "noop" => {
+            if !args.is_empty() {
+                return Err(Error::UnexpectedArguments { line_number, command });
+            }
+            return Ok(if command == "break" {
+                Instruction::Break
+            } else {
+                Instruction::Noop
+            });
+        }
+        _ if args.is_empty() => return Err(Error::MissingArguments { line_number, command }),
+        _ => {}
+    }
+
+    let commit = |token: &BStr| {
+        token
+            .to_str()
+            .ok()
+            .ok_or(gix_hash::prefix::from_hex::Error::Invalid)
+            .and_then(Prefix::from_hex)
+            .map_err(|source| Error::InvalidCommit {
+                line_number,
+                commit: token.into(),
+                source,
+            })
+    };
+    Ok(match command {
+        "exec" => Instruction::Exec { command: args.into() },
+        "label" => Instruction::Label {
+            name: label_name(args).into(),
+        },
+        "reset" => Instruction::Reset {
+            name: label_name(args).into(),
+        },
+        "update-ref" => Instruction::UpdateRef {
+            name: split_token(args).0.into(),
+        },
+        "merge" => {
+            let (flag, rest) = split_token(args);
+            let (commit, edit_message, rest) = match flag.as_bytes() {
+                b"-C" | b"-c" => {
+                    let (id, rest) = split_token(rest);
+                    if id.is_empty() {
+                        return Err(Error::MissingArguments { line_number, command });
+                    }
+                    (Some(commit(id)?), flag == "-c", rest)
+                }
+                _ => (None, true, args),
+            };
+            let (labels, summary) = match rest.find_byte(b'#') {
+                Some(pos) => (rest[..pos].as_bstr(), rest[pos + 1..].trim_start().as_bstr()),
+                None => (rest, "".into()),
+            };
+            let labels: Vec<_> = labels.fields().map(|label| label.as_bstr().to_owned()).collect();
+            if labels.is_empty() {
+                return Err(Error::MissingArguments { line_number, command });
+            }
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary: summary.into(),
+            }
+        }
+        _ => {
+            let (mut token, mut rest) = split_token(args);
+            let mut message = FixupMessage::Discard;
+            if command == "fixup" && (token == "-C" || token == "-c") {
+                message = if token == "-C" {
+                    FixupMessage::Use
+                } else {
+                    FixupMessage::Edit
+                };
+                (token, rest) = split_token(rest);
+                if token.is_empty() {
+                    return Err(Error::MissingArguments { line_number, command });
+                }
+            }
+            let commit = commit(token)?;
+            let summary = rest.into();
+            match command {
+                "pick" => Instruction::Pick { commit, summary },
+                "reword" => Instruction::Reword { commit, summary },
+                "edit" => Instruction::Edit { commit, summary },
+                "squash" => Instruction::Squash { commit, summary },
+                "fixup" => Instruction::Fixup {
+                    commit,
+                    message,
+                    summary,
+                },
+                "drop" => Instruction::Drop { commit, summary },
+                _ => unreachable!("all commands are handled"),
+            }
+        }
+    })
+}
+
+/// Split the first whitespace-separated token off `input` and return it along with the remainder, without leading whitespace.
+fn split_token(input: &BStr) -> (&BStr, &BStr) {
+    match input.find_byteset(b" \t") {
+        Some(pos) => (input[..pos].as_bstr(), input[pos..].trim_start().as_bstr()),
+        None => (input, "".into()),
+    }
+}
+
+/// Labels end at the first whitespace, except for the special `[new root]` label which names the lack of a commit.
+fn label_name(args: &BStr) -> &BStr {
+    const NEW_ROOT: &[u8] = b"[new root]";
+    if args.starts_with(NEW_ROOT) {
+        args[..NEW_ROOT.len()].as_bstr()
+    } else {
+        split_token(args).0
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+
+use bstr::BStr;
+
+use crate::todo::{FixupMessage, Instruction, Line, List};
+
+impl List {
+    /// Serialize all lines to `out` in the format of the `git-rebase-todo` file, with commands spelled out in full.
+    pub fn write_to(&self, mut out: &mut dyn io::Write) -> io::Result<()> {
+        for line in &self.lines {
+            match line {
+                Line::Instruction(instruction) => instruction.write_to(&mut out)?,
+                Line::Comment(comment) => out.write_all(comment)?,
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl Instruction {
+    /// Serialize this instruction to `out` as a single line, without the trailing newline.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        out.write_all(self.name().as_bytes())?;
+        match self {
+            Instruction::Pick { commit, summary }
+            | Instruction::Reword { commit, summary }
+            | Instruction::Edit { commit, summary }
+            | Instruction::Squash { commit, summary }
+            | Instruction::Drop { commit, summary } => {
+                write!(out, " {commit}")?;
+                write_summary(out, summary.as_ref(), "")?;
+            }
+            Instruction::Fixup {
+                commit,
+                message,
+                summary,
+            } => {
+                match message {
+                    FixupMessage::Discard => {}
+                    FixupMessage::Use => out.write_all(b" -C")?,
+                    FixupMessage::Edit => out.write_all(b" -c")?,
+                }
+                write!(out, " {commit}")?;
+                write_summary(out, summary.as_ref(), "")?;
+            }
+            Instruction::Exec { command: name }
+            | Instruction::Label { name }
+            | Instruction::Reset { name }
+            | Instruction::UpdateRef { name } => {
+                out.write_all(b" ")?;
+                out.write_all(name)?;
+            }
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary,
+            } => {
+                if let Some(commit) = commit {
+                    write!(out, " {} {commit}", if *edit_message { "-c" } else { "-C" })?;
+                }
+                for label in labels {
+                    out.write_all(b" ")?;
+                    out.write_all(label)?;
+                }
+                write_summary(out, summary.as_ref(), "# ")?;
+            }
+            Instruction::Break | Instruction::Noop => {}
+        }
+        Ok(())
+    }
+}
+
+fn write_summary(out: &mut dyn io::Write, summary: &BStr, prefix: &str) -> io::Result<()> {
+    if !summary.is_empty() {
+        out.write_all(b" ")?;
+        out.write_all(prefix.as_bytes())?;
+        out.write_all(summary)?;
+    }
+    Ok(())
+}
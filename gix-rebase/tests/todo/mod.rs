@@ -0,0 +1,194 @@
+use gix_rebase::todo::{parse, FixupMessage, Instruction, Line, List};
+
+fn prefix(hex: &str) -> gix_hash::Prefix {
+    gix_hash::Prefix::from_hex(hex).expect("valid")
+}
+
+fn write(list: &List) -> String {
+    let mut buf = Vec::new();
+    list.write_to(&mut buf).expect("in-memory writes succeed");
+    String::from_utf8(buf).expect("utf8")
+}
+
+#[test]
+fn all_instructions_in_long_and_short_form() -> Result<(), parse::Error> {
+    let input = "pick 1234567 first
+r 2345678 second
+edit 3456789
+s 456789a fourth with more words
+fixup -C 56789ab fifth
+f -c 6789abc
+fixup 789abcd seventh
+d 89abcde
+exec cargo test --workspace
+x  make
+break
+label onto
+t [new root]
+reset onto # a comment
+merge -C 9abcdef topic # Merge branch 'topic'
+m -c 9abcdef a b
+merge topic
+update-ref refs/heads/topic
+noop
+";
+    let list = List::from_bytes(input.as_bytes(), b'#')?;
+    assert_eq!(
+        list.instructions().cloned().collect::<Vec<_>>(),
+        [
+            Instruction::Pick {
+                commit: prefix("1234567"),
+                summary: "first".into()
+            },
+            Instruction::Reword {
+                commit: prefix("2345678"),
+                summary: "second".into()
+            },
+            Instruction::Edit {
+                commit: prefix("3456789"),
+                summary: "".into()
+            },
+            Instruction::Squash {
+                commit: prefix("456789a"),
+                summary: "fourth with more words".into()
+            },
+            Instruction::Fixup {
+                commit: prefix("56789ab"),
+                message: FixupMessage::Use,
+                summary: "fifth".into()
+            },
+            Instruction::Fixup {
+                commit: prefix("6789abc"),
+                message: FixupMessage::Edit,
+                summary: "".into()
+            },
+            Instruction::Fixup {
+                commit: prefix("789abcd"),
+                message: FixupMessage::Discard,
+                summary: "seventh".into()
+            },
+            Instruction::Drop {
+                commit: prefix("89abcde"),
+                summary: "".into()
+            },
+            Instruction::Exec {
+                command: "cargo test --workspace".into()
+            },
+            Instruction::Exec { command: "make".into() },
+            Instruction::Break,
+            Instruction::Label { name: "onto".into() },
+            Instruction::Reset {
+                name: "[new root]".into()
+            },
+            Instruction::Reset { name: "onto".into() },
+            Instruction::Merge {
+                commit: Some(prefix("9abcdef")),
+                edit_message: false,
+                labels: vec!["topic".into()],
+                summary: "Merge branch 'topic'".into()
+            },
+            Instruction::Merge {
+                commit: Some(prefix("9abcdef")),
+                edit_message: true,
+                labels: vec!["a".into(), "b".into()],
+                summary: "".into()
+            },
+            Instruction::Merge {
+                commit: None,
+                edit_message: true,
+                labels: vec!["topic".into()],
+                summary: "".into()
+            },
+            Instruction::UpdateRef {
+                name: "refs/heads/topic".into()
+            },
+            Instruction::Noop,
+        ]
+    );
+
+    assert_eq!(
+        write(&list),
+        "pick 1234567 first
+reword 2345678 second
+edit 3456789
+squash 456789a fourth with more words
+fixup -C 56789ab fifth
+fixup -c 6789abc
+fixup 789abcd seventh
+drop 89abcde
+exec cargo test --workspace
+exec make
+break
+label onto
+reset [new root]
+reset onto
+merge -C 9abcdef topic # Merge branch 'topic'
+merge -c 9abcdef a b
+merge topic
+update-ref refs/heads/topic
+noop
+",
+        "commands are spelled out in full"
+    );
+    assert_eq!(
+        List::from_bytes(write(&list).as_bytes(), b'#')?,
+        list,
+        "serialization round-trips"
+    );
+    Ok(())
+}
+
+#[test]
+fn comments_and_empty_lines_are_kept_verbatim() -> Result<(), parse::Error> {
+    let input = "  pick 1234567 first\r\n\n; Rebase 1234567..89abcde onto 1234567\n  ; indented\n";
+    let err = List::from_bytes(input.as_bytes(), b'#').unwrap_err();
+    assert!(matches!(err, parse::Error::UnknownCommand { line_number: 3, .. }));
+
+    let list = List::from_bytes(input.as_bytes(), b';')?;
+    assert_eq!(
+        list.lines,
+        [
+            Line::Instruction(Instruction::Pick {
+                commit: prefix("1234567"),
+                summary: "first".into()
+            }),
+            Line::Comment("".into()),
+            Line::Comment("; Rebase 1234567..89abcde onto 1234567".into()),
+            Line::Comment("  ; indented".into()),
+        ],
+        "the comment character is configurable, and leading whitespace doesn't matter"
+    );
+    assert_eq!(
+        write(&list),
+        "pick 1234567 first\n\n; Rebase 1234567..89abcde onto 1234567\n  ; indented\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn errors() {
+    for (input, expected) in [
+        ("frobnicate 1234567", "Line 1: unknown command \"frobnicate\""),
+        ("pick", "Line 1: missing arguments for 'pick'"),
+        ("fixup -C", "Line 1: missing arguments for 'fixup'"),
+        ("merge -C 1234567", "Line 1: missing arguments for 'merge'"),
+        ("exec  ", "Line 1: missing arguments for 'exec'"),
+        ("noop\nbreak now", "Line 2: 'break' does not accept arguments"),
+        ("pick HEAD~1", "Line 1: \"HEAD~1\" is not a valid commit"),
+        ("pick 123", "Line 1: \"123\" is not a valid commit"),
+    ] {
+        assert_eq!(
+            List::from_bytes(input.as_bytes(), b'#').unwrap_err().to_string(),
+            expected,
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn from_instructions() {
+    let list: List = [Instruction::Break, Instruction::Noop].into_iter().collect();
+    assert_eq!(write(&list), "break\nnoop\n");
+    assert_eq!(list.instructions().count(), 2);
+    assert_eq!(Instruction::Break.commit(), None);
+}
@@ -14,6 +14,12 @@
 //!     * one reference maps to a file on disk
 //!   * **packed**
 //!     * references are stored in a single human-readable file, along with their targets if they are symbolic.
+//! * **[reftable][reftable::Store]**
+//!   * references and their logs are stored in a stack of binary tables, with each transaction adding a new table.
+//! * **[memory][memory::Store]**
+//!   * references are kept in memory only, which is useful for tests.
+//!
+//! All of them implement [`RefStore`] to allow code to be generic over the backend.
 //!
 //! ## Feature Flags
 #![cfg_attr(
@@ -29,7 +35,7 @@ use gix_object::bstr::{BStr, BString};
 
 #[path = "store/mod.rs"]
 mod store_impl;
-pub use store_impl::{file, packed};
+pub use store_impl::{file, memory, packed, reftable};
 
 mod fullname;
 ///
@@ -52,6 +58,10 @@ pub mod log;
 ///
 pub mod peel;
 
+///
+pub mod ref_store;
+pub use ref_store::RefStore;
+
 ///
 pub mod store {
     ///
@@ -66,10 +76,18 @@ pub mod store {
             pub object_hash: gix_hash::Kind,
             /// The equivalent of `core.precomposeUnicode`.
             pub precompose_unicode: bool,
+            /// The equivalent of `core.ignoreCase`, which should be `true` if the filesystem holding loose references
+            /// treats names that only differ in case as the same, as can be determined with
+            /// [`gix_fs::Capabilities::probe()`].
+            pub ignore_case: bool,
             /// If `true`, we will avoid reading from or writing to references that contains Windows device names
             /// to avoid side effects. This only needs to be `true` on Windows, but can be `true` on other platforms
             /// if they need to remain compatible with Windows.
+            /// Characters that are illegal in Windows paths and path components ending in dots or spaces are refused as well.
             pub prohibit_windows_device_names: bool,
+            /// How to sync loose references, `packed-refs` and reference logs after writing them, if at all,
+            /// typically obtained from the `reference` component of `core.fsync` and from `core.fsyncMethod`.
+            pub fsync: Option<gix_fs::fsync::Method>,
         }
     }
     /// The way a file store handles the reflog
@@ -87,7 +105,7 @@ pub mod store {
     /// A thread-local handle for interacting with a [`Store`][crate::Store] to find and iterate references.
     #[derive(Clone)]
     #[allow(dead_code)]
-    pub(crate) struct Handle {
+    pub struct Handle {
         /// A way to access shared state with the requirement that interior mutability doesn't leak or is incorporated into error types
         /// if it could. The latter can't happen if references to said internal aren't ever returned.
         state: handle::State,
@@ -96,21 +114,22 @@ pub mod store {
     #[allow(dead_code)]
     pub(crate) enum State {
         Loose { store: file::Store },
+        Reftable { store: reftable::Store },
     }
 
-    pub(crate) mod general;
+    ///
+    pub mod general;
 
     ///
     #[path = "general/handle/mod.rs"]
     mod handle;
-    use crate::file;
+    use crate::{file, reftable};
     pub use handle::find;
 }
 
-/// The git reference store.
-/// TODO: Figure out if handles are needed at all, which depends on the ref-table implementation.
-#[allow(dead_code)]
-pub(crate) struct Store {
+/// The git reference store, which keeps references either in [loose and packed files](file::Store) or in a [reftable](reftable::Store),
+/// depending on what's found in the repository.
+pub struct Store {
     inner: store::State,
 }
 
@@ -2,7 +2,8 @@
 pub mod to_id {
     use gix_object::bstr::BString;
 
-    /// The error returned by [`crate::file::ReferenceExt::peel_to_id_in_place()`].
+    /// The error returned by [`crate::file::ReferenceExt::peel_to_id_in_place()`] and
+    /// [`reftable::Store::peel_to_id_in_place()`](crate::reftable::Store::peel_to_id_in_place()).
     #[derive(Debug, thiserror::Error)]
     #[allow(missing_docs)]
     pub enum Error {
@@ -21,15 +22,49 @@ pub mod to_object {
 
     use crate::file;
 
-    /// The error returned by [`file::ReferenceExt::follow_to_object_in_place_packed()`].
+    /// The error returned by [`file::ReferenceExt::follow_to_object_in_place_packed()`] and
+    /// [`reftable::Store::follow_to_object_in_place()`](crate::reftable::Store::follow_to_object_in_place()).
     #[derive(Debug, thiserror::Error)]
     #[allow(missing_docs)]
     pub enum Error {
         #[error("Could not follow a single level of a symbolic reference")]
         Follow(#[from] file::find::existing::Error),
+        #[error("Could not follow a single level of a symbolic reference in the reftable")]
+        FollowReftable(#[from] crate::reftable::find::existing::Error),
         #[error("Aborting due to reference cycle with first seen path being {start_absolute:?}")]
         Cycle { start_absolute: PathBuf },
         #[error("Refusing to follow more than {max_depth} levels of indirection")]
         DepthLimitExceeded { max_depth: usize },
     }
 }
+
+/// Follow the chain of annotated tags starting at `oid` using `objects`, and return the first object that isn't a tag.
+///
+/// `name` is the name of the reference that points to `oid`, for use in errors.
+pub(crate) fn to_first_non_tag(
+    mut oid: gix_hash::ObjectId,
+    name: &crate::FullNameRef,
+    objects: &dyn gix_object::Find,
+) -> Result<gix_hash::ObjectId, to_id::Error> {
+    let mut buf = Vec::new();
+    loop {
+        let gix_object::Data { kind, data } =
+            objects
+                .try_find(&oid, &mut buf)?
+                .ok_or_else(|| to_id::Error::NotFound {
+                    oid,
+                    name: name.as_bstr().to_owned(),
+                })?;
+        match kind {
+            gix_object::Kind::Tag => {
+                oid = gix_object::TagRefIter::from_bytes(data)
+                    .target_id()
+                    .map_err(|_err| to_id::Error::NotFound {
+                        oid,
+                        name: name.as_bstr().to_owned(),
+                    })?;
+            }
+            _ => break Ok(oid),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use gix_object::bstr::{BStr, ByteSlice};
+
+use crate::{file, memory, reftable, transaction::RefEdit, PartialNameRef, Reference};
+
+/// The error returned by all methods of [`RefStore`], which is whatever error the store implementation produces.
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Find, iterate and edit references without knowing how they are stored, to allow code to be generic over the backend.
+///
+/// It's implemented by the [file store](file::Store), the [reftable store](reftable::Store) and the
+/// [in-memory store](memory::Store), with the latter being useful for tests.
+pub trait RefStore {
+    /// Find the reference with the given `partial` name using git's lookup rules, or return `None` if it doesn't exist.
+    fn try_find_reference(&self, partial: &PartialNameRef) -> Result<Option<Reference>, Error>;
+
+    /// Return all references in `refs/` sorted by name, like [`file::iter::Platform::all()`] does,
+    /// or only those whose name starts with `prefix`, like `refs/heads/`.
+    ///
+    /// `prefix` must end with a slash for all stores to produce the same results.
+    /// Broken references don't stop the iteration, but are returned as error instead.
+    fn iter_references(
+        &self,
+        prefix: Option<&BStr>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Reference, Error>> + '_>, Error>;
+
+    /// Apply `edits` in a single transaction, using `lock_fail_mode` to determine how to wait for locks,
+    /// and return the performed edits like [`file::Transaction::commit()`] does.
+    ///
+    /// `committer` is used for reflog entries, if the store writes them.
+    fn apply_edits(
+        &self,
+        edits: Vec<RefEdit>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<Vec<RefEdit>, Error>;
+}
+
+impl RefStore for file::Store {
+    fn try_find_reference(&self, partial: &PartialNameRef) -> Result<Option<Reference>, Error> {
+        Ok(self.try_find(partial)?)
+    }
+
+    fn iter_references(
+        &self,
+        prefix: Option<&BStr>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Reference, Error>> + '_>, Error> {
+        // The iterator borrows the packed-refs buffer of the platform, so it has to be collected.
+        let platform = self.iter()?;
+        let references: Vec<_> = match prefix {
+            Some(prefix) => platform.prefixed(&gix_path::from_bstr(prefix.trim_end_with(|c| c == '/').as_bstr()))?,
+            None => platform.all()?,
+        }
+        .map(|reference| reference.map_err(Error::from))
+        .collect();
+        Ok(Box::new(references.into_iter()))
+    }
+
+    fn apply_edits(
+        &self,
+        edits: Vec<RefEdit>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<Vec<RefEdit>, Error> {
+        Ok(self
+            .transaction()
+            .prepare(edits, lock_fail_mode, lock_fail_mode)?
+            .commit(committer)?)
+    }
+}
+
+impl RefStore for reftable::Store {
+    fn try_find_reference(&self, partial: &PartialNameRef) -> Result<Option<Reference>, Error> {
+        Ok(self.try_find(partial)?)
+    }
+
+    fn iter_references(
+        &self,
+        prefix: Option<&BStr>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Reference, Error>> + '_>, Error> {
+        let references = self.iter_prefixed(prefix.unwrap_or(b"refs/".as_bstr()).to_owned())?;
+        Ok(Box::new(references.map(Ok)))
+    }
+
+    fn apply_edits(
+        &self,
+        edits: Vec<RefEdit>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<Vec<RefEdit>, Error> {
+        Ok(self.transaction().prepare(edits, lock_fail_mode)?.commit(committer)?)
+    }
+}
+
+impl RefStore for memory::Store {
+    fn try_find_reference(&self, partial: &PartialNameRef) -> Result<Option<Reference>, Error> {
+        Ok(self.try_find(partial))
+    }
+
+    fn iter_references(
+        &self,
+        prefix: Option<&BStr>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Reference, Error>> + '_>, Error> {
+        let references = self.iter_prefixed(prefix.unwrap_or(b"refs/".as_bstr()));
+        Ok(Box::new(references.into_iter().map(Ok)))
+    }
+
+    fn apply_edits(
+        &self,
+        edits: Vec<RefEdit>,
+        _lock_fail_mode: gix_lock::acquire::Fail,
+        _committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<Vec<RefEdit>, Error> {
+        Ok(self.apply(edits)?)
+    }
+}
@@ -9,7 +9,7 @@ pub use error::Error;
 use crate::{
     file,
     store_impl::{file::loose, packed},
-    BStr, BString, FullNameRef, PartialName, PartialNameRef, Reference,
+    BStr, BString, FullName, FullNameRef, PartialName, PartialNameRef, Reference,
 };
 
 /// ### Finding References - notes about precomposed unicode.
@@ -70,6 +70,54 @@ impl file::Store {
         self.find_one_with_verified_input(partial.try_into()?, packed)
     }
 
+    /// Return `true` if the reference named `name` exists as loose reference or in the packed-refs buffer, without reading
+    /// or parsing its target, which makes it much cheaper than [`try_find()`](Self::try_find()).
+    ///
+    /// Note that `name` is used as is, without applying the lookup rules for partial names, and that references which
+    /// couldn't be parsed are considered to exist as well.
+    /// The packed buffer is checked for modifications each time the method is called. See [`file::Store::contains_packed()`]
+    /// for a version with more control.
+    pub fn contains(&self, name: &FullNameRef) -> Result<bool, Error> {
+        let packed = self.assure_packed_refs_uptodate()?;
+        self.contains_packed(name, packed.as_ref().map(|b| &***b))
+    }
+
+    /// Similar to [`file::Store::contains()`], but allows to pass a snapshotted packed buffer instead.
+    pub fn contains_packed(&self, name: &FullNameRef, packed: Option<&packed::Buffer>) -> Result<bool, Error> {
+        let precomposed = self
+            .precompose_unicode
+            .then(|| {
+                use gix_object::bstr::ByteSlice;
+                match gix_utils::str::precompose(name.as_bstr().to_str().ok()?.into()) {
+                    Cow::Owned(precomposed) => Some(FullName(precomposed.into())),
+                    Cow::Borrowed(_) => None,
+                }
+            })
+            .flatten();
+        let name = precomposed.as_ref().map_or(name, FullName::as_ref);
+        if self.loose_ref_exists(name).map_err(|err| Error::ReadFileContents {
+            source: err,
+            path: self.reference_path(name),
+        })? {
+            return Ok(true);
+        }
+        let Some(packed) = packed else {
+            return Ok(false);
+        };
+        let Some(name) = packed::find::transform_full_name_for_lookup(name) else {
+            return Ok(false);
+        };
+        let namespaced_name;
+        let name = match &self.namespace {
+            Some(namespace) => {
+                namespaced_name = namespace.to_owned().into_namespaced_name(name);
+                namespaced_name.as_ref()
+            }
+            None => name,
+        };
+        Ok(packed.contains(name)?)
+    }
+
     pub(crate) fn find_one_with_verified_input(
         &self,
         partial_name: &PartialNameRef,
@@ -249,20 +297,28 @@ impl file::Store {
         base.join(relative_path)
     }
 
+    /// If [`ignore_case`](file::Store::ignore_case) is set, return the name of the loose reference, or the directory of
+    /// loose references, that would be accessed for `name` if it only differs in case, or `None` otherwise.
+    ///
+    /// The returned name includes the namespace, if one is set.
+    pub(crate) fn loose_name_differing_in_case(&self, name: &FullNameRef) -> io::Result<Option<BString>> {
+        if !self.ignore_case {
+            return Ok(None);
+        }
+        let (base, relative_path) = self.reference_path_with_base(name);
+        Ok(
+            file::name_on_disk_if_case_differs(&base, &relative_path, self.precompose_unicode)?
+                .map(|name_on_disk| file::path_to_name(name_on_disk).into_owned()),
+        )
+    }
+
     /// Read the file contents with a verified full reference path and return it in the given vector if possible.
+    ///
+    /// If [`ignore_case`](file::Store::ignore_case) is set, files that belong to a reference whose name only differs
+    /// in case are treated as if they didn't exist.
     pub(crate) fn ref_contents(&self, name: &FullNameRef) -> io::Result<Option<Vec<u8>>> {
         let (base, relative_path) = self.reference_path_with_base(name);
-        if self.prohibit_windows_device_names
-            && relative_path
-                .components()
-                .filter_map(|c| gix_path::try_os_str_into_bstr(c.as_os_str().into()).ok())
-                .any(|c| gix_validate::path::component_is_windows_device(c.as_ref()))
-        {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Illegal use of reserved Windows device name in \"{}\"", name.as_bstr()),
-            ));
-        }
+        self.assure_valid_loose_path(name, &relative_path)?;
 
         let ref_path = base.join(relative_path);
         match std::fs::File::open(&ref_path) {
@@ -271,14 +327,77 @@ impl file::Store {
                 if let Err(err) = file.read_to_end(&mut buf) {
                     return if ref_path.is_dir() { Ok(None) } else { Err(err) };
                 }
+                if self.loose_name_differing_in_case(name)?.is_some() {
+                    return Ok(None);
+                }
                 Ok(buf.into())
             }
             Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
             #[cfg(windows)]
             Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => Ok(None),
+            // A reference whose name is a directory of `name` exists, like `refs/heads/a` for `refs/heads/a/b`.
+            Err(_)
+                if ref_path
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|path| *path != base)
+                    .any(Path::is_file) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Return `true` if the file of the loose reference `name` exists, without reading it.
+    ///
+    /// Just like with [`ref_contents()`](Self::ref_contents()), files that belong to a reference whose name only differs
+    /// in case are treated as if they didn't exist.
+    pub(crate) fn loose_ref_exists(&self, name: &FullNameRef) -> io::Result<bool> {
+        let (base, relative_path) = self.reference_path_with_base(name);
+        self.assure_valid_loose_path(name, &relative_path)?;
+        match std::fs::metadata(base.join(relative_path)) {
+            Ok(meta) if meta.is_file() => Ok(self.loose_name_differing_in_case(name)?.is_none()),
+            Ok(_) => Ok(false),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            #[cfg(windows)]
+            Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => Ok(false),
             Err(err) => Err(err),
         }
     }
+
+    fn assure_valid_loose_path(&self, name: &FullNameRef, relative_path: &Path) -> io::Result<()> {
+        if self.prohibit_windows_device_names {
+            let options = gix_validate::path::component::Options {
+                protect_windows: true,
+                protect_hfs: false,
+                protect_ntfs: true,
+            };
+            for component in relative_path
+                .components()
+                .filter_map(|c| gix_path::try_os_str_into_bstr(c.as_os_str().into()).ok())
+            {
+                if gix_validate::path::component_is_windows_device(component.as_ref()) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("Illegal use of reserved Windows device name in \"{}\"", name.as_bstr()),
+                    ));
+                }
+                // Refnames may contain characters that are illegal on Windows, or end in dots or spaces
+                // which Windows would silently strip, making distinct references alias each other.
+                if gix_validate::path::component(component.as_ref(), None, options).is_err() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!(
+                            "Illegal characters or trailing dots or spaces for use on Windows in \"{}\"",
+                            name.as_bstr()
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 ///
@@ -0,0 +1,103 @@
+use gix_hash::ObjectId;
+use gix_lock::acquire::Fail;
+use gix_object::bstr::BString;
+
+use crate::{
+    file,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Category, FullNameRef, Target,
+};
+
+/// The error returned by [`file::Store::detach_head()`] and [`file::Store::attach_head()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("HEAD could not be found")]
+    FindHead(#[from] file::find::existing::Error),
+    #[error("HEAD can only be attached to local branches, got {name:?}")]
+    NotABranch { name: BString },
+    #[error(transparent)]
+    Prepare(#[from] file::transaction::prepare::Error),
+    #[error(transparent)]
+    Commit(#[from] file::transaction::commit::Error),
+}
+
+impl file::Store {
+    /// Detach `HEAD` by pointing it to `commit` directly, and log the change as `checkout: moving from <previous> to <commit>`
+    /// with `committer` just like `git checkout --detach` would.
+    ///
+    /// Note that `commit` isn't validated, and that only the reference is changed while the worktree and index remain untouched.
+    /// `HEAD` must still have the value it had when this method started, and the performed edits are returned.
+    pub fn detach_head<'a>(
+        &self,
+        commit: ObjectId,
+        committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+        lock_fail_mode: Fail,
+    ) -> Result<Vec<RefEdit>, Error> {
+        self.set_head(
+            Target::Object(commit),
+            commit.to_string().into(),
+            committer.into(),
+            lock_fail_mode,
+        )
+    }
+
+    /// Attach `HEAD` to the local `branch` by making it a symbolic reference to it, and log the change as
+    /// `checkout: moving from <previous> to <branch>` with `committer` just like `git checkout <branch>` would.
+    ///
+    /// `branch` doesn't have to exist, which makes it unborn, and only the reference is changed while the worktree and
+    /// index remain untouched.
+    /// `HEAD` must still have the value it had when this method started, and the performed edits are returned.
+    pub fn attach_head<'a>(
+        &self,
+        branch: &FullNameRef,
+        committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+        lock_fail_mode: Fail,
+    ) -> Result<Vec<RefEdit>, Error> {
+        if branch.category() != Some(Category::LocalBranch) {
+            return Err(Error::NotABranch {
+                name: branch.as_bstr().to_owned(),
+            });
+        }
+        self.set_head(
+            Target::Symbolic(branch.to_owned()),
+            branch.shorten().to_owned(),
+            committer.into(),
+            lock_fail_mode,
+        )
+    }
+
+    fn set_head(
+        &self,
+        new: Target,
+        to: BString,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+        lock_fail_mode: Fail,
+    ) -> Result<Vec<RefEdit>, Error> {
+        let head = self.find("HEAD")?;
+        let from: BString = match &head.target {
+            Target::Symbolic(name) => name.shorten().to_owned(),
+            Target::Object(id) => id.to_string().into(),
+        };
+        Ok(self
+            .transaction()
+            .prepare(
+                Some(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: format!("checkout: moving from {from} to {to}").into(),
+                        },
+                        expected: PreviousValue::MustExistAndMatch(head.target),
+                        new,
+                    },
+                    name: head.name,
+                    deref: false,
+                }),
+                lock_fail_mode,
+                Fail::Immediately,
+            )?
+            .commit(committer)?)
+    }
+}
@@ -77,6 +77,78 @@ impl<'a> Iterator for Forward<'a> {
     }
 }
 
+/// Returns a forward iterator over the lines read from `log`, starting from the first line in the file and ending at the last.
+///
+/// Unlike [`forward()`], lines are read incrementally and yielded as owned [`Line`][crate::log::Line]s,
+/// so the caller doesn't have to keep the entire reflog file in memory to stream it.
+///
+/// It will continue parsing even if individual log entries failed to parse, leaving it to the driver to decide whether to
+/// abort or continue.
+pub fn forward_owned<R>(log: R) -> ForwardOwned<R>
+where
+    R: std::io::BufRead,
+{
+    ForwardOwned {
+        read: Some(log),
+        buf: Vec::new(),
+        count: 0,
+    }
+}
+
+/// An iterator yielding owned parsed lines read from a file from start to end, oldest to newest.
+pub struct ForwardOwned<R> {
+    read: Option<R>,
+    buf: Vec<u8>,
+    count: usize,
+}
+
+///
+pub mod forward_owned {
+    use super::decode;
+
+    /// The error returned by the [`ForwardOwned`][super::ForwardOwned] iterator
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The next line could not be read")]
+        Io(#[from] std::io::Error),
+        #[error("Could not decode log line")]
+        Decode(#[from] decode::Error),
+    }
+}
+
+impl<R> Iterator for ForwardOwned<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = Result<crate::log::Line, forward_owned::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let read = self.read.as_mut()?;
+        self.buf.clear();
+        match read.read_until(b'\n', &mut self.buf) {
+            Ok(0) => {
+                self.read = None;
+                return None;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                self.read = None;
+                return Some(Err(err.into()));
+            }
+        }
+        let line = self
+            .buf
+            .strip_suffix(b"\n")
+            .map_or(self.buf.as_slice(), |line| line.strip_suffix(b"\r").unwrap_or(line));
+        let res = log::LineRef::from_bytes(line)
+            .map(Into::into)
+            .map_err(|err| decode::Error::new(err, LineNumber::FromStart(self.count)).into());
+        self.count += 1;
+        Some(res)
+    }
+}
+
 /// A platform to store a buffer to hold ref log lines for iteration.
 #[must_use = "Iterators should be obtained from this platform"]
 pub struct Platform<'a, 's> {
@@ -103,6 +175,11 @@ impl<'a, 's> Platform<'a, 's> {
         self.buf.clear();
         self.store.reflog_iter(self.name, &mut self.buf).map_err(must_be_io_err)
     }
+
+    /// Return a forward iterator over all log-lines, oldest to most recent, which reads lines incrementally and yields them owned.
+    pub fn all_owned(&self) -> std::io::Result<Option<log::iter::ForwardOwned<std::io::BufReader<std::fs::File>>>> {
+        self.store.reflog_iter_owned(self.name).map_err(must_be_io_err)
+    }
 }
 
 /// An iterator yielding parsed lines in a file in reverse, most recent to oldest.
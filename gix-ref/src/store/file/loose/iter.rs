@@ -28,6 +28,15 @@ impl SortedLoosePaths {
             }),
         }
     }
+
+    /// An iterator that doesn't yield any paths.
+    pub fn empty(base: PathBuf) -> Self {
+        SortedLoosePaths {
+            base,
+            filename_prefix: None,
+            file_walk: None,
+        }
+    }
 }
 
 impl Iterator for SortedLoosePaths {
@@ -27,6 +27,8 @@ pub mod reference;
 mod init {
     use std::path::PathBuf;
 
+    use gix_object::bstr::ByteSlice;
+
     use crate::store_impl::file;
 
     impl file::Store {
@@ -42,7 +44,9 @@ mod init {
                 write_reflog,
                 object_hash,
                 precompose_unicode,
+                ignore_case,
                 prohibit_windows_device_names,
+                fsync,
             }: crate::store::init::Options,
         ) -> Self {
             file::Store {
@@ -51,10 +55,13 @@ mod init {
                 common_dir: None,
                 write_reflog,
                 namespace: None,
+                committer: None,
                 prohibit_windows_device_names,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
                 precompose_unicode,
+                ignore_case,
+                fsync,
             }
         }
 
@@ -70,7 +77,9 @@ mod init {
                 write_reflog,
                 object_hash,
                 precompose_unicode,
+                ignore_case,
                 prohibit_windows_device_names,
+                fsync,
             }: crate::store::init::Options,
         ) -> Self {
             file::Store {
@@ -79,12 +88,35 @@ mod init {
                 common_dir: Some(common_dir),
                 write_reflog,
                 namespace: None,
+                committer: None,
                 prohibit_windows_device_names,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
                 precompose_unicode,
+                ignore_case,
+                fsync,
             }
         }
+
+        /// Like [`at()`][file::Store::at()], but if `git_dir` contains a `commondir` file like the private directory of
+        /// _linked_ work-trees does, use [`for_linked_worktree()`][file::Store::for_linked_worktree()] with the directory
+        /// it points to as `common_dir`, so shared references are found there.
+        ///
+        /// A relative path in the `commondir` file is relative to `git_dir`, and the only errors are those of reading it.
+        pub fn at_resolving_common_dir(
+            git_dir: PathBuf,
+            options: crate::store::init::Options,
+        ) -> std::io::Result<Self> {
+            let content = match std::fs::read(git_dir.join("commondir")) {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::at(git_dir, options)),
+                Err(err) => return Err(err),
+            };
+            let common_dir = gix_path::try_from_byte_slice(content.trim())
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            let common_dir = git_dir.join(common_dir);
+            Ok(Self::for_linked_worktree(git_dir, common_dir, options))
+        }
     }
 
     fn packed_refs_mmap_threshold() -> u64 {
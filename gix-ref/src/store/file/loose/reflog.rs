@@ -73,6 +73,33 @@ impl file::Store {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Return a reflog forward iterator for the given fully qualified `name` which reads the file incrementally
+    /// and yields owned lines.
+    ///
+    /// The iterator will traverse log entries from oldest to newest without the need for a caller-provided buffer.
+    /// Return `Ok(None)` if no reflog exists.
+    pub fn reflog_iter_owned<'a, Name, E>(
+        &self,
+        name: Name,
+    ) -> Result<Option<log::iter::ForwardOwned<std::io::BufReader<std::fs::File>>>, Error>
+    where
+        Name: TryInto<&'a FullNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        let path = self.reflog_path(name);
+        if path.is_dir() {
+            return Ok(None);
+        }
+        match std::fs::File::open(&path) {
+            Ok(file) => Ok(Some(log::iter::forward_owned(std::io::BufReader::new(file)))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            #[cfg(windows)]
+            Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
 }
 
 impl file::Store {
@@ -161,6 +188,7 @@ pub mod create_or_update {
                                     writeln!(file)
                                 }
                             })
+                            .and_then(|_| self.fsync.map_or(Ok(()), |method| gix_fs::fsync::file(&file, method)))
                             .map_err(|err| Error::Append {
                                 source: err,
                                 reflog_path: self.reflog_path(name),
@@ -3,7 +3,11 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{bstr::BStr, store::WriteReflog, Namespace};
+use crate::{
+    bstr::{BStr, BString},
+    store::WriteReflog,
+    Namespace,
+};
 
 /// A store for reference which uses plain files.
 ///
@@ -27,8 +31,12 @@ pub struct Store {
     pub write_reflog: WriteReflog,
     /// The namespace to use for edits and reads
     pub namespace: Option<Namespace>,
+    /// The identity to record in reference logs if a [transaction is committed](Transaction::commit()) without committer.
+    pub committer: Option<Committer>,
     /// This is only needed on Windows, where some device names are reserved at any level of a path, so that
     /// reading or writing `refs/heads/CON` for example would read from the console, or write to it.
+    /// Similarly, references with characters that are illegal in Windows paths, like `refs/heads/a|b`, or with path
+    /// components that end in dots or spaces, like `refs/heads/a./b`, are refused as Windows would alter their names.
     pub prohibit_windows_device_names: bool,
     /// If set, we will convert decomposed unicode like `a\u308` into precomposed unicode like `ä` when reading
     /// ref names from disk.
@@ -37,6 +45,18 @@ pub struct Store {
     /// Iterated references will be returned verbatim, thus when sending them over the wire they have to be precomposed
     /// as needed.
     pub precompose_unicode: bool,
+    /// If set, the filesystem is assumed to treat names that only differ in case as the same, so `refs/heads/Foo` and
+    /// `refs/heads/foo` would be stored in the same file.
+    ///
+    /// Then loose references are only found if their name on disk matches the name that is looked up, so that a
+    /// reference is never returned under a name that isn't its own, and [iteration](Self::iter()) of directories
+    /// whose name on disk differs only in case yields no loose references.
+    /// Transactions fail if they would change a loose reference with a name that only differs in case, or if
+    /// they would create a reference whose name only differs in case from a packed reference or another
+    /// reference of the same transaction.
+    pub ignore_case: bool,
+    /// How to sync loose references, `packed-refs` and reference logs after writing them, if at all.
+    pub fsync: Option<gix_fs::fsync::Method>,
     /// A packed buffer which can be mapped in one version and shared as such.
     /// It's updated only in one spot, which is prior to reading it based on file stamps.
     /// Doing it like this has the benefit of being able to hand snapshots out to people without blocking others from updating it.
@@ -84,12 +104,44 @@ mod access {
     }
 }
 
+/// A way to obtain the identity to record in reference logs, for use as [`Store::committer`].
+///
+/// It's called each time a transaction is committed without committer, which allows to use the current time for each
+/// reflog entry.
+#[derive(Clone)]
+pub struct Committer(std::sync::Arc<dyn Fn() -> Option<gix_actor::Signature> + Send + Sync>);
+
+impl Committer {
+    /// Obtain the identity from `committer`, which may return `None` if none is available.
+    pub fn new(committer: impl Fn() -> Option<gix_actor::Signature> + Send + Sync + 'static) -> Self {
+        Committer(std::sync::Arc::new(committer))
+    }
+
+    /// Always use `signature`, including its time.
+    pub fn fixed(signature: gix_actor::Signature) -> Self {
+        Self::new(move || Some(signature.clone()))
+    }
+
+    /// Return the identity to record, if available.
+    pub fn signature(&self) -> Option<gix_actor::Signature> {
+        (self.0)()
+    }
+}
+
+impl std::fmt::Debug for Committer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Committer(..)")
+    }
+}
+
 /// A transaction on a file store
 pub struct Transaction<'s, 'p> {
     store: &'s Store,
     packed_transaction: Option<crate::store_impl::packed::Transaction>,
     updates: Option<Vec<transaction::Edit>>,
     packed_refs: transaction::PackedRefs<'p>,
+    default_log_message: Option<BString>,
+    hook: Option<transaction::hook::Hook<'p>>,
 }
 
 pub(in crate::store_impl::file) fn path_to_name<'a>(path: impl Into<Cow<'a, Path>>) -> Cow<'a, BStr> {
@@ -97,6 +149,56 @@ pub(in crate::store_impl::file) fn path_to_name<'a>(path: impl Into<Cow<'a, Path
     gix_path::to_unix_separators_on_windows(path)
 }
 
+/// Return the longest existing leading portion of `relative_path` as it is named on disk below `base` if its name only
+/// differs in case, or `None` if it exists under exactly this name or doesn't exist at all.
+/// Names on disk are precomposed before comparing them if `precompose_unicode` is set.
+///
+/// This is useful on case-insensitive filesystems, where `relative_path` would access a file or directory that belongs
+/// to another name.
+pub(in crate::store_impl::file) fn name_on_disk_if_case_differs(
+    base: &Path,
+    relative_path: &Path,
+    precompose_unicode: bool,
+) -> std::io::Result<Option<PathBuf>> {
+    use gix_object::bstr::ByteSlice;
+    let fold_case = |name: &std::ffi::OsStr| gix_path::os_str_into_bstr(name).ok().map(|name| name.to_lowercase());
+
+    let mut name_on_disk = PathBuf::new();
+    let mut differs = false;
+    for component in relative_path.components() {
+        let component = component.as_os_str();
+        let dir = base.join(&name_on_disk);
+        if !dir.is_dir() {
+            break;
+        }
+        let folded_component = fold_case(component);
+        let mut same_but_case = None;
+        let mut exists = false;
+        for entry in std::fs::read_dir(dir)? {
+            let mut entry_name = entry?.file_name();
+            if precompose_unicode {
+                entry_name = gix_utils::str::precompose_os_string(entry_name.into()).into_owned();
+            }
+            if entry_name == component {
+                exists = true;
+                break;
+            }
+            if same_but_case.is_none() && folded_component.is_some() && fold_case(&entry_name) == folded_component {
+                same_but_case = Some(entry_name);
+            }
+        }
+        match same_but_case {
+            Some(entry_name) if !exists => {
+                name_on_disk.push(entry_name);
+                differs = true;
+            }
+            _ if exists => name_on_disk.push(component),
+            _ => break,
+        }
+    }
+    Ok(differs.then_some(name_on_disk))
+}
+
 ///
 pub mod loose;
 mod overlay_iter;
@@ -123,5 +225,24 @@ pub mod transaction;
 ///
 pub mod packed;
 
+///
+pub mod pack_refs;
+
+///
+pub mod reflog_expire;
+
+///
+pub mod verify;
+
+///
+pub mod pseudo;
+
+///
+#[cfg(feature = "watch")]
+pub mod watch;
+
+///
+pub mod head;
+
 mod raw_ext;
 pub use raw_ext::ReferenceExt;
@@ -9,17 +9,20 @@ use std::{
 use crate::{
     file::{loose, loose::iter::SortedLoosePaths, path_to_name},
     store_impl::{file, packed},
-    BString, FullName, Namespace, Reference,
+    BStr, BString, FullName, Namespace, Reference,
 };
+use gix_object::bstr::ByteSlice;
 
 /// An iterator stepping through sorted input of loose references and packed references, preferring loose refs over otherwise
 /// equivalent packed references.
 ///
 /// All errors will be returned verbatim, while packed errors are depleted first if loose refs also error.
 pub struct LooseThenPacked<'p, 's> {
-    git_dir: &'s Path,
+    git_dir: Cow<'s, Path>,
     common_dir: Option<&'s Path>,
     namespace: Option<&'s Namespace>,
+    /// A prefix like `main-worktree/` to prepend to all names if references of another worktree are iterated.
+    worktree_prefix: Option<BString>,
     iter_packed: Option<Peekable<packed::Iter<'p>>>,
     iter_git_dir: Peekable<SortedLoosePaths>,
     #[allow(dead_code)]
@@ -45,6 +48,9 @@ impl<'p, 's> LooseThenPacked<'p, 's> {
         if let Some(namespace) = &self.namespace {
             r.strip_namespace(namespace);
         }
+        if let Some(prefix) = &self.worktree_prefix {
+            r.name.0.splice(0..0, prefix.iter().copied());
+        }
         r
     }
 
@@ -95,7 +101,7 @@ impl<'p, 's> LooseThenPacked<'p, 's> {
         loose::Reference::try_from_path(name, &self.buf)
             .map_err(|err| {
                 let relative_path = refpath
-                    .strip_prefix(self.git_dir)
+                    .strip_prefix(&self.git_dir)
                     .ok()
                     .or_else(|| {
                         self.common_dir
@@ -105,6 +111,7 @@ impl<'p, 's> LooseThenPacked<'p, 's> {
                 Error::ReferenceCreation {
                     source: err,
                     relative_path: relative_path.into(),
+                    content: self.buf.as_bstr().into(),
                 }
             })
             .map(Into::into)
@@ -191,13 +198,17 @@ impl<'s> Platform<'s> {
     /// Return an iterator over all references, loose or `packed`, sorted by their name.
     ///
     /// Errors are returned similarly to what would happen when loose and packed refs where iterated by themselves.
+    /// Broken references don't stop the iteration, and are returned as [error](Error::ReferenceCreation) along with
+    /// their path and content.
     pub fn all(&self) -> std::io::Result<LooseThenPacked<'_, '_>> {
         self.store.iter_packed(self.packed.as_ref().map(|b| &***b))
     }
 
     /// As [`iter(…)`][file::Store::iter()], but filters by `prefix`, i.e. "refs/heads".
     ///
-    /// Please note that "refs/heads" or "refs\\heads" is equivalent to "refs/heads/"
+    /// Please note that "refs/heads" or "refs\\heads" is equivalent to "refs/heads/".
+    /// Prefixes like `main-worktree/refs/bisect` or `worktrees/<name>/refs/bisect` iterate the references as seen by the
+    /// respective worktree, with names that retain the prefix.
     pub fn prefixed(&self, prefix: &Path) -> std::io::Result<LooseThenPacked<'_, '_>> {
         self.store
             .iter_prefixed_packed(prefix, self.packed.as_ref().map(|b| &***b))
@@ -248,6 +259,14 @@ pub(crate) enum IterInfo<'a> {
         /// If `true`, we will convert decomposed into precomposed unicode.
         precompose_unicode: bool,
     },
+    /// The directory to iterate exists under a name that only differs in case from `prefix` on a case-insensitive
+    /// filesystem, so it doesn't contain any loose references with that prefix.
+    CaseMismatch {
+        /// The top-level directory as boundary of all references
+        base: &'a Path,
+        /// The original prefix
+        prefix: Cow<'a, Path>,
+    },
 }
 
 impl<'a> IterInfo<'a> {
@@ -255,9 +274,9 @@ impl<'a> IterInfo<'a> {
         match self {
             IterInfo::Base { .. } => None,
             IterInfo::PrefixAndBase { prefix, .. } => Some(*prefix),
-            IterInfo::ComputedIterationRoot { prefix, .. } | IterInfo::BaseAndIterRoot { prefix, .. } => {
-                prefix.as_ref().into()
-            }
+            IterInfo::ComputedIterationRoot { prefix, .. }
+            | IterInfo::BaseAndIterRoot { prefix, .. }
+            | IterInfo::CaseMismatch { prefix, .. } => prefix.as_ref().into(),
         }
     }
 
@@ -285,11 +304,19 @@ impl<'a> IterInfo<'a> {
                 remainder,
                 precompose_unicode,
             } => SortedLoosePaths::at(&iter_root, base.into(), remainder, precompose_unicode),
+            IterInfo::CaseMismatch { base, prefix: _ } => SortedLoosePaths::empty(base.into()),
         }
         .peekable()
     }
 
-    fn from_prefix(base: &'a Path, prefix: Cow<'a, Path>, precompose_unicode: bool) -> std::io::Result<Self> {
+    /// Note that `ignore_case` should be set on case-insensitive filesystems, to avoid iterating a directory
+    /// whose name only differs in case from `prefix`.
+    fn from_prefix(
+        base: &'a Path,
+        prefix: Cow<'a, Path>,
+        precompose_unicode: bool,
+        ignore_case: bool,
+    ) -> std::io::Result<Self> {
         if prefix.is_absolute() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
@@ -304,6 +331,16 @@ impl<'a> IterInfo<'a> {
             ));
         }
         let iter_root = base.join(prefix.as_ref());
+        if ignore_case {
+            let existing_dir = if iter_root.is_dir() {
+                prefix.as_ref()
+            } else {
+                prefix.parent().unwrap_or(prefix.as_ref())
+            };
+            if super::name_on_disk_if_case_differs(base, existing_dir, precompose_unicode)?.is_some() {
+                return Ok(IterInfo::CaseMismatch { base, prefix });
+            }
+        }
         if iter_root.is_dir() {
             Ok(IterInfo::BaseAndIterRoot {
                 base,
@@ -376,34 +413,114 @@ impl file::Store {
 
     /// As [`iter(…)`][file::Store::iter()], but filters by `prefix`, i.e. "refs/heads".
     ///
-    /// Please note that "refs/heads" or "refs\\heads" is equivalent to "refs/heads/"
+    /// Please note that "refs/heads" or "refs\\heads" is equivalent to "refs/heads/".
+    /// Prefixes like `main-worktree/refs/bisect` or `worktrees/<name>/refs/bisect` iterate the references as seen by the
+    /// respective worktree, with names that retain the prefix.
     pub fn iter_prefixed_packed<'s, 'p>(
         &'s self,
         prefix: &Path,
         packed: Option<&'p packed::Buffer>,
     ) -> std::io::Result<LooseThenPacked<'p, 's>> {
+        if let Some(worktree) = self.worktree_prefix(prefix)? {
+            return self.iter_worktree_prefixed_packed(worktree, packed);
+        }
         match self.namespace.as_ref() {
             None => {
-                let git_dir_info = IterInfo::from_prefix(self.git_dir(), prefix.into(), self.precompose_unicode)?;
+                let git_dir_info =
+                    IterInfo::from_prefix(self.git_dir(), prefix.into(), self.precompose_unicode, self.ignore_case)?;
                 let common_dir_info = self
                     .common_dir()
-                    .map(|base| IterInfo::from_prefix(base, prefix.into(), self.precompose_unicode))
+                    .map(|base| IterInfo::from_prefix(base, prefix.into(), self.precompose_unicode, self.ignore_case))
                     .transpose()?;
                 self.iter_from_info(git_dir_info, common_dir_info, packed)
             }
             Some(namespace) => {
                 let prefix = namespace.to_owned().into_namespaced_prefix(prefix);
-                let git_dir_info =
-                    IterInfo::from_prefix(self.git_dir(), prefix.clone().into(), self.precompose_unicode)?;
+                let git_dir_info = IterInfo::from_prefix(
+                    self.git_dir(),
+                    prefix.clone().into(),
+                    self.precompose_unicode,
+                    self.ignore_case,
+                )?;
                 let common_dir_info = self
                     .common_dir()
-                    .map(|base| IterInfo::from_prefix(base, prefix.into(), self.precompose_unicode))
+                    .map(|base| IterInfo::from_prefix(base, prefix.into(), self.precompose_unicode, self.ignore_case))
                     .transpose()?;
                 self.iter_from_info(git_dir_info, common_dir_info, packed)
             }
         }
     }
 
+    /// Iterate the references of the worktree described by `worktree`, as if its own reference store was used.
+    fn iter_worktree_prefixed_packed<'s, 'p>(
+        &'s self,
+        worktree: WorktreePrefix,
+        packed: Option<&'p packed::Buffer>,
+    ) -> std::io::Result<LooseThenPacked<'p, 's>> {
+        let prefix = match (self.namespace.as_ref(), worktree.prefix) {
+            (None, None) => None,
+            (Some(namespace), None) => Some(namespace.to_path().to_owned()),
+            (Some(namespace), Some(prefix)) => Some(namespace.to_owned().into_namespaced_prefix(&prefix)),
+            (None, Some(prefix)) => Some(prefix),
+        };
+        let info = |base| match &prefix {
+            Some(prefix) => {
+                IterInfo::from_prefix(base, prefix.as_path().into(), self.precompose_unicode, self.ignore_case)
+            }
+            None => Ok(IterInfo::Base {
+                base,
+                precompose_unicode: self.precompose_unicode,
+            }),
+        };
+        let common_dir = self.common_dir_resolved();
+        let (git_dir, common_dir): (Cow<'s, Path>, _) = match worktree.git_dir {
+            Some(git_dir) => (git_dir.into(), Some(common_dir)),
+            None => (common_dir.into(), None),
+        };
+        let mut iter = self.iter_from_info(info(&git_dir)?, common_dir.map(info).transpose()?, packed)?;
+        iter.git_dir = git_dir;
+        iter.common_dir = common_dir;
+        iter.worktree_prefix = Some(worktree.name_prefix);
+        Ok(iter)
+    }
+
+    /// Return information about the worktree referred to by `prefix` if it starts with `main-worktree/` or `worktrees/<name>/`.
+    fn worktree_prefix(&self, prefix: &Path) -> std::io::Result<Option<WorktreePrefix>> {
+        let prefix = path_to_name(prefix);
+        let (name_prefix, git_dir, rest) = if let Some(rest) = strip_component(prefix.as_ref(), b"main-worktree") {
+            (BString::from("main-worktree/"), None, rest)
+        } else if let Some(rest) = strip_component(prefix.as_ref(), b"worktrees") {
+            let (name, rest) = match rest.find_byte(b'/') {
+                Some(pos) => (rest[..pos].as_bstr(), rest[pos + 1..].as_bstr()),
+                None => (rest, BStr::new(b"")),
+            };
+            if name.is_empty() {
+                return Ok(None);
+            }
+            if name == "." || name == ".." {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Refusing to handle worktree names that are relative path components",
+                ));
+            }
+            let mut name_prefix = BString::from("worktrees/");
+            name_prefix.extend_from_slice(name);
+            name_prefix.push(b'/');
+            let git_dir = self
+                .common_dir_resolved()
+                .join("worktrees")
+                .join(gix_path::from_bstr(name));
+            (name_prefix, Some(git_dir), rest)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(WorktreePrefix {
+            name_prefix,
+            git_dir,
+            prefix: (!rest.is_empty()).then(|| gix_path::from_bstr(rest).into_owned()),
+        }))
+    }
+
     fn iter_from_info<'s, 'p>(
         &'s self,
         git_dir_info: IterInfo<'_>,
@@ -411,8 +528,9 @@ impl file::Store {
         packed: Option<&'p packed::Buffer>,
     ) -> std::io::Result<LooseThenPacked<'p, 's>> {
         Ok(LooseThenPacked {
-            git_dir: self.git_dir(),
+            git_dir: self.git_dir().into(),
             common_dir: self.common_dir(),
+            worktree_prefix: None,
             iter_packed: match packed {
                 Some(packed) => Some(
                     match git_dir_info.prefix() {
@@ -432,6 +550,26 @@ impl file::Store {
     }
 }
 
+/// A parsed prefix referring to the references of a specific worktree.
+struct WorktreePrefix {
+    /// The prefix to prepend to the name of each reference, like `main-worktree/` or `worktrees/<name>/`.
+    name_prefix: BString,
+    /// The private git directory of a linked worktree, or `None` for the main worktree.
+    git_dir: Option<PathBuf>,
+    /// The prefix to use within the worktree, or `None` to iterate all of its references.
+    prefix: Option<PathBuf>,
+}
+
+/// Return what follows `component` in `name`, if `name` is `component` or starts with `component/`.
+fn strip_component<'a>(name: &'a BStr, component: &[u8]) -> Option<&'a BStr> {
+    let rest = name.strip_prefix(component)?;
+    match rest.split_first() {
+        None => Some(BStr::new(b"")),
+        Some((b'/', rest)) => Some(rest.as_bstr()),
+        Some(_) => None,
+    }
+}
+
 mod error {
     use std::{io, path::PathBuf};
 
@@ -447,10 +585,13 @@ mod error {
         Traversal(#[source] io::Error),
         #[error("The ref file {path:?} could not be read in full")]
         ReadFileContents { source: io::Error, path: PathBuf },
+        /// A loose reference that couldn't be parsed, which doesn't stop the iteration so that all broken references can be
+        /// reported along with their `content`.
         #[error("The reference at \"{relative_path}\" could not be instantiated")]
         ReferenceCreation {
             source: file::loose::reference::decode::Error,
             relative_path: PathBuf,
+            content: BString,
         },
         #[error("Invalid reference in line {line_number}: {invalid_line:?}")]
         PackedReference { invalid_line: BString, line_number: usize },
@@ -0,0 +1,119 @@
+use gix_lock::acquire::Fail;
+
+use crate::{
+    file::{self, transaction::PackedRefs},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Category, Target,
+};
+
+/// Options for use in [`file::Store::pack_refs()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// If `true`, all loose references will be packed. Otherwise only tags and the references that are already present in
+    /// the `packed-refs` file are packed, just like `git pack-refs` does by default, as branches tend to change
+    /// frequently while tags usually don't.
+    ///
+    /// This is equivalent to `git pack-refs --all`.
+    pub all: bool,
+    /// If `true`, the loose references will be deleted after they were written to the `packed-refs` file, leaving only their
+    /// packed copy.
+    ///
+    /// This is equivalent to `git pack-refs --prune`, which is the default.
+    pub prune: bool,
+    /// How to handle the case of loose references or the `packed-refs` file being locked by another process.
+    pub lock_fail_mode: Fail,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            all: false,
+            prune: true,
+            lock_fail_mode: Fail::Immediately,
+        }
+    }
+}
+
+/// The error returned by [`file::Store::pack_refs()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not iterate loose references")]
+    Iter(#[from] std::io::Error),
+    #[error("The existing packed-refs file could not be opened")]
+    OpenPacked(#[from] crate::packed::buffer::open::Error),
+    #[error("A loose reference could not be looked up in the packed-refs file")]
+    FindPacked(#[from] crate::packed::find::Error),
+    #[error(transparent)]
+    Prepare(#[from] file::transaction::prepare::Error),
+    #[error(transparent)]
+    Commit(#[from] file::transaction::commit::Error),
+}
+
+impl file::Store {
+    /// Move loose references into the `packed-refs` file as configured by `options`, while using `objects` to find the objects
+    /// they point to to be able to peel them.
+    ///
+    /// Return the edits that were performed, which will be empty if there was nothing to pack.
+    ///
+    /// Symbolic references, references which can't be parsed and references that are private to a worktree are never packed.
+    /// All references are locked while they are written into the `packed-refs` file, so the loose files are only removed
+    /// if they weren't changed in the meantime. With the default `options` this is equivalent to `git pack-refs`,
+    /// and with `options.all` set it is equivalent to `git pack-refs --all`.
+    pub fn pack_refs<'p>(
+        &self,
+        objects: impl gix_object::Find + 'p,
+        Options {
+            all,
+            prune,
+            lock_fail_mode,
+        }: Options,
+    ) -> Result<Vec<RefEdit>, Error> {
+        let packed = self.cached_packed_buffer()?;
+        let mut edits = Vec::new();
+        for r in self.loose_iter()?.filter_map(Result::ok) {
+            let category = r.name.category();
+            if !matches!(r.target, Target::Object(_)) || matches!(category, Some(c) if c.is_worktree_private()) {
+                continue;
+            }
+            if !all && category != Some(Category::Tag) {
+                let full_name = match &self.namespace {
+                    Some(namespace) => namespace.clone().into_namespaced_name(r.name.as_ref()),
+                    None => r.name.clone(),
+                };
+                let is_packed = match packed.as_ref() {
+                    Some(packed) => packed.try_find(full_name.as_ref())?.is_some(),
+                    None => false,
+                };
+                if !is_packed {
+                    continue;
+                }
+            }
+            edits.push(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "pack-refs".into(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(r.target.clone()),
+                    new: r.target,
+                },
+                name: r.name,
+                deref: false,
+            });
+        }
+        drop(packed);
+
+        let objects = Box::new(objects);
+        Ok(self
+            .transaction()
+            .packed_refs(if prune {
+                PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(objects)
+            } else {
+                PackedRefs::DeletionsAndNonSymbolicUpdates(objects)
+            })
+            .prepare(edits, lock_fail_mode, lock_fail_mode)?
+            .commit(None)?)
+    }
+}
@@ -0,0 +1,85 @@
+use std::io;
+
+use gix_hash::ObjectId;
+use gix_object::bstr::{BString, ByteSlice};
+
+/// A line of `FETCH_HEAD`, describing an object that was fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// The object that was fetched.
+    pub id: ObjectId,
+    /// If `true`, the object is merged by `git pull`, otherwise it's marked as `not-for-merge`.
+    pub for_merge: bool,
+    /// Where the object came from, like `branch 'main' of https://example.com/repo`.
+    pub description: BString,
+}
+
+const NOT_FOR_MERGE: &[u8] = b"not-for-merge";
+
+///
+pub mod decode {
+    use gix_object::bstr::BString;
+
+    /// The error returned by [`decode()`](super::decode()).
+    #[derive(Debug, thiserror::Error)]
+    #[error("Line {line_number} of FETCH_HEAD could not be parsed: {line:?}")]
+    pub struct Error {
+        /// The line that couldn't be parsed.
+        pub line: BString,
+        /// The number of the line, starting at 1.
+        pub line_number: usize,
+    }
+}
+
+/// Parse all lines of a `FETCH_HEAD` file from `data`, which have the form `<id>\t[not-for-merge]\t<description>`.
+///
+/// Empty lines are ignored.
+pub fn decode(data: &[u8]) -> Result<Vec<Entry>, decode::Error> {
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(idx, line)| {
+            Entry::from_line(line).ok_or_else(|| decode::Error {
+                line: line.into(),
+                line_number: idx + 1,
+            })
+        })
+        .collect()
+}
+
+impl Entry {
+    fn from_line(line: &[u8]) -> Option<Self> {
+        let mut tokens = line.splitn_str(3, b"\t");
+        let id = ObjectId::from_hex(tokens.next()?).ok()?;
+        let for_merge = match tokens.next()? {
+            b"" => true,
+            NOT_FOR_MERGE => false,
+            _ => return None,
+        };
+        let description = tokens.next()?.into();
+        Some(Entry {
+            id,
+            for_merge,
+            description,
+        })
+    }
+
+    /// Serialize this instance to `out` as line of a `FETCH_HEAD` file.
+    ///
+    /// The description must not contain newlines.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        if self.description.find_byte(b'\n').is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "FETCH_HEAD descriptions must not contain newlines",
+            ));
+        }
+        write!(out, "{}\t", self.id)?;
+        if !self.for_merge {
+            out.write_all(NOT_FOR_MERGE)?;
+        }
+        out.write_all(b"\t")?;
+        out.write_all(&self.description)?;
+        out.write_all(b"\n")
+    }
+}
@@ -0,0 +1,206 @@
+use std::{io::Write, path::PathBuf};
+
+use gix_hash::ObjectId;
+use gix_lock::acquire::Fail;
+use gix_object::bstr::ByteSlice;
+
+use crate::{file, Category, FullNameRef};
+
+///
+pub mod fetch_head;
+
+///
+pub mod read {
+    use gix_object::bstr::BString;
+
+    use crate::file::pseudo::fetch_head;
+
+    /// The error returned by [`file::Store::read_fetch_head()`](crate::file::Store::read_fetch_head())
+    /// and [`file::Store::read_pseudo_ref()`](crate::file::Store::read_pseudo_ref()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("{name:?} isn't a pseudo-ref that contains only object ids, like MERGE_HEAD")]
+        NotAPseudoRef { name: BString },
+        #[error("Could not read {name:?}")]
+        Io { source: std::io::Error, name: BString },
+        #[error("Line {line:?} of {name:?} isn't an object id")]
+        Decode { line: BString, name: BString },
+        #[error(transparent)]
+        FetchHead(#[from] fetch_head::decode::Error),
+    }
+}
+
+///
+pub mod write {
+    use gix_object::bstr::BString;
+
+    /// The error returned by the methods of [`file::Store`](crate::file::Store) that write or remove pseudo-refs.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("{name:?} isn't a pseudo-ref that contains only object ids, like MERGE_HEAD")]
+        NotAPseudoRef { name: BString },
+        #[error("Could not lock {name:?} for writing")]
+        LockAcquire {
+            source: Box<gix_lock::acquire::Error>,
+            name: BString,
+        },
+        #[error("Could not write {name:?}")]
+        Io { source: std::io::Error, name: BString },
+        #[error("Could not move the written {name:?} into place")]
+        LockCommit {
+            source: gix_lock::commit::Error<gix_lock::File>,
+            name: BString,
+        },
+    }
+}
+
+const FETCH_HEAD: &str = "FETCH_HEAD";
+
+/// Pseudo-refs
+///
+/// These are files in the `.git` directory of each worktree which aren't references, but which can be used like them, like
+/// `MERGE_HEAD` with the commits to merge or `FETCH_HEAD` with everything obtained by the last fetch.
+/// As they may contain more than one object id, they aren't written by transactions.
+impl file::Store {
+    /// Read all entries of `FETCH_HEAD`, or return `None` if it doesn't exist.
+    pub fn read_fetch_head(&self) -> Result<Option<Vec<fetch_head::Entry>>, read::Error> {
+        match read_file(self.git_dir.join(FETCH_HEAD), FETCH_HEAD)? {
+            Some(data) => Ok(Some(fetch_head::decode(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replace `FETCH_HEAD` with `entries`, as done by `git fetch`, using `lock_fail_mode` to handle concurrent writers.
+    pub fn write_fetch_head<'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a fetch_head::Entry>,
+        lock_fail_mode: Fail,
+    ) -> Result<(), write::Error> {
+        self.write_file(FETCH_HEAD, lock_fail_mode, |out| {
+            entries.into_iter().try_for_each(|entry| entry.write_to(out))
+        })
+    }
+
+    /// Read all object ids stored in the pseudo-ref `name`, like `MERGE_HEAD` or `CHERRY_PICK_HEAD`,
+    /// or return `None` if it doesn't exist.
+    ///
+    /// Only pseudo-refs that consist of object ids on separate lines are supported, which excludes `HEAD` and `FETCH_HEAD`.
+    pub fn read_pseudo_ref(&self, name: &FullNameRef) -> Result<Option<Vec<ObjectId>>, read::Error> {
+        let name = validated_pseudo_ref(name).ok_or_else(|| read::Error::NotAPseudoRef {
+            name: name.as_bstr().to_owned(),
+        })?;
+        let Some(data) = read_file(self.git_dir.join(name), name)? else {
+            return Ok(None);
+        };
+        data.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                ObjectId::from_hex(line).map_err(|_| read::Error::Decode {
+                    line: line.into(),
+                    name: name.into(),
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// Write `ids` into the pseudo-ref `name`, like `MERGE_HEAD` or `CHERRY_PICK_HEAD`, one per line,
+    /// using `lock_fail_mode` to handle concurrent writers.
+    ///
+    /// Only pseudo-refs that consist of object ids on separate lines are supported, which excludes `HEAD` and `FETCH_HEAD`.
+    pub fn write_pseudo_ref(
+        &self,
+        name: &FullNameRef,
+        ids: impl IntoIterator<Item = ObjectId>,
+        lock_fail_mode: Fail,
+    ) -> Result<(), write::Error> {
+        let name = validated_pseudo_ref(name).ok_or_else(|| write::Error::NotAPseudoRef {
+            name: name.as_bstr().to_owned(),
+        })?;
+        self.write_file(name, lock_fail_mode, |out| {
+            ids.into_iter().try_for_each(|id| writeln!(out, "{id}"))
+        })
+    }
+
+    /// Remove the pseudo-ref `name`, like `MERGE_HEAD` or `FETCH_HEAD`, and return `true` if it existed.
+    ///
+    /// This fails if another process holds a lock on it, which is waited for according to `lock_fail_mode`.
+    pub fn remove_pseudo_ref(&self, name: &FullNameRef, lock_fail_mode: Fail) -> Result<bool, write::Error> {
+        let name = match name.as_bstr().to_str() {
+            Ok(FETCH_HEAD) => FETCH_HEAD,
+            _ => validated_pseudo_ref(name).ok_or_else(|| write::Error::NotAPseudoRef {
+                name: name.as_bstr().to_owned(),
+            })?,
+        };
+        let path = self.git_dir.join(name);
+        let _lock = gix_lock::Marker::acquire_to_hold_resource(&path, lock_fail_mode, None).map_err(|err| {
+            write::Error::LockAcquire {
+                source: err.into(),
+                name: name.into(),
+            }
+        })?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(write::Error::Io {
+                source: err,
+                name: name.into(),
+            }),
+        }
+    }
+
+    fn write_file(
+        &self,
+        name: &str,
+        lock_fail_mode: Fail,
+        write: impl FnOnce(&mut dyn std::io::Write) -> std::io::Result<()>,
+    ) -> Result<(), write::Error> {
+        let mut lock = gix_lock::File::acquire_to_update_resource(self.git_dir.join(name), lock_fail_mode, None)
+            .map_err(|err| write::Error::LockAcquire {
+                source: err.into(),
+                name: name.into(),
+            })?;
+        lock.with_mut(|file| {
+            let mut out = std::io::BufWriter::new(&mut *file);
+            write(&mut out)?;
+            out.flush()?;
+            drop(out);
+            self.fsync.map_or(Ok(()), |method| gix_fs::fsync::file(file, method))
+        })
+        .map_err(|err| write::Error::Io {
+            source: err,
+            name: name.into(),
+        })?;
+        lock.commit().map_err(|err| write::Error::LockCommit {
+            source: err,
+            name: name.into(),
+        })?;
+        self.fsync
+            .map_or(Ok(()), |method| gix_fs::fsync::directory(&self.git_dir, method))
+            .map_err(|err| write::Error::Io {
+                source: err,
+                name: name.into(),
+            })
+    }
+}
+
+/// Return `name` as path relative to the `.git` directory if it's a pseudo-ref other than `HEAD` and `FETCH_HEAD`.
+fn validated_pseudo_ref(name: &FullNameRef) -> Option<&str> {
+    let is_supported = name.category() == Some(Category::PseudoRef)
+        && name.as_bstr().ends_with(b"_HEAD")
+        && name.as_bstr() != FETCH_HEAD;
+    is_supported.then(|| name.as_bstr().to_str().expect("pseudo-refs are ASCII"))
+}
+
+fn read_file(path: PathBuf, name: &str) -> Result<Option<Vec<u8>>, read::Error> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(read::Error::Io {
+            source: err,
+            name: name.into(),
+        }),
+    }
+}
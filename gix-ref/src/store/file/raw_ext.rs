@@ -25,6 +25,9 @@ pub trait ReferenceExt: Sealed {
     ///
     /// This is useful to learn where this reference is ultimately pointing to after following all symbolic
     /// refs and all annotated tags to the first non-tag object.
+    ///
+    /// The result is cached in [`peeled`](Reference::peeled) so subsequent calls don't access objects. Similarly, if
+    /// the reference that is ultimately pointed to is packed along with its peeled object, `objects` isn't used either.
     fn peel_to_id_in_place(
         &mut self,
         store: &file::Store,
@@ -98,39 +101,20 @@ impl ReferenceExt for Reference {
         objects: &dyn gix_object::Find,
         packed: Option<&packed::Buffer>,
     ) -> Result<ObjectId, peel::to_id::Error> {
-        match self.peeled {
-            Some(peeled) => {
-                self.target = Target::Object(peeled.to_owned());
-                Ok(peeled)
-            }
+        let peeled_id = match self.peeled {
+            Some(peeled) => peeled,
             None => {
-                let mut oid = self.follow_to_object_in_place_packed(store, packed)?;
-                let mut buf = Vec::new();
-                let peeled_id = loop {
-                    let gix_object::Data { kind, data } =
-                        objects
-                            .try_find(&oid, &mut buf)?
-                            .ok_or_else(|| peel::to_id::Error::NotFound {
-                                oid,
-                                name: self.name.0.clone(),
-                            })?;
-                    match kind {
-                        gix_object::Kind::Tag => {
-                            oid = gix_object::TagRefIter::from_bytes(data).target_id().map_err(|_err| {
-                                peel::to_id::Error::NotFound {
-                                    oid,
-                                    name: self.name.0.clone(),
-                                }
-                            })?;
-                        }
-                        _ => break oid,
-                    };
-                };
-                self.peeled = Some(peeled_id);
-                self.target = Target::Object(peeled_id);
-                Ok(peeled_id)
+                let oid = self.follow_to_object_in_place_packed(store, packed)?;
+                match self.peeled {
+                    // The symbolic reference led to a packed reference which already knows its peeled object.
+                    Some(peeled) => peeled,
+                    None => peel::to_first_non_tag(oid, self.name.as_ref(), objects)?,
+                }
             }
-        }
+        };
+        self.peeled = Some(peeled_id);
+        self.target = Target::Object(peeled_id);
+        Ok(peeled_id)
     }
 
     fn follow_to_object_in_place_packed(
@@ -0,0 +1,215 @@
+use std::io::Write;
+
+use gix_actor::date::SecondsSinceUnixEpoch;
+use gix_hash::oid;
+use gix_lock::acquire::Fail;
+use gix_object::bstr::ByteSlice;
+
+use crate::{
+    file::{self, loose::iter::SortedLoosePaths},
+    store_impl::file::log::LineRef,
+    FullName, FullNameRef,
+};
+
+/// Options for use in [`file::Store::reflog_expire()`] and [`file::Store::reflog_expire_all()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Remove all entries that are older than the given time, or keep them regardless of their age if `None`.
+    ///
+    /// This is equivalent to `git reflog expire --expire=<time>`.
+    pub expire: Option<SecondsSinceUnixEpoch>,
+    /// Remove all entries that are older than the given time and whose new object isn't reachable from the tip of
+    /// the reference anymore, or keep them regardless of their age if `None`.
+    ///
+    /// This is equivalent to `git reflog expire --expire-unreachable=<time>`.
+    pub expire_unreachable: Option<SecondsSinceUnixEpoch>,
+    /// If `true`, only determine which entries would be removed, but don't change any reflog.
+    pub dry_run: bool,
+    /// How to handle the case of the reference or its reflog being locked by another process.
+    pub lock_fail_mode: Fail,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            expire: None,
+            expire_unreachable: None,
+            dry_run: false,
+            lock_fail_mode: Fail::Immediately,
+        }
+    }
+}
+
+/// The outcome of expiring the entries of a single reflog.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of entries that remain in the reflog.
+    pub kept: usize,
+    /// The amount of entries that were removed, or that would have been removed in a dry-run.
+    pub removed: usize,
+}
+
+/// The error returned by [`file::Store::reflog_expire()`] and [`file::Store::reflog_expire_all()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The reflog name is not a valid ref name")]
+    RefnameValidation(#[from] crate::name::Error),
+    #[error("Could not iterate reflogs")]
+    Iter(#[source] std::io::Error),
+    #[error("Could not read the reflog at {path:?}")]
+    Read {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("Could not lock the reference '{}' or its reflog", name.as_bstr())]
+    Lock {
+        source: Box<gix_lock::acquire::Error>,
+        name: FullName,
+    },
+    #[error("Could not write the reflog at {path:?}")]
+    Write {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+}
+
+impl file::Store {
+    /// Remove the entries of the reflog of the reference `name` as configured by `options`, and return how many entries
+    /// were kept and removed.
+    ///
+    /// `is_reachable(name, id)` is called for entries that are candidates for [`expire_unreachable`](Options::expire_unreachable)
+    /// and returns `true` if `id` can be reached from the tip of the reference `name`.
+    ///
+    /// The reference is locked while its reflog is rewritten, and the new reflog is moved into place atomically.
+    /// Lines that can't be parsed are kept. A reflog which doesn't exist is treated as empty.
+    pub fn reflog_expire<'a, Name, E>(
+        &self,
+        name: Name,
+        options: Options,
+        is_reachable: &mut dyn FnMut(&FullNameRef, &oid) -> bool,
+    ) -> Result<Outcome, Error>
+    where
+        Name: TryInto<&'a FullNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        let name = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        self.reflog_expire_inner(name, options, is_reachable)
+    }
+
+    /// Like [`reflog_expire()`](file::Store::reflog_expire()), but for all reflogs that exist in this store,
+    /// returning the name of each reference along with its outcome.
+    ///
+    /// This is equivalent to `git reflog expire --all`.
+    pub fn reflog_expire_all(
+        &self,
+        options: Options,
+        is_reachable: &mut dyn FnMut(&FullNameRef, &oid) -> bool,
+    ) -> Result<Vec<(FullName, Outcome)>, Error> {
+        let mut names = Vec::new();
+        let git_dir_logs = self.git_dir.join("logs");
+        let common_dir_logs = self.common_dir.as_ref().map(|dir| dir.join("logs"));
+        for logs in std::iter::once(&git_dir_logs).chain(common_dir_logs.as_ref()) {
+            for entry in SortedLoosePaths::at(logs, logs.clone(), None, self.precompose_unicode) {
+                let (path, name) = entry.map_err(Error::Iter)?;
+                let name = match &self.namespace {
+                    Some(namespace) => match name.as_bstr().strip_prefix(namespace.as_bstr().as_bytes()) {
+                        Some(name) => FullName(name.into()),
+                        None => continue,
+                    },
+                    None => name,
+                };
+                // The common directory also contains the logs of references that are private to the main worktree.
+                if self.reflog_path(name.as_ref()) != path {
+                    continue;
+                }
+                names.push(name);
+            }
+        }
+
+        let mut out = Vec::with_capacity(names.len());
+        for name in names {
+            let outcome = self.reflog_expire_inner(name.as_ref(), options, is_reachable)?;
+            out.push((name, outcome));
+        }
+        Ok(out)
+    }
+
+    fn reflog_expire_inner(
+        &self,
+        name: &FullNameRef,
+        Options {
+            expire,
+            expire_unreachable,
+            dry_run,
+            lock_fail_mode,
+        }: Options,
+        is_reachable: &mut dyn FnMut(&FullNameRef, &oid) -> bool,
+    ) -> Result<Outcome, Error> {
+        let lock_err = |source: gix_lock::acquire::Error| Error::Lock {
+            source: source.into(),
+            name: name.to_owned(),
+        };
+        let _reference_lock = if dry_run {
+            None
+        } else {
+            let (base, relative_path) = self.reference_path_with_base(name);
+            Some(
+                gix_lock::Marker::acquire_to_hold_resource(
+                    base.join(relative_path),
+                    lock_fail_mode,
+                    Some(base.into_owned()),
+                )
+                .map_err(lock_err)?,
+            )
+        };
+
+        let path = self.reflog_path(name);
+        let buf = match std::fs::read(&path) {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Outcome::default()),
+            Err(source) => return Err(Error::Read { source, path }),
+        };
+
+        let mut outcome = Outcome::default();
+        let mut kept = Vec::with_capacity(buf.len());
+        for line in buf.lines_with_terminator() {
+            let keep = match LineRef::from_bytes(line.trim_end_with(|c| c == '\n' || c == '\r')) {
+                Ok(entry) => {
+                    let time = entry.signature.time.seconds;
+                    let is_expired = matches!(expire, Some(expire) if time < expire)
+                        || (matches!(expire_unreachable, Some(expire) if time < expire) && {
+                            let id = entry.new_oid();
+                            id.is_null() || !is_reachable(name, &id)
+                        });
+                    !is_expired
+                }
+                Err(_) => true,
+            };
+            if keep {
+                outcome.kept += 1;
+                kept.extend_from_slice(line);
+            } else {
+                outcome.removed += 1;
+            }
+        }
+
+        if dry_run || outcome.removed == 0 {
+            return Ok(outcome);
+        }
+        let mut lock = gix_lock::File::acquire_to_update_resource(&path, lock_fail_mode, None).map_err(lock_err)?;
+        lock.write_all(&kept)
+            .and_then(|_| {
+                self.fsync
+                    .map_or(Ok(()), |method| lock.with_mut(|file| gix_fs::fsync::file(file, method)))
+            })
+            .and_then(|_| lock.commit().map(|_| ()).map_err(|err| err.error))
+            .and_then(|_| {
+                self.fsync.map_or(Ok(()), |method| {
+                    gix_fs::fsync::directory(path.parent().expect("reflogs are in a directory"), method)
+                })
+            })
+            .map_err(|source| Error::Write { source, path })?;
+        Ok(outcome)
+    }
+}
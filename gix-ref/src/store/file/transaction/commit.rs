@@ -1,40 +1,108 @@
+use std::path::PathBuf;
+
 use crate::{
-    store_impl::file::{transaction::PackedRefs, Transaction},
+    store_impl::{
+        file,
+        file::{
+            transaction::{write_target, Origin, PackedRefs},
+            Transaction,
+        },
+    },
     transaction::{Change, LogChange, RefEdit, RefLog},
-    Target,
+    FullNameRef, Target,
 };
 
 impl<'s, 'p> Transaction<'s, 'p> {
     /// Make all [prepared][Transaction::prepare()] permanent and return the performed edits which represent the current
     /// state of the affected refs in the ref store in that instant. Please note that the obtained edits may have been
     /// adjusted to contain more dependent edits or additional information.
-    /// `committer` is used in the reflog and only if the reflog is actually written, which is why it is optional. Please note
-    /// that if `None` is passed and the reflog needs to be written, the operation will be aborted late and a few refs may have been
-    /// successfully committed already, making clear the non-atomic nature of multi-file edits.
+    ///
+    /// The `expected` field of each returned edit is [`MustExistAndMatch(previous)`](PreviousValue::MustExistAndMatch)
+    /// if the reference existed before the transaction, or [`MustNotExist`](PreviousValue::MustNotExist) if it didn't,
+    /// so that [`Change::previous_value()`] returns the actual previous value.
+    /// `committer` is used in the reflog and only if the reflog is actually written, which is why it is optional. If `None`, the
+    /// [committer of the store](file::Store::committer) is used instead. Please note that if no committer is available and the
+    /// reflog needs to be written, the operation will be aborted late and a few refs may have been successfully committed already,
+    /// making clear the non-atomic nature of multi-file edits.
     ///
     /// On error the transaction may have been performed partially, depending on the nature of the error, and no attempt to roll back
     /// partial changes is made.
     ///
     /// In this stage, we perform the following operations:
     ///
+    /// * move the ref log of renamed refs and copy the one of copied refs
     /// * update the ref log
     /// * move updated refs into place
     /// * delete reflogs and empty parent directories
     /// * delete packed refs
     /// * delete their corresponding reference (if applicable)
     ///   along with empty parent directories
+    /// * if the [store syncs files](file::Store::fsync), sync all directories with changed entries
     ///
     /// Note that transactions will be prepared automatically as needed.
     pub fn commit<'a>(self, committer: impl Into<Option<gix_actor::SignatureRef<'a>>>) -> Result<Vec<RefEdit>, Error> {
         self.commit_inner(committer.into())
     }
 
-    fn commit_inner(self, committer: Option<gix_actor::SignatureRef<'_>>) -> Result<Vec<RefEdit>, Error> {
+    fn commit_inner(mut self, committer: Option<gix_actor::SignatureRef<'_>>) -> Result<Vec<RefEdit>, Error> {
+        let hook = self.hook.take();
+        let edits = self.commit_edits(committer);
+        if let Some(mut hook) = hook {
+            if edits.is_ok() {
+                hook.committed();
+            } else {
+                hook.forget();
+            }
+        }
+        edits
+    }
+
+    fn commit_edits(self, committer: Option<gix_actor::SignatureRef<'_>>) -> Result<Vec<RefEdit>, Error> {
+        let store_committer = committer
+            .is_none()
+            .then(|| self.store.committer.as_ref().and_then(file::Committer::signature))
+            .flatten();
+        let committer = committer.or_else(|| store_committer.as_ref().map(gix_actor::Signature::to_ref));
         let mut updates = self.updates.expect("BUG: must call prepare before commit");
         let delete_loose_refs = matches!(
             self.packed_refs,
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
         );
+        // The paths of all files that were created, replaced or removed, if their directories are to be synced.
+        let mut changed_paths = self.store.fsync.map(|_| Vec::<PathBuf>::new());
+        let mut record_change = |path: PathBuf| {
+            if let Some(paths) = changed_paths.as_mut() {
+                paths.push(path);
+            }
+        };
+
+        // Renamed references whose name is a directory of their new name or vice versa are in the way of the new reference,
+        // which is why they are removed first.
+        let mut removed_rename_sources = Vec::new();
+        for idx in 0..updates.len() {
+            let Some(source) = updates[idx].nested_rename_source().cloned() else {
+                continue;
+            };
+            let Some(source_idx) = updates
+                .iter()
+                .position(|edit| edit.update.name == source && matches!(edit.update.change, Change::Delete { .. }))
+            else {
+                continue;
+            };
+            let (base, relative_path) = self.store.reference_path_with_base(source.as_ref());
+            let reference_path = base.join(relative_path.as_ref());
+            record_change(reference_path.clone());
+            if let Err(err) = std::fs::remove_file(&reference_path) {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    return Err(Error::DeleteReference {
+                        err,
+                        full_name: source.as_bstr().to_owned(),
+                    });
+                }
+            }
+            drop(updates[source_idx].lock.take());
+            removed_rename_sources.push(source_idx);
+        }
 
         // Perform updates first so live commits remain referenced
         for change in &mut updates {
@@ -42,7 +110,20 @@ impl<'s, 'p> Transaction<'s, 'p> {
             match &change.update.change {
                 // reflog first, then reference
                 Change::Update { log, new, expected } => {
-                    let lock = change.lock.take();
+                    let mut lock = change.lock.take();
+                    if let Some(origin) = &change.origin {
+                        let (source, keep_source) = match origin {
+                            Origin::Renamed(source) => (source, false),
+                            Origin::Copied(source) => (source, true),
+                        };
+                        transfer_reflog(self.store, source.as_ref(), change.update.name.as_ref(), keep_source)
+                            .map_err(|err| Error::TransferReflog {
+                                source: err,
+                                full_name: change.name(),
+                            })?;
+                        record_change(self.store.reflog_path(source.as_ref()));
+                        record_change(self.store.reflog_path(change.update.name.as_ref()));
+                    }
                     let (update_ref, update_reflog) = match log.mode {
                         RefLog::Only => (false, true),
                         RefLog::AndReference => (true, true),
@@ -50,16 +131,21 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     if update_reflog {
                         let log_update = match new {
                             Target::Symbolic(_) => {
-                                // Special HACK: no reflog for symref changes as there is no OID involved which the reflog needs.
-                                // Unless, the ref is new and we can obtain a peeled id
+                                // Symrefs have no OID of their own, so their log records the peeled ids of the previous and new target.
+                                // If the new target can't be peeled, we can still log if the ref is new and we can obtain a peeled id
                                 // identified by the expectation of what could be there, as is the case when cloning.
-                                match expected {
-                                    PreviousValue::ExistingMustMatch(Target::Object(oid)) => {
+                                match (&change.symbolic_target_reflog_oids, expected) {
+                                    (Some((previous, new)), _) => Some((Some(*previous), new)),
+                                    (None, PreviousValue::ExistingMustMatch(Target::Object(oid))) => {
                                         Some((Some(gix_hash::ObjectId::null(oid.kind())), oid))
                                     }
                                     _ => None,
                                 }
                             }
+                            Target::Object(new_oid) if change.origin.is_some() => {
+                                // Renames and copies keep the value, and git records them as such.
+                                Some((Some(*new_oid), new_oid))
+                            }
                             Target::Object(new_oid) => {
                                 let previous = match expected {
                                     // Here, this means that the ref already existed, and that it will receive (even transitively)
@@ -72,7 +158,10 @@ impl<'s, 'p> Transaction<'s, 'p> {
                             }
                         };
                         if let Some((previous, new_oid)) = log_update {
-                            let do_update = previous.as_ref().map_or(true, |previous| previous != new_oid);
+                            // Retargeting a symref is always worth logging, even if both targets point to the same object.
+                            let do_update = matches!(new, Target::Symbolic(_))
+                                || change.origin.is_some()
+                                || previous.as_ref() != Some(new_oid);
                             if do_update {
                                 self.store.reflog_create_or_append(
                                     change.update.name.as_ref(),
@@ -82,6 +171,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                                     log.message.as_ref(),
                                     log.force_create_reflog,
                                 )?;
+                                record_change(self.store.reflog_path(change.update.name.as_ref()));
                             }
                         }
                     }
@@ -93,6 +183,25 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         continue;
                     }
                     if update_ref {
+                        if lock.is_none() && change.lock_when_committing() {
+                            let (base, relative_path) = self.store.reference_path_with_base(change.update.name.as_ref());
+                            let new_lock = gix_lock::File::acquire_to_update_resource(
+                                base.join(relative_path.as_ref()),
+                                gix_lock::acquire::Fail::Immediately,
+                                Some(base.into_owned()),
+                            )
+                            .map_err(|err| Error::LockAcquire {
+                                source: err.into(),
+                                full_name: change.name(),
+                            })?;
+                            lock = Some(write_target(self.store, new_lock, new).map_err(|err| Error::LockCommit {
+                                source: err,
+                                full_name: change.name(),
+                            })?);
+                        }
+                        if let Some(lock) = &lock {
+                            record_change(lock.resource_path().to_owned());
+                        }
                         if let Some(Err(err)) = lock.map(gix_lock::Marker::commit) {
                             // TODO: when Kind::IsADirectory becomes stable, use that.
                             let err = if err.instance.resource_path().is_dir() {
@@ -114,17 +223,27 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     }
                 }
                 Change::Delete { .. } => {}
+                Change::Rename { .. } | Change::Copy { .. } => {
+                    unreachable!("BUG: renames and copies were split into two edits")
+                }
             }
         }
 
-        for change in &mut updates {
+        for (idx, change) in updates.iter_mut().enumerate() {
+            if removed_rename_sources.contains(&idx) {
+                continue;
+            }
             let (reflog_root, relative_name) = self.store.reflog_base_and_relative_path(change.update.name.as_ref());
             match &change.update.change {
                 Change::Update { .. } => {}
+                Change::Rename { .. } | Change::Copy { .. } => {
+                    unreachable!("BUG: renames and copies were split into two edits")
+                }
                 Change::Delete { .. } => {
                     // Reflog deletion happens first in case it fails a ref without log is less terrible than
                     // a log without a reference.
                     let reflog_path = reflog_root.join(relative_name);
+                    record_change(reflog_path.clone());
                     if let Err(err) = std::fs::remove_file(&reflog_path) {
                         if err.kind() != std::io::ErrorKind::NotFound {
                             return Err(Error::DeleteReflog {
@@ -150,7 +269,10 @@ impl<'s, 'p> Transaction<'s, 'p> {
             self.store.force_refresh_packed_buffer().ok();
         }
 
-        for change in &mut updates {
+        for (idx, change) in updates.iter_mut().enumerate() {
+            if removed_rename_sources.contains(&idx) {
+                continue;
+            }
             let take_lock_and_delete = match &change.update.change {
                 Change::Update {
                     log: LogChange { mode, .. },
@@ -158,10 +280,14 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     ..
                 } => delete_loose_refs && *mode == RefLog::AndReference && matches!(new, Target::Object(_)),
                 Change::Delete { log: mode, .. } => *mode == RefLog::AndReference,
+                Change::Rename { .. } | Change::Copy { .. } => {
+                    unreachable!("BUG: renames and copies were split into two edits")
+                }
             };
             if take_lock_and_delete {
                 let lock = change.lock.take();
                 let reference_path = self.store.reference_path(change.update.name.as_ref());
+                record_change(reference_path.clone());
                 if let Err(err) = std::fs::remove_file(reference_path) {
                     if err.kind() != std::io::ErrorKind::NotFound {
                         return Err(Error::DeleteReference {
@@ -173,9 +299,121 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 drop(lock);
             }
         }
-        Ok(updates.into_iter().map(|edit| edit.update).collect())
+        if let Some((paths, method)) = changed_paths.zip(self.store.fsync) {
+            sync_directories(self.store, paths, method)?;
+        }
+        Ok(into_performed_edits(updates))
+    }
+
+    /// Return the edits exactly as [`commit()`](Self::commit()) would, but without writing anything, which makes this
+    /// a pre-flight check for whether a transaction would succeed, as all expectations are verified and all locks
+    /// are obtained when [preparing](Self::prepare()).
+    ///
+    /// All locks are released afterwards, so the state on disk may change right after calling this method.
+    /// The [hook](Self::hook()) sees the transaction as aborted.
+    pub fn commit_dry_run(mut self) -> Vec<RefEdit> {
+        into_performed_edits(self.updates.take().expect("BUG: must call prepare before commit"))
+    }
+}
+
+fn into_performed_edits(updates: Vec<file::transaction::Edit>) -> Vec<RefEdit> {
+    updates
+        .into_iter()
+        .map(|mut edit| {
+            // Existing references have their previous value set by now, so everything else didn't exist.
+            // Deletions can't expect a reference to not exist, so they are left without any expectation instead,
+            // which allows them to be submitted again.
+            match &mut edit.update.change {
+                Change::Update { expected, .. } if !matches!(expected, PreviousValue::MustExistAndMatch(_)) => {
+                    *expected = PreviousValue::MustNotExist;
+                }
+                Change::Delete { expected, .. } if !matches!(expected, PreviousValue::MustExistAndMatch(_)) => {
+                    *expected = PreviousValue::Any;
+                }
+                _ => {}
+            }
+            edit.update
+        })
+        .collect()
+}
+/// Sync the directories containing `paths`, along with all directories containing them within the repository,
+/// as these might have been created for the changed files.
+/// Directories that don't exist anymore as they were emptied are skipped.
+fn sync_directories(store: &file::Store, paths: Vec<PathBuf>, method: gix_fs::fsync::Method) -> Result<(), Error> {
+    let roots = [store.git_dir(), store.common_dir_resolved()];
+    let mut directories = std::collections::BTreeSet::new();
+    for path in &paths {
+        for directory in path
+            .ancestors()
+            .skip(1)
+            .take_while(|directory| roots.iter().any(|root| directory.starts_with(root)))
+        {
+            if !directories.insert(directory) {
+                break;
+            }
+        }
+    }
+    // Sync nested directories before those containing them.
+    for directory in directories.into_iter().rev() {
+        match gix_fs::fsync::directory(directory, method) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(Error::SyncDirectory {
+                    source: err,
+                    path: directory.to_owned(),
+                })
+            }
+            Ok(()) => {}
+        }
     }
+    Ok(())
 }
+
+/// Move the reflog of `source` to `destination`, or copy it if `keep_source` is set, replacing the one that might exist there already.
+/// If `source` has no reflog, the one of `destination` is removed as it would otherwise be continued.
+fn transfer_reflog(
+    store: &file::Store,
+    source: &FullNameRef,
+    destination: &FullNameRef,
+    keep_source: bool,
+) -> std::io::Result<()> {
+    let (source_root, source_relative) = store.reflog_base_and_relative_path(source);
+    let source_path = source_root.join(source_relative);
+    let (destination_root, destination_relative) = store.reflog_base_and_relative_path(destination);
+    let destination_path = destination_root.join(destination_relative);
+    if !source_path.is_file() {
+        return match std::fs::remove_file(&destination_path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        };
+    }
+    let source_path = if keep_source {
+        source_path
+    } else {
+        // Move the log out of the way first as its name may be a directory of the destination or vice versa, like git does.
+        let tmp_path = source_root.join("refs").join(".tmp-renamed-log");
+        std::fs::create_dir_all(tmp_path.parent().expect("never without parent"))?;
+        std::fs::rename(&source_path, &tmp_path)?;
+        gix_tempfile::remove_dir::empty_upward_until_boundary(
+            source_path.parent().expect("never without parent"),
+            &source_root,
+        )
+        .ok();
+        tmp_path
+    };
+    if destination_path.is_dir() {
+        gix_tempfile::remove_dir::empty_depth_first(destination_path.clone())?;
+    } else if let Some(parent) = destination_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if keep_source {
+        std::fs::copy(&source_path, &destination_path)?;
+    } else {
+        std::fs::rename(&source_path, &destination_path)?;
+    }
+    Ok(())
+}
+
 mod error {
     use gix_object::bstr::BString;
 
@@ -189,14 +427,26 @@ mod error {
         PackedTransactionCommit(#[source] packed::transaction::commit::Error),
         #[error("Edit preprocessing failed with error")]
         PreprocessingFailed { source: std::io::Error },
+        #[error("A lock could not be obtained for reference {full_name:?}")]
+        LockAcquire {
+            source: Box<gix_lock::acquire::Error>,
+            full_name: BString,
+        },
         #[error("The change for reference {full_name:?} could not be committed")]
         LockCommit { source: std::io::Error, full_name: BString },
         #[error("The reference {full_name} could not be deleted")]
         DeleteReference { full_name: BString, err: std::io::Error },
         #[error("The reflog of reference {full_name:?} could not be deleted")]
         DeleteReflog { full_name: BString, source: std::io::Error },
+        #[error("The reflog of the reference renamed or copied to {full_name:?} could not be moved or copied")]
+        TransferReflog { source: std::io::Error, full_name: BString },
         #[error("The reflog could not be created or updated")]
         CreateOrUpdateRefLog(#[from] file::log::create_or_update::Error),
+        #[error("The directory at '{}' could not be synced", path.display())]
+        SyncDirectory {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
     }
 }
 pub use error::Error;
@@ -0,0 +1,142 @@
+//! Types for the [hook](crate::file::Transaction::hook()) of a transaction, which allows to implement the semantics
+//! of git's `reference-transaction` hook.
+use std::io;
+
+use crate::{
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+/// The error a hook may return to reject a [prepared](State::Prepared) transaction.
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The state a transaction entered, named like the argument of git's `reference-transaction` hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    /// All references were locked and their expected values were verified, but nothing was written yet.
+    ///
+    /// Returning an error from the hook in this state rolls the transaction back, which is reported as [`Aborted`](State::Aborted).
+    Prepared,
+    /// All changes were written successfully.
+    Committed,
+    /// The prepared transaction was rolled back, either [explicitly](crate::file::Transaction::rollback()), by dropping it,
+    /// or because the hook rejected it.
+    Aborted,
+}
+
+impl State {
+    /// Return the name of the state as passed to git's `reference-transaction` hook.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            State::Prepared => "prepared",
+            State::Committed => "committed",
+            State::Aborted => "aborted",
+        }
+    }
+}
+
+/// The change of a single reference as seen by a hook, which corresponds to a line on the standard input
+/// of git's `reference-transaction` hook.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Update {
+    /// The name of the reference that is changed.
+    pub name: FullName,
+    /// The value the reference had before, or `None` if it didn't exist.
+    pub previous: Option<Target>,
+    /// The value the reference will have, or `None` if it is deleted.
+    pub new: Option<Target>,
+}
+
+impl Update {
+    /// Serialize this update to `out` like git does for its `reference-transaction` hook, as
+    /// `<previous> SP <new> SP <name> LF`, with missing values written as null hash of kind `object_hash`
+    /// and symbolic targets written as `ref:<name>`.
+    pub fn write_to(&self, out: &mut dyn io::Write, object_hash: gix_hash::Kind) -> io::Result<()> {
+        for target in [&self.previous, &self.new] {
+            match target {
+                Some(Target::Object(oid)) => write!(out, "{oid}")?,
+                Some(Target::Symbolic(name)) => {
+                    out.write_all(b"ref:")?;
+                    out.write_all(name.as_bstr())?;
+                }
+                None => write!(out, "{}", object_hash.null())?,
+            }
+            out.write_all(b" ")?;
+        }
+        out.write_all(self.name.as_bstr())?;
+        out.write_all(b"\n")
+    }
+}
+
+pub(in crate::store_impl::file) type Callback<'a> = Box<dyn FnMut(State, &[Update]) -> Result<(), Error> + 'a>;
+
+/// The hook of a transaction along with the updates it was prepared with, to report them as aborted
+/// unless the transaction was committed.
+pub(in crate::store_impl::file) struct Hook<'a> {
+    callback: Callback<'a>,
+    prepared: Option<Vec<Update>>,
+}
+
+impl<'a> Hook<'a> {
+    pub(in crate::store_impl::file) fn new(callback: Callback<'a>) -> Self {
+        Hook {
+            callback,
+            prepared: None,
+        }
+    }
+
+    /// Call the hook with `edits` as they are prepared, and remember them to report them as aborted if the transaction
+    /// is dropped without being committed.
+    pub(in crate::store_impl::file) fn prepared<'e>(
+        &mut self,
+        edits: impl IntoIterator<Item = &'e RefEdit>,
+    ) -> Result<(), Error> {
+        let updates = self.prepared.insert(edits.into_iter().filter_map(to_update).collect());
+        (self.callback)(State::Prepared, updates)
+    }
+
+    /// Report the prepared edits as committed.
+    pub(in crate::store_impl::file) fn committed(&mut self) {
+        if let Some(updates) = self.prepared.take() {
+            (self.callback)(State::Committed, &updates).ok();
+        }
+    }
+
+    /// Don't report the prepared edits at all, as it's unknown which of them were performed.
+    pub(in crate::store_impl::file) fn forget(&mut self) {
+        self.prepared.take();
+    }
+
+    /// Report the prepared edits as aborted.
+    pub(in crate::store_impl::file) fn aborted(&mut self) {
+        if let Some(updates) = self.prepared.take() {
+            (self.callback)(State::Aborted, &updates).ok();
+        }
+    }
+}
+
+impl Drop for Hook<'_> {
+    fn drop(&mut self) {
+        self.aborted();
+    }
+}
+
+/// Like git, ignore edits that only affect the reflog, as these are the symbolic references that were dereferenced.
+fn to_update(edit: &RefEdit) -> Option<Update> {
+    let (previous, new) = match &edit.change {
+        Change::Update { log, expected, new, .. } if log.mode == RefLog::AndReference => (expected, Some(new.clone())),
+        Change::Delete {
+            expected,
+            log: RefLog::AndReference,
+        } => (expected, None),
+        _ => return None,
+    };
+    Some(Update {
+        name: edit.name.clone(),
+        previous: match previous {
+            PreviousValue::MustExistAndMatch(previous) => Some(previous.clone()),
+            _ => None,
+        },
+        new,
+    })
+}
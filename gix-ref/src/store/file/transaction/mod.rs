@@ -6,6 +6,7 @@ use gix_object::bstr::BString;
 use crate::{
     store_impl::{file, file::Transaction},
     transaction::RefEdit,
+    FullName, Target,
 };
 
 /// How to handle packed refs during a transaction
@@ -39,12 +40,61 @@ pub(in crate::store_impl::file) struct Edit {
     /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
     /// peeled value of the leaf referent.
     leaf_referent_previous_oid: Option<ObjectId>,
+    /// For updates to symbolic targets, these are the peeled previous and new values to put into the reflog of the symbolic
+    /// reference itself, with the previous value being null if it didn't exist or couldn't be peeled.
+    /// It's `None` if the target doesn't change or if it can't be peeled, like when pointing to an unborn branch.
+    symbolic_target_reflog_oids: Option<(ObjectId, ObjectId)>,
+    /// Set if this update creates a reference by renaming or copying another reference, whose reflog is moved or copied
+    /// to this reference.
+    origin: Option<Origin>,
+}
+
+/// The reference that a reference created by a rename or copy originates from.
+#[derive(Debug)]
+pub(in crate::store_impl::file) enum Origin {
+    /// The reference with the given name is renamed, and its reflog is moved.
+    Renamed(FullName),
+    /// The reference with the given name is copied, and its reflog is copied.
+    Copied(FullName),
 }
 
 impl Edit {
     fn name(&self) -> BString {
         self.update.name.0.clone()
     }
+
+    /// Return the name of the reference this edit renames if it's the name of a directory of this reference or vice versa,
+    /// like `refs/heads/a` and `refs/heads/a/b`, which means the renamed reference has to be removed from disk before
+    /// this one can take its place.
+    fn nested_rename_source(&self) -> Option<&FullName> {
+        let Some(Origin::Renamed(source)) = &self.origin else {
+            return None;
+        };
+        let is_directory_of = |parent: &FullName, child: &FullName| {
+            child.0.len() > parent.0.len() && child.0.starts_with(&parent.0) && child.0[parent.0.len()] == b'/'
+        };
+        (is_directory_of(source, &self.update.name) || is_directory_of(&self.update.name, source)).then_some(source)
+    }
+
+    /// Return `true` if the reference this edit renames is the name of a directory of this reference, which is in the way
+    /// of the lock file of this reference until it's removed when committing.
+    fn lock_when_committing(&self) -> bool {
+        self.nested_rename_source()
+            .map_or(false, |source| self.update.name.0.starts_with(&source.0))
+    }
+}
+
+/// Write `target` into `lock` and close it, so the locked reference is replaced by it once committed.
+fn write_target(store: &file::Store, mut lock: gix_lock::File, target: &Target) -> std::io::Result<gix_lock::Marker> {
+    use std::io::Write;
+    lock.with_mut(|file| {
+        match target {
+            Target::Object(oid) => write!(file, "{oid}"),
+            Target::Symbolic(name) => writeln!(file, "ref: {}", name.0),
+        }?;
+        store.fsync.map_or(Ok(()), |method| gix_fs::fsync::file(file, method))
+    })?;
+    lock.close()
 }
 
 impl std::borrow::Borrow<RefEdit> for Edit {
@@ -73,6 +123,8 @@ impl file::Store {
             packed_transaction: None,
             updates: None,
             packed_refs: PackedRefs::default(),
+            default_log_message: None,
+            hook: None,
         }
     }
 }
@@ -83,6 +135,28 @@ impl<'s, 'p> Transaction<'s, 'p> {
         self.packed_refs = packed_refs;
         self
     }
+
+    /// Use `message` as reflog message for all updates that don't have a message, like
+    /// [`LogMessage::UpdateByPush`](crate::transaction::LogMessage::UpdateByPush) for updates of remote tracking branches
+    /// after pushing.
+    pub fn default_log_message(mut self, message: impl Into<BString>) -> Self {
+        self.default_log_message = Some(message.into());
+        self
+    }
+
+    /// Call `hook` with all changes to references when the transaction was [prepared](hook::State::Prepared),
+    /// [committed](hook::State::Committed) or [aborted](hook::State::Aborted), to implement the semantics of
+    /// git's `reference-transaction` hook.
+    ///
+    /// Changes that only affect the reflog aren't passed, which includes symbolic references that were dereferenced,
+    /// while their referents are passed instead.
+    /// If `hook` fails once the transaction is prepared, the transaction is rolled back and
+    /// [`prepare()`](Self::prepare()) fails. Errors in all other states are ignored.
+    /// If [`commit()`](Self::commit()) fails, `hook` isn't called again as changes may have been performed partially.
+    pub fn hook(mut self, hook: impl FnMut(hook::State, &[hook::Update]) -> Result<(), hook::Error> + 'p) -> Self {
+        self.hook = Some(hook::Hook::new(Box::new(hook)));
+        self
+    }
 }
 
 impl std::fmt::Debug for Transaction<'_, '_> {
@@ -94,6 +168,9 @@ impl std::fmt::Debug for Transaction<'_, '_> {
     }
 }
 
+///
+pub mod hook;
+
 ///
 pub mod prepare;
 
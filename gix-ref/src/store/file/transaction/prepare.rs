@@ -5,12 +5,12 @@ use crate::{
         file,
         file::{
             loose,
-            transaction::{Edit, PackedRefs},
+            transaction::{write_target, Edit, Origin, PackedRefs},
             Transaction,
         },
     },
     transaction::{Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
-    FullName, FullNameRef, Reference, Target,
+    FullName, FullNameRef, ObjectId, Reference, Target,
 };
 
 impl<'s, 'p> Transaction<'s, 'p> {
@@ -22,36 +22,15 @@ impl<'s, 'p> Transaction<'s, 'p> {
         has_global_lock: bool,
         direct_to_packed_refs: bool,
     ) -> Result<(), Error> {
-        use std::io::Write;
         assert!(
             change.lock.is_none(),
             "locks can only be acquired once and it's all or nothing"
         );
 
-        let existing_ref = store
-            .ref_contents(change.update.name.as_ref())
-            .map_err(Error::from)
-            .and_then(|maybe_loose| {
-                maybe_loose
-                    .map(|buf| {
-                        loose::Reference::try_from_path(change.update.name.clone(), &buf)
-                            .map(Reference::from)
-                            .map_err(Error::from)
-                    })
-                    .transpose()
-            })
-            .or_else(|err| match err {
-                Error::ReferenceDecode(_) => Ok(None),
-                other => Err(other),
-            })
-            .and_then(|maybe_loose| match (maybe_loose, packed) {
-                (None, Some(packed)) => packed
-                    .try_find(change.update.name.as_ref())
-                    .map(|opt| opt.map(Into::into))
-                    .map_err(Error::from),
-                (None, None) => Ok(None),
-                (maybe_loose, _) => Ok(maybe_loose),
-            })?;
+        let existing_ref = find_exact(store, packed, change.update.name.as_ref())?;
+        // The renamed reference is in the way of the lock file of the new one if it's the name of a directory of it, which is
+        // why the new reference is only locked and written when committing, once the renamed reference was removed.
+        let lock_when_committing = change.lock_when_committing();
         let lock = match &mut change.update.change {
             Change::Delete { expected, .. } => {
                 let (base, relative_path) = store.reference_path_with_base(change.update.name.as_ref());
@@ -64,7 +43,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         Some(base.clone().into_owned()),
                     )
                     .map_err(|err| Error::LockAcquire {
-                        source: err,
+                        source: err.into(),
                         full_name: "borrowcheck won't allow change.name()".into(),
                     })?
                     .into()
@@ -76,7 +55,12 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     }
                     (PreviousValue::ExistingMustMatch(_) | PreviousValue::Any, None)
                     | (PreviousValue::MustExist | PreviousValue::Any, Some(_)) => {}
-                    (PreviousValue::MustExist | PreviousValue::MustExistAndMatch(_), None) => {
+                    (
+                        PreviousValue::MustExist
+                        | PreviousValue::MustExistAndMatch(_)
+                        | PreviousValue::SymbolicMustMatch { .. },
+                        None,
+                    ) => {
                         return Err(Error::DeleteReferenceMustExist {
                             full_name: change.name(),
                         })
@@ -95,6 +79,17 @@ impl<'s, 'p> Transaction<'s, 'p> {
                             });
                         }
                     }
+                    // The referent expectation only applies to dereferenced edits, which were split already.
+                    (PreviousValue::SymbolicMustMatch { target, .. }, Some(existing)) => {
+                        let expected = Target::Symbolic(target.clone());
+                        if expected != existing.target {
+                            return Err(Error::ReferenceOutOfDate {
+                                full_name: change.name(),
+                                expected,
+                                actual: existing.target.clone(),
+                            });
+                        }
+                    }
                 }
 
                 // Keep the previous value for the caller and ourselves. Maybe they want to keep a log of sorts.
@@ -113,11 +108,13 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         Some(base.clone().into_owned()),
                     )
                     .map_err(|err| Error::LockAcquire {
-                        source: err,
+                        source: err.into(),
                         full_name: "borrowcheck won't allow change.name() and this will be corrected by caller".into(),
                     })
                 };
-                let mut lock = (!has_global_lock).then(obtain_lock).transpose()?;
+                let mut lock = (!has_global_lock && !lock_when_committing)
+                    .then(obtain_lock)
+                    .transpose()?;
 
                 match (&expected, &existing_ref) {
                     (PreviousValue::Any, _)
@@ -159,6 +156,24 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         let full_name = change.name();
                         return Err(Error::MustExist { full_name, expected });
                     }
+                    (PreviousValue::SymbolicMustMatch { target, .. }, None) => {
+                        let expected = Target::Symbolic(target.clone());
+                        let full_name = change.name();
+                        return Err(Error::MustExist { full_name, expected });
+                    }
+                    // The referent expectation only applies to dereferenced edits, which were split already.
+                    (PreviousValue::SymbolicMustMatch { target, .. }, Some(existing)) => {
+                        let expected = Target::Symbolic(target.clone());
+                        if expected != existing.target {
+                            let actual = existing.target.clone();
+                            let full_name = change.name();
+                            return Err(Error::ReferenceOutOfDate {
+                                full_name,
+                                actual,
+                                expected,
+                            });
+                        }
+                    }
                 };
 
                 fn new_would_change_existing(new: &Target, existing: &Target) -> (bool, bool) {
@@ -178,18 +193,16 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     (true, matches!(new, Target::Symbolic(_)))
                 };
 
-                if (is_effective && !direct_to_packed_refs) || is_symbolic {
-                    let mut lock = lock.take().map_or_else(obtain_lock, Ok)?;
-
-                    lock.with_mut(|file| match new {
-                        Target::Object(oid) => write!(file, "{oid}"),
-                        Target::Symbolic(name) => writeln!(file, "ref: {}", name.0),
-                    })?;
-                    Some(lock.close()?)
+                if ((is_effective && !direct_to_packed_refs) || is_symbolic) && !lock_when_committing {
+                    let lock = lock.take().map_or_else(obtain_lock, Ok)?;
+                    Some(write_target(store, lock, new)?)
                 } else {
                     None
                 }
             }
+            Change::Rename { .. } | Change::Copy { .. } => {
+                unreachable!("BUG: renames and copies were split into two edits")
+            }
         };
         change.lock = lock;
         Ok(())
@@ -223,14 +236,84 @@ impl<'s, 'p> Transaction<'s, 'p> {
     ) -> Result<Self, Error> {
         assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
         let store = self.store;
+        let default_log_message = self.default_log_message.take();
         let mut updates: Vec<_> = edits
+            .map(|mut update| {
+                if let (Some(default_message), Change::Update { log, .. }) = (&default_log_message, &mut update.change)
+                {
+                    if log.message.is_empty() {
+                        log.message = default_message.clone();
+                    }
+                }
+                update
+            })
             .map(|update| Edit {
                 update,
                 lock: None,
                 parent_index: None,
                 leaf_referent_previous_oid: None,
+                symbolic_target_reflog_oids: None,
+                origin: None,
             })
             .collect();
+        let renames_and_copies: Vec<_> = {
+            let packed = if updates
+                .iter()
+                .any(|edit| matches!(edit.update.change, Change::Rename { .. } | Change::Copy { .. }))
+            {
+                store.assure_packed_refs_uptodate()?
+            } else {
+                None
+            };
+            let packed = packed.as_deref().map(|packed| &**packed);
+            let mut renames_and_copies = Vec::new();
+            for edit in &mut updates {
+                let (Change::Rename {
+                    new_name,
+                    new_name_expected,
+                    ..
+                }
+                | Change::Copy {
+                    new_name,
+                    new_name_expected,
+                    ..
+                }) = &edit.update.change
+                else {
+                    continue;
+                };
+                let current = find_exact(store, packed, edit.update.name.as_ref())?
+                    .ok_or_else(|| Error::DeleteReferenceMustExist { full_name: edit.name() })?;
+                let renamed = matches!(edit.update.change, Change::Rename { .. }).then(|| edit.update.name.as_ref());
+                assure_no_name_conflict(store, packed, new_name.as_ref(), renamed)?;
+                if *new_name_expected == PreviousValue::MustNotExist {
+                    // Unlike updates, renames and copies never overwrite an existing reference, even if it has the same value.
+                    if let Some(existing) = find_exact(store, packed, new_name.as_ref())? {
+                        return Err(Error::MustNotExist {
+                            full_name: new_name.as_bstr().to_owned(),
+                            actual: existing.target,
+                            new: current.target,
+                        });
+                    }
+                }
+                let origin = match edit.update.change {
+                    Change::Rename { .. } => Origin::Renamed(edit.update.name.clone()),
+                    _ => Origin::Copied(edit.update.name.clone()),
+                };
+                renames_and_copies.push(Edit {
+                    update: edit
+                        .update
+                        .split_rename_or_copy(&current.target)
+                        .expect("a rename or copy"),
+                    lock: None,
+                    parent_index: None,
+                    leaf_referent_previous_oid: None,
+                    symbolic_target_reflog_oids: None,
+                    origin: Some(origin),
+                });
+            }
+            renames_and_copies
+        };
+        updates.extend(renames_and_copies);
         updates
             .pre_process(
                 &mut |name| {
@@ -245,6 +328,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     lock: None,
                     parent_index: Some(idx),
                     leaf_referent_previous_oid: None,
+                    symbolic_target_reflog_oids: None,
+                    origin: None,
                 },
             )
             .map_err(Error::PreprocessingFailed)?;
@@ -267,8 +352,13 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         ..
                     } => mode,
                     Change::Delete { log, .. } => log,
+                    Change::Rename { .. } | Change::Copy { .. } => {
+                        unreachable!("BUG: renames and copies were split into two edits")
+                    }
                 };
                 if log_mode == RefLog::Only {
+                    // The reference itself isn't changed, but its current value may only be available in packed refs.
+                    needs_packed_refs_lookups = true;
                     continue;
                 }
                 let name = match possibly_adjust_name_for_prefixes(edit.update.name.as_ref()) {
@@ -332,6 +422,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                                     packed_refs_lock_fail_mode,
                                     self.store.precompose_unicode,
                                     self.store.namespace.clone(),
+                                    self.store.fsync,
                                 )
                                 .map_err(Error::PackedTransactionAcquire)
                             })
@@ -350,6 +441,14 @@ impl<'s, 'p> Transaction<'s, 'p> {
             }
         }
 
+        if self.store.ignore_case {
+            assure_no_case_conflict(
+                self.store,
+                self.packed_transaction.as_ref().and_then(packed::Transaction::buffer),
+                &updates,
+            )?;
+        }
+
         for cid in 0..updates.len() {
             let change = &mut updates[cid];
             if let Err(err) = Self::lock_ref_and_apply_change(
@@ -401,10 +500,90 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 }
             }
         }
+
+        // Symbolic refs don't have an id of their own, so their reflog records the ids of what they point to instead.
+        // These may be packed even if we have no packed transaction, as is the case if only `HEAD` is changed.
+        let packed_snapshot = match self.packed_transaction.as_ref() {
+            None if updates.iter().any(|edit| {
+                matches!(
+                    &edit.update.change,
+                    Change::Update {
+                        new: Target::Symbolic(_),
+                        ..
+                    } | Change::Update {
+                        expected: PreviousValue::MustExistAndMatch(Target::Symbolic(_)),
+                        ..
+                    }
+                )
+            }) =>
+            {
+                self.store.assure_packed_refs_uptodate()?
+            }
+            _ => None,
+        };
+        let packed = self
+            .packed_transaction
+            .as_ref()
+            .and_then(packed::Transaction::buffer)
+            .or(packed_snapshot.as_ref().map(|snapshot| &***snapshot));
+        for cid in 0..updates.len() {
+            if let Change::Update {
+                new: Target::Object(_),
+                expected: PreviousValue::MustExistAndMatch(previous @ Target::Symbolic(_)),
+                ..
+            } = &updates[cid].update.change
+            {
+                // Like when detaching `HEAD`, which then logs the id of the branch it was on.
+                if updates[cid].leaf_referent_previous_oid.is_none() {
+                    let previous_oid = peel(store, packed, updates[cid].update.name.clone(), previous.clone());
+                    updates[cid].leaf_referent_previous_oid = previous_oid;
+                }
+                continue;
+            }
+            let (new, previous) = match &updates[cid].update.change {
+                Change::Update {
+                    new: Target::Symbolic(new),
+                    expected,
+                    ..
+                } => match expected {
+                    PreviousValue::MustExistAndMatch(Target::Symbolic(previous)) if previous == new => continue,
+                    PreviousValue::MustExistAndMatch(previous) => (new, Some(previous)),
+                    _ => (new, None),
+                },
+                _ => continue,
+            };
+            let new_oid = updates
+                .iter()
+                .find_map(|edit| match &edit.update.change {
+                    Change::Update {
+                        new: Target::Object(oid),
+                        ..
+                    } if edit.update.name == *new => Some(*oid),
+                    _ => None,
+                })
+                .or_else(|| peel(store, packed, new.clone(), Target::Symbolic(new.clone())));
+            let Some(new_oid) = new_oid else { continue };
+            let previous_oid = previous
+                .and_then(|previous| peel(store, packed, updates[cid].update.name.clone(), previous.clone()))
+                .unwrap_or_else(|| store.object_hash.null());
+            updates[cid].symbolic_target_reflog_oids = Some((previous_oid, new_oid));
+        }
         self.updates = Some(updates);
+        if let Some(hook) = &mut self.hook {
+            hook.prepared(self.updates.iter().flatten().map(|edit| &edit.update))
+                .map_err(Error::Hook)?;
+        }
         Ok(self)
     }
 
+    /// Return the edits as they were [prepared](Self::prepare()), or nothing if this transaction wasn't prepared yet.
+    ///
+    /// Like the edits returned by [`rollback()`](Self::rollback()), they may have been split and their `expected` value
+    /// may have been adjusted to what's currently on disk, which is guaranteed not to change while their locks are held.
+    pub fn prepared_edits(&self) -> impl Iterator<Item = &RefEdit> + '_ {
+        self.updates.iter().flatten().map(|edit| &edit.update)
+    }
+
     /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
     ///
     /// Note that they have been altered compared to what was initially provided as they have
@@ -420,6 +599,149 @@ impl<'s, 'p> Transaction<'s, 'p> {
     }
 }
 
+/// Find the reference with exactly the given `name`, loose or `packed`, or return `None` if it doesn't exist or can't be parsed.
+fn find_exact(
+    store: &file::Store,
+    packed: Option<&packed::Buffer>,
+    name: &FullNameRef,
+) -> Result<Option<Reference>, Error> {
+    store
+        .ref_contents(name)
+        .map_err(Error::from)
+        .and_then(|maybe_loose| {
+            maybe_loose
+                .map(|buf| {
+                    loose::Reference::try_from_path(name.to_owned(), &buf)
+                        .map(Reference::from)
+                        .map_err(Error::from)
+                })
+                .transpose()
+        })
+        .or_else(|err| match err {
+            Error::ReferenceDecode(_) => Ok(None),
+            other => Err(other),
+        })
+        .and_then(|maybe_loose| match (maybe_loose, packed) {
+            (None, Some(packed)) => packed
+                .try_find(name)
+                .map(|opt| opt.map(Into::into))
+                .map_err(Error::from),
+            (None, None) => Ok(None),
+            (maybe_loose, _) => Ok(maybe_loose),
+        })
+}
+
+/// Fail if a reference named `name` can't be created as a reference exists whose name is a directory of `name`,
+/// or which is inside of the directory that `name` would be, like `refs/heads/a` and `refs/heads/a/b`.
+/// The `renamed` reference doesn't conflict as it's removed to make room for `name`.
+fn assure_no_name_conflict(
+    store: &file::Store,
+    packed: Option<&packed::Buffer>,
+    name: &FullNameRef,
+    renamed: Option<&FullNameRef>,
+) -> Result<(), Error> {
+    use gix_object::bstr::ByteSlice;
+    let full_name = name.as_bstr();
+    // The first component, like `refs`, is always a directory.
+    for end in full_name.find_iter(b"/").skip(1) {
+        let parent = FullNameRef::new_unchecked(full_name[..end].as_bstr());
+        if Some(parent) != renamed && find_exact(store, packed, parent)?.is_some() {
+            return Err(Error::NameConflict {
+                full_name: full_name.to_owned(),
+                conflicting: parent.as_bstr().to_owned(),
+            });
+        }
+    }
+    let mut prefix = full_name.to_owned();
+    prefix.push(b'/');
+    if let Some(existing) = store
+        .iter_prefixed_packed(&gix_path::from_bstring(prefix.clone()), packed)?
+        .find(|r| {
+            r.as_ref().map_or(true, |r| {
+                r.name.as_bstr().starts_with(&prefix) && Some(r.name.as_ref()) != renamed
+            })
+        })
+    {
+        return Err(Error::NameConflict {
+            full_name: full_name.to_owned(),
+            conflicting: existing.map_or(prefix, |r| r.name.0),
+        });
+    }
+    Ok(())
+}
+
+/// Fail if one of `updates` would change a loose reference whose name on disk only differs in case, or if it would create
+/// a reference whose name only differs in case from a `packed` reference or another reference of the transaction,
+/// as these names can't be told apart on case-insensitive filesystems.
+fn assure_no_case_conflict(
+    store: &file::Store,
+    packed: Option<&packed::Buffer>,
+    updates: &[Edit],
+) -> Result<(), Error> {
+    use gix_object::bstr::{BString, ByteSlice};
+    let namespaced = |name: &FullNameRef| match &store.namespace {
+        Some(namespace) => namespace.to_owned().into_namespaced_name(name).0,
+        None => name.as_bstr().to_owned(),
+    };
+    let mut names_by_folded_name = None::<std::collections::HashMap<Vec<u8>, BString>>;
+    for edit in updates {
+        let name = edit.update.name.as_ref();
+        if let Some(conflicting) = store.loose_name_differing_in_case(name)? {
+            return Err(Error::CaseConflict {
+                full_name: edit.name(),
+                conflicting,
+            });
+        }
+        let is_creation =
+            matches!(edit.update.change, Change::Update { .. }) && find_exact(store, packed, name)?.is_none();
+        if !is_creation {
+            continue;
+        }
+        let names = match names_by_folded_name.as_mut() {
+            Some(names) => names,
+            None => {
+                let mut names = std::collections::HashMap::new();
+                if let Some(packed) = packed {
+                    let iter = packed
+                        .iter()
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+                    for packed_ref in iter.filter_map(Result::ok) {
+                        names.insert(
+                            packed_ref.name.as_bstr().to_lowercase(),
+                            packed_ref.name.as_bstr().to_owned(),
+                        );
+                    }
+                }
+                for edit in updates {
+                    let name = namespaced(edit.update.name.as_ref());
+                    names.entry(name.to_lowercase()).or_insert(name);
+                }
+                names_by_folded_name.insert(names)
+            }
+        };
+        let name = namespaced(name);
+        if let Some(conflicting) = names.get(&name.to_lowercase()).filter(|existing| **existing != name) {
+            return Err(Error::CaseConflict {
+                full_name: edit.name(),
+                conflicting: conflicting.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Follow `target` of the reference named `name` to the object it ultimately points to, or return `None` if that's not possible.
+fn peel(store: &file::Store, packed: Option<&packed::Buffer>, name: FullName, target: Target) -> Option<ObjectId> {
+    use crate::file::ReferenceExt;
+    Reference {
+        name,
+        target,
+        peeled: None,
+    }
+    .follow_to_object_in_place_packed(store, packed)
+    .ok()
+}
+
 fn possibly_adjust_name_for_prefixes(name: &FullNameRef) -> Option<FullName> {
     match name.category_and_short_name() {
         Some((c, sn)) => {
@@ -463,7 +785,7 @@ mod error {
         PreprocessingFailed(#[source] std::io::Error),
         #[error("A lock could not be obtained for reference {full_name:?}")]
         LockAcquire {
-            source: gix_lock::acquire::Error,
+            source: Box<gix_lock::acquire::Error>,
             full_name: BString,
         },
         #[error("An IO error occurred while applying an edit")]
@@ -486,6 +808,14 @@ mod error {
         },
         #[error("Could not read reference")]
         ReferenceDecode(#[from] file::loose::reference::decode::Error),
+        #[error(
+            "The reference {full_name:?} can't be created as it conflicts with the existing reference {conflicting:?}"
+        )]
+        NameConflict { full_name: BString, conflicting: BString },
+        #[error("The reference {full_name:?} can't be written as its name only differs in case from {conflicting:?}, which can't be told apart on case-insensitive filesystems")]
+        CaseConflict { full_name: BString, conflicting: BString },
+        #[error("The transaction was rejected by its hook")]
+        Hook(#[source] crate::file::transaction::hook::Error),
     }
 }
 
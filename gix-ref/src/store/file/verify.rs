@@ -0,0 +1,290 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use gix_object::bstr::{BString, ByteSlice};
+
+use crate::{
+    file::{self, loose},
+    store_impl::{file::log::LineRef, packed},
+    FullName, Target,
+};
+
+/// A problem found by [`file::Store::verify()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The file in which the problem was found.
+    pub path: PathBuf,
+    /// The kind of problem.
+    pub kind: Kind,
+}
+
+/// The kind of problem found by [`file::Store::verify()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// A loose reference file doesn't have a name that is valid according to `git check-ref-format`.
+    InvalidName {
+        /// The name of the reference as derived from its path.
+        name: BString,
+    },
+    /// The content of a loose reference is neither an object id nor a symbolic reference.
+    MalformedContent {
+        /// The content that couldn't be parsed.
+        content: BString,
+    },
+    /// A symbolic reference points to a reference whose name isn't valid.
+    InvalidSymbolicTarget {
+        /// The name of the symbolic reference.
+        name: FullName,
+        /// The invalid name it points to.
+        target: BString,
+    },
+    /// A symbolic reference points to a reference which exists neither as loose nor as packed reference.
+    DanglingSymbolicRef {
+        /// The name of the symbolic reference.
+        name: FullName,
+        /// The name of the reference it points to.
+        target: FullName,
+    },
+    /// A line in a reflog couldn't be parsed.
+    MalformedReflogLine {
+        /// The number of the line, starting at 1.
+        line_number: usize,
+        /// The line itself, without its line terminator.
+        line: BString,
+    },
+    /// The header of the packed-refs file couldn't be parsed.
+    MalformedPackedRefsHeader {
+        /// The first line of the file.
+        line: BString,
+    },
+    /// A line in the packed-refs file couldn't be parsed.
+    MalformedPackedRef {
+        /// The number of the line, starting at 1.
+        line_number: usize,
+        /// The line itself, without its line terminator.
+        line: BString,
+    },
+    /// A reference in a packed-refs file which claims to be sorted is ordered before its predecessor.
+    UnsortedPackedRef {
+        /// The name of the out-of-order reference.
+        name: FullName,
+        /// The name of the reference preceding it.
+        previous: FullName,
+    },
+    /// A reference is contained more than once in the packed-refs file.
+    DuplicatePackedRef {
+        /// The name of the duplicate reference.
+        name: FullName,
+    },
+}
+
+/// The error returned by [`file::Store::verify()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not traverse the directory at {path:?}")]
+    Traverse { source: std::io::Error, path: PathBuf },
+    #[error("Could not read the file at {path:?}")]
+    Read { source: std::io::Error, path: PathBuf },
+}
+
+impl file::Store {
+    /// Check all loose references, reflogs and the packed-refs file for problems, and return all of them
+    /// instead of stopping at the first one. An empty list means no problem was found.
+    ///
+    /// This is similar to what `git refs verify` does. Namespaces are ignored, all references of the store are verified.
+    /// Note that `HEAD` may point to a branch that doesn't exist yet, which isn't considered a problem.
+    /// Lock files are ignored as they belong to ongoing transactions.
+    pub fn verify(&self) -> Result<Vec<Finding>, Error> {
+        let mut findings = Vec::new();
+        let packed_names = self.verify_packed_refs(&mut findings)?;
+
+        let mut loose_names = BTreeSet::new();
+        let mut symbolic_refs = Vec::new();
+        for (path, name) in self.verify_candidates("refs", Some("HEAD"))? {
+            let name = match gix_validate::reference::name(name.as_ref()) {
+                Ok(_) => FullName(name),
+                Err(_) => {
+                    findings.push(Finding {
+                        path,
+                        kind: Kind::InvalidName { name },
+                    });
+                    continue;
+                }
+            };
+            let content = std::fs::read(&path).map_err(|source| Error::Read {
+                source,
+                path: path.clone(),
+            })?;
+            match loose::Reference::try_from_path(name.clone(), &content) {
+                Ok(reference) => {
+                    if let Target::Symbolic(target) = reference.target {
+                        symbolic_refs.push((path, name.clone(), target));
+                    }
+                }
+                Err(loose::reference::decode::Error::Parse { content }) => findings.push(Finding {
+                    path,
+                    kind: Kind::MalformedContent { content },
+                }),
+                Err(loose::reference::decode::Error::RefnameValidation { path: target, .. }) => {
+                    findings.push(Finding {
+                        path,
+                        kind: Kind::InvalidSymbolicTarget {
+                            name: name.clone(),
+                            target,
+                        },
+                    });
+                }
+            }
+            loose_names.insert(name);
+        }
+
+        for (path, name, target) in symbolic_refs {
+            if name.as_bstr() == "HEAD" || loose_names.contains(&target) || packed_names.contains(&target) {
+                continue;
+            }
+            findings.push(Finding {
+                path,
+                kind: Kind::DanglingSymbolicRef { name, target },
+            });
+        }
+
+        for (path, _name) in self.verify_candidates("logs", None)? {
+            let content = std::fs::read(&path).map_err(|source| Error::Read {
+                source,
+                path: path.clone(),
+            })?;
+            for (line_number, line) in content.lines().enumerate() {
+                if LineRef::from_bytes(line).is_err() {
+                    findings.push(Finding {
+                        path: path.clone(),
+                        kind: Kind::MalformedReflogLine {
+                            line_number: line_number + 1,
+                            line: line.into(),
+                        },
+                    });
+                }
+            }
+        }
+        Ok(findings)
+    }
+
+    /// Return the paths and names of all files in the `dir` directory of the git dir and the common dir,
+    /// along with `root_file` in the git dir, if it exists. The names are relative to the `dir`'s parent directory.
+    fn verify_candidates(&self, dir: &str, root_file: Option<&str>) -> Result<Vec<(PathBuf, BString)>, Error> {
+        let mut out = Vec::new();
+        if let Some(root_file) = root_file {
+            let path = self.git_dir.join(root_file);
+            if path.is_file() {
+                out.push((path, root_file.into()));
+            }
+        }
+        for base in std::iter::once(self.git_dir.as_path()).chain(self.common_dir.as_deref()) {
+            collect_files(base, &base.join(dir), self.precompose_unicode, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    /// Check the packed-refs file, if there is one, and return the names of all references it contains.
+    fn verify_packed_refs(&self, findings: &mut Vec<Finding>) -> Result<BTreeSet<FullName>, Error> {
+        let mut names = BTreeSet::new();
+        let path = self.packed_refs_path();
+        let buf = match std::fs::read(&path) {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+            Err(source) => return Err(Error::Read { source, path }),
+        };
+
+        let is_sorted = buf.first() == Some(&b'#')
+            && packed::decode::header::<()>(&mut buf.as_slice()).map_or(false, |header| header.sorted);
+        let iter = match packed::Iter::new(&buf) {
+            Ok(iter) => iter,
+            Err(packed::iter::Error::Header { invalid_first_line }) => {
+                findings.push(Finding {
+                    path,
+                    kind: Kind::MalformedPackedRefsHeader {
+                        line: invalid_first_line,
+                    },
+                });
+                return Ok(names);
+            }
+            Err(packed::iter::Error::Reference { .. }) => unreachable!("only the header is parsed on creation"),
+        };
+
+        let mut previous: Option<FullName> = None;
+        for reference in iter {
+            let kind = match reference {
+                Ok(reference) => {
+                    let name = reference.name.to_owned();
+                    let kind = if names.contains(&name) {
+                        Some(Kind::DuplicatePackedRef { name: name.clone() })
+                    } else {
+                        match previous.as_ref() {
+                            Some(previous) if is_sorted && previous.as_bstr() > name.as_bstr() => {
+                                Some(Kind::UnsortedPackedRef {
+                                    name: name.clone(),
+                                    previous: previous.clone(),
+                                })
+                            }
+                            _ => None,
+                        }
+                    };
+                    names.insert(name.clone());
+                    previous = Some(name);
+                    kind
+                }
+                Err(packed::iter::Error::Reference {
+                    invalid_line,
+                    line_number,
+                }) => Some(Kind::MalformedPackedRef {
+                    line_number,
+                    line: invalid_line,
+                }),
+                Err(packed::iter::Error::Header { .. }) => unreachable!("the header was parsed already"),
+            };
+            if let Some(kind) = kind {
+                findings.push(Finding {
+                    path: path.clone(),
+                    kind,
+                });
+            }
+        }
+        Ok(names)
+    }
+}
+
+/// Add all files in `dir` to `out`, along with their path relative to `base` with slashes as separators.
+/// Lock files are skipped.
+fn collect_files(
+    base: &Path,
+    dir: &Path,
+    precompose_unicode: bool,
+    out: &mut Vec<(PathBuf, BString)>,
+) -> Result<(), Error> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in
+        gix_features::fs::walkdir_sorted_new(dir, gix_features::fs::walkdir::Parallelism::Serial, precompose_unicode)
+    {
+        let entry = entry.map_err(|err| Error::Traverse {
+            source: err.into_io_error().expect("no symlink related errors"),
+            path: dir.to_owned(),
+        })?;
+        if !entry.file_type().map_or(false, |ft| ft.is_file()) {
+            continue;
+        }
+        let path = entry.path().into_owned();
+        let Ok(name) = gix_path::try_into_bstr(path.strip_prefix(base).expect("base is a parent of dir")) else {
+            continue;
+        };
+        let name = gix_path::to_unix_separators_on_windows(name).into_owned();
+        if name.ends_with(b".lock") {
+            continue;
+        }
+        out.push((path, name));
+    }
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::{file, FullName, Target};
+
+/// A change to a reference as observed by a [`Watcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The reference was created.
+    Created {
+        /// The name of the new reference.
+        name: FullName,
+        /// The target of the new reference.
+        new: Target,
+    },
+    /// The target of the reference changed.
+    Updated {
+        /// The name of the changed reference.
+        name: FullName,
+        /// The target of the reference before the change.
+        previous: Target,
+        /// The target of the reference after the change.
+        new: Target,
+    },
+    /// The reference was deleted.
+    Deleted {
+        /// The name of the deleted reference.
+        name: FullName,
+        /// The target of the reference before it was deleted.
+        previous: Target,
+    },
+}
+
+impl Change {
+    /// Return the name of the reference that changed.
+    pub fn name(&self) -> &FullName {
+        match self {
+            Change::Created { name, .. } | Change::Updated { name, .. } | Change::Deleted { name, .. } => name,
+        }
+    }
+}
+
+/// All references of a store along with their targets at a point in time, as obtained by [`file::Store::watch_state()`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    refs: BTreeMap<FullName, Target>,
+}
+
+impl State {
+    /// Return all changes needed to get from this state to the `newer` one, ordered by reference name.
+    pub fn changes_to(&self, newer: &State) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let mut previous = self.refs.iter().peekable();
+        let mut new = newer.refs.iter().peekable();
+        loop {
+            let change = match (previous.peek(), new.peek()) {
+                (None, None) => break,
+                (Some((name, target)), None) => {
+                    let change = Change::Deleted {
+                        name: (*name).clone(),
+                        previous: (*target).clone(),
+                    };
+                    previous.next();
+                    change
+                }
+                (None, Some((name, target))) => {
+                    let change = Change::Created {
+                        name: (*name).clone(),
+                        new: (*target).clone(),
+                    };
+                    new.next();
+                    change
+                }
+                (Some((previous_name, previous_target)), Some((new_name, new_target))) => {
+                    match previous_name.cmp(new_name) {
+                        std::cmp::Ordering::Less => {
+                            let change = Change::Deleted {
+                                name: (*previous_name).clone(),
+                                previous: (*previous_target).clone(),
+                            };
+                            previous.next();
+                            change
+                        }
+                        std::cmp::Ordering::Greater => {
+                            let change = Change::Created {
+                                name: (*new_name).clone(),
+                                new: (*new_target).clone(),
+                            };
+                            new.next();
+                            change
+                        }
+                        std::cmp::Ordering::Equal => {
+                            let change = (previous_target != new_target).then(|| Change::Updated {
+                                name: (*new_name).clone(),
+                                previous: (*previous_target).clone(),
+                                new: (*new_target).clone(),
+                            });
+                            previous.next();
+                            new.next();
+                            match change {
+                                Some(change) => change,
+                                None => continue,
+                            }
+                        }
+                    }
+                }
+            };
+            changes.push(change);
+        }
+        changes
+    }
+
+    /// Return the target of the reference with `name`, if it exists.
+    pub fn target(&self, name: &FullName) -> Option<&Target> {
+        self.refs.get(name)
+    }
+
+    /// Return all references and their targets, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&FullName, &Target)> + '_ {
+        self.refs.iter()
+    }
+}
+
+/// Configure how a [`Watcher`] learns about changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, use the notifications of the filesystem, like `inotify` or `FSEvents`, to learn about changes,
+    /// and fall back to polling if these aren't available or if there is no `refs` directory yet.
+    pub native: bool,
+    /// How often to check for changes when polling.
+    ///
+    /// With native notifications, this is the time to wait for more notifications before looking for changes,
+    /// as a single change to a reference typically causes multiple notifications.
+    pub interval: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            native: true,
+            interval: Duration::from_millis(100),
+        }
+    }
+}
+
+/// The error returned by [`file::Store::watch()`] and the methods of [`Watcher`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    PackedOpen(#[from] crate::packed::buffer::open::Error),
+    #[error(transparent)]
+    Iter(#[from] file::iter::loose_then_packed::Error),
+    #[error(transparent)]
+    Find(#[from] file::find::Error),
+    #[error("Could not traverse the references directory")]
+    Traverse(#[from] std::io::Error),
+}
+
+/// Watch a [`file::Store`] for changes to its references, which may be made by other processes.
+///
+/// Use it as iterator to block until the next change is observed, or use [`Watcher::changes()`] to check for changes without blocking.
+pub struct Watcher {
+    store: file::Store,
+    state: State,
+    interval: Duration,
+    notifications: Option<native::Notifications>,
+}
+
+impl file::Store {
+    /// Return all references and their targets, including `HEAD`, for comparison with a later [`State`].
+    pub fn watch_state(&self) -> Result<State, Error> {
+        if self.packed_refs_path().is_file() {
+            // Changes within the same second might otherwise go unnoticed.
+            self.force_refresh_packed_buffer().or_else(|err| match err {
+                crate::packed::buffer::open::Error::Io(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                err => Err(err),
+            })?;
+        }
+        let mut refs = BTreeMap::new();
+        if let Some(head) = self.try_find("HEAD")? {
+            refs.insert(head.name, head.target);
+        }
+        for reference in self.iter()?.all()? {
+            let reference = reference?;
+            refs.insert(reference.name, reference.target);
+        }
+        Ok(State { refs })
+    }
+
+    /// Start watching this store for changes to its references as configured by `options`, with all current references
+    /// as baseline.
+    ///
+    /// Note that changes are detected by comparing the state of all references, which means intermediate states that
+    /// exist for less than the [interval](Options::interval) may not be observed.
+    pub fn watch(&self, options: Options) -> Result<Watcher, Error> {
+        let notifications = if options.native {
+            native::Notifications::new(self)
+        } else {
+            None
+        };
+        Ok(Watcher {
+            store: self.clone(),
+            state: self.watch_state()?,
+            interval: options.interval,
+            notifications,
+        })
+    }
+}
+
+/// Access
+impl Watcher {
+    /// Return the state of the references as of the last observed change.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Return `true` if filesystem notifications are used, or `false` if the store is polled.
+    pub fn is_native(&self) -> bool {
+        self.notifications.is_some()
+    }
+
+    /// Return all changes since the last call without blocking, which may be empty.
+    ///
+    /// With native notifications, the references are only read if there was a notification.
+    pub fn changes(&mut self) -> Result<Vec<Change>, Error> {
+        if let Some(notifications) = self.notifications.as_mut() {
+            if !notifications.drain() {
+                return Ok(Vec::new());
+            }
+        }
+        self.update()
+    }
+
+    /// Block until there is at least one change and return all of them.
+    pub fn wait_for_changes(&mut self) -> Result<Vec<Change>, Error> {
+        loop {
+            match self.notifications.as_mut() {
+                Some(notifications) => {
+                    if !notifications.wait(self.interval) {
+                        // The watcher stopped working, fall back to polling.
+                        self.notifications = None;
+                        continue;
+                    }
+                }
+                None => std::thread::sleep(self.interval),
+            }
+            let changes = self.update()?;
+            if !changes.is_empty() {
+                return Ok(changes);
+            }
+        }
+    }
+
+    fn update(&mut self) -> Result<Vec<Change>, Error> {
+        let state = self.store.watch_state()?;
+        let changes = self.state.changes_to(&state);
+        self.state = state;
+        Ok(changes)
+    }
+}
+
+/// Block until the next change and return it. The iteration never ends.
+impl Iterator for Watcher {
+    type Item = Result<Vec<Change>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.wait_for_changes())
+    }
+}
+
+mod native {
+    use std::{
+        path::{Path, PathBuf},
+        sync::mpsc,
+        time::Duration,
+    };
+
+    use notify::Watcher as _;
+
+    use crate::file;
+
+    /// Notifications about changes to files that may contain references.
+    pub(super) struct Notifications {
+        _watcher: notify::RecommendedWatcher,
+        rx: mpsc::Receiver<()>,
+    }
+
+    impl Notifications {
+        /// Return `None` if native notifications aren't available.
+        pub(super) fn new(store: &file::Store) -> Option<Self> {
+            let dirs: Vec<PathBuf> = std::iter::once(store.git_dir().to_owned())
+                .chain(store.common_dir().map(ToOwned::to_owned))
+                .collect();
+            let is_relevant = {
+                let dirs = dirs.clone();
+                move |path: &Path| {
+                    if path.extension().map_or(false, |ext| ext == "lock") {
+                        return false;
+                    }
+                    dirs.iter().any(|dir| {
+                        path.strip_prefix(dir).map_or(false, |rela_path| {
+                            rela_path.starts_with("refs")
+                                || rela_path == Path::new("packed-refs")
+                                || rela_path == Path::new("HEAD")
+                        })
+                    })
+                }
+            };
+
+            let (tx, rx) = mpsc::channel();
+            let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else { return };
+                if event.paths.iter().any(|path| is_relevant(path)) {
+                    tx.send(()).ok();
+                }
+            })
+            .ok()?;
+            for dir in &dirs {
+                watcher.watch(dir, notify::RecursiveMode::NonRecursive).ok()?;
+                // The directory has to exist to be watched, so creating it later would go unnoticed.
+                watcher
+                    .watch(&dir.join("refs"), notify::RecursiveMode::Recursive)
+                    .ok()?;
+            }
+            Some(Notifications { _watcher: watcher, rx })
+        }
+
+        /// Consume all pending notifications and return `true` if there was at least one.
+        pub(super) fn drain(&mut self) -> bool {
+            let mut has_notification = false;
+            while self.rx.try_recv().is_ok() {
+                has_notification = true;
+            }
+            has_notification
+        }
+
+        /// Block until there is a notification, then wait for `batch_interval` to consume more notifications
+        /// belonging to the same change.
+        /// Return `false` if the watcher stopped working.
+        pub(super) fn wait(&mut self, batch_interval: Duration) -> bool {
+            if self.rx.recv().is_err() {
+                return false;
+            }
+            std::thread::sleep(batch_interval);
+            self.drain();
+            true
+        }
+    }
+}
@@ -4,6 +4,7 @@ use crate::{store, Namespace};
 #[derive(Clone)]
 pub(crate) enum State {
     Loose { store: crate::file::Store },
+    Reftable { store: crate::reftable::Store },
 }
 
 impl crate::Store {
@@ -28,6 +29,13 @@ impl crate::Store {
                         store
                     },
                 },
+                store::State::Reftable { store } => store::handle::State::Reftable {
+                    store: {
+                        let mut store = store.clone();
+                        store.namespace = namespace;
+                        store
+                    },
+                },
             },
         }
     }
@@ -12,22 +12,28 @@ mod error {
 
 pub use error::Error;
 
-use crate::file;
+use crate::{file, reftable};
 
-#[allow(dead_code)]
 impl crate::Store {
     /// Create a new store at the given location, typically the `.git/` directory.
     /// Use [`opts`](crate::store::init::Options) to adjust settings.
     ///
+    /// If `git_dir` contains a `reftable/` directory, references are read from and written to a [reftable](reftable::Store),
+    /// and to [loose and packed files](file::Store) otherwise.
+    ///
     /// Note that if [`precompose_unicode`](crate::store::init::Options::precompose_unicode) is set in the options,
     /// the `git_dir` is also expected to use precomposed unicode, or else some operations that strip prefixes will fail.
     pub fn at(git_dir: PathBuf, opts: crate::store::init::Options) -> Result<Self, Error> {
-        // for now, just try to read the directory - later we will do that naturally as we have to figure out if it's a ref-table or not.
         std::fs::read_dir(&git_dir)?;
-        Ok(crate::Store {
-            inner: crate::store::State::Loose {
+        let inner = if git_dir.join("reftable").is_dir() {
+            crate::store::State::Reftable {
+                store: reftable::Store::at(git_dir, opts),
+            }
+        } else {
+            crate::store::State::Loose {
                 store: file::Store::at(git_dir, opts),
-            },
-        })
+            }
+        };
+        Ok(crate::Store { inner })
     }
 }
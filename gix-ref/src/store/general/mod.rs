@@ -1 +1,23 @@
-mod init;
+///
+pub mod init;
+
+use crate::{file, reftable, store};
+
+/// Access
+impl crate::Store {
+    /// Return the store of loose and packed references, or `None` if references are kept in a reftable.
+    pub fn as_file(&self) -> Option<&file::Store> {
+        match &self.inner {
+            store::State::Loose { store } => Some(store),
+            store::State::Reftable { .. } => None,
+        }
+    }
+
+    /// Return the reftable store, or `None` if references are kept in loose and packed files.
+    pub fn as_reftable(&self) -> Option<&reftable::Store> {
+        match &self.inner {
+            store::State::Loose { .. } => None,
+            store::State::Reftable { store } => Some(store),
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::{collections::BTreeMap, ops::Bound, sync::RwLock};
+
+use gix_object::bstr::{BStr, ByteSlice};
+
+use crate::{
+    transaction::{Change, PreviousValue, RefEdit, RefEditsExt, RefLog},
+    FullName, FullNameRef, PartialNameRef, Reference, Target,
+};
+
+/// A reference store which keeps all references in memory, without reflogs, which is useful for tests
+/// or for references that are never supposed to be persisted.
+///
+/// It follows the same lookup rules as the other stores, and applies edits and checks their expectations just like them.
+#[derive(Debug, Default)]
+pub struct Store {
+    references: RwLock<BTreeMap<FullName, Target>>,
+    object_hash: gix_hash::Kind,
+}
+
+impl Store {
+    /// Create a new store for references to objects of kind `object_hash`, which initially contains `references`.
+    pub fn new(object_hash: gix_hash::Kind, references: impl IntoIterator<Item = (FullName, Target)>) -> Self {
+        Store {
+            references: RwLock::new(references.into_iter().collect()),
+            object_hash,
+        }
+    }
+
+    /// Find a single reference by the given `partial` name using git's lookup rules,
+    /// like [`file::Store::try_find()`](crate::file::Store::try_find()), or return `None` if it doesn't exist.
+    pub fn try_find(&self, partial: &PartialNameRef) -> Option<Reference> {
+        let references = self.references.read().expect("no panic while holding the lock");
+        find(&references, partial).map(|(name, target)| Reference {
+            name: name.to_owned(),
+            target: target.clone(),
+            peeled: None,
+        })
+    }
+
+    /// Return all references whose name starts with `prefix`, like `refs/heads/`, sorted by name.
+    pub fn iter_prefixed(&self, prefix: &BStr) -> Vec<Reference> {
+        let references = self.references.read().expect("no panic while holding the lock");
+        references
+            .range::<FullNameRef, _>((Bound::Included(FullNameRef::new_unchecked(prefix)), Bound::Unbounded))
+            .take_while(|(name, _)| name.as_bstr().starts_with(prefix))
+            .map(|(name, target)| Reference {
+                name: name.clone(),
+                target: target.clone(),
+                peeled: None,
+            })
+            .collect()
+    }
+
+    /// Apply all `edits` at once if all of their expectations are met, or change nothing at all.
+    ///
+    /// Return the performed edits, which are adjusted just like the ones returned by
+    /// [`file::Transaction::commit()`](crate::file::Transaction::commit()).
+    pub fn apply(&self, edits: impl IntoIterator<Item = RefEdit>) -> Result<Vec<RefEdit>, apply::Error> {
+        use apply::Error;
+        let mut references = self.references.write().expect("no panic while holding the lock");
+        let mut edits: Vec<RefEdit> = edits.into_iter().collect();
+        let mut renames_and_copies = Vec::new();
+        for edit in &mut edits {
+            let (Change::Rename {
+                new_name,
+                new_name_expected,
+                ..
+            }
+            | Change::Copy {
+                new_name,
+                new_name_expected,
+                ..
+            }) = &edit.change
+            else {
+                continue;
+            };
+            let current = references
+                .get(&edit.name)
+                .cloned()
+                .ok_or_else(|| Error::DeleteReferenceMustExist {
+                    full_name: edit.name.as_bstr().to_owned(),
+                })?;
+            if *new_name_expected == PreviousValue::MustNotExist {
+                // Unlike updates, renames and copies never overwrite an existing reference, even if it has the same value.
+                if let Some(existing) = references.get(new_name) {
+                    return Err(Error::MustNotExist {
+                        full_name: new_name.as_bstr().to_owned(),
+                        actual: existing.clone(),
+                        new: current,
+                    });
+                }
+            }
+            renames_and_copies.push(edit.split_rename_or_copy(&current).expect("a rename or copy"));
+        }
+        edits.extend(renames_and_copies);
+        edits
+            .pre_process(
+                &mut |name| find(&references, name).map(|(_, target)| target.clone()),
+                &mut |_parent_index, update| update,
+            )
+            .map_err(Error::PreprocessingFailed)?;
+
+        let mut changed = references.clone();
+        for edit in &mut edits {
+            check_and_record_previous_value(edit, references.get(&edit.name), self.object_hash)?;
+            match &edit.change {
+                Change::Update { log, new, .. } => {
+                    if log.mode == RefLog::AndReference {
+                        changed.insert(edit.name.clone(), new.clone());
+                    }
+                }
+                Change::Delete { log, .. } => {
+                    if *log == RefLog::AndReference {
+                        changed.remove(&edit.name);
+                    }
+                }
+                Change::Rename { .. } | Change::Copy { .. } => {
+                    unreachable!("BUG: renames and copies were split into two edits")
+                }
+            }
+        }
+        for edit in &edits {
+            if matches!(edit.change, Change::Update { .. }) {
+                assure_no_name_conflict(&changed, edit.name.as_ref())?;
+            }
+        }
+        *references = changed;
+
+        for edit in &mut edits {
+            // Existing references have their previous value set by now, so everything else didn't exist.
+            let (Change::Update { expected, .. }
+            | Change::Delete { expected, .. }
+            | Change::Rename { expected, .. }
+            | Change::Copy { expected, .. }) = &mut edit.change;
+            if !matches!(expected, PreviousValue::MustExistAndMatch(_)) {
+                *expected = PreviousValue::MustNotExist;
+            }
+        }
+        Ok(edits)
+    }
+}
+
+/// Find `partial` in `references` like [`file::Store::try_find()`](crate::file::Store::try_find()) would.
+fn find<'a>(
+    references: &'a BTreeMap<FullName, Target>,
+    partial: &PartialNameRef,
+) -> Option<(&'a FullName, &'a Target)> {
+    let mut buf = Default::default();
+    for inbetween in &["", "tags", "heads", "remotes"] {
+        let name = partial.construct_full_name_ref(inbetween, &mut buf);
+        if let Some(found) = references.get_key_value(name) {
+            return Some(found);
+        }
+    }
+    if partial.as_bstr() != "HEAD" {
+        let partial = partial.to_owned().join("HEAD".into()).ok()?;
+        let name = partial.as_ref().construct_full_name_ref("remotes", &mut buf);
+        return references.get_key_value(name);
+    }
+    None
+}
+
+/// Fail if `name` is the directory of another reference, or if one of its directories is a reference,
+/// as the other stores can't represent these.
+fn assure_no_name_conflict(references: &BTreeMap<FullName, Target>, name: &FullNameRef) -> Result<(), apply::Error> {
+    let full_name = name.as_bstr();
+    let conflicting = full_name
+        .find_iter(b"/")
+        .skip(1)
+        .map(|end| full_name[..end].as_bstr())
+        .find(|parent| references.contains_key(FullNameRef::new_unchecked(parent)))
+        .map(ToOwned::to_owned)
+        .or_else(|| {
+            let mut prefix = full_name.to_owned();
+            prefix.push(b'/');
+            references
+                .range::<FullNameRef, _>((
+                    Bound::Included(FullNameRef::new_unchecked(prefix.as_bstr())),
+                    Bound::Unbounded,
+                ))
+                .next()
+                .filter(|(other, _)| other.as_bstr().starts_with(&prefix))
+                .map(|(other, _)| other.as_bstr().to_owned())
+        });
+    match conflicting {
+        Some(conflicting) => Err(apply::Error::NameConflict {
+            full_name: full_name.to_owned(),
+            conflicting,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check the expectations of `edit` against the `existing` target of the reference and record it as previous value.
+fn check_and_record_previous_value(
+    edit: &mut RefEdit,
+    existing: Option<&Target>,
+    object_hash: gix_hash::Kind,
+) -> Result<(), apply::Error> {
+    use apply::Error;
+    let full_name = edit.name.as_bstr().to_owned();
+    let (new, expected) = match &mut edit.change {
+        Change::Update { new, expected, .. } => (Some(&*new), expected),
+        Change::Delete {
+            expected: PreviousValue::MustNotExist,
+            ..
+        } => {
+            panic!("BUG: MustNotExist constraint makes no sense if references are to be deleted")
+        }
+        Change::Delete { expected, .. } => (None, expected),
+        Change::Rename { .. } | Change::Copy { .. } => {
+            unreachable!("BUG: renames and copies were split into two edits")
+        }
+    };
+    match (&*expected, existing) {
+        (PreviousValue::Any, _)
+        | (PreviousValue::MustExist, Some(_))
+        | (PreviousValue::MustNotExist | PreviousValue::ExistingMustMatch(_), None) => {}
+        (
+            PreviousValue::MustExist | PreviousValue::MustExistAndMatch(_) | PreviousValue::SymbolicMustMatch { .. },
+            None,
+        ) if new.is_none() => return Err(Error::DeleteReferenceMustExist { full_name }),
+        (PreviousValue::MustExist, None) => {
+            return Err(Error::MustExist {
+                full_name,
+                expected: Target::Object(object_hash.null()),
+            })
+        }
+        (PreviousValue::MustExistAndMatch(previous), None) => {
+            return Err(Error::MustExist {
+                full_name,
+                expected: previous.clone(),
+            })
+        }
+        (PreviousValue::MustNotExist, Some(existing)) => {
+            let new = new.expect("deletions were handled earlier");
+            if existing != new {
+                return Err(Error::MustNotExist {
+                    full_name,
+                    actual: existing.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+        (PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+            if previous != existing {
+                return Err(Error::ReferenceOutOfDate {
+                    full_name,
+                    expected: previous.clone(),
+                    actual: existing.clone(),
+                });
+            }
+        }
+        (PreviousValue::SymbolicMustMatch { target, .. }, None) => {
+            return Err(Error::MustExist {
+                full_name,
+                expected: Target::Symbolic(target.clone()),
+            })
+        }
+        // The referent expectation only applies to dereferenced edits, which were split already.
+        (PreviousValue::SymbolicMustMatch { target, .. }, Some(existing)) => {
+            let expected = Target::Symbolic(target.clone());
+            if expected != *existing {
+                return Err(Error::ReferenceOutOfDate {
+                    full_name,
+                    expected,
+                    actual: existing.clone(),
+                });
+            }
+        }
+    }
+    // Keep the previous value for the caller. Maybe they want to keep a log of sorts.
+    if let Some(existing) = existing {
+        *expected = PreviousValue::MustExistAndMatch(existing.clone());
+    }
+    Ok(())
+}
+
+///
+pub mod apply {
+    use gix_object::bstr::BString;
+
+    use crate::Target;
+
+    /// The error returned by [`Store::apply()`](crate::memory::Store::apply()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Edit preprocessing failed with an error")]
+        PreprocessingFailed(#[source] std::io::Error),
+        #[error("The reference {full_name:?} for deletion did not exist")]
+        DeleteReferenceMustExist { full_name: BString },
+        #[error("Reference {full_name:?} was not supposed to exist when writing it with value {new:?}, but actual content was {actual:?}")]
+        MustNotExist {
+            full_name: BString,
+            actual: Target,
+            new: Target,
+        },
+        #[error("Reference {full_name:?} was supposed to exist with value {expected}, but didn't.")]
+        MustExist { full_name: BString, expected: Target },
+        #[error("The reference {full_name:?} should have content {expected}, actual content was {actual}")]
+        ReferenceOutOfDate {
+            full_name: BString,
+            expected: Target,
+            actual: Target,
+        },
+        #[error(
+            "The reference {full_name:?} can't be created as it conflicts with the existing reference {conflicting:?}"
+        )]
+        NameConflict { full_name: BString, conflicting: BString },
+    }
+}
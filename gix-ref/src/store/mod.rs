@@ -1,5 +1,11 @@
 ///
 pub mod file;
 
+///
+pub mod memory;
+
 ///
 pub mod packed;
+
+///
+pub mod reftable;
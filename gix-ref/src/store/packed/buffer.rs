@@ -1,4 +1,4 @@
-use crate::store_impl::packed;
+use crate::{store_impl::packed, FullNameRef};
 
 impl AsRef<[u8]> for packed::Buffer {
     fn as_ref(&self) -> &[u8] {
@@ -15,6 +15,24 @@ impl AsRef<[u8]> for packed::Backing {
     }
 }
 
+/// Access
+impl packed::Buffer {
+    /// Return the peeling information the packed-refs file declared in its header.
+    pub fn peeled(&self) -> packed::Peeled {
+        self.peeled
+    }
+
+    /// Return `true` if the peeled value of the reference with `name` is known, i.e. if a reference without a
+    /// [peeled object](packed::Reference::object) is guaranteed not to point to an annotated tag.
+    pub fn knows_peeled_value(&self, name: &FullNameRef) -> bool {
+        match self.peeled {
+            packed::Peeled::Fully => true,
+            packed::Peeled::Partial => name.as_bstr().starts_with(b"refs/tags/"),
+            packed::Peeled::Unspecified => false,
+        }
+    }
+}
+
 ///
 pub mod open {
     use std::path::PathBuf;
@@ -26,19 +44,20 @@ pub mod open {
     /// Initialization
     impl packed::Buffer {
         fn open_with_backing(backing: packed::Backing, path: PathBuf) -> Result<Self, Error> {
-            let (backing, offset) = {
-                let (offset, sorted) = {
+            let (backing, offset, peeled) = {
+                let (offset, header) = {
                     let mut input = backing.as_ref();
                     if *input.first().unwrap_or(&b' ') == b'#' {
                         let header = packed::decode::header::<()>
                             .parse_next(&mut input)
                             .map_err(|_| Error::HeaderParsing)?;
                         let offset = input.offset_from(&backing.as_ref());
-                        (offset, header.sorted)
+                        (offset, header)
                     } else {
-                        (0, false)
+                        (0, Default::default())
                     }
                 };
+                let packed::decode::Header { peeled, sorted } = header;
 
                 if !sorted {
                     // this implementation is likely slower than what git does, but it's less code, too.
@@ -56,14 +75,15 @@ pub mod open {
                             serialized.push(b'\n');
                         }
                     }
-                    (Backing::InMemory(serialized), 0)
+                    (Backing::InMemory(serialized), 0, peeled)
                 } else {
-                    (backing, offset)
+                    (backing, offset, peeled)
                 }
             };
             Ok(packed::Buffer {
                 offset,
                 data: backing,
+                peeled,
                 path,
             })
         }
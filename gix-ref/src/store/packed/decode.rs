@@ -8,20 +8,13 @@ use winnow::{
 
 use crate::{
     parse::{hex_hash, newline},
-    store_impl::packed,
+    store_impl::{packed, packed::Peeled},
 };
 
-#[derive(Debug, PartialEq, Eq)]
-enum Peeled {
-    Unspecified,
-    Partial,
-    Fully,
-}
-
 /// Information parsed from the header of a packed ref file
 #[derive(Debug, PartialEq, Eq)]
 pub struct Header {
-    peeled: Peeled,
+    pub peeled: Peeled,
     pub sorted: bool,
 }
 
@@ -54,7 +47,7 @@ where
             for token in traits.as_bstr().split_str(b" ") {
                 if token == b"fully-peeled" {
                     peeled = Peeled::Fully;
-                } else if token == b"peeled" {
+                } else if token == b"peeled" && peeled != Peeled::Fully {
                     peeled = Peeled::Partial;
                 } else if token == b"sorted" {
                     sorted = true;
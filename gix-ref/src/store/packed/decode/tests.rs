@@ -62,10 +62,7 @@ mod header {
     use winnow::prelude::*;
 
     use super::Result;
-    use crate::store_impl::packed::{
-        decode,
-        decode::{Header, Peeled},
-    };
+    use crate::store_impl::packed::{decode, decode::Header, Peeled};
 
     #[test]
     fn invalid() {
@@ -101,6 +98,23 @@ mod header {
         Ok(())
     }
 
+    #[test]
+    fn valid_fully_peeled_in_any_order() -> Result {
+        let input: &[u8] = b"# pack-refs with: sorted fully-peeled peeled\n";
+        let (rest, header) = decode::header::<()>.parse_peek(input).unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(
+            header,
+            Header {
+                peeled: Peeled::Fully,
+                sorted: true
+            },
+            "the strongest peeling trait wins"
+        );
+        Ok(())
+    }
+
     #[test]
     fn valid_peeled_unsorted() -> Result {
         let input: &[u8] = b"# pack-refs with: peeled\n";
@@ -57,6 +57,17 @@ impl packed::Buffer {
         }
     }
 
+    /// Return `true` if a reference with exactly the given `name` is contained, without parsing its record.
+    ///
+    /// Just like [`try_find()`](Self::try_find()), it doesn't deal with namespaces or special prefixes.
+    pub fn contains(&self, name: &FullNameRef) -> Result<bool, Error> {
+        match self.binary_search_by(name.as_bstr()) {
+            Ok(_) => Ok(true),
+            Err((true, _)) => Err(Error::Parse),
+            Err((false, _)) => Ok(false),
+        }
+    }
+
     /// Find a reference with the given `name` and return it.
     pub fn find<'a, Name, E>(&self, name: Name) -> Result<packed::Reference<'_>, existing::Error>
     where
@@ -72,38 +83,58 @@ impl packed::Buffer {
 
     /// Perform a binary search where `Ok(pos)` is the beginning of the line that matches `name` perfectly and `Err(pos)`
     /// is the beginning of the line at which `name` could be inserted to still be in sort order.
+    ///
+    /// Only the names of the records visited during the search are extracted, without fully parsing or validating them,
+    /// which is left to the caller. Malformed records are considered to sort before `full_name`, and are indicated in the error.
     pub(in crate::store_impl::packed) fn binary_search_by(&self, full_name: &BStr) -> Result<usize, (bool, usize)> {
         let a = self.as_ref();
-        let search_start_of_record = |ofs: usize| {
-            a[..ofs]
-                .rfind(b"\n")
-                .and_then(|pos| {
-                    let candidate = pos + 1;
-                    a.get(candidate).and_then(|b| {
-                        if *b == b'^' {
-                            a[..pos].rfind(b"\n").map(|pos| pos + 1)
-                        } else {
-                            Some(candidate)
-                        }
-                    })
-                })
-                .unwrap_or(0)
+        let line_start = |ofs: usize| a[..ofs].rfind_byte(b'\n').map_or(0, |pos| pos + 1);
+        let line_end = |ofs: usize| a[ofs..].find_byte(b'\n').map_or(a.len(), |pos| ofs + pos + 1);
+        let start_of_record = |ofs: usize| {
+            let start = line_start(ofs);
+            if start > 0 && a[start] == b'^' {
+                line_start(start - 1)
+            } else {
+                start
+            }
         };
+        let end_of_record = |start: usize| {
+            let end = line_end(start);
+            if a.get(end) == Some(&b'^') {
+                line_end(end)
+            } else {
+                end
+            }
+        };
+        let name_of_record = |start: usize| {
+            let line = &a[start..line_end(start)];
+            let (target, name) = line.split_at(line.find_byte(b' ')?);
+            let is_hex_hash = (gix_hash::Kind::shortest().len_in_hex()..=gix_hash::Kind::longest().len_in_hex())
+                .contains(&target.len())
+                && target.iter().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+            if !is_hex_hash {
+                return None;
+            }
+            let name = &name[1..];
+            let name = name.strip_suffix(b"\n").unwrap_or(name);
+            Some(name.strip_suffix(b"\r").unwrap_or(name))
+        };
+
         let mut encountered_parse_failure = false;
-        a.binary_search_by_key(&full_name.as_ref(), |b: &u8| {
-            let ofs = b as *const u8 as usize - a.as_ptr() as usize;
-            let mut line = &a[search_start_of_record(ofs)..];
-            packed::decode::reference::<()>
-                .parse_next(&mut line)
-                .map(|r| r.name.as_bstr().as_bytes())
-                .map_err(|err| {
-                    encountered_parse_failure = true;
-                    err
-                })
-                .unwrap_or(&[])
-        })
-        .map(search_start_of_record)
-        .map_err(|pos| (encountered_parse_failure, search_start_of_record(pos)))
+        let (mut lo, mut hi) = (0, a.len());
+        while lo < hi {
+            let start = start_of_record(lo + (hi - lo) / 2);
+            let name = name_of_record(start).unwrap_or_else(|| {
+                encountered_parse_failure = true;
+                &[]
+            });
+            match name.cmp(full_name.as_bytes()) {
+                std::cmp::Ordering::Equal => return Ok(start),
+                std::cmp::Ordering::Less => lo = end_of_record(start),
+                std::cmp::Ordering::Greater => hi = start,
+            }
+        }
+        Err((encountered_parse_failure, lo))
     }
 }
 
@@ -14,14 +14,29 @@ enum Backing {
     Mapped(Mmap),
 }
 
+/// The peeling information a packed-ref file claims to contain, as declared in the traits of its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Peeled {
+    /// Nothing is known about peeled references, so references without a peeled value may still point to annotated tags.
+    Unspecified,
+    /// References in `refs/tags/` that point to annotated tags have their peeled value stored, and nothing is known
+    /// about all other references.
+    Partial,
+    /// All references that point to annotated tags have their peeled value stored.
+    Fully,
+}
+
 /// A buffer containing a packed-ref file that is either memory mapped or fully in-memory depending on a cutoff.
 ///
 /// The buffer is guaranteed to be sorted as per the packed-ref rules which allows some operations to be more efficient.
+/// Lookups are performed with a binary search, and [iteration](Buffer::iter()) parses references only as they are needed.
 #[derive(Debug)]
 pub struct Buffer {
     data: Backing,
     /// The offset to the first record, how many bytes to skip past the header
     offset: usize,
+    /// The peeling information as declared in the header
+    peeled: Peeled,
     /// The path from which we were loaded
     path: PathBuf,
 }
@@ -38,9 +53,13 @@ pub(crate) struct Transaction {
     lock: Option<gix_lock::File>,
     #[allow(dead_code)] // It just has to be kept alive, hence no reads
     closed_lock: Option<gix_lock::Marker>,
+    /// The peeled values of existing references that didn't have them, to be written if the file is rewritten
+    peeled_existing: std::collections::BTreeMap<crate::FullName, ObjectId>,
     precompose_unicode: bool,
     /// The namespace to use when preparing or writing refs
     namespace: Option<Namespace>,
+    /// How to sync the file before committing it, if at all.
+    fsync: Option<gix_fs::fsync::Method>,
 }
 
 /// A reference as parsed from the `packed-refs` file
@@ -81,7 +100,7 @@ pub struct Iter<'a> {
     prefix: Option<BString>,
 }
 
-mod decode;
+pub(crate) mod decode;
 
 ///
 pub mod iter;
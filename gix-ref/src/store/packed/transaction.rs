@@ -1,11 +1,14 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::{fmt::Formatter, io::Write};
 
+use gix_hash::ObjectId;
+
 use crate::{
     file,
     store_impl::{packed, packed::Edit},
     transaction::{Change, RefEdit},
-    Namespace, Target,
+    FullName, Namespace, Target,
 };
 
 pub(crate) const HEADER_LINE: &[u8] = b"# pack-refs with: peeled fully-peeled sorted \n";
@@ -17,14 +20,17 @@ impl packed::Transaction {
         lock: gix_lock::File,
         precompose_unicode: bool,
         namespace: Option<Namespace>,
+        fsync: Option<gix_fs::fsync::Method>,
     ) -> Self {
         packed::Transaction {
             buffer,
             edits: None,
             lock: Some(lock),
             closed_lock: None,
+            peeled_existing: Default::default(),
             precompose_unicode,
             namespace,
+            fsync,
         }
     }
 }
@@ -106,27 +112,7 @@ impl packed::Transaction {
                 ..
             } = edit.inner.change
             {
-                let mut next_id = new;
-                edit.peeled = loop {
-                    let kind = objects.try_find(&next_id, &mut buf)?.map(|d| d.kind);
-                    match kind {
-                        Some(gix_object::Kind::Tag) => {
-                            next_id = gix_object::TagRefIter::from_bytes(&buf).target_id().map_err(|_| {
-                                prepare::Error::Resolve(
-                                    format!("Couldn't get target object id from tag {next_id}").into(),
-                                )
-                            })?;
-                        }
-                        Some(_) => {
-                            break if next_id == new { None } else { Some(next_id) };
-                        }
-                        None => {
-                            return Err(prepare::Error::Resolve(
-                                format!("Couldn't find object with id {next_id}").into(),
-                            ))
-                        }
-                    }
-                };
+                edit.peeled = peel(new, objects, &mut buf)?;
             }
         }
 
@@ -141,6 +127,21 @@ impl packed::Transaction {
             // NOTE that we don't do any additional checks here but apply all edits unconditionally.
             // This is because this transaction system is internal and will be used correctly from the
             // loose ref store transactions, which do the necessary checking.
+
+            // We always write fully peeled files, so existing references of which we don't know the peeled value
+            // have to be peeled now. Like git, we ignore references that can't be peeled as they might be broken.
+            if let Some(buffer) = self.buffer().filter(|b| b.peeled() != packed::Peeled::Fully) {
+                let mut peeled_existing = BTreeMap::new();
+                for pref in buffer.iter()?.filter_map(Result::ok) {
+                    if pref.object.is_some() || buffer.knows_peeled_value(pref.name) {
+                        continue;
+                    }
+                    if let Ok(Some(peeled)) = peel(pref.target(), objects, &mut buf) {
+                        peeled_existing.insert(pref.name.to_owned(), peeled);
+                    }
+                }
+                self.peeled_existing = peeled_existing;
+            }
         }
         self.edits = Some(edits);
         Ok(self)
@@ -183,7 +184,7 @@ impl packed::Transaction {
                 (Some(Ok(_)), None) => {
                     let pref = refs_sorted.next().expect("next").expect("no err");
                     num_written_lines += 1;
-                    file.with_mut(|out| write_packed_ref(out, pref))?;
+                    file.with_mut(|out| write_packed_ref(out, pref, &self.peeled_existing))?;
                 }
                 (Some(Ok(pref)), Some(edit)) => {
                     use std::cmp::Ordering::*;
@@ -191,7 +192,7 @@ impl packed::Transaction {
                         Less => {
                             let pref = refs_sorted.next().expect("next").expect("valid");
                             num_written_lines += 1;
-                            file.with_mut(|out| write_packed_ref(out, pref))?;
+                            file.with_mut(|out| write_packed_ref(out, pref, &self.peeled_existing))?;
                         }
                         Greater => {
                             let edit = peekable_sorted_edits.next().expect("next");
@@ -211,26 +212,62 @@ impl packed::Transaction {
             }
         }
 
+        let path = file.resource_path().to_owned();
         if num_written_lines == 0 {
-            std::fs::remove_file(file.resource_path())?;
+            std::fs::remove_file(&path)?;
         } else {
+            if let Some(method) = self.fsync {
+                file.with_mut(|f| gix_fs::fsync::file(f, method))?;
+            }
             file.commit()?;
         }
+        if let Some(method) = self.fsync {
+            gix_fs::fsync::directory(path.parent().expect("packed-refs are in a directory"), method)?;
+        }
         drop(refs_sorted);
         Ok(())
     }
 }
 
-fn write_packed_ref(out: &mut dyn std::io::Write, pref: packed::Reference<'_>) -> std::io::Result<()> {
+fn write_packed_ref(
+    out: &mut dyn std::io::Write,
+    pref: packed::Reference<'_>,
+    peeled_existing: &BTreeMap<FullName, ObjectId>,
+) -> std::io::Result<()> {
     write!(out, "{} ", pref.target)?;
     out.write_all(pref.name.as_bstr())?;
     out.write_all(b"\n")?;
     if let Some(object) = pref.object {
         writeln!(out, "^{object}")?;
+    } else if let Some(object) = peeled_existing.get(pref.name) {
+        writeln!(out, "^{object}")?;
     }
     Ok(())
 }
 
+/// Peel `id` until the first object that isn't a tag is found, and return it if it's not `id` itself.
+fn peel(id: ObjectId, objects: &dyn gix_object::Find, buf: &mut Vec<u8>) -> Result<Option<ObjectId>, prepare::Error> {
+    let mut next_id = id;
+    loop {
+        let kind = objects.try_find(&next_id, buf)?.map(|d| d.kind);
+        match kind {
+            Some(gix_object::Kind::Tag) => {
+                next_id = gix_object::TagRefIter::from_bytes(buf).target_id().map_err(|_| {
+                    prepare::Error::Resolve(format!("Couldn't get target object id from tag {next_id}").into())
+                })?;
+            }
+            Some(_) => {
+                break Ok(if next_id == id { None } else { Some(next_id) });
+            }
+            None => {
+                return Err(prepare::Error::Resolve(
+                    format!("Couldn't find object with id {next_id}").into(),
+                ))
+            }
+        }
+    }
+}
+
 fn write_edit(out: &mut dyn std::io::Write, edit: &Edit, lines_written: &mut i32) -> std::io::Result<()> {
     match edit.inner.change {
         Change::Delete { .. } => {}
@@ -250,6 +287,9 @@ fn write_edit(out: &mut dyn std::io::Write, edit: &Edit, lines_written: &mut i32
             new: Target::Symbolic(_),
             ..
         } => unreachable!("BUG: packed refs cannot contain symbolic refs, catch that in prepare(…)"),
+        Change::Rename { .. } | Change::Copy { .. } => {
+            unreachable!("BUG: renames and copies were split into two edits")
+        }
     }
     Ok(())
 }
@@ -260,6 +300,7 @@ pub(crate) fn buffer_into_transaction(
     lock_mode: gix_lock::acquire::Fail,
     precompose_unicode: bool,
     namespace: Option<Namespace>,
+    fsync: Option<gix_fs::fsync::Method>,
 ) -> Result<packed::Transaction, gix_lock::acquire::Error> {
     let lock = gix_lock::File::acquire_to_update_resource(&buffer.path, lock_mode, None)?;
     Ok(packed::Transaction {
@@ -267,8 +308,10 @@ pub(crate) fn buffer_into_transaction(
         lock: Some(lock),
         closed_lock: None,
         edits: None,
+        peeled_existing: Default::default(),
         precompose_unicode,
         namespace,
+        fsync,
     })
 }
 
@@ -282,6 +325,8 @@ pub mod prepare {
         CloseLock(#[from] std::io::Error),
         #[error("The lookup of an object failed while peeling it")]
         Resolve(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error("The existing packed references could not be iterated")]
+        Iter(#[from] crate::packed::iter::Error),
     }
 }
 
@@ -0,0 +1,148 @@
+use gix_object::bstr::{BString, ByteSlice};
+
+pub use error::Error;
+
+use crate::{
+    store_impl::reftable::{stack::Stack, Store},
+    FullNameRef, PartialNameRef, Reference,
+};
+
+/// Finding references
+impl Store {
+    /// Find a single reference by the given `partial` name, which is required to be a valid reference name.
+    ///
+    /// Returns `Ok(None)` if no such ref exists.
+    ///
+    /// The lookup algorithm follows the one in [the git documentation][git-lookup-docs], just like
+    /// [`file::Store::try_find()`](crate::file::Store::try_find()).
+    ///
+    /// [git-lookup-docs]: https://github.com/git/git/blob/5d5b1473453400224ebb126bf3947e0a3276bdf5/Documentation/revisions.txt#L34-L46
+    pub fn try_find<'a, Name, E>(&self, partial: Name) -> Result<Option<Reference>, Error>
+    where
+        Name: TryInto<&'a PartialNameRef, Error = E>,
+        Error: From<E>,
+    {
+        let partial = partial.try_into()?;
+        let stack = self.stack()?;
+        Ok(self.find_in_stack(&stack, partial))
+    }
+
+    /// Similar to [`Store::try_find()`], but a non-existing ref is treated as error.
+    pub fn find<'a, Name, E>(&self, partial: Name) -> Result<Reference, existing::Error>
+    where
+        Name: TryInto<&'a PartialNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        let partial = partial.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        match self.try_find(partial) {
+            Ok(Some(r)) => Ok(r),
+            Ok(None) => Err(existing::Error::NotFound {
+                name: partial.as_bstr().to_owned(),
+            }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Return `true` if the reference with the exact `name` exists, like [`file::Store::contains()`](crate::file::Store::contains()).
+    pub fn contains(&self, name: &FullNameRef) -> Result<bool, Error> {
+        let stack = self.stack()?;
+        Ok(match &self.namespace {
+            Some(namespace) => stack.contains(namespace.to_owned().into_namespaced_name(name).as_ref()),
+            None => stack.contains(name),
+        })
+    }
+
+    /// Return all references sorted by name, or all references within our namespace if one is set.
+    pub fn iter(&self) -> Result<std::vec::IntoIter<Reference>, Error> {
+        self.iter_prefixed(Default::default())
+    }
+
+    /// Return all references whose name starts with `prefix`, sorted by name, like `refs/heads/`.
+    ///
+    /// If a namespace is set, the prefix is relative to the namespace and all returned names have it stripped.
+    pub fn iter_prefixed(&self, prefix: BString) -> Result<std::vec::IntoIter<Reference>, Error> {
+        let stack = self.stack()?;
+        let prefix = match &self.namespace {
+            Some(namespace) => {
+                let mut full_prefix = namespace.as_bstr().to_owned();
+                full_prefix.extend_from_slice(&prefix);
+                full_prefix
+            }
+            None => prefix,
+        };
+        Ok(stack
+            .references_prefixed(prefix.as_bstr())
+            .map(|mut r| {
+                if let Some(namespace) = &self.namespace {
+                    r.strip_namespace(namespace);
+                }
+                r
+            })
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+
+    pub(crate) fn find_in_stack(&self, stack: &Stack, partial: &PartialNameRef) -> Option<Reference> {
+        let mut buf = BString::default();
+        for inbetween in &["", "tags", "heads", "remotes"] {
+            if let Some(r) = self.find_full_name_in_stack(stack, partial.construct_full_name_ref(inbetween, &mut buf)) {
+                return Some(r);
+            }
+        }
+        if partial.as_bstr() != "HEAD" {
+            let partial = partial.to_owned().join("HEAD".into()).ok()?;
+            return self.find_full_name_in_stack(stack, partial.as_ref().construct_full_name_ref("remotes", &mut buf));
+        }
+        None
+    }
+
+    pub(crate) fn find_full_name_in_stack(&self, stack: &Stack, name: &FullNameRef) -> Option<Reference> {
+        match &self.namespace {
+            Some(namespace) => {
+                let mut r = stack.try_find_full_name(namespace.to_owned().into_namespaced_name(name).as_ref())?;
+                r.strip_namespace(namespace);
+                Some(r)
+            }
+            None => stack.try_find_full_name(name),
+        }
+    }
+}
+
+mod error {
+    use std::convert::Infallible;
+
+    /// The error returned by [`Store::try_find()`](crate::reftable::Store::try_find()) and related methods.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The ref name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error("The reftable stack could not be loaded")]
+        Load(#[from] crate::reftable::stack::load::Error),
+    }
+
+    impl From<Infallible> for Error {
+        fn from(_: Infallible) -> Self {
+            unreachable!("this impl is needed to allow passing a known valid partial path as parameter")
+        }
+    }
+}
+
+///
+pub mod existing {
+    pub use error::Error;
+
+    mod error {
+        use gix_object::bstr::BString;
+
+        /// The error returned by [`Store::find()`](crate::reftable::Store::find()).
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("An error occurred while finding a reference in the reftable")]
+            Find(#[from] crate::reftable::find::Error),
+            #[error("The ref partially named {name:?} could not be found")]
+            NotFound { name: BString },
+        }
+    }
+}
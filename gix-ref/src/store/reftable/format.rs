@@ -0,0 +1,273 @@
+//! Low-level building blocks of the reftable format, as described in
+//! [the git documentation](https://git-scm.com/docs/reftable).
+
+/// The magic bytes every table starts with.
+pub const MAGIC: &[u8; 4] = b"REFT";
+/// The hash identifier of SHA-1 in version 2 tables.
+pub const HASH_ID_SHA1: u32 = u32::from_be_bytes(*b"sha1");
+/// The hash identifier of SHA-256 in version 2 tables.
+pub const HASH_ID_SHA256: u32 = u32::from_be_bytes(*b"s256");
+
+/// The type of a block containing reference records.
+pub const BLOCK_TYPE_REF: u8 = b'r';
+/// The type of a block containing log records.
+pub const BLOCK_TYPE_LOG: u8 = b'g';
+/// The type of a block containing object records.
+pub const BLOCK_TYPE_OBJ: u8 = b'o';
+/// The type of a block containing index records.
+pub const BLOCK_TYPE_INDEX: u8 = b'i';
+
+/// The size of the block header, i.e. the block type and the 24 bit block length.
+pub const BLOCK_HEADER_LEN: usize = 4;
+/// How many records are written before a restart point is placed, i.e. a record with its full key.
+pub const RESTART_INTERVAL: usize = 16;
+/// The block size used by `git` unless configured otherwise.
+pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+/// The largest block size the 24 bit block size field can represent.
+pub const MAX_BLOCK_SIZE: usize = (1 << 24) - 1;
+
+/// Value types of reference records.
+pub mod ref_value {
+    /// The reference was deleted.
+    pub const DELETION: u8 = 0;
+    /// The reference points to an object.
+    pub const VAL1: u8 = 1;
+    /// The reference points to an object which peels to another object.
+    pub const VAL2: u8 = 2;
+    /// The reference is a symbolic reference.
+    pub const SYMREF: u8 = 3;
+}
+
+/// Value types of log records.
+pub mod log_value {
+    /// The log entry was deleted.
+    pub const DELETION: u8 = 0;
+    /// The log entry records an update.
+    pub const UPDATE: u8 = 1;
+}
+
+/// The information stored at the beginning of each table, and again in its footer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// The format version, 1 for SHA-1 only tables and 2 for tables which specify their hash.
+    pub version: u8,
+    /// The size of padded blocks, or 0 if blocks are unaligned.
+    pub block_size: u32,
+    /// The smallest update index of all records in the table.
+    pub min_update_index: u64,
+    /// The largest update index of all records in the table.
+    pub max_update_index: u64,
+    /// The kind of hash used for all object ids in the table.
+    pub object_hash: gix_hash::Kind,
+}
+
+impl Header {
+    /// Return the amount of bytes the header occupies in the file.
+    pub fn size(&self) -> usize {
+        header_size(self.version)
+    }
+
+    /// Return the amount of bytes the footer occupies in the file.
+    pub fn footer_size(&self) -> usize {
+        self.size() + 5 * 8 + 4
+    }
+
+    /// Decode a header from the beginning of `data`, or return `None` if it's not a header.
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        if data.len() < header_size(1) || &data[..4] != MAGIC {
+            return Err("file doesn't start with the reftable magic");
+        }
+        let version = data[4];
+        if !matches!(version, 1 | 2) {
+            return Err("unsupported reftable version");
+        }
+        if data.len() < header_size(version) {
+            return Err("header is truncated");
+        }
+        let object_hash = if version == 1 {
+            gix_hash::Kind::Sha1
+        } else {
+            match be_u32(&data[24..28]) {
+                HASH_ID_SHA1 => gix_hash::Kind::Sha1,
+                HASH_ID_SHA256 => return Err("SHA-256 isn't supported"),
+                _ => return Err("unknown hash id"),
+            }
+        };
+        Ok(Header {
+            version,
+            block_size: be_u24(&data[5..8]),
+            min_update_index: be_u64(&data[8..16]),
+            max_update_index: be_u64(&data[16..24]),
+            object_hash,
+        })
+    }
+
+    /// Append the encoded header to `out`.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(MAGIC);
+        out.push(self.version);
+        put_u24(out, self.block_size);
+        out.extend_from_slice(&self.min_update_index.to_be_bytes());
+        out.extend_from_slice(&self.max_update_index.to_be_bytes());
+        if self.version == 2 {
+            let id = match self.object_hash {
+                gix_hash::Kind::Sha1 => HASH_ID_SHA1,
+            };
+            out.extend_from_slice(&id.to_be_bytes());
+        }
+    }
+}
+
+/// Return the size of the header of a table with `version`.
+pub fn header_size(version: u8) -> usize {
+    match version {
+        1 => 24,
+        _ => 28,
+    }
+}
+
+/// The information stored at the end of each table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Footer {
+    /// The position of the ref index, or 0 if there is none.
+    pub ref_index_position: u64,
+    /// The position of the first object block, or 0 if there is none.
+    pub obj_position: u64,
+    /// The length of abbreviated object ids in object blocks.
+    pub obj_id_len: u8,
+    /// The position of the object index, or 0 if there is none.
+    pub obj_index_position: u64,
+    /// The position of the first log block, or 0 if there is none or if it's the first block in the table.
+    pub log_position: u64,
+    /// The position of the log index, or 0 if there is none.
+    pub log_index_position: u64,
+}
+
+impl Footer {
+    /// Decode the footer at the end of `data`, which must repeat `header`.
+    pub fn decode(data: &[u8], header: &Header) -> Result<Self, &'static str> {
+        let footer_size = header.footer_size();
+        if data.len() < header.size() + footer_size {
+            return Err("table is too short to contain a footer");
+        }
+        let footer = &data[data.len() - footer_size..];
+        let (content, checksum) = footer.split_at(footer_size - 4);
+        if crc32(content) != be_u32(checksum) {
+            return Err("footer checksum mismatch");
+        }
+        if Header::decode(content)? != *header {
+            return Err("footer doesn't repeat the header");
+        }
+        let positions = &content[header.size()..];
+        let obj = be_u64(&positions[8..16]);
+        Ok(Footer {
+            ref_index_position: be_u64(&positions[..8]),
+            obj_position: obj >> 5,
+            obj_id_len: (obj & 0x1f) as u8,
+            obj_index_position: be_u64(&positions[16..24]),
+            log_position: be_u64(&positions[24..32]),
+            log_index_position: be_u64(&positions[32..40]),
+        })
+    }
+
+    /// Append the encoded footer, including a copy of `header` and the checksum, to `out`.
+    pub fn encode(&self, header: &Header, out: &mut Vec<u8>) {
+        let start = out.len();
+        header.encode(out);
+        out.extend_from_slice(&self.ref_index_position.to_be_bytes());
+        out.extend_from_slice(&((self.obj_position << 5) | u64::from(self.obj_id_len)).to_be_bytes());
+        out.extend_from_slice(&self.obj_index_position.to_be_bytes());
+        out.extend_from_slice(&self.log_position.to_be_bytes());
+        out.extend_from_slice(&self.log_index_position.to_be_bytes());
+        let checksum = crc32(&out[start..]);
+        out.extend_from_slice(&checksum.to_be_bytes());
+    }
+}
+
+/// Decode a variable length integer from the beginning of `input` and advance it past the integer.
+///
+/// The encoding is the same as the one used for offsets of `ofs-delta` objects in packs.
+pub fn decode_varint(input: &mut &[u8]) -> Option<u64> {
+    let (&first, rest) = input.split_first()?;
+    *input = rest;
+    let mut value = u64::from(first & 0x7f);
+    let mut byte = first;
+    while byte & 0x80 != 0 {
+        let (&next, rest) = input.split_first()?;
+        *input = rest;
+        byte = next;
+        if value >= (u64::MAX >> 7) {
+            return None;
+        }
+        value = ((value + 1) << 7) | u64::from(byte & 0x7f);
+    }
+    Some(value)
+}
+
+/// Append `value` as variable length integer to `out`.
+pub fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 10];
+    let mut pos = buf.len() - 1;
+    buf[pos] = (value & 0x7f) as u8;
+    value >>= 7;
+    while value != 0 {
+        value -= 1;
+        pos -= 1;
+        buf[pos] = 0x80 | (value & 0x7f) as u8;
+        value >>= 7;
+    }
+    out.extend_from_slice(&buf[pos..]);
+}
+
+pub(crate) fn be_u24(data: &[u8]) -> u32 {
+    u32::from_be_bytes([0, data[0], data[1], data[2]])
+}
+
+pub(crate) fn put_u24(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes()[1..]);
+}
+
+pub(crate) fn set_u24(out: &mut [u8], value: u32) {
+    out.copy_from_slice(&value.to_be_bytes()[1..]);
+}
+
+pub(crate) fn be_u16(data: &[u8]) -> u16 {
+    u16::from_be_bytes([data[0], data[1]])
+}
+
+pub(crate) fn be_u32(data: &[u8]) -> u32 {
+    u32::from_be_bytes(data[..4].try_into().expect("4 bytes"))
+}
+
+pub(crate) fn be_u64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data[..8].try_into().expect("8 bytes"))
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    gix_features::hash::crc32(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_varint, encode_varint};
+
+    #[test]
+    fn varint_round_trip() {
+        for (value, expected) in [
+            (0u64, &[0x00][..]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x00]),
+            (255, &[0x80, 0x7f]),
+            (16511, &[0xff, 0x7f]),
+            (16512, &[0x80, 0x80, 0x00]),
+        ] {
+            let mut buf = Vec::new();
+            encode_varint(value, &mut buf);
+            assert_eq!(buf, expected, "{value} is encoded like in packs");
+            assert_eq!(decode_varint(&mut buf.as_slice()), Some(value));
+        }
+        let mut buf = Vec::new();
+        encode_varint(u64::MAX, &mut buf);
+        assert_eq!(decode_varint(&mut buf.as_slice()), Some(u64::MAX));
+    }
+}
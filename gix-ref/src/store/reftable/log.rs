@@ -0,0 +1,48 @@
+use crate::{
+    store_impl::reftable::{stack, Store},
+    FullNameRef,
+};
+
+/// Reflog access
+impl Store {
+    /// Return `true` if a reflog entry exists for the given reference `name`.
+    pub fn reflog_exists<'a, Name, E>(&self, name: Name) -> Result<bool, Error>
+    where
+        Name: TryInto<&'a FullNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        Ok(!self.reflog(name)?.is_empty())
+    }
+
+    /// Return all reflog entries of the reference with `name`, oldest first.
+    ///
+    /// The returned list is empty if there is no reflog.
+    pub fn reflog<'a, Name, E>(&self, name: Name) -> Result<Vec<crate::log::Line>, Error>
+    where
+        Name: TryInto<&'a FullNameRef, Error = E>,
+        crate::name::Error: From<E>,
+    {
+        let name = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        let stack = self.stack()?;
+        Ok(match &self.namespace {
+            Some(namespace) => stack
+                .reflog(namespace.to_owned().into_namespaced_name(name).as_ref())
+                .cloned()
+                .collect(),
+            None => stack.reflog(name).cloned().collect(),
+        })
+    }
+}
+
+mod error {
+    /// The error returned by [`Store::reflog()`](crate::reftable::Store::reflog()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reflog name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error("The reftable stack could not be loaded")]
+        Load(#[from] super::stack::load::Error),
+    }
+}
+pub use error::Error;
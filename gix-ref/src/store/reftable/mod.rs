@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use crate::{store::WriteReflog, Namespace};
+
+/// A store for references which keeps them in a stack of binary tables, as written by `git` if
+/// `extensions.refStorage` is set to `reftable`.
+///
+/// All tables live in the `reftable/` directory within the `.git` directory, and the `reftable/tables.list` file
+/// lists the tables of the stack from oldest to newest. Records in newer tables shadow records of the same name in older ones.
+#[derive(Debug, Clone)]
+pub struct Store {
+    /// The `.git` directory whose `reftable/` directory contains the tables.
+    git_dir: PathBuf,
+    /// The kind of hash to expect in all tables, and to use when writing new ones.
+    object_hash: gix_hash::Kind,
+
+    /// The way to handle reflog edits
+    pub write_reflog: WriteReflog,
+    /// The namespace to use for edits and reads
+    pub namespace: Option<Namespace>,
+}
+
+/// Initialization
+impl Store {
+    /// Create a new instance at the given `git_dir`, which must contain a `reftable/` directory for references to be found.
+    ///
+    /// Only [`write_reflog`](crate::store::init::Options::write_reflog) and [`object_hash`](crate::store::init::Options::object_hash)
+    /// are used from `opts`.
+    pub fn at(git_dir: PathBuf, opts: crate::store::init::Options) -> Self {
+        Store {
+            git_dir,
+            object_hash: opts.object_hash,
+            write_reflog: opts.write_reflog,
+            namespace: None,
+        }
+    }
+}
+
+/// Access
+impl Store {
+    /// Return the `.git` directory at which all references are loaded.
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// Return the directory containing all tables along with the `tables.list` file.
+    pub fn reftable_dir(&self) -> PathBuf {
+        self.git_dir.join("reftable")
+    }
+
+    /// Return the path to the file listing all tables of the stack, oldest first.
+    pub fn tables_list_path(&self) -> PathBuf {
+        self.reftable_dir().join("tables.list")
+    }
+}
+
+/// A transaction on a reftable store, writing all of its edits into a single new table.
+pub struct Transaction<'s> {
+    store: &'s Store,
+    lock: Option<gix_lock::File>,
+    stack: Option<stack::Stack>,
+    updates: Option<Vec<transaction::Edit>>,
+}
+
+impl std::fmt::Debug for Transaction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transaction")
+            .field("store", self.store)
+            .field("edits", &self.updates.as_ref().map(Vec::len))
+            .finish_non_exhaustive()
+    }
+}
+
+///
+pub mod format;
+
+///
+pub mod table;
+
+///
+pub mod stack;
+
+///
+pub mod find;
+
+///
+pub mod log;
+
+mod peel;
+
+///
+pub mod transaction;
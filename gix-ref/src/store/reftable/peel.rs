@@ -0,0 +1,69 @@
+use std::collections::BTreeSet;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    peel,
+    store_impl::reftable::{find::existing, Store},
+    Reference, Target,
+};
+
+/// Peeling
+impl Store {
+    /// Follow all symbolic targets of `reference` until it points to an object, altering it in place, and return that object,
+    /// just like [`file::ReferenceExt::follow_to_object_in_place_packed()`](crate::file::ReferenceExt::follow_to_object_in_place_packed())
+    /// does for the file store.
+    pub fn follow_to_object_in_place(&self, reference: &mut Reference) -> Result<ObjectId, peel::to_object::Error> {
+        if let Target::Object(id) = reference.target {
+            return Ok(id);
+        }
+        let stack = self.stack().map_err(|err| existing::Error::Find(err.into()))?;
+        let mut seen = BTreeSet::new();
+        while let Target::Symbolic(name) = &reference.target {
+            let next = self
+                .find_full_name_in_stack(&stack, name.as_ref())
+                .ok_or_else(|| existing::Error::NotFound {
+                    name: name.as_bstr().to_owned(),
+                })?;
+            if seen.contains(&next.name) {
+                return Err(peel::to_object::Error::Cycle {
+                    start_absolute: self.reftable_dir().join(reference.name.to_path()),
+                });
+            }
+            *reference = next;
+            seen.insert(reference.name.clone());
+            const MAX_REF_DEPTH: usize = 5;
+            if seen.len() == MAX_REF_DEPTH {
+                return Err(peel::to_object::Error::DepthLimitExceeded {
+                    max_depth: MAX_REF_DEPTH,
+                });
+            }
+        }
+        Ok(reference.target.try_id().expect("peeled ref").to_owned())
+    }
+
+    /// Follow all symbolic targets of `reference` and peel the object it ultimately points to until the first non-tag object,
+    /// using `objects` to access them, and return it, just like
+    /// [`file::ReferenceExt::peel_to_id_in_place()`](crate::file::ReferenceExt::peel_to_id_in_place()) does for the file store.
+    ///
+    /// The result is cached in `reference`, and `objects` isn't used if the table already stores the peeled object.
+    pub fn peel_to_id_in_place(
+        &self,
+        reference: &mut Reference,
+        objects: &dyn gix_object::Find,
+    ) -> Result<ObjectId, peel::to_id::Error> {
+        let peeled_id = match reference.peeled {
+            Some(peeled) => peeled,
+            None => {
+                let oid = self.follow_to_object_in_place(reference)?;
+                match reference.peeled {
+                    Some(peeled) => peeled,
+                    None => peel::to_first_non_tag(oid, reference.name.as_ref(), objects)?,
+                }
+            }
+        };
+        reference.peeled = Some(peeled_id);
+        reference.target = Target::Object(peeled_id);
+        Ok(peeled_id)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use gix_object::bstr::{BStr, BString, ByteSlice};
+
+use crate::{
+    store_impl::reftable::{
+        table::{LogRecord, LogValue, RefRecord, RefValue, Table},
+        Store,
+    },
+    FullName, FullNameRef, Reference, Target,
+};
+
+/// A snapshot of all tables of a reftable stack, merged so that only the newest record of each reference is visible.
+#[derive(Debug, Clone, Default)]
+pub struct Stack {
+    tables: Vec<BString>,
+    refs: BTreeMap<BString, RefRecord>,
+    logs: BTreeMap<(BString, u64), crate::log::Line>,
+    max_update_index: u64,
+}
+
+///
+pub mod load {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Store::stack()`](crate::reftable::Store::stack()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read reftable file at '{}'", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+        #[error("The table at '{}' could not be decoded", path.display())]
+        Decode {
+            source: crate::reftable::table::decode::Error,
+            path: PathBuf,
+        },
+        #[error("The table at '{}' uses object hash {actual}, but {expected} was expected", path.display())]
+        ObjectHash {
+            path: PathBuf,
+            actual: gix_hash::Kind,
+            expected: gix_hash::Kind,
+        },
+    }
+}
+
+impl Store {
+    /// Read all tables of the stack and merge them into a snapshot.
+    ///
+    /// If there is no `tables.list` file, the stack is empty.
+    pub fn stack(&self) -> Result<Stack, load::Error> {
+        let list_path = self.tables_list_path();
+        let list = match std::fs::read(&list_path) {
+            Ok(list) => list,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Stack::default()),
+            Err(source) => {
+                return Err(load::Error::Io {
+                    source,
+                    path: list_path,
+                })
+            }
+        };
+        let mut stack = Stack::default();
+        for name in list.lines().filter(|line| !line.is_empty()) {
+            let path = self.reftable_dir().join(gix_path::from_byte_slice(name));
+            let data = std::fs::read(&path).map_err(|source| load::Error::Io {
+                source,
+                path: path.clone(),
+            })?;
+            let table = Table::from_bytes(data).map_err(|source| load::Error::Decode {
+                source,
+                path: path.clone(),
+            })?;
+            if table.header.object_hash != self.object_hash {
+                return Err(load::Error::ObjectHash {
+                    path,
+                    actual: table.header.object_hash,
+                    expected: self.object_hash,
+                });
+            }
+            let decode_err = |source| load::Error::Decode {
+                source,
+                path: path.clone(),
+            };
+            stack.add(
+                table.header.max_update_index,
+                table.refs().map_err(decode_err)?,
+                table.logs().map_err(decode_err)?,
+            );
+            stack.tables.push(name.into());
+        }
+        stack.refs.retain(|_, record| record.value != RefValue::Deletion);
+        Ok(stack)
+    }
+}
+
+impl Stack {
+    /// Add the records of a table that is newer than all previously added ones.
+    fn add(&mut self, max_update_index: u64, refs: Vec<RefRecord>, logs: Vec<LogRecord>) {
+        self.max_update_index = self.max_update_index.max(max_update_index);
+        for record in refs {
+            self.refs.insert(record.name.clone(), record);
+        }
+        for record in logs {
+            let key = (record.name, record.update_index);
+            match record.value {
+                LogValue::Deletion => {
+                    self.logs.remove(&key);
+                }
+                LogValue::Update(line) => {
+                    self.logs.insert(key, line);
+                }
+            }
+        }
+    }
+}
+
+/// Access
+impl Stack {
+    /// Return the names of all tables in the stack, oldest first.
+    pub fn tables(&self) -> &[BString] {
+        &self.tables
+    }
+
+    /// Return the largest update index of all tables, or 0 if there are none.
+    pub fn max_update_index(&self) -> u64 {
+        self.max_update_index
+    }
+
+    /// Return the reference with the exact `name`, if it exists.
+    pub fn try_find_full_name(&self, name: &FullNameRef) -> Option<Reference> {
+        self.refs.get(name.as_bstr()).map(to_reference)
+    }
+
+    /// Return `true` if a reference with the exact `name` exists.
+    pub fn contains(&self, name: &FullNameRef) -> bool {
+        self.refs.contains_key(name.as_bstr())
+    }
+
+    /// Return all references sorted by name.
+    pub fn references(&self) -> impl Iterator<Item = Reference> + '_ {
+        self.refs.values().map(to_reference)
+    }
+
+    /// Return all references whose name starts with `prefix`, sorted by name.
+    pub fn references_prefixed<'a>(&'a self, prefix: &'a BStr) -> impl Iterator<Item = Reference> + 'a {
+        self.refs
+            .range::<BStr, _>((std::ops::Bound::Included(prefix), std::ops::Bound::Unbounded))
+            .take_while(move |(name, _)| name.starts_with(prefix))
+            .map(|(_, record)| to_reference(record))
+    }
+
+    /// Return all reflog entries of the reference with `name`, oldest first, or an empty iterator if there is no reflog.
+    pub fn reflog(&self, name: &FullNameRef) -> impl DoubleEndedIterator<Item = &crate::log::Line> + '_ {
+        let name = name.as_bstr().to_owned();
+        self.logs
+            .range((name.clone(), 0)..=(name, u64::MAX))
+            .map(|(_, line)| line)
+    }
+
+    /// Return the update indices of all reflog entries of the reference with `name`, oldest first.
+    pub(crate) fn reflog_update_indices(&self, name: &BStr) -> impl Iterator<Item = u64> + '_ {
+        let name = name.to_owned();
+        self.logs
+            .range((name.clone(), 0)..=(name, u64::MAX))
+            .map(|((_, update_index), _)| *update_index)
+    }
+}
+
+fn to_reference(record: &RefRecord) -> Reference {
+    let (target, peeled) = match &record.value {
+        RefValue::Object(id) => (Target::Object(*id), None),
+        RefValue::Peeled { target, peeled } => (Target::Object(*target), Some(*peeled)),
+        RefValue::Symbolic(name) => (Target::Symbolic(FullName(name.clone())), None),
+        RefValue::Deletion => unreachable!("BUG: deletions are removed when loading the stack"),
+    };
+    Reference {
+        name: FullName(record.name.clone()),
+        target,
+        peeled,
+    }
+}
@@ -0,0 +1,535 @@
+use std::io::Write;
+
+use gix_hash::ObjectId;
+use gix_object::bstr::{BString, ByteSlice};
+
+use crate::{
+    log::Line,
+    store_impl::reftable::format::{self, decode_varint, encode_varint, Footer, Header},
+};
+
+/// A record of a reference in a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefRecord {
+    /// The full name of the reference.
+    pub name: BString,
+    /// The update index of the transaction that wrote this record.
+    pub update_index: u64,
+    /// The value of the reference.
+    pub value: RefValue,
+}
+
+/// The value of a [`RefRecord`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefValue {
+    /// The reference was deleted, hiding all of its records in older tables.
+    Deletion,
+    /// The reference points to the given object.
+    Object(ObjectId),
+    /// The reference points to the annotated tag `target`, which peels to `peeled`.
+    Peeled {
+        /// The object the reference points to.
+        target: ObjectId,
+        /// The object `target` ultimately peels to.
+        peeled: ObjectId,
+    },
+    /// The reference is a symbolic reference pointing to the reference of the given name.
+    Symbolic(BString),
+}
+
+/// A record of a reflog entry in a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// The full name of the reference the reflog belongs to.
+    pub name: BString,
+    /// The update index of the transaction that wrote this entry, unique per reference.
+    pub update_index: u64,
+    /// The value of the log entry.
+    pub value: LogValue,
+}
+
+/// The value of a [`LogRecord`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogValue {
+    /// The log entry was deleted, hiding the entry of the same update index in older tables.
+    Deletion,
+    /// The reflog entry itself.
+    Update(Line),
+}
+
+///
+pub mod decode {
+    /// The error returned by [`Table::from_bytes()`](super::Table::from_bytes()) and the methods to read its records.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reftable is corrupt at offset {offset}: {message}")]
+        Corrupt { offset: usize, message: &'static str },
+    }
+}
+
+/// A single table of a reftable stack, fully loaded into memory.
+pub struct Table {
+    /// The header of the table.
+    pub header: Header,
+    /// The footer of the table.
+    pub footer: Footer,
+    data: Vec<u8>,
+}
+
+/// Lifecycle
+impl Table {
+    /// Parse the header and footer of the table in `data`.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, decode::Error> {
+        let header = Header::decode(&data).map_err(|message| decode::Error::Corrupt { offset: 0, message })?;
+        let footer = Footer::decode(&data, &header).map_err(|message| decode::Error::Corrupt {
+            offset: data.len().saturating_sub(header.footer_size()),
+            message,
+        })?;
+        Ok(Table { header, footer, data })
+    }
+}
+
+/// Access
+impl Table {
+    /// Return all reference records in the order they are stored, i.e. sorted by name.
+    pub fn refs(&self) -> Result<Vec<RefRecord>, decode::Error> {
+        let mut out = Vec::new();
+        let mut offset = 0;
+        while let Some((header_off, block_type)) = self.block_at(offset) {
+            if block_type != format::BLOCK_TYPE_REF {
+                break;
+            }
+            let block_len = self.block_len(offset, header_off)?;
+            let block = &self.data[offset..offset + block_len];
+            for_each_record(block, header_off, offset, |key, value_type, mut value| {
+                let corrupt = |message| decode::Error::Corrupt { offset, message };
+                let update_index_delta = decode_varint(&mut value).ok_or_else(|| corrupt("invalid update index"))?;
+                let record_value = match value_type {
+                    format::ref_value::DELETION => RefValue::Deletion,
+                    format::ref_value::VAL1 => RefValue::Object(self.read_id(&mut value, offset)?),
+                    format::ref_value::VAL2 => RefValue::Peeled {
+                        target: self.read_id(&mut value, offset)?,
+                        peeled: self.read_id(&mut value, offset)?,
+                    },
+                    format::ref_value::SYMREF => {
+                        let len = decode_varint(&mut value).ok_or_else(|| corrupt("invalid symref length"))? as usize;
+                        let target = value.get(..len).ok_or_else(|| corrupt("symref target is truncated"))?;
+                        value = &value[len..];
+                        RefValue::Symbolic(target.into())
+                    }
+                    _ => return Err(corrupt("unknown reference value type")),
+                };
+                out.push(RefRecord {
+                    name: key.into(),
+                    update_index: self.header.min_update_index + update_index_delta,
+                    value: record_value,
+                });
+                Ok(value)
+            })?;
+            offset += match self.header.block_size {
+                0 => block_len,
+                block_size => block_size as usize,
+            };
+        }
+        Ok(out)
+    }
+
+    /// Return all log records in the order they are stored, i.e. sorted by name and by descending update index.
+    pub fn logs(&self) -> Result<Vec<LogRecord>, decode::Error> {
+        let mut out = Vec::new();
+        let mut offset = match self.footer.log_position {
+            0 if self.block_at(0).map(|(_, block_type)| block_type) == Some(format::BLOCK_TYPE_LOG) => 0,
+            0 => return Ok(out),
+            position => position as usize,
+        };
+        let mut inflate = gix_features::zlib::Inflate::default();
+        while let Some((header_off, block_type)) = self.block_at(offset) {
+            if block_type != format::BLOCK_TYPE_LOG {
+                break;
+            }
+            let corrupt = |message| decode::Error::Corrupt { offset, message };
+            let block_len = format::be_u24(&self.data[offset + header_off + 1..]) as usize;
+            let uncompressed_start = header_off + format::BLOCK_HEADER_LEN;
+            if block_len < uncompressed_start {
+                return Err(corrupt("log block is too short"));
+            }
+            let mut block = self.data[offset..offset + uncompressed_start].to_vec();
+            block.resize(block_len + 1, 0);
+            let compressed = &self.data[offset + uncompressed_start..self.data.len() - self.header.footer_size()];
+            inflate.reset();
+            let (mut consumed_in, mut consumed_out) = (0, 0);
+            loop {
+                let (status, read, written) = inflate
+                    .once(
+                        &compressed[consumed_in..],
+                        &mut block[uncompressed_start + consumed_out..],
+                    )
+                    .map_err(|_| corrupt("could not decompress log block"))?;
+                consumed_in += read;
+                consumed_out += written;
+                match status {
+                    gix_features::zlib::Status::StreamEnd => break,
+                    _ if read == 0 && written == 0 => return Err(corrupt("log block is truncated")),
+                    _ => {}
+                }
+            }
+            if uncompressed_start + consumed_out != block_len {
+                return Err(corrupt("log block has unexpected length"));
+            }
+            block.truncate(block_len);
+
+            for_each_record(&block, header_off, offset, |key, value_type, mut value| {
+                let (name, update_index) = match key.len().checked_sub(9) {
+                    Some(name_len) if key[name_len] == 0 => {
+                        (&key[..name_len], u64::MAX - format::be_u64(&key[name_len + 1..]))
+                    }
+                    _ => return Err(corrupt("invalid log key")),
+                };
+                let record_value = match value_type {
+                    format::log_value::DELETION => LogValue::Deletion,
+                    format::log_value::UPDATE => {
+                        let previous_oid = self.read_id(&mut value, offset)?;
+                        let new_oid = self.read_id(&mut value, offset)?;
+                        let name = read_string(&mut value).ok_or_else(|| corrupt("invalid log name"))?;
+                        let email = read_string(&mut value).ok_or_else(|| corrupt("invalid log email"))?;
+                        let seconds = decode_varint(&mut value).ok_or_else(|| corrupt("invalid log time"))?;
+                        let tz_offset = value
+                            .get(..2)
+                            .map(|tz| i16::from_be_bytes([tz[0], tz[1]]))
+                            .ok_or_else(|| corrupt("invalid log timezone"))?;
+                        value = &value[2..];
+                        let mut message = read_string(&mut value).ok_or_else(|| corrupt("invalid log message"))?;
+                        if message.last() == Some(&b'\n') {
+                            message.pop();
+                        }
+                        LogValue::Update(Line {
+                            previous_oid,
+                            new_oid,
+                            signature: gix_actor::Signature {
+                                name,
+                                email,
+                                time: gix_actor::date::Time {
+                                    seconds: seconds as gix_actor::date::SecondsSinceUnixEpoch,
+                                    offset: decimal_tz_to_seconds(tz_offset),
+                                    sign: if tz_offset < 0 {
+                                        gix_actor::date::time::Sign::Minus
+                                    } else {
+                                        gix_actor::date::time::Sign::Plus
+                                    },
+                                },
+                            },
+                            message,
+                        })
+                    }
+                    _ => return Err(corrupt("unknown log value type")),
+                };
+                out.push(LogRecord {
+                    name: name.into(),
+                    update_index,
+                    value: record_value,
+                });
+                Ok(value)
+            })?;
+            offset += uncompressed_start + consumed_in;
+        }
+        Ok(out)
+    }
+
+    /// Return the offset of the block type relative to `offset`, and the block type, for the block at `offset`,
+    /// or `None` if the table ends there.
+    fn block_at(&self, offset: usize) -> Option<(usize, u8)> {
+        let header_off = if offset == 0 { self.header.size() } else { 0 };
+        let end = self.data.len() - self.header.footer_size();
+        (offset + header_off + format::BLOCK_HEADER_LEN <= end).then(|| (header_off, self.data[offset + header_off]))
+    }
+
+    fn block_len(&self, offset: usize, header_off: usize) -> Result<usize, decode::Error> {
+        let block_len = format::be_u24(&self.data[offset + header_off + 1..]) as usize;
+        if block_len < header_off + format::BLOCK_HEADER_LEN + 2
+            || offset + block_len > self.data.len() - self.header.footer_size()
+        {
+            return Err(decode::Error::Corrupt {
+                offset,
+                message: "invalid block length",
+            });
+        }
+        Ok(block_len)
+    }
+
+    fn read_id(&self, input: &mut &[u8], offset: usize) -> Result<ObjectId, decode::Error> {
+        let len = self.header.object_hash.len_in_bytes();
+        let id = input.get(..len).ok_or(decode::Error::Corrupt {
+            offset,
+            message: "object id is truncated",
+        })?;
+        *input = &input[len..];
+        Ok(ObjectId::from_bytes_or_panic(id))
+    }
+}
+
+/// `git` stores the timezone like it appears in signatures, i.e. `+0130` is stored as `130`.
+fn decimal_tz_to_seconds(tz: i16) -> i32 {
+    let tz = i32::from(tz);
+    (tz / 100 * 60 + tz % 100) * 60
+}
+
+fn seconds_to_decimal_tz(offset: i32) -> i16 {
+    let minutes = offset / 60;
+    (minutes / 60 * 100 + minutes % 60) as i16
+}
+
+fn read_string(input: &mut &[u8]) -> Option<BString> {
+    let len = decode_varint(input)? as usize;
+    let out = input.get(..len)?;
+    *input = &input[len..];
+    Some(out.into())
+}
+
+/// Call `parse(key, value_type, value)` for each record in the uncompressed `block` whose type starts at `header_off`,
+/// with `parse` returning the remaining, unconsumed `value` bytes.
+fn for_each_record<'a>(
+    block: &'a [u8],
+    header_off: usize,
+    offset: usize,
+    mut parse: impl FnMut(&[u8], u8, &'a [u8]) -> Result<&'a [u8], decode::Error>,
+) -> Result<(), decode::Error> {
+    let corrupt = |message| decode::Error::Corrupt { offset, message };
+    let restart_count = format::be_u16(&block[block.len() - 2..]) as usize;
+    let records_end = (block.len() - 2)
+        .checked_sub(restart_count * 3)
+        .filter(|end| *end >= header_off + format::BLOCK_HEADER_LEN)
+        .ok_or_else(|| corrupt("invalid restart count"))?;
+    let mut input = &block[header_off + format::BLOCK_HEADER_LEN..records_end];
+    let mut key = Vec::new();
+    while !input.is_empty() {
+        let prefix_len = decode_varint(&mut input).ok_or_else(|| corrupt("invalid key prefix length"))? as usize;
+        let suffix_and_type = decode_varint(&mut input).ok_or_else(|| corrupt("invalid key suffix length"))?;
+        let suffix_len = (suffix_and_type >> 3) as usize;
+        if prefix_len > key.len() || suffix_len > input.len() {
+            return Err(corrupt("invalid key"));
+        }
+        key.truncate(prefix_len);
+        key.extend_from_slice(&input[..suffix_len]);
+        input = parse(&key, (suffix_and_type & 0x7) as u8, &input[suffix_len..])?;
+    }
+    Ok(())
+}
+
+/// Encode a complete table with the given `refs`, sorted by name, and `logs`, sorted by name and descending update index,
+/// using `min_update_index` and `max_update_index` for the header.
+pub fn encode(
+    object_hash: gix_hash::Kind,
+    min_update_index: u64,
+    max_update_index: u64,
+    refs: &[RefRecord],
+    logs: &[LogRecord],
+) -> Vec<u8> {
+    let ref_records: Vec<_> = refs
+        .iter()
+        .map(|record| {
+            let mut value = Vec::new();
+            encode_varint(record.update_index - min_update_index, &mut value);
+            let value_type = match &record.value {
+                RefValue::Deletion => format::ref_value::DELETION,
+                RefValue::Object(id) => {
+                    value.extend_from_slice(id.as_slice());
+                    format::ref_value::VAL1
+                }
+                RefValue::Peeled { target, peeled } => {
+                    value.extend_from_slice(target.as_slice());
+                    value.extend_from_slice(peeled.as_slice());
+                    format::ref_value::VAL2
+                }
+                RefValue::Symbolic(target) => {
+                    encode_varint(target.len() as u64, &mut value);
+                    value.extend_from_slice(target);
+                    format::ref_value::SYMREF
+                }
+            };
+            (record.name.to_vec(), value_type, value)
+        })
+        .collect();
+    let log_records: Vec<_> = logs
+        .iter()
+        .map(|record| {
+            let mut key = record.name.to_vec();
+            key.push(0);
+            key.extend_from_slice(&(u64::MAX - record.update_index).to_be_bytes());
+            let mut value = Vec::new();
+            let value_type = match &record.value {
+                LogValue::Deletion => format::log_value::DELETION,
+                LogValue::Update(line) => {
+                    value.extend_from_slice(line.previous_oid.as_slice());
+                    value.extend_from_slice(line.new_oid.as_slice());
+                    for field in [&line.signature.name, &line.signature.email] {
+                        encode_varint(field.len() as u64, &mut value);
+                        value.extend_from_slice(field);
+                    }
+                    encode_varint(line.signature.time.seconds.max(0) as u64, &mut value);
+                    value.extend_from_slice(&seconds_to_decimal_tz(line.signature.time.offset).to_be_bytes());
+                    let mut message = line.message.trim_end().to_vec();
+                    message.push(b'\n');
+                    encode_varint(message.len() as u64, &mut value);
+                    value.extend_from_slice(&message);
+                    format::log_value::UPDATE
+                }
+            };
+            (key, value_type, value)
+        })
+        .collect();
+
+    let header = Header {
+        version: 1,
+        block_size: block_size_for(&ref_records, &log_records, object_hash) as u32,
+        min_update_index,
+        max_update_index,
+        object_hash,
+    };
+    let block_size = header.block_size as usize;
+    let mut out = Vec::new();
+    let mut file_header = Vec::new();
+    header.encode(&mut file_header);
+
+    let mut block = BlockWriter::new(format::BLOCK_TYPE_REF, Some(&file_header));
+    for (key, value_type, value) in &ref_records {
+        if !block.add(key, *value_type, value, block_size) {
+            block.finish_into(&mut out, Some(block_size));
+            block = BlockWriter::new(format::BLOCK_TYPE_REF, None);
+            assert!(
+                block.add(key, *value_type, value, block_size),
+                "BUG: block size fits every record"
+            );
+        }
+    }
+    if block.entries != 0 {
+        block.finish_into(&mut out, Some(block_size));
+    }
+
+    let mut footer = Footer::default();
+    if !log_records.is_empty() {
+        footer.log_position = out.len() as u64;
+        let mut block = BlockWriter::new(format::BLOCK_TYPE_LOG, out.is_empty().then_some(file_header.as_slice()));
+        for (key, value_type, value) in &log_records {
+            if !block.add(key, *value_type, value, block_size) {
+                block.finish_into(&mut out, None);
+                block = BlockWriter::new(format::BLOCK_TYPE_LOG, None);
+                assert!(
+                    block.add(key, *value_type, value, block_size),
+                    "BUG: block size fits every record"
+                );
+            }
+        }
+        block.finish_into(&mut out, None);
+    }
+    if out.is_empty() {
+        out.extend_from_slice(&file_header);
+    }
+    footer.encode(&header, &mut out);
+    out
+}
+
+/// Choose a block size that fits each of the given records, which is the default block size unless
+/// records are very large.
+fn block_size_for(
+    refs: &[(Vec<u8>, u8, Vec<u8>)],
+    logs: &[(Vec<u8>, u8, Vec<u8>)],
+    object_hash: gix_hash::Kind,
+) -> usize {
+    // file header, block header, restart point and restart count, and the varints of each record.
+    let overhead = format::header_size(if object_hash == gix_hash::Kind::Sha1 { 1 } else { 2 })
+        + format::BLOCK_HEADER_LEN
+        + 3
+        + 2
+        + 2 * 10;
+    let largest_record = refs
+        .iter()
+        .chain(logs)
+        .map(|(key, _, value)| key.len() + value.len())
+        .max()
+        .unwrap_or_default();
+    (largest_record + overhead)
+        .next_power_of_two()
+        .clamp(format::DEFAULT_BLOCK_SIZE, format::MAX_BLOCK_SIZE)
+}
+
+struct BlockWriter {
+    buf: Vec<u8>,
+    header_off: usize,
+    block_type: u8,
+    restarts: Vec<u32>,
+    entries: usize,
+    last_key: Vec<u8>,
+}
+
+impl BlockWriter {
+    fn new(block_type: u8, file_header: Option<&[u8]>) -> Self {
+        let mut buf = file_header.map(<[u8]>::to_vec).unwrap_or_default();
+        let header_off = buf.len();
+        buf.extend_from_slice(&[block_type, 0, 0, 0]);
+        BlockWriter {
+            buf,
+            header_off,
+            block_type,
+            restarts: Vec::new(),
+            entries: 0,
+            last_key: Vec::new(),
+        }
+    }
+
+    /// Add the record with `key` if it fits into a block of `block_size`, and return `false` otherwise.
+    fn add(&mut self, key: &[u8], value_type: u8, value: &[u8], block_size: usize) -> bool {
+        let is_restart = self.entries % format::RESTART_INTERVAL == 0;
+        let prefix_len = if is_restart {
+            0
+        } else {
+            key.iter().zip(&self.last_key).take_while(|(a, b)| a == b).count()
+        };
+        let mut record = Vec::with_capacity(key.len() + value.len() + 4);
+        encode_varint(prefix_len as u64, &mut record);
+        encode_varint(
+            (((key.len() - prefix_len) as u64) << 3) | u64::from(value_type),
+            &mut record,
+        );
+        record.extend_from_slice(&key[prefix_len..]);
+        record.extend_from_slice(value);
+
+        let restarts = self.restarts.len() + usize::from(is_restart);
+        if self.buf.len() + record.len() + 3 * restarts + 2 > block_size || restarts > usize::from(u16::MAX) {
+            return false;
+        }
+        if is_restart {
+            self.restarts.push(self.buf.len() as u32);
+        }
+        self.buf.extend_from_slice(&record);
+        self.entries += 1;
+        self.last_key.clear();
+        self.last_key.extend_from_slice(key);
+        true
+    }
+
+    /// Finish the block and write it to `out`, padded to `block_size` if set, or compressed if it's a log block.
+    fn finish_into(mut self, out: &mut Vec<u8>, block_size: Option<usize>) {
+        for restart in &self.restarts {
+            format::put_u24(&mut self.buf, *restart);
+        }
+        self.buf.extend_from_slice(&(self.restarts.len() as u16).to_be_bytes());
+        let block_len = self.buf.len() as u32;
+        let header_end = self.header_off + format::BLOCK_HEADER_LEN;
+        format::set_u24(&mut self.buf[self.header_off + 1..header_end], block_len);
+
+        if self.block_type == format::BLOCK_TYPE_LOG {
+            out.extend_from_slice(&self.buf[..header_end]);
+            let mut deflate = gix_features::zlib::stream::deflate::Write::new(out);
+            deflate
+                .write_all(&self.buf[header_end..])
+                .and_then(|_| deflate.flush())
+                .expect("writing to memory never fails");
+        } else {
+            out.extend_from_slice(&self.buf);
+            if let Some(block_size) = block_size {
+                out.resize(out.len() + block_size - self.buf.len(), 0);
+            }
+        }
+    }
+}
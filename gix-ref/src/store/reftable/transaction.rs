@@ -0,0 +1,607 @@
+use gix_hash::ObjectId;
+use gix_object::bstr::{BString, ByteSlice};
+
+use crate::{
+    store::WriteReflog,
+    store_impl::reftable::{
+        table::{self, LogRecord, LogValue, RefRecord, RefValue},
+        Store, Transaction,
+    },
+    transaction::{Change, PreviousValue, RefEdit, RefEditsExt, RefLog},
+    FullName, FullNameRef, Target,
+};
+
+#[derive(Debug)]
+pub(in crate::store_impl::reftable) struct Edit {
+    update: RefEdit,
+    /// Set if this update is coming from a symbolic reference and used to make it appear like it is the one that is handled,
+    /// instead of the referent reference.
+    parent_index: Option<usize>,
+    /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
+    /// peeled value of the leaf referent.
+    leaf_referent_previous_oid: Option<ObjectId>,
+    /// Set if this update creates a reference by renaming or copying the reference with the given name, whose reflog
+    /// is moved or copied to this reference.
+    origin: Option<FullName>,
+}
+
+impl Edit {
+    fn name(&self) -> BString {
+        self.update.name.0.clone()
+    }
+}
+
+impl std::borrow::Borrow<RefEdit> for Edit {
+    fn borrow(&self) -> &RefEdit {
+        &self.update
+    }
+}
+
+impl std::borrow::BorrowMut<RefEdit> for Edit {
+    fn borrow_mut(&mut self) -> &mut RefEdit {
+        &mut self.update
+    }
+}
+
+/// Edits
+impl Store {
+    /// Open a transaction which will write all of its edits into a single new table on top of the stack.
+    ///
+    /// The transaction inherits the parent namespace.
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            store: self,
+            lock: None,
+            stack: None,
+            updates: None,
+        }
+    }
+}
+
+impl Transaction<'_> {
+    /// Prepare for calling [`commit(…)`](Transaction::commit()) by locking the stack and checking all `edits` against
+    /// the current state of references, using `lock_fail_mode` to determine how to wait for the lock.
+    ///
+    /// If the operation succeeds, the transaction can be committed or dropped to cause a rollback automatically.
+    pub fn prepare(
+        mut self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+    ) -> Result<Self, prepare::Error> {
+        assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
+        let store = self.store;
+        let lock = gix_lock::File::acquire_to_update_resource(
+            store.tables_list_path(),
+            lock_fail_mode,
+            Some(store.git_dir.clone()),
+        )?;
+        let stack = store.stack()?;
+
+        let mut updates: Vec<_> = edits
+            .into_iter()
+            .map(|update| Edit {
+                update,
+                parent_index: None,
+                leaf_referent_previous_oid: None,
+                origin: None,
+            })
+            .collect();
+        let mut renames_and_copies = Vec::new();
+        for edit in &mut updates {
+            let (Change::Rename {
+                new_name,
+                new_name_expected,
+                ..
+            }
+            | Change::Copy {
+                new_name,
+                new_name_expected,
+                ..
+            }) = &edit.update.change
+            else {
+                continue;
+            };
+            let current = store
+                .find_full_name_in_stack(&stack, edit.update.name.as_ref())
+                .ok_or_else(|| prepare::Error::DeleteReferenceMustExist { full_name: edit.name() })?;
+            store.assure_no_name_conflict(&stack, new_name.as_ref())?;
+            if *new_name_expected == PreviousValue::MustNotExist {
+                // Unlike updates, renames and copies never overwrite an existing reference, even if it has the same value.
+                if let Some(existing) = store.find_full_name_in_stack(&stack, new_name.as_ref()) {
+                    return Err(prepare::Error::MustNotExist {
+                        full_name: new_name.as_bstr().to_owned(),
+                        actual: existing.target,
+                        new: current.target,
+                    });
+                }
+            }
+            renames_and_copies.push(Edit {
+                update: edit
+                    .update
+                    .split_rename_or_copy(&current.target)
+                    .expect("a rename or copy"),
+                parent_index: None,
+                leaf_referent_previous_oid: None,
+                origin: Some(edit.update.name.clone()),
+            });
+        }
+        updates.extend(renames_and_copies);
+        updates
+            .pre_process(
+                &mut |name| store.find_in_stack(&stack, name).map(|r| r.target),
+                &mut |idx, update| Edit {
+                    update,
+                    parent_index: Some(idx),
+                    leaf_referent_previous_oid: None,
+                    origin: None,
+                },
+            )
+            .map_err(prepare::Error::PreprocessingFailed)?;
+
+        for cid in 0..updates.len() {
+            let change = &mut updates[cid];
+            let existing = store.find_full_name_in_stack(&stack, change.update.name.as_ref());
+            check_and_record_previous_value(change, existing, store.object_hash)?;
+
+            // traverse parent chain from leaf/peeled ref and set the leaf previous oid accordingly
+            // to help with their reflog entries
+            if let (Some(crate::TargetRef::Object(oid)), Some(parent_idx)) =
+                (change.update.change.previous_value(), change.parent_index)
+            {
+                let oid = oid.to_owned();
+                let mut parent_idx_cursor = Some(parent_idx);
+                while let Some(parent) = parent_idx_cursor.take().map(|idx| &mut updates[idx]) {
+                    parent_idx_cursor = parent.parent_index;
+                    parent.leaf_referent_previous_oid = Some(oid);
+                }
+            }
+        }
+        self.lock = Some(lock);
+        self.stack = Some(stack);
+        self.updates = Some(updates);
+        Ok(self)
+    }
+
+    /// Make all [prepared](Transaction::prepare()) edits permanent by writing them into a new table and adding it to the stack,
+    /// and return the performed edits, which are adjusted just like the ones returned by
+    /// [`file::Transaction::commit()`](crate::file::Transaction::commit()).
+    ///
+    /// `committer` is used in the reflog and only if the reflog is actually written, which is why it is optional.
+    /// As all edits are written at once, a missing `committer` aborts the transaction without changing anything.
+    pub fn commit<'a>(
+        mut self,
+        committer: impl Into<Option<gix_actor::SignatureRef<'a>>>,
+    ) -> Result<Vec<RefEdit>, commit::Error> {
+        let committer = committer.into();
+        let updates = self.updates.take().expect("BUG: must call prepare before commit");
+        let stack = self.stack.take().expect("prepared");
+        let mut lock = self.lock.take().expect("prepared");
+        let store = self.store;
+
+        let update_index = stack.max_update_index() + 1;
+        let mut refs = Vec::new();
+        let mut logs = Vec::new();
+        for change in &updates {
+            assert!(!change.update.deref, "Deref mode is turned into splits and turned off");
+            let name = match &store.namespace {
+                Some(namespace) => namespace.to_owned().into_namespaced_name(change.update.name.as_ref()),
+                None => change.update.name.clone(),
+            };
+            match &change.update.change {
+                Change::Update { log, new, expected } => {
+                    if log.mode == RefLog::AndReference {
+                        refs.push(RefRecord {
+                            name: name.0.clone(),
+                            update_index,
+                            value: match new {
+                                Target::Object(id) => RefValue::Object(*id),
+                                Target::Symbolic(target) => RefValue::Symbolic(target.0.clone()),
+                            },
+                        });
+                    }
+                    let origin = change.origin.as_ref().map(|source| match &store.namespace {
+                        Some(namespace) => namespace.to_owned().into_namespaced_name(source.as_ref()),
+                        None => source.clone(),
+                    });
+                    let mut transferred_reflog = false;
+                    if let Some(source) = &origin {
+                        // Copy the reflog entries, and replace the reflog that might exist already. The source reflog of renames
+                        // is deleted along with the source reference.
+                        let copied_indices: Vec<_> = stack.reflog_update_indices(source.as_bstr()).collect();
+                        logs.extend(
+                            stack
+                                .reflog_update_indices(name.as_bstr())
+                                .filter(|update_index| !copied_indices.contains(update_index))
+                                .map(|update_index| LogRecord {
+                                    name: name.0.clone(),
+                                    update_index,
+                                    value: LogValue::Deletion,
+                                }),
+                        );
+                        logs.extend(copied_indices.iter().zip(stack.reflog(source.as_ref())).map(
+                            |(update_index, line)| LogRecord {
+                                name: name.0.clone(),
+                                update_index: *update_index,
+                                value: LogValue::Update(line.clone()),
+                            },
+                        ));
+                        transferred_reflog = !copied_indices.is_empty();
+                    }
+                    let log_update = match new {
+                        // No reflog for symref changes, unless the ref is new and we know its peeled id, just like the file store.
+                        Target::Symbolic(_) => match expected {
+                            PreviousValue::ExistingMustMatch(Target::Object(oid)) => {
+                                Some((Some(ObjectId::null(oid.kind())), oid))
+                            }
+                            _ => None,
+                        },
+                        // Renames and copies keep the value, and git records them as such.
+                        Target::Object(new_oid) if origin.is_some() => Some((Some(*new_oid), new_oid)),
+                        Target::Object(new_oid) => {
+                            let previous = match expected {
+                                PreviousValue::MustExistAndMatch(Target::Object(oid)) => Some(oid.to_owned()),
+                                _ => None,
+                            }
+                            .or(change.leaf_referent_previous_oid);
+                            Some((previous, new_oid))
+                        }
+                    };
+                    let Some((previous, new_oid)) = log_update else {
+                        continue;
+                    };
+                    if (previous.as_ref() == Some(new_oid) && origin.is_none())
+                        || !(transferred_reflog || store.should_write_reflog(&stack, &name, log.force_create_reflog))
+                    {
+                        continue;
+                    }
+                    let committer = committer.ok_or(commit::Error::MissingCommitter)?;
+                    logs.push(LogRecord {
+                        name: name.0,
+                        update_index,
+                        value: LogValue::Update(crate::log::Line {
+                            previous_oid: previous.unwrap_or_else(|| new_oid.kind().null()),
+                            new_oid: *new_oid,
+                            signature: committer.to_owned(),
+                            message: log.message.clone(),
+                        }),
+                    });
+                }
+                Change::Delete { log, .. } => {
+                    if *log == RefLog::AndReference {
+                        refs.push(RefRecord {
+                            name: name.0.clone(),
+                            update_index,
+                            value: RefValue::Deletion,
+                        });
+                    }
+                    logs.extend(
+                        stack
+                            .reflog_update_indices(name.0.as_ref())
+                            .map(|update_index| LogRecord {
+                                name: name.0.clone(),
+                                update_index,
+                                value: LogValue::Deletion,
+                            }),
+                    );
+                }
+                Change::Rename { .. } | Change::Copy { .. } => {
+                    unreachable!("BUG: renames and copies were split into two edits")
+                }
+            }
+        }
+
+        if !refs.is_empty() || !logs.is_empty() {
+            refs.sort_by(|a, b| a.name.cmp(&b.name));
+            logs.sort_by(|a, b| a.name.cmp(&b.name).then(b.update_index.cmp(&a.update_index)));
+            let min_update_index = logs
+                .iter()
+                .map(|log| log.update_index)
+                .chain(Some(update_index))
+                .min()
+                .expect("at least one");
+            let data = table::encode(store.object_hash, min_update_index, update_index, &refs, &logs);
+
+            let table_name = format!(
+                "0x{min_update_index:012x}-0x{update_index:012x}-{:08x}.ref",
+                random_suffix()
+            );
+            let mut table_lock = gix_lock::File::acquire_to_update_resource(
+                store.reftable_dir().join(&table_name),
+                gix_lock::acquire::Fail::Immediately,
+                None,
+            )?;
+            table_lock.with_mut(|file| std::io::Write::write_all(file, &data))?;
+            table_lock.commit().map_err(|err| err.error)?;
+
+            let mut list = Vec::new();
+            for table in stack.tables() {
+                list.extend_from_slice(table);
+                list.push(b'\n');
+            }
+            list.extend_from_slice(table_name.as_bytes());
+            list.push(b'\n');
+            lock.with_mut(|file| std::io::Write::write_all(file, &list))?;
+            lock.commit().map_err(|err| err.error)?;
+        }
+
+        Ok(into_performed_edits(updates))
+    }
+
+    /// Return the edits as [`commit()`](Self::commit()) would return them, but without changing any reference,
+    /// releasing the lock on the stack.
+    pub fn commit_dry_run(mut self) -> Vec<RefEdit> {
+        into_performed_edits(self.updates.take().expect("BUG: must call prepare before commit"))
+    }
+
+    /// Return the edits as they will be committed, which might be more than the ones that were provided due to the splitting
+    /// of symbolic references, renames and copies, and whose `expected` values are the ones seen in the stack after locking it.
+    pub fn prepared_edits(&self) -> impl Iterator<Item = &RefEdit> + '_ {
+        self.updates.iter().flatten().map(|edit| &edit.update)
+    }
+
+    /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
+    ///
+    /// Note that they have been altered compared to what was initially provided as they have
+    /// been split and know about their current state on disk.
+    ///
+    /// # Note
+    ///
+    /// A rollback happens automatically as this instance is dropped as well.
+    pub fn rollback(self) -> Vec<RefEdit> {
+        self.updates
+            .map(|updates| updates.into_iter().map(|u| u.update).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn into_performed_edits(updates: Vec<Edit>) -> Vec<RefEdit> {
+    updates
+        .into_iter()
+        .map(|mut edit| {
+            // Existing references have their previous value set by now, so everything else didn't exist.
+            // Deletions can't expect a reference to not exist, so they are left without any expectation instead,
+            // just like the file store does.
+            match &mut edit.update.change {
+                Change::Update { expected, .. } if !matches!(expected, PreviousValue::MustExistAndMatch(_)) => {
+                    *expected = PreviousValue::MustNotExist;
+                }
+                Change::Delete { expected, .. } if !matches!(expected, PreviousValue::MustExistAndMatch(_)) => {
+                    *expected = PreviousValue::Any;
+                }
+                _ => {}
+            }
+            edit.update
+        })
+        .collect()
+}
+
+impl Store {
+    /// Fail if a reference named `name` can't be created as a reference exists whose name is a directory of `name`,
+    /// or which is inside of the directory that `name` would be, like `refs/heads/a` and `refs/heads/a/b`.
+    ///
+    /// Even though reftables could store both, this would make them incompatible with the files backend.
+    fn assure_no_name_conflict(&self, stack: &super::stack::Stack, name: &FullNameRef) -> Result<(), prepare::Error> {
+        let full_name = name.as_bstr();
+        // The first component, like `refs`, is always a directory.
+        for end in full_name.find_iter(b"/").skip(1) {
+            let parent = FullNameRef::new_unchecked(full_name[..end].as_bstr());
+            if self.find_full_name_in_stack(stack, parent).is_some() {
+                return Err(prepare::Error::NameConflict {
+                    full_name: full_name.to_owned(),
+                    conflicting: parent.as_bstr().to_owned(),
+                });
+            }
+        }
+        let mut prefix = match &self.namespace {
+            Some(namespace) => namespace.to_owned().into_namespaced_name(name).0,
+            None => full_name.to_owned(),
+        };
+        prefix.push(b'/');
+        if let Some(mut existing) = stack.references_prefixed(prefix.as_bstr()).next() {
+            if let Some(namespace) = &self.namespace {
+                existing.strip_namespace(namespace);
+            }
+            return Err(prepare::Error::NameConflict {
+                full_name: full_name.to_owned(),
+                conflicting: existing.name.0,
+            });
+        }
+        Ok(())
+    }
+
+    fn should_write_reflog(&self, stack: &super::stack::Stack, name: &FullName, force_create_reflog: bool) -> bool {
+        match self.write_reflog {
+            WriteReflog::Disable => false,
+            WriteReflog::Always => true,
+            WriteReflog::Normal => {
+                let name = name.as_bstr();
+                force_create_reflog
+                    || [&b"refs/heads/"[..], b"refs/remotes/", b"refs/notes/", b"refs/worktree/"]
+                        .iter()
+                        .any(|prefix| name.starts_with(prefix))
+                    || name == "HEAD"
+                    || stack.reflog_update_indices(name).next().is_some()
+            }
+        }
+    }
+}
+
+/// Check the expectations of `change` against the `existing` reference and record its previous value.
+fn check_and_record_previous_value(
+    change: &mut Edit,
+    existing: Option<crate::Reference>,
+    object_hash: gix_hash::Kind,
+) -> Result<(), prepare::Error> {
+    use prepare::Error;
+    let full_name = change.name();
+    match &mut change.update.change {
+        Change::Delete { expected, .. } => match (&expected, &existing) {
+            (PreviousValue::MustNotExist, _) => {
+                panic!("BUG: MustNotExist constraint makes no sense if references are to be deleted")
+            }
+            (PreviousValue::ExistingMustMatch(_) | PreviousValue::Any, None)
+            | (PreviousValue::MustExist | PreviousValue::Any, Some(_)) => {}
+            (
+                PreviousValue::MustExist
+                | PreviousValue::MustExistAndMatch(_)
+                | PreviousValue::SymbolicMustMatch { .. },
+                None,
+            ) => return Err(Error::DeleteReferenceMustExist { full_name }),
+            (
+                PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
+                Some(existing),
+            ) => {
+                if *previous != existing.target {
+                    return Err(Error::ReferenceOutOfDate {
+                        full_name,
+                        expected: previous.clone(),
+                        actual: existing.target.clone(),
+                    });
+                }
+            }
+            // The referent expectation only applies to dereferenced edits, which were split already.
+            (PreviousValue::SymbolicMustMatch { target, .. }, Some(existing)) => {
+                let expected = Target::Symbolic(target.clone());
+                if expected != existing.target {
+                    return Err(Error::ReferenceOutOfDate {
+                        full_name,
+                        expected,
+                        actual: existing.target.clone(),
+                    });
+                }
+            }
+        },
+        Change::Update { expected, new, .. } => match (&expected, &existing) {
+            (PreviousValue::Any, _)
+            | (PreviousValue::MustExist, Some(_))
+            | (PreviousValue::MustNotExist | PreviousValue::ExistingMustMatch(_), None) => {}
+            (PreviousValue::MustExist, None) => {
+                return Err(Error::MustExist {
+                    full_name,
+                    expected: Target::Object(object_hash.null()),
+                });
+            }
+            (PreviousValue::MustNotExist, Some(existing)) => {
+                if existing.target != *new {
+                    return Err(Error::MustNotExist {
+                        full_name,
+                        actual: existing.target.clone(),
+                        new: new.clone(),
+                    });
+                }
+            }
+            (
+                PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
+                Some(existing),
+            ) => {
+                if *previous != existing.target {
+                    return Err(Error::ReferenceOutOfDate {
+                        full_name,
+                        expected: previous.clone(),
+                        actual: existing.target.clone(),
+                    });
+                }
+            }
+            (PreviousValue::MustExistAndMatch(previous), None) => {
+                return Err(Error::MustExist {
+                    full_name,
+                    expected: previous.clone(),
+                });
+            }
+            (PreviousValue::SymbolicMustMatch { target, .. }, None) => {
+                return Err(Error::MustExist {
+                    full_name,
+                    expected: Target::Symbolic(target.clone()),
+                });
+            }
+            // The referent expectation only applies to dereferenced edits, which were split already.
+            (PreviousValue::SymbolicMustMatch { target, .. }, Some(existing)) => {
+                let expected = Target::Symbolic(target.clone());
+                if expected != existing.target {
+                    return Err(Error::ReferenceOutOfDate {
+                        full_name,
+                        expected,
+                        actual: existing.target.clone(),
+                    });
+                }
+            }
+        },
+        Change::Rename { .. } | Change::Copy { .. } => {
+            unreachable!("BUG: renames and copies were split into two edits")
+        }
+    }
+    // Keep the previous value for the caller and ourselves. Maybe they want to keep a log of sorts.
+    let (Change::Update { expected, .. }
+    | Change::Delete { expected, .. }
+    | Change::Rename { expected, .. }
+    | Change::Copy { expected, .. }) = &mut change.update.change;
+    if let Some(existing) = existing {
+        *expected = PreviousValue::MustExistAndMatch(existing.target);
+    }
+    Ok(())
+}
+
+fn random_suffix() -> u32 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()),
+    );
+    hasher.finish() as u32
+}
+
+///
+pub mod prepare {
+    use gix_object::bstr::BString;
+
+    use crate::Target;
+
+    /// The error returned by [`Transaction::prepare()`](crate::reftable::Transaction::prepare()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The lock for the reftable stack could not be obtained")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("The reftable stack could not be loaded")]
+        Load(#[from] crate::reftable::stack::load::Error),
+        #[error("Edit preprocessing failed with an error")]
+        PreprocessingFailed(#[source] std::io::Error),
+        #[error("The reference {full_name:?} for deletion did not exist")]
+        DeleteReferenceMustExist { full_name: BString },
+        #[error("Reference {full_name:?} was not supposed to exist when writing it with value {new:?}, but actual content was {actual:?}")]
+        MustNotExist {
+            full_name: BString,
+            actual: Target,
+            new: Target,
+        },
+        #[error("Reference {full_name:?} was supposed to exist with value {expected}, but didn't.")]
+        MustExist { full_name: BString, expected: Target },
+        #[error("The reference {full_name:?} should have content {expected}, actual content was {actual}")]
+        ReferenceOutOfDate {
+            full_name: BString,
+            expected: Target,
+            actual: Target,
+        },
+        #[error(
+            "The reference {full_name:?} can't be created as it conflicts with the existing reference {conflicting:?}"
+        )]
+        NameConflict { full_name: BString, conflicting: BString },
+    }
+}
+
+///
+pub mod commit {
+    /// The error returned by [`Transaction::commit()`](crate::reftable::Transaction::commit()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Reflog entries need a committer, but none was provided")]
+        MissingCommitter,
+        #[error("The lock for the new table could not be obtained")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("The new table could not be written or added to the stack")]
+        Io(#[from] std::io::Error),
+    }
+}
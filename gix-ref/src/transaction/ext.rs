@@ -84,7 +84,7 @@ where
                                 *mode = RefLog::Only;
                                 RefEdit {
                                     change: Change::Delete {
-                                        expected: previous.clone(),
+                                        expected: split_expectation(previous),
                                         log: current_mode,
                                     },
                                     name: referent,
@@ -100,7 +100,13 @@ where
                                         force_create_reflog: log.force_create_reflog,
                                     },
                                 );
-                                let next = std::mem::replace(expected, PreviousValue::Any);
+                                let next = match std::mem::replace(expected, PreviousValue::Any) {
+                                    PreviousValue::SymbolicMustMatch { target, referent } => {
+                                        *expected = PreviousValue::MustExistAndMatch(Target::Symbolic(target));
+                                        *referent
+                                    }
+                                    other => other,
+                                };
                                 RefEdit {
                                     change: Change::Update {
                                         expected: next,
@@ -111,6 +117,7 @@ where
                                     deref: true,
                                 }
                             }
+                            Change::Rename { .. } | Change::Copy { .. } => continue,
                         },
                     ));
                 }
@@ -131,3 +138,18 @@ where
         }
     }
 }
+
+/// Return the expectation for the referent of a symbolic reference to be deleted, and keep only the expectation towards
+/// its target in `previous` if there is one.
+fn split_expectation(previous: &mut PreviousValue) -> PreviousValue {
+    match std::mem::replace(previous, PreviousValue::Any) {
+        PreviousValue::SymbolicMustMatch { target, referent } => {
+            *previous = PreviousValue::MustExistAndMatch(Target::Symbolic(target));
+            *referent
+        }
+        other => {
+            *previous = other.clone();
+            other
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use gix_object::bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::FullName;
+
+/// The standard reflog messages as written by `git`, for use as [`LogChange::message`](super::LogChange::message) or
+/// as default for edits without message, see [`Transaction::default_log_message()`](crate::file::Transaction::default_log_message()).
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub enum LogMessage {
+    /// `update by push`, written for remote tracking branches that are updated after a successful push.
+    UpdateByPush,
+    /// `branch: Created from <start_point>`, written when creating a branch, like with `git branch <name> <start_point>`.
+    BranchCreatedFrom(BString),
+    /// `branch: Reset to <start_point>`, written when forcefully resetting an existing branch, like with `git branch -f`.
+    BranchResetTo(BString),
+    /// `Branch: renamed <from> to <to>`, written when renaming a reference, like with `git branch -m`.
+    ///
+    /// It's used automatically for [renames](super::Change::Rename) without a message.
+    Renamed {
+        /// The reference that was renamed.
+        from: FullName,
+        /// The name of the reference after the rename.
+        to: FullName,
+    },
+    /// `Branch: copied <from> to <to>`, written when copying a reference, like with `git branch -c`.
+    ///
+    /// It's used automatically for [copies](super::Change::Copy) without a message.
+    Copied {
+        /// The reference that was copied.
+        from: FullName,
+        /// The name of the copy.
+        to: FullName,
+    },
+    /// `reset: moving to <revision>`, written when resetting `HEAD` to another revision, like with `git reset`.
+    ResetMovingTo(BString),
+    /// `checkout: moving from <from> to <to>`, written when checking out another branch or revision, like with `git checkout`.
+    CheckoutMovingFrom {
+        /// The previously checked out branch or revision.
+        from: BString,
+        /// The newly checked out branch or revision.
+        to: BString,
+    },
+    /// `clone: from <url>`, written for the references created when cloning from `url`.
+    CloneFrom(BString),
+}
+
+impl LogMessage {
+    /// Render this template into a message suitable for [`LogChange::message`](super::LogChange::message).
+    pub fn to_bstring(&self) -> BString {
+        let parts: &[&BStr] = match self {
+            LogMessage::UpdateByPush => &["update by push".into()],
+            LogMessage::BranchCreatedFrom(start_point) => &["branch: Created from ".into(), start_point.as_ref()],
+            LogMessage::BranchResetTo(start_point) => &["branch: Reset to ".into(), start_point.as_ref()],
+            LogMessage::Renamed { from, to } => {
+                &["Branch: renamed ".into(), from.as_bstr(), " to ".into(), to.as_bstr()]
+            }
+            LogMessage::Copied { from, to } => &["Branch: copied ".into(), from.as_bstr(), " to ".into(), to.as_bstr()],
+            LogMessage::ResetMovingTo(revision) => &["reset: moving to ".into(), revision.as_ref()],
+            LogMessage::CheckoutMovingFrom { from, to } => &[
+                "checkout: moving from ".into(),
+                from.as_ref(),
+                " to ".into(),
+                to.as_ref(),
+            ],
+            LogMessage::CloneFrom(url) => &["clone: from ".into(), url.as_ref()],
+        };
+        let mut out = BString::default();
+        for part in parts {
+            out.push_str(part);
+        }
+        // Messages must be a single line.
+        out.replace(b"\n", b" ").into()
+    }
+}
+
+impl From<LogMessage> for BString {
+    fn from(message: LogMessage) -> Self {
+        message.to_bstring()
+    }
+}
+
+impl std::fmt::Display for LogMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_bstring().fmt(f)
+    }
+}
@@ -5,6 +5,8 @@
 //!
 //! * create or update reference
 //! * delete references
+//! * rename references along with their reflog
+//! * copy references along with their reflog
 //!
 //! The following guarantees are made:
 //!
@@ -52,6 +54,17 @@ pub enum PreviousValue {
     MustExistAndMatch(Target),
     /// The ref _may_ exist and have the given value, or may not exist at all.
     ExistingMustMatch(Target),
+    /// The ref _must_ exist as symbolic reference pointing to `target`, which is verified independently of the value
+    /// of the reference it points to.
+    ///
+    /// If the edit is [dereferenced](RefEdit::deref), `referent` is the expectation towards the reference it points to,
+    /// otherwise it is ignored.
+    SymbolicMustMatch {
+        /// The name of the reference the symbolic reference is expected to point to.
+        target: FullName,
+        /// The expected value of `target` if the edit is dereferenced.
+        referent: Box<PreviousValue>,
+    },
 }
 
 /// A description of an edit to perform.
@@ -65,7 +78,7 @@ pub enum Change {
         log: LogChange,
         /// The expected value already present in the reference.
         /// If a ref was existing previously this field will be overwritten with `MustExistAndMatch(actual_value)` for use after
-        /// the transaction was committed successfully.
+        /// the transaction was committed successfully, or with `MustNotExist` if it didn't exist.
         expected: PreviousValue,
         /// The new state of the reference, either for updating an existing one or creating a new one.
         new: Target,
@@ -75,11 +88,56 @@ pub enum Change {
         /// The expected value of the reference, with the `MustNotExist` variant being invalid.
         ///
         /// If a previous ref existed, this value will be filled in automatically as `MustExistAndMatch(actual_value)` and
-        /// can be accessed if the transaction was committed successfully.
+        /// can be accessed if the transaction was committed successfully. Otherwise, it will be set to `Any`.
         expected: PreviousValue,
         /// How to treat the reference log during deletion.
         log: RefLog,
     },
+    /// Rename a reference to `new_name` while keeping its value, and move its reflog along with it, similar to `git branch -m`.
+    ///
+    /// When preparing the transaction, this change is split into the deletion of the reference under its current name and
+    /// the creation of a reference named `new_name`, which are the edits returned after committing.
+    /// The moved reflog receives an entry with `message` to record the rename.
+    ///
+    /// The rename fails if `new_name` conflicts with the directory structure of another reference, which includes
+    /// the renamed reference itself, like when renaming `refs/heads/a` to `refs/heads/a/b`.
+    /// Note that the [`deref`](RefEdit::deref) flag has no effect on renames.
+    Rename {
+        /// The expected value of the reference to rename, with the `MustNotExist` variant being invalid.
+        expected: PreviousValue,
+        /// The name of the reference after the rename.
+        new_name: FullName,
+        /// The expected value of a reference that might already exist under `new_name`, typically `MustNotExist`, which
+        /// unlike with updates fails even if the existing reference has the same value.
+        /// If it is allowed to exist, it will be overwritten and its reflog is replaced with the one of the renamed reference.
+        new_name_expected: PreviousValue,
+        /// The message to put into the reflog entry recording the rename. It must be a single line.
+        /// If empty, [`LogMessage::Renamed`] is used.
+        message: BString,
+    },
+    /// Copy a reference to `new_name` and copy its reflog along with it, similar to `git branch -c`.
+    ///
+    /// When preparing the transaction, this change is split into an update of the reference that keeps its current value,
+    /// which is how its expectation is verified, and the creation of a reference named `new_name`, which are the edits
+    /// returned after committing.
+    /// The copied reflog receives an entry with `message` to record the copy.
+    ///
+    /// The copy fails if `new_name` conflicts with the directory structure of another reference, which includes
+    /// the copied reference itself, like when copying `refs/heads/a` to `refs/heads/a/b`.
+    /// Note that the [`deref`](RefEdit::deref) flag has no effect on copies.
+    Copy {
+        /// The expected value of the reference to copy, with the `MustNotExist` variant being invalid.
+        expected: PreviousValue,
+        /// The name of the copy.
+        new_name: FullName,
+        /// The expected value of a reference that might already exist under `new_name`, typically `MustNotExist`, which
+        /// unlike with updates fails even if the existing reference has the same value.
+        /// If it is allowed to exist, it will be overwritten and its reflog is replaced with the one of the copied reference.
+        new_name_expected: PreviousValue,
+        /// The message to put into the reflog entry recording the copy. It must be a single line.
+        /// If empty, [`LogMessage::Copied`] is used.
+        message: BString,
+    },
 }
 
 impl Change {
@@ -87,12 +145,31 @@ impl Change {
     pub fn new_value(&self) -> Option<crate::TargetRef<'_>> {
         match self {
             Change::Update { new, .. } => new.to_ref().into(),
-            Change::Delete { .. } => None,
+            Change::Delete { .. } | Change::Rename { .. } | Change::Copy { .. } => None,
         }
     }
 
     /// Return references to values that are in common between all variants and denote the previous observed value.
     pub fn previous_value(&self) -> Option<crate::TargetRef<'_>> {
+        if let Change::Update {
+            expected: PreviousValue::SymbolicMustMatch { target, .. },
+            ..
+        }
+        | Change::Delete {
+            expected: PreviousValue::SymbolicMustMatch { target, .. },
+            ..
+        }
+        | Change::Rename {
+            expected: PreviousValue::SymbolicMustMatch { target, .. },
+            ..
+        }
+        | Change::Copy {
+            expected: PreviousValue::SymbolicMustMatch { target, .. },
+            ..
+        } = self
+        {
+            return crate::TargetRef::Symbolic(target.as_ref()).into();
+        }
         match self {
             Change::Update {
                 expected: PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
@@ -101,6 +178,14 @@ impl Change {
             | Change::Delete {
                 expected: PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
                 ..
+            }
+            | Change::Rename {
+                expected: PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
+                ..
+            }
+            | Change::Copy {
+                expected: PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
+                ..
             } => previous,
             _ => return None,
         }
@@ -121,6 +206,75 @@ pub struct RefEdit {
     pub deref: bool,
 }
 
+impl RefEdit {
+    /// If this edit is a rename or copy, turn it into the deletion or the unchanged update of the reference respectively,
+    /// and return the edit creating it under its new name with `current`, the current value of the reference to rename or copy.
+    /// Return `None` if this edit is neither a rename nor a copy.
+    pub(crate) fn split_rename_or_copy(&mut self, current: &Target) -> Option<RefEdit> {
+        let is_rename = matches!(self.change, Change::Rename { .. });
+        let (Change::Rename {
+            expected,
+            new_name,
+            new_name_expected,
+            message,
+        }
+        | Change::Copy {
+            expected,
+            new_name,
+            new_name_expected,
+            message,
+        }) = &mut self.change
+        else {
+            return None;
+        };
+        let new = match expected {
+            PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous) => previous.clone(),
+            _ => current.clone(),
+        };
+        let expected = match std::mem::replace(expected, PreviousValue::Any) {
+            PreviousValue::Any | PreviousValue::MustExist => PreviousValue::MustExistAndMatch(current.clone()),
+            PreviousValue::ExistingMustMatch(previous) => PreviousValue::MustExistAndMatch(previous),
+            other => other,
+        };
+        let creation = RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: if message.is_empty() {
+                        let (from, to) = (self.name.clone(), new_name.clone());
+                        if is_rename {
+                            LogMessage::Renamed { from, to }
+                        } else {
+                            LogMessage::Copied { from, to }
+                        }
+                        .to_bstring()
+                    } else {
+                        std::mem::take(message)
+                    },
+                },
+                expected: std::mem::replace(new_name_expected, PreviousValue::Any),
+                new: new.clone(),
+            },
+            name: new_name.clone(),
+            deref: false,
+        };
+        self.change = match self.change {
+            Change::Rename { .. } => Change::Delete {
+                expected,
+                log: RefLog::AndReference,
+            },
+            _ => Change::Update {
+                log: LogChange::default(),
+                expected,
+                new,
+            },
+        };
+        self.deref = false;
+        Some(creation)
+    }
+}
+
 /// The way to deal with the Reflog in deletions.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 pub enum RefLog {
@@ -132,3 +286,8 @@ pub enum RefLog {
 
 mod ext;
 pub use ext::RefEditsExt;
+
+mod log_message;
+pub use log_message::LogMessage;
+
+pub mod stdin;
@@ -0,0 +1,385 @@
+//! Parse the command language of `git update-ref --stdin` into [edits](RefEdit), so transactions can be driven
+//! by the exact same input `git` accepts.
+//!
+//! The following commands are supported, in the format that terminates each command with a newline and allows to quote
+//! fields like strings in C, as well as in the [NUL-terminated format](Format::Nul) that doesn't support quoting:
+//!
+//! * `update SP <ref> SP <new-oid> [SP <old-oid>] LF`
+//! * `create SP <ref> SP <new-oid> LF`
+//! * `delete SP <ref> [SP <old-oid>] LF`
+//! * `verify SP <ref> [SP <old-oid>] LF`
+//! * `option SP no-deref LF`
+//!
+//! Unlike `git`, object ids must be given as full hexadecimal hashes as there is no repository to resolve other object names.
+//! The transaction control commands `start`, `prepare`, `commit` and `abort` are rejected as all edits are performed
+//! in a single transaction.
+use std::borrow::Cow;
+
+use gix_hash::ObjectId;
+use gix_object::bstr::{BStr, BString, ByteSlice};
+
+use crate::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
+    FullName, Target,
+};
+
+/// The way commands are terminated.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Each command is terminated by a newline, and fields are separated by a space and may be quoted like strings in C.
+    ///
+    /// An empty value means the null object id, and a missing value is specified by omitting it along with its space.
+    #[default]
+    LineFeed,
+    /// Commands and each of their values are terminated by a NUL byte, which is what `git update-ref -z --stdin` expects.
+    ///
+    /// An empty value is missing, except for the new value of an `update`, which is the null object id.
+    Nul,
+}
+
+impl Format {
+    fn terminator(&self) -> u8 {
+        match self {
+            Format::LineFeed => b'\n',
+            Format::Nul => 0,
+        }
+    }
+}
+
+/// Options for use in [`parse()`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The way commands are terminated.
+    pub format: Format,
+    /// The kind of hash all object ids must be.
+    pub object_hash: gix_hash::Kind,
+    /// If `false`, symbolic references aren't dereferenced, like `git update-ref --no-deref`.
+    ///
+    /// Note that the `no-deref` option turns this off for the command that follows it.
+    pub no_deref: bool,
+    /// If `true`, create a reflog for each updated reference even if one wouldn't be created otherwise, like `--create-reflog`.
+    pub create_reflog: bool,
+    /// The message to use for reflog entries, like `-m <reason>`.
+    pub message: BString,
+}
+
+/// Parse all commands in `input` as given to `git update-ref --stdin` into edits, configured by `options`.
+///
+/// Like `git`, it's an error to have multiple commands for the same reference.
+/// Note that verifying that a reference doesn't exist, either with `verify` or with an `update` to and from the null object id,
+/// isn't supported as it can't be expressed as edit, and `verify` with an object id is an update to the same value
+/// which leaves the reference and its reflog unchanged.
+pub fn parse(input: &[u8], options: &Options) -> Result<Vec<RefEdit>, parse::Error> {
+    use parse::Error;
+
+    let terminator = options.format.terminator();
+    let mut edits = Vec::new();
+    let mut no_deref = options.no_deref;
+    let mut input = input;
+    let mut command = 0;
+    while !input.is_empty() {
+        command += 1;
+        let line = match input.find_byte(terminator) {
+            Some(pos) => {
+                let line = &input[..pos];
+                input = &input[pos + 1..];
+                line
+            }
+            None if options.format == Format::LineFeed => std::mem::take(&mut input),
+            None => return Err(Error::Unterminated { command }),
+        };
+        let (name, args) = match line.find_byte(b' ') {
+            Some(pos) => (&line[..pos], Some(&line[pos + 1..])),
+            None => (line, None),
+        };
+        let mut fields = Fields {
+            command,
+            format: options.format,
+            object_hash: options.object_hash,
+            args: args.unwrap_or_default(),
+            input: &mut input,
+        };
+        let (ref_name, change) = match name {
+            b"update" | b"create" | b"delete" | b"verify" => {
+                let ref_name = args
+                    .ok_or(Error::Malformed {
+                        command,
+                        message: "missing reference name",
+                    })
+                    .and_then(|_| fields.ref_name())?;
+                let change = match name {
+                    b"update" => {
+                        let new = fields.value(true)?.ok_or(Error::Malformed {
+                            command,
+                            message: "missing new object id",
+                        })?;
+                        let expected = expected(fields.value(false)?);
+                        if new.is_null() {
+                            if expected == PreviousValue::MustNotExist {
+                                return Err(Error::UnsupportedVerification {
+                                    command,
+                                    name: ref_name.into_inner(),
+                                });
+                            }
+                            Change::Delete {
+                                expected,
+                                log: RefLog::AndReference,
+                            }
+                        } else {
+                            Change::Update {
+                                log: log_change(options),
+                                expected,
+                                new: Target::Object(new),
+                            }
+                        }
+                    }
+                    b"create" => match fields.value(false)? {
+                        Some(new) if !new.is_null() => Change::Update {
+                            log: log_change(options),
+                            expected: PreviousValue::MustNotExist,
+                            new: Target::Object(new),
+                        },
+                        Some(_) => {
+                            return Err(Error::Malformed {
+                                command,
+                                message: "the new object id must not be null",
+                            })
+                        }
+                        None => {
+                            return Err(Error::Malformed {
+                                command,
+                                message: "missing new object id",
+                            })
+                        }
+                    },
+                    b"delete" => match fields.value(false)? {
+                        Some(previous) if previous.is_null() => {
+                            return Err(Error::Malformed {
+                                command,
+                                message: "the old object id must not be null",
+                            })
+                        }
+                        previous => Change::Delete {
+                            expected: expected(previous),
+                            log: RefLog::AndReference,
+                        },
+                    },
+                    _ => match fields.value(false)? {
+                        Some(previous) if !previous.is_null() => Change::Update {
+                            log: log_change(options),
+                            expected: PreviousValue::MustExistAndMatch(Target::Object(previous)),
+                            new: Target::Object(previous),
+                        },
+                        _ => {
+                            return Err(Error::UnsupportedVerification {
+                                command,
+                                name: ref_name.into_inner(),
+                            })
+                        }
+                    },
+                };
+                (ref_name, change)
+            }
+            b"option" => {
+                match args {
+                    Some(b"no-deref") => no_deref = true,
+                    Some(option) => {
+                        return Err(Error::UnknownOption {
+                            command,
+                            option: option.into(),
+                        })
+                    }
+                    None => {
+                        return Err(Error::Malformed {
+                            command,
+                            message: "missing option name",
+                        })
+                    }
+                }
+                continue;
+            }
+            b"start" | b"prepare" | b"commit" | b"abort" => {
+                return Err(Error::UnsupportedCommand {
+                    command,
+                    name: name.into(),
+                })
+            }
+            _ => {
+                return Err(Error::UnknownCommand {
+                    command,
+                    name: name.into(),
+                })
+            }
+        };
+        if !fields.args.is_empty() {
+            return Err(Error::Malformed {
+                command,
+                message: "unexpected input after the last field",
+            });
+        }
+        edits.push(RefEdit {
+            change,
+            name: ref_name,
+            deref: !std::mem::replace(&mut no_deref, options.no_deref),
+        });
+    }
+    edits
+        .assure_one_name_has_one_edit()
+        .map_err(|name| Error::DuplicateName { name })?;
+    Ok(edits)
+}
+
+fn expected(previous: Option<ObjectId>) -> PreviousValue {
+    match previous {
+        None => PreviousValue::Any,
+        Some(previous) if previous.is_null() => PreviousValue::MustNotExist,
+        Some(previous) => PreviousValue::MustExistAndMatch(Target::Object(previous)),
+    }
+}
+
+fn log_change(options: &Options) -> LogChange {
+    LogChange {
+        mode: RefLog::AndReference,
+        force_create_reflog: options.create_reflog,
+        message: options.message.clone(),
+    }
+}
+
+/// The fields of a single command following its name.
+struct Fields<'a, 'b> {
+    command: usize,
+    format: Format,
+    object_hash: gix_hash::Kind,
+    /// The unparsed remainder of the command after its name.
+    args: &'a [u8],
+    /// The input after the command, from which values are read in the NUL-terminated format.
+    input: &'b mut &'a [u8],
+}
+
+impl<'a> Fields<'a, '_> {
+    fn ref_name(&mut self) -> Result<FullName, parse::Error> {
+        let name = match self.format {
+            Format::LineFeed => self.arg()?,
+            Format::Nul => std::mem::take(&mut self.args).as_bstr().into(),
+        };
+        if name.is_empty() {
+            return Err(parse::Error::Malformed {
+                command: self.command,
+                message: "missing reference name",
+            });
+        }
+        FullName::try_from(name.as_ref()).map_err(|err| parse::Error::InvalidName {
+            command: self.command,
+            source: err,
+        })
+    }
+
+    /// Read the next value, returning `None` if it is missing. If `empty_is_null` is `true`, an empty value is the null object id
+    /// in the NUL-terminated format, which it always is in the newline-terminated one.
+    fn value(&mut self, empty_is_null: bool) -> Result<Option<ObjectId>, parse::Error> {
+        let value = match self.format {
+            Format::LineFeed => {
+                let Some(rest) = self.args.strip_prefix(b" ") else {
+                    return if self.args.is_empty() {
+                        Ok(None)
+                    } else {
+                        Err(parse::Error::Malformed {
+                            command: self.command,
+                            message: "expected a space before the next field",
+                        })
+                    };
+                };
+                self.args = rest;
+                let value = self.arg()?;
+                if value.is_empty() {
+                    return Ok(Some(self.object_hash.null()));
+                }
+                value
+            }
+            Format::Nul => {
+                let pos = self.input.find_byte(0).ok_or(parse::Error::Malformed {
+                    command: self.command,
+                    message: "unexpected end of input",
+                })?;
+                let value = self.input[..pos].as_bstr();
+                *self.input = &self.input[pos + 1..];
+                if value.is_empty() {
+                    return Ok(empty_is_null.then(|| self.object_hash.null()));
+                }
+                value.into()
+            }
+        };
+        ObjectId::from_hex(&value)
+            .ok()
+            .filter(|id| id.kind() == self.object_hash)
+            .map(Some)
+            .ok_or_else(|| parse::Error::InvalidObjectId {
+                command: self.command,
+                value: value.into_owned(),
+                kind: self.object_hash,
+            })
+    }
+
+    /// Read the next space-separated argument of the newline-terminated format, which may be quoted.
+    fn arg(&mut self) -> Result<Cow<'a, BStr>, parse::Error> {
+        let args = self.args.as_bstr();
+        let (arg, consumed) = if args.starts_with(b"\"") {
+            gix_quote::ansi_c::undo(args).map_err(|err| parse::Error::Unquote {
+                command: self.command,
+                source: err,
+            })?
+        } else {
+            let end = args.find_byte(b' ').unwrap_or(args.len());
+            (args[..end].as_bstr().into(), end)
+        };
+        self.args = &args[consumed..];
+        if !self.args.is_empty() && !self.args.starts_with(b" ") {
+            return Err(parse::Error::Malformed {
+                command: self.command,
+                message: "expected a space after a quoted field",
+            });
+        }
+        Ok(arg)
+    }
+}
+
+///
+pub mod parse {
+    use gix_object::bstr::BString;
+
+    /// The error returned by [`parse()`](super::parse()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Command {command} isn't terminated")]
+        Unterminated { command: usize },
+        #[error("Command {command} is unknown: {name:?}")]
+        UnknownCommand { command: usize, name: BString },
+        #[error("Command {command} controls the transaction with {name:?}, which is unsupported")]
+        UnsupportedCommand { command: usize, name: BString },
+        #[error("Command {command} uses the unknown option {option:?}")]
+        UnknownOption { command: usize, option: BString },
+        #[error("Command {command} is malformed: {message}")]
+        Malformed { command: usize, message: &'static str },
+        #[error("Command {command} verifies that {name:?} doesn't exist, which is unsupported")]
+        UnsupportedVerification { command: usize, name: BString },
+        #[error("Command {command} has value {value:?} which isn't a full hexadecimal {kind} object id")]
+        InvalidObjectId {
+            command: usize,
+            value: BString,
+            kind: gix_hash::Kind,
+        },
+        #[error("Command {command} has an invalid reference name")]
+        InvalidName {
+            command: usize,
+            source: gix_validate::reference::name::Error,
+        },
+        #[error("Command {command} has a field that could not be unquoted")]
+        Unquote {
+            command: usize,
+            source: gix_quote::ansi_c::undo::Error,
+        },
+        #[error("A reference named {name:?} has multiple commands")]
+        DuplicateName { name: BString },
+    }
+}
@@ -191,4 +191,45 @@ mod iter {
             assert!(iter.next().is_none(), "iterator exhausted");
         }
     }
+    mod forward_owned {
+        use gix_ref::file::log::iter::forward_owned;
+
+        use crate::file::log::iter::reflog;
+
+        #[test]
+        fn all_success() -> crate::Result {
+            let log = reflog("HEAD")?;
+            assert_eq!(
+                forward_owned(log.as_slice()).count(),
+                5,
+                "the log as a known amount of entries"
+            );
+
+            let mut iter = forward_owned(log.as_slice());
+            let line = iter.next().unwrap()?;
+            assert_eq!(line.previous_oid, gix_hash::Kind::Sha1.null());
+            assert_eq!(line.new_oid.to_string(), "134385f6d781b7e97062102c6a483440bfda2a03");
+            assert_eq!(line.message, "commit (initial): c1");
+            assert!(iter.all(|l| l.is_ok()), "all lines parse fine");
+            Ok(())
+        }
+
+        #[test]
+        fn a_single_failure_does_not_abort_iteration() {
+            let log_first_broken = "0000000000000000000000000000000000000000 134385fbroken7062102c6a483440bfda2a03 committer <committer@example.com> 946771200 +0000	commit
+0000000000000000000000000000000000000000 134385f6d781b7e97062102c6a483440bfda2a03 committer <committer@example.com> 946771200 +0000	commit (initial): c1";
+
+            let mut iter = forward_owned(log_first_broken.as_bytes());
+            let err = iter.next().expect("error is not none").expect_err("the line is broken");
+            assert!(
+                matches!(err, gix_ref::file::log::iter::forward_owned::Error::Decode(_)),
+                "decode errors are reported per line"
+            );
+            assert!(
+                iter.next().expect("a second line without trailing newline").is_ok(),
+                "line parses ok"
+            );
+            assert!(iter.next().is_none(), "iterator exhausted");
+        }
+    }
 }
@@ -90,6 +90,32 @@ mod peel {
         Ok(())
     }
 
+    #[test]
+    fn peel_symbolic_ref_to_packed_tag_without_object_lookup() -> crate::Result {
+        let (_keep, store) = file::store_writable("make_packed_ref_repository.sh")?;
+        std::fs::write(
+            store.git_dir().join("refs").join("heads").join("to-dt1"),
+            "ref: refs/tags/dt1\n",
+        )?;
+
+        let mut r: Reference = store.find_loose("to-dt1")?.into();
+        assert_eq!(r.peeled, None, "loose references don't know their peeled object");
+        let expected = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+        assert_eq!(
+            r.peel_to_id_in_place(&store, &gix_object::find::Never)?,
+            expected,
+            "the peeled object of the packed annotated tag is used"
+        );
+        assert_eq!(r.name.as_bstr(), "refs/tags/dt1");
+        assert_eq!(r.peeled, Some(expected), "the peeled object is cached…");
+        assert_eq!(
+            r.peel_to_id_in_place(&store, &gix_object::find::Never)?,
+            expected,
+            "…and reused"
+        );
+        Ok(())
+    }
+
     #[test]
     fn peel_one_level_with_pack() -> crate::Result {
         let store = store_with_packed_refs()?;
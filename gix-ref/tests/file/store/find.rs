@@ -90,6 +90,40 @@ mod existing {
     }
 }
 
+mod contains {
+    use crate::file::{store, store_at};
+
+    #[test]
+    fn loose_and_packed_refs() -> crate::Result {
+        let store = store_at("make_packed_ref_repository_for_overlay.sh")?;
+        for (name, expected) in [
+            ("HEAD", true),
+            ("refs/heads/main", true),
+            ("refs/heads/newer-as-loose", true),
+            ("refs/remotes/origin/HEAD", true),
+            ("refs/tags/tag-object", true),
+            ("refs/heads/does-not-exist", false),
+            ("refs/heads", false),
+            ("refs/remotes/origin/main", true),
+        ] {
+            assert_eq!(store.contains(name.try_into()?)?, expected, "{name}");
+        }
+        assert!(
+            !store.contains_packed("refs/heads/main".try_into()?, None)?,
+            "without packed buffer, only loose refs are seen"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn refs_are_not_parsed() -> crate::Result {
+        let store = store()?;
+        assert!(store.try_find("refs/broken").is_err(), "the ref is corrupt");
+        assert!(store.contains("refs/broken".try_into()?)?, "but it exists nonetheless");
+        Ok(())
+    }
+}
+
 mod loose {
     use crate::{file::store, hex_to_id};
 
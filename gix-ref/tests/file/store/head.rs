@@ -0,0 +1,63 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{file::head::Error, Target};
+
+use crate::file::{store_writable, transaction::prepare_and_commit::committer};
+
+#[test]
+fn detach_and_attach_log_like_git_checkout() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main = store.find("main")?.target.into_id();
+    let parent = gix_ref::log::Line::from(
+        store
+            .reflog_iter("HEAD", &mut Vec::new())?
+            .expect("present")
+            .next()
+            .expect("at least one line")?,
+    )
+    .new_oid;
+
+    let edits = store.detach_head(parent, committer().to_ref(), Fail::Immediately)?;
+    assert_eq!(edits.len(), 1);
+    assert_eq!(store.find("HEAD")?.target, Target::Object(parent));
+    assert_eq!(store.find("main")?.target.id(), main, "the branch is untouched");
+
+    store.attach_head("refs/heads/main".try_into()?, committer().to_ref(), Fail::Immediately)?;
+    assert_eq!(
+        store.find("HEAD")?.target,
+        Target::Symbolic("refs/heads/main".try_into()?)
+    );
+
+    let mut buf = [0u8; 512];
+    let lines: Vec<_> = store
+        .reflog_iter_rev("HEAD", &mut buf)?
+        .expect("present")
+        .take(2)
+        .map(|line| line.map(|line| (line.previous_oid, line.new_oid, line.message)))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(
+        lines,
+        vec![
+            (parent, main, format!("checkout: moving from {parent} to main").into()),
+            (main, parent, format!("checkout: moving from main to {parent}").into()),
+        ],
+        "the log records the peeled values and the names like git does"
+    );
+    Ok(())
+}
+
+#[test]
+fn attaching_is_only_possible_to_local_branches() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let err = store
+        .attach_head("refs/tags/v1".try_into()?, committer().to_ref(), Fail::Immediately)
+        .unwrap_err();
+    assert!(matches!(err, Error::NotABranch { .. }), "{err:?}");
+
+    store.attach_head("refs/heads/unborn".try_into()?, committer().to_ref(), Fail::Immediately)?;
+    assert_eq!(
+        store.find("HEAD")?.target,
+        Target::Symbolic("refs/heads/unborn".try_into()?),
+        "unborn branches can be attached to"
+    );
+    Ok(())
+}
@@ -278,10 +278,16 @@ fn loose_iter_with_broken_refs() -> crate::Result {
     let msg = "The reference at \"refs/broken\" could not be instantiated";
     #[cfg(windows)]
     let msg = "The reference at \"refs\\broken\" could not be instantiated";
-    assert_eq!(
-        actual[first_error].as_ref().expect_err("unparsable ref").to_string(),
-        msg
-    );
+    let err = actual[first_error].as_ref().expect_err("unparsable ref");
+    assert_eq!(err.to_string(), msg);
+    let gix_ref::file::iter::loose_then_packed::Error::ReferenceCreation {
+        relative_path, content, ..
+    } = err
+    else {
+        panic!("unexpected error: {err:?}")
+    };
+    assert_eq!(relative_path, &std::path::Path::new("refs").join("broken"));
+    assert_eq!(content, "notahexsha\n", "the raw content is provided for inspection");
     let ref_paths: Vec<_> = actual
         .drain(..first_error)
         .filter_map(|e| e.ok().map(|e| e.name.into_inner()))
@@ -8,8 +8,15 @@ use gix_ref::Target;
 
 mod access;
 mod find;
+mod head;
 mod iter;
+mod pack_refs;
+mod pseudo;
 mod reflog;
+mod reflog_expire;
+mod verify;
+#[cfg(feature = "watch")]
+mod watch;
 
 #[test]
 fn precompose_unicode_journey() -> crate::Result {
@@ -0,0 +1,145 @@
+use gix_lock::acquire::Fail;
+use gix_object::bstr::BString;
+use gix_ref::file::pack_refs::Options;
+
+use crate::file::{
+    store_with_packed_refs, store_writable,
+    transaction::prepare_and_commit::{committer, create_at},
+};
+
+#[test]
+fn all_with_prune_is_equivalent_to_git() -> crate::Result {
+    let (_keep, store) = store_writable("make_ref_repository.sh")?;
+    assert!(
+        store.open_packed_buffer()?.is_none(),
+        "there should be no packed refs to start out with"
+    );
+    let odb = gix_odb::at(store.git_dir().join("objects"))?;
+    let edits = store.pack_refs(
+        odb,
+        Options {
+            all: true,
+            prune: true,
+            lock_fail_mode: Fail::Immediately,
+        },
+    )?;
+    assert_eq!(edits.len(), 8, "all loose refs that point to objects are packed");
+
+    let remaining_loose: Vec<_> = store.loose_iter()?.collect();
+    assert!(
+        remaining_loose
+            .iter()
+            .all(|r| r.as_ref().map_or(true, |r| r.kind() == gix_ref::Kind::Symbolic)),
+        "only symbolic refs and broken refs are left"
+    );
+
+    let expected_pack_data: BString = std::fs::read(store_with_packed_refs()?.packed_refs_path())?.into();
+    let actual_packed_data: BString = std::fs::read(store.packed_refs_path())?.into();
+    assert_eq!(
+        actual_packed_data, expected_pack_data,
+        "both gitoxide and git must agree on the packed refs file perfectly"
+    );
+
+    let edits = store.pack_refs(
+        gix_odb::at(store.git_dir().join("objects"))?,
+        Options {
+            all: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(edits.is_empty(), "there is nothing left to pack");
+    Ok(())
+}
+
+#[test]
+fn by_default_only_tags_and_packed_refs_are_updated() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository_for_overlay.sh")?;
+    store
+        .transaction()
+        .prepare(
+            Some(create_at("refs/heads/loose-only")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    let loose_id = store.find_loose("newer-as-loose")?.target.into_id();
+    std::fs::write(store.git_dir().join("refs/tags/loose-tag"), format!("{loose_id}\n"))?;
+    assert_ne!(
+        store
+            .open_packed_buffer()?
+            .expect("packed")
+            .find("newer-as-loose")?
+            .target(),
+        loose_id,
+        "the loose ref is more recent than its packed counterpart"
+    );
+
+    let odb = gix_odb::at(store.git_dir().join("objects"))?;
+    let edits = store.pack_refs(odb, Options::default())?;
+    let mut names: Vec<_> = edits.iter().map(|e| e.name.as_bstr().to_owned()).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["refs/heads/newer-as-loose", "refs/tags/loose-tag"],
+        "like git, the ref that was already packed is updated and tags are always packed"
+    );
+
+    assert_eq!(
+        store
+            .open_packed_buffer()?
+            .expect("packed")
+            .find("newer-as-loose")?
+            .target(),
+        loose_id,
+        "the packed ref was updated…"
+    );
+    assert!(
+        store.try_find_loose("newer-as-loose")?.is_none(),
+        "…and the loose ref was pruned"
+    );
+    assert!(
+        store.try_find_loose("loose-tag")?.is_none(),
+        "the loose tag was packed and pruned as well"
+    );
+    assert!(
+        store
+            .open_packed_buffer()?
+            .expect("packed")
+            .try_find("loose-tag")?
+            .is_some(),
+        "it's now present in the packed-refs file"
+    );
+    assert!(
+        store.try_find_loose("loose-only")?.is_some(),
+        "refs that weren't packed before stay loose"
+    );
+    assert!(
+        store.try_find_loose("refs/remotes/origin/HEAD")?.is_some(),
+        "symbolic refs can't be packed"
+    );
+    Ok(())
+}
+
+#[test]
+fn without_prune_loose_refs_are_kept() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository_for_overlay.sh")?;
+    let odb = gix_odb::at(store.git_dir().join("objects"))?;
+    let edits = store.pack_refs(
+        odb,
+        Options {
+            prune: false,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        store
+            .open_packed_buffer()?
+            .expect("packed")
+            .find("newer-as-loose")?
+            .target(),
+        store.find_loose("newer-as-loose")?.target.into_id(),
+        "the packed ref is now up to date and the loose ref still exists"
+    );
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use gix_lock::acquire::Fail;
+use gix_ref::file::pseudo::{fetch_head::Entry, read, write};
+
+use crate::{file::Store, hex_to_id};
+
+fn empty_store() -> crate::Result<(gix_testtools::tempfile::TempDir, Store)> {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let store = Store::at(dir.path().into(), Default::default());
+    Ok((dir, store))
+}
+
+#[test]
+fn fetch_head_round_trip() -> crate::Result {
+    let (dir, store) = empty_store()?;
+    assert_eq!(store.read_fetch_head()?, None, "it's fine if it doesn't exist");
+
+    let written_by_git = "0f8abe7a0a4d5a8b1f9dd6ef3fe3a26a7ee0e4a2\t\tbranch 'main' of https://example.com/repo\n\
+                          134385f6d781b7e97062102c6a483440bfda2a03\tnot-for-merge\tbranch 'dev' of https://example.com/repo\n\
+                          134385f6d781b7e97062102c6a483440bfda2a03\tnot-for-merge\ttag 'v1.0' of https://example.com/repo\n";
+    std::fs::write(dir.path().join("FETCH_HEAD"), written_by_git)?;
+    let entries = store.read_fetch_head()?.expect("present");
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                id: hex_to_id("0f8abe7a0a4d5a8b1f9dd6ef3fe3a26a7ee0e4a2"),
+                for_merge: true,
+                description: "branch 'main' of https://example.com/repo".into(),
+            },
+            Entry {
+                id: hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03"),
+                for_merge: false,
+                description: "branch 'dev' of https://example.com/repo".into(),
+            },
+            Entry {
+                id: hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03"),
+                for_merge: false,
+                description: "tag 'v1.0' of https://example.com/repo".into(),
+            },
+        ]
+    );
+
+    std::fs::remove_file(dir.path().join("FETCH_HEAD"))?;
+    store.write_fetch_head(&entries, Fail::Immediately)?;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("FETCH_HEAD"))?,
+        written_by_git,
+        "it's written exactly like git does"
+    );
+
+    assert!(store.remove_pseudo_ref("FETCH_HEAD".try_into()?, Fail::Immediately)?);
+    assert_eq!(store.read_fetch_head()?, None);
+    Ok(())
+}
+
+#[test]
+fn fetch_head_errors() -> crate::Result {
+    let (dir, store) = empty_store()?;
+    std::fs::write(
+        dir.path().join("FETCH_HEAD"),
+        "0f8abe7a0a4d5a8b1f9dd6ef3fe3a26a7ee0e4a2\t\tfine\n\n134385f6d781b7e97062102c6a483440bfda2a03\tfor-merge\tinvalid marker\n",
+    )?;
+    assert_eq!(
+        store.read_fetch_head().unwrap_err().to_string(),
+        "Line 3 of FETCH_HEAD could not be parsed: \"134385f6d781b7e97062102c6a483440bfda2a03\\tfor-merge\\tinvalid marker\""
+    );
+
+    let err = store
+        .write_fetch_head(
+            Some(&Entry {
+                id: hex_to_id("0f8abe7a0a4d5a8b1f9dd6ef3fe3a26a7ee0e4a2"),
+                for_merge: true,
+                description: "two\nlines".into(),
+            }),
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(matches!(err, write::Error::Io { .. }), "newlines can't be written");
+    assert!(
+        store.read_fetch_head().is_err(),
+        "the previous file remains untouched on error"
+    );
+    Ok(())
+}
+
+#[test]
+fn simple_pseudo_refs_round_trip() -> crate::Result {
+    let (dir, store) = empty_store()?;
+    let merge_head = "MERGE_HEAD".try_into()?;
+    assert_eq!(store.read_pseudo_ref(merge_head)?, None);
+    assert!(!store.remove_pseudo_ref(merge_head, Fail::Immediately)?);
+
+    let ids = vec![
+        hex_to_id("0f8abe7a0a4d5a8b1f9dd6ef3fe3a26a7ee0e4a2"),
+        hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03"),
+    ];
+    store.write_pseudo_ref(merge_head, ids.iter().copied(), Fail::Immediately)?;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("MERGE_HEAD"))?,
+        "0f8abe7a0a4d5a8b1f9dd6ef3fe3a26a7ee0e4a2\n134385f6d781b7e97062102c6a483440bfda2a03\n"
+    );
+    assert_eq!(store.read_pseudo_ref(merge_head)?, Some(ids.clone()));
+
+    let cherry_pick_head = "CHERRY_PICK_HEAD".try_into()?;
+    store.write_pseudo_ref(cherry_pick_head, Some(ids[1]), Fail::Immediately)?;
+    assert_eq!(store.read_pseudo_ref(cherry_pick_head)?, Some(vec![ids[1]]));
+    assert_eq!(
+        store.find("CHERRY_PICK_HEAD")?.target.into_id(),
+        ids[1],
+        "single-id pseudo-refs can also be found like references"
+    );
+
+    assert!(store.remove_pseudo_ref(merge_head, Fail::Immediately)?);
+    assert_eq!(store.read_pseudo_ref(merge_head)?, None);
+    Ok(())
+}
+
+#[test]
+fn only_pseudo_refs_with_object_ids_are_supported() -> crate::Result {
+    let (dir, store) = empty_store()?;
+    for name in ["HEAD", "FETCH_HEAD", "refs/heads/main", "main-worktree/MERGE_HEAD"] {
+        let name = name.try_into()?;
+        assert!(matches!(
+            store.read_pseudo_ref(name),
+            Err(read::Error::NotAPseudoRef { .. })
+        ));
+        assert!(matches!(
+            store.write_pseudo_ref(name, None, Fail::Immediately),
+            Err(write::Error::NotAPseudoRef { .. })
+        ));
+    }
+    assert!(matches!(
+        store.remove_pseudo_ref("HEAD".try_into()?, Fail::Immediately),
+        Err(write::Error::NotAPseudoRef { .. })
+    ));
+
+    std::fs::write(dir.path().join("ORIG_HEAD"), "ref: refs/heads/main\n")?;
+    assert!(matches!(
+        store.read_pseudo_ref("ORIG_HEAD".try_into()?),
+        Err(read::Error::Decode { .. })
+    ));
+    Ok(())
+}
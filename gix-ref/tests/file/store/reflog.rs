@@ -67,3 +67,42 @@ mod iter_rev {
         Ok(())
     }
 }
+
+mod iter_owned {
+    use gix_ref::log::Line;
+
+    use crate::file::store::reflog::store;
+
+    #[test]
+    fn non_existing_and_directory_returns_none() -> crate::Result {
+        let store = store()?;
+        for name in &["FAILURE_NONEXISTING", "refs/heads"] {
+            assert!(
+                matches!(store.reflog_iter_owned(*name), Ok(None)),
+                "this one does not exist"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn for_head_and_main_matches_the_borrowed_forward_iterator() -> crate::Result {
+        let store = store()?;
+        let mut buf = Vec::new();
+        for name in ["HEAD", "refs/heads/main"] {
+            let owned = store
+                .reflog_iter_owned(name)?
+                .expect("exists")
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(owned.len(), 5);
+
+            let borrowed = store
+                .reflog_iter(name, &mut buf)?
+                .expect("exists")
+                .map(|line| line.map(Line::from))
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(owned, borrowed, "both iterators yield the same lines in the same order");
+        }
+        Ok(())
+    }
+}
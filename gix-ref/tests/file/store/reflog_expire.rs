@@ -0,0 +1,154 @@
+use gix_ref::file::reflog_expire::{Error, Options, Outcome};
+
+use crate::{
+    file::{store_writable, transaction::prepare_and_commit::empty_store},
+    hex_to_id,
+};
+
+const A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+fn line(previous: &str, new: &str, time: u32, message: &str) -> String {
+    format!("{previous} {new} committer <committer@example.com> {time} +0000\t{message}\n")
+}
+
+fn store_with_main_reflog(lines: &[String]) -> crate::Result<(gix_testtools::tempfile::TempDir, gix_ref::file::Store)> {
+    let (tmp, store) = empty_store()?;
+    let log_dir = store.git_dir().join("logs/refs/heads");
+    std::fs::create_dir_all(&log_dir)?;
+    std::fs::write(log_dir.join("main"), lines.concat())?;
+    Ok((tmp, store))
+}
+
+fn reflog(store: &gix_ref::file::Store) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(store.git_dir().join("logs/refs/heads/main"))?)
+}
+
+#[test]
+fn entries_older_than_expire_are_removed_and_all_others_are_kept_as_is() -> crate::Result {
+    let null = "0".repeat(40);
+    let lines = [
+        line(&null, A, 100, "first"),
+        line(A, B, 200, "second"),
+        "not a valid line\n".to_string(),
+        line(B, A, 300, "third"),
+    ];
+    let (_keep, store) = store_with_main_reflog(&lines)?;
+    let mut is_reachable = |_: &gix_ref::FullNameRef, _: &gix_hash::oid| unreachable!("not needed for plain expiry");
+    let options = Options {
+        expire: Some(250),
+        ..Default::default()
+    };
+
+    let outcome = store.reflog_expire(
+        "refs/heads/main",
+        Options {
+            dry_run: true,
+            ..options
+        },
+        &mut is_reachable,
+    )?;
+    assert_eq!(outcome, Outcome { kept: 2, removed: 2 });
+    assert_eq!(reflog(&store)?, lines.concat(), "dry-runs don't change anything");
+
+    let outcome = store.reflog_expire("refs/heads/main", options, &mut is_reachable)?;
+    assert_eq!(outcome, Outcome { kept: 2, removed: 2 });
+    assert_eq!(
+        reflog(&store)?,
+        [lines[2].clone(), lines[3].clone()].concat(),
+        "lines that can't be parsed are kept"
+    );
+    assert!(
+        !store.git_dir().join("refs/heads/main.lock").exists()
+            && !store.git_dir().join("logs/refs/heads/main.lock").exists(),
+        "all locks are released"
+    );
+
+    let outcome = store.reflog_expire("refs/heads/main", options, &mut is_reachable)?;
+    assert_eq!(outcome, Outcome { kept: 2, removed: 0 }, "nothing else to do");
+
+    let outcome = store.reflog_expire("refs/heads/does-not-exist", options, &mut is_reachable)?;
+    assert_eq!(outcome, Outcome::default(), "missing reflogs are empty");
+    Ok(())
+}
+
+#[test]
+fn unreachable_entries_are_removed_only_if_older_than_expire_unreachable() -> crate::Result {
+    let lines = [
+        line(B, A, 100, "old and reachable"),
+        line(A, B, 100, "old and unreachable"),
+        line(B, B, 500, "new and unreachable"),
+    ];
+    let (_keep, store) = store_with_main_reflog(&lines)?;
+    let mut calls = Vec::new();
+    let outcome = store.reflog_expire(
+        "refs/heads/main",
+        Options {
+            expire_unreachable: Some(300),
+            ..Default::default()
+        },
+        &mut |name, id| {
+            calls.push((name.as_bstr().to_owned(), id.to_owned()));
+            id == hex_to_id(A)
+        },
+    )?;
+    assert_eq!(outcome, Outcome { kept: 2, removed: 1 });
+    assert_eq!(reflog(&store)?, [lines[0].clone(), lines[2].clone()].concat());
+    assert_eq!(
+        calls,
+        [
+            ("refs/heads/main".into(), hex_to_id(A)),
+            ("refs/heads/main".into(), hex_to_id(B))
+        ],
+        "reachability is only checked for entries that are old enough"
+    );
+    Ok(())
+}
+
+#[test]
+fn locked_references_cause_an_error() -> crate::Result {
+    let (_keep, store) = store_with_main_reflog(&[line(A, B, 100, "entry")])?;
+    std::fs::create_dir_all(store.git_dir().join("refs/heads"))?;
+    std::fs::write(store.git_dir().join("refs/heads/main.lock"), "")?;
+    let err = store
+        .reflog_expire(
+            "refs/heads/main",
+            Options {
+                expire: Some(i64::MAX),
+                ..Default::default()
+            },
+            &mut |_, _| true,
+        )
+        .unwrap_err();
+    assert!(matches!(err, Error::Lock { .. }));
+    assert_eq!(reflog(&store)?, line(A, B, 100, "entry"), "nothing was changed");
+    Ok(())
+}
+
+#[test]
+fn all_reflogs_can_be_expired_at_once() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let outcomes = store.reflog_expire_all(
+        Options {
+            expire: Some(i64::MAX),
+            ..Default::default()
+        },
+        &mut |_, _| true,
+    )?;
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(|(name, outcome)| (name.as_bstr().to_string(), outcome.kept))
+            .collect::<Vec<_>>(),
+        [("HEAD".to_string(), 0), ("refs/heads/main".into(), 0)]
+    );
+    assert!(outcomes.iter().all(|(_, outcome)| outcome.removed > 0));
+    for name in ["HEAD", "refs/heads/main"] {
+        assert_eq!(
+            std::fs::read(store.git_dir().join("logs").join(name))?.len(),
+            0,
+            "the reflog remains, but is empty, just like git does it"
+        );
+    }
+    Ok(())
+}
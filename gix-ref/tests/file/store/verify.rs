@@ -0,0 +1,131 @@
+use gix_ref::file::verify::{Finding, Kind};
+
+use crate::file::{store_with_packed_refs, transaction::prepare_and_commit::empty_store};
+
+const A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+fn write(store: &gix_ref::file::Store, rela_path: &str, content: &str) -> crate::Result {
+    let path = store.git_dir().join(rela_path);
+    std::fs::create_dir_all(path.parent().expect("parent"))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[test]
+fn only_actual_problems_are_reported() -> crate::Result {
+    let store = store_with_packed_refs()?;
+    assert_eq!(
+        store.verify()?,
+        vec![Finding {
+            path: store.git_dir().join("refs/broken"),
+            kind: Kind::MalformedContent {
+                content: "notahexsha\n".into()
+            }
+        }],
+        "the fixture contains a single broken reference on purpose"
+    );
+
+    let (_keep, store) = empty_store()?;
+    write(&store, "HEAD", "ref: refs/heads/unborn\n")?;
+    assert_eq!(
+        store.verify()?,
+        Vec::new(),
+        "HEAD may point to a branch that doesn't exist yet"
+    );
+    Ok(())
+}
+
+#[test]
+fn all_problems_are_reported() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    write(&store, "HEAD", "ref: refs/heads/main\n")?;
+    write(&store, "refs/heads/main", &format!("{A}\n"))?;
+    write(&store, "refs/heads/garbage", "not a hash\n")?;
+    write(&store, "refs/heads/in..valid", &format!("{A}\n"))?;
+    write(&store, "refs/heads/main.lock", "ignored as it belongs to a transaction")?;
+    write(&store, "refs/heads/dangling", "ref: refs/heads/missing\n")?;
+    write(&store, "refs/heads/to-packed", "ref: refs/tags/packed\n")?;
+    write(&store, "refs/heads/invalid-target", "ref: refs/heads/a..b\n")?;
+    write(
+        &store,
+        "logs/refs/heads/main",
+        &format!("{A} {B} committer <committer@example.com> 1 +0000\tvalid\nnot a reflog line\n"),
+    )?;
+    write(
+        &store,
+        "packed-refs",
+        &format!(
+            "# pack-refs with: peeled fully-peeled sorted \n{B} refs/tags/packed\n{A} refs/heads/zz\n{A} refs/heads/aa\ngarbage\n{A} refs/heads/zz\n"
+        ),
+    )?;
+
+    let git_dir = store.git_dir();
+    let packed_refs = git_dir.join("packed-refs");
+    assert_eq!(
+        store.verify()?,
+        vec![
+            Finding {
+                path: packed_refs.clone(),
+                kind: Kind::UnsortedPackedRef {
+                    name: "refs/heads/zz".try_into()?,
+                    previous: "refs/tags/packed".try_into()?,
+                }
+            },
+            Finding {
+                path: packed_refs.clone(),
+                kind: Kind::UnsortedPackedRef {
+                    name: "refs/heads/aa".try_into()?,
+                    previous: "refs/heads/zz".try_into()?,
+                }
+            },
+            Finding {
+                path: packed_refs.clone(),
+                kind: Kind::MalformedPackedRef {
+                    line_number: 5,
+                    line: "garbage".into(),
+                }
+            },
+            Finding {
+                path: packed_refs,
+                kind: Kind::DuplicatePackedRef {
+                    name: "refs/heads/zz".try_into()?,
+                }
+            },
+            Finding {
+                path: git_dir.join("refs/heads/garbage"),
+                kind: Kind::MalformedContent {
+                    content: "not a hash\n".into()
+                }
+            },
+            Finding {
+                path: git_dir.join("refs/heads/in..valid"),
+                kind: Kind::InvalidName {
+                    name: "refs/heads/in..valid".into()
+                }
+            },
+            Finding {
+                path: git_dir.join("refs/heads/invalid-target"),
+                kind: Kind::InvalidSymbolicTarget {
+                    name: "refs/heads/invalid-target".try_into()?,
+                    target: "refs/heads/a..b".into(),
+                }
+            },
+            Finding {
+                path: git_dir.join("refs/heads/dangling"),
+                kind: Kind::DanglingSymbolicRef {
+                    name: "refs/heads/dangling".try_into()?,
+                    target: "refs/heads/missing".try_into()?,
+                }
+            },
+            Finding {
+                path: git_dir.join("logs/refs/heads/main"),
+                kind: Kind::MalformedReflogLine {
+                    line_number: 2,
+                    line: "not a reflog line".into(),
+                }
+            },
+        ]
+    );
+    Ok(())
+}
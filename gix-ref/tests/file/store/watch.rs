@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use gix_ref::{
+    file::watch::{Change, Options},
+    Target,
+};
+
+use crate::file::transaction::prepare_and_commit::empty_store;
+
+const A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+fn write(store: &gix_ref::file::Store, rela_path: &str, content: &str) -> crate::Result {
+    let path = store.git_dir().join(rela_path);
+    std::fs::create_dir_all(path.parent().expect("parent"))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn id(hex: &str) -> Target {
+    Target::Object(gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("valid"))
+}
+
+#[test]
+fn changes_between_states_are_ordered_by_name() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    write(&store, "HEAD", "ref: refs/heads/main\n")?;
+    write(&store, "refs/heads/main", &format!("{A}\n"))?;
+    write(&store, "refs/heads/deleted", &format!("{A}\n"))?;
+    write(&store, "packed-refs", &format!("{A} refs/tags/packed\n"))?;
+    let previous = store.watch_state()?;
+    assert_eq!(
+        previous.iter().count(),
+        4,
+        "HEAD is included along with loose and packed refs"
+    );
+    assert_eq!(previous.changes_to(&previous), Vec::new());
+
+    write(&store, "HEAD", "ref: refs/heads/new\n")?;
+    write(&store, "refs/heads/main", &format!("{B}\n"))?;
+    write(&store, "refs/heads/new", &format!("{A}\n"))?;
+    std::fs::remove_file(store.git_dir().join("refs/heads/deleted"))?;
+    write(&store, "packed-refs", &format!("{B} refs/tags/packed\n"))?;
+    let new = store.watch_state()?;
+
+    assert_eq!(
+        previous.changes_to(&new),
+        vec![
+            Change::Updated {
+                name: "HEAD".try_into()?,
+                previous: Target::Symbolic("refs/heads/main".try_into()?),
+                new: Target::Symbolic("refs/heads/new".try_into()?),
+            },
+            Change::Deleted {
+                name: "refs/heads/deleted".try_into()?,
+                previous: id(A),
+            },
+            Change::Updated {
+                name: "refs/heads/main".try_into()?,
+                previous: id(A),
+                new: id(B),
+            },
+            Change::Created {
+                name: "refs/heads/new".try_into()?,
+                new: id(A),
+            },
+            Change::Updated {
+                name: "refs/tags/packed".try_into()?,
+                previous: id(A),
+                new: id(B),
+            },
+        ],
+        "changes to packed-refs are visible even if they happen in the same second"
+    );
+    Ok(())
+}
+
+#[test]
+fn polling_watcher_observes_changes() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    write(&store, "refs/heads/main", &format!("{A}\n"))?;
+    let mut watcher = store.watch(Options {
+        native: false,
+        interval: Duration::from_millis(1),
+    })?;
+    assert!(!watcher.is_native());
+    assert_eq!(watcher.changes()?, Vec::new(), "nothing changed yet");
+
+    write(&store, "refs/heads/main", &format!("{B}\n"))?;
+    let expected = vec![Change::Updated {
+        name: "refs/heads/main".try_into()?,
+        previous: id(A),
+        new: id(B),
+    }];
+    assert_eq!(watcher.changes()?, expected);
+    assert_eq!(watcher.changes()?, Vec::new(), "changes are only reported once");
+
+    std::fs::remove_file(store.git_dir().join("refs/heads/main"))?;
+    assert_eq!(
+        watcher.next().expect("never ends")?,
+        vec![Change::Deleted {
+            name: "refs/heads/main".try_into()?,
+            previous: id(B),
+        }]
+    );
+    assert_eq!(watcher.state().iter().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn native_watcher_observes_changes_or_falls_back_to_polling() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    std::fs::create_dir_all(store.git_dir().join("refs/heads"))?;
+    let mut watcher = store.watch(Options {
+        native: true,
+        interval: Duration::from_millis(10),
+    })?;
+
+    write(&store, "refs/heads/new", &format!("{A}\n"))?;
+    assert_eq!(
+        watcher.wait_for_changes()?,
+        vec![Change::Created {
+            name: "refs/heads/new".try_into()?,
+            new: id(A),
+        }]
+    );
+    Ok(())
+}
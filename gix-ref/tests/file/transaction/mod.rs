@@ -75,7 +75,7 @@ pub(crate) mod prepare_and_commit {
         }
     }
 
-    fn delete_at(name: &str) -> RefEdit {
+    pub(crate) fn delete_at(name: &str) -> RefEdit {
         RefEdit {
             change: Change::Delete {
                 expected: PreviousValue::Any,
@@ -86,7 +86,17 @@ pub(crate) mod prepare_and_commit {
         }
     }
 
+    mod copy;
+
     mod create_or_update;
 
     mod delete;
+
+    mod hook;
+
+    mod ignore_case;
+
+    mod log_message;
+
+    mod rename;
 }
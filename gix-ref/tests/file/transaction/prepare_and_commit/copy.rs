@@ -0,0 +1,204 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file::transaction::prepare,
+    transaction::{Change, PreviousValue, RefEdit},
+    Target,
+};
+
+use crate::{
+    file::{
+        store_writable,
+        transaction::prepare_and_commit::{committer, create_at, empty_store, log_line, reflog_lines},
+    },
+    hex_to_id,
+};
+
+fn copy(name: &str, new_name: &str, expected: PreviousValue, new_name_expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Copy {
+            expected,
+            new_name: new_name.try_into().expect("valid"),
+            new_name_expected,
+            message: format!("copied {name} to {new_name}").into(),
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+#[test]
+fn duplicates_the_ref_and_its_reflog_and_records_the_copy() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            Some(create_at("refs/heads/a/original")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/a/original",
+                "refs/heads/b/copy",
+                PreviousValue::MustExistAndMatch(Target::Object(id)),
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert_eq!(edits.len(), 2, "copies are split into two updates");
+    assert_eq!(edits[0].name.as_bstr(), "refs/heads/a/original");
+    assert_eq!(
+        edits[0].change.previous_value().expect("existed").id(),
+        edits[0].change.new_value().expect("update").id(),
+        "the original keeps its value"
+    );
+    assert_eq!(edits[1].name.as_bstr(), "refs/heads/b/copy");
+    assert_eq!(edits[1].change.new_value().expect("update").id(), id);
+
+    assert_eq!(store.find_loose("refs/heads/a/original")?.target, Target::Object(id));
+    assert_eq!(store.find_loose("refs/heads/b/copy")?.target, Target::Object(id));
+    let original_log = vec![log_line(gix_hash::Kind::Sha1.null(), id, "log peeled")];
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/a/original")?,
+        original_log,
+        "the reflog of the original is unchanged"
+    );
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/b/copy")?,
+        vec![
+            original_log[0].clone(),
+            log_line(id, id, "copied refs/heads/a/original to refs/heads/b/copy")
+        ],
+        "the reflog is copied and receives the copy"
+    );
+    Ok(())
+}
+
+#[test]
+fn the_expected_values_of_source_and_destination_are_verified() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [create_at("refs/heads/original"), create_at("refs/heads/taken")],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let outdated = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let err = store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/original",
+                "refs/heads/copy",
+                PreviousValue::MustExistAndMatch(Target::Object(outdated)),
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(matches!(err, prepare::Error::ReferenceOutOfDate { .. }), "{err:?}");
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/missing",
+                "refs/heads/copy",
+                PreviousValue::Any,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, prepare::Error::DeleteReferenceMustExist { .. }),
+        "copied refs must exist: {err:?}"
+    );
+    assert!(store.try_find_loose("refs/heads/copy")?.is_none());
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/original",
+                "refs/heads/taken",
+                PreviousValue::Any,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, prepare::Error::MustNotExist { .. }),
+        "existing refs aren't overwritten even if they have the same value: {err:?}"
+    );
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/original",
+                "refs/heads/taken",
+                PreviousValue::Any,
+                PreviousValue::MustExistAndMatch(Target::Object(outdated)),
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(matches!(err, prepare::Error::ReferenceOutOfDate { .. }), "{err:?}");
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/original",
+                "refs/heads/original/copy",
+                PreviousValue::Any,
+                PreviousValue::Any,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(matches!(err, prepare::Error::NameConflict { .. }), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn packed_refs_can_be_copied() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+    assert!(store.try_find_loose("main")?.is_none(), "main is packed");
+
+    let id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    store
+        .transaction()
+        .prepare(
+            Some(copy(
+                "refs/heads/main",
+                "refs/heads/copy",
+                PreviousValue::MustExist,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(store.find("main")?.target, Target::Object(id), "the original remains");
+    assert_eq!(store.find("copy")?.target, Target::Object(id));
+    Ok(())
+}
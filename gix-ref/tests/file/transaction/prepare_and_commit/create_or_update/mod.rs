@@ -54,6 +54,113 @@ fn intermediate_directories_are_removed_on_rollback() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn prepared_edits_are_split_and_know_their_previous_value_before_anything_is_written() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [
+                create_symbolic_at("HEAD", "refs/heads/main"),
+                create_at("refs/heads/main"),
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let previous = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let transaction = store.transaction().prepare(
+        Some(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::Any,
+                new: Target::Object(new),
+            },
+            name: "HEAD".try_into()?,
+            deref: true,
+        }),
+        Fail::Immediately,
+        Fail::Immediately,
+    )?;
+    let edits: Vec<_> = transaction.prepared_edits().cloned().collect();
+    assert_eq!(
+        edits.iter().map(|e| e.name.as_bstr()).collect::<Vec<_>>(),
+        ["HEAD", "refs/heads/main"],
+        "symbolic refs are split"
+    );
+    assert_eq!(
+        edits[1].change.previous_value(),
+        Some(Target::Object(previous).to_ref()),
+        "the value that is locked is known"
+    );
+    assert_eq!(
+        store.find("main")?.target,
+        Target::Object(previous),
+        "nothing is written before committing"
+    );
+
+    transaction.commit(committer().to_ref())?;
+    assert_eq!(store.find("main")?.target, Target::Object(new));
+    Ok(())
+}
+
+#[test]
+fn dry_runs_return_the_edits_of_a_commit_without_writing_anything() -> crate::Result {
+    let (dir, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [
+                create_symbolic_at("HEAD", "refs/heads/main"),
+                create_at("refs/heads/main"),
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let edits = || {
+        [
+            RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    expected: PreviousValue::Any,
+                    new: Target::Object(hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242")),
+                },
+                name: "HEAD".try_into().expect("valid"),
+                deref: true,
+            },
+            create_at("refs/heads/a/new"),
+        ]
+    };
+    let dry_run_edits = store
+        .transaction()
+        .prepare(edits(), Fail::Immediately, Fail::Immediately)?
+        .commit_dry_run();
+    assert_eq!(
+        store.find("main")?.target,
+        Target::Object(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")),
+        "nothing was changed"
+    );
+    assert!(store.try_find("a/new")?.is_none());
+    assert!(
+        !dir.path().join("refs/heads/a").exists(),
+        "locks were released and their directories removed"
+    );
+
+    let committed_edits = store
+        .transaction()
+        .prepare(edits(), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        dry_run_edits, committed_edits,
+        "dry runs return the edits exactly like a commit would"
+    );
+    Ok(())
+}
+
 #[test]
 fn reference_with_equally_named_empty_or_non_empty_directory_already_in_place_can_potentially_recover() -> crate::Result
 {
@@ -162,6 +269,161 @@ fn reference_with_explicit_value_must_match_the_value_on_update() -> crate::Resu
     Ok(())
 }
 
+#[test]
+fn symbolic_target_is_verified_independently_of_the_referent_value() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let previous_id = hex_to_id("02a7a22d90d7c02fb494ed25551850b868e634f0");
+    let new_id = hex_to_id("01dd4e2a978a9f5bd773dae6da7aa4a5ac1cdbbc");
+    let edit = |target: &str, referent_id: ObjectId| -> crate::Result<RefEdit> {
+        Ok(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::SymbolicMustMatch {
+                    target: target.try_into()?,
+                    referent: Box::new(PreviousValue::MustExistAndMatch(Target::Object(referent_id))),
+                },
+                new: Target::Object(new_id),
+            },
+            name: "HEAD".try_into()?,
+            deref: true,
+        })
+    };
+
+    let res = store.transaction().prepare(
+        Some(edit("refs/heads/other", previous_id)?),
+        Fail::Immediately,
+        Fail::Immediately,
+    );
+    match res {
+        Err(transaction::prepare::Error::ReferenceOutOfDate {
+            full_name,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(full_name, "HEAD", "the symbolic ref itself is checked");
+            assert_eq!(expected, Target::Symbolic("refs/heads/other".try_into()?));
+            assert_eq!(actual, Target::Symbolic("refs/heads/main".try_into()?));
+        }
+        _ => unreachable!("unexpected result"),
+    }
+
+    let res = store.transaction().prepare(
+        Some(edit("refs/heads/main", new_id)?),
+        Fail::Immediately,
+        Fail::Immediately,
+    );
+    match res {
+        Err(transaction::prepare::Error::ReferenceOutOfDate { full_name, actual, .. }) => {
+            assert_eq!(full_name, "refs/heads/main", "the referent is checked as well");
+            assert_eq!(actual, Target::Object(previous_id));
+        }
+        _ => unreachable!("unexpected result"),
+    }
+
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(edit("refs/heads/main", previous_id)?),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 2, "HEAD and its referent");
+    assert_eq!(
+        edits[0].change.previous_value(),
+        Some(Target::Symbolic("refs/heads/main".try_into()?).to_ref())
+    );
+    assert_eq!(
+        edits[1].change.previous_value(),
+        Some(Target::Object(previous_id).to_ref())
+    );
+    assert_eq!(store.find_loose("main")?.target.into_id(), new_id);
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.last().expect("at least one line"),
+        &log_line(previous_id, new_id, ""),
+        "HEAD's reflog is updated as the change is made through it"
+    );
+    Ok(())
+}
+
+#[test]
+fn symbolic_target_expectation_fails_if_the_reference_does_not_exist() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let res = store.transaction().prepare(
+        Some(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::SymbolicMustMatch {
+                    target: "refs/heads/main".try_into()?,
+                    referent: Box::new(PreviousValue::Any),
+                },
+                new: Target::Symbolic("refs/heads/new".try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        }),
+        Fail::Immediately,
+        Fail::Immediately,
+    );
+    match res {
+        Err(transaction::prepare::Error::MustExist { full_name, expected }) => {
+            assert_eq!(full_name, "HEAD");
+            assert_eq!(expected, Target::Symbolic("refs/heads/main".try_into()?));
+        }
+        _ => unreachable!("unexpected result"),
+    }
+    Ok(())
+}
+
+#[test]
+fn symbolic_reference_can_point_to_new_referent_if_its_target_matches() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let edit = |expected_target: &str| -> crate::Result<RefEdit> {
+        Ok(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::SymbolicMustMatch {
+                    target: expected_target.try_into()?,
+                    referent: Box::new(PreviousValue::MustNotExist),
+                },
+                new: Target::Symbolic("refs/heads/new".try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        })
+    };
+
+    let edits = store
+        .transaction()
+        .prepare(Some(edit("refs/heads/main")?), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 1, "the referent expectation is ignored without deref");
+    assert_eq!(
+        edits[0].change.previous_value(),
+        Some(Target::Symbolic("refs/heads/main".try_into()?).to_ref())
+    );
+    assert_eq!(
+        store.find_loose("HEAD")?.target,
+        Target::Symbolic("refs/heads/new".try_into()?)
+    );
+
+    let res = store
+        .transaction()
+        .prepare(Some(edit("refs/heads/main")?), Fail::Immediately, Fail::Immediately);
+    match res {
+        Err(transaction::prepare::Error::ReferenceOutOfDate { full_name, actual, .. }) => {
+            assert_eq!(full_name, "HEAD");
+            assert_eq!(
+                actual,
+                Target::Symbolic("refs/heads/new".try_into()?),
+                "a concurrent change is detected"
+            );
+        }
+        _ => unreachable!("unexpected result"),
+    }
+    Ok(())
+}
+
 #[test]
 fn the_existing_must_match_constraint_allow_non_existing_references_to_be_created() -> crate::Result {
     let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
@@ -189,11 +451,12 @@ fn the_existing_must_match_constraint_allow_non_existing_references_to_be_create
             change: Change::Update {
                 log: LogChange::default(),
                 new: Target::Object(gix_hash::Kind::Sha1.null()),
-                expected,
+                expected: PreviousValue::MustNotExist,
             },
             name: "refs/heads/new".try_into()?,
             deref: false,
-        }]
+        }],
+        "the expectation is replaced to indicate there was no previous value"
     );
     Ok(())
 }
@@ -266,6 +529,178 @@ fn namespaced_updates_or_deletions_are_transparent_and_not_observable() -> crate
     Ok(())
 }
 
+#[test]
+fn namespaced_edits_are_written_into_the_namespace_and_only_visible_from_within() -> crate::Result {
+    let (dir, mut store) = empty_store()?;
+    store.namespace = gix_ref::namespace::expand("foo")?.into();
+    store
+        .transaction()
+        .prepare(
+            [
+                create_at("refs/heads/main"),
+                create_symbolic_at("HEAD", "refs/heads/main"),
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert!(dir.path().join("refs/namespaces/foo/refs/heads/main").is_file());
+    assert!(dir.path().join("refs/namespaces/foo/HEAD").is_file());
+    assert!(
+        !dir.path().join("refs/heads/main").exists(),
+        "nothing is written outside of the namespace"
+    );
+
+    let main = store.find("main")?;
+    assert_eq!(main.name.as_bstr(), "refs/heads/main", "the namespace isn't observable");
+    let head = store.find("HEAD")?;
+    assert_eq!(
+        head.target.try_name().map(gix_ref::FullNameRef::as_bstr),
+        Some("refs/heads/main".into())
+    );
+    assert_eq!(
+        store
+            .iter()?
+            .all()?
+            .map(|r| r.map(|r| r.name.as_bstr().to_owned()))
+            .collect::<Result<Vec<_>, _>>()?,
+        ["HEAD", "refs/heads/main"],
+        "iteration only yields references from within the namespace"
+    );
+    assert_eq!(reflog_lines(&store, "refs/heads/main")?.len(), 1);
+
+    store.namespace = None;
+    assert!(
+        store.try_find("main")?.is_none(),
+        "the reference isn't visible outside the namespace"
+    );
+    assert!(store.try_find("refs/namespaces/foo/refs/heads/main")?.is_some());
+    Ok(())
+}
+
+#[test]
+fn committed_edits_carry_the_actual_previous_value_even_if_no_expectation_was_set() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let previous_head = store.find_loose("HEAD")?.target;
+    assert!(
+        matches!(previous_head, Target::Symbolic(_)),
+        "HEAD is symbolic in this fixture"
+    );
+
+    let edits = store
+        .transaction()
+        .prepare(
+            [
+                RefEdit {
+                    change: Change::Update {
+                        log: LogChange::default(),
+                        expected: PreviousValue::Any,
+                        new: Target::Symbolic("refs/heads/new".try_into()?),
+                    },
+                    name: "HEAD".try_into()?,
+                    deref: false,
+                },
+                RefEdit {
+                    change: Change::Update {
+                        log: LogChange::default(),
+                        expected: PreviousValue::Any,
+                        new: Target::Object(hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242")),
+                    },
+                    name: "refs/heads/new".try_into()?,
+                    deref: false,
+                },
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert_eq!(edits.len(), 2);
+    assert_eq!(
+        edits[0].change.previous_value(),
+        Some(previous_head.to_ref()),
+        "the symbolic ref reports what it pointed to before"
+    );
+    assert_eq!(
+        edits[1].change.previous_value(),
+        None,
+        "the created ref didn't exist before"
+    );
+    assert!(matches!(
+        edits[1].change,
+        Change::Update {
+            expected: PreviousValue::MustNotExist,
+            ..
+        }
+    ));
+    Ok(())
+}
+
+#[test]
+fn synced_edits_are_written_to_loose_refs_packed_refs_and_reflogs() -> crate::Result {
+    for method in [
+        gix_fs::fsync::Method::Fsync,
+        gix_fs::fsync::Method::WriteoutOnly,
+        gix_fs::fsync::Method::Batch,
+    ] {
+        let (_keep, mut store) = store_writable("make_repo_for_reflog.sh")?;
+        store.write_reflog = WriteReflog::Always;
+        store.fsync = Some(method);
+        let num_head_log_lines = reflog_lines(&store, "HEAD")?.len();
+        let new = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+        let edits = store
+            .transaction()
+            .packed_refs(PackedRefs::DeletionsAndNonSymbolicUpdates(Box::new(EmptyCommit)))
+            .prepare(
+                [
+                    RefEdit {
+                        change: Change::Update {
+                            log: LogChange::default(),
+                            expected: PreviousValue::Any,
+                            new: Target::Symbolic("refs/heads/new".try_into()?),
+                        },
+                        name: "HEAD".try_into()?,
+                        deref: false,
+                    },
+                    RefEdit {
+                        change: Change::Update {
+                            log: LogChange::default(),
+                            expected: PreviousValue::Any,
+                            new: Target::Object(new),
+                        },
+                        name: "refs/heads/new".try_into()?,
+                        deref: false,
+                    },
+                ],
+                Fail::Immediately,
+                Fail::Immediately,
+            )?
+            .commit(committer().to_ref())?;
+        assert_eq!(edits.len(), 2);
+
+        assert_eq!(
+            store.find_loose("HEAD")?.target,
+            Target::Symbolic("refs/heads/new".try_into()?)
+        );
+        assert_eq!(
+            store
+                .open_packed_buffer()?
+                .expect("created")
+                .find("refs/heads/new")?
+                .target(),
+            new,
+            "the object update went into packed-refs"
+        );
+        assert_eq!(
+            reflog_lines(&store, "HEAD")?.len(),
+            num_head_log_lines + 1,
+            "a line was appended"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn reference_with_must_exist_constraint_must_exist_already_with_any_value() -> crate::Result {
     let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
@@ -431,6 +866,134 @@ fn symbolic_reference_writes_reflog_if_previous_value_is_set() -> crate::Result
     Ok(())
 }
 
+#[test]
+fn symbolic_reference_creation_and_retargeting_logs_the_peeled_ids_of_its_targets() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let first = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let second = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let update = |name: &str, expected: PreviousValue, new: Target, message: &str| RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: message.into(),
+            },
+            expected,
+            new,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    };
+    let symbolic = |name: &str| Target::Symbolic(name.try_into().expect("valid"));
+
+    store
+        .transaction()
+        .prepare(
+            [
+                update(
+                    "refs/heads/main",
+                    PreviousValue::MustNotExist,
+                    Target::Object(first),
+                    "create",
+                ),
+                update("HEAD", PreviousValue::MustNotExist, symbolic("refs/heads/main"), "init"),
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?,
+        vec![log_line(ObjectId::null(gix_hash::Kind::Sha1), first, "init")],
+        "the referent is created in the same transaction, and its new value is used"
+    );
+
+    store
+        .transaction()
+        .prepare(
+            Some(update(
+                "refs/heads/other",
+                PreviousValue::MustNotExist,
+                Target::Object(second),
+                "create",
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(update(
+                "HEAD",
+                PreviousValue::MustExistAndMatch(symbolic("refs/heads/main")),
+                symbolic("refs/heads/other"),
+                "checkout: moving from main to other",
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 1, "no split was performed as HEAD itself is changed");
+    assert_eq!(
+        store.find_loose("HEAD")?.target,
+        symbolic("refs/heads/other"),
+        "the symbolic ref was retargeted"
+    );
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?,
+        vec![
+            log_line(ObjectId::null(gix_hash::Kind::Sha1), first, "init"),
+            log_line(first, second, "checkout: moving from main to other")
+        ],
+        "the log of the symbolic ref contains the peeled ids of the previous and the new target"
+    );
+    assert!(
+        store.reflog_exists("refs/heads/other")?,
+        "the referent's own log is untouched and was only written on creation"
+    );
+    assert_eq!(reflog_lines(&store, "refs/heads/other")?.len(), 1);
+
+    store
+        .transaction()
+        .prepare(
+            Some(update(
+                "HEAD",
+                PreviousValue::Any,
+                symbolic("refs/heads/other"),
+                "no change",
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.len(),
+        2,
+        "setting the same target again isn't logged"
+    );
+
+    store
+        .transaction()
+        .prepare(
+            Some(update(
+                "HEAD",
+                PreviousValue::Any,
+                symbolic("refs/heads/unborn"),
+                "to unborn",
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.len(),
+        2,
+        "pointing to an unborn branch isn't logged as there is no id to log"
+    );
+    Ok(())
+}
+
 #[test]
 fn windows_device_name_is_illegal_with_enabled_windows_protections() -> crate::Result {
     let (_keep, mut store) = empty_store()?;
@@ -488,6 +1051,65 @@ fn windows_device_name_is_illegal_with_enabled_windows_protections() -> crate::R
     Ok(())
 }
 
+#[test]
+fn windows_illegal_characters_and_trailing_dots_are_illegal_with_enabled_windows_protections() -> crate::Result {
+    let (_keep, mut store) = empty_store()?;
+    store.prohibit_windows_device_names = true;
+    let log_ignored = LogChange {
+        mode: RefLog::AndReference,
+        force_create_reflog: false,
+        message: "ignored".into(),
+    };
+
+    let new = Target::Object(hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242"));
+    for invalid_name in [
+        "refs/heads/a|b",
+        "refs/heads/a<b>",
+        "refs/heads/\"quoted\"",
+        "refs/heads/a./b",
+    ] {
+        let err = store
+            .transaction()
+            .prepare(
+                Some(RefEdit {
+                    change: Change::Update {
+                        log: log_ignored.clone(),
+                        new: new.clone(),
+                        expected: PreviousValue::Any,
+                    },
+                    name: invalid_name.try_into()?,
+                    deref: false,
+                }),
+                Fail::Immediately,
+                Fail::Immediately,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.source().expect("inner").to_string(),
+            format!("Illegal characters or trailing dots or spaces for use on Windows in \"{invalid_name}\"")
+        );
+    }
+
+    #[cfg(not(windows))]
+    {
+        store.prohibit_windows_device_names = false;
+        let _prepared_transaction = store.transaction().prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: log_ignored,
+                    new,
+                    expected: PreviousValue::Any,
+                },
+                name: "refs/heads/a./b|c".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?;
+    }
+    Ok(())
+}
+
 #[test]
 fn symbolic_head_missing_referent_then_update_referent() -> crate::Result {
     for reflog_writemode in &[WriteReflog::Normal, WriteReflog::Disable, WriteReflog::Always] {
@@ -593,7 +1215,7 @@ fn symbolic_head_missing_referent_then_update_referent() -> crate::Result {
                     change: Change::Update {
                         log,
                         new: new.clone(),
-                        expected: PreviousValue::Any, // there is no previous value, so we can't put `MustExistAndMatch` here.
+                        expected: PreviousValue::MustNotExist, // there is no previous value, so we can't put `MustExistAndMatch` here.
                     },
                     name: referent.try_into()?,
                     deref: false,
@@ -753,6 +1375,154 @@ fn packed_refs_are_looked_up_when_checking_existing_values() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn reflog_only_update_of_packed_ref_uses_its_value_as_previous_value() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+    assert!(
+        store.try_find_loose("main")?.is_none(),
+        "no loose main available, it's packed"
+    );
+    let old_id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    let new_id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::Only,
+                        force_create_reflog: false,
+                        message: "log only".into(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Object(old_id)),
+                    new: Target::Object(new_id),
+                },
+                name: "refs/heads/main".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/main")?
+            .last()
+            .expect("at least one line"),
+        &log_line(old_id, new_id, "log only"),
+        "the previous value is obtained from packed refs"
+    );
+    assert!(store.try_find_loose("main")?.is_none(), "no loose ref was created");
+    assert_eq!(
+        store.find("main")?.target.into_id(),
+        old_id,
+        "the reference itself is unchanged"
+    );
+    Ok(())
+}
+
+#[test]
+fn symbolic_reference_reflog_uses_the_ids_of_packed_targets() -> crate::Result {
+    let main_id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    let new_id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let tag_id = hex_to_id("4c3f4cce493d7beb45012e478021b5f65295e5a3");
+    let update_head = |new: Target| RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: "m".into(),
+            },
+            expected: PreviousValue::Any,
+            new,
+        },
+        name: "HEAD".try_into().expect("valid"),
+        deref: false,
+    };
+    let symbolic = |name: &str| Target::Symbolic(name.try_into().expect("valid"));
+
+    for (new, expected_new_id, message) in [
+        (
+            Target::Object(new_id),
+            new_id,
+            "detaching logs the id of the packed branch HEAD was on",
+        ),
+        (
+            symbolic("refs/heads/d1"),
+            main_id,
+            "the new target is peeled through packed refs",
+        ),
+        (
+            symbolic("refs/multi-link"),
+            main_id,
+            "chains of symbolic refs are followed",
+        ),
+        (
+            symbolic("refs/tags/dt1"),
+            tag_id,
+            "annotated tags aren't peeled, like git does",
+        ),
+    ] {
+        let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+        assert!(
+            store.try_find_loose("main")?.is_none(),
+            "HEAD points to a branch that is only packed"
+        );
+        store
+            .transaction()
+            .prepare(Some(update_head(new)), Fail::Immediately, Fail::Immediately)?
+            .commit(committer().to_ref())?;
+        assert_eq!(
+            reflog_lines(&store, "HEAD")?.last().expect("at least one line"),
+            &log_line(main_id, expected_new_id, "m"),
+            "{message}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn packed_ref_deleted_and_recreated_starts_a_new_reflog() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+    let old_id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    store
+        .transaction()
+        .prepare(Some(delete_at("refs/heads/main")), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert!(
+        !store.reflog_exists("refs/heads/main")?,
+        "the reflog is deleted with the packed ref"
+    );
+
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "recreate".into(),
+                    },
+                    expected: PreviousValue::MustNotExist,
+                    new: Target::Object(old_id),
+                },
+                name: "refs/heads/main".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/main")?,
+        vec![log_line(gix_hash::Kind::Sha1.null(), old_id, "recreate")],
+        "nothing of the previous reflog remains"
+    );
+    Ok(())
+}
+
 #[test]
 fn packed_refs_creation_with_tag_loop_are_not_handled_and_cannot_exist_due_to_object_hashes() {
     // Tag loops cannot exist as you cannot create them thanks to hashing.
@@ -907,3 +1677,61 @@ fn packed_refs_deletion_in_deletions_and_updates_mode() -> crate::Result {
     );
     Ok(())
 }
+
+#[test]
+fn packed_refs_without_peeled_values_are_rewritten_fully_peeled_and_sorted() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+    let packed_refs_path = store.packed_refs_path();
+    let original: BString = std::fs::read(&packed_refs_path)?.into();
+    let mut lines: Vec<_> = original
+        .lines()
+        .filter(|line| !line.starts_with(b"#") && !line.starts_with(b"^"))
+        .collect();
+    lines.reverse();
+    let mut unsorted_without_header = lines.join(&b'\n');
+    unsorted_without_header.push(b'\n');
+    std::fs::write(&packed_refs_path, unsorted_without_header)?;
+
+    let packed = store.open_packed_buffer()?.expect("packed refs is available");
+    assert_eq!(packed.peeled(), gix_ref::packed::Peeled::Unspecified);
+    assert_eq!(
+        packed.try_find("refs/tags/dt1")?.expect("present").object,
+        None,
+        "without header and peeled values, we can't know if this ref is peeled"
+    );
+
+    let odb = gix_odb::at(store.git_dir().join("objects"))?;
+    store
+        .transaction()
+        .packed_refs(PackedRefs::DeletionsAndNonSymbolicUpdates(Box::new(odb)))
+        .prepare(
+            Some(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                },
+                name: "refs/heads/d1".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let actual: BString = std::fs::read(&packed_refs_path)?.into();
+    let expected: BString = original
+        .lines_with_terminator()
+        .filter(|line| !line.ends_with(b" refs/heads/d1\n"))
+        .flat_map(|line| line.iter().copied())
+        .collect::<Vec<_>>()
+        .into();
+    assert_eq!(
+        actual, expected,
+        "the header is written and the peeled values of all existing refs are recomputed, just like git would"
+    );
+    assert_eq!(
+        store.open_packed_buffer()?.expect("still present").peeled(),
+        gix_ref::packed::Peeled::Fully
+    );
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use gix_lock::acquire::Fail;
 use gix_ref::{
-    file::ReferenceExt,
+    file::{transaction, ReferenceExt},
     transaction::{Change, PreviousValue, RefEdit, RefLog},
     Reference, Target,
 };
@@ -8,7 +8,7 @@ use gix_ref::{
 use crate::{
     file::{
         store_writable,
-        transaction::prepare_and_commit::{committer, empty_store},
+        transaction::prepare_and_commit::{committer, create_at, delete_at, empty_store},
     },
     hex_to_id,
 };
@@ -60,6 +60,52 @@ fn delete_a_ref_which_is_gone_but_must_exist_fails() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn delete_with_symbolic_expectation_fails_if_ref_is_missing_or_points_elsewhere() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let delete = |name: &str| -> crate::Result<RefEdit> {
+        Ok(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::SymbolicMustMatch {
+                    target: "refs/heads/other".try_into()?,
+                    referent: Box::new(PreviousValue::Any),
+                },
+                log: RefLog::AndReference,
+            },
+            name: name.try_into()?,
+            deref: false,
+        })
+    };
+
+    let res = store
+        .transaction()
+        .prepare(Some(delete("DOES_NOT_EXIST")?), Fail::Immediately, Fail::Immediately);
+    match res {
+        Err(transaction::prepare::Error::DeleteReferenceMustExist { full_name }) => {
+            assert_eq!(full_name, "DOES_NOT_EXIST");
+        }
+        _ => unreachable!("the symbolic reference must exist to be deleted"),
+    }
+
+    let res = store
+        .transaction()
+        .prepare(Some(delete("HEAD")?), Fail::Immediately, Fail::Immediately);
+    match res {
+        Err(transaction::prepare::Error::ReferenceOutOfDate {
+            full_name,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(full_name, "HEAD");
+            assert_eq!(expected, Target::Symbolic("refs/heads/other".try_into()?));
+            assert_eq!(actual, Target::Symbolic("refs/heads/main".try_into()?));
+        }
+        _ => unreachable!("HEAD points to a different reference"),
+    }
+    assert!(store.try_find_loose("HEAD")?.is_some(), "nothing was deleted");
+    Ok(())
+}
+
 #[test]
 fn delete_ref_and_reflog_on_symbolic_no_deref() -> crate::Result {
     let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
@@ -263,12 +309,44 @@ fn non_existing_can_be_deleted_with_the_may_exist_match_constraint() -> crate::R
         edits,
         vec![RefEdit {
             change: Change::Delete {
-                expected: previous_value,
+                expected: PreviousValue::Any,
                 log: RefLog::AndReference,
             },
             name: "refs/heads/not-there".try_into()?,
             deref: false,
-        }]
+        }],
+        "the expectation is replaced to indicate there was no previous value"
+    );
+    Ok(())
+}
+
+#[test]
+fn returned_deletions_of_non_existing_refs_can_be_submitted_again() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(delete_at("refs/heads/not-there")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        edits[0].change,
+        Change::Delete {
+            expected: PreviousValue::Any,
+            log: RefLog::AndReference,
+        },
+        "deletions can't expect the reference to not exist"
+    );
+
+    let resubmitted = store
+        .transaction()
+        .prepare(edits.clone(), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        resubmitted, edits,
+        "nothing changes as the reference still doesn't exist"
     );
     Ok(())
 }
@@ -380,6 +458,43 @@ fn packed_refs_are_consulted_when_determining_previous_value_of_ref_to_be_delete
     Ok(())
 }
 
+#[test]
+fn reflog_only_deletion_of_packed_ref_checks_its_value_and_keeps_it() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+    assert!(
+        store.try_find_loose("main")?.is_none(),
+        "no loose main available, it's packed"
+    );
+    assert!(store.reflog_exists("refs/heads/main")?, "the packed ref has a reflog");
+
+    let old_id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(Target::Object(old_id)),
+                    log: RefLog::Only,
+                },
+                name: "refs/heads/main".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].change.previous_value(), Some(Target::Object(old_id).to_ref()));
+    assert!(!store.reflog_exists("refs/heads/main")?, "the reflog was deleted");
+    assert!(
+        store.open_packed_buffer()?.expect("packed").try_find("main")?.is_some(),
+        "the packed ref is still present"
+    );
+    assert!(store.try_find_loose("main")?.is_none(), "no loose ref was created");
+    Ok(())
+}
+
 #[test]
 fn a_loose_ref_with_old_value_check_and_outdated_packed_refs_value_deletes_both_refs() -> crate::Result {
     let (_keep, store) = store_writable("make_packed_ref_repository_for_overlay.sh")?;
@@ -457,3 +572,73 @@ fn all_contained_references_deletes_the_packed_ref_file_too() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+fn a_loose_ref_without_value_check_and_outdated_packed_refs_value_deletes_both_refs() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository_for_overlay.sh")?;
+    assert!(store.try_find_loose("newer-as-loose")?.is_some());
+    assert!(store
+        .open_packed_buffer()?
+        .expect("packed-refs")
+        .try_find("newer-as-loose")?
+        .is_some());
+
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                },
+                name: "refs/heads/newer-as-loose".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert_eq!(edits.len(), 1);
+    assert!(
+        store
+            .open_packed_buffer()?
+            .expect("packed-refs")
+            .try_find("newer-as-loose")?
+            .is_none(),
+        "the packed ref was removed as well, so it can't reappear"
+    );
+    assert!(
+        store.try_find("newer-as-loose")?.is_none(),
+        "reference is deleted everywhere"
+    );
+    Ok(())
+}
+
+#[test]
+fn synced_deletions_remove_references_their_reflogs_and_empty_directories() -> crate::Result {
+    let (dir, mut store) = empty_store()?;
+    store.write_reflog = gix_ref::store::WriteReflog::Always;
+    store.fsync = Some(gix_fs::fsync::Method::Fsync);
+    let name = "refs/heads/a/b/c";
+    store
+        .transaction()
+        .prepare(Some(create_at(name)), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert!(
+        store.try_find_loose(name)?.is_some(),
+        "new directories are created and synced"
+    );
+    assert!(dir.path().join("logs").join(name).is_file());
+
+    store
+        .transaction()
+        .prepare(Some(delete_at(name)), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert!(store.try_find_loose(name)?.is_none());
+    assert!(
+        !dir.path().join("refs/heads/a").exists() && !dir.path().join("logs/refs/heads/a").exists(),
+        "removed directories are skipped when syncing"
+    );
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file::transaction::hook::{State, Update},
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+    Target,
+};
+
+use crate::{
+    file::{
+        store_writable,
+        transaction::prepare_and_commit::{committer, create_at, delete_at},
+    },
+    hex_to_id,
+};
+
+fn update_head(new: &str) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected: PreviousValue::Any,
+            new: Target::Object(hex_to_id(new)),
+        },
+        name: "HEAD".try_into().expect("valid"),
+        deref: true,
+    }
+}
+
+#[test]
+fn receives_previous_and_new_values_when_prepared_and_committed() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main = store.find_loose("main")?.target;
+    let new = "28ce6a8b26aa170e1de65536fe8abe1832bd3242";
+    let mut calls = Vec::new();
+    store
+        .transaction()
+        .hook(|state, updates| {
+            calls.push((state, updates.to_vec()));
+            Ok(())
+        })
+        .prepare(
+            [
+                update_head(new),
+                create_at("refs/heads/new"),
+                delete_at("refs/heads/gone"),
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let expected = vec![
+        Update {
+            name: "refs/heads/new".try_into()?,
+            previous: None,
+            new: Some(Target::Object(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"))),
+        },
+        Update {
+            name: "refs/heads/gone".try_into()?,
+            previous: None,
+            new: None,
+        },
+        Update {
+            name: "refs/heads/main".try_into()?,
+            previous: Some(main.clone()),
+            new: Some(Target::Object(hex_to_id(new))),
+        },
+    ];
+    assert_eq!(
+        calls,
+        [(State::Prepared, expected.clone()), (State::Committed, expected)],
+        "dereferenced symbolic refs only change their reflog and are skipped, just like in git"
+    );
+
+    let mut buf = Vec::new();
+    for update in &calls[0].1 {
+        update.write_to(&mut buf, gix_hash::Kind::Sha1)?;
+    }
+    let null = gix_hash::Kind::Sha1.null();
+    assert_eq!(
+        buf,
+        format!(
+            "{null} e69de29bb2d1d6434b8b29ae775ad8c2e48c5391 refs/heads/new\n{null} {null} refs/heads/gone\n{} {new} refs/heads/main\n",
+            main.id()
+        )
+        .as_bytes()
+    );
+    Ok(())
+}
+
+#[test]
+fn rejection_when_prepared_aborts_the_transaction() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main = store.find_loose("main")?.target;
+    let mut states = Vec::new();
+    let err = store
+        .transaction()
+        .hook(|state, _updates| {
+            states.push(state);
+            Err("rejected".into())
+        })
+        .prepare(
+            Some(update_head("28ce6a8b26aa170e1de65536fe8abe1832bd3242")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "The transaction was rejected by its hook");
+    assert_eq!(states, [State::Prepared, State::Aborted]);
+    assert_eq!(store.find_loose("main")?.target, main, "nothing was changed");
+
+    store
+        .transaction()
+        .prepare(
+            Some(update_head("28ce6a8b26aa170e1de65536fe8abe1832bd3242")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    Ok(())
+}
+
+#[test]
+fn rollbacks_and_dry_runs_are_aborted() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let mut states = Vec::new();
+    let mut record = |state, _updates: &[Update]| {
+        states.push(state);
+        Ok(())
+    };
+    let edit = || Some(create_at("refs/heads/new"));
+    store
+        .transaction()
+        .hook(&mut record)
+        .prepare(edit(), Fail::Immediately, Fail::Immediately)?
+        .rollback();
+    store
+        .transaction()
+        .hook(&mut record)
+        .prepare(edit(), Fail::Immediately, Fail::Immediately)?
+        .commit_dry_run();
+    drop(
+        store
+            .transaction()
+            .hook(&mut record)
+            .prepare(edit(), Fail::Immediately, Fail::Immediately)?,
+    );
+    store.transaction().hook(&mut record);
+    assert_eq!(
+        states,
+        [
+            State::Prepared,
+            State::Aborted,
+            State::Prepared,
+            State::Aborted,
+            State::Prepared,
+            State::Aborted
+        ],
+        "unprepared transactions aren't reported"
+    );
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file::transaction::prepare,
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+    Target,
+};
+
+use crate::{
+    file::{
+        store_writable,
+        transaction::prepare_and_commit::{committer, create_at, delete_at, empty_store},
+    },
+    hex_to_id,
+};
+
+fn assert_case_conflict(err: prepare::Error, expected_full_name: &str, expected_conflicting: &str) {
+    match err {
+        prepare::Error::CaseConflict { full_name, conflicting } => {
+            assert_eq!(full_name, expected_full_name);
+            assert_eq!(conflicting, expected_conflicting);
+        }
+        err => unreachable!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn loose_refs_whose_name_only_differs_in_case_are_not_changed() -> crate::Result {
+    let (_keep, mut store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            Some(create_at("refs/heads/feature/main")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    store.ignore_case = true;
+    for (name, conflicting) in [
+        ("refs/heads/feature/MAIN", "refs/heads/feature/main"),
+        ("refs/heads/Feature/other", "refs/heads/feature"),
+    ] {
+        let err = store
+            .transaction()
+            .prepare(Some(create_at(name)), Fail::Immediately, Fail::Immediately)
+            .unwrap_err();
+        assert_case_conflict(err, name, conflicting);
+    }
+    let err = store
+        .transaction()
+        .prepare(
+            Some(delete_at("refs/heads/feature/Main")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert_case_conflict(err, "refs/heads/feature/Main", "refs/heads/feature/main");
+
+    store
+        .transaction()
+        .prepare(
+            [
+                create_at("refs/heads/feature/other"),
+                delete_at("refs/heads/feature/main"),
+            ],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert!(
+        store.try_find_loose("refs/heads/feature/other")?.is_some(),
+        "names that match exactly are fine"
+    );
+    Ok(())
+}
+
+#[test]
+fn refs_whose_name_only_differs_in_case_from_packed_refs_or_each_other_are_not_created() -> crate::Result {
+    let (_keep, mut store) = store_writable("make_packed_ref_repository.sh")?;
+    assert!(store.try_find_loose("main")?.is_none(), "main is packed");
+
+    store.ignore_case = true;
+    let err = store
+        .transaction()
+        .prepare(Some(create_at("refs/heads/MAIN")), Fail::Immediately, Fail::Immediately)
+        .unwrap_err();
+    assert_case_conflict(err, "refs/heads/MAIN", "refs/heads/main");
+
+    let err = store
+        .transaction()
+        .prepare(
+            [create_at("refs/heads/new"), create_at("refs/heads/NEW")],
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert_case_conflict(err, "refs/heads/NEW", "refs/heads/new");
+
+    let new_id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    expected: PreviousValue::MustExist,
+                    new: Target::Object(new_id),
+                },
+                name: "refs/heads/main".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(
+        store.find("main")?.target.into_id(),
+        new_id,
+        "existing refs can still be updated"
+    );
+
+    store.ignore_case = false;
+    store
+        .transaction()
+        .prepare(Some(create_at("refs/heads/MAIN")), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert!(
+        store.try_find_loose("refs/heads/MAIN")?.is_some(),
+        "without ignore-case, names that only differ in case are distinct"
+    );
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file,
+    transaction::{Change, LogChange, LogMessage, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    file::transaction::prepare_and_commit::{committer, create_at, empty_store, log_line, reflog_lines},
+    hex_to_id,
+};
+
+fn update_without_message(name: &str) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: true,
+                message: Default::default(),
+            },
+            expected: PreviousValue::MustNotExist,
+            new: Target::Object(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")),
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+#[test]
+fn templates_render_like_git() {
+    for (message, expected) in [
+        (LogMessage::UpdateByPush, "update by push"),
+        (
+            LogMessage::BranchCreatedFrom("main".into()),
+            "branch: Created from main",
+        ),
+        (LogMessage::BranchResetTo("HEAD".into()), "branch: Reset to HEAD"),
+        (
+            LogMessage::Renamed {
+                from: "refs/heads/a".try_into().expect("valid"),
+                to: "refs/heads/b".try_into().expect("valid"),
+            },
+            "Branch: renamed refs/heads/a to refs/heads/b",
+        ),
+        (
+            LogMessage::Copied {
+                from: "refs/heads/a".try_into().expect("valid"),
+                to: "refs/heads/b".try_into().expect("valid"),
+            },
+            "Branch: copied refs/heads/a to refs/heads/b",
+        ),
+        (LogMessage::ResetMovingTo("HEAD~1".into()), "reset: moving to HEAD~1"),
+        (
+            LogMessage::CheckoutMovingFrom {
+                from: "main".into(),
+                to: "feature".into(),
+            },
+            "checkout: moving from main to feature",
+        ),
+        (
+            LogMessage::CloneFrom("https://example.com/repo".into()),
+            "clone: from https://example.com/repo",
+        ),
+        (
+            LogMessage::BranchCreatedFrom("two\nlines".into()),
+            "branch: Created from two lines",
+        ),
+    ] {
+        assert_eq!(message.to_bstring(), expected);
+        assert_eq!(message.to_string(), expected);
+    }
+}
+
+#[test]
+fn the_store_committer_and_default_message_are_used_if_none_are_given() -> crate::Result {
+    let (_keep, mut store) = empty_store()?;
+    let err = store
+        .transaction()
+        .prepare(
+            Some(update_without_message("refs/heads/a")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(None)
+        .unwrap_err();
+    assert!(
+        matches!(err, file::transaction::commit::Error::CreateOrUpdateRefLog(_)),
+        "without any committer, reflogs can't be written"
+    );
+
+    store.committer = Some(file::Committer::fixed(committer()));
+    let edits = store
+        .transaction()
+        .default_log_message(LogMessage::UpdateByPush)
+        .prepare(
+            [update_without_message("refs/heads/b"), create_at("refs/heads/c")],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(None)?;
+    match &edits[0].change {
+        Change::Update { log, .. } => assert_eq!(log.message, "update by push", "returned edits have the message"),
+        _ => unreachable!("only updates"),
+    }
+
+    let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let null = gix_hash::Kind::Sha1.null();
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/b")?,
+        vec![log_line(null, id, "update by push")],
+        "the store's committer is used along with the default message"
+    );
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/c")?,
+        vec![log_line(null, id, "log peeled")],
+        "explicit messages are kept"
+    );
+    Ok(())
+}
+
+#[test]
+fn renames_and_copies_without_message_are_recorded_like_git() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(Some(create_at("refs/heads/a")), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+
+    let edit = |change_copy: bool, name: &str, new_name: &str| RefEdit {
+        change: if change_copy {
+            Change::Copy {
+                expected: PreviousValue::MustExist,
+                new_name: new_name.try_into().expect("valid"),
+                new_name_expected: PreviousValue::MustNotExist,
+                message: Default::default(),
+            }
+        } else {
+            Change::Rename {
+                expected: PreviousValue::MustExist,
+                new_name: new_name.try_into().expect("valid"),
+                new_name_expected: PreviousValue::MustNotExist,
+                message: Default::default(),
+            }
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    };
+    store
+        .transaction()
+        .prepare(
+            Some(edit(true, "refs/heads/a", "refs/heads/b")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    store
+        .transaction()
+        .prepare(
+            Some(edit(false, "refs/heads/b", "refs/heads/c")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/c")?,
+        vec![
+            log_line(gix_hash::Kind::Sha1.null(), id, "log peeled"),
+            log_line(id, id, "Branch: copied refs/heads/a to refs/heads/b"),
+            log_line(id, id, "Branch: renamed refs/heads/b to refs/heads/c"),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,333 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file::transaction::prepare,
+    transaction::{Change, PreviousValue, RefEdit},
+    Target,
+};
+
+use crate::{
+    file::{
+        store_writable,
+        transaction::prepare_and_commit::{committer, create_at, empty_store, log_line, reflog_lines},
+    },
+    hex_to_id,
+};
+
+fn rename(name: &str, new_name: &str, expected: PreviousValue, new_name_expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Rename {
+            expected,
+            new_name: new_name.try_into().expect("valid"),
+            new_name_expected,
+            message: format!("renamed {name} to {new_name}").into(),
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+#[test]
+fn moves_the_ref_and_its_reflog_and_records_the_rename() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            Some(create_at("refs/heads/a/old")),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let edits = store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/a/old",
+                "refs/heads/new",
+                PreviousValue::MustExistAndMatch(Target::Object(id)),
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    assert_eq!(edits.len(), 2, "renames are split into a deletion and an update");
+    assert!(matches!(
+        &edits[0].change,
+        Change::Delete { expected: PreviousValue::MustExistAndMatch(Target::Object(previous)), .. } if *previous == id
+    ));
+    assert_eq!(edits[0].name.as_bstr(), "refs/heads/a/old");
+    assert_eq!(edits[1].name.as_bstr(), "refs/heads/new");
+    assert_eq!(edits[1].change.new_value().expect("update").id(), id);
+
+    assert!(
+        store.try_find_loose("refs/heads/a/old")?.is_none(),
+        "the original is gone"
+    );
+    assert!(
+        !store.git_dir().join("refs/heads/a").exists(),
+        "empty directories are removed"
+    );
+    let new = store.find_loose("refs/heads/new")?;
+    assert_eq!(new.target, Target::Object(id));
+    assert!(
+        !store.git_dir().join("logs/refs/heads/a").exists(),
+        "the reflog was moved"
+    );
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/new")?,
+        vec![
+            log_line(gix_hash::Kind::Sha1.null(), id, "log peeled"),
+            log_line(id, id, "renamed refs/heads/a/old to refs/heads/new")
+        ],
+        "the original reflog is kept and receives the rename"
+    );
+    Ok(())
+}
+
+#[test]
+fn the_expected_value_is_verified() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(Some(create_at("refs/heads/old")), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+
+    let outdated = hex_to_id("28ce6a8b26aa170e1de65536fe8abe1832bd3242");
+    let err = store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/old",
+                "refs/heads/new",
+                PreviousValue::MustExistAndMatch(Target::Object(outdated)),
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(matches!(err, prepare::Error::ReferenceOutOfDate { .. }), "{err:?}");
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/missing",
+                "refs/heads/new",
+                PreviousValue::Any,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, prepare::Error::DeleteReferenceMustExist { .. }),
+        "renamed refs must exist: {err:?}"
+    );
+    assert!(store.try_find_loose("refs/heads/new")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn existing_refs_are_only_overwritten_if_permitted() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [create_at("refs/heads/old"), create_at("refs/heads/new")],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/old",
+                "refs/heads/new",
+                PreviousValue::Any,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(matches!(err, prepare::Error::MustNotExist { .. }), "{err:?}");
+    assert!(store.try_find_loose("refs/heads/old")?.is_some(), "nothing changed");
+
+    store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/old",
+                "refs/heads/new",
+                PreviousValue::Any,
+                PreviousValue::Any,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert!(store.try_find_loose("refs/heads/old")?.is_none());
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/new")?.len(),
+        2,
+        "the reflog of the overwritten ref is replaced"
+    );
+    Ok(())
+}
+
+#[test]
+fn directory_file_conflicts_are_detected() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [create_at("refs/heads/a"), create_at("refs/heads/b/c")],
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    for (new_name, conflicting) in [
+        ("refs/heads/b", "refs/heads/b/c"),
+        ("refs/heads/b/c/d", "refs/heads/b/c"),
+    ] {
+        let err = store
+            .transaction()
+            .prepare(
+                Some(rename("refs/heads/a", new_name, PreviousValue::Any, PreviousValue::Any)),
+                Fail::Immediately,
+                Fail::Immediately,
+            )
+            .unwrap_err();
+        match err {
+            prepare::Error::NameConflict {
+                full_name,
+                conflicting: actual,
+            } => {
+                assert_eq!(full_name, new_name);
+                assert_eq!(actual, conflicting);
+            }
+            err => unreachable!("unexpected error: {err:?}"),
+        }
+    }
+    assert!(store.try_find_loose("refs/heads/a")?.is_some(), "nothing changed");
+    Ok(())
+}
+
+#[test]
+fn refs_can_be_renamed_to_their_parent_directory_and_back() -> crate::Result {
+    let (_keep, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(Some(create_at("refs/heads/a/b")), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+
+    let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    for (renames, (name, new_name)) in [("refs/heads/a/b", "refs/heads/a"), ("refs/heads/a", "refs/heads/a/b")]
+        .into_iter()
+        .enumerate()
+    {
+        store
+            .transaction()
+            .prepare(
+                Some(rename(
+                    name,
+                    new_name,
+                    PreviousValue::MustExist,
+                    PreviousValue::MustNotExist,
+                )),
+                Fail::Immediately,
+                Fail::Immediately,
+            )?
+            .commit(committer().to_ref())?;
+
+        assert!(store.try_find_loose(name)?.is_none(), "{name} is gone");
+        assert_eq!(store.find_loose(new_name)?.target, Target::Object(id));
+        let lines = reflog_lines(&store, new_name)?;
+        assert_eq!(lines.len(), 2 + renames, "the reflog is moved and receives the rename");
+        assert_eq!(
+            lines[1 + renames],
+            log_line(id, id, format!("renamed {name} to {new_name}"))
+        );
+    }
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/a/b")?[0],
+        log_line(gix_hash::Kind::Sha1.null(), id, "log peeled"),
+        "the original reflog is kept"
+    );
+    assert!(
+        !store.git_dir().join("logs/refs/.tmp-renamed-log").exists(),
+        "the reflog was moved through a temporary file"
+    );
+    Ok(())
+}
+
+#[test]
+fn packed_refs_can_be_renamed_and_conflict() -> crate::Result {
+    let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
+    assert!(store.try_find_loose("main")?.is_none(), "main is packed");
+
+    let err = store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/main",
+                "refs/heads/d1/new",
+                PreviousValue::MustExist,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, prepare::Error::NameConflict { ref conflicting, .. } if conflicting == "refs/heads/d1"),
+        "packed refs are considered as well: {err:?}"
+    );
+
+    let id = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/main",
+                "refs/heads/renamed",
+                PreviousValue::MustExist,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    let packed = store.open_packed_buffer()?.expect("still there");
+    assert!(packed.try_find("main")?.is_none(), "it was removed from packed refs");
+    assert_eq!(store.find("renamed")?.target, Target::Object(id));
+
+    store
+        .transaction()
+        .prepare(
+            Some(rename(
+                "refs/heads/renamed",
+                "refs/heads/renamed/main",
+                PreviousValue::MustExist,
+                PreviousValue::MustNotExist,
+            )),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert!(store.try_find("refs/heads/renamed")?.is_none());
+    assert_eq!(
+        store.find("refs/heads/renamed/main")?.target,
+        Target::Object(id),
+        "packed refs may be renamed into their own directory as well"
+    );
+    Ok(())
+}
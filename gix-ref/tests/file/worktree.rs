@@ -78,7 +78,36 @@ impl From<Mode> for bool {
 }
 
 mod read_only {
-    use crate::file::worktree::{assert_reflog, into_peel, main_store, worktree_store, Mode};
+    use crate::file::worktree::{assert_reflog, dir, into_peel, main_store, worktree_store, Mode};
+
+    #[test]
+    fn common_dir_is_resolved_from_the_git_dir() -> crate::Result {
+        let (dir, _tmp) = dir(false, false)?;
+        let main_git_dir = dir.join("repo").join(".git");
+        let store = gix_ref::file::Store::at_resolving_common_dir(main_git_dir.clone(), Default::default())?;
+        assert_eq!(store.common_dir(), None, "the main worktree has no common dir");
+
+        let store = gix_ref::file::Store::at_resolving_common_dir(
+            main_git_dir.join("worktrees").join("w1"),
+            Default::default(),
+        )?;
+        assert_eq!(
+            store.common_dir().map(std::fs::canonicalize).transpose()?,
+            Some(main_git_dir.canonicalize()?),
+            "the relative path in the 'commondir' file is resolved"
+        );
+        assert_eq!(
+            store.find("refs/stacks/common")?.name.as_bstr(),
+            "refs/stacks/common",
+            "shared refs are found in the common dir"
+        );
+        assert_ne!(
+            store.find("refs/bisect/bad")?.target,
+            store.find("main-worktree/refs/bisect/bad")?.target,
+            "worktree-private refs are found in the git dir"
+        );
+        Ok(())
+    }
 
     #[test]
     fn linked() -> crate::Result {
@@ -186,6 +215,66 @@ mod read_only {
         }
         Ok(())
     }
+
+    #[test]
+    fn iter_prefixed_with_worktree_pseudo_paths() -> crate::Result {
+        for packed in [false, true] {
+            let (store, odb, _tmp) = worktree_store(packed, "w1", Mode::Read)?;
+            let peel = into_peel(&store, odb);
+            let names = |prefix: &str| -> crate::Result<Vec<String>> {
+                Ok(store
+                    .iter()?
+                    .prefixed(prefix.as_ref())?
+                    .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+                    .collect::<Result<_, _>>()?)
+            };
+
+            assert_eq!(names("refs/bisect/")?, ["refs/bisect/bad"]);
+            assert_eq!(names("main-worktree/refs/bisect/")?, ["main-worktree/refs/bisect/bad"]);
+            assert_eq!(
+                names("worktrees/w-detached/refs/bisect")?,
+                ["worktrees/w-detached/refs/bisect/bad"]
+            );
+            assert_eq!(
+                names("worktrees/w-detached/refs/stacks/")?,
+                [
+                    "worktrees/w-detached/refs/stacks/common",
+                    "worktrees/w-detached/refs/stacks/w1",
+                    "worktrees/w-detached/refs/stacks/wtdetached"
+                ],
+                "shared references are visible from all worktrees"
+            );
+            assert_eq!(names("main-worktree/refs/heads/m")?, ["main-worktree/refs/heads/main"]);
+            assert!(
+                names("main-worktree")?.len() > names("main-worktree/refs/bisect")?.len(),
+                "the pseudo-path by itself iterates all references"
+            );
+
+            for name in [
+                "main-worktree/refs/bisect/bad",
+                "worktrees/w-detached/refs/bisect/bad",
+                "worktrees/w1/refs/bisect/bad",
+            ] {
+                let prefix = name.rsplit_once('/').expect("has slash").0;
+                let iter = store.iter()?;
+                let mut iter = iter.prefixed(prefix.as_ref())?;
+                let iterated = iter.next().expect("one reference")?;
+                assert!(iter.next().is_none());
+                assert_eq!(iterated.name.as_bstr(), name, "names retain their prefix");
+                assert_eq!(
+                    peel(iterated),
+                    peel(store.find(name)?),
+                    "iterated references are the same as the ones found by name"
+                );
+            }
+            assert_ne!(
+                peel(store.find("main-worktree/refs/bisect/bad")?),
+                peel(store.find("worktrees/w1/refs/bisect/bad")?),
+                "each worktree has its own bisect state"
+            );
+        }
+        Ok(())
+    }
 }
 
 mod writable {
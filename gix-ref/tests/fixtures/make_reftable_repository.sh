@@ -0,0 +1,30 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q --ref-format=reftable
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+first=$(git rev-parse HEAD)
+git commit -q --allow-empty -m c2
+second=$(git rev-parse HEAD)
+
+git tag -a -m "annotated" annotated
+git tag lightweight "$first"
+git symbolic-ref refs/heads/symbolic refs/heads/main
+
+# All of these end up in a single table that needs many blocks, so git writes restart points,
+# a ref index and object blocks, along with many compressed log blocks.
+for n in $(seq 1000); do
+  if [ $((n % 2)) = 0 ]; then
+    echo "create refs/heads/branch-$n $first"
+  else
+    echo "create refs/heads/branch-$n $second"
+  fi
+done | git update-ref --stdin -m "create many branches"
+
+git update-ref -d refs/heads/branch-7
+git update-ref -m "reset main" refs/heads/main "$first"
+
+git for-each-ref --format='%(refname) %(objectname) %(symref)' >refs.baseline
+git reflog show --format='%H %gs' refs/heads/main >main-reflog.baseline
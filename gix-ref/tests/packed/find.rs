@@ -1,3 +1,4 @@
+use gix_object::bstr::ByteSlice;
 use gix_ref::packed;
 use gix_testtools::fixture_path_standalone;
 
@@ -39,6 +40,47 @@ fn binary_search_a_name_past_the_end_of_the_packed_refs_file() -> crate::Result
     Ok(())
 }
 
+#[test]
+fn binary_search_finds_every_record_and_nothing_in_between() -> crate::Result {
+    let mut packed_refs = b"# pack-refs with: peeled fully-peeled sorted \n".to_vec();
+    for idx in 0..100 {
+        packed_refs
+            .extend_from_slice(format!("916840c0e2f67d370291042cb5274a597f4fa9bc refs/tags/v{idx:03}\n").as_bytes());
+        if idx % 3 == 0 {
+            packed_refs.extend_from_slice(b"^13da90b54699a6b500ec5cd7d175f2cd5a1bed06\n");
+        }
+    }
+    for (packed_refs, mmap_threshold) in [
+        (packed_refs.clone(), 0),
+        (packed_refs.replace(b"\n", b"\r\n"), u64::MAX),
+    ] {
+        let (_keep, path) = write_packed_refs_with(&packed_refs)?;
+        let buf = packed::Buffer::open(path, mmap_threshold)?;
+        for idx in 0..100 {
+            let name = format!("refs/tags/v{idx:03}");
+            let reference = buf.try_find(name.as_str())?.expect("every record can be found");
+            assert_eq!(reference.name.as_bstr(), name);
+            assert_eq!(
+                reference.object.is_some(),
+                idx % 3 == 0,
+                "peeled objects are associated with the right record"
+            );
+            assert!(
+                buf.try_find(format!("{name}-between").as_str())?.is_none(),
+                "names sorting between records are not found"
+            );
+        }
+        assert!(buf.try_find("refs/tags/a")?.is_none(), "before the first record");
+        assert!(buf.try_find("refs/tags/z")?.is_none(), "past the last record");
+        assert_eq!(
+            buf.iter_prefixed("refs/tags/v05".into())?.count(),
+            10,
+            "prefixed iteration uses the same search"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn find_packed_refs_with_peeled_items_and_full_or_partial_names() -> crate::Result {
     let packed_refs = b"# pack-refs with: peeled fully-peeled sorted
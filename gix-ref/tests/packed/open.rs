@@ -37,6 +37,33 @@ fn unsorted_buffers_or_those_without_a_header_can_be_opened_and_searched() {
     }
 }
 
+#[test]
+fn peeling_traits_are_read_from_the_header() -> crate::Result {
+    use gix_ref::packed::Peeled;
+    let name: &gix_ref::FullNameRef = "refs/heads/main".try_into()?;
+    let tag_name: &gix_ref::FullNameRef = "refs/tags/v1".try_into()?;
+    for (header, expected, knows_peeled_value_of_branch, knows_peeled_value_of_tag) in [
+        ("", Peeled::Unspecified, false, false),
+        ("# pack-refs with: sorted \n", Peeled::Unspecified, false, false),
+        ("# pack-refs with: peeled sorted \n", Peeled::Partial, false, true),
+        ("# pack-refs with: peeled fully-peeled \n", Peeled::Fully, true, true),
+    ] {
+        let buffer = gix_ref::packed::Buffer::from_bytes(header.as_bytes())?;
+        assert_eq!(buffer.peeled(), expected, "{header:?}");
+        assert_eq!(
+            buffer.knows_peeled_value(name),
+            knows_peeled_value_of_branch,
+            "{header:?}"
+        );
+        assert_eq!(
+            buffer.knows_peeled_value(tag_name),
+            knows_peeled_value_of_tag,
+            "{header:?}"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn bogus_content_triggers_an_error() -> crate::Result {
     let packed_refs_data = b"starts with a bogus record, not a header anyway";
@@ -0,0 +1,264 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    memory,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    RefStore, Target,
+};
+
+use crate::file::transaction::prepare_and_commit::committer;
+
+fn id(byte: u8) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_bytes_or_panic(&[byte; 20])
+}
+
+fn update(name: &str, new: Target, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                message: format!("update {name}").into(),
+                ..Default::default()
+            },
+            expected,
+            new,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn delete(name: &str, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Delete {
+            expected,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn apply(store: &dyn RefStore, edits: Vec<RefEdit>) -> Result<Vec<RefEdit>, gix_ref::ref_store::Error> {
+    store.apply_edits(edits, Fail::Immediately, Some(committer().to_ref()))
+}
+
+fn names(store: &dyn RefStore, prefix: Option<&str>) -> crate::Result<Vec<String>> {
+    store
+        .iter_references(prefix.map(Into::into))?
+        .map(|reference| reference.map(|reference| reference.name.as_bstr().to_string()))
+        .collect()
+}
+
+/// Exercise `store`, which is expected to be empty, to verify all stores behave the same.
+fn assert_store_behaviour(store: &dyn RefStore) -> crate::Result {
+    assert_eq!(store.try_find_reference("main".try_into()?)?, None);
+    assert_eq!(names(store, None)?, Vec::<String>::new());
+
+    let edits = apply(
+        store,
+        vec![
+            update("refs/heads/main", Target::Object(id(1)), PreviousValue::MustNotExist),
+            update("refs/tags/v1", Target::Object(id(2)), PreviousValue::MustNotExist),
+            update(
+                "HEAD",
+                Target::Symbolic("refs/heads/main".try_into()?),
+                PreviousValue::MustNotExist,
+            ),
+        ],
+    )?;
+    assert_eq!(edits.len(), 3);
+    assert!(
+        edits.iter().all(|edit| matches!(
+            edit.change,
+            Change::Update {
+                expected: PreviousValue::MustNotExist,
+                ..
+            }
+        )),
+        "none of the references existed before"
+    );
+
+    let main = store.try_find_reference("main".try_into()?)?.expect("present");
+    assert_eq!(main.name.as_bstr(), "refs/heads/main", "git's lookup rules are used");
+    assert_eq!(main.target, Target::Object(id(1)));
+    assert_eq!(
+        store.try_find_reference("v1".try_into()?)?.expect("present").target,
+        Target::Object(id(2))
+    );
+    assert_eq!(
+        store.try_find_reference("HEAD".try_into()?)?.expect("present").target,
+        Target::Symbolic("refs/heads/main".try_into()?)
+    );
+
+    assert_eq!(names(store, None)?, ["refs/heads/main", "refs/tags/v1"]);
+    assert_eq!(names(store, Some("refs/heads/"))?, ["refs/heads/main"]);
+    assert_eq!(names(store, Some("refs/remotes/"))?, Vec::<String>::new());
+
+    let err = apply(
+        store,
+        vec![update(
+            "refs/heads/main",
+            Target::Object(id(3)),
+            PreviousValue::MustNotExist,
+        )],
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("refs/heads/main"),
+        "the reference exists with a different value: {err}"
+    );
+    let err = apply(
+        store,
+        vec![update(
+            "refs/heads/main",
+            Target::Object(id(3)),
+            PreviousValue::MustExistAndMatch(Target::Object(id(2))),
+        )],
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("refs/heads/main"), "out of date: {err}");
+    assert_eq!(
+        store.try_find_reference("main".try_into()?)?.expect("present").target,
+        Target::Object(id(1)),
+        "failed edits don't change anything"
+    );
+
+    let mut through_head = update("HEAD", Target::Object(id(3)), PreviousValue::Any);
+    through_head.deref = true;
+    let edits = apply(store, vec![through_head])?;
+    assert_eq!(
+        edits.len(),
+        2,
+        "the edit of HEAD and its split-off edit of the referent"
+    );
+    assert!(
+        edits.iter().any(|edit| edit.name.as_bstr() == "refs/heads/main"
+            && matches!(&edit.change, Change::Update { expected: PreviousValue::MustExistAndMatch(previous), .. } if *previous == Target::Object(id(1)))),
+        "the previous value is recorded: {edits:#?}"
+    );
+    assert_eq!(
+        store.try_find_reference("main".try_into()?)?.expect("present").target,
+        Target::Object(id(3)),
+        "HEAD was dereferenced"
+    );
+    assert_eq!(
+        store.try_find_reference("HEAD".try_into()?)?.expect("present").target,
+        Target::Symbolic("refs/heads/main".try_into()?),
+        "HEAD itself didn't change"
+    );
+
+    apply(
+        store,
+        vec![delete(
+            "refs/tags/v1",
+            PreviousValue::MustExistAndMatch(Target::Object(id(2))),
+        )],
+    )?;
+    assert_eq!(store.try_find_reference("v1".try_into()?)?, None);
+    assert!(
+        apply(store, vec![delete("refs/tags/v1", PreviousValue::MustExist)]).is_err(),
+        "deleted references don't exist anymore"
+    );
+    assert_eq!(names(store, None)?, ["refs/heads/main"]);
+    Ok(())
+}
+
+#[test]
+fn memory_store() -> crate::Result {
+    assert_store_behaviour(&memory::Store::default())
+}
+
+#[test]
+fn file_store() -> crate::Result {
+    let (_dir, store) = crate::file::transaction::prepare_and_commit::empty_store()?;
+    assert_store_behaviour(&store)
+}
+
+#[test]
+fn reftable_store() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    std::fs::create_dir(dir.path().join("reftable"))?;
+    assert_store_behaviour(&gix_ref::reftable::Store::at(dir.path().into(), Default::default()))
+}
+
+mod memory_store {
+    use gix_ref::{
+        memory,
+        transaction::{Change, PreviousValue, RefEdit},
+        Target,
+    };
+
+    use super::{id, update};
+
+    #[test]
+    fn initial_references_are_visible() -> crate::Result {
+        let store = memory::Store::new(
+            gix_hash::Kind::Sha1,
+            [
+                ("refs/remotes/origin/HEAD".try_into()?, Target::Object(id(1))),
+                ("refs/heads/a".try_into()?, Target::Object(id(2))),
+            ],
+        );
+        assert_eq!(
+            store.try_find("origin".try_into()?).expect("present").target,
+            Target::Object(id(1)),
+            "remote HEADs are found by the remote name"
+        );
+        assert_eq!(
+            store
+                .iter_prefixed("refs/".into())
+                .into_iter()
+                .map(|reference| reference.name.as_bstr().to_string())
+                .collect::<Vec<_>>(),
+            ["refs/heads/a", "refs/remotes/origin/HEAD"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn name_conflicts_are_rejected() -> crate::Result {
+        let store = memory::Store::new(
+            gix_hash::Kind::Sha1,
+            [("refs/heads/a".try_into()?, Target::Object(id(1)))],
+        );
+        for name in ["refs/heads/a/b", "refs/heads"] {
+            let err = store
+                .apply([update(name, Target::Object(id(2)), PreviousValue::Any)])
+                .unwrap_err();
+            assert!(
+                matches!(err, memory::apply::Error::NameConflict { .. }),
+                "{name} can't be stored in files next to refs/heads/a: {err}"
+            );
+        }
+        store.apply([
+            update("refs/heads/a", Target::Object(id(1)), PreviousValue::Any),
+            update("refs/heads/b", Target::Object(id(2)), PreviousValue::Any),
+        ])?;
+        assert_eq!(store.iter_prefixed("refs/heads/".into()).len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn renames_move_the_reference() -> crate::Result {
+        let store = memory::Store::new(
+            gix_hash::Kind::Sha1,
+            [("refs/heads/a".try_into()?, Target::Object(id(1)))],
+        );
+        let edits = store.apply([RefEdit {
+            change: Change::Rename {
+                new_name: "refs/heads/b".try_into()?,
+                new_name_expected: PreviousValue::MustNotExist,
+                message: "rename".into(),
+                expected: PreviousValue::MustExist,
+            },
+            name: "refs/heads/a".try_into()?,
+            deref: false,
+        }])?;
+        assert_eq!(edits.len(), 2, "the deletion and the creation");
+        assert_eq!(store.try_find("a".try_into()?), None);
+        assert_eq!(
+            store.try_find("b".try_into()?).expect("moved").target,
+            Target::Object(id(1))
+        );
+        Ok(())
+    }
+}
@@ -11,5 +11,7 @@ mod fullname;
 mod namespace;
 mod packed;
 mod reference;
+mod ref_store;
+mod reftable;
 mod store;
 mod transaction;
@@ -0,0 +1,590 @@
+use gix_lock::acquire::Fail;
+use gix_object::bstr::ByteSlice;
+use gix_ref::{
+    reftable,
+    reftable::table::{LogRecord, LogValue, RefRecord, RefValue, Table},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::file::transaction::prepare_and_commit::committer;
+
+fn empty_store() -> crate::Result<(gix_testtools::tempfile::TempDir, reftable::Store)> {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    std::fs::create_dir(dir.path().join("reftable"))?;
+    let store = reftable::Store::at(dir.path().into(), Default::default());
+    Ok((dir, store))
+}
+
+fn update(name: &str, new: Target, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                message: format!("update {name}").into(),
+                ..Default::default()
+            },
+            expected,
+            new,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn id(byte: u8) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_bytes_or_panic(&[byte; 20])
+}
+
+#[test]
+fn table_round_trips_records_across_multiple_blocks() -> crate::Result {
+    let refs: Vec<_> = (0..500u64)
+        .map(|n| RefRecord {
+            name: format!("refs/heads/branch-{n:04}").into(),
+            update_index: 5 + n % 3,
+            value: match n % 4 {
+                0 => RefValue::Object(id(n as u8)),
+                1 => RefValue::Peeled {
+                    target: id(n as u8),
+                    peeled: id(!n as u8),
+                },
+                2 => RefValue::Symbolic("refs/heads/main".into()),
+                _ => RefValue::Deletion,
+            },
+        })
+        .collect();
+    let logs: Vec<_> = (0..200u64)
+        .map(|n| LogRecord {
+            name: format!("refs/heads/branch-{:04}", n / 2).into(),
+            update_index: 7 - n % 2,
+            value: if n % 5 == 0 {
+                LogValue::Deletion
+            } else {
+                LogValue::Update(gix_ref::log::Line {
+                    previous_oid: id(0),
+                    new_oid: id(n as u8),
+                    signature: committer(),
+                    message: format!("message {n}").into(),
+                })
+            },
+        })
+        .collect();
+    let data = reftable::table::encode(gix_hash::Kind::Sha1, 5, 7, &refs, &logs);
+    assert!(data.len() > 2 * 4096, "more than one block is needed");
+
+    let table = Table::from_bytes(data)?;
+    assert_eq!(table.header.min_update_index, 5);
+    assert_eq!(table.header.max_update_index, 7);
+    assert_eq!(table.refs()?, refs);
+    assert_eq!(table.logs()?, logs);
+    Ok(())
+}
+
+#[test]
+fn empty_table_round_trips() -> crate::Result {
+    let table = Table::from_bytes(reftable::table::encode(gix_hash::Kind::Sha1, 1, 1, &[], &[]))?;
+    assert!(table.refs()?.is_empty());
+    assert!(table.logs()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn corrupt_tables_are_detected() -> crate::Result {
+    let refs = [RefRecord {
+        name: "refs/heads/main".into(),
+        update_index: 1,
+        value: RefValue::Object(id(1)),
+    }];
+    let mut data = reftable::table::encode(gix_hash::Kind::Sha1, 1, 1, &refs, &[]);
+    let last = data.len() - 1;
+    data[last] ^= 0xff;
+    assert!(
+        Table::from_bytes(data).is_err(),
+        "the footer checksum doesn't match anymore"
+    );
+    assert!(Table::from_bytes(b"not a table".to_vec()).is_err());
+    Ok(())
+}
+
+#[test]
+fn transactions_write_refs_and_reflogs_into_new_tables() -> crate::Result {
+    let (_dir, store) = empty_store()?;
+    assert!(store.try_find("HEAD")?.is_none(), "empty stacks have no references");
+
+    let edits = store
+        .transaction()
+        .prepare(
+            [
+                update(
+                    "HEAD",
+                    Target::Symbolic("refs/heads/main".try_into()?),
+                    PreviousValue::MustNotExist,
+                ),
+                update("refs/heads/main", Target::Object(id(1)), PreviousValue::MustNotExist),
+                update("refs/tags/v1", Target::Object(id(2)), PreviousValue::Any),
+            ],
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 3);
+
+    let head = store.find("HEAD")?;
+    assert_eq!(head.target, Target::Symbolic("refs/heads/main".try_into()?));
+    assert_eq!(
+        store.find("main")?.target,
+        Target::Object(id(1)),
+        "partial names are looked up"
+    );
+    assert_eq!(store.find("v1")?.name.as_bstr(), "refs/tags/v1");
+    assert_eq!(
+        store
+            .iter_prefixed("refs/heads/".into())?
+            .map(|r| r.name)
+            .collect::<Vec<_>>(),
+        vec![FullName::try_from("refs/heads/main")?]
+    );
+    assert_eq!(store.iter()?.count(), 3);
+
+    let edits = store
+        .transaction()
+        .prepare(
+            [RefEdit {
+                deref: true,
+                ..update(
+                    "HEAD",
+                    Target::Object(id(3)),
+                    PreviousValue::MustExistAndMatch(Target::Object(id(1))),
+                )
+            }],
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 2, "symbolic refs are split");
+    assert_eq!(store.find("main")?.target, Target::Object(id(3)));
+
+    let log = store.reflog("refs/heads/main")?;
+    assert_eq!(log.len(), 2, "branches have a reflog, oldest first");
+    assert_eq!(
+        (log[0].previous_oid, log[0].new_oid),
+        (gix_hash::Kind::Sha1.null(), id(1))
+    );
+    assert_eq!((log[1].previous_oid, log[1].new_oid), (id(1), id(3)));
+    assert_eq!(log[1].message, "update HEAD");
+    assert_eq!(log[1].signature, committer());
+    assert_eq!(store.reflog("HEAD")?.len(), 1, "HEAD receives the dereferenced update");
+    assert!(
+        !store.reflog_exists("refs/tags/v1")?,
+        "tags don't get reflogs by default"
+    );
+
+    let err = store
+        .transaction()
+        .prepare(
+            [update(
+                "refs/heads/main",
+                Target::Object(id(4)),
+                PreviousValue::MustExistAndMatch(Target::Object(id(1))),
+            )],
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, reftable::transaction::prepare::Error::ReferenceOutOfDate { .. }),
+        "expectations are checked against the stack: {err:?}"
+    );
+
+    store
+        .transaction()
+        .prepare(
+            [RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExist,
+                    log: RefLog::AndReference,
+                },
+                name: "refs/heads/main".try_into()?,
+                deref: false,
+            }],
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert!(
+        store.try_find("refs/heads/main")?.is_none(),
+        "deletions hide older records"
+    );
+    assert!(
+        !store.reflog_exists("refs/heads/main")?,
+        "the reflog is deleted as well"
+    );
+    assert_eq!(
+        store.try_find("HEAD")?.expect("still present").target,
+        Target::Symbolic("refs/heads/main".try_into()?),
+        "the now dangling symbolic ref remains"
+    );
+
+    let stack = store.stack()?;
+    assert_eq!(stack.tables().len(), 3, "each transaction adds one table");
+    assert_eq!(stack.max_update_index(), 3);
+    assert_eq!(
+        std::fs::read(store.tables_list_path())?.lines().count(),
+        3,
+        "tables are listed oldest first"
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_committer_aborts_without_changes() -> crate::Result {
+    let (_dir, store) = empty_store()?;
+    let err = store
+        .transaction()
+        .prepare(
+            [update("refs/heads/main", Target::Object(id(1)), PreviousValue::Any)],
+            Fail::Immediately,
+        )?
+        .commit(None)
+        .unwrap_err();
+    assert!(matches!(err, reftable::transaction::commit::Error::MissingCommitter));
+    assert!(store.try_find("main")?.is_none());
+    assert!(store.stack()?.tables().is_empty());
+    Ok(())
+}
+
+#[test]
+fn namespaces_are_applied_to_reads_and_writes() -> crate::Result {
+    let (_dir, mut store) = empty_store()?;
+    store.namespace = Some(gix_ref::namespace::expand("ns")?);
+    store
+        .transaction()
+        .prepare(
+            [update("refs/heads/main", Target::Object(id(1)), PreviousValue::Any)],
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+    assert_eq!(store.find("main")?.name.as_bstr(), "refs/heads/main");
+    assert!(store.contains("refs/heads/main".try_into()?)?);
+    assert!(
+        !store.reflog_exists("refs/heads/main")?,
+        "like git, the reflog rules apply to the namespaced name, which isn't a branch"
+    );
+
+    store.namespace = None;
+    assert!(store.try_find("refs/heads/main")?.is_none());
+    assert!(!store.contains("refs/heads/main".try_into()?)?);
+    assert_eq!(
+        store.iter()?.map(|r| r.name).collect::<Vec<_>>(),
+        vec![FullName::try_from("refs/namespaces/ns/refs/heads/main")?]
+    );
+    Ok(())
+}
+
+#[test]
+fn renames_move_the_reflog_and_detect_name_conflicts() -> crate::Result {
+    let (_dir, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [
+                update("refs/heads/old", Target::Object(id(1)), PreviousValue::MustNotExist),
+                update(
+                    "refs/heads/taken/sub",
+                    Target::Object(id(2)),
+                    PreviousValue::MustNotExist,
+                ),
+            ],
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let rename = |new_name: &str| RefEdit {
+        change: Change::Rename {
+            expected: PreviousValue::MustExistAndMatch(Target::Object(id(1))),
+            new_name: new_name.try_into().expect("valid"),
+            new_name_expected: PreviousValue::MustNotExist,
+            message: "renamed".into(),
+        },
+        name: "refs/heads/old".try_into().expect("valid"),
+        deref: false,
+    };
+    for new_name in ["refs/heads/taken", "refs/heads/old/sub"] {
+        let err = store
+            .transaction()
+            .prepare([rename(new_name)], Fail::Immediately)
+            .unwrap_err();
+        assert!(
+            matches!(err, reftable::transaction::prepare::Error::NameConflict { .. }),
+            "{new_name}: {err:?}"
+        );
+    }
+
+    let edits = store
+        .transaction()
+        .prepare([rename("refs/heads/new")], Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 2, "renames are split into a deletion and an update");
+    assert!(store.try_find("refs/heads/old")?.is_none());
+    assert!(!store.reflog_exists("refs/heads/old")?);
+    assert_eq!(store.find("refs/heads/new")?.target, Target::Object(id(1)));
+
+    let log = store.reflog("refs/heads/new")?;
+    assert_eq!(log.len(), 2, "the reflog was moved and records the rename");
+    assert_eq!(log[0].message, "update refs/heads/old");
+    assert_eq!((log[1].previous_oid, log[1].new_oid), (id(1), id(1)));
+    assert_eq!(log[1].message, "renamed");
+    Ok(())
+}
+
+#[test]
+fn copies_keep_the_original_and_copy_its_reflog() -> crate::Result {
+    let (_dir, store) = empty_store()?;
+    store
+        .transaction()
+        .prepare(
+            [update(
+                "refs/heads/original",
+                Target::Object(id(1)),
+                PreviousValue::MustNotExist,
+            )],
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref())?;
+
+    let copy = |expected: PreviousValue| RefEdit {
+        change: Change::Copy {
+            expected,
+            new_name: "refs/heads/copy".try_into().expect("valid"),
+            new_name_expected: PreviousValue::MustNotExist,
+            message: "copied".into(),
+        },
+        name: "refs/heads/original".try_into().expect("valid"),
+        deref: false,
+    };
+    let err = store
+        .transaction()
+        .prepare(
+            [copy(PreviousValue::MustExistAndMatch(Target::Object(id(2))))],
+            Fail::Immediately,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, reftable::transaction::prepare::Error::ReferenceOutOfDate { .. }),
+        "{err:?}"
+    );
+
+    let edits = store
+        .transaction()
+        .prepare([copy(PreviousValue::MustExist)], Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert_eq!(edits.len(), 2, "copies are split into two updates");
+    assert_eq!(store.find("refs/heads/original")?.target, Target::Object(id(1)));
+    assert_eq!(store.find("refs/heads/copy")?.target, Target::Object(id(1)));
+    assert_eq!(
+        store.reflog("refs/heads/original")?.len(),
+        1,
+        "the original reflog is unchanged"
+    );
+
+    let log = store.reflog("refs/heads/copy")?;
+    assert_eq!(log.len(), 2, "the reflog was copied and records the copy");
+    assert_eq!(log[0].message, "update refs/heads/original");
+    assert_eq!((log[1].previous_oid, log[1].new_oid), (id(1), id(1)));
+    assert_eq!(log[1].message, "copied");
+    Ok(())
+}
+
+mod git_interop {
+    use gix_hash::ObjectId;
+    use gix_lock::acquire::Fail;
+    use gix_object::bstr::{BString, ByteSlice};
+    use gix_ref::{
+        reftable,
+        reftable::table::Table,
+        transaction::{Change, PreviousValue, RefEdit, RefLog},
+        Target,
+    };
+
+    use super::update;
+    use crate::file::transaction::prepare_and_commit::committer;
+
+    fn store_at(git_dir: std::path::PathBuf) -> reftable::Store {
+        reftable::Store::at(
+            git_dir,
+            gix_ref::store::init::Options {
+                write_reflog: gix_ref::store::WriteReflog::Normal,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> crate::Result<BString> {
+        let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(out.status.success(), "git {args:?} failed: {}", out.stderr.as_bstr());
+        Ok(out.stdout.into())
+    }
+
+    #[test]
+    fn tables_written_by_git_can_be_read() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 45, 0) {
+            return Ok(());
+        }
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_reftable_repository.sh")?;
+        let store = store_at(dir.join(".git"));
+
+        let mut saw_indexed_table = false;
+        for name in store.stack()?.tables() {
+            let table = Table::from_bytes(std::fs::read(store.reftable_dir().join(name.to_str()?))?)?;
+            let (refs, logs) = (table.refs()?, table.logs()?);
+            assert!(
+                refs.windows(2).all(|w| w[0].name < w[1].name),
+                "prefix-compressed records are restored in order"
+            );
+            if refs.len() >= 1000 {
+                assert_ne!(table.footer.ref_index_position, 0, "many ref blocks are indexed");
+                assert_ne!(
+                    table.footer.obj_position, 0,
+                    "object blocks map object ids to the refs pointing to them"
+                );
+                assert!(
+                    logs.len() >= 1000,
+                    "each branch has its reflog in the zlib-compressed log blocks"
+                );
+                saw_indexed_table = true;
+            }
+        }
+        assert!(saw_indexed_table, "the fixture produces a table with all branches");
+
+        let mut num_refs = 0;
+        for line in std::fs::read(dir.join("refs.baseline"))?.lines() {
+            let mut tokens = line.splitn_str(3, " ");
+            let (name, id, symref) = (
+                tokens.next().expect("name").as_bstr(),
+                tokens.next().expect("id"),
+                tokens.next().unwrap_or_default(),
+            );
+            let expected = if symref.is_empty() {
+                Target::Object(ObjectId::from_hex(id)?)
+            } else {
+                Target::Symbolic(symref.as_bstr().try_into()?)
+            };
+            assert_eq!(store.find(name)?.target, expected, "{name}");
+            num_refs += 1;
+        }
+        assert_eq!(
+            store.iter_prefixed("refs/".into())?.count(),
+            num_refs,
+            "deleted refs aren't listed"
+        );
+        assert!(store.try_find("refs/heads/branch-7")?.is_none());
+        assert_eq!(
+            store.find("HEAD")?.target,
+            Target::Symbolic("refs/heads/main".try_into()?)
+        );
+
+        let expected: Vec<_> = std::fs::read(dir.join("main-reflog.baseline"))?
+            .lines()
+            .map(|line| {
+                let (id, message) = line.split_once_str(" ").expect("id and message");
+                (ObjectId::from_hex(id).expect("valid hex"), message.as_bstr().to_owned())
+            })
+            .collect();
+        let actual: Vec<_> = store
+            .reflog("refs/heads/main")?
+            .into_iter()
+            .rev()
+            .map(|line| (line.new_oid, line.message))
+            .collect();
+        assert_eq!(actual, expected, "git shows the most recent entry first");
+        Ok(())
+    }
+
+    #[test]
+    fn tables_written_by_gix_can_be_read_by_git() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 45, 0) {
+            return Ok(());
+        }
+        let dir = gix_testtools::scripted_fixture_writable_standalone("make_reftable_repository.sh")?;
+        let store = store_at(dir.path().join(".git"));
+        let first = store.find("main")?.target.into_id();
+        let second = store.find("branch-1")?.target.into_id();
+
+        let mut edits: Vec<_> = (0..500)
+            .map(|n| {
+                update(
+                    &format!("refs/heads/gix/{n:03}"),
+                    Target::Object(first),
+                    PreviousValue::MustNotExist,
+                )
+            })
+            .collect();
+        edits.extend([
+            update(
+                "refs/heads/gix-symbolic",
+                Target::Symbolic("refs/heads/gix/250".try_into()?),
+                PreviousValue::MustNotExist,
+            ),
+            update(
+                "refs/heads/main",
+                Target::Object(second),
+                PreviousValue::MustExistAndMatch(Target::Object(first)),
+            ),
+            RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExist,
+                    log: RefLog::AndReference,
+                },
+                name: "refs/heads/branch-2".try_into()?,
+                deref: false,
+            },
+        ]);
+        store
+            .transaction()
+            .prepare(edits, Fail::Immediately)?
+            .commit(committer().to_ref())?;
+
+        let dir = dir.path();
+        assert_eq!(
+            git(dir, &["for-each-ref", "--format=%(refname)", "refs/heads/gix/"])?
+                .lines()
+                .count(),
+            500
+        );
+        assert_eq!(
+            git(
+                dir,
+                &[
+                    "rev-parse",
+                    "refs/heads/gix/250",
+                    "refs/heads/gix-symbolic",
+                    "refs/heads/main"
+                ]
+            )?,
+            format!("{first}\n{first}\n{second}\n")
+        );
+        assert_eq!(
+            git(dir, &["symbolic-ref", "refs/heads/gix-symbolic"])?,
+            "refs/heads/gix/250\n"
+        );
+        assert_eq!(
+            git(dir, &["reflog", "show", "-n1", "--format=%gs", "refs/heads/main"])?,
+            "update refs/heads/main\n"
+        );
+        assert!(
+            !std::process::Command::new("git")
+                .args(["show-ref", "--verify", "--quiet", "refs/heads/branch-2"])
+                .current_dir(dir)
+                .status()?
+                .success(),
+            "the deletion is visible to git"
+        );
+
+        git(
+            dir,
+            &["update-ref", "-m", "written by git", "refs/heads/after-gix", "HEAD"],
+        )?;
+        assert_eq!(
+            store.find("after-gix")?.target,
+            Target::Object(second),
+            "tables written by git on top of ours can be read as well"
+        );
+        assert_eq!(store.find("gix/250")?.target, Target::Object(first));
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+mod stdin;
+
 mod refedit_ext {
     use std::{cell::RefCell, collections::BTreeMap};
 
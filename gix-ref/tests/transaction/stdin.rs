@@ -0,0 +1,251 @@
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{
+        stdin::{parse, parse::Error, Format, Options},
+        Change, LogChange, PreviousValue, RefEdit, RefLog,
+    },
+    Target,
+};
+
+const A: &str = "1111111111111111111111111111111111111111";
+const B: &str = "2222222222222222222222222222222222222222";
+const NULL: &str = "0000000000000000000000000000000000000000";
+
+fn id(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.as_bytes()).expect("valid")
+}
+
+fn edit(name: &str, change: Change) -> RefEdit {
+    RefEdit {
+        change,
+        name: name.try_into().expect("valid"),
+        deref: true,
+    }
+}
+
+fn update(expected: PreviousValue, new: &str) -> Change {
+    Change::Update {
+        log: LogChange::default(),
+        expected,
+        new: Target::Object(id(new)),
+    }
+}
+
+fn delete(expected: PreviousValue) -> Change {
+    Change::Delete {
+        expected,
+        log: RefLog::AndReference,
+    }
+}
+
+fn must_match(hex: &str) -> PreviousValue {
+    PreviousValue::MustExistAndMatch(Target::Object(id(hex)))
+}
+
+fn lf(input: &str) -> Result<Vec<RefEdit>, Error> {
+    parse(input.as_bytes(), &Options::default())
+}
+
+fn nul(input: &str) -> Result<Vec<RefEdit>, Error> {
+    parse(
+        input.as_bytes(),
+        &Options {
+            format: Format::Nul,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn all_commands_in_both_formats() -> crate::Result {
+    let expected = vec![
+        edit("refs/heads/update", update(PreviousValue::Any, A)),
+        edit("refs/heads/update-checked", update(must_match(B), A)),
+        edit("refs/heads/update-new", update(PreviousValue::MustNotExist, A)),
+        edit("refs/heads/update-delete", delete(must_match(B))),
+        edit("refs/heads/create", update(PreviousValue::MustNotExist, B)),
+        edit("refs/heads/delete", delete(PreviousValue::Any)),
+        edit("refs/heads/delete-checked", delete(must_match(A))),
+        edit("refs/heads/verify", update(must_match(A), A)),
+        RefEdit {
+            deref: false,
+            ..edit("HEAD", update(PreviousValue::Any, B))
+        },
+        edit("refs/heads/deref-again", update(PreviousValue::Any, B)),
+    ];
+    assert_eq!(
+        lf(&format!(
+            "update refs/heads/update {A}\n\
+             update refs/heads/update-checked {A} {B}\n\
+             update refs/heads/update-new {A} {NULL}\n\
+             update refs/heads/update-delete  {B}\n\
+             create refs/heads/create {B}\n\
+             delete refs/heads/delete\n\
+             delete refs/heads/delete-checked {A}\n\
+             verify refs/heads/verify {A}\n\
+             option no-deref\n\
+             update HEAD {B}\n\
+             update refs/heads/deref-again {B}"
+        ))?,
+        expected,
+        "the last newline is optional"
+    );
+    assert_eq!(
+        nul(&format!(
+            "update refs/heads/update\0{A}\0\0\
+             update refs/heads/update-checked\0{A}\0{B}\0\
+             update refs/heads/update-new\0{A}\0{NULL}\0\
+             update refs/heads/update-delete\0\0{B}\0\
+             create refs/heads/create\0{B}\0\
+             delete refs/heads/delete\0\0\
+             delete refs/heads/delete-checked\0{A}\0\
+             verify refs/heads/verify\0{A}\0\
+             option no-deref\0\
+             update HEAD\0{B}\0\0\
+             update refs/heads/deref-again\0{B}\0\0"
+        ))?,
+        expected
+    );
+    Ok(())
+}
+
+#[test]
+fn options_apply_to_all_commands() -> crate::Result {
+    let edits = parse(
+        format!("update refs/heads/main {A}\noption no-deref\ndelete HEAD\n").as_bytes(),
+        &Options {
+            no_deref: true,
+            create_reflog: true,
+            message: "reason".into(),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        edits,
+        vec![
+            RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: true,
+                        message: "reason".into(),
+                    },
+                    expected: PreviousValue::Any,
+                    new: Target::Object(id(A)),
+                },
+                name: "refs/heads/main".try_into()?,
+                deref: false,
+            },
+            RefEdit {
+                change: delete(PreviousValue::Any),
+                name: "HEAD".try_into()?,
+                deref: false,
+            }
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn quoted_fields_only_in_newline_format() -> crate::Result {
+    assert_eq!(
+        lf(&format!("update \"refs/heads/\\x\" {A} \"{B}\"\n"))
+            .unwrap_err()
+            .to_string(),
+        "Command 1 has a field that could not be unquoted"
+    );
+    assert_eq!(
+        lf(&format!("update \"refs/heads/quo\\164ed\" \"{A}\" \"\"\n"))?,
+        vec![edit("refs/heads/quoted", update(PreviousValue::MustNotExist, A))],
+        "an empty quoted value is the null object id"
+    );
+    assert_eq!(
+        nul(&format!("update \"refs/heads/main\"\0{A}\0\0"))?[0].name.as_bstr(),
+        "\"refs/heads/main\"",
+        "quotes are taken verbatim"
+    );
+    Ok(())
+}
+
+#[test]
+fn errors() {
+    for (input, expected) in [
+        (
+            format!("update refs/heads/main {A}\nupdate refs/heads/main {B}\n"),
+            "A reference named \"refs/heads/main\" has multiple commands",
+        ),
+        ("frobnicate\n".into(), "Command 1 is unknown: \"frobnicate\""),
+        ("\n".into(), "Command 1 is unknown: \"\""),
+        (
+            "start\n".into(),
+            "Command 1 controls the transaction with \"start\", which is unsupported",
+        ),
+        ("option deref\n".into(), "Command 1 uses the unknown option \"deref\""),
+        ("update\n".into(), "Command 1 is malformed: missing reference name"),
+        (
+            "update refs/heads/main\n".into(),
+            "Command 1 is malformed: missing new object id",
+        ),
+        (
+            format!("update refs/heads/main {A} {B} {B}\n"),
+            "Command 1 is malformed: unexpected input after the last field",
+        ),
+        (
+            format!("update refs/heads/main {A}  {B}\n"),
+            "Command 1 is malformed: unexpected input after the last field",
+        ),
+        (
+            "update refs/heads/main abc\n".into(),
+            "Command 1 has value \"abc\" which isn't a full hexadecimal SHA1 object id",
+        ),
+        (
+            "update refs/heads/main HEAD~1\n".into(),
+            "Command 1 has value \"HEAD~1\" which isn't a full hexadecimal SHA1 object id",
+        ),
+        (
+            format!("create refs/heads/main {NULL}\n"),
+            "Command 1 is malformed: the new object id must not be null",
+        ),
+        (
+            format!("delete refs/heads/main {NULL}\n"),
+            "Command 1 is malformed: the old object id must not be null",
+        ),
+        (
+            "verify refs/heads/main\n".into(),
+            "Command 1 verifies that \"refs/heads/main\" doesn't exist, which is unsupported",
+        ),
+        (
+            format!("update refs/heads/main {NULL} {NULL}\n"),
+            "Command 1 verifies that \"refs/heads/main\" doesn't exist, which is unsupported",
+        ),
+        (
+            format!("option no-deref\nupdate refs/heads/a..b {A}\n"),
+            "Command 2 has an invalid reference name",
+        ),
+    ] {
+        assert_eq!(lf(&input).unwrap_err().to_string(), expected, "{input:?}");
+    }
+
+    for (input, expected) in [
+        (
+            format!("update refs/heads/main\0{A}\0"),
+            "Command 1 is malformed: unexpected end of input",
+        ),
+        ("delete refs/heads/main".into(), "Command 1 isn't terminated"),
+        (
+            "create refs/heads/main\0\0".into(),
+            "Command 1 is malformed: missing new object id",
+        ),
+    ] {
+        assert_eq!(nul(&input).unwrap_err().to_string(), expected, "{input:?}");
+    }
+}
+
+#[test]
+fn empty_new_value_in_nul_format_deletes() -> crate::Result {
+    assert_eq!(
+        nul("update refs/heads/main\0\0\0")?,
+        vec![edit("refs/heads/main", delete(PreviousValue::Any))]
+    );
+    Ok(())
+}
@@ -50,7 +50,7 @@ pub enum Fetch<'a> {
         /// Note that this may not be a glob pattern, as those need to be matched by a destination which isn't present here.
         src: &'a BStr,
     },
-    /// Exclude a single ref.
+    /// Exclude a single ref, or all refs matching a pattern.
     Exclude {
         /// A single partial or full ref name to exclude on the remote, or a pattern with a single `*`. It cannot be a spelled out object hash.
         src: &'a BStr,
@@ -14,8 +14,6 @@ pub enum Error {
     NegativeObjectHash,
     #[error("Negative specs must be full ref names, starting with \"refs/\"")]
     NegativePartialName,
-    #[error("Negative glob patterns are not allowed")]
-    NegativeGlobPattern,
     #[error("Fetch destinations must be ref-names, like 'HEAD:refs/heads/branch'")]
     InvalidFetchDestination,
     #[error("Cannot push into an empty destination")]
@@ -130,9 +128,7 @@ pub(crate) mod function {
         if mode == Mode::Negative {
             match src {
                 Some(spec) => {
-                    if src_had_pattern {
-                        return Err(Error::NegativeGlobPattern);
-                    } else if looks_like_object_hash(spec) {
+                    if looks_like_object_hash(spec) {
                         return Err(Error::NegativeObjectHash);
                     } else if !spec.starts_with(b"refs/") && spec != "HEAD" {
                         return Err(Error::NegativePartialName);
@@ -159,7 +159,8 @@ impl<'a> RefSpecRef<'a> {
                 };
                 if let Some(rest) = source.strip_prefix(b"refs/") {
                     if !rest.contains(&b'/') {
-                        out.push(source.into());
+                        let end = source.find_byte(b'*').unwrap_or(source.len());
+                        out.push(source[..end].into());
                     }
                     return;
                 } else if gix_hash::ObjectId::from_hex(source).is_ok() {
@@ -70,6 +70,7 @@ git clone --shared ./base clone
   baseline "^main" "refs/heads/*:refs/remotes/origin/*"
   baseline "^refs/heads/main" "refs/heads/*:refs/remotes/origin/*"
   baseline "refs/heads/*:refs/remotes/origin/*" "^refs/heads/main"
+  baseline "refs/heads/*:refs/remotes/origin/*" "^refs/heads/f*"
   baseline "refs/heads/*:refs/remotes/origin/*" "refs/heads/main:refs/remotes/new-origin/main"
   baseline "refs/heads/*:refs/remotes/origin/*" "refs/heads/main:refs/remotes/origin/main"
   baseline "refs/heads/f1:refs/remotes/origin/conflict" "refs/heads/f2:refs/remotes/origin/conflict"
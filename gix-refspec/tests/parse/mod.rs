@@ -39,10 +39,7 @@ fn baseline() {
                 _ => {
                     match (res.as_ref().err(), err_code == 0) {
                         (
-                            Some(
-                                gix_refspec::parse::Error::NegativePartialName
-                                | gix_refspec::parse::Error::NegativeGlobPattern,
-                            ),
+                            Some(gix_refspec::parse::Error::NegativePartialName),
                             true,
                         ) => {} // we prefer failing fast, git let's it pass
                         _ => {
@@ -93,6 +93,12 @@ mod expand_prefixes {
         assert_eq!(parse("refs/short"), ["refs/short"]);
     }
 
+    #[test]
+    fn short_globs_expand_to_everything_before_the_glob() {
+        assert_eq!(parse("+refs/*:refs/*"), ["refs/"]);
+        assert_eq!(parse("refs/ma*:refs/remotes/origin/ma*"), ["refs/ma"]);
+    }
+
     #[test]
     fn full_names_expand_to_their_prefix() {
         assert_eq!(parse("refs/heads/main"), ["refs/heads/"]);
@@ -20,6 +20,10 @@ pub mod merge_base;
 #[cfg(feature = "merge_base")]
 pub use merge_base::function::merge_base;
 
+/// Answer questions about whether commits can be reached from other commits.
+#[cfg(feature = "reachability")]
+pub mod reachability;
+
 ///
 pub mod spec;
 pub use gix_revwalk::{graph, Graph, PriorityQueue};
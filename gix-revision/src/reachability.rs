@@ -0,0 +1,144 @@
+use gix_hash::ObjectId;
+use gix_hashtable::ObjectIdSet;
+use gix_revwalk::graph;
+
+use crate::Graph;
+
+bitflags::bitflags! {
+    /// The flags used in the graph of a reachability [`Oracle`].
+    #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+    pub struct Flags: u8 {
+        /// The commit was already seen during the current query.
+        const SEEN = 1 << 0;
+    }
+}
+
+/// The error returned by [`Oracle`] queries.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A commit could not be inserted into the graph")]
+    InsertCommit(#[from] gix_revwalk::graph::get_or_insert_default::Error),
+}
+
+/// The means by which an [`Oracle`] answers its queries, from best to worst.
+///
+/// Note that reachability bitmaps aren't supported yet, making the commit-graph the best available backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Backend {
+    /// Generation numbers of a commit-graph are used to stop walks as soon as the commit in question can't be reached anymore.
+    CommitGraph,
+    /// Commits are traversed by loading them one by one, without the means to stop walks early.
+    Walk,
+}
+
+/// A utility to answer questions about which commits can be reached from other commits, using the best available [`Backend`].
+///
+/// # Performance
+///
+/// For repeated queries, be sure to re-use the same instance as commits are kept in memory once they are loaded.
+pub struct Oracle<'find, 'cache> {
+    graph: Graph<'find, 'cache, graph::Commit<Flags>>,
+    backend: Backend,
+}
+
+/// Lifecycle
+impl<'find, 'cache> Oracle<'find, 'cache> {
+    /// Create a new instance which finds commits in `objects`, and which uses generation numbers of `cache` to avoid
+    /// unnecessary work if it is available.
+    pub fn new(objects: impl gix_object::Find + 'find, cache: Option<&'cache gix_commitgraph::Graph>) -> Self {
+        Oracle {
+            backend: if cache.is_some() {
+                Backend::CommitGraph
+            } else {
+                Backend::Walk
+            },
+            graph: Graph::new(objects, cache),
+        }
+    }
+}
+
+/// Access
+impl Oracle<'_, '_> {
+    /// Return the backend used to answer queries.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Return `true` if `ancestor` can be reached from `descendant` by following its parents, which is also the case if both
+    /// are the same.
+    /// This is the case if a reference pointing to `ancestor` can be fast-forwarded to `descendant`.
+    ///
+    /// `false` is returned if any of the two isn't a commit, or can't be found.
+    pub fn is_ancestor(&mut self, ancestor: ObjectId, descendant: ObjectId) -> Result<bool, Error> {
+        let _span = gix_trace::detail!("gix_revision::reachability::is_ancestor()", ?ancestor, ?descendant);
+        if ancestor == descendant {
+            return Ok(true);
+        }
+        self.graph.clear_commit_data(|f| *f = Flags::empty());
+        let target_generation = match self.graph.get_or_insert_commit(ancestor, |_| {})? {
+            Some(commit) => commit.generation,
+            None => return Ok(false),
+        };
+        let mut stack = Vec::new();
+        if let Some(commit) = self.graph.get_or_insert_commit(descendant, |f| *f |= Flags::SEEN)? {
+            if may_reach(commit.generation, target_generation) {
+                stack.push(descendant);
+            }
+        }
+        while let Some(id) = stack.pop() {
+            let parents = self
+                .graph
+                .get(&id)
+                .expect("queued commits are in graph")
+                .parents
+                .clone();
+            for parent_id in parents {
+                if parent_id == ancestor {
+                    return Ok(true);
+                }
+                let mut is_new = false;
+                let parent = self.graph.get_or_insert_commit(parent_id, |f| {
+                    is_new = !f.contains(Flags::SEEN);
+                    *f |= Flags::SEEN;
+                })?;
+                if let Some(parent) = parent.filter(|_| is_new) {
+                    if may_reach(parent.generation, target_generation) {
+                        stack.push(parent_id);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Return all commits that can be reached from `tips` by following their parents, including the `tips` themselves.
+    ///
+    /// Tips that aren't commits or that can't be found are ignored, as are missing parents like in shallow repositories.
+    pub fn reachable_from(&mut self, tips: impl IntoIterator<Item = ObjectId>) -> Result<ObjectIdSet, Error> {
+        let _span = gix_trace::detail!("gix_revision::reachability::reachable_from()");
+        let mut out = ObjectIdSet::default();
+        let mut stack: Vec<_> = tips.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if out.contains(&id) {
+                continue;
+            }
+            if let Some(commit) = self.graph.get_or_insert_commit(id, |_| {})? {
+                stack.extend(commit.parents.iter().filter(|parent| !out.contains(parent)).copied());
+                out.insert(id);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Return `true` if a commit with `generation` may be able to reach a commit with `target_generation`.
+///
+/// A commit-graph contains all ancestors of the commits it contains, so commits within it can't reach commits outside of it.
+fn may_reach(generation: Option<u32>, target_generation: Option<u32>) -> bool {
+    match (generation, target_generation) {
+        (Some(generation), Some(target_generation)) => generation > target_generation,
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
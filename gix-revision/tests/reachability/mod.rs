@@ -0,0 +1,70 @@
+use gix_hash::ObjectId;
+use gix_revision::reachability::{Backend, Oracle};
+
+fn fixture() -> crate::Result<(gix_odb::Handle, gix_commitgraph::Graph, std::path::PathBuf)> {
+    let root = gix_testtools::scripted_fixture_read_only("make_merge_base_repos.sh")?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let cache = gix_commitgraph::Graph::from_info_dir(&odb.store_ref().path().join("info"))?;
+    Ok((odb, cache, root.join(".git/refs/tags")))
+}
+
+fn tag(tags_dir: &std::path::Path, name: &str) -> ObjectId {
+    let hex = std::fs::read_to_string(tags_dir.join(name)).expect("loose tag exists");
+    ObjectId::from_hex(hex.trim().as_bytes()).expect("valid hex")
+}
+
+#[test]
+fn is_ancestor_with_and_without_commitgraph() -> crate::Result {
+    let (odb, cache, tags) = fixture()?;
+    for use_commitgraph in [false, true] {
+        let mut oracle = Oracle::new(&odb, use_commitgraph.then_some(&cache));
+        assert_eq!(
+            oracle.backend(),
+            if use_commitgraph {
+                Backend::CommitGraph
+            } else {
+                Backend::Walk
+            }
+        );
+        for (ancestor, descendant, expected) in [
+            ("E", "E", true),
+            ("E", "A", true),
+            ("B", "G", true),
+            ("F", "H", true),
+            ("E", "H", true),
+            ("G", "H", false),
+            ("H", "E", false),
+            ("F", "A", false),
+            ("S", "PL", true),
+            ("C0", "PR", true),
+            ("R2", "PL", false),
+            ("L0", "PR", false),
+            ("DA", "DB", false),
+        ] {
+            assert_eq!(
+                oracle.is_ancestor(tag(&tags, ancestor), tag(&tags, descendant))?,
+                expected,
+                "{ancestor} -> {descendant} (commitgraph: {use_commitgraph})"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn reachable_from_includes_tips() -> crate::Result {
+    let (odb, cache, tags) = fixture()?;
+    for use_commitgraph in [false, true] {
+        let mut oracle = Oracle::new(&odb, use_commitgraph.then_some(&cache));
+        let actual = oracle.reachable_from([tag(&tags, "G"), tag(&tags, "C0")])?;
+        let mut expected: Vec<_> = ["G", "B", "C", "D", "E", "C0", "S"]
+            .into_iter()
+            .map(|name| tag(&tags, name))
+            .collect();
+        expected.sort();
+        let mut actual: Vec<_> = actual.into_iter().collect();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+    Ok(())
+}
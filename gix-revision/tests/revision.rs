@@ -2,6 +2,8 @@
 mod describe;
 #[cfg(feature = "merge_base")]
 mod merge_base;
+#[cfg(feature = "reachability")]
+mod reachability;
 mod spec;
 
 pub use gix_testtools::Result;
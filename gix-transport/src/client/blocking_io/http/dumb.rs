@@ -0,0 +1,190 @@
+//! The 'dumb' protocol reads files directly from the repository on the server, and is only used if the server doesn't
+//! indicate support for the 'smart' protocol, which is when [`Error::SmartProtocolUnavailable`](super::Error::SmartProtocolUnavailable)
+//! is returned by the handshake.
+//!
+//! It's up to the caller to figure out which objects to download, by traversing the object graph starting at
+//! the [advertised references](super::Transport::dumb_refs()) and fetching each object either as
+//! [loose object](super::Transport::dumb_loose_object()) or as part of one of the [available packs](super::Transport::dumb_packs()).
+use std::{borrow::Cow, io::Read};
+
+use bstr::{BString, ByteSlice};
+
+use super::{append_url, GetResponse, Http, Transport};
+use crate::client;
+
+/// A reference as listed in the `info/refs` file of the remote repository.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ref {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: BString,
+    /// The object the reference points to.
+    pub target: gix_hash::ObjectId,
+    /// The object the annotated tag the reference points to peels to, if it's a tag.
+    pub peeled: Option<gix_hash::ObjectId>,
+}
+
+/// The `HEAD` reference of the remote repository.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Head {
+    /// `HEAD` points to the reference with the given full name, like `refs/heads/main`.
+    Symbolic(BString),
+    /// `HEAD` is detached and points to the given object directly.
+    Detached(gix_hash::ObjectId),
+}
+
+///
+pub mod parse {
+    use bstr::BString;
+
+    /// The error returned when parsing the files used by the 'dumb' protocol.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not parse line {line:?} of 'info/refs'")]
+        InfoRefsLine { line: BString },
+        #[error("Could not parse line {line:?} of 'objects/info/packs'")]
+        InfoPacksLine { line: BString },
+        #[error("Could not parse the content of 'HEAD': {content:?}")]
+        Head { content: BString },
+        #[error(transparent)]
+        DecodeHex(#[from] gix_hash::decode::Error),
+    }
+}
+
+/// Parse the content of an `info/refs` file, as written by `git update-server-info`.
+pub fn parse_info_refs(input: &[u8]) -> Result<Vec<Ref>, parse::Error> {
+    let mut out = Vec::<Ref>::new();
+    for line in input.lines().filter(|line| !line.is_empty()) {
+        let (hex, name) = line
+            .split_once_str(b"\t")
+            .ok_or_else(|| parse::Error::InfoRefsLine { line: line.into() })?;
+        let id = gix_hash::ObjectId::from_hex(hex)?;
+        match name.strip_suffix(b"^{}") {
+            Some(tag_name) => match out.last_mut() {
+                Some(tag) if tag.name == tag_name && tag.peeled.is_none() => tag.peeled = Some(id),
+                _ => return Err(parse::Error::InfoRefsLine { line: line.into() }),
+            },
+            None => out.push(Ref {
+                name: name.into(),
+                target: id,
+                peeled: None,
+            }),
+        }
+    }
+    Ok(out)
+}
+
+/// Parse the content of an `objects/info/packs` file to obtain the file names of all packs, like `pack-<hash>.pack`.
+pub fn parse_info_packs(input: &[u8]) -> Result<Vec<BString>, parse::Error> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.strip_prefix(b"P ")
+                .filter(|name| name.starts_with(b"pack-") && name.ends_with(b".pack"))
+                .map(Into::into)
+                .ok_or_else(|| parse::Error::InfoPacksLine { line: line.into() })
+        })
+        .collect()
+}
+
+/// Parse the content of the `HEAD` file.
+pub fn parse_head(input: &[u8]) -> Result<Head, parse::Error> {
+    let content = input.trim_end();
+    Ok(match content.strip_prefix(b"ref: ") {
+        Some(name) => Head::Symbolic(name.trim().into()),
+        None => Head::Detached(
+            gix_hash::ObjectId::from_hex(content).map_err(|_| parse::Error::Head { content: input.into() })?,
+        ),
+    })
+}
+
+/// The error returned by the 'dumb' protocol methods of [`Transport`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Client(#[from] client::Error),
+    #[error("Could not read the response body")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] parse::Error),
+    #[error("Pack name {name:?} is invalid")]
+    InvalidPackName { name: BString },
+}
+
+/// Dumb protocol
+impl<H: Http> Transport<H> {
+    /// Return all references listed in the `info/refs` file of the remote repository.
+    pub fn dumb_refs(&mut self) -> Result<Vec<Ref>, Error> {
+        Ok(parse_info_refs(&self.dumb_get_to_vec("info/refs")?)?)
+    }
+
+    /// Return the `HEAD` reference of the remote repository.
+    pub fn dumb_head(&mut self) -> Result<Head, Error> {
+        Ok(parse_head(&self.dumb_get_to_vec("HEAD")?)?)
+    }
+
+    /// Return the file names of all packs listed in the `objects/info/packs` file of the remote repository,
+    /// like `pack-<hash>.pack`.
+    pub fn dumb_packs(&mut self) -> Result<Vec<BString>, Error> {
+        Ok(parse_info_packs(&self.dumb_get_to_vec("objects/info/packs")?)?)
+    }
+
+    /// Return the zlib-compressed content of the loose object with `id`, exactly as stored in the remote repository.
+    ///
+    /// This fails if the object isn't stored as loose object, in which case it is expected to be in one of
+    /// the [packs](Self::dumb_packs()).
+    pub fn dumb_loose_object(&mut self, id: &gix_hash::oid) -> Result<Vec<u8>, Error> {
+        let hex = id.to_hex().to_string();
+        self.dumb_get_to_vec(&format!("objects/{}/{}", &hex[..2], &hex[2..]))
+    }
+
+    /// Return a reader for the pack with the file name `pack_name`, like `pack-<hash>.pack`, as obtained by [`dumb_packs()`](Self::dumb_packs()).
+    pub fn dumb_pack(&mut self, pack_name: &bstr::BStr) -> Result<H::ResponseBody, Error> {
+        let path = Self::dumb_pack_path(pack_name, ".pack")?;
+        self.dumb_get(&path)
+    }
+
+    /// Return a reader for the index of the pack with the file name `pack_name`, like `pack-<hash>.pack`,
+    /// to learn which objects it contains before downloading it.
+    pub fn dumb_pack_index(&mut self, pack_name: &bstr::BStr) -> Result<H::ResponseBody, Error> {
+        let path = Self::dumb_pack_path(pack_name, ".idx")?;
+        self.dumb_get(&path)
+    }
+
+    fn dumb_pack_path(pack_name: &bstr::BStr, extension: &str) -> Result<String, Error> {
+        let invalid = || Error::InvalidPackName {
+            name: pack_name.to_owned(),
+        };
+        let stem = pack_name
+            .strip_suffix(b".pack")
+            .and_then(|stem| stem.strip_prefix(b"pack-"))
+            .filter(|hash| hash.iter().all(u8::is_ascii_hexdigit))
+            .ok_or_else(invalid)?;
+        Ok(format!(
+            "objects/pack/pack-{}{extension}",
+            stem.to_str().map_err(|_| invalid())?
+        ))
+    }
+
+    fn dumb_get_to_vec(&mut self, path: &str) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::new();
+        self.dumb_get(path)?.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn dumb_get(&mut self, path: &str) -> Result<H::ResponseBody, Error> {
+        let url = append_url(&self.url, path);
+        let static_headers = [Cow::Borrowed(self.user_agent_header)];
+        let mut dynamic_headers = Vec::<Cow<'_, str>>::new();
+        self.add_basic_auth_if_present(&mut dynamic_headers)?;
+        let GetResponse { headers: _, body } = self
+            .http
+            .get(&url, &self.url, static_headers.iter().chain(&dynamic_headers))
+            .map_err(client::Error::from)?;
+        Ok(body)
+    }
+}
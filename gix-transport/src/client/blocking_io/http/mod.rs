@@ -38,6 +38,9 @@ pub mod reqwest;
 
 mod traits;
 
+/// Access to repositories served as static files, using the 'dumb' http protocol as fallback if the 'smart' one isn't available.
+pub mod dumb;
+
 ///
 pub mod options {
     /// A function to authenticate a URL.
@@ -285,11 +288,7 @@ impl<H: Http> Transport<H> {
                 name.eq_ignore_ascii_case("content-type") && value.trim() == wanted_content_type
             })
         }) {
-            return Err(client::Error::Http(Error::Detail {
-                description: format!(
-                    "Didn't find '{wanted_content_type}' header to indicate 'smart' protocol, and 'dumb' protocol is not supported."
-                ),
-            }));
+            return Err(client::Error::Http(Error::SmartProtocolUnavailable { wanted_content_type }));
         }
         Ok(())
     }
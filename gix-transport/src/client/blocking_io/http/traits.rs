@@ -10,6 +10,8 @@ pub enum Error {
     },
     #[error("{description}")]
     Detail { description: String },
+    #[error("Didn't find '{wanted_content_type}' header to indicate 'smart' protocol, the server might only support the 'dumb' protocol")]
+    SmartProtocolUnavailable { wanted_content_type: String },
     #[error("An IO error occurred while uploading the body of a POST request")]
     PostBody(#[from] std::io::Error),
 }
@@ -16,5 +16,8 @@ pub use request::RequestWriter;
 ///
 pub mod ssh;
 
+///
+pub mod throttle;
+
 mod traits;
 pub use traits::{SetServiceResponse, Transport, TransportV2Ext};
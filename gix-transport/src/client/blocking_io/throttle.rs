@@ -0,0 +1,206 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bstr::BStr;
+
+use crate::{
+    client,
+    client::{ExtendedBufRead, HandleProgress, MessageKind, ReadlineBufRead, RequestWriter, WriteMode},
+    Protocol, Service,
+};
+
+/// Limit the rate at which data is sent and received by all transports, readers and writers created from the same instance.
+///
+/// Clones share their budget, which makes it possible to limit the combined rate of multiple transports.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    upload: Budget,
+    download: Budget,
+}
+
+impl Throttle {
+    /// Create a new instance which allows to send at most `upload_bytes_per_second` and to receive at most
+    /// `download_bytes_per_second`, with `None` or `0` meaning there is no limit.
+    pub fn new(upload_bytes_per_second: Option<u64>, download_bytes_per_second: Option<u64>) -> Self {
+        Throttle {
+            upload: Budget::new(upload_bytes_per_second),
+            download: Budget::new(download_bytes_per_second),
+        }
+    }
+
+    /// Wrap `inner`, which is typically a [`Transport`](client::Transport), so that all data passing through it is limited by us.
+    pub fn wrap<T>(&self, inner: T) -> Throttled<T> {
+        Throttled {
+            inner,
+            throttle: self.clone(),
+        }
+    }
+}
+
+/// The amount of bytes we may transfer per second in one direction, along with what was transferred already.
+#[derive(Debug, Clone)]
+struct Budget {
+    bytes_per_second: Option<u64>,
+    /// The time at which we started to count, and the amount of bytes transferred since, or `None` if nothing was transferred yet.
+    transferred: Arc<Mutex<Option<(Instant, u64)>>>,
+}
+
+impl Budget {
+    fn new(bytes_per_second: Option<u64>) -> Self {
+        Budget {
+            bytes_per_second: bytes_per_second.filter(|limit| *limit != 0),
+            transferred: Default::default(),
+        }
+    }
+
+    /// Account for `bytes` having been transferred, and sleep until we are back within our limit.
+    fn spend(&self, bytes: usize) {
+        let Some(limit) = self.bytes_per_second else {
+            return;
+        };
+        let mut state = self.transferred.lock().expect("no panic while holding the lock");
+        let (start, transferred) = state.get_or_insert_with(|| (Instant::now(), 0));
+        *transferred += bytes as u64;
+        let due = Duration::from_secs_f64(*transferred as f64 / limit as f64);
+        let elapsed = start.elapsed();
+        match due.checked_sub(elapsed) {
+            Some(wait) => std::thread::sleep(wait),
+            // Don't let long pauses turn into bursts that exceed the limit.
+            None if elapsed.saturating_sub(due) > MAX_IDLE_CREDIT => *state = None,
+            None => {}
+        }
+    }
+}
+
+/// The amount of time we may fall behind our budget before starting to count anew.
+const MAX_IDLE_CREDIT: Duration = Duration::from_secs(1);
+
+/// A transport, reader or writer whose transfer rate is limited by a [`Throttle`].
+///
+/// Note that data exchanged during the handshake isn't limited, but data of all subsequent requests is.
+pub struct Throttled<T> {
+    inner: T,
+    throttle: Throttle,
+}
+
+impl<T> Throttled<T> {
+    /// Return the wrapped instance.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: io::Read> io::Read for Throttled<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.throttle.download.spend(read);
+        Ok(read)
+    }
+}
+
+impl<T: io::BufRead> io::BufRead for Throttled<T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.throttle.download.spend(amt);
+    }
+}
+
+impl<T: io::Write> io::Write for Throttled<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.throttle.upload.spend(written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: ReadlineBufRead> ReadlineBufRead for Throttled<T> {
+    fn readline(
+        &mut self,
+    ) -> Option<io::Result<Result<gix_packetline::PacketLineRef<'_>, gix_packetline::decode::Error>>> {
+        self.inner.readline()
+    }
+
+    fn readline_str(&mut self, line: &mut String) -> io::Result<usize> {
+        self.inner.readline_str(line)
+    }
+}
+
+impl<'a, T: ExtendedBufRead<'a>> ExtendedBufRead<'a> for Throttled<T> {
+    fn set_progress_handler(&mut self, handle_progress: Option<HandleProgress<'a>>) {
+        self.inner.set_progress_handler(handle_progress);
+    }
+
+    fn peek_data_line(&mut self) -> Option<io::Result<Result<&[u8], client::Error>>> {
+        self.inner.peek_data_line()
+    }
+
+    fn reset(&mut self, version: Protocol) {
+        self.inner.reset(version);
+    }
+
+    fn stopped_at(&self) -> Option<MessageKind> {
+        self.inner.stopped_at()
+    }
+}
+
+impl<T: client::TransportWithoutIO> client::TransportWithoutIO for Throttled<T> {
+    fn set_identity(&mut self, identity: client::Account) -> Result<(), client::Error> {
+        self.inner.set_identity(identity)
+    }
+
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        let throttle = self.throttle.clone();
+        let (writer, reader) = self.inner.request(write_mode, on_into_read, trace)?.into_parts();
+        Ok(RequestWriter::new_from_bufread(
+            throttle.wrap(writer),
+            Box::new(throttle.wrap(reader)),
+            write_mode,
+            on_into_read,
+            trace,
+        ))
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        self.inner.to_url()
+    }
+
+    fn supported_protocol_versions(&self) -> &[Protocol] {
+        self.inner.supported_protocol_versions()
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        self.inner.connection_persists_across_multiple_requests()
+    }
+
+    fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.inner.configure(config)
+    }
+}
+
+impl<T: client::Transport> client::Transport for Throttled<T> {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<client::SetServiceResponse<'_>, client::Error> {
+        self.inner.handshake(service, extra_parameters)
+    }
+}
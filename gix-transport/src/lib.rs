@@ -84,6 +84,9 @@ pub use traits::IsSpuriousError;
 ///
 pub mod client;
 
+///
+pub mod remote_helper;
+
 #[doc(inline)]
 #[cfg(any(feature = "blocking-client", all(feature = "async-client", feature = "async-std")))]
 pub use client::connect;
@@ -0,0 +1,306 @@
+use std::{
+    ffi::OsString,
+    io::{BufRead, Write},
+    process::{Child, ChildStdin, ChildStdout, Stdio},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::remote_helper::{
+    decode, decode::CommandLine, Capability, Command, ConnectResponse, Fetch, ListedRef, OptionResponse, PushStatus,
+};
+
+/// Read a single line without its trailing newline into `buf`, returning `false` if the end of the stream was reached.
+fn read_line(input: &mut dyn BufRead, buf: &mut Vec<u8>) -> std::io::Result<bool> {
+    buf.clear();
+    if input.read_until(b'\n', buf)? == 0 {
+        return Ok(false);
+    }
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+    }
+    Ok(true)
+}
+
+///
+pub mod client {
+    use bstr::BString;
+
+    /// The error returned by methods of the [`Client`][crate::remote_helper::Client].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("An IO error occurred when communicating with the remote helper")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Decode(#[from] crate::remote_helper::decode::Error),
+        #[error("The remote helper terminated its output unexpectedly")]
+        UnexpectedEof,
+        #[error("Expected an empty line or 'fallback' in response to 'connect', got {line:?}")]
+        UnexpectedConnectResponse { line: BString },
+    }
+}
+
+/// A client to drive a remote helper program by sending it [commands][Command] and parsing its responses.
+pub struct Client<R, W> {
+    reader: R,
+    writer: W,
+    /// Declared after `writer` so the helper sees the end of its input before we wait for it.
+    child: Option<ChildGuard>,
+    buf: Vec<u8>,
+}
+
+/// Instantiation
+impl Client<std::io::BufReader<ChildStdout>, ChildStdin> {
+    /// Spawn the remote helper program `git-remote-<transport>` with the name of the `remote` and its `url` as arguments,
+    /// and connect to its standard input and output.
+    ///
+    /// When the returned instance is dropped, the standard input of the program is closed and it is waited for.
+    pub fn spawn(
+        transport: &str,
+        remote: impl Into<OsString>,
+        url: impl Into<OsString>,
+    ) -> std::io::Result<Self> {
+        let mut child = gix_command::prepare(format!("git-remote-{transport}"))
+            .arg(remote)
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let writer = child.stdin.take().expect("configured");
+        let reader = std::io::BufReader::new(child.stdout.take().expect("configured"));
+        Ok(Client {
+            reader,
+            writer,
+            child: Some(ChildGuard(Some(child))),
+            buf: Vec::new(),
+        })
+    }
+}
+
+/// Instantiation
+impl<R, W> Client<R, W>
+where
+    R: BufRead,
+    W: Write,
+{
+    /// Create a new instance which reads responses from `reader` and writes commands to `writer`.
+    pub fn from_io(reader: R, writer: W) -> Self {
+        Client {
+            reader,
+            writer,
+            child: None,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Commands
+impl<R, W> Client<R, W>
+where
+    R: BufRead,
+    W: Write,
+{
+    /// Obtain all capabilities of the remote helper.
+    pub fn capabilities(&mut self) -> Result<Vec<Capability>, client::Error> {
+        self.send(&Command::Capabilities)?;
+        let mut out = Vec::new();
+        while let Some(line) = self.read_line_until_empty()? {
+            out.push(decode::capability(line));
+        }
+        Ok(out)
+    }
+
+    /// List all refs of the remote, in preparation of a push if `for_push` is `true`.
+    ///
+    /// Keyword lines like `:object-format sha1` are skipped.
+    pub fn list(&mut self, for_push: bool) -> Result<Vec<ListedRef>, client::Error> {
+        self.send(&Command::List { for_push })?;
+        let mut out = Vec::new();
+        while let Some(line) = self.read_line_until_empty()? {
+            if line.starts_with(b":") {
+                continue;
+            }
+            out.push(decode::listed_ref(line)?);
+        }
+        Ok(out)
+    }
+
+    /// Set the option `name` to `value`, which only works if the helper has the `option` capability.
+    pub fn option(&mut self, name: &BStr, value: &BStr) -> Result<OptionResponse, client::Error> {
+        self.send(&Command::Option {
+            name: name.into(),
+            value: value.into(),
+        })?;
+        let line = self.read_line()?.ok_or(client::Error::UnexpectedEof)?;
+        Ok(decode::option_response(line)?)
+    }
+
+    /// Fetch all objects in `fetches` into the repository the helper was started for, and return once the helper is done.
+    pub fn fetch(&mut self, fetches: &[Fetch]) -> Result<(), client::Error> {
+        self.send(&Command::Fetch(fetches.to_owned()))?;
+        // Lines like `lock <file>` or `connectivity-ok` may precede the terminating empty line.
+        while self.read_line_until_empty()?.is_some() {}
+        Ok(())
+    }
+
+    /// Push the given refspecs to the remote and return the status of each pushed ref.
+    pub fn push(&mut self, specs: &[BString]) -> Result<Vec<PushStatus>, client::Error> {
+        self.send(&Command::Push(specs.to_owned()))?;
+        let mut out = Vec::new();
+        while let Some(line) = self.read_line_until_empty()? {
+            out.push(decode::push_status(line)?);
+        }
+        Ok(out)
+    }
+
+    /// Connect to `service`, like `git-upload-pack`, and if [established][ConnectResponse::Established], use
+    /// [`into_io()`][Self::into_io()] to speak the service protocol directly.
+    pub fn connect(&mut self, service: &BStr) -> Result<ConnectResponse, client::Error> {
+        self.send(&Command::Connect { service: service.into() })?;
+        let line = self.read_line()?.ok_or(client::Error::UnexpectedEof)?;
+        match line.as_bytes() {
+            b"" => Ok(ConnectResponse::Established),
+            b"fallback" => Ok(ConnectResponse::Fallback),
+            _ => Err(client::Error::UnexpectedConnectResponse { line: line.into() }),
+        }
+    }
+
+    /// Return the underlying reader and writer, along with the child process if this instance was [spawned][Client::spawn()].
+    pub fn into_io(mut self) -> (R, W, Option<Child>) {
+        let child = self.child.take().map(|mut guard| guard.0.take().expect("only taken here or on drop"));
+        (self.reader, self.writer, child)
+    }
+
+    fn send(&mut self, command: &Command) -> std::io::Result<()> {
+        command.write_to(&mut self.writer)?;
+        self.writer.flush()
+    }
+
+    fn read_line(&mut self) -> std::io::Result<Option<&BStr>> {
+        Ok(read_line(&mut self.reader, &mut self.buf)?.then(|| self.buf.as_bstr()))
+    }
+
+    /// Read the next line of a response which is terminated by an empty line, returning `None` once it was seen.
+    fn read_line_until_empty(&mut self) -> Result<Option<&BStr>, client::Error> {
+        match self.read_line()? {
+            None => Err(client::Error::UnexpectedEof),
+            Some(line) if line.is_empty() => Ok(None),
+            Some(_) => Ok(Some(self.buf.as_bstr())),
+        }
+    }
+}
+
+/// Waits for the helper process to finish when dropped, which it will as soon as its standard input is closed.
+struct ChildGuard(Option<Child>);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.0.take() {
+            child.wait().ok();
+        }
+    }
+}
+
+/// The error type used in [`Delegate`] methods.
+pub type DelegateError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The implementation of a remote helper, driven by [`serve()`].
+pub trait Delegate {
+    /// Return the capabilities the helper supports, typically including `fetch`, `push` and `option`.
+    fn capabilities(&mut self) -> Vec<Capability>;
+    /// Return all refs of the remote, in preparation of a push if `for_push` is `true`.
+    fn list(&mut self, for_push: bool) -> Result<Vec<ListedRef>, DelegateError>;
+    /// Set the option `name` to `value`.
+    ///
+    /// By default, all options are unsupported.
+    fn option(&mut self, _name: &BStr, _value: &BStr) -> OptionResponse {
+        OptionResponse::Unsupported
+    }
+    /// Fetch all `fetches` into the local repository, returning only when all objects are present.
+    fn fetch(&mut self, fetches: &[Fetch]) -> Result<(), DelegateError>;
+    /// Push all refspecs in `specs` to the remote, returning the status of each destination ref.
+    fn push(&mut self, specs: &[BString]) -> Result<Vec<PushStatus>, DelegateError>;
+}
+
+///
+pub mod serve {
+    use bstr::BString;
+
+    /// The error returned by [`serve()`][crate::remote_helper::serve()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("An IO error occurred when communicating with the caller")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Decode(#[from] crate::remote_helper::decode::Error),
+        #[error("Only further {expected:?} commands are allowed in a batch, got {line:?}")]
+        UnexpectedCommandInBatch { expected: &'static str, line: BString },
+        #[error(transparent)]
+        Delegate(crate::remote_helper::DelegateError),
+    }
+}
+
+/// Act as remote helper by reading commands from `input`, passing them to `delegate`, and writing its responses
+/// to `output`, until the end of `input` or an empty line is reached.
+///
+/// As `connect` isn't supported, it will always be answered with `fallback`.
+pub fn serve(input: &mut dyn BufRead, output: &mut dyn Write, delegate: &mut dyn Delegate) -> Result<(), serve::Error> {
+    let mut buf = Vec::new();
+    while read_line(input, &mut buf)? && !buf.is_empty() {
+        match decode::command_line(buf.as_bstr())? {
+            CommandLine::Capabilities => {
+                for capability in delegate.capabilities() {
+                    capability.write_to(output)?;
+                }
+                output.write_all(b"\n")?;
+            }
+            CommandLine::List { for_push } => {
+                for listed_ref in delegate.list(for_push).map_err(serve::Error::Delegate)? {
+                    listed_ref.write_to(output)?;
+                }
+                output.write_all(b"\n")?;
+            }
+            CommandLine::Option { name, value } => {
+                delegate.option(name.as_ref(), value.as_ref()).write_to(output)?;
+            }
+            CommandLine::Fetch(first) => {
+                let mut fetches = vec![first];
+                while read_line(input, &mut buf)? && !buf.is_empty() {
+                    match decode::command_line(buf.as_bstr())? {
+                        CommandLine::Fetch(fetch) => fetches.push(fetch),
+                        _ => return Err(unexpected_in_batch("fetch", &buf)),
+                    }
+                }
+                delegate.fetch(&fetches).map_err(serve::Error::Delegate)?;
+                output.write_all(b"\n")?;
+            }
+            CommandLine::Push(first) => {
+                let mut specs = vec![first];
+                while read_line(input, &mut buf)? && !buf.is_empty() {
+                    match decode::command_line(buf.as_bstr())? {
+                        CommandLine::Push(spec) => specs.push(spec),
+                        _ => return Err(unexpected_in_batch("push", &buf)),
+                    }
+                }
+                for status in delegate.push(&specs).map_err(serve::Error::Delegate)? {
+                    status.write_to(output)?;
+                }
+                output.write_all(b"\n")?;
+            }
+            CommandLine::Connect => {
+                output.write_all(b"fallback\n")?;
+            }
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn unexpected_in_batch(expected: &'static str, line: &[u8]) -> serve::Error {
+    serve::Error::UnexpectedCommandInBatch {
+        expected,
+        line: line.into(),
+    }
+}
@@ -0,0 +1,144 @@
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::remote_helper::{Capability, Fetch, ListedRef, OptionResponse, PushStatus, Value};
+
+/// The error returned by the functions in the [`decode`][crate::remote_helper::decode] module.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not parse object id in line {line:?}")]
+    ObjectId { line: BString, source: gix_hash::decode::Error },
+    #[error("Expected a value and a ref name, got {line:?}")]
+    MalformedListLine { line: BString },
+    #[error("Expected 'ok', 'unsupported' or 'error <message>' as option response, got {line:?}")]
+    MalformedOptionResponse { line: BString },
+    #[error("Expected 'ok <dst>' or 'error <dst> [<why>]' as push status, got {line:?}")]
+    MalformedPushStatus { line: BString },
+    #[error("Expected '<id> <name>' as fetch arguments, got {line:?}")]
+    MalformedFetch { line: BString },
+    #[error("Unknown command {line:?}")]
+    UnknownCommand { line: BString },
+}
+
+/// Parse a single line of the response to the `capabilities` command, without the trailing newline.
+pub fn capability(line: &BStr) -> Capability {
+    let (mandatory, line) = match line.strip_prefix(b"*") {
+        Some(rest) => (true, rest.as_bstr()),
+        None => (false, line),
+    };
+    let (name, value) = split_once_space(line);
+    Capability {
+        name: name.into(),
+        value: value.map(Into::into),
+        mandatory,
+    }
+}
+
+/// Parse a single line of the response to the `list` command, without the trailing newline.
+///
+/// Note that lines starting with `:` are keywords, like `:object-format sha1`, and must be handled by the caller.
+pub fn listed_ref(line: &BStr) -> Result<ListedRef, Error> {
+    let mut tokens = line.split_str(" ");
+    let (value, name) = match (tokens.next(), tokens.next()) {
+        (Some(value), Some(name)) if !value.is_empty() && !name.is_empty() => (value, name),
+        _ => return Err(Error::MalformedListLine { line: line.into() }),
+    };
+    let value = match value {
+        b"?" => Value::Unknown,
+        _ => match value.strip_prefix(b"@") {
+            Some(target) => Value::Symbolic(target.into()),
+            None => Value::Object(object_id(value, line)?),
+        },
+    };
+    Ok(ListedRef {
+        value,
+        name: name.into(),
+        attributes: tokens.filter(|t| !t.is_empty()).map(Into::into).collect(),
+    })
+}
+
+/// Parse the response to the `option` command, without the trailing newline.
+pub fn option_response(line: &BStr) -> Result<OptionResponse, Error> {
+    Ok(match line.as_bytes() {
+        b"ok" => OptionResponse::Ok,
+        b"unsupported" => OptionResponse::Unsupported,
+        b"error" => OptionResponse::Error(BString::default()),
+        _ => match line.strip_prefix(b"error ") {
+            Some(message) => OptionResponse::Error(message.into()),
+            None => return Err(Error::MalformedOptionResponse { line: line.into() }),
+        },
+    })
+}
+
+/// Parse a single line of the response to the `push` command, without the trailing newline.
+pub fn push_status(line: &BStr) -> Result<PushStatus, Error> {
+    if let Some(destination) = line.strip_prefix(b"ok ") {
+        return Ok(PushStatus::Ok {
+            destination: destination.into(),
+        });
+    }
+    match line.strip_prefix(b"error ").map(|rest| split_once_space(rest.as_bstr())) {
+        Some((destination, reason)) if !destination.is_empty() => Ok(PushStatus::Error {
+            destination: destination.into(),
+            reason: reason.map(Into::into),
+        }),
+        _ => Err(Error::MalformedPushStatus { line: line.into() }),
+    }
+}
+
+/// Parse the arguments of a `fetch <id> <name>` line, i.e. everything after `fetch `.
+pub fn fetch(args: &BStr) -> Result<Fetch, Error> {
+    match split_once_space(args) {
+        (id, Some(name)) if !name.is_empty() => Ok(Fetch {
+            id: object_id(id, args)?,
+            name: name.into(),
+        }),
+        _ => Err(Error::MalformedFetch { line: args.into() }),
+    }
+}
+
+/// A single line of a command as sent to a remote helper, with batched commands not yet combined.
+#[cfg(feature = "blocking-client")]
+pub(crate) enum CommandLine {
+    Capabilities,
+    List { for_push: bool },
+    Option { name: BString, value: BString },
+    Fetch(Fetch),
+    Push(BString),
+    Connect,
+}
+
+#[cfg(feature = "blocking-client")]
+pub(crate) fn command_line(line: &BStr) -> Result<CommandLine, Error> {
+    let (command, args) = split_once_space(line);
+    Ok(match (command.as_bytes(), args) {
+        (b"capabilities", None) => CommandLine::Capabilities,
+        (b"list", None) => CommandLine::List { for_push: false },
+        (b"list", Some(args)) if args == "for-push" => CommandLine::List { for_push: true },
+        (b"option", Some(args)) => {
+            let (name, value) = split_once_space(args);
+            CommandLine::Option {
+                name: name.into(),
+                value: value.map(Into::into).unwrap_or_default(),
+            }
+        }
+        (b"fetch", Some(args)) => CommandLine::Fetch(fetch(args)?),
+        (b"push", Some(spec)) => CommandLine::Push(spec.into()),
+        (b"connect", Some(_service)) => CommandLine::Connect,
+        _ => return Err(Error::UnknownCommand { line: line.into() }),
+    })
+}
+
+fn object_id(hex: &[u8], line: &BStr) -> Result<gix_hash::ObjectId, Error> {
+    gix_hash::ObjectId::from_hex(hex).map_err(|source| Error::ObjectId {
+        line: line.into(),
+        source,
+    })
+}
+
+fn split_once_space(line: &BStr) -> (&BStr, Option<&BStr>) {
+    match line.find_byte(b' ') {
+        Some(pos) => (line[..pos].as_bstr(), Some(line[pos + 1..].as_bstr())),
+        None => (line, None),
+    }
+}
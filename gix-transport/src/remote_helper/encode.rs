@@ -0,0 +1,120 @@
+use std::io::Write;
+
+use crate::remote_helper::{Capability, Command, ListedRef, OptionResponse, PushStatus, Value};
+
+/// Serialization
+impl Command {
+    /// Write this command to `out` in the format expected by remote helpers, including the trailing newline.
+    ///
+    /// Batched commands, i.e. `fetch` and `push`, are terminated with an empty line.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            Command::Capabilities => out.write_all(b"capabilities\n"),
+            Command::List { for_push } => out.write_all(if *for_push { b"list for-push\n" } else { b"list\n" }),
+            Command::Option { name, value } => {
+                out.write_all(b"option ")?;
+                out.write_all(name)?;
+                out.write_all(b" ")?;
+                out.write_all(value)?;
+                out.write_all(b"\n")
+            }
+            Command::Fetch(fetches) => {
+                for fetch in fetches {
+                    write!(out, "fetch {} ", fetch.id)?;
+                    out.write_all(&fetch.name)?;
+                    out.write_all(b"\n")?;
+                }
+                out.write_all(b"\n")
+            }
+            Command::Push(specs) => {
+                for spec in specs {
+                    out.write_all(b"push ")?;
+                    out.write_all(spec)?;
+                    out.write_all(b"\n")?;
+                }
+                out.write_all(b"\n")
+            }
+            Command::Connect { service } => {
+                out.write_all(b"connect ")?;
+                out.write_all(service)?;
+                out.write_all(b"\n")
+            }
+        }
+    }
+}
+
+/// Serialization
+impl Capability {
+    /// Write this capability as line to `out`, including the trailing newline.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.mandatory {
+            out.write_all(b"*")?;
+        }
+        out.write_all(&self.name)?;
+        if let Some(value) = &self.value {
+            out.write_all(b" ")?;
+            out.write_all(value)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+/// Serialization
+impl ListedRef {
+    /// Write this ref as line to `out` as part of the response to the `list` command, including the trailing newline.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match &self.value {
+            Value::Object(id) => write!(out, "{id}")?,
+            Value::Symbolic(target) => {
+                out.write_all(b"@")?;
+                out.write_all(target)?;
+            }
+            Value::Unknown => out.write_all(b"?")?,
+        }
+        out.write_all(b" ")?;
+        out.write_all(&self.name)?;
+        for attribute in &self.attributes {
+            out.write_all(b" ")?;
+            out.write_all(attribute)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+/// Serialization
+impl OptionResponse {
+    /// Write this response as line to `out`, including the trailing newline.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            OptionResponse::Ok => out.write_all(b"ok\n"),
+            OptionResponse::Unsupported => out.write_all(b"unsupported\n"),
+            OptionResponse::Error(message) => {
+                out.write_all(b"error ")?;
+                out.write_all(message)?;
+                out.write_all(b"\n")
+            }
+        }
+    }
+}
+
+/// Serialization
+impl PushStatus {
+    /// Write this status as line to `out` as part of the response to the `push` command, including the trailing newline.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            PushStatus::Ok { destination } => {
+                out.write_all(b"ok ")?;
+                out.write_all(destination)?;
+            }
+            PushStatus::Error { destination, reason } => {
+                out.write_all(b"error ")?;
+                out.write_all(destination)?;
+                if let Some(reason) = reason {
+                    out.write_all(b" ")?;
+                    out.write_all(reason)?;
+                }
+            }
+        }
+        out.write_all(b"\n")
+    }
+}
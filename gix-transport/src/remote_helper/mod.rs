@@ -0,0 +1,130 @@
+//! An implementation of the [remote helper protocol](https://git-scm.com/docs/gitremote-helpers), which is spoken
+//! by `git remote-<transport>` programs over their standard input and output.
+//!
+//! It's possible to drive existing remote helpers with a [`Client`], or to implement a remote helper by providing a
+//! [`Delegate`] to [`serve()`], so that `git` itself can talk to `gitoxide`.
+use bstr::BString;
+
+/// A single capability advertised by a remote helper in response to the `capabilities` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capability {
+    /// The name of the capability, like `fetch`, `push` or `refspec`.
+    pub name: BString,
+    /// The value following the name after a space, like `refs/heads/*:refs/svn/origin/branches/*` for `refspec`.
+    pub value: Option<BString>,
+    /// If `true`, the capability was prefixed with `*` and the caller must abort if it doesn't understand it.
+    pub mandatory: bool,
+}
+
+/// A command sent to the remote helper.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    /// Ask for the list of [capabilities][Capability] supported by the helper.
+    Capabilities,
+    /// List all refs along with their values.
+    List {
+        /// If `true`, the listing is made in preparation of a push, i.e. `list for-push`.
+        for_push: bool,
+    },
+    /// Set the transport option `name` to `value`.
+    Option {
+        /// The name of the option, like `verbosity` or `depth`.
+        name: BString,
+        /// The value of the option, which is `true` or `false` for boolean options.
+        value: BString,
+    },
+    /// Fetch the given objects, sent as batch of `fetch <id> <name>` lines.
+    Fetch(Vec<Fetch>),
+    /// Push the given refspecs, sent as batch of `push <refspec>` lines.
+    Push(Vec<BString>),
+    /// Connect to the given `service`, like `git-upload-pack`, to talk to it directly.
+    Connect {
+        /// The service to connect to.
+        service: BString,
+    },
+}
+
+/// A single object to fetch as part of [`Command::Fetch`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fetch {
+    /// The object to fetch.
+    pub id: gix_hash::ObjectId,
+    /// The name of the ref pointing to `id`, as previously returned by the `list` command.
+    pub name: BString,
+}
+
+/// The value of a ref as returned by the `list` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    /// The ref points to the given object.
+    Object(gix_hash::ObjectId),
+    /// The ref is symbolic and points to the ref with the given name, like `@refs/heads/main`.
+    Symbolic(BString),
+    /// The value isn't known to the helper, indicated by `?`.
+    Unknown,
+}
+
+/// A ref as returned by the `list` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListedRef {
+    /// The value the ref currently has.
+    pub value: Value,
+    /// The full name of the ref, like `refs/heads/main`.
+    pub name: BString,
+    /// Additional space-separated attributes, like `unchanged`.
+    pub attributes: Vec<BString>,
+}
+
+/// The response to an [option][Command::Option].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptionResponse {
+    /// The option was set.
+    Ok,
+    /// The option isn't supported by the helper.
+    Unsupported,
+    /// The option is supported, but the value was invalid.
+    Error(BString),
+}
+
+/// The outcome of pushing a single refspec as part of [`Command::Push`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PushStatus {
+    /// The ref at `destination` was updated successfully.
+    Ok {
+        /// The name of the updated remote ref.
+        destination: BString,
+    },
+    /// The ref at `destination` could not be updated.
+    Error {
+        /// The name of the remote ref that couldn't be updated.
+        destination: BString,
+        /// A possibly available explanation, like `non-fast forward`.
+        reason: Option<BString>,
+    },
+}
+
+/// The response to a [`Command::Connect`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConnectResponse {
+    /// The connection was established and standard input and output are now connected to the service.
+    Established,
+    /// The helper can't connect and the caller should fall back to the dumb protocol using `fetch` and `push`.
+    Fallback,
+}
+
+///
+pub mod decode;
+mod encode;
+
+#[cfg(feature = "blocking-client")]
+mod blocking_io;
+#[cfg(feature = "blocking-client")]
+pub use blocking_io::{client, serve, Client, Delegate, DelegateError};
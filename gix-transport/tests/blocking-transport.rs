@@ -12,3 +12,5 @@ pub fn fixture_bytes(path: &str) -> Vec<u8> {
 
 #[cfg(not(feature = "http-client-curl"))]
 mod client;
+
+mod remote_helper;
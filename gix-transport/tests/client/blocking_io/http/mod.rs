@@ -685,6 +685,120 @@ fn check_content_type_is_case_insensitive() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn dumb_protocol_fallback() -> crate::Result {
+    fn response(body: &str) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .into_bytes()
+    }
+    let info_refs = "\
+4b825dc642cb6eb9a060e54bf8d69288fbee4904\trefs/heads/main
+1111111111111111111111111111111111111111\trefs/tags/v1
+4b825dc642cb6eb9a060e54bf8d69288fbee4904\trefs/tags/v1^{}
+";
+    let server = mock::Server::new(response(info_refs));
+    let url = format!("http://{}:{}/repo.git", server.addr.ip(), server.addr.port());
+    let mut client = gix_transport::client::http::connect(url.as_str().try_into()?, Protocol::V2, false);
+
+    let err = client
+        .handshake(Service::UploadPack, &[])
+        .err()
+        .expect("the server doesn't speak the smart protocol");
+    assert!(
+        matches!(err, client::Error::Http(http::Error::SmartProtocolUnavailable { .. })),
+        "{err:?}"
+    );
+    assert!(server
+        .received_as_string()
+        .starts_with("GET /repo.git/info/refs?service=git-upload-pack "));
+
+    server.next_read_and_respond_with(response(info_refs));
+    let main = gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1);
+    assert_eq!(
+        client.dumb_refs()?,
+        [
+            http::dumb::Ref {
+                name: "refs/heads/main".into(),
+                target: main,
+                peeled: None,
+            },
+            http::dumb::Ref {
+                name: "refs/tags/v1".into(),
+                target: gix_hash::ObjectId::from_hex(b"1111111111111111111111111111111111111111")?,
+                peeled: Some(main),
+            }
+        ]
+    );
+    assert!(server.received_as_string().starts_with("GET /repo.git/info/refs "));
+
+    server.next_read_and_respond_with(response("ref: refs/heads/main\n"));
+    assert_eq!(
+        client.dumb_head()?,
+        http::dumb::Head::Symbolic("refs/heads/main".into())
+    );
+    assert!(server.received_as_string().starts_with("GET /repo.git/HEAD "));
+
+    let pack_name = "pack-2c4a4e4e7e0f9c7ad5b1a1f4b7a2c5e0b8e6a1f0.pack";
+    server.next_read_and_respond_with(response(&format!("P {pack_name}\n\n")));
+    assert_eq!(client.dumb_packs()?, [pack_name]);
+    assert!(server
+        .received_as_string()
+        .starts_with("GET /repo.git/objects/info/packs "));
+
+    server.next_read_and_respond_with(response("compressed"));
+    assert_eq!(client.dumb_loose_object(&main)?, b"compressed");
+    assert!(server
+        .received_as_string()
+        .starts_with("GET /repo.git/objects/4b/825dc642cb6eb9a060e54bf8d69288fbee4904 "));
+
+    server.next_read_and_respond_with(response("idx"));
+    let mut buf = Vec::new();
+    client.dumb_pack_index(pack_name.into())?.read_to_end(&mut buf)?;
+    assert_eq!(buf, b"idx");
+    assert!(server.received_as_string().starts_with(&format!(
+        "GET /repo.git/objects/pack/{}.idx ",
+        pack_name.trim_end_matches(".pack")
+    )));
+
+    assert!(
+        matches!(
+            client.dumb_pack("../pack-1234.pack".into()),
+            Err(http::dumb::Error::InvalidPackName { .. })
+        ),
+        "pack names are validated to not escape the pack directory"
+    );
+    Ok(())
+}
+
+#[test]
+fn dumb_protocol_parsing() -> crate::Result {
+    use http::dumb::{parse::Error, parse_head, parse_info_packs, parse_info_refs};
+    assert!(matches!(
+        parse_info_refs(b"4b825dc642cb6eb9a060e54bf8d69288fbee4904 refs/heads/main"),
+        Err(Error::InfoRefsLine { .. })
+    ));
+    assert!(
+        matches!(
+            parse_info_refs(b"4b825dc642cb6eb9a060e54bf8d69288fbee4904\trefs/heads/main^{}"),
+            Err(Error::InfoRefsLine { .. })
+        ),
+        "peeled objects must follow their tag"
+    );
+    assert!(matches!(
+        parse_info_packs(b"P ../evil"),
+        Err(Error::InfoPacksLine { .. })
+    ));
+    assert_eq!(
+        parse_head(b"4b825dc642cb6eb9a060e54bf8d69288fbee4904\n")?,
+        http::dumb::Head::Detached(gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1))
+    );
+    assert!(matches!(parse_head(b"garbage"), Err(Error::Head { .. })));
+    Ok(())
+}
+
 fn ignore_reqwest_content_length(header_line: &String) -> bool {
     header_line != "content-length: 0"
 }
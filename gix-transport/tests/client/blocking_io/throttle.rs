@@ -0,0 +1,54 @@
+use std::{
+    io::{Read, Write},
+    time::{Duration, Instant},
+};
+
+use gix_transport::client::throttle::Throttle;
+
+#[test]
+fn reads_and_writes_are_limited_independently() -> crate::Result {
+    let throttle = Throttle::new(Some(10_000), Some(20_000));
+    let data = vec![0u8; 4_000];
+
+    let start = Instant::now();
+    let mut writer = throttle.wrap(Vec::new());
+    writer.write_all(&data)?;
+    assert_eq!(writer.into_inner(), data);
+    assert!(
+        start.elapsed() >= Duration::from_millis(400),
+        "4KB at 10KB/s take 400ms"
+    );
+
+    let start = Instant::now();
+    let mut buf = Vec::new();
+    throttle.wrap(data.as_slice()).read_to_end(&mut buf)?;
+    assert_eq!(buf, data);
+    assert!(
+        start.elapsed() >= Duration::from_millis(200),
+        "4KB at 20KB/s take 200ms"
+    );
+    Ok(())
+}
+
+#[test]
+fn clones_share_their_budget() -> crate::Result {
+    let throttle = Throttle::new(None, Some(10_000));
+    let data = vec![0u8; 2_000];
+
+    let start = Instant::now();
+    let mut buf = Vec::new();
+    throttle.wrap(data.as_slice()).read_to_end(&mut buf)?;
+    throttle.clone().wrap(data.as_slice()).read_to_end(&mut buf)?;
+    assert!(
+        start.elapsed() >= Duration::from_millis(400),
+        "4KB at 10KB/s take 400ms, no matter how they are read"
+    );
+
+    let start = Instant::now();
+    throttle.wrap(Vec::new()).write_all(&data)?;
+    assert!(
+        start.elapsed() < Duration::from_millis(200),
+        "uploads aren't limited if no limit was set"
+    );
+    Ok(())
+}
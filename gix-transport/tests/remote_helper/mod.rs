@@ -0,0 +1,213 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_transport::remote_helper::{
+    decode, serve, Capability, Client, ConnectResponse, Delegate, DelegateError, Fetch, ListedRef, OptionResponse,
+    PushStatus, Value,
+};
+
+fn id(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("valid hex")
+}
+
+#[derive(Default)]
+struct Recorder {
+    fetched: Vec<Fetch>,
+    pushed: Vec<BString>,
+}
+
+impl Delegate for Recorder {
+    fn capabilities(&mut self) -> Vec<Capability> {
+        vec![
+            decode::capability("fetch".into()),
+            decode::capability("*push".into()),
+            decode::capability("refspec refs/heads/*:refs/remote/heads/*".into()),
+            decode::capability("option".into()),
+        ]
+    }
+
+    fn list(&mut self, for_push: bool) -> Result<Vec<ListedRef>, DelegateError> {
+        let mut refs = vec![
+            decode::listed_ref("@refs/heads/main HEAD".into())?,
+            decode::listed_ref("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main".into())?,
+        ];
+        if for_push {
+            refs.push(decode::listed_ref("? refs/heads/unknown unchanged".into())?);
+        }
+        Ok(refs)
+    }
+
+    fn option(&mut self, name: &BStr, _value: &BStr) -> OptionResponse {
+        if name == "verbosity" {
+            OptionResponse::Ok
+        } else {
+            OptionResponse::Unsupported
+        }
+    }
+
+    fn fetch(&mut self, fetches: &[Fetch]) -> Result<(), DelegateError> {
+        self.fetched.extend_from_slice(fetches);
+        Ok(())
+    }
+
+    fn push(&mut self, specs: &[BString]) -> Result<Vec<PushStatus>, DelegateError> {
+        self.pushed.extend_from_slice(specs);
+        Ok(vec![
+            decode::push_status("ok refs/heads/main".into())?,
+            decode::push_status("error refs/heads/other non-fast forward".into())?,
+        ])
+    }
+}
+
+#[test]
+fn serve_answers_all_commands_until_empty_line() -> crate::Result {
+    let input = "capabilities\n\
+                 list\n\
+                 list for-push\n\
+                 option verbosity 2\n\
+                 option depth 1\n\
+                 fetch 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main\n\
+                 fetch 1f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/other\n\
+                 \n\
+                 push +refs/heads/main:refs/heads/main\n\
+                 push refs/heads/other:refs/heads/other\n\
+                 \n\
+                 connect git-upload-pack\n\
+                 \n\
+                 capabilities\n";
+    let mut out = Vec::new();
+    let mut delegate = Recorder::default();
+    serve(&mut input.as_bytes(), &mut out, &mut delegate)?;
+
+    assert_eq!(
+        out.as_bstr(),
+        "fetch\n*push\nrefspec refs/heads/*:refs/remote/heads/*\noption\n\n\
+         @refs/heads/main HEAD\n0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main\n\n\
+         @refs/heads/main HEAD\n0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main\n? refs/heads/unknown unchanged\n\n\
+         ok\n\
+         unsupported\n\
+         \n\
+         ok refs/heads/main\nerror refs/heads/other non-fast forward\n\n\
+         fallback\n",
+        "the empty line terminates the session, so the last command isn't answered"
+    );
+    assert_eq!(
+        delegate.fetched,
+        vec![
+            Fetch {
+                id: id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3"),
+                name: "refs/heads/main".into()
+            },
+            Fetch {
+                id: id("1f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3"),
+                name: "refs/heads/other".into()
+            }
+        ]
+    );
+    assert_eq!(
+        delegate.pushed,
+        vec![
+            BString::from("+refs/heads/main:refs/heads/main"),
+            "refs/heads/other:refs/heads/other".into()
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn serve_rejects_mixed_batches() {
+    let input = "fetch 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main\npush a:b\n\n";
+    let err = serve(&mut input.as_bytes(), &mut Vec::new(), &mut Recorder::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Only further \"fetch\" commands are allowed in a batch, got \"push a:b\""
+    );
+}
+
+#[test]
+fn client_sends_commands_and_parses_responses() -> crate::Result {
+    let responses = "fetch\n*push\nrefspec refs/heads/*:refs/remote/heads/*\n\n\
+                     :object-format sha1\n@refs/heads/main HEAD\n0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main\n\n\
+                     error invalid value\n\
+                     lock .git/objects/pack/pack-1.keep\n\n\
+                     ok refs/heads/main\nerror refs/heads/other\n\n\
+                     fallback\n";
+    let mut sent = Vec::new();
+    {
+        let mut client = Client::from_io(responses.as_bytes(), &mut sent);
+        let caps = client.capabilities()?;
+        assert_eq!(
+            caps,
+            vec![
+                Capability {
+                    name: "fetch".into(),
+                    value: None,
+                    mandatory: false
+                },
+                Capability {
+                    name: "push".into(),
+                    value: None,
+                    mandatory: true
+                },
+                Capability {
+                    name: "refspec".into(),
+                    value: Some("refs/heads/*:refs/remote/heads/*".into()),
+                    mandatory: false
+                }
+            ]
+        );
+
+        let refs = client.list(false)?;
+        assert_eq!(
+            refs,
+            vec![
+                ListedRef {
+                    value: Value::Symbolic("refs/heads/main".into()),
+                    name: "HEAD".into(),
+                    attributes: vec![]
+                },
+                ListedRef {
+                    value: Value::Object(id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3")),
+                    name: "refs/heads/main".into(),
+                    attributes: vec![]
+                }
+            ],
+            "keywords are skipped"
+        );
+
+        assert_eq!(
+            client.option("depth".into(), "-1".into())?,
+            OptionResponse::Error("invalid value".into())
+        );
+        client.fetch(&[Fetch {
+            id: id("0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3"),
+            name: "refs/heads/main".into(),
+        }])?;
+        assert_eq!(
+            client.push(&["refs/heads/main".into(), "refs/heads/other".into()])?,
+            vec![
+                PushStatus::Ok {
+                    destination: "refs/heads/main".into()
+                },
+                PushStatus::Error {
+                    destination: "refs/heads/other".into(),
+                    reason: None
+                }
+            ]
+        );
+        assert_eq!(client.connect("git-upload-pack".into())?, ConnectResponse::Fallback);
+        assert!(
+            matches!(
+                client.capabilities(),
+                Err(gix_transport::remote_helper::client::Error::UnexpectedEof)
+            ),
+            "the helper can't just close the connection"
+        );
+    }
+    assert_eq!(
+        sent.as_bstr(),
+        "capabilities\nlist\noption depth -1\n\
+         fetch 0f7bce1b3d58d2b4f2ceb1b0c3dbc3b6d0a1a2a3 refs/heads/main\n\n\
+         push refs/heads/main\npush refs/heads/other\n\n\
+         connect git-upload-pack\ncapabilities\n"
+    );
+    Ok(())
+}
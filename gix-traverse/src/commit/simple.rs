@@ -1,6 +1,6 @@
 use gix_date::SecondsSinceUnixEpoch;
 use gix_hash::ObjectId;
-use gix_hashtable::HashSet;
+use gix_hashtable::ObjectIdSet;
 use smallvec::SmallVec;
 use std::collections::VecDeque;
 
@@ -67,7 +67,7 @@ pub(super) struct State {
     next: VecDeque<ObjectId>,
     queue: gix_revwalk::PriorityQueue<SecondsSinceUnixEpoch, ObjectId>,
     buf: Vec<u8>,
-    seen: HashSet<ObjectId>,
+    seen: ObjectIdSet,
     parents_buf: Vec<u8>,
     parent_ids: SmallVec<[(ObjectId, SecondsSinceUnixEpoch); 2]>,
 }
@@ -139,11 +139,10 @@ mod init {
         }
 
         /// Change our commit parent handling mode to the given one.
+        ///
+        /// Note that the [sorting](Self::sorting()) is retained, so a cutoff date also applies when only following the first parent.
         pub fn parents(mut self, mode: Parents) -> Self {
             self.parents = mode;
-            if matches!(self.parents, Parents::First) {
-                self.queue_to_vecdeque();
-            }
             self
         }
 
@@ -249,16 +248,10 @@ mod init {
         type Item = Result<Info, Error>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            if matches!(self.parents, Parents::First) {
-                self.next_by_topology()
-            } else {
-                match self.sorting {
-                    Sorting::BreadthFirst => self.next_by_topology(),
-                    Sorting::ByCommitTimeNewestFirst => self.next_by_commit_date(None),
-                    Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds } => {
-                        self.next_by_commit_date(seconds.into())
-                    }
-                }
+            match self.sorting {
+                Sorting::BreadthFirst => self.next_by_topology(),
+                Sorting::ByCommitTimeNewestFirst => self.next_by_commit_date(None),
+                Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds } => self.next_by_commit_date(seconds.into()),
             }
         }
     }
@@ -297,13 +290,14 @@ mod init {
                     for (id, parent_commit_time) in state.parent_ids.drain(..) {
                         parents.push(id);
                         let was_inserted = state.seen.insert(id);
-                        if !(was_inserted && (self.predicate)(&id)) {
-                            continue;
+                        if was_inserted && (self.predicate)(&id) {
+                            match cutoff_older_than {
+                                Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => {}
+                                Some(_) | None => state.queue.insert(parent_commit_time, id),
+                            }
                         }
-
-                        match cutoff_older_than {
-                            Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                            Some(_) | None => state.queue.insert(parent_commit_time, id),
+                        if matches!(self.parents, Parents::First) {
+                            break;
                         }
                     }
                 }
@@ -314,18 +308,22 @@ mod init {
                             Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
                                 parents.push(id);
                                 let was_inserted = state.seen.insert(id);
-                                if !(was_inserted && (self.predicate)(&id)) {
-                                    continue;
-                                }
-
-                                let parent = self.objects.find_commit_iter(id.as_ref(), &mut state.parents_buf).ok();
-                                let parent_commit_time = parent
-                                    .and_then(|parent| parent.committer().ok().map(|committer| committer.time.seconds))
-                                    .unwrap_or_default();
+                                if was_inserted && (self.predicate)(&id) {
+                                    let parent =
+                                        self.objects.find_commit_iter(id.as_ref(), &mut state.parents_buf).ok();
+                                    let parent_commit_time = parent
+                                        .and_then(|parent| {
+                                            parent.committer().ok().map(|committer| committer.time.seconds)
+                                        })
+                                        .unwrap_or_default();
 
-                                match cutoff_older_than {
-                                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                                    Some(_) | None => state.queue.insert(parent_commit_time, id),
+                                    match cutoff_older_than {
+                                        Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => {}
+                                        Some(_) | None => state.queue.insert(parent_commit_time, id),
+                                    }
+                                }
+                                if matches!(self.parents, Parents::First) {
+                                    break;
                                 }
                             }
                             Ok(_unused_token) => break,
@@ -12,16 +12,29 @@ pub enum Error {
     Cancelled,
     #[error(transparent)]
     ObjectDecode(#[from] gix_object::decode::Error),
+    #[error("Trees are nested more than {max_depth} levels deep")]
+    DepthLimitExceeded { max_depth: usize },
 }
 
 /// The state used and potentially shared by multiple tree traversals.
 #[derive(Default, Clone)]
 pub struct State {
-    next: VecDeque<ObjectId>,
+    /// Trees to traverse along with their depth, with trees in the root tree being at depth 1.
+    next: VecDeque<(ObjectId, usize)>,
     buf: Vec<u8>,
+    max_depth: Option<usize>,
 }
 
 impl State {
+    /// Fail with [`Error::DepthLimitExceeded`] when encountering trees nested more than `max_depth` levels deep,
+    /// or traverse trees of any depth if `None`.
+    ///
+    /// Limiting the depth is useful to bound the resources used for trees from untrusted sources.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
     fn clear(&mut self) {
         self.next.clear();
         self.buf.clear();
@@ -62,6 +75,7 @@ pub(crate) mod impl_ {
         let state = state.borrow_mut();
         state.clear();
         let mut tree = root;
+        let mut depth = 0;
         loop {
             for entry in tree {
                 let entry = entry?;
@@ -72,9 +86,12 @@ pub(crate) mod impl_ {
                     match action {
                         Skip => {}
                         Continue => {
+                            if let Some(max_depth) = state.max_depth.filter(|max_depth| depth >= *max_depth) {
+                                return Err(Error::DepthLimitExceeded { max_depth });
+                            }
                             delegate.pop_path_component();
                             delegate.push_back_tracked_path_component(entry.filename);
-                            state.next.push_back(entry.oid.to_owned());
+                            state.next.push_back((entry.oid.to_owned(), depth + 1));
                         }
                         Cancel => {
                             return Err(Error::Cancelled);
@@ -89,7 +106,8 @@ pub(crate) mod impl_ {
                 delegate.pop_path_component();
             }
             match state.next.pop_front() {
-                Some(oid) => {
+                Some((oid, tree_depth)) => {
+                    depth = tree_depth;
                     delegate.pop_front_tracked_path_and_set_current();
                     tree = objects.find_tree_iter(&oid, &mut state.buf)?;
                 }
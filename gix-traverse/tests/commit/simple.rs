@@ -419,4 +419,18 @@ mod adjusted_dates {
         .with_parents(Parents::First)
         .check()
     }
+
+    #[test]
+    fn head_date_order_with_cutoff_first_parent_only() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits_with_dates.sh",
+            &["288e509293165cb5630d08f4185bdf2445bf6170"], /* m1b1 */
+            &[],
+        )
+        .with_sorting(Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
+            seconds: 978393600, // =2001-01-02 00:00:00 +0000
+        })
+        .with_parents(Parents::First)
+        .check()
+    }
 }
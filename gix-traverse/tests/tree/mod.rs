@@ -147,3 +147,35 @@ fn breadth_first_no_location() -> crate::Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn breadth_first_with_max_depth() -> crate::Result<()> {
+    let db = db()?;
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let mut commit = db
+        .find_commit_iter(&hex_to_id("85df34aa34848b8138b2b3dcff5fb5c2b734e0ce"), &mut buf)?
+        .0;
+    let tree_id = commit.tree_id().expect("a tree is available in a commit");
+    let mut recorder = tree::Recorder::default();
+    gix_traverse::tree::breadthfirst(
+        db.find_tree_iter(&tree_id, &mut buf2)?.0,
+        tree::breadthfirst::State::default().with_max_depth(Some(2)),
+        &db,
+        &mut recorder,
+    )?;
+    assert_eq!(recorder.records.len(), 12, "`f/d` is nested two levels deep");
+
+    let err = gix_traverse::tree::breadthfirst(
+        db.find_tree_iter(&tree_id, &mut buf2)?.0,
+        tree::breadthfirst::State::default().with_max_depth(Some(1)),
+        &db,
+        &mut tree::Recorder::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        tree::breadthfirst::Error::DepthLimitExceeded { max_depth: 1 }
+    ));
+    Ok(())
+}
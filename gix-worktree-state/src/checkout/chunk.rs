@@ -85,6 +85,7 @@ pub struct Options {
     pub overwrite_existing: bool,
     pub keep_going: bool,
     pub filter_process_delay: gix_filter::driver::apply::Delay,
+    pub max_buffer_size: Option<usize>,
 }
 
 impl From<&checkout::Options> for Options {
@@ -95,6 +96,7 @@ impl From<&checkout::Options> for Options {
             overwrite_existing: opts.overwrite_existing,
             keep_going: opts.keep_going,
             filter_process_delay: opts.filter_process_delay,
+            max_buffer_size: opts.max_buffer_size,
         }
     }
 }
@@ -141,6 +143,11 @@ where
             }
             entry::Outcome::Delayed(delayed) => delayed_filter_results.push(delayed),
         }
+        if let Some(max) = ctx.options.max_buffer_size {
+            if ctx.buf.capacity() > max {
+                ctx.buf = Vec::with_capacity(max);
+            }
+        }
     }
 
     Ok(Outcome {
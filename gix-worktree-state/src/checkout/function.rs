@@ -5,6 +5,9 @@ use gix_worktree::{stack, Stack};
 
 use crate::checkout::chunk;
 
+/// The maximum amount of entries a thread processes before picking up more work.
+const MAX_CHUNK_SIZE: usize = 50;
+
 /// Checkout the entire `index` into `dir`, and resolve objects found in index entries with `objects` to write their content to their
 /// respective path in `dir`.
 /// Use `files` to count each fully checked out file, and count the amount written `bytes`. If `should_interrupt` is `true`, the
@@ -57,6 +60,9 @@ where
         options.thread_limit,
         None,
     );
+    // Threads pull chunks from a shared queue as soon as they are done with their previous one, so smaller chunks
+    // keep all threads busy even if some files take much longer to decode, filter and write than others.
+    let chunk_size = chunk_size.min(MAX_CHUNK_SIZE);
 
     let mut ctx = chunk::Context {
         buf: Vec::new(),
@@ -68,6 +68,11 @@ pub struct Options {
     pub filters: gix_filter::Pipeline,
     /// Control how long-running processes may use the 'delay' capability.
     pub filter_process_delay: gix_filter::driver::apply::Delay,
+    /// If set, the amount of bytes each thread may keep allocated for decoded and filtered blobs after writing them.
+    ///
+    /// Without a limit, each thread keeps a buffer as large as the largest blob it checked out so far,
+    /// which adds up when checking out many large files with many threads.
+    pub max_buffer_size: Option<usize>,
 }
 
 /// The error returned by the [checkout()][crate::checkout()] function.
@@ -267,6 +267,21 @@ fn symlinks_become_files_if_disabled() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn bounded_buffers_do_not_affect_content() -> crate::Result {
+    for max_buffer_size in [0, 1, 1024] {
+        let mut opts = opts_from_probe();
+        opts.max_buffer_size = Some(max_buffer_size);
+        let (source_tree, destination, _index, outcome) =
+            checkout_index_in_tmp_dir(opts.clone(), "make_mixed_without_submodules", None)?;
+
+        assert_equality(&source_tree, &destination, opts.fs.symlink)?;
+        assert!(outcome.collisions.is_empty());
+        assert!(outcome.errors.is_empty());
+    }
+    Ok(())
+}
+
 #[test]
 fn symlinks_to_directories_are_usable() -> crate::Result {
     let opts = opts_from_probe();
@@ -142,7 +142,10 @@ pub mod main_worktree {
             files.show_throughput(start);
             bytes.show_throughput(start);
 
-            index.write(Default::default())?;
+            index.write(gix_index::write::Options {
+                fsync: repo.config.fsync.method_for(gix_fs::fsync::Component::Index),
+                ..Default::default()
+            })?;
             Ok((self.repo.take().expect("still present").clone(), outcome))
         }
     }
@@ -200,6 +200,7 @@ impl PrepareFetch {
         };
         let outcome = pending_pack
             .with_write_packed_refs_only(true)
+            .with_write_fetch_head(false)
             .with_reflog_message(RefLogMessage::Override {
                 message: reflog_message.clone(),
             })
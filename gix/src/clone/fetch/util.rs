@@ -108,8 +108,7 @@ pub fn update_head(
                 head_ref_name: referent.to_owned(),
                 source: err,
             })?;
-            repo.refs
-                .transaction()
+            repo.ref_transaction()?
                 .packed_refs(gix_ref::file::transaction::PackedRefs::DeletionsAndNonSymbolicUpdates(
                     Box::new(&repo.objects),
                 ))
@@ -178,6 +178,71 @@ impl Cache {
         })
     }
 
+    /// Returns the time before which unreachable objects may be pruned relative to `now`, or `None` if they never expire.
+    #[cfg(feature = "maintenance")]
+    pub(crate) fn prune_expiry_date(
+        &self,
+        now: std::time::SystemTime,
+    ) -> Result<Option<gix_date::Time>, config::time::Error> {
+        use crate::config::tree::Gc;
+        let value = self
+            .resolved
+            .string_filter(Gc::PRUNE_EXPIRE, &mut self.filter_config_section.clone())
+            .unwrap_or(Cow::Borrowed("2.weeks.ago".into()));
+        Gc::PRUNE_EXPIRE
+            .try_into_expiry_date(value, now)
+            .with_leniency(self.lenient_config)
+    }
+
+    #[cfg(feature = "maintenance")]
+    pub(crate) fn may_run_maintenance_automatically(&self) -> Result<bool, config::boolean::Error> {
+        use crate::config::tree::Maintenance;
+        const DEFAULT: bool = true;
+        self.resolved
+            .boolean_filter(Maintenance::AUTO, &mut self.filter_config_section.clone())
+            .map_or(Ok(DEFAULT), |res| {
+                Maintenance::AUTO
+                    .enrich_error(res)
+                    .with_lenient_default_value(self.lenient_config, DEFAULT)
+            })
+    }
+
+    /// Returns (is-enabled, threshold) of the given maintenance `task`.
+    #[cfg(feature = "maintenance")]
+    pub(crate) fn maintenance_task(
+        &self,
+        task: crate::maintenance::Task,
+    ) -> Result<(bool, i64), crate::maintenance::due::Error> {
+        use crate::config::tree::{Maintenance, Section};
+        let enabled = self
+            .resolved
+            .boolean_filter_by(
+                Maintenance.name(),
+                Some(task.as_str().into()),
+                Maintenance::TASK_ENABLED.name,
+                &mut self.filter_config_section.clone(),
+            )
+            .map_or(Ok(true), |res| {
+                Maintenance::TASK_ENABLED
+                    .enrich_error(res)
+                    .with_lenient_default_value(self.lenient_config, true)
+            })?;
+        let threshold = self
+            .resolved
+            .integer_filter_by(
+                Maintenance.name(),
+                Some(task.as_str().into()),
+                Maintenance::TASK_AUTO.name,
+                &mut self.filter_config_section.clone(),
+            )
+            .map_or(Ok(task.default_threshold()), |res| {
+                Maintenance::TASK_AUTO
+                    .try_into_threshold(res)
+                    .with_lenient_default_value(self.lenient_config, task.default_threshold())
+            })?;
+        Ok((enabled, threshold))
+    }
+
     /// Returns (file-timeout, pack-refs timeout)
     pub(crate) fn lock_timeout(
         &self,
@@ -297,7 +362,7 @@ impl Cache {
                 .integer_filter("checkout.workers", &mut self.filter_config_section.clone())
                 .map(|value| crate::config::tree::Checkout::WORKERS.try_from_workers(value)),
         )?;
-        let capabilities = self.fs_capabilities()?;
+        let capabilities = repo.filesystem_capabilities()?;
         let filters = {
             let mut filters =
                 gix_filter::Pipeline::new(repo.command_context()?, crate::filter::Pipeline::options(repo)?);
@@ -336,6 +401,8 @@ impl Cache {
                 }
                 config::stat_options::Error::ConfigBoolean(err) => config::checkout_options::Error::ConfigBoolean(err),
             })?,
+            // Don't let each thread hold on to the memory needed for the largest file it has seen.
+            max_buffer_size: Some(16 * 1024 * 1024),
         })
     }
 
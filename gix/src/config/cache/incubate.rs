@@ -2,7 +2,7 @@
 
 use super::{util, Error};
 use crate::config::cache::util::{ApplyLeniency, ApplyLeniencyDefaultValue};
-use crate::config::tree::{gitoxide, Core, Extensions};
+use crate::config::tree::{gitoxide, Core, Extensions, Key, Section};
 
 /// A utility to deal with the cyclic dependency between the ref store and the configuration. The ref-store needs the
 /// object hash kind, and the configuration needs the current branch name to resolve conditional includes with `onbranch`.
@@ -13,6 +13,7 @@ pub(crate) struct StageOne {
     pub is_bare: bool,
     pub lossy: Option<bool>,
     pub object_hash: gix_hash::Kind,
+    pub repository_format: crate::config::RepositoryFormat,
     pub reflog: Option<gix_ref::store::WriteReflog>,
     pub precompose_unicode: bool,
     pub protect_windows: bool,
@@ -40,29 +41,8 @@ impl StageOne {
         // Note that we assume the repo is bare by default unless we are told otherwise. This is relevant if
         // the repo doesn't have a configuration file.
         let is_bare = util::config_bool(&config, &Core::BARE, "core.bare", true, lenient)?;
-        let repo_format_version = config
-            .integer("core.repositoryFormatVersion")
-            .map(|version| Core::REPOSITORY_FORMAT_VERSION.try_into_usize(version))
-            .transpose()?
-            .unwrap_or_default();
-        let object_hash = (repo_format_version != 1)
-            .then_some(Ok(gix_hash::Kind::Sha1))
-            .or_else(|| {
-                config
-                    .string(Extensions::OBJECT_FORMAT)
-                    .map(|format| Extensions::OBJECT_FORMAT.try_into_object_format(format))
-            })
-            .transpose()?
-            .unwrap_or(gix_hash::Kind::Sha1);
-
-        let extension_worktree = util::config_bool(
-            &config,
-            &Extensions::WORKTREE_CONFIG,
-            "extensions.worktreeConfig",
-            false,
-            lenient,
-        )?;
-        if extension_worktree {
+        let repository_format = repository_format(&config, lenient)?;
+        if repository_format.worktree_config {
             let worktree_config = load_config(
                 git_dir.join("config.worktree"),
                 &mut buf,
@@ -96,7 +76,8 @@ impl StageOne {
             buf,
             is_bare,
             lossy,
-            object_hash,
+            object_hash: repository_format.object_hash,
+            repository_format,
             reflog,
             precompose_unicode,
             protect_windows,
@@ -104,6 +85,85 @@ impl StageOne {
     }
 }
 
+/// Parse the repository format version and all extensions, and fail if any of them isn't supported.
+fn repository_format(
+    config: &gix_config::File<'static>,
+    lenient: bool,
+) -> Result<crate::config::RepositoryFormat, Error> {
+    let version = config
+        .integer("core.repositoryFormatVersion")
+        .map(|version| Core::REPOSITORY_FORMAT_VERSION.try_into_usize(version))
+        .transpose()?
+        .unwrap_or_default();
+    if version > 1 {
+        return Err(Error::UnsupportedRepositoryFormatVersion { version });
+    }
+
+    let mut object_hash = gix_hash::Kind::Sha1;
+    if version == 1 {
+        if let Some(format) = config.string(Extensions::OBJECT_FORMAT) {
+            object_hash = Extensions::OBJECT_FORMAT.try_into_object_format(format)?;
+        }
+        if let Some(storage) = config.string(Extensions::REF_STORAGE) {
+            Extensions::REF_STORAGE.try_into_ref_storage(storage)?;
+        }
+        // Like `git`, refuse to work with repositories that require extensions we don't know or support, as we would
+        // otherwise silently misbehave. In lenient mode, these are merely reported.
+        let known = Extensions.keys();
+        for section in config
+            .sections_by_name(Extensions.name())
+            .into_iter()
+            .flatten()
+            .filter(|section| section.header().subsection_name().is_none())
+        {
+            for name in section.value_names() {
+                let err = if name.eq_ignore_ascii_case(Extensions::COMPAT_OBJECT_FORMAT.name().as_bytes()) {
+                    Error::UnsupportedExtension {
+                        name: name.as_ref().into(),
+                    }
+                } else if known.iter().any(|key| name.eq_ignore_ascii_case(key.name().as_bytes()))
+                    || ["noop", "noop-v1"]
+                        .iter()
+                        .any(|noop| name.eq_ignore_ascii_case(noop.as_bytes()))
+                {
+                    continue;
+                } else {
+                    Error::UnknownExtension {
+                        name: name.as_ref().into(),
+                    }
+                };
+                if lenient {
+                    gix_trace::warn!("ignoring: {err:#?}");
+                } else {
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    Ok(crate::config::RepositoryFormat {
+        version,
+        object_hash,
+        worktree_config: util::config_bool(
+            config,
+            &Extensions::WORKTREE_CONFIG,
+            "extensions.worktreeConfig",
+            false,
+            lenient,
+        )?,
+        precious_objects: util::config_bool(
+            config,
+            &Extensions::PRECIOUS_OBJECTS,
+            "extensions.preciousObjects",
+            false,
+            lenient,
+        )?,
+        partial_clone: config
+            .string(Extensions::PARTIAL_CLONE)
+            .map(std::borrow::Cow::into_owned),
+    })
+}
+
 fn load_config(
     config_path: std::path::PathBuf,
     buf: &mut Vec<u8>,
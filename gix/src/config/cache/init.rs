@@ -26,6 +26,7 @@ impl Cache {
             lossy,
             is_bare,
             object_hash,
+            repository_format,
             reflog: _,
             precompose_unicode: _,
             protect_windows: _,
@@ -146,6 +147,7 @@ impl Cache {
         use util::config_bool;
         let reflog = util::query_refupdates(&config, lenient_config)?;
         let refs_namespace = util::query_refs_namespace(&config, lenient_config)?;
+        let fsync = util::parse_fsync(&config, lenient_config)?;
         let ignore_case = config_bool(&config, &Core::IGNORE_CASE, "core.ignoreCase", false, lenient_config)?;
         let use_multi_pack_index = config_bool(
             &config,
@@ -163,6 +165,7 @@ impl Cache {
             resolved: config.into(),
             use_multi_pack_index,
             object_hash,
+            repository_format,
             #[cfg(feature = "revision")]
             object_kind_hint,
             static_pack_cache_limit_bytes,
@@ -170,6 +173,7 @@ impl Cache {
             object_cache_bytes,
             reflog,
             refs_namespace,
+            fsync,
             is_bare,
             ignore_case,
             hex_len,
@@ -186,6 +190,7 @@ impl Cache {
             url_scheme: Default::default(),
             #[cfg(feature = "blob-diff")]
             diff_algorithm: Default::default(),
+            worktree_fs_capabilities: Default::default(),
         })
     }
 
@@ -227,11 +232,13 @@ impl Cache {
         }
         let reflog = util::query_refupdates(config, self.lenient_config)?;
         let refs_namespace = util::query_refs_namespace(config, self.lenient_config)?;
+        let fsync = util::parse_fsync(config, self.lenient_config)?;
 
         self.hex_len = hex_len;
         self.ignore_case = ignore_case;
         self.reflog = reflog;
         self.refs_namespace = refs_namespace;
+        self.fsync = fsync;
 
         self.user_agent = Default::default();
         self.personas = Default::default();
@@ -305,6 +312,12 @@ impl crate::Repository {
     fn apply_changed_values(&mut self) {
         self.refs.write_reflog = util::reflog_or_default(self.config.reflog, self.work_dir().is_some());
         self.refs.namespace.clone_from(&self.config.refs_namespace);
+        self.refs.fsync = self.config.fsync.method_for(gix_fs::fsync::Component::Reference);
+        self.refs.ignore_case = self.config.ignore_case;
+        if let Some(reftable) = self.reftable.as_mut() {
+            reftable.write_reflog = self.refs.write_reflog;
+            reftable.namespace.clone_from(&self.refs.namespace);
+        }
     }
 }
 
@@ -507,6 +520,14 @@ fn apply_environment_overrides(
                     let key = &gitoxide::Objects::CACHE_LIMIT;
                     (env(key), key.name)
                 },
+                {
+                    let key = &gitoxide::Objects::DIRECTORY;
+                    (env(key), key.name)
+                },
+                {
+                    let key = &gitoxide::Objects::ALTERNATES;
+                    (env(key), key.name)
+                },
             ],
         ),
         (
@@ -78,6 +78,31 @@ pub(crate) fn reflog_or_default(
     })
 }
 
+pub(crate) fn parse_fsync(config: &gix_config::File<'static>, lenient: bool) -> Result<gix_fs::fsync::Config, Error> {
+    let method = config
+        .string("core.fsyncMethod")
+        .map(|method| Core::FSYNC_METHOD.try_into_fsync_method(method))
+        .transpose()
+        .with_leniency(lenient)?
+        .unwrap_or_default();
+    let mut components = config
+        .string("core.fsync")
+        .map(|components| Core::FSYNC.try_into_components(components))
+        .transpose()
+        .with_leniency(lenient)?
+        .unwrap_or_default();
+    if config_bool(
+        config,
+        &Core::FSYNC_OBJECT_FILES,
+        "core.fsyncObjectFiles",
+        false,
+        lenient,
+    )? {
+        components.insert(gix_fs::fsync::Component::LooseObject);
+    }
+    Ok(gix_fs::fsync::Config { method, components })
+}
+
 /// Return `(pack_cache_bytes, object_cache_bytes)` as parsed from gix-config
 pub(crate) fn parse_object_caches(
     config: &gix_config::File<'static>,
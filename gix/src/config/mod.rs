@@ -67,6 +67,26 @@ pub mod set_value {
     }
 }
 
+/// The format of a repository as configured by `core.repositoryFormatVersion` and the `extensions` section,
+/// which describes the capabilities required to operate on the repository.
+///
+/// Repositories with unknown extensions or unsupported values for known extensions can't be opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryFormat {
+    /// The value of `core.repositoryFormatVersion`, or `0` if unset.
+    ///
+    /// Only in version `1` extensions other than `worktreeConfig`, `preciousObjects` and `partialClone` are used.
+    pub version: usize,
+    /// The kind of hash used for objects as configured by `extensions.objectFormat`.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, `extensions.worktreeConfig` causes the `config.worktree` file of each worktree to be loaded.
+    pub worktree_config: bool,
+    /// If `true`, `extensions.preciousObjects` forbids deleting any object, even if it's unreachable.
+    pub precious_objects: bool,
+    /// The name of the remote to fetch missing objects from as set by `extensions.partialClone`, if this is a partial clone.
+    pub partial_clone: Option<BString>,
+}
+
 /// The error returned when failing to initialize the repository configuration.
 ///
 /// This configuration is on the critical path when opening a repository.
@@ -83,6 +103,12 @@ pub enum Error {
     RefsNamespace(#[from] refs_namespace::Error),
     #[error("Cannot handle objects formatted as {:?}", .name)]
     UnsupportedObjectFormat { name: BString },
+    #[error("Repository format version {version} is unsupported, only versions 0 and 1 are known")]
+    UnsupportedRepositoryFormatVersion { version: usize },
+    #[error("The repository requires the unknown extension 'extensions.{name}'")]
+    UnknownExtension { name: BString },
+    #[error("The repository requires the extension 'extensions.{name}' which isn't supported")]
+    UnsupportedExtension { name: BString },
     #[error(transparent)]
     CoreAbbrev(#[from] abbrev::Error),
     #[error("Could not read configuration file at \"{}\"", path.display())]
@@ -563,12 +589,16 @@ pub(crate) struct Cache {
     pub is_bare: bool,
     /// The type of hash to use.
     pub object_hash: gix_hash::Kind,
+    /// The format version and extensions of the repository as seen when opening it.
+    pub repository_format: RepositoryFormat,
     /// If true, multi-pack indices, whether present or not, may be used by the object database.
     pub use_multi_pack_index: bool,
     /// The representation of `core.logallrefupdates`, or `None` if the variable wasn't set.
     pub reflog: Option<gix_ref::store::WriteReflog>,
     /// The representation of `gitoxide.core.refsNamespace`, or `None` if the variable wasn't set.
     pub refs_namespace: Option<gix_ref::Namespace>,
+    /// Which files to sync and how, as configured by `core.fsync`, `core.fsyncMethod` and `core.fsyncObjectFiles`.
+    pub fsync: gix_fs::fsync::Config,
     /// The configured user agent for presentation to servers.
     pub(crate) user_agent: OnceCell<String>,
     /// identities for later use, lazy initialization.
@@ -584,6 +614,8 @@ pub(crate) struct Cache {
     /// The algorithm to use when diffing blobs
     #[cfg(feature = "blob-diff")]
     pub(crate) diff_algorithm: OnceCell<gix_diff::blob::Algorithm>,
+    /// The capabilities of the filesystem the worktree is located on, probed once on first use.
+    pub(crate) worktree_fs_capabilities: OnceCell<gix_fs::Capabilities>,
     /// The amount of bytes to use for a memory backed delta pack cache. If `Some(0)`, no cache is used, if `None`
     /// a standard cache is used which costs near to nothing and always pays for itself.
     pub(crate) pack_cache_bytes: Option<usize>,
@@ -37,6 +37,8 @@ pub(crate) mod root {
         pub const EXTENSIONS: sections::Extensions = sections::Extensions;
         /// The `fetch` section.
         pub const FETCH: sections::Fetch = sections::Fetch;
+        /// The `gc` section.
+        pub const GC: sections::Gc = sections::Gc;
         /// The `gitoxide` section.
         pub const GITOXIDE: sections::Gitoxide = sections::Gitoxide;
         /// The `http` section.
@@ -47,12 +49,20 @@ pub(crate) mod root {
         pub const INIT: sections::Init = sections::Init;
         /// The `mailmap` section.
         pub const MAILMAP: sections::Mailmap = sections::Mailmap;
+        /// The `maintenance` section.
+        pub const MAINTENANCE: sections::Maintenance = sections::Maintenance;
+        /// The `merge` section.
+        pub const MERGE: sections::Merge = sections::Merge;
         /// The `pack` section.
         pub const PACK: sections::Pack = sections::Pack;
         /// The `protocol` section.
         pub const PROTOCOL: sections::Protocol = sections::Protocol;
+        /// The `pull` section.
+        pub const PULL: sections::Pull = sections::Pull;
         /// The `push` section.
         pub const PUSH: sections::Push = sections::Push;
+        /// The `rebase` section.
+        pub const REBASE: sections::Rebase = sections::Rebase;
         /// The `remote` section.
         pub const REMOTE: sections::Remote = sections::Remote;
         /// The `safe` section.
@@ -81,14 +91,19 @@ pub(crate) mod root {
                 &Self::DIFF,
                 &Self::EXTENSIONS,
                 &Self::FETCH,
+                &Self::GC,
                 &Self::GITOXIDE,
                 &Self::HTTP,
                 &Self::INDEX,
                 &Self::INIT,
                 &Self::MAILMAP,
+                &Self::MAINTENANCE,
+                &Self::MERGE,
                 &Self::PACK,
                 &Self::PROTOCOL,
+                &Self::PULL,
                 &Self::PUSH,
+                &Self::REBASE,
                 &Self::REMOTE,
                 &Self::SAFE,
                 &Self::SSH,
@@ -103,9 +118,9 @@ pub(crate) mod root {
 
 mod sections;
 pub use sections::{
-    branch, checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, ssh, Author,
-    Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gitoxide, Http, Index, Init, Mailmap,
-    Pack, Protocol, Push, Remote, Safe, Ssh, Url, User,
+    branch, checkout, core, credential, extensions, fetch, gc, gitoxide, http, index, maintenance, protocol, pull,
+    push, remote, ssh, Author, Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gc, Gitoxide,
+    Http, Index, Init, Mailmap, Maintenance, Merge, Pack, Protocol, Pull, Push, Rebase, Remote, Safe, Ssh, Url, User,
 };
 #[cfg(feature = "blob-diff")]
 pub use sections::{diff, Diff};
@@ -10,6 +10,10 @@ impl Branch {
     pub const PUSH_REMOTE: keys::RemoteName =
         keys::RemoteName::new_remote_name("pushRemote", &crate::config::Tree::BRANCH)
             .with_subsection_requirement(NAME_PARAMETER);
+    /// The `branch.<name>.rebase` key.
+    pub const REBASE: super::pull::Rebase =
+        super::pull::Rebase::new_with_validate("rebase", &crate::config::Tree::BRANCH, super::pull::validate::Rebase)
+            .with_subsection_requirement(NAME_PARAMETER);
     /// The `branch.<name>.remote` key.
     pub const REMOTE: keys::RemoteName = keys::RemoteName::new_remote_name("remote", &crate::config::Tree::BRANCH)
         .with_subsection_requirement(NAME_PARAMETER);
@@ -21,7 +25,7 @@ impl Section for Branch {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::MERGE, &Self::PUSH_REMOTE, &Self::REMOTE]
+        &[&Self::MERGE, &Self::PUSH_REMOTE, &Self::REBASE, &Self::REMOTE]
     }
 }
 
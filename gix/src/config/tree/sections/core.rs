@@ -65,6 +65,8 @@ impl Core {
         .with_note("fallback is 'SSH_ASKPASS'");
     /// The `core.excludesFile` key.
     pub const EXCLUDES_FILE: keys::Path = keys::Path::new_path("excludesFile", &config::Tree::CORE);
+    /// The `core.hooksPath` key.
+    pub const HOOKS_PATH: keys::Path = keys::Path::new_path("hooksPath", &config::Tree::CORE);
     /// The `core.attributesFile` key.
     pub const ATTRIBUTES_FILE: keys::Path =
         keys::Path::new_path("attributesFile", &config::Tree::CORE)
@@ -77,6 +79,19 @@ impl Core {
         .with_environment_override("GIT_NO_REPLACE_OBJECTS");
     /// The `core.commitGraph` key.
     pub const COMMIT_GRAPH: keys::Boolean = keys::Boolean::new_boolean("commitGraph", &config::Tree::CORE);
+    /// The `core.sparseCheckout` key.
+    pub const SPARSE_CHECKOUT: keys::Boolean = keys::Boolean::new_boolean("sparseCheckout", &config::Tree::CORE);
+    /// The `core.sparseCheckoutCone` key.
+    pub const SPARSE_CHECKOUT_CONE: keys::Boolean =
+        keys::Boolean::new_boolean("sparseCheckoutCone", &config::Tree::CORE);
+    /// The `core.fsync` key.
+    pub const FSYNC: Fsync = Fsync::new_with_validate("fsync", &config::Tree::CORE, validate::Fsync);
+    /// The `core.fsyncMethod` key.
+    pub const FSYNC_METHOD: FsyncMethod =
+        FsyncMethod::new_with_validate("fsyncMethod", &config::Tree::CORE, validate::FsyncMethod);
+    /// The `core.fsyncObjectFiles` key.
+    pub const FSYNC_OBJECT_FILES: keys::Boolean = keys::Boolean::new_boolean("fsyncObjectFiles", &config::Tree::CORE)
+        .with_note("deprecated in favor of the 'loose-object' component of core.fsync");
     /// The `core.safecrlf` key.
     #[cfg(feature = "attributes")]
     pub const SAFE_CRLF: SafeCrlf = SafeCrlf::new_with_validate("safecrlf", &config::Tree::CORE, validate::SafeCrlf);
@@ -125,9 +140,15 @@ impl Section for Core {
             &Self::ASKPASS,
             &Self::EXCLUDES_FILE,
             &Self::ATTRIBUTES_FILE,
+            &Self::HOOKS_PATH,
             &Self::SSH_COMMAND,
             &Self::USE_REPLACE_REFS,
             &Self::COMMIT_GRAPH,
+            &Self::SPARSE_CHECKOUT,
+            &Self::SPARSE_CHECKOUT_CONE,
+            &Self::FSYNC,
+            &Self::FSYNC_METHOD,
+            &Self::FSYNC_OBJECT_FILES,
             #[cfg(feature = "attributes")]
             &Self::SAFE_CRLF,
             #[cfg(feature = "attributes")]
@@ -152,6 +173,12 @@ pub type LogAllRefUpdates = keys::Any<validate::LogAllRefUpdates>;
 /// The `core.disambiguate` key.
 pub type Disambiguate = keys::Any<validate::Disambiguate>;
 
+/// The `core.fsync` key.
+pub type Fsync = keys::Any<validate::Fsync>;
+
+/// The `core.fsyncMethod` key.
+pub type FsyncMethod = keys::Any<validate::FsyncMethod>;
+
 #[cfg(feature = "attributes")]
 mod filter {
     use super::validate;
@@ -357,6 +384,99 @@ mod log_all_ref_updates {
     }
 }
 
+mod fsync {
+    use std::borrow::Cow;
+
+    use gix_fs::fsync::{Component, Components, Method};
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::core::{Fsync, FsyncMethod},
+    };
+
+    impl Fsync {
+        /// Convert `value`, a comma-separated list of components, into the set of components whose files should be synced.
+        ///
+        /// Starting with the default components, `none` removes all components, and each component or group of components
+        /// is added, or removed if it is prefixed with `-`.
+        ///
+        /// ### Deviation
+        ///
+        /// git warns about unknown components and ignores them, we will fail instead.
+        pub fn try_into_components(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<Components, config::key::GenericErrorWithValue> {
+            let mut current = Components::default();
+            let mut added = Components::NONE;
+            let mut removed = Components::NONE;
+            for name in value
+                .split(|b| *b == b',')
+                .map(ByteSlice::trim)
+                .filter(|n| !n.is_empty())
+            {
+                if name == b"none" {
+                    current = Components::NONE;
+                    continue;
+                }
+                let (name, set) = match name.strip_prefix(b"-") {
+                    Some(name) => (name, &mut removed),
+                    None => (name, &mut added),
+                };
+                let components: &[Component] = match name {
+                    b"loose-object" => &[Component::LooseObject],
+                    b"pack" => &[Component::Pack],
+                    b"pack-metadata" => &[Component::PackMetadata],
+                    b"commit-graph" => &[Component::CommitGraph],
+                    b"index" => &[Component::Index],
+                    b"reference" => &[Component::Reference],
+                    b"objects" => &[Component::LooseObject, Component::Pack],
+                    b"derived-metadata" => &[Component::PackMetadata, Component::CommitGraph],
+                    b"committed" => &[Component::LooseObject, Component::Pack, Component::Reference],
+                    b"added" => &[
+                        Component::LooseObject,
+                        Component::Pack,
+                        Component::Reference,
+                        Component::Index,
+                    ],
+                    b"all" => &Component::ALL,
+                    _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+                };
+                for component in components {
+                    set.insert(*component);
+                }
+            }
+            for component in removed.iter() {
+                current.remove(component);
+            }
+            for component in added.iter() {
+                current.insert(component);
+            }
+            Ok(current)
+        }
+    }
+
+    impl FsyncMethod {
+        /// Convert `value` into the method to use for syncing files.
+        ///
+        /// ### Deviation
+        ///
+        /// git warns about unknown methods and ignores them, we will fail instead.
+        pub fn try_into_fsync_method(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<Method, config::key::GenericErrorWithValue> {
+            Ok(match value.as_ref().as_bytes() {
+                b"fsync" => Method::Fsync,
+                b"writeout-only" => Method::WriteoutOnly,
+                b"batch" => Method::Batch,
+                _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            })
+        }
+    }
+}
+
 mod check_stat {
     use std::borrow::Cow;
 
@@ -472,6 +592,22 @@ mod validate {
         }
     }
 
+    pub struct Fsync;
+    impl keys::Validate for Fsync {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::FSYNC.try_into_components(value.into())?;
+            Ok(())
+        }
+    }
+
+    pub struct FsyncMethod;
+    impl keys::Validate for FsyncMethod {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::FSYNC_METHOD.try_into_fsync_method(value.into())?;
+            Ok(())
+        }
+    }
+
     pub struct CheckStat;
     impl keys::Validate for CheckStat {
         fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
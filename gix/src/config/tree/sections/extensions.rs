@@ -6,16 +6,32 @@ use crate::{
 impl Extensions {
     /// The `extensions.worktreeConfig` key.
     pub const WORKTREE_CONFIG: keys::Boolean = keys::Boolean::new_boolean("worktreeConfig", &config::Tree::EXTENSIONS);
+    /// The `extensions.preciousObjects` key.
+    pub const PRECIOUS_OBJECTS: keys::Boolean =
+        keys::Boolean::new_boolean("preciousObjects", &config::Tree::EXTENSIONS);
+    /// The `extensions.partialClone` key.
+    pub const PARTIAL_CLONE: keys::RemoteName =
+        keys::RemoteName::new_remote_name("partialClone", &config::Tree::EXTENSIONS);
+    /// The `extensions.refStorage` key.
+    pub const REF_STORAGE: RefStorage =
+        RefStorage::new_with_validate("refStorage", &config::Tree::EXTENSIONS, validate::RefStorage)
+            .with_note("Linked worktrees of repositories using the 'reftable' backend can't be opened yet");
     /// The `extensions.objectFormat` key.
     pub const OBJECT_FORMAT: ObjectFormat =
         ObjectFormat::new_with_validate("objectFormat", &config::Tree::EXTENSIONS, validate::ObjectFormat).with_note(
             "Support for SHA256 is prepared but not fully implemented yet. For now we abort when encountered",
         );
+    /// The `extensions.compatObjectFormat` key.
+    pub const COMPAT_OBJECT_FORMAT: keys::Any = keys::Any::new("compatObjectFormat", &config::Tree::EXTENSIONS)
+        .with_note("Maintaining objects in a second hash format isn't supported, so such repositories can't be opened");
 }
 
-/// The `core.checkStat` key.
+/// The `extensions.objectFormat` key.
 pub type ObjectFormat = keys::Any<validate::ObjectFormat>;
 
+/// The `extensions.refStorage` key.
+pub type RefStorage = keys::Any<validate::RefStorage>;
+
 mod object_format {
     use std::borrow::Cow;
 
@@ -35,13 +51,40 @@ mod object_format {
     }
 }
 
+mod ref_storage {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::sections::extensions::RefStorage};
+
+    impl RefStorage {
+        /// Assure that `value` names the `files` or the `reftable` reference backend, the ones we support.
+        pub fn try_into_ref_storage(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<(), config::key::GenericErrorWithValue> {
+            if value.as_ref().eq_ignore_ascii_case(b"files") || value.as_ref().eq_ignore_ascii_case(b"reftable") {
+                Ok(())
+            } else {
+                Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
+            }
+        }
+    }
+}
+
 impl Section for Extensions {
     fn name(&self) -> &str {
         "extensions"
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::OBJECT_FORMAT, &Self::WORKTREE_CONFIG]
+        &[
+            &Self::OBJECT_FORMAT,
+            &Self::COMPAT_OBJECT_FORMAT,
+            &Self::WORKTREE_CONFIG,
+            &Self::PRECIOUS_OBJECTS,
+            &Self::PARTIAL_CLONE,
+            &Self::REF_STORAGE,
+        ]
     }
 }
 
@@ -56,4 +99,13 @@ mod validate {
             Ok(())
         }
     }
+
+    pub struct RefStorage;
+
+    impl keys::Validate for RefStorage {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Extensions::REF_STORAGE.try_into_ref_storage(value.into())?;
+            Ok(())
+        }
+    }
 }
@@ -10,10 +10,16 @@ impl Fetch {
         &config::Tree::FETCH,
         validate::NegotiationAlgorithm,
     );
+    /// The `fetch.prune` key.
+    pub const PRUNE: keys::Boolean = keys::Boolean::new_boolean("prune", &config::Tree::FETCH);
+    /// The `fetch.pruneTags` key.
+    pub const PRUNE_TAGS: keys::Boolean = keys::Boolean::new_boolean("pruneTags", &config::Tree::FETCH);
     /// The `fetch.recurseSubmodules` key.
     #[cfg(feature = "attributes")]
     pub const RECURSE_SUBMODULES: RecurseSubmodules =
         RecurseSubmodules::new_with_validate("recurseSubmodules", &config::Tree::FETCH, validate::RecurseSubmodules);
+    /// The `fetch.writeFetchHead` key.
+    pub const WRITE_FETCH_HEAD: keys::Boolean = keys::Boolean::new_boolean("writeFetchHead", &config::Tree::FETCH);
 }
 
 impl Section for Fetch {
@@ -24,8 +30,11 @@ impl Section for Fetch {
     fn keys(&self) -> &[&dyn Key] {
         &[
             &Self::NEGOTIATION_ALGORITHM,
+            &Self::PRUNE,
+            &Self::PRUNE_TAGS,
             #[cfg(feature = "attributes")]
             &Self::RECURSE_SUBMODULES,
+            &Self::WRITE_FETCH_HEAD,
         ]
     }
 }
@@ -0,0 +1,75 @@
+use crate::{
+    config,
+    config::tree::{keys, Gc, Key, Section},
+};
+
+impl Gc {
+    /// The `gc.pruneExpire` key.
+    pub const PRUNE_EXPIRE: PruneExpire =
+        PruneExpire::new_with_validate("pruneExpire", &config::Tree::GC, validate::PruneExpire);
+}
+
+impl Section for Gc {
+    fn name(&self) -> &str {
+        "gc"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::PRUNE_EXPIRE]
+    }
+}
+
+/// The `gc.pruneExpire` key.
+pub type PruneExpire = keys::Any<validate::PruneExpire>;
+
+mod prune_expire {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::gc::PruneExpire};
+
+    impl PruneExpire {
+        /// Interpret `value` as the time before which unreachable objects may be pruned, with `now` as reference for relative dates.
+        ///
+        /// `never` yields `None` to indicate that nothing may be pruned, while `now` means that there is no grace period.
+        /// Like `git`, periods may be separated by dots, as in the default value `2.weeks.ago`.
+        pub fn try_into_expiry_date(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: std::time::SystemTime,
+        ) -> Result<Option<gix_date::Time>, config::time::Error> {
+            parse(value.as_ref(), now).map_err(|err| config::time::Error::from(self).with_source(err))
+        }
+    }
+
+    pub(super) fn parse(
+        value: &BStr,
+        now: std::time::SystemTime,
+    ) -> Result<Option<gix_date::Time>, gix_date::parse::Error> {
+        let value = value.to_string();
+        Ok(match value.as_str() {
+            "never" | "false" => None,
+            "now" | "all" => Some(gix_date::Time::new(
+                now.duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs() as gix_date::SecondsSinceUnixEpoch),
+                0,
+            )),
+            _ => Some(
+                gix_date::parse(&value, Some(now))
+                    .or_else(|err| gix_date::parse(&value.replace('.', " "), Some(now)).map_err(|_| err))?,
+            ),
+        })
+    }
+}
+
+///
+pub mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct PruneExpire;
+    impl keys::Validate for PruneExpire {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::prune_expire::parse(value, std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
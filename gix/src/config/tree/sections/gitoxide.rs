@@ -426,11 +426,24 @@ mod subsections {
             keys::UnsignedInteger::new_unsigned_integer("cacheLimit", &Gitoxide::OBJECTS)
                 .with_note("If unset or 0, there is no object cache")
                 .with_environment_override("GIX_OBJECT_CACHE_MEMORY");
+        /// The `gitoxide.objects.resolveMemoryLimit` key.
+        pub const RESOLVE_MEMORY_LIMIT: keys::UnsignedInteger =
+            keys::UnsignedInteger::new_unsigned_integer("resolveMemoryLimit", &Gitoxide::OBJECTS).with_note(
+                "The amount of bytes objects may use while resolving the deltas of received packs. If unset or 0, there is no limit",
+            );
         /// The `gitoxide.objects.noReplace` key.
         pub const NO_REPLACE: keys::Boolean = keys::Boolean::new_boolean("noReplace", &Gitoxide::OBJECTS);
         /// The `gitoxide.objects.replaceRefBase` key.
         pub const REPLACE_REF_BASE: keys::Any =
             keys::Any::new("replaceRefBase", &Gitoxide::OBJECTS).with_environment_override("GIT_REPLACE_REF_BASE");
+        /// The `gitoxide.objects.directory` key.
+        pub const DIRECTORY: keys::Path = keys::Path::new_path("directory", &Gitoxide::OBJECTS)
+            .with_environment_override("GIT_OBJECT_DIRECTORY")
+            .with_note("relative paths are relative to the current working directory");
+        /// The `gitoxide.objects.alternates` key.
+        pub const ALTERNATES: keys::Any = keys::Any::new("alternates", &Gitoxide::OBJECTS)
+            .with_environment_override("GIT_ALTERNATE_OBJECT_DIRECTORIES")
+            .with_note("a list of paths separated by ':' (or ';' on Windows), each of which may be quoted");
     }
 
     impl Section for Objects {
@@ -439,7 +452,13 @@ mod subsections {
         }
 
         fn keys(&self) -> &[&dyn Key] {
-            &[&Self::CACHE_LIMIT, &Self::REPLACE_REF_BASE]
+            &[
+                &Self::CACHE_LIMIT,
+                &Self::RESOLVE_MEMORY_LIMIT,
+                &Self::REPLACE_REF_BASE,
+                &Self::DIRECTORY,
+                &Self::ALTERNATES,
+            ]
         }
 
         fn parent(&self) -> Option<&dyn Section> {
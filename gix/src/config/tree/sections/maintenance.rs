@@ -0,0 +1,65 @@
+use crate::{
+    config,
+    config::tree::{keys, traits::SubSectionRequirement, Key, Maintenance, Section},
+};
+
+const TASK_PARAMETER: Option<SubSectionRequirement> = Some(SubSectionRequirement::Parameter("task"));
+
+impl Maintenance {
+    /// The `maintenance.auto` key.
+    pub const AUTO: keys::Boolean = keys::Boolean::new_boolean("auto", &config::Tree::MAINTENANCE);
+    /// The `maintenance.<task>.enabled` key.
+    pub const TASK_ENABLED: keys::Boolean = keys::Boolean::new_boolean("enabled", &config::Tree::MAINTENANCE)
+        .with_subsection_requirement(TASK_PARAMETER)
+        .with_deviation(
+            "Tasks are enabled unless set to false, as only tasks that git runs incrementally are implemented",
+        );
+    /// The `maintenance.<task>.auto` key.
+    pub const TASK_AUTO: TaskAuto = TaskAuto::new_with_validate("auto", &config::Tree::MAINTENANCE, validate::TaskAuto)
+        .with_subsection_requirement(TASK_PARAMETER);
+}
+
+impl Section for Maintenance {
+    fn name(&self) -> &str {
+        "maintenance"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::AUTO, &Self::TASK_ENABLED, &Self::TASK_AUTO]
+    }
+}
+
+/// The `maintenance.<task>.auto` key.
+pub type TaskAuto = keys::Any<validate::TaskAuto>;
+
+mod task_auto {
+    use crate::{config, config::tree::sections::maintenance::TaskAuto};
+
+    impl TaskAuto {
+        /// Try to interpret an integer value as threshold at which a maintenance task should run automatically.
+        ///
+        /// `0` disables the automatic run of a task, negative values cause it to always run, and positive values are
+        /// the threshold to reach for the task to run.
+        pub fn try_into_threshold(
+            &'static self,
+            value: Result<i64, gix_config::value::Error>,
+        ) -> Result<i64, config::key::GenericError> {
+            value.map_err(|err| config::key::GenericError::from(self).with_source(err))
+        }
+    }
+}
+
+///
+pub mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct TaskAuto;
+    impl keys::Validate for TaskAuto {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            gix_config::Integer::try_from(value)?
+                .to_decimal()
+                .ok_or_else(|| format!("integer {value} cannot be represented as `i64`"))?;
+            Ok(())
+        }
+    }
+}
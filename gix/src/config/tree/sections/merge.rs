@@ -0,0 +1,19 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Merge, Section},
+};
+
+impl Merge {
+    /// The `merge.autoStash` key.
+    pub const AUTO_STASH: keys::Boolean = keys::Boolean::new_boolean("autoStash", &config::Tree::MERGE);
+}
+
+impl Section for Merge {
+    fn name(&self) -> &str {
+        "merge"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::AUTO_STASH]
+    }
+}
@@ -52,6 +52,11 @@ pub mod extensions;
 pub struct Fetch;
 pub mod fetch;
 
+/// The `gc` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Gc;
+pub mod gc;
+
 /// The `gitoxide` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Gitoxide;
@@ -76,6 +81,16 @@ mod init;
 pub struct Mailmap;
 mod mailmap;
 
+/// The `maintenance` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Maintenance;
+pub mod maintenance;
+
+/// The `merge` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Merge;
+mod merge;
+
 /// The `pack` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Pack;
@@ -86,11 +101,21 @@ pub mod pack;
 pub struct Protocol;
 pub mod protocol;
 
+/// The `pull` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Pull;
+pub mod pull;
+
 /// The `push` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Push;
 pub mod push;
 
+/// The `rebase` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Rebase;
+mod rebase;
+
 /// The `remote` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Remote;
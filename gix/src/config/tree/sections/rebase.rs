@@ -0,0 +1,19 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Rebase, Section},
+};
+
+impl Rebase {
+    /// The `rebase.autoStash` key.
+    pub const AUTO_STASH: keys::Boolean = keys::Boolean::new_boolean("autoStash", &config::Tree::REBASE);
+}
+
+impl Section for Rebase {
+    fn name(&self) -> &str {
+        "rebase"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::AUTO_STASH]
+    }
+}
@@ -62,6 +62,19 @@ pub mod pipeline {
             Convert(#[from] gix_filter::pipeline::convert::to_worktree::Error),
         }
     }
+
+    ///
+    pub mod audit_eol {
+        /// The error returned by [Pipeline::audit_eol()][crate::filter::Pipeline::audit_eol()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("Failed to prime attributes to the path at which the data resides")]
+            WorktreeCacheAtPath(#[from] std::io::Error),
+            #[error(transparent)]
+            Audit(#[from] gix_filter::pipeline::audit_eol::Error),
+        }
+    }
 }
 
 /// A git pipeline for transforming data *to-git* and *to-worktree*, based
@@ -186,6 +199,26 @@ impl<'repo> Pipeline<'repo> {
         )?)
     }
 
+    /// Determine which changes to line endings `git` would make to the file at `rela_path` the next time it's added or checked out,
+    /// given the object stored for it in the index as `index_object` and its worktree content as `worktree`, if available.
+    /// The outcome also tells which attributes configure the end-of-line conversion, and where they are defined.
+    pub fn audit_eol(
+        &mut self,
+        rela_path: &BStr,
+        index_object: Option<&[u8]>,
+        worktree: Option<&[u8]>,
+    ) -> Result<gix_filter::pipeline::audit_eol::Outcome, pipeline::audit_eol::Error> {
+        let entry = self.cache.at_entry(rela_path, None, &self.repo.objects)?;
+        Ok(self.inner.audit_eol(
+            rela_path,
+            &mut |_, attrs| {
+                entry.matching_attributes(attrs);
+            },
+            index_object,
+            worktree,
+        )?)
+    }
+
     /// Retrieve the static context that is made available to the process filters.
     ///
     /// The context set here is relevant for the [`convert_to_git()`][Self::convert_to_git()] and
@@ -197,6 +197,8 @@ pub(crate) mod util;
 ///
 pub mod progress;
 ///
+pub mod pull;
+///
 pub mod push;
 
 ///
@@ -309,6 +311,19 @@ pub mod worktree;
 
 pub mod revision;
 
+#[cfg(feature = "tree-editor")]
+pub mod rewrite;
+
+///
+#[cfg(feature = "merge")]
+pub mod merge;
+
+#[cfg(feature = "merge")]
+pub mod rebase;
+
+#[cfg(all(feature = "merge", feature = "status"))]
+pub mod stash;
+
 #[cfg(feature = "attributes")]
 pub mod filter;
 
@@ -318,6 +333,10 @@ pub mod remote;
 ///
 pub mod init;
 
+///
+#[cfg(feature = "maintenance")]
+pub mod maintenance;
+
 /// Not to be confused with 'status'.
 pub mod state;
 
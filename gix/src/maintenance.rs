@@ -0,0 +1,204 @@
+/// A task to keep the repository fast as it grows, as run by `git maintenance run --task=<name>`.
+///
+/// Only tasks that `git` runs incrementally are implemented, and each one of them is enabled unless
+/// `maintenance.<task>.enabled` is set to `false`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Task {
+    /// Pack all loose objects into a new pack and delete them, like the `loose-objects` task.
+    ///
+    /// It's due once there are `maintenance.loose-objects.auto` loose objects, 100 by default.
+    LooseObjects,
+    /// Write a multi-pack-index that includes all packs, like the `incremental-repack` task.
+    ///
+    /// It's due once there are `maintenance.incremental-repack.auto` packs which aren't part of the multi-pack-index,
+    /// 10 by default.
+    ///
+    /// ### Deviation
+    ///
+    /// `git` also repacks small packs into a bigger one and deletes packs that are unused by the multi-pack-index, which isn't done here.
+    IncrementalRepack,
+    /// Add all commits reachable from references to the split commit-graph, like the `commit-graph` task.
+    ///
+    /// Only commits which aren't yet in the chain of commit-graph files are written into a new layer, which is merged
+    /// with smaller layers below it just like `git` does, to keep this cheap even in large repositories.
+    ///
+    /// It's due once there are `maintenance.commit-graph.auto` reachable commits which aren't part of the commit-graph,
+    /// 100 by default, but never in shallow repositories or if `core.commitGraph` is `false`.
+    CommitGraph,
+}
+
+impl Task {
+    /// All tasks, in the order in which they are run by [`Repository::maintenance_auto()`](crate::Repository::maintenance_auto()).
+    pub const ALL: &'static [Task] = &[Task::LooseObjects, Task::IncrementalRepack, Task::CommitGraph];
+
+    /// Return the name of the task as used in `git` configuration, like `loose-objects`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Task::LooseObjects => "loose-objects",
+            Task::IncrementalRepack => "incremental-repack",
+            Task::CommitGraph => "commit-graph",
+        }
+    }
+
+    /// Return `true` if the task deletes objects from the object database, even if they remain accessible otherwise.
+    ///
+    /// Such tasks never run in repositories with `extensions.preciousObjects` set.
+    pub fn deletes_objects(&self) -> bool {
+        matches!(self, Task::LooseObjects)
+    }
+
+    /// The amount of objects, packs or commits at which the task is due if `maintenance.<task>.auto` isn't set.
+    pub(crate) fn default_threshold(&self) -> i64 {
+        match self {
+            Task::LooseObjects | Task::CommitGraph => 100,
+            Task::IncrementalRepack => 10,
+        }
+    }
+}
+
+///
+pub mod due {
+    /// The error returned by [`Repository::maintenance_tasks_due()`](crate::Repository::maintenance_tasks_due()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        ConfigThreshold(#[from] crate::config::key::GenericError),
+        #[error("Could not list loose objects")]
+        IterateLooseObjects(#[from] gix_features::fs::walkdir::Error),
+        #[error("Could not list pack indices")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        ReachableCommits(#[from] super::reachable::Error),
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+    }
+}
+
+///
+pub mod run {
+    /// The error returned by [`Repository::maintenance_run()`](crate::Repository::maintenance_run()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not list loose objects")]
+        IterateLooseObjects(#[from] gix_features::fs::walkdir::Error),
+        #[error(transparent)]
+        CountObjects(#[from] gix_pack::data::output::count::objects::Error),
+        #[error(transparent)]
+        WritePackData(
+            #[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>,
+        ),
+        #[error(transparent)]
+        WritePack(#[from] gix_pack::bundle::write::Error),
+        #[error(transparent)]
+        WriteMultiIndex(#[from] gix_pack::multi_index::write::Error),
+        #[error(transparent)]
+        WriteCommitGraph(#[from] gix_commitgraph::write::Error),
+        #[error(transparent)]
+        ReachableCommits(#[from] super::reachable::Error),
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error(transparent)]
+        LockCommit(#[from] gix_lock::commit::Error<gix_lock::File>),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error("Refusing to run maintenance task '{}' as it deletes objects, which extensions.preciousObjects forbids", task.as_str())]
+        PreciousObjects { task: super::Task },
+    }
+}
+
+///
+pub mod auto {
+    /// The error returned by [`Repository::maintenance_auto()`](crate::Repository::maintenance_auto()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Due(#[from] super::due::Error),
+        #[error("Maintenance task '{}' failed", task.as_str())]
+        Run {
+            task: super::Task,
+            source: super::run::Error,
+        },
+    }
+}
+
+///
+pub mod reachable {
+    /// The error returned when obtaining all commits that references point to.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        InitRefsIterator(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        InitRefsIteratorPlatform(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ObtainRefDuringIteration(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        FindHeader(#[from] crate::object::find::existing::Error),
+    }
+}
+
+///
+pub mod prune {
+    use std::path::PathBuf;
+
+    /// The outcome of [`Repository::prune_unreachable_objects()`](crate::Repository::prune_unreachable_objects()).
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Outcome {
+        /// Unreachable loose objects older than the grace period were deleted.
+        Pruned {
+            /// The amount of deleted loose objects.
+            objects: usize,
+        },
+        /// Nothing was pruned as `gc.pruneExpire` is set to `never`.
+        NeverExpires,
+        /// Nothing was pruned as another operation that may add objects to the repository appears to be in progress.
+        OperationInProgress {
+            /// The path to the temporary file or quarantine directory which indicates the operation.
+            marker: PathBuf,
+        },
+    }
+
+    /// The error returned by [`Repository::prune_unreachable_objects()`](crate::Repository::prune_unreachable_objects()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ExpiryDate(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        InitRefsIterator(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        InitRefsIteratorPlatform(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ObtainRefDuringIteration(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        DecodeRefLog(#[from] gix_ref::file::log::iter::decode::Error),
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        FindObject(gix_object::find::Error),
+        #[error(transparent)]
+        DecodeObject(#[from] gix_object::decode::Error),
+        #[error("Could not list loose objects")]
+        IterateLooseObjects(#[from] gix_features::fs::walkdir::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error("Refusing to prune objects as extensions.preciousObjects is set")]
+        PreciousObjects,
+    }
+}
@@ -0,0 +1,21 @@
+//! Three-way merges of blobs and trees, as needed to combine the changes of two commits since their merge base.
+//!
+//! Use [`Repository::merge_trees()`](crate::Repository::merge_trees()) to merge entire trees.
+pub use gix_merge::blob;
+
+///
+pub mod tree {
+    pub use gix_merge::tree::{Conflict, ConflictKind, Entry, Outcome};
+
+    /// The error returned by [`Repository::merge_trees()`](crate::Repository::merge_trees()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Merge(#[from] gix_merge::tree::Error),
+    }
+}
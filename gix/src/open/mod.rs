@@ -20,6 +20,12 @@ pub struct Permissions {
 /// The environment variables `GIT_REPLACE_REF_BASE` and `GIT_NO_REPLACE_OBJECTS` are mapped to `gitoxide.objects.replaceRefBase`
 /// and `gitoxide.objects.noReplace` respectively and then interpreted exactly as their environment variable counterparts.
 ///
+/// ### Object database locations
+///
+/// The environment variables `GIT_OBJECT_DIRECTORY` and `GIT_ALTERNATE_OBJECT_DIRECTORIES` are mapped to `gitoxide.objects.directory`
+/// and `gitoxide.objects.alternates` respectively, to change the directory that objects are read from and written to, and
+/// to add alternate object databases to read from.
+///
 /// Use [Permissions] to control which environment variables can be read, and config-overrides to control these values programmatically.
 #[derive(Clone)]
 pub struct Options {
@@ -35,6 +41,7 @@ pub struct Options {
     pub(crate) api_config_overrides: Vec<BString>,
     pub(crate) cli_config_overrides: Vec<BString>,
     pub(crate) open_path_as_is: bool,
+    pub(crate) run_hooks: bool,
     /// Internal to pass an already obtained CWD on to where it may also be used. This avoids the CWD being queried more than once per repo.
     pub(crate) current_dir: Option<PathBuf>,
 }
@@ -56,6 +63,10 @@ pub enum Error {
     UnsafeGitDir { path: PathBuf },
     #[error(transparent)]
     EnvironmentAccessDenied(#[from] gix_sec::permission::Error<std::path::PathBuf>),
+    #[error("Could not determine how references are stored")]
+    RefStore(#[from] gix_ref::store::general::init::Error),
+    #[error("The linked worktree at '{}' can't be opened as its repository keeps references in a reftable, which isn't supported for worktrees yet", .git_dir.display())]
+    ReftableInLinkedWorktree { git_dir: PathBuf },
 }
 
 mod options;
@@ -14,6 +14,7 @@ impl Default for Options {
             lenient_config: true,
             bail_if_untrusted: false,
             open_path_as_is: false,
+            run_hooks: true,
             api_config_overrides: Vec::new(),
             cli_config_overrides: Vec::new(),
             current_dir: None,
@@ -25,8 +26,13 @@ impl Default for Options {
 impl Options {
     /// Options configured to prevent accessing anything else than the repository configuration file, prohibiting
     /// accessing the environment or spreading beyond the git repository location.
+    ///
+    /// This makes the repository independent of the invoking user's configuration and environment, which is useful for servers
+    /// and tests. Note that configuration from the system, the user or environment variables like `GIT_CONFIG_COUNT` isn't loaded,
+    /// and that environment variables like `GIT_COMMITTER_NAME` are ignored. Use [Permissions] to allow some of these selectively.
+    /// Hooks of the repository aren't [run](Self::run_hooks()) either.
     pub fn isolated() -> Self {
-        Options::default().permissions(Permissions::isolated())
+        Options::default().permissions(Permissions::isolated()).run_hooks(false)
     }
 }
 
@@ -79,6 +85,15 @@ impl Options {
         self
     }
 
+    /// If `false`, default `true` unless the repository isn't fully trusted or the options are [isolated](Self::isolated()),
+    /// hooks like `reference-transaction` won't be run when the repository is changed.
+    ///
+    /// Note that hooks are never run in repositories that aren't fully trusted, and that running them requires the `command` feature.
+    pub fn run_hooks(mut self, toggle: bool) -> Self {
+        self.run_hooks = toggle;
+        self
+    }
+
     /// Set the trust level of the `.git` directory we are about to open.
     ///
     /// This can be set manually to force trust even though otherwise it might
@@ -167,6 +182,7 @@ impl gix_sec::trust::DefaultForLevel for Options {
                 bail_if_untrusted: false,
                 lenient_config: true,
                 open_path_as_is: false,
+                run_hooks: true,
                 api_config_overrides: Vec::new(),
                 cli_config_overrides: Vec::new(),
                 current_dir: None,
@@ -179,6 +195,7 @@ impl gix_sec::trust::DefaultForLevel for Options {
                 bail_if_untrusted: false,
                 lenient_config: true,
                 open_path_as_is: false,
+                run_hooks: false,
                 lossy_config: None,
                 api_config_overrides: Vec::new(),
                 cli_config_overrides: Vec::new(),
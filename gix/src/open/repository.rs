@@ -162,6 +162,7 @@ impl ThreadSafeRepository {
             lenient_config,
             bail_if_untrusted,
             open_path_as_is: _,
+            run_hooks: _,
             permissions:
                 Permissions {
                     ref env,
@@ -210,23 +211,31 @@ impl ThreadSafeRepository {
             current_dir_ref.as_path()
         };
 
-        let mut refs = {
-            let reflog = repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable);
-            let object_hash = repo_config.object_hash;
-            let ref_store_init_opts = gix_ref::store::init::Options {
-                write_reflog: reflog,
-                object_hash,
-                precompose_unicode: repo_config.precompose_unicode,
-                prohibit_windows_device_names: repo_config.protect_windows,
-            };
-            match &common_dir {
-                Some(common_dir) => {
-                    crate::RefStore::for_linked_worktree(git_dir.to_owned(), common_dir.into(), ref_store_init_opts)
-                }
-                None => crate::RefStore::at(git_dir.to_owned(), ref_store_init_opts),
+        let ref_store_init_opts = gix_ref::store::init::Options {
+            write_reflog: repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable),
+            object_hash: repo_config.object_hash,
+            precompose_unicode: repo_config.precompose_unicode,
+            prohibit_windows_device_names: repo_config.protect_windows,
+            // Set once the configuration is fully loaded.
+            ignore_case: false,
+            fsync: None,
+        };
+        let mut refs = match &common_dir {
+            Some(common_dir) => {
+                crate::RefStore::for_linked_worktree(git_dir.to_owned(), common_dir.into(), ref_store_init_opts)
             }
+            None => crate::RefStore::at(git_dir.to_owned(), ref_store_init_opts),
+        };
+        let mut reftable = gix_ref::Store::at(common_dir_ref.to_owned(), ref_store_init_opts)?
+            .as_reftable()
+            .cloned();
+        if reftable.is_some() && common_dir.is_some() {
+            return Err(Error::ReftableInLinkedWorktree { git_dir });
+        }
+        let head = match &reftable {
+            Some(store) => store.find("HEAD").ok(),
+            None => refs.find("HEAD").ok(),
         };
-        let head = refs.find("HEAD").ok();
         let git_install_dir = crate::path::install_dir().ok();
         let home = gix_path::env::home_dir().and_then(|home| env.home.check_opt(home));
 
@@ -296,14 +305,25 @@ impl ThreadSafeRepository {
 
         refs.write_reflog = config::cache::util::reflog_or_default(config.reflog, worktree_dir.is_some());
         refs.namespace.clone_from(&config.refs_namespace);
+        refs.fsync = config.fsync.method_for(gix_fs::fsync::Component::Reference);
+        refs.ignore_case = config.ignore_case;
+        if let Some(reftable) = reftable.as_mut() {
+            reftable.write_reflog = refs.write_reflog;
+            reftable.namespace.clone_from(&refs.namespace);
+        }
         let replacements = replacement_objects_refs_prefix(&config.resolved, lenient_config, filter_config_section)?
             .and_then(|prefix| {
                 let _span = gix_trace::detail!("find replacement objects");
-                let platform = refs.iter().ok()?;
-                let iter = platform.prefixed(&prefix).ok()?;
+                let references: Vec<_> = match &reftable {
+                    Some(store) => store
+                        .iter_prefixed(gix_path::into_bstr(prefix.as_path()).into_owned())
+                        .ok()?
+                        .collect(),
+                    None => refs.iter().ok()?.prefixed(&prefix).ok()?.filter_map(Result::ok).collect(),
+                };
                 let prefix = prefix.to_str()?;
-                let replacements = iter
-                    .filter_map(Result::ok)
+                let replacements = references
+                    .into_iter()
                     .filter_map(|r: gix_ref::Reference| {
                         let target = r.target.try_id()?.to_owned();
                         let source =
@@ -315,19 +335,30 @@ impl ThreadSafeRepository {
             })
             .unwrap_or_default();
 
+        let (objects_dir, alternates) = object_directories(
+            &config.resolved,
+            common_dir_ref,
+            current_dir,
+            lenient_config,
+            filter_config_section,
+        )?;
         Ok(ThreadSafeRepository {
             objects: OwnShared::new(gix_odb::Store::at_opts(
-                common_dir_ref.join("objects"),
+                objects_dir,
                 &mut replacements.into_iter(),
                 gix_odb::store::init::Options {
                     slots: object_store_slots,
                     object_hash: config.object_hash,
                     use_multi_pack_index: config.use_multi_pack_index,
                     current_dir: current_dir.to_owned().into(),
+                    alternates,
+                    fsync_loose_objects: config.fsync.method_for(gix_fs::fsync::Component::LooseObject),
+                    limits: Default::default(),
                 },
             )?),
             common_dir,
             refs,
+            reftable,
             work_tree: worktree_dir,
             config,
             // used when spawning new repositories off this one when following worktrees
@@ -341,6 +372,47 @@ impl ThreadSafeRepository {
     }
 }
 
+/// Return the objects directory to use, along with additional alternate object directories, both of which are typically
+/// configured via environment variables.
+fn object_directories(
+    config: &gix_config::File<'static>,
+    common_dir: &std::path::Path,
+    current_dir: &std::path::Path,
+    lenient: bool,
+    mut filter_config_section: fn(&gix_config::file::Metadata) -> bool,
+) -> Result<(PathBuf, Vec<PathBuf>), Error> {
+    let objects_dir = config
+        .string_filter(
+            gitoxide::Objects::DIRECTORY.logical_name().as_str(),
+            &mut filter_config_section,
+        )
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(
+            || common_dir.join("objects"),
+            |dir| current_dir.join(gix_path::from_bstr(dir)),
+        );
+    let alternates = match config.string_filter(
+        gitoxide::Objects::ALTERNATES.logical_name().as_str(),
+        &mut filter_config_section,
+    ) {
+        Some(list) => match gix_odb::alternate::parse::path_list(list.as_ref()) {
+            Ok(alternates) => alternates,
+            Err(_) if lenient => Vec::new(),
+            Err(_) => {
+                return Err(
+                    config::Error::ConfigTypedString(config::key::GenericErrorWithValue::from_value(
+                        &gitoxide::Objects::ALTERNATES,
+                        list.into_owned(),
+                    ))
+                    .into(),
+                )
+            }
+        },
+        None => Vec::new(),
+    };
+    Ok((objects_dir, alternates))
+}
+
 // TODO: tests
 fn replacement_objects_refs_prefix(
     config: &gix_config::File<'static>,
@@ -0,0 +1,142 @@
+/// All possible values of `pull.rebase` and `branch.<name>.rebase`.
+#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Ord, Eq, Hash, Debug)]
+pub enum Rebase {
+    /// Merge the fetched changes into the current branch.
+    #[default]
+    False,
+    /// Rebase the commits of the current branch onto the fetched changes.
+    True,
+    /// Like [`True`](Rebase::True), but recreate merge commits instead of linearizing them.
+    Merges,
+    /// Like [`True`](Rebase::True), but let the user edit the commits to rebase.
+    Interactive,
+}
+
+/// All possible values of `pull.ff`.
+#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Ord, Eq, Hash, Debug)]
+pub enum FastForward {
+    /// Fast-forward the current branch if possible, or merge otherwise.
+    #[default]
+    Allow,
+    /// Always create a merge commit, even if a fast-forward would be possible.
+    Never,
+    /// Refuse to do anything unless the current branch can be fast-forwarded.
+    Only,
+}
+
+#[cfg(all(feature = "merge", feature = "blocking-network-client"))]
+pub use types::{Options, Outcome, Strategy};
+
+#[cfg(all(feature = "merge", feature = "blocking-network-client"))]
+mod types {
+    use gix_hash::ObjectId;
+
+    /// Options for [`Repository::pull()`](crate::Repository::pull()).
+    #[derive(Default, Debug, Clone, Copy)]
+    pub struct Options {
+        /// If set, override `branch.<name>.rebase` and `pull.rebase`.
+        pub rebase: Option<super::Rebase>,
+        /// If set, override `pull.ff`.
+        pub fast_forward: Option<super::FastForward>,
+    }
+
+    /// The way the fetched changes were integrated into the current branch.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Strategy {
+        /// The current branch already contained all fetched changes, and wasn't changed.
+        UpToDate,
+        /// The current branch was set to the fetched commit as it didn't have any commits of its own,
+        /// or because it was unborn.
+        FastForward,
+        /// A merge commit was created which has the previous tip of the current branch and the fetched commit as parents.
+        Merge,
+        /// The commits of the current branch were recreated on top of the fetched commit.
+        Rebase {
+            /// The commits that were recreated, in order, along with the commit they were recreated as.
+            rebased: Vec<(ObjectId, ObjectId)>,
+            /// The commits which weren't recreated as their changes were already fetched, or because they are merge commits.
+            skipped: Vec<ObjectId>,
+        },
+    }
+
+    /// The outcome of [`Repository::pull()`](crate::Repository::pull()).
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// The outcome of fetching from the remote, which also contains all remote-tracking references that were updated.
+        pub fetch: crate::remote::fetch::Outcome,
+        /// The fetched commit that was integrated into the current branch, as listed in `FETCH_HEAD`.
+        pub upstream: ObjectId,
+        /// How the fetched changes were integrated.
+        pub strategy: Strategy,
+        /// The name of the current branch.
+        pub reference: gix_ref::FullName,
+        /// The commit the current branch pointed to before, or `None` if it was unborn.
+        pub previous: Option<ObjectId>,
+        /// The commit the current branch points to now.
+        ///
+        /// Note that the index and worktree aren't updated, which is left to the caller.
+        pub new: ObjectId,
+    }
+}
+
+///
+#[cfg(all(feature = "merge", feature = "blocking-network-client"))]
+pub mod error {
+    use gix_hash::ObjectId;
+
+    use crate::{bstr::BString, merge::tree::Conflict};
+
+    /// The error returned by [`Repository::pull()`](crate::Repository::pull()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot pull with a detached HEAD as there is no branch to integrate the fetched changes into")]
+        DetachedHead,
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error("There is no remote to pull from")]
+        NoRemote,
+        #[error(transparent)]
+        FindRemote(#[from] crate::remote::find::existing::Error),
+        #[error(transparent)]
+        Connect(#[from] crate::remote::connect::Error),
+        #[error(transparent)]
+        PrepareFetch(#[from] crate::remote::fetch::prepare::Error),
+        #[error(transparent)]
+        Fetch(#[from] crate::remote::fetch::Error),
+        #[error("Could not read FETCH_HEAD to learn which fetched commit to integrate")]
+        ReadFetchHead(#[from] gix_ref::file::pseudo::read::Error),
+        #[error("There is no tracking information for the branch {branch:?}, configure `branch.<name>.merge` to pull")]
+        NoMergeCandidate { branch: BString },
+        #[error("Cannot integrate {count} fetched commits at once")]
+        MultipleMergeCandidates { count: usize },
+        #[error(transparent)]
+        Config(#[from] crate::config::key::GenericErrorWithValue),
+        #[error("Interactive rebases are not supported")]
+        InteractiveRebase,
+        #[error("Rebasing merge commits is not supported, but {commit} is a merge commit")]
+        RebaseMerges { commit: ObjectId },
+        #[error("Not possible to fast-forward, and `pull.ff` is set to `only`")]
+        NotFastForward,
+        #[error(transparent)]
+        MergeBase(#[from] crate::repository::merge_base::Error),
+        #[error(transparent)]
+        MergeTrees(#[from] crate::merge::tree::Error),
+        #[error(transparent)]
+        RebaseTodo(#[from] crate::repository::rebase_todo::Error),
+        #[error(transparent)]
+        Rebase(#[from] crate::rebase::Error),
+        #[error("Merging the fetched commit resulted in {} conflict(s)", conflicts.len())]
+        MergeConflict { conflicts: Vec<Conflict> },
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+        #[error(transparent)]
+        ParseTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        EditReference(#[from] crate::reference::edit::Error),
+    }
+}
+#[cfg(all(feature = "merge", feature = "blocking-network-client"))]
+pub use error::Error;
@@ -0,0 +1,148 @@
+//! Interactive rebases which perform the instructions of a [todo list](todo::List) one by one, and which use a [`Delegate`]
+//! for all interactions with the user.
+//!
+//! Use [`Repository::rebase_todo()`](crate::Repository::rebase_todo()) to obtain the initial todo list, and
+//! [`Repository::rebase()`](crate::Repository::rebase()) to perform it.
+use gix_hash::ObjectId;
+
+use crate::bstr::{BStr, BString};
+
+pub use gix_rebase::todo;
+
+/// The error a [`Delegate`] may return to abort the rebase.
+pub type DelegateError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The reason for a commit message to be presented to [`Delegate::edit_message()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    /// The message of a commit picked with `reword`.
+    Reword,
+    /// The message of a commit that other commits were folded into with `squash` or `fixup -c`, combined from all of
+    /// their messages.
+    Squash,
+    /// The message of a merge commit created with `merge -c <commit>`, or without any commit to take the message from.
+    Merge,
+}
+
+/// The reason for [`Delegate::stop()`] to be called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stop {
+    /// `commit` was picked with `edit`, and the current commit is the picked commit.
+    Edit {
+        /// The original commit.
+        commit: ObjectId,
+    },
+    /// A `break` instruction was encountered.
+    Break,
+}
+
+/// Callbacks for all interactions with the user during a [rebase](crate::Repository::rebase()).
+///
+/// All methods have default implementations which perform the rebase without any interaction, except for
+/// [`exec()`](Delegate::exec()) which fails as it can't run commands.
+/// Errors returned by any of the methods abort the rebase.
+pub trait Delegate {
+    /// Let the user edit the todo list before any of its instructions are performed, like `git rebase -i` would
+    /// do by opening an editor.
+    fn edit_todo(&mut self, _list: &mut todo::List) -> Result<(), DelegateError> {
+        Ok(())
+    }
+
+    /// Let the user edit the message of a new commit, for the given reason.
+    fn edit_message(&mut self, _kind: MessageKind, _message: &mut BString) -> Result<(), DelegateError> {
+        Ok(())
+    }
+
+    /// Stop the rebase for the given reason so that the user can inspect or amend the current commit `head`,
+    /// which is `None` after `reset [new root]`.
+    /// Return the commit to continue from, which is `head` unless the user created or amended commits.
+    fn stop(&mut self, _stop: Stop, head: Option<ObjectId>) -> Result<Option<ObjectId>, DelegateError> {
+        Ok(head)
+    }
+
+    /// Run `command` with a shell for an `exec` instruction, while `head` is the current commit.
+    /// Return the commit to continue from, which is `head` unless `command` created or amended commits.
+    ///
+    /// Just like with `git`, a failing `command` should return an error to abort the rebase.
+    fn exec(&mut self, command: &BStr, _head: Option<ObjectId>) -> Result<Option<ObjectId>, DelegateError> {
+        Err(format!("Cannot run {command:?} as commands are not supported").into())
+    }
+}
+
+/// A delegate which performs a rebase without any interaction, see [`Delegate`] for details.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct NonInteractive;
+
+impl Delegate for NonInteractive {}
+
+/// The outcome of [`Repository::rebase()`](crate::Repository::rebase()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The commit that was current after performing the last instruction, which is `None` only if there was no commit
+    /// after a `reset [new root]`.
+    ///
+    /// Note that no reference was changed, which is left to the caller.
+    pub head: Option<ObjectId>,
+    /// The commits that were applied along with the commit they are contained in now, in order.
+    ///
+    /// Commits that were applied without changes are contained in their own id, and commits that were folded into
+    /// their predecessor are contained in the same commit as the predecessor.
+    pub rewritten: Vec<(ObjectId, ObjectId)>,
+    /// The commits that weren't applied because they were dropped explicitly, or because their changes were already present.
+    pub dropped: Vec<ObjectId>,
+    /// The references named in `update-ref` instructions, along with the commit they should point to.
+    pub update_refs: Vec<(gix_ref::FullName, ObjectId)>,
+}
+
+///
+pub mod error {
+    use gix_hash::{ObjectId, Prefix};
+
+    use crate::{bstr::BString, merge::tree::Conflict};
+
+    /// The error returned by [`Repository::rebase()`](crate::Repository::rebase()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The rebase was aborted")]
+        Delegate(#[source] super::DelegateError),
+        #[error("The commit {prefix} doesn't exist")]
+        NotFound { prefix: Prefix },
+        #[error("The short id {prefix} is ambiguous")]
+        Ambiguous { prefix: Prefix },
+        #[error(transparent)]
+        LookupPrefix(#[from] gix_odb::store::prefix::lookup::Error),
+        #[error("Could not resolve label {name:?}")]
+        ResolveLabel {
+            name: BString,
+            source: crate::revision::spec::parse::single::Error,
+        },
+        #[error("Cannot pick {commit} as it is a merge commit")]
+        PickMerge { commit: ObjectId },
+        #[error("Cannot '{instruction}' without a current commit")]
+        NoCurrentCommit { instruction: &'static str },
+        #[error("Octopus merges of {count} commits aren't supported")]
+        OctopusMerge { count: usize },
+        #[error("Applying commit {commit} resulted in {} conflict(s)", conflicts.len())]
+        Conflict { commit: ObjectId, conflicts: Vec<Conflict> },
+        #[error(transparent)]
+        InvalidRefName(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+        #[error(transparent)]
+        TreeId(#[from] crate::object::commit::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        MergeBase(#[from] crate::repository::merge_base::Error),
+        #[error(transparent)]
+        MergeTrees(#[from] crate::merge::tree::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+        #[error(transparent)]
+        ParseTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+    }
+}
+pub use error::Error;
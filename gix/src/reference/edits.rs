@@ -73,3 +73,152 @@ pub mod delete {
         }
     }
 }
+
+///
+pub mod transaction {
+    use gix_ref::transaction::RefEdit;
+
+    use crate::Repository;
+
+    /// Reference edits whose locks are held and whose expectations were verified, ready to be
+    /// [committed](Prepared::commit()) or [rolled back](Prepared::rollback()).
+    ///
+    /// This makes it possible to do other work, like writing a pack, while nobody else can change the affected references.
+    /// Dropping this instance rolls back all edits, releasing all locks and removing what was written in preparation.
+    ///
+    /// Obtained by [`Repository::prepare_reference_edits()`].
+    pub struct Prepared<'repo> {
+        pub(crate) repo: &'repo Repository,
+        pub(crate) transaction: Transaction<'repo>,
+    }
+
+    /// The transaction of the reference store the repository uses.
+    pub(crate) enum Transaction<'repo> {
+        File(gix_ref::file::Transaction<'repo, 'repo>),
+        Reftable(gix_ref::reftable::Transaction<'repo>),
+    }
+
+    impl<'repo> Prepared<'repo> {
+        /// Return the edits as they will be committed, which might be more than the ones that were provided due to the splitting
+        /// of symbolic references, and whose `expected` values are the ones seen in storage after the reference was locked.
+        pub fn edits(&self) -> impl Iterator<Item = &RefEdit> + '_ {
+            let (file, reftable) = match &self.transaction {
+                Transaction::File(transaction) => (Some(transaction.prepared_edits()), None),
+                Transaction::Reftable(transaction) => (None, Some(transaction.prepared_edits())),
+            };
+            file.into_iter().flatten().chain(reftable.into_iter().flatten())
+        }
+
+        /// Make all edits permanent, writing reference logs with the committer configured in the repository,
+        /// and return them as they were performed.
+        ///
+        /// If loose objects are synced in batches as configured by `core.fsyncMethod`, the batch is ended first
+        /// so that all objects the references may point to are durable.
+        pub fn commit(self) -> Result<Vec<RefEdit>, crate::reference::edit::Error> {
+            let committer = self.repo.committer().transpose()?;
+            if self.repo.config.fsync.method_for(gix_fs::fsync::Component::LooseObject)
+                == Some(gix_fs::fsync::Method::Batch)
+            {
+                gix_fs::fsync::flush_batch(self.repo.objects.store_ref().path())
+                    .map_err(crate::reference::edit::Error::FlushObjectBatch)?;
+            }
+            match self.transaction {
+                Transaction::File(transaction) => transaction.commit(committer).map_err(Into::into),
+                Transaction::Reftable(transaction) => transaction.commit(committer).map_err(Into::into),
+            }
+        }
+
+        /// Release all locks without changing any reference, and return the edits exactly as [`commit()`](Self::commit())
+        /// would have returned them, which is useful for validating edits before actually performing them.
+        pub fn commit_dry_run(self) -> Vec<RefEdit> {
+            match self.transaction {
+                Transaction::File(transaction) => transaction.commit_dry_run(),
+                Transaction::Reftable(transaction) => transaction.commit_dry_run(),
+            }
+        }
+
+        /// Release all locks without changing any reference, and return the edits as they were prepared.
+        pub fn rollback(self) -> Vec<RefEdit> {
+            match self.transaction {
+                Transaction::File(transaction) => transaction.rollback(),
+                Transaction::Reftable(transaction) => transaction.rollback(),
+            }
+        }
+    }
+
+    impl std::fmt::Debug for Prepared<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Prepared")
+                .field("edits", &self.edits().collect::<Vec<_>>())
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// Running the `reference-transaction` hook of a repository.
+    #[cfg(feature = "command")]
+    pub mod hook {
+        use std::{
+            io::Write,
+            path::{Path, PathBuf},
+            process::{ExitStatus, Stdio},
+        };
+
+        use gix_ref::file::transaction::hook::{State, Update};
+
+        use crate::Repository;
+
+        /// The error returned if the `reference-transaction` hook rejects a transaction or couldn't be run.
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("Could not run the hook at {path:?}")]
+            Run { path: PathBuf, source: std::io::Error },
+            #[error("The hook at {path:?} rejected the transaction with {status}")]
+            Rejected { path: PathBuf, status: ExitStatus },
+        }
+
+        /// Run the hook at `path` in `repo` like `git` does, passing `state` as argument and `updates` on standard input.
+        ///
+        /// Only a hook that fails with the transaction in the [prepared](State::Prepared) state rejects it.
+        pub(crate) fn run(repo: &Repository, path: &Path, state: State, updates: &[Update]) -> Result<(), Error> {
+            let err = |source| Error::Run {
+                path: path.to_owned(),
+                source,
+            };
+            let mut input = Vec::new();
+            for update in updates {
+                update
+                    .write_to(&mut input, repo.object_hash())
+                    .expect("no IO errors writing to memory");
+            }
+            let realpath = |path: &Path| {
+                gix_path::realpath(path)
+                    .map_err(|realpath_err| err(std::io::Error::new(std::io::ErrorKind::Other, realpath_err)))
+            };
+            let cwd = realpath(repo.work_dir().unwrap_or(repo.git_dir()))?;
+            let mut cmd: std::process::Command = gix_command::prepare(realpath(path)?)
+                .with_context(gix_command::Context {
+                    git_dir: Some(realpath(repo.git_dir())?),
+                    ..Default::default()
+                })
+                .arg(state.as_str())
+                .stdin(Stdio::piped())
+                .into();
+            let mut child = cmd.current_dir(&cwd).spawn().map_err(err)?;
+            let mut stdin = child.stdin.take().expect("configured");
+            match stdin.write_all(&input) {
+                Err(io_err) if io_err.kind() == std::io::ErrorKind::BrokenPipe => {}
+                res => res.map_err(err)?,
+            }
+            drop(stdin);
+            let status = child.wait().map_err(err)?;
+            if state == State::Prepared && !status.success() {
+                return Err(Error::Rejected {
+                    path: path.to_owned(),
+                    status,
+                });
+            }
+            Ok(())
+        }
+    }
+}
@@ -12,11 +12,19 @@ pub mod edit {
         #[error(transparent)]
         FileTransactionCommit(#[from] gix_ref::file::transaction::commit::Error),
         #[error(transparent)]
+        ReftableTransactionPrepare(#[from] gix_ref::reftable::transaction::prepare::Error),
+        #[error(transparent)]
+        ReftableTransactionCommit(#[from] gix_ref::reftable::transaction::commit::Error),
+        #[error(transparent)]
         NameValidation(#[from] gix_validate::reference::name::Error),
         #[error("Could not interpret core.filesRefLockTimeout or core.packedRefsTimeout, it must be the number in milliseconds to wait for locks or negative to wait forever")]
         LockTimeoutConfiguration(#[from] config::lock_timeout::Error),
         #[error(transparent)]
         ParseCommitterTime(#[from] crate::config::time::Error),
+        #[error("Could not make the batch of loose objects written so far durable")]
+        FlushObjectBatch(#[source] std::io::Error),
+        #[error("The value for `core.hooksPath` could not be read from configuration")]
+        HooksPath(#[from] gix_config::path::interpolate::Error),
     }
 }
 
@@ -53,6 +61,16 @@ pub mod peel {
 
 ///
 pub mod follow {
+    /// The error returned by [`Reference::follow(…)`](crate::Reference::follow()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Follow(#[from] gix_ref::file::find::existing::Error),
+        #[error(transparent)]
+        FollowReftable(#[from] gix_ref::reftable::find::existing::Error),
+    }
+
     ///
     pub mod to_object {
         /// The error returned by [`Reference::follow_to_object(…)`](crate::Reference::follow_to_object()).
@@ -128,6 +146,8 @@ pub mod find {
         #[error(transparent)]
         Find(#[from] gix_ref::file::find::Error),
         #[error(transparent)]
+        FindReftable(#[from] gix_ref::reftable::find::Error),
+        #[error(transparent)]
         PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
     }
 }
@@ -7,22 +7,33 @@ use gix_ref::file::ReferenceExt;
 /// A platform to create iterators over references.
 #[must_use = "Iterators should be obtained from this iterator platform"]
 pub struct Platform<'r> {
-    pub(crate) platform: gix_ref::file::iter::Platform<'r>,
+    pub(crate) platform: PlatformInner<'r>,
     pub(crate) repo: &'r crate::Repository,
 }
 
+/// The iteration platform of the reference store the repository uses.
+pub(crate) enum PlatformInner<'r> {
+    Loose(gix_ref::file::iter::Platform<'r>),
+    Reftable(&'r gix_ref::reftable::Store),
+}
+
 /// An iterator over references, with or without filter.
 pub struct Iter<'r> {
-    inner: gix_ref::file::iter::LooseThenPacked<'r, 'r>,
+    inner: IterInner<'r>,
     peel_with_packed: Option<gix_ref::file::packed::SharedBufferSnapshot>,
     peel: bool,
     repo: &'r crate::Repository,
 }
 
+enum IterInner<'r> {
+    Loose(gix_ref::file::iter::LooseThenPacked<'r, 'r>),
+    Reftable(std::vec::IntoIter<gix_ref::Reference>),
+}
+
 impl<'r> Iter<'r> {
-    fn new(repo: &'r crate::Repository, platform: gix_ref::file::iter::LooseThenPacked<'r, 'r>) -> Self {
+    fn new(repo: &'r crate::Repository, inner: IterInner<'r>) -> Self {
         Iter {
-            inner: platform,
+            inner,
             peel_with_packed: None,
             peel: false,
             repo,
@@ -36,7 +47,13 @@ impl<'r> Platform<'r> {
     /// Even broken or otherwise unparsable or inaccessible references are returned and have to be handled by the caller on a
     /// case by case basis.
     pub fn all(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.all()?))
+        Ok(Iter::new(
+            self.repo,
+            match &self.platform {
+                PlatformInner::Loose(platform) => IterInner::Loose(platform.all()?),
+                PlatformInner::Reftable(store) => IterInner::Reftable(store.iter_prefixed("refs/".into())?),
+            },
+        ))
     }
 
     /// Return an iterator over all references that match the given `prefix`.
@@ -45,7 +62,21 @@ impl<'r> Platform<'r> {
     // TODO: Create a custom `Path` type that enforces the requirements of git naturally, this type is surprising possibly on windows
     //       and when not using a trailing '/' to signal directories.
     pub fn prefixed(&self, prefix: impl AsRef<Path>) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed(prefix.as_ref())?))
+        let prefix = prefix.as_ref();
+        Ok(Iter::new(
+            self.repo,
+            match &self.platform {
+                PlatformInner::Loose(platform) => IterInner::Loose(platform.prefixed(prefix)?),
+                PlatformInner::Reftable(store) => {
+                    // Like with loose references, `refs/heads` is the same as `refs/heads/`.
+                    let mut prefix = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(prefix)).into_owned();
+                    if !prefix.is_empty() && !prefix.ends_with(b"/") {
+                        prefix.push(b'/');
+                    }
+                    IterInner::Reftable(store.iter_prefixed(prefix)?)
+                }
+            },
+        ))
     }
 
     // TODO: tests
@@ -53,7 +84,7 @@ impl<'r> Platform<'r> {
     ///
     /// They are all prefixed with `refs/tags`.
     pub fn tags(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed("refs/tags/".as_ref())?))
+        self.prefixed("refs/tags/")
     }
 
     // TODO: tests
@@ -61,7 +92,7 @@ impl<'r> Platform<'r> {
     ///
     /// They are all prefixed with `refs/heads`.
     pub fn local_branches(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed("refs/heads/".as_ref())?))
+        self.prefixed("refs/heads/")
     }
 
     // TODO: tests
@@ -69,7 +100,7 @@ impl<'r> Platform<'r> {
     ///
     /// They are all prefixed with `refs/remotes`.
     pub fn remote_branches(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed("refs/remotes/".as_ref())?))
+        self.prefixed("refs/remotes/")
     }
 }
 
@@ -83,7 +114,9 @@ impl<'r> Iter<'r> {
     /// Doing this is necessary as the packed-refs buffer is already held by the iterator, disallowing the consumer of the iterator
     /// to peel the returned references themselves.
     pub fn peeled(mut self) -> Result<Self, gix_ref::packed::buffer::open::Error> {
-        self.peel_with_packed = self.repo.refs.cached_packed_buffer()?;
+        if self.repo.reftable.is_none() {
+            self.peel_with_packed = self.repo.refs.cached_packed_buffer()?;
+        }
         self.peel = true;
         Ok(self)
     }
@@ -93,24 +126,32 @@ impl<'r> Iterator for Iter<'r> {
     type Item = Result<crate::Reference<'r>, Box<dyn std::error::Error + Send + Sync + 'static>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|res| {
-            res.map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
-                .and_then(|mut r| {
-                    if self.peel {
-                        let repo = &self.repo;
-                        r.peel_to_id_in_place_packed(
+        let res = match &mut self.inner {
+            IterInner::Loose(iter) => iter
+                .next()?
+                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>),
+            IterInner::Reftable(iter) => Ok(iter.next()?),
+        };
+        Some(
+            res.and_then(|mut r| {
+                if self.peel {
+                    let repo = &self.repo;
+                    match &repo.reftable {
+                        Some(store) => store.peel_to_id_in_place(&mut r, &repo.objects),
+                        None => r.peel_to_id_in_place_packed(
                             &repo.refs,
                             &repo.objects,
                             self.peel_with_packed.as_ref().map(|p| &***p),
-                        )
-                        .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
-                        .map(|_| r)
-                    } else {
-                        Ok(r)
+                        ),
                     }
-                })
-                .map(|r| crate::Reference::from_ref(r, self.repo))
-        })
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
+                    .map(|_| r)
+                } else {
+                    Ok(r)
+                }
+            })
+            .map(|r| crate::Reference::from_ref(r, self.repo)),
+        )
     }
 }
 
@@ -122,6 +163,8 @@ pub mod init {
     pub enum Error {
         #[error(transparent)]
         Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Reftable(#[from] gix_ref::reftable::find::Error),
     }
 }
 
@@ -70,7 +70,10 @@ impl<'repo> Reference<'repo> {
     /// This is useful to learn where this reference is ultimately pointing to after following
     /// the chain of symbolic refs and annotated tags.
     pub fn peel_to_id_in_place(&mut self) -> Result<Id<'repo>, peel::Error> {
-        let oid = self.inner.peel_to_id_in_place(&self.repo.refs, &self.repo.objects)?;
+        let oid = match &self.repo.reftable {
+            Some(store) => store.peel_to_id_in_place(&mut self.inner, &self.repo.objects)?,
+            None => self.inner.peel_to_id_in_place(&self.repo.refs, &self.repo.objects)?,
+        };
         Ok(Id::from_id(oid, self.repo))
     }
 
@@ -79,13 +82,18 @@ impl<'repo> Reference<'repo> {
     ///
     /// This is useful to learn where this reference is ultimately pointing to after following
     /// the chain of symbolic refs and annotated tags.
+    ///
+    /// `packed` is unused if references are kept in a reftable.
     pub fn peel_to_id_in_place_packed(
         &mut self,
         packed: Option<&gix_ref::packed::Buffer>,
     ) -> Result<Id<'repo>, peel::Error> {
-        let oid = self
-            .inner
-            .peel_to_id_in_place_packed(&self.repo.refs, &self.repo.objects, packed)?;
+        let oid = match &self.repo.reftable {
+            Some(store) => store.peel_to_id_in_place(&mut self.inner, &self.repo.objects)?,
+            None => self
+                .inner
+                .peel_to_id_in_place_packed(&self.repo.refs, &self.repo.objects, packed)?,
+        };
         Ok(Id::from_id(oid, self.repo))
     }
 
@@ -101,6 +109,9 @@ impl<'repo> Reference<'repo> {
     /// from [`peel_to_id_in_place()`](Self::peel_to_id_in_place()) where it will point to the first non-tag object.
     #[doc(alias = "peel", alias = "git2")]
     pub fn peel_to_kind(&mut self, kind: gix_object::Kind) -> Result<Object<'repo>, peel::to_kind::Error> {
+        if self.repo.reftable.is_some() {
+            return self.peel_to_kind_packed(kind, None);
+        }
         let packed = self.repo.refs.cached_packed_buffer().map_err(|err| {
             peel::to_kind::Error::FollowToObject(gix_ref::peel::to_object::Error::Follow(
                 file::find::existing::Error::Find(file::find::Error::PackedOpen(err)),
@@ -140,15 +151,14 @@ impl<'repo> Reference<'repo> {
 
     /// Like [`peel_to_kind()`](Self::peel_to_kind), but allows to provide `packed` for best possible performance
     /// when peeling many refs.
+    ///
+    /// `packed` is unused if references are kept in a reftable.
     pub fn peel_to_kind_packed(
         &mut self,
         kind: gix_object::Kind,
         packed: Option<&gix_ref::packed::Buffer>,
     ) -> Result<Object<'repo>, peel::to_kind::Error> {
-        let target = self
-            .inner
-            .follow_to_object_in_place_packed(&self.repo.refs, packed)?
-            .attach(self.repo);
+        let target = self.follow_to_object_in_place_inner(packed)?.attach(self.repo);
         Ok(target.object()?.peel_to_kind(kind)?)
     }
 
@@ -158,6 +168,9 @@ impl<'repo> Reference<'repo> {
     /// a symbolic target ref was looked up from packed-refs.
     #[doc(alias = "resolve", alias = "git2")]
     pub fn follow_to_object(&mut self) -> Result<Id<'repo>, follow::to_object::Error> {
+        if self.repo.reftable.is_some() {
+            return self.follow_to_object_packed(None);
+        }
         let packed = self.repo.refs.cached_packed_buffer().map_err(|err| {
             follow::to_object::Error::FollowToObject(gix_ref::peel::to_object::Error::Follow(
                 file::find::existing::Error::Find(file::find::Error::PackedOpen(err)),
@@ -168,30 +181,46 @@ impl<'repo> Reference<'repo> {
 
     /// Like [`follow_to_object`](Self::follow_to_object), but can be used for repeated calls as it won't
     /// look up `packed` each time, but can reuse it instead.
+    ///
+    /// `packed` is unused if references are kept in a reftable.
     #[doc(alias = "resolve", alias = "git2")]
     pub fn follow_to_object_packed(
         &mut self,
         packed: Option<&gix_ref::packed::Buffer>,
     ) -> Result<Id<'repo>, follow::to_object::Error> {
-        Ok(self
-            .inner
-            .follow_to_object_in_place_packed(&self.repo.refs, packed)?
-            .attach(self.repo))
+        Ok(self.follow_to_object_in_place_inner(packed)?.attach(self.repo))
+    }
+
+    fn follow_to_object_in_place_inner(
+        &mut self,
+        packed: Option<&gix_ref::packed::Buffer>,
+    ) -> Result<gix_hash::ObjectId, gix_ref::peel::to_object::Error> {
+        match &self.repo.reftable {
+            Some(store) => store.follow_to_object_in_place(&mut self.inner),
+            None => self.inner.follow_to_object_in_place_packed(&self.repo.refs, packed),
+        }
     }
 
     /// Follow this symbolic reference one level and return the ref it refers to.
     ///
     /// Returns `None` if this is not a symbolic reference, hence the leaf of the chain.
-    pub fn follow(&self) -> Option<Result<Reference<'repo>, gix_ref::file::find::existing::Error>> {
-        self.inner.follow(&self.repo.refs).map(|res| {
-            res.map(|r| Reference {
-                inner: r,
-                repo: self.repo,
-            })
-        })
+    pub fn follow(&self) -> Option<Result<Reference<'repo>, follow::Error>> {
+        let res = match &self.repo.reftable {
+            Some(store) => {
+                let gix_ref::Target::Symbolic(name) = &self.inner.target else {
+                    return None;
+                };
+                store.find(name.as_ref()).map_err(Into::into)
+            }
+            None => self.inner.follow(&self.repo.refs)?.map_err(Into::into),
+        };
+        Some(res.map(|r| Reference {
+            inner: r,
+            repo: self.repo,
+        }))
     }
 }
 
 mod edits;
-pub use edits::{delete, set_target_id};
+pub use edits::{delete, set_target_id, transaction};
 use gix_ref::file;
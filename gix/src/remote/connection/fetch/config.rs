@@ -1,6 +1,10 @@
 use super::Error;
 use crate::{
-    config::{cache::util::ApplyLeniency, tree::Pack},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{gitoxide, keys, Fetch, Pack, Remote, Section},
+    },
+    remote::fetch::Prune,
     Repository,
 };
 
@@ -14,6 +18,20 @@ pub fn index_threads(repo: &Repository) -> Result<Option<usize>, Error> {
         .with_leniency(repo.options.lenient_config)?)
 }
 
+pub fn resolve_memory_limit(repo: &Repository) -> Result<Option<usize>, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .integer_filter(
+            gitoxide::Objects::RESOLVE_MEMORY_LIMIT,
+            &mut repo.filter_config_section(),
+        )
+        .map(|limit| gitoxide::Objects::RESOLVE_MEMORY_LIMIT.try_into_usize(limit))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)?
+        .filter(|limit| *limit != 0))
+}
+
 pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version, Error> {
     Ok(repo
         .config
@@ -24,3 +42,54 @@ pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version,
         .with_leniency(repo.options.lenient_config)?
         .unwrap_or(gix_pack::index::Version::V2))
 }
+
+/// Obtain the way to prune references after fetching from the remote named `remote_name`, with `remote.<name>.prune`
+/// and `remote.<name>.pruneTags` overriding `fetch.prune` and `fetch.pruneTags` respectively.
+pub fn prune(repo: &Repository, remote_name: Option<&str>) -> Result<Prune, Error> {
+    let boolean = |remote_key: &'static keys::Boolean, fetch_key: &'static keys::Boolean| -> Result<bool, Error> {
+        let config = &repo.config.resolved;
+        remote_name
+            .and_then(|name| {
+                config
+                    .boolean_filter_by(
+                        Remote.name(),
+                        Some(name.into()),
+                        remote_key.name,
+                        &mut repo.filter_config_section(),
+                    )
+                    .map(|res| remote_key.enrich_error(res))
+            })
+            .or_else(|| {
+                config
+                    .boolean_filter(fetch_key, &mut repo.filter_config_section())
+                    .map(|res| fetch_key.enrich_error(res))
+            })
+            .transpose()
+            .with_leniency(repo.options.lenient_config)
+            .map(Option::unwrap_or_default)
+            .map_err(Error::PruneConfig)
+    };
+    Ok(
+        match (
+            boolean(&Remote::PRUNE, &Fetch::PRUNE)?,
+            boolean(&Remote::PRUNE_TAGS, &Fetch::PRUNE_TAGS)?,
+        ) {
+            (false, _) => Prune::Never,
+            (true, false) => Prune::Refs,
+            (true, true) => Prune::RefsAndTags,
+        },
+    )
+}
+
+/// Return `true` if `FETCH_HEAD` should be written after fetching, as configured by `fetch.writeFetchHead`.
+pub fn write_fetch_head(repo: &Repository) -> Result<bool, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter(Fetch::WRITE_FETCH_HEAD, &mut repo.filter_config_section())
+        .map(|res| Fetch::WRITE_FETCH_HEAD.enrich_error(res))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::WriteFetchHeadConfig)?
+        .unwrap_or(true))
+}
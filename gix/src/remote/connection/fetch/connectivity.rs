@@ -0,0 +1,75 @@
+use gix_hash::ObjectId;
+use gix_object::{FindHeader, Kind};
+
+use crate::{remote::fetch, Repository};
+
+/// The error returned by the connectivity check after receiving a pack.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    InitRefsIterator(#[from] crate::reference::iter::init::Error),
+    #[error(transparent)]
+    InitRefsIteratorPlatform(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ObtainRefDuringIteration(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    ShallowOpen(#[from] crate::shallow::open::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error("{} object(s) reachable from the fetched references are missing, like {} {}", missing.len(), missing[0].1, missing[0].0)]
+    MissingObjects {
+        /// All missing objects along with the kind they are expected to have, in the order they were encountered.
+        missing: Vec<(ObjectId, Kind)>,
+    },
+}
+
+/// Assure that all objects reachable from the remote objects in `mappings` are present in `repo`, without traversing
+/// objects that are reachable from the references of `repo` as these are known to be connected.
+/// Parents of shallow commits aren't traversed either.
+pub(crate) fn check(repo: &Repository, mappings: &[fetch::Mapping]) -> Result<(), Error> {
+    let mut missing = Vec::new();
+    let mut check = gix_fsck::Connectivity::new(&repo.objects, |id: &ObjectId, kind| missing.push((*id, kind)));
+
+    for reference in repo.references()?.all()? {
+        if let Some(id) = reference?.try_id() {
+            check.mark_connected(id.detach());
+        }
+    }
+    if let Some(shallow_commits) = repo.shallow_commits()? {
+        let mut buf = Vec::new();
+        for shallow_commit in shallow_commits.iter() {
+            if let Ok(commit) = gix_object::FindExt::find_commit(&repo.objects, shallow_commit, &mut buf) {
+                for parent in commit.parents() {
+                    check.mark_connected(parent);
+                }
+            }
+        }
+    }
+
+    for mapping in mappings {
+        let (tip, expected_kind) = match &mapping.remote {
+            fetch::Source::ObjectId(id) => (*id, Kind::Commit),
+            fetch::Source::Ref(r) => match r {
+                gix_protocol::handshake::Ref::Peeled { tag, .. }
+                | gix_protocol::handshake::Ref::Symbolic { tag: Some(tag), .. } => (*tag, Kind::Tag),
+                gix_protocol::handshake::Ref::Direct { object, .. }
+                | gix_protocol::handshake::Ref::Symbolic { object, .. } => (*object, Kind::Commit),
+                gix_protocol::handshake::Ref::Unborn { .. } => continue,
+            },
+        };
+        let kind = repo
+            .objects
+            .try_header(&tip)
+            .ok()
+            .flatten()
+            .map_or(expected_kind, |header| header.kind);
+        check.check_reachable(&tip, kind)?;
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::MissingObjects { missing })
+    }
+}
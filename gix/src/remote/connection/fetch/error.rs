@@ -17,6 +17,11 @@ pub enum Error {
     },
     #[error(transparent)]
     Negotiate(#[from] super::negotiate::Error),
+    #[error("The connectivity check of the received objects failed")]
+    Connectivity(#[from] super::connectivity::Error),
+    #[cfg(feature = "maintenance")]
+    #[error("Automatic maintenance after fetching failed")]
+    Maintenance(#[from] crate::maintenance::auto::Error),
     #[error(transparent)]
     Client(#[from] gix_protocol::transport::client::Error),
     #[error(transparent)]
@@ -43,6 +48,16 @@ pub enum Error {
     RejectShallowRemoteConfig(#[from] config::boolean::Error),
     #[error("Receiving objects from shallow remotes is prohibited due to the value of `clone.rejectShallow`")]
     RejectShallowRemote,
+    #[error("Could not obtain configuration to learn which references to prune")]
+    PruneConfig(#[source] config::boolean::Error),
+    #[error("Could not obtain configuration to learn if FETCH_HEAD should be written")]
+    WriteFetchHeadConfig(#[source] config::boolean::Error),
+    #[error("Could not find HEAD to learn which fetched objects should be marked for merging in FETCH_HEAD")]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error("Could not obtain the branch to merge with to write FETCH_HEAD")]
+    BranchMergeRef(#[from] crate::repository::branch_remote_ref_name::Error),
+    #[error("Could not write FETCH_HEAD")]
+    WriteFetchHead(#[from] gix_ref::file::pseudo::write::Error),
     #[error(transparent)]
     NegotiationAlgorithmConfig(#[from] config::key::GenericErrorWithValue),
     #[error("Failed to read remaining bytes in stream")]
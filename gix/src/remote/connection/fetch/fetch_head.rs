@@ -0,0 +1,172 @@
+use gix_ref::file::pseudo::fetch_head::Entry;
+
+use super::{refs, Error};
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    remote,
+    remote::fetch,
+    Remote,
+};
+
+/// Write all objects fetched by `mappings` into `FETCH_HEAD`, with the objects to merge first, just like `git fetch` does.
+///
+/// An object is to be merged if…
+///
+/// * …it was fetched with one of the `extra_refspecs`, which correspond to refspecs passed on the command-line, except for the
+///   implicit refspec to fetch tags.
+/// * …the current branch has `branch.<name>.remote` set to `remote`, and its `branch.<name>.merge` names the remote reference.
+/// * …no current branch has a merge configuration for `remote`, and it was fetched by its first refspec which isn't a pattern.
+///
+/// Mappings whose object wasn't received, like implicit tags, aren't written.
+pub fn write(
+    remote: &Remote<'_>,
+    mappings: &[fetch::Mapping],
+    extra_refspecs: &[gix_refspec::RefSpec],
+    update_refs: &refs::update::Outcome,
+) -> Result<(), Error> {
+    let repo = remote.repo;
+    let refspecs = remote.refspecs(remote::Direction::Fetch);
+    let implicit_tag_refspec = remote.fetch_tags.to_refspec();
+    let merge_ref = merge_ref_of_current_branch(remote)?;
+    let url = remote
+        .url(remote::Direction::Fetch)
+        .map(anonymized_url)
+        .unwrap_or_default();
+
+    let mut for_merge = Vec::new();
+    let mut not_for_merge = Vec::new();
+    let mut seen = std::collections::BTreeSet::new();
+    for (update, mapping, spec, _edit) in update_refs.iter_mapping_updates(mappings, refspecs, extra_refspecs) {
+        if matches!(
+            update.mode,
+            refs::update::Mode::ImplicitTagNotSentByRemote | refs::update::Mode::RejectedSourceObjectNotFound { .. }
+        ) {
+            continue;
+        }
+        let Some(id) = mapping.remote.as_id() else {
+            continue;
+        };
+        let name = match mapping.remote.as_name() {
+            Some(name) => name.to_owned(),
+            None => id.to_hex().to_string().into(),
+        };
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        let is_for_merge = match mapping.spec_index {
+            fetch::SpecIndex::Implicit(_) => spec.map_or(false, |spec| Some(spec.to_ref()) != implicit_tag_refspec),
+            fetch::SpecIndex::ExplicitInRemote(idx) => match &merge_ref {
+                Some(merge_ref) => name == merge_ref.as_bstr(),
+                None => {
+                    idx == 0
+                        && spec.map_or(false, |spec| {
+                            spec.to_ref().source().map_or(false, |src| !src.contains(&b'*'))
+                        })
+                }
+            },
+        };
+        let entry = Entry {
+            id: id.to_owned(),
+            for_merge: is_for_merge,
+            description: describe(name.as_ref(), url.as_ref()),
+        };
+        if is_for_merge {
+            for_merge.push(entry);
+        } else {
+            not_for_merge.push(entry);
+        }
+    }
+
+    repo.refs
+        .write_fetch_head(
+            for_merge.iter().chain(not_for_merge.iter()),
+            gix_lock::acquire::Fail::Immediately,
+        )
+        .map_err(Into::into)
+}
+
+/// Return the name of the remote reference configured via `branch.<name>.merge` for the currently checked out branch,
+/// if its `branch.<name>.remote` is `remote`.
+fn merge_ref_of_current_branch(remote: &Remote<'_>) -> Result<Option<gix_ref::FullName>, Error> {
+    let repo = remote.repo;
+    let Some(head_name) = repo.head_name()? else {
+        return Ok(None);
+    };
+    let is_configured_for_remote = match (
+        repo.branch_remote_name(head_name.shorten(), remote::Direction::Fetch),
+        remote.name(),
+    ) {
+        (Some(branch_remote), Some(remote)) => branch_remote.as_bstr() == remote.as_bstr(),
+        _ => false,
+    };
+    if !is_configured_for_remote {
+        return Ok(None);
+    }
+    Ok(repo
+        .branch_remote_ref_name(head_name.as_ref(), remote::Direction::Fetch)
+        .transpose()?
+        .map(std::borrow::Cow::into_owned))
+}
+
+/// Produce the description of a fetched object like `branch 'main' of https://example.com/repo`, based on the `name` of the
+/// reference on the remote.
+fn describe(name: &BStr, url: &BStr) -> BString {
+    let (kind, what) = if name == "HEAD" {
+        ("", "".into())
+    } else if let Some(what) = name.strip_prefix(b"refs/heads/") {
+        ("branch ", what.as_bstr())
+    } else if let Some(what) = name.strip_prefix(b"refs/tags/") {
+        ("tag ", what.as_bstr())
+    } else if let Some(what) = name.strip_prefix(b"refs/remotes/") {
+        ("remote-tracking branch ", what.as_bstr())
+    } else {
+        ("", name)
+    };
+    let mut out = BString::default();
+    if !what.is_empty() {
+        out.push_str(kind);
+        out.push_str("'");
+        out.push_str(what);
+        out.push_str("' of ");
+    }
+    out.push_str(url);
+    out
+}
+
+/// Return `url` without user information and without trailing slashes or `.git` suffix.
+fn anonymized_url(url: &gix_url::Url) -> BString {
+    let mut url = url.clone();
+    url.set_user(None);
+    url.set_password(None);
+    let mut url = url.to_bstring();
+    while url.last() == Some(&b'/') {
+        url.pop();
+    }
+    let len = url.len();
+    if len > 4 && url.ends_with(b".git") {
+        url.truncate(len - 4);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::describe;
+
+    #[test]
+    fn describe_matches_git() {
+        let url = "https://example.com/repo".into();
+        for (name, expected) in [
+            ("HEAD", "https://example.com/repo"),
+            ("refs/heads/main", "branch 'main' of https://example.com/repo"),
+            ("refs/tags/v1.0", "tag 'v1.0' of https://example.com/repo"),
+            (
+                "refs/remotes/origin/main",
+                "remote-tracking branch 'origin/main' of https://example.com/repo",
+            ),
+            ("refs/notes/commits", "'refs/notes/commits' of https://example.com/repo"),
+        ] {
+            assert_eq!(describe(name.into(), url), expected);
+        }
+    }
+}
@@ -137,6 +137,9 @@ impl From<ProgressId> for gix_features::progress::Id {
 
 pub(crate) mod negotiate;
 
+///
+pub mod connectivity;
+
 ///
 pub mod prepare {
     /// The error returned by [`prepare_fetch()`][super::Connection::prepare_fetch()].
@@ -185,14 +188,20 @@ where
         if self.remote.refspecs(remote::Direction::Fetch).is_empty() && options.extra_refspecs.is_empty() {
             return Err(prepare::Error::MissingRefSpecs);
         }
-        let ref_map = self.ref_map_inner(progress, options).await?;
+        let (ref_map, _) = self.ref_map_inner(progress, options, false).await?;
         Ok(Prepare {
             con: Some(self),
             ref_map,
             dry_run: DryRun::No,
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
+            prune: None,
+            write_fetch_head: None,
             shallow: Default::default(),
+            check_connectivity: false,
+            max_pack_size: None,
+            #[cfg(feature = "maintenance")]
+            run_maintenance: false,
         })
     }
 }
@@ -208,6 +217,7 @@ where
 }
 
 mod config;
+mod fetch_head;
 mod receive_pack;
 ///
 #[path = "update_refs/mod.rs"]
@@ -223,7 +233,13 @@ where
     dry_run: DryRun,
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
+    prune: Option<remote::fetch::Prune>,
+    write_fetch_head: Option<bool>,
     shallow: remote::fetch::Shallow,
+    check_connectivity: bool,
+    max_pack_size: Option<u64>,
+    #[cfg(feature = "maintenance")]
+    run_maintenance: bool,
 }
 
 /// Builder
@@ -252,6 +268,24 @@ where
         self
     }
 
+    /// Define which local references to remove after fetching as they don't exist on the remote anymore, overriding
+    /// `remote.<name>.prune`, `remote.<name>.pruneTags`, `fetch.prune` and `fetch.pruneTags`.
+    ///
+    /// References are removed in the same transaction that updates all other references.
+    pub fn with_prune(mut self, prune: remote::fetch::Prune) -> Self {
+        self.prune = Some(prune);
+        self
+    }
+
+    /// If enabled, write all fetched objects to `FETCH_HEAD` after updating references, with those that `git pull` would merge
+    /// listed first and all others marked as `not-for-merge`. This overrides `fetch.writeFetchHead`, which is enabled by default.
+    ///
+    /// Nothing is written in [dry-run mode](Self::with_dry_run()).
+    pub fn with_write_fetch_head(mut self, enabled: bool) -> Self {
+        self.write_fetch_head = Some(enabled);
+        self
+    }
+
     /// Set the reflog message to use when updating refs after fetching a pack.
     pub fn with_reflog_message(mut self, reflog_message: RefLogMessage) -> Self {
         self.reflog_message = reflog_message.into();
@@ -265,6 +299,35 @@ where
         self.shallow = shallow;
         self
     }
+
+    /// If enabled, assure that all objects reachable from the fetched references are present after receiving the pack,
+    /// and fail with a list of missing objects before any reference is updated otherwise.
+    ///
+    /// Objects reachable from existing references are assumed to be present and aren't traversed.
+    /// This is disabled by default.
+    pub fn with_connectivity_check(mut self, enabled: bool) -> Self {
+        self.check_connectivity = enabled;
+        self
+    }
+
+    /// If set, fail once the pack sent by the remote exceeds the given amount of bytes, before any reference is updated.
+    ///
+    /// This protects against remotes sending more data than expected, and is unset by default.
+    pub fn with_max_pack_size(mut self, max_bytes: Option<u64>) -> Self {
+        self.max_pack_size = max_bytes;
+        self
+    }
+
+    /// If enabled, run all [maintenance tasks](crate::maintenance::Task) that are due after a pack was received and
+    /// references were updated, similar to what `git fetch` does unless `--no-auto-maintenance` is given.
+    ///
+    /// See [`Repository::maintenance_auto()`](crate::Repository::maintenance_auto()) for details.
+    /// This is disabled by default.
+    #[cfg(feature = "maintenance")]
+    pub fn with_auto_maintenance(mut self, enabled: bool) -> Self {
+        self.run_maintenance = enabled;
+        self
+    }
 }
 
 impl<'remote, 'repo, T> Drop for Prepare<'remote, 'repo, T>
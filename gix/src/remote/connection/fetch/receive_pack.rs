@@ -16,7 +16,7 @@ use crate::{
     },
     remote,
     remote::{
-        connection::fetch::config,
+        connection::fetch::{config, connectivity, fetch_head},
         fetch,
         fetch::{
             negotiate, negotiate::Algorithm, outcome, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage,
@@ -71,6 +71,11 @@ where
     /// ### Configuration
     ///
     /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
+    /// - `remote.<name>.prune` and `remote.<name>.pruneTags`, or `fetch.prune` and `fetch.pruneTags` as fallback, are read to learn
+    ///   which references to remove if they don't exist on the remote anymore, unless [`with_prune()`](Self::with_prune()) was called.
+    /// - `fetch.writeFetchHead` is read to learn if `FETCH_HEAD` should be written, unless
+    ///   [`with_write_fetch_head()`](Self::with_write_fetch_head()) was called. `branch.<name>.remote` and `branch.<name>.merge`
+    ///   of the current branch determine which of its entries aren't marked `not-for-merge`.
     ///
     #[gix_protocol::maybe_async::maybe_async]
     pub async fn receive<P>(self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
@@ -270,9 +275,13 @@ where
 
                 let options = gix_pack::bundle::write::Options {
                     thread_limit: config::index_threads(repo)?,
+                    memory_limit: config::resolve_memory_limit(repo)?,
                     index_version: config::pack_index_version(repo)?,
                     iteration_mode: gix_pack::data::input::Mode::Verify,
                     object_hash: con.remote.repo.object_hash(),
+                    max_pack_size: self.max_pack_size,
+                    fsync_pack: repo.config.fsync.method_for(gix_fs::fsync::Component::Pack),
+                    fsync_pack_metadata: repo.config.fsync.method_for(gix_fs::fsync::Component::PackMetadata),
                 };
 
                 let write_pack_bundle = if matches!(self.dry_run, fetch::DryRun::No) {
@@ -329,6 +338,14 @@ where
             }
         };
 
+        if self.check_connectivity && matches!(self.dry_run, fetch::DryRun::No) {
+            connectivity::check(repo, &self.ref_map.mappings)?;
+        }
+
+        let prune = match self.prune {
+            Some(prune) => prune,
+            None => config::prune(repo, con.remote.name().and_then(remote::Name::as_symbol))?,
+        };
         let update_refs = refs::update(
             repo,
             self.reflog_message
@@ -338,10 +355,24 @@ where
             con.remote.refspecs(remote::Direction::Fetch),
             &self.ref_map.extra_refspecs,
             con.remote.fetch_tags,
+            prune,
             self.dry_run,
             self.write_packed_refs,
         )?;
 
+        let write_fetch_head = match self.write_fetch_head {
+            Some(enabled) => enabled,
+            None => config::write_fetch_head(repo)?,
+        };
+        if write_fetch_head && matches!(self.dry_run, fetch::DryRun::No) {
+            fetch_head::write(
+                con.remote,
+                &self.ref_map.mappings,
+                &self.ref_map.extra_refspecs,
+                &update_refs,
+            )?;
+        }
+
         if let Some(bundle) = write_pack_bundle.as_mut() {
             if !update_refs.edits.is_empty() || bundle.index.num_objects == 0 {
                 if let Some(path) = bundle.keep_path.take() {
@@ -350,6 +381,11 @@ where
             }
         }
 
+        #[cfg(feature = "maintenance")]
+        if self.run_maintenance && write_pack_bundle.is_some() && matches!(self.dry_run, fetch::DryRun::No) {
+            repo.maintenance_auto(progress, should_interrupt)?;
+        }
+
         let out = Outcome {
             ref_map: std::mem::take(&mut self.ref_map),
             status: match write_pack_bundle {
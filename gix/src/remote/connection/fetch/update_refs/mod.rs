@@ -1,14 +1,16 @@
 #![allow(clippy::result_large_err)]
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
 
-use gix_object::Exists;
+use gix_object::{bstr::ByteSlice, Exists};
 use gix_ref::{
     transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
     Target, TargetRef,
 };
 
 use crate::{
-    ext::ObjectIdExt,
     remote::{
         fetch,
         fetch::{
@@ -49,6 +51,8 @@ impl From<Mode> for Update {
 /// `repo` is not actually changed. Also it won't perform an 'object exists' check as these are likely not to exist as the pack
 /// wasn't fetched either.
 /// `action` is the prefix used for reflog entries, and is typically "fetch".
+/// Local refs that don't exist on the remote anymore are deleted according to `prune`, in the same transaction that applies
+/// all updates.
 ///
 /// It can be used to produce typical information that one is used to from `git fetch`.
 ///
@@ -67,6 +71,7 @@ pub(crate) fn update(
     refspecs: &[gix_refspec::RefSpec],
     extra_refspecs: &[gix_refspec::RefSpec],
     fetch_tags: fetch::Tags,
+    prune: fetch::Prune,
     dry_run: fetch::DryRun,
     write_packed_refs: fetch::WritePackedRefs,
 ) -> Result<update::Outcome, update::Error> {
@@ -78,6 +83,8 @@ pub(crate) fn update(
     let implicit_tag_refspec = fetch_tags
         .to_refspec()
         .filter(|_| matches!(fetch_tags, crate::remote::fetch::Tags::Included));
+    let commit_graph = repo.commit_graph_if_enabled()?;
+    let mut oracle = gix_revision::reachability::Oracle::new(&repo.objects, commit_graph.as_ref());
     for (remote, local, spec, is_implicit_tag) in mappings.iter().filter_map(
         |fetch::Mapping {
              remote,
@@ -150,28 +157,12 @@ pub(crate) fn update(
                                     let mut force = spec.allow_non_fast_forward();
                                     let is_fast_forward = match dry_run {
                                         fetch::DryRun::No => {
-                                            let ancestors = repo
-                                                .find_object(local_id)?
-                                                .try_into_commit()
-                                                .map_err(|_| ())
-                                                .and_then(|c| {
-                                                    c.committer().map(|a| a.time.seconds).map_err(|_| ())
-                                                }).and_then(|local_commit_time|
-                                                remote_id
-                                                    .to_owned()
-                                                    .ancestors(&repo.objects)
-                                                    .sorting(
-                                                        gix_traverse::commit::simple::Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
-                                                            seconds: local_commit_time
-                                                        },
-                                                    )
-                                                    .map_err(|_| ())
-                                            );
-                                            match ancestors {
-                                                Ok(mut ancestors) => {
-                                                    ancestors.any(|cid| cid.map_or(false, |c| c.id == local_id))
-                                                }
-                                                Err(_) => {
+                                            let local_is_commit = repo.find_object(local_id)?.try_into_commit().is_ok();
+                                            match local_is_commit
+                                                .then(|| oracle.is_ancestor(local_id, remote_id.to_owned()))
+                                            {
+                                                Some(Ok(is_ancestor)) => is_ancestor,
+                                                None | Some(Err(_)) => {
                                                     force = true;
                                                     false
                                                 }
@@ -308,22 +299,41 @@ pub(crate) fn update(
                     }
                     _ => unreachable!("at this point it can only be one variant"),
                 },
-                Change::Delete { .. } => {
+                Change::Delete { .. } | Change::Rename { .. } | Change::Copy { .. } => {
                     unreachable!("we don't do that here")
                 }
             };
         }
     }
 
+    let mut pruned = Vec::new();
+    if prune != fetch::Prune::Never {
+        for (name, id) in stale_refs(repo, mappings, refspecs, extra_refspecs, fetch_tags, prune)? {
+            edits.push(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(Target::Object(id)),
+                    log: RefLog::AndReference,
+                },
+                name: name.clone(),
+                deref: false,
+            });
+            pruned.push(name);
+        }
+    }
+
     let edits = match dry_run {
+        // A reftable has no notion of packed references, all edits go into a new table.
+        fetch::DryRun::No if repo.reftable.is_some() => {
+            let _span = gix_trace::detail!("apply", edits = edits.len());
+            repo.edit_references(edits)?
+        }
         fetch::DryRun::No => {
             let _span = gix_trace::detail!("apply", edits = edits.len());
             let (file_lock_fail, packed_refs_lock_fail) = repo
                 .config
                 .lock_timeout()
                 .map_err(crate::reference::edit::Error::from)?;
-            repo.refs
-                .transaction()
+            repo.ref_transaction()?
                 .packed_refs(
                     match write_packed_refs {
                         fetch::WritePackedRefs::Only => {
@@ -339,7 +349,90 @@ pub(crate) fn update(
         fetch::DryRun::Yes => edits,
     };
 
-    Ok(update::Outcome { edits, updates })
+    Ok(update::Outcome { edits, updates, pruned })
+}
+
+/// Return the names and ids of all local refs that are destinations of `refspecs` and `extra_refspecs`, but whose source doesn't exist
+/// on the remote anymore as no mapping in `mappings` points to them, for deletion according to `prune`.
+///
+/// Like in `git`, symbolic refs are never stale, nor are refs whose source is excluded by a negative refspec.
+fn stale_refs(
+    repo: &Repository,
+    mappings: &[fetch::Mapping],
+    refspecs: &[gix_refspec::RefSpec],
+    extra_refspecs: &[gix_refspec::RefSpec],
+    fetch_tags: fetch::Tags,
+    prune: fetch::Prune,
+) -> Result<Vec<(gix_ref::FullName, gix_hash::ObjectId)>, update::Error> {
+    let implicit_tag_refspec = fetch_tags.to_refspec();
+    let specs: Vec<_> = refspecs
+        .iter()
+        .chain(extra_refspecs)
+        .map(gix_refspec::RefSpec::to_ref)
+        .filter(|spec| prune == fetch::Prune::RefsAndTags || Some(*spec) != implicit_tag_refspec)
+        .collect();
+    // Swap source and destination to learn which remote ref a local ref would be fetched from.
+    let reversed_specs: Vec<_> = specs
+        .iter()
+        .filter_map(|spec| match spec.instruction() {
+            gix_refspec::Instruction::Fetch(gix_refspec::instruction::Fetch::AndUpdate { src, dst, .. }) => {
+                let mut reversed = dst.to_owned();
+                reversed.push(b':');
+                reversed.extend_from_slice(src);
+                gix_refspec::parse(reversed.as_bstr(), gix_refspec::parse::Operation::Fetch)
+                    .ok()
+                    .map(|spec| spec.to_owned())
+            }
+            _ => None,
+        })
+        .collect();
+    if reversed_specs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut local_refs = Vec::new();
+    for reference in repo.references()?.all()? {
+        let reference = reference.map_err(update::Error::IterReferences)?;
+        if let Some(id) = reference.target().try_id() {
+            local_refs.push((reference.name().to_owned(), id.to_owned()));
+        }
+    }
+    let sources = gix_refspec::MatchGroup::from_fetch_specs(reversed_specs.iter().map(gix_refspec::RefSpec::to_ref))
+        .match_remotes(local_refs.iter().map(|(name, id)| gix_refspec::match_group::Item {
+            full_ref_name: name.as_bstr(),
+            target: id,
+            object: None,
+        }))
+        .mappings
+        .into_iter()
+        .filter_map(|m| m.item_index.zip(m.rhs))
+        .collect::<Vec<_>>();
+
+    // Match the sources with the original refspecs to apply negative refspecs, while assuring they map to the local ref we started from.
+    let null_id = repo.object_hash().null();
+    let fetched: BTreeSet<_> = mappings
+        .iter()
+        .filter_map(|m| m.local.as_ref().map(|name| name.as_bstr()))
+        .collect();
+    let mut stale = BTreeSet::new();
+    for m in gix_refspec::MatchGroup::from_fetch_specs(specs)
+        .match_remotes(sources.iter().map(|(_, source)| gix_refspec::match_group::Item {
+            full_ref_name: source.as_ref(),
+            target: &null_id,
+            object: None,
+        }))
+        .mappings
+    {
+        let (Some(source_index), Some(destination)) = (m.item_index, m.rhs) else {
+            continue;
+        };
+        let local_index = sources[source_index].0;
+        let local_name = local_refs[local_index].0.as_bstr();
+        if local_name == destination.as_ref() && !fetched.contains(local_name) {
+            stale.insert(local_index);
+        }
+    }
+    Ok(stale.into_iter().map(|idx| local_refs[idx].clone()).collect())
 }
 
 /// Figure out if target of `edit` points to a reference that doesn't exist in `repo` and won't exist as it's not in any of `edits`.
@@ -359,7 +452,7 @@ fn update_needs_adjustment_as_edits_symbolic_target_is_missing(
                             if current_target_name.as_ref() == new_target_ref {
                                 return false; // no-op are always fine
                             }
-                            let current_is_unborn = repo.refs.try_find(current_target_name).ok().flatten().is_none();
+                            let current_is_unborn = repo.try_find_reference(current_target_name).ok().flatten().is_none();
                             if current_is_unborn {
                                 return false;
                             }
@@ -370,11 +463,11 @@ fn update_needs_adjustment_as_edits_symbolic_target_is_missing(
                         unreachable!("BUG: we don't do that here")
                     }
                 },
-                Change::Delete { .. } => {
-                    unreachable!("we don't ever delete here")
+                Change::Delete { .. } | Change::Rename { .. } | Change::Copy { .. } => {
+                    unreachable!("we don't ever delete, rename or copy here")
                 }
             };
-            let target_ref_exists_locally = repo.refs.try_find(new_target_ref).ok().flatten().is_some();
+            let target_ref_exists_locally = repo.try_find_reference(new_target_ref).ok().flatten().is_some();
             if target_ref_exists_locally {
                 return false;
             }
@@ -151,6 +151,7 @@ mod update {
                 &specs,
                 &[],
                 fetch::Tags::None,
+                fetch::Prune::Never,
                 reflog_message.map_or(fetch::DryRun::No, |_| fetch::DryRun::Yes),
                 fetch::WritePackedRefs::Never,
             )
@@ -215,6 +216,7 @@ mod update {
                 &specs,
                 &[],
                 fetch::Tags::None,
+                fetch::Prune::Never,
                 fetch::DryRun::Yes,
                 fetch::WritePackedRefs::Never,
             )?;
@@ -247,6 +249,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )?;
@@ -274,6 +277,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )?;
@@ -314,6 +318,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )?;
@@ -365,6 +370,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )?;
@@ -413,6 +419,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )?;
@@ -443,6 +450,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )?;
@@ -587,6 +595,7 @@ mod update {
                 &specs,
                 &[],
                 fetch::Tags::None,
+                fetch::Prune::Never,
                 fetch::DryRun::Yes,
                 fetch::WritePackedRefs::Never,
             )
@@ -619,6 +628,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )
@@ -664,6 +674,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )
@@ -691,6 +702,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )
@@ -742,6 +754,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )
@@ -792,6 +805,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::Yes,
             fetch::WritePackedRefs::Never,
         )
@@ -827,6 +841,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::No,
             fetch::WritePackedRefs::Never,
         )
@@ -850,6 +865,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::No,
             fetch::WritePackedRefs::Never,
         )
@@ -884,6 +900,7 @@ mod update {
             &specs,
             &[],
             fetch::Tags::None,
+            fetch::Prune::Never,
             fetch::DryRun::No,
             fetch::WritePackedRefs::Never,
         )
@@ -907,12 +924,121 @@ mod update {
         }
     }
 
+    #[test]
+    fn refs_missing_on_the_remote_are_pruned_in_the_same_transaction_unless_excluded() -> Result {
+        let (repo, _tmp) = repo_rw("two-origins");
+        let (mappings, specs) = mapping_from_specs(&["refs/heads/*:refs/remotes/origin/*", "^refs/heads/a"], &repo);
+        let out = fetch::refs::update(
+            &repo,
+            prefixed("action"),
+            &mappings,
+            &specs,
+            &[],
+            fetch::Tags::None,
+            fetch::Prune::Refs,
+            fetch::DryRun::No,
+            fetch::WritePackedRefs::Never,
+        )?;
+
+        let expected_pruned: Vec<_> = "bcdefghij"
+            .chars()
+            .map(|c| gix_ref::FullName::try_from(format!("refs/remotes/origin/{c}")).expect("valid"))
+            .collect();
+        assert_eq!(
+            out.pruned, expected_pruned,
+            "refs/remotes/origin/a is excluded by the negative refspec, and symbolic refs are never pruned"
+        );
+        assert_eq!(
+            out.updates.len(),
+            mappings.len(),
+            "updates still correspond to mappings"
+        );
+        let deletions = out
+            .edits
+            .iter()
+            .filter(|edit| matches!(edit.change, Change::Delete { .. }))
+            .count();
+        assert_eq!(deletions, expected_pruned.len());
+        for name in &expected_pruned {
+            assert!(repo.try_find_reference(name.as_ref())?.is_none(), "{name} was deleted");
+        }
+        for name in [
+            "refs/remotes/origin/a",
+            "refs/remotes/origin/HEAD",
+            "refs/remotes/origin/main",
+        ] {
+            assert!(repo.try_find_reference(name)?.is_some(), "{name} was kept");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn implicitly_fetched_tags_are_only_pruned_if_configured() -> Result {
+        let repo = repo("two-origins");
+        let (mut mappings, mut specs) = mapping_from_specs(
+            &["refs/heads/*:refs/remotes/origin/*", "refs/tags/*:refs/tags/*"],
+            &repo,
+        );
+        let extra_refspecs = vec![specs.pop().expect("tag spec")];
+        mappings.retain(|m| m.local.as_ref().map_or(true, |name| name != "refs/tags/b-tag"));
+        for m in &mut mappings {
+            if m.spec_index == SpecIndex::ExplicitInRemote(1) {
+                m.spec_index = SpecIndex::Implicit(0);
+            }
+        }
+
+        for (prune, expected) in [
+            (fetch::Prune::Never, None),
+            (fetch::Prune::Refs, None),
+            (fetch::Prune::RefsAndTags, Some("refs/tags/b-tag")),
+        ] {
+            let out = fetch::refs::update(
+                &repo,
+                prefixed("action"),
+                &mappings,
+                &specs,
+                &extra_refspecs,
+                fetch::Tags::Included,
+                prune,
+                fetch::DryRun::Yes,
+                fetch::WritePackedRefs::Never,
+            )?;
+            let pruned_tags: Vec<_> = out
+                .pruned
+                .iter()
+                .filter(|name| name.category() == Some(gix_ref::Category::Tag))
+                .map(|name| name.as_bstr().to_string())
+                .collect();
+            assert_eq!(
+                pruned_tags,
+                expected.into_iter().map(ToOwned::to_owned).collect::<Vec<_>>(),
+                "{prune:?}"
+            );
+            assert_eq!(
+                out.pruned.is_empty(),
+                prune == fetch::Prune::Never,
+                "remote-tracking branches are pruned as well"
+            );
+        }
+        Ok(())
+    }
+
     fn mapping_from_spec(
         spec: &str,
         remote_repo: &gix::Repository,
     ) -> (Vec<fetch::Mapping>, Vec<gix::refspec::RefSpec>) {
-        let spec = gix_refspec::parse(spec.into(), gix_refspec::parse::Operation::Fetch).unwrap();
-        let group = gix_refspec::MatchGroup::from_fetch_specs(Some(spec));
+        mapping_from_specs(&[spec], remote_repo)
+    }
+
+    fn mapping_from_specs(
+        specs: &[&str],
+        remote_repo: &gix::Repository,
+    ) -> (Vec<fetch::Mapping>, Vec<gix::refspec::RefSpec>) {
+        let specs: Vec<_> = specs
+            .iter()
+            .map(|spec| gix_refspec::parse((*spec).into(), gix_refspec::parse::Operation::Fetch).unwrap())
+            .collect();
+        let group = gix_refspec::MatchGroup::from_fetch_specs(specs.iter().copied());
         let references = remote_repo.references().unwrap();
         let mut references: Vec<_> = references.all().unwrap().map(|r| into_remote_ref(r.unwrap())).collect();
         references.push(into_remote_ref(remote_repo.find_reference("HEAD").unwrap()));
@@ -932,7 +1058,7 @@ mod update {
                 spec_index: SpecIndex::ExplicitInRemote(m.spec_index),
             })
             .collect();
-        (mappings, vec![spec.to_owned()])
+        (mappings, specs.iter().map(gix_refspec::RefSpecRef::to_owned).collect())
     }
 
     fn into_remote_ref(mut r: gix::Reference<'_>) -> gix_protocol::handshake::Ref {
@@ -17,12 +17,20 @@ mod error {
         WorktreeListing(#[from] std::io::Error),
         #[error("Could not open worktree repository")]
         OpenWorktreeRepo(#[from] crate::open::Error),
+        #[error("Could not open the commit-graph for the fast-forward ancestor check")]
+        OpenCommitGraph(#[from] crate::repository::commit_graph_if_enabled::Error),
         #[error("Could not find local commit for fast-forward ancestor check")]
         FindCommit(#[from] crate::object::find::existing::Error),
         #[error("Could not peel symbolic local reference to its ID")]
         PeelToId(#[from] crate::reference::peel::Error),
         #[error("Failed to follow a symbolic reference to assure worktree isn't affected")]
-        FollowSymref(#[from] gix_ref::file::find::existing::Error),
+        FollowSymref(#[from] crate::reference::follow::Error),
+        #[error(transparent)]
+        OpenReferences(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        IterReferencesInit(#[from] crate::reference::iter::init::Error),
+        #[error("Could not read a reference to learn if it should be pruned")]
+        IterReferences(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
     }
 }
 
@@ -37,6 +45,9 @@ pub struct Outcome {
     /// Use [`iter_mapping_updates()`][Self::iter_mapping_updates()] to recombine the update information with ref-edits and their
     /// mapping.
     pub updates: Vec<super::Update>,
+    /// The names of all local refs that were deleted as they don't exist on the remote anymore, as configured by
+    /// [`Prune`](crate::remote::fetch::Prune). Their deletions are also contained in `edits`.
+    pub pruned: Vec<gix_ref::FullName>,
 }
 
 /// Describe the way a ref was updated, with particular focus on how the (peeled) target commit was affected.
@@ -1,5 +1,6 @@
 use crate::Remote;
 
+#[derive(Debug, Clone)]
 pub(crate) struct HandshakeWithRefs {
     outcome: gix_protocol::handshake::Outcome,
     refs: Vec<gix_protocol::handshake::Ref>,
@@ -1,4 +1,8 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use gix_features::progress::Progress;
 use gix_protocol::transport::client::Transport;
@@ -59,6 +63,12 @@ pub struct Options {
     ///
     /// This is useful for handling `remote.<name>.tagOpt` for example.
     pub extra_refspecs: Vec<gix_refspec::RefSpec>,
+    /// If set, [`Connection::ref_map()`] will use the references advertised by the remote from this cache if they
+    /// were listed recently enough, without contacting the remote. Fetches always contact the remote, but
+    /// keep the cache up to date.
+    ///
+    /// Defaults to `None`.
+    pub cache: Option<Cache>,
 }
 
 impl Default for Options {
@@ -67,10 +77,70 @@ impl Default for Options {
             prefix_from_spec_as_filter_on_remote: true,
             handshake_parameters: Vec::new(),
             extra_refspecs: Vec::new(),
+            cache: None,
+        }
+    }
+}
+
+/// A cache for the references advertised by remotes, shared among all of its clones, to avoid contacting the same remote
+/// over and over again when listing its references, for use in [`Options::cache`].
+///
+/// Entries are keyed by the remote URL, the configured protocol version and everything else that affects which
+/// references are advertised, and are used until they are older than the time-to-live of the cache.
+/// Note that entries aren't revalidated with the remote.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    time_to_live: Duration,
+    entries: Arc<Mutex<HashMap<CacheKey, (Instant, HandshakeWithRefs)>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    url: BString,
+    protocol: Option<gix_protocol::transport::Protocol>,
+    filter_by_prefix: bool,
+    handshake_parameters: Vec<(String, Option<String>)>,
+    refspecs: Vec<gix_refspec::RefSpec>,
+}
+
+/// Lifecycle
+impl Cache {
+    /// Create a new empty cache whose entries are used for at most `time_to_live`.
+    pub fn new(time_to_live: Duration) -> Self {
+        Cache {
+            time_to_live,
+            entries: Default::default(),
         }
     }
 }
 
+/// Access
+impl Cache {
+    /// Remove all entries from the cache, which forces the next listing of each remote to contact it.
+    pub fn clear(&self) {
+        self.entries.lock().expect("no panic while holding the lock").clear();
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<HandshakeWithRefs> {
+        let mut entries = self.entries.lock().expect("no panic while holding the lock");
+        match entries.get(key) {
+            Some((created, value)) if created.elapsed() <= self.time_to_live => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn put(&self, key: CacheKey, value: HandshakeWithRefs) {
+        self.entries
+            .lock()
+            .expect("no panic while holding the lock")
+            .insert(key, (Instant::now(), value));
+    }
+}
+
 impl<'remote, 'repo, T> Connection<'remote, 'repo, T>
 where
     T: Transport,
@@ -94,11 +164,13 @@ where
     #[allow(clippy::result_large_err)]
     #[gix_protocol::maybe_async::maybe_async]
     pub async fn ref_map(mut self, progress: impl Progress, options: Options) -> Result<fetch::RefMap, Error> {
-        let res = self.ref_map_inner(progress, options).await;
-        gix_protocol::indicate_end_of_interaction(&mut self.transport, self.trace)
-            .await
-            .ok();
-        res
+        let res = self.ref_map_inner(progress, options, true).await;
+        if !matches!(res, Ok((_, RefsSource::Cache))) {
+            gix_protocol::indicate_end_of_interaction(&mut self.transport, self.trace)
+                .await
+                .ok();
+        }
+        res.map(|(ref_map, _)| ref_map)
     }
 
     #[allow(clippy::result_large_err)]
@@ -110,8 +182,10 @@ where
             prefix_from_spec_as_filter_on_remote,
             handshake_parameters,
             mut extra_refspecs,
+            cache,
         }: Options,
-    ) -> Result<fetch::RefMap, Error> {
+        may_use_cache: bool,
+    ) -> Result<(fetch::RefMap, RefsSource), Error> {
         let _span = gix_trace::coarse!("remote::Connection::ref_map()");
         let null = gix_hash::ObjectId::null(gix_hash::Kind::Sha1); // OK to hardcode Sha1, it's not supposed to match, ever.
 
@@ -125,14 +199,47 @@ where
             s.extend(extra_refspecs.clone());
             s
         };
-        let remote = self
-            .fetch_refs(
-                prefix_from_spec_as_filter_on_remote,
-                handshake_parameters,
-                &specs,
-                progress,
-            )
-            .await?;
+        let cache_key = cache.as_ref().map(|_| CacheKey {
+            url: self.transport.to_url().into_owned(),
+            protocol: crate::config::tree::Protocol::VERSION
+                .try_into_protocol_version(
+                    self.remote
+                        .repo
+                        .config
+                        .resolved
+                        .integer(crate::config::tree::Protocol::VERSION),
+                )
+                .ok(),
+            filter_by_prefix: prefix_from_spec_as_filter_on_remote,
+            handshake_parameters: handshake_parameters.clone(),
+            refspecs: specs.clone(),
+        });
+        let cached = match (&cache, &cache_key) {
+            (Some(cache), Some(key)) if may_use_cache => cache.get(key),
+            _ => None,
+        };
+        let source = if cached.is_some() {
+            RefsSource::Cache
+        } else {
+            RefsSource::Remote
+        };
+        let remote = match cached {
+            Some(remote) => remote,
+            None => {
+                let remote = self
+                    .fetch_refs(
+                        prefix_from_spec_as_filter_on_remote,
+                        handshake_parameters,
+                        &specs,
+                        progress,
+                    )
+                    .await?;
+                if let Some((cache, key)) = cache.zip(cache_key) {
+                    cache.put(key, remote.clone());
+                }
+                remote
+            }
+        };
         let num_explicit_specs = self.remote.fetch_specs.len();
         let group = gix_refspec::MatchGroup::from_fetch_specs(specs.iter().map(gix_refspec::RefSpec::to_ref));
         let (res, fixes) = group
@@ -169,14 +276,17 @@ where
             .collect();
 
         let object_hash = extract_object_format(self.remote.repo, &remote.outcome)?;
-        Ok(fetch::RefMap {
-            mappings,
-            extra_refspecs,
-            fixes,
-            remote_refs: remote.refs,
-            handshake: remote.outcome,
-            object_hash,
-        })
+        Ok((
+            fetch::RefMap {
+                mappings,
+                extra_refspecs,
+                fixes,
+                remote_refs: remote.refs,
+                handshake: remote.outcome,
+                object_hash,
+            },
+            source,
+        ))
     }
 
     #[allow(clippy::result_large_err)]
@@ -253,6 +363,14 @@ where
     }
 }
 
+/// Where the references of a ref-map came from.
+pub(crate) enum RefsSource {
+    /// The remote was contacted.
+    Remote,
+    /// The references were taken from the cache, without contacting the remote.
+    Cache,
+}
+
 /// Assume sha1 if server says nothing, otherwise configure anything beyond sha1 in the local repo configuration
 #[allow(clippy::result_large_err)]
 fn extract_object_format(
@@ -13,7 +13,7 @@ pub mod negotiate {
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
 pub use super::connection::fetch::{
-    outcome, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
+    connectivity, outcome, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
 };
 
 /// If `Yes`, don't really make changes but do as much as possible to get an idea of what would be done.
@@ -37,16 +37,26 @@ pub(crate) enum WritePackedRefs {
 }
 
 /// Describe how to handle tags when fetching
+///
+/// It's configured with `remote.<name>.tagOpt`, which can be `--tags` or `--no-tags`, with the default being [`Tags::Included`].
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Tags {
     /// Fetch all tags from the remote, even if these are not reachable from objects referred to by our refspecs.
+    ///
+    /// This is the equivalent of `git fetch --tags`.
     All,
     /// Fetch only the tags that point to the objects being sent.
     /// That way, annotated tags that point to an object we receive are automatically transmitted and their refs are created.
     /// The same goes for lightweight tags.
+    ///
+    /// This is done by requesting the `include-tag` feature from the server, which then sends all tag objects that point into the
+    /// history it sends. Tags whose objects weren't sent are skipped with `ImplicitTagNotSentByRemote`
+    /// when updating references. Note that servers without support for `include-tag` can't be fetched from in this mode.
     #[default]
     Included,
     /// Do not fetch any tags.
+    ///
+    /// This is the equivalent of `git fetch --no-tags`.
     None,
 }
 
@@ -65,6 +75,27 @@ impl Tags {
     }
 }
 
+/// Describe which local references to remove after fetching as they don't exist on the remote anymore.
+///
+/// It's configured with `fetch.prune` and `fetch.pruneTags`, which are overridden by `remote.<name>.prune`
+/// and `remote.<name>.pruneTags` respectively, with the default being [`Prune::Never`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Prune {
+    /// Keep all local references, even if they don't exist on the remote anymore.
+    #[default]
+    Never,
+    /// Remove local references that are the destination of a refspec if their source doesn't exist on the remote anymore,
+    /// unless the source is excluded by a negative refspec.
+    ///
+    /// Tags that are only fetched due to [`Tags::Included`] or [`Tags::All`] are kept.
+    /// This is the equivalent of `git fetch --prune`.
+    Refs,
+    /// Like [`Prune::Refs`], but also remove local tags that don't exist on the remote anymore, as long as tags are fetched.
+    ///
+    /// This is the equivalent of `git fetch --prune --prune-tags`.
+    RefsAndTags,
+}
+
 /// Describe how shallow clones are handled when fetching, with variants defining how the *shallow boundary* is handled.
 ///
 /// The *shallow boundary* is a set of commits whose parents are not present in the repository.
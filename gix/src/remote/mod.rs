@@ -58,6 +58,10 @@ mod connection;
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 pub use connection::{ref_map, AuthenticateFn, Connection};
 
+///
+#[cfg(feature = "credentials")]
+pub mod push;
+
 ///
 pub mod save;
 
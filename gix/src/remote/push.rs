@@ -0,0 +1,204 @@
+use gix_hash::ObjectId;
+
+use crate::bstr::BStr;
+
+/// Protect a remote reference from being overwritten if it changed unexpectedly, as in `git push --force-with-lease`.
+///
+/// If the lease holds, the update is performed even if it isn't a fast-forward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lease {
+    /// Expect the remote reference to point to the same object as its remote-tracking reference, which is found using
+    /// the fetch refspecs of the remote. If there is no such remote-tracking reference, the remote reference is expected
+    /// not to exist.
+    ///
+    /// This is the equivalent of `--force-with-lease=<ref>`.
+    RemoteTracking,
+    /// Expect the remote reference to point to the given object, or not to exist if `None`.
+    ///
+    /// This is the equivalent of `--force-with-lease=<ref>:<expect>`.
+    Expect(Option<ObjectId>),
+}
+
+/// A reference update to push to a remote, to be checked with [`Remote::check_push_updates()`](crate::Remote::check_push_updates()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    /// The full name of the reference on the remote, like `refs/heads/main`.
+    pub remote_ref: gix_ref::FullName,
+    /// The object the remote reference points to as advertised by the remote, or `None` if it doesn't exist there.
+    pub remote_id: Option<ObjectId>,
+    /// The object the remote reference should point to, or `None` if it should be deleted.
+    pub new_id: Option<ObjectId>,
+    /// If `true`, the update is performed even if it isn't a fast-forward or would overwrite an existing tag,
+    /// as in `git push --force` or if the refspec starts with `+`.
+    pub force: bool,
+    /// If set, the update is only performed if the remote reference is at the expected value.
+    pub lease: Option<Lease>,
+}
+
+/// Describe how a reference on the remote would be affected by an [`Update`], or why it would be rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// The remote reference already points to the desired object.
+    NoChangeNeeded,
+    /// The remote reference doesn't exist yet and would be created.
+    New,
+    /// The remote reference would be deleted.
+    Delete,
+    /// The object of the remote reference is an ancestor of the new one, allowing for a fast-forward.
+    FastForward,
+    /// The remote reference would be set to the new object without taking into consideration its ancestry, either because
+    /// the update was forced or because its lease held.
+    Forced,
+    /// The remote reference doesn't point to the object expected by the [lease](Lease).
+    RejectedStale {
+        /// The object we expected the remote reference to point to, or `None` if it was expected not to exist.
+        expected: Option<ObjectId>,
+    },
+    /// Tags on the remote can't be changed without force.
+    RejectedTagUpdate,
+    /// The object the remote reference points to isn't available locally, so it must be fetched first to see if a
+    /// fast-forward is possible.
+    RejectedFetchFirst,
+    /// The current or the new object isn't a commit, so a fast-forward is impossible and the update needs force.
+    RejectedNeedsForce,
+    /// The update would not be a fast-forward, and force wasn't specified.
+    RejectedNonFastForward,
+}
+
+impl Mode {
+    /// Return `true` if the update would be rejected.
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self,
+            Mode::RejectedStale { .. }
+                | Mode::RejectedTagUpdate
+                | Mode::RejectedFetchFirst
+                | Mode::RejectedNeedsForce
+                | Mode::RejectedNonFastForward
+        )
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::NoChangeNeeded => "up-to-date",
+            Mode::New => "new",
+            Mode::Delete => "deleted",
+            Mode::FastForward => "fast-forward",
+            Mode::Forced => "forced-update",
+            Mode::RejectedStale { .. } => "rejected (stale info)",
+            Mode::RejectedTagUpdate => "rejected (already exists)",
+            Mode::RejectedFetchFirst => "rejected (fetch first)",
+            Mode::RejectedNeedsForce => "rejected (needs force)",
+            Mode::RejectedNonFastForward => "rejected (non-fast-forward)",
+        }
+        .fmt(f)
+    }
+}
+
+///
+pub mod check {
+    /// The error returned by [`Remote::check_push_updates()`](crate::Remote::check_push_updates()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error("Could not open the commit-graph for the fast-forward ancestor check")]
+        OpenCommitGraph(#[from] crate::repository::commit_graph_if_enabled::Error),
+        #[error("Could not find an object for the fast-forward ancestor check")]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error("Could not determine if the remote reference would be fast-forwarded")]
+        Reachability(#[from] gix_revision::reachability::Error),
+    }
+}
+
+impl crate::Remote<'_> {
+    /// Check each of the `updates` to push to this remote like `git push` would before sending anything, and return
+    /// how each one would affect the remote reference, in order.
+    ///
+    /// Updates are [rejected](Mode::is_rejected()) if they would overwrite a tag, or if they aren't a fast-forward, unless
+    /// they are forced. Updates with a [lease](Lease) are rejected if the remote reference isn't at the expected value,
+    /// and are performed even if they aren't fast-forwards otherwise. Remote-tracking references are found using the
+    /// [fetch refspecs](crate::Remote::refspecs()) of this remote.
+    ///
+    /// Note that remote references that point to objects we don't have locally can only be updated with force, as
+    /// it can't be determined whether the update would be a fast-forward.
+    pub fn check_push_updates(&self, updates: &[Update]) -> Result<Vec<Mode>, check::Error> {
+        let repo = self.repo;
+        let commit_graph = repo.commit_graph_if_enabled()?;
+        let mut oracle = gix_revision::reachability::Oracle::new(&repo.objects, commit_graph.as_ref());
+        let is_commit =
+            |id: ObjectId| -> Result<bool, check::Error> { Ok(repo.find_object(id)?.kind == gix_object::Kind::Commit) };
+
+        let mut out = Vec::with_capacity(updates.len());
+        for update in updates {
+            let mut force = update.force;
+            if let Some(lease) = update.lease {
+                let expected = match lease {
+                    Lease::Expect(id) => id,
+                    Lease::RemoteTracking => self.remote_tracking_id(update.remote_ref.as_bstr())?,
+                };
+                if expected != update.remote_id {
+                    out.push(Mode::RejectedStale { expected });
+                    continue;
+                }
+                force = true;
+            }
+
+            let mode = match (update.remote_id, update.new_id) {
+                (_, None) => Mode::Delete,
+                (None, Some(_)) => Mode::New,
+                (Some(remote_id), Some(new_id)) if remote_id == new_id => Mode::NoChangeNeeded,
+                (Some(remote_id), Some(new_id)) => {
+                    let rejection = if update.remote_ref.as_bstr().starts_with(b"refs/tags/") {
+                        Some(Mode::RejectedTagUpdate)
+                    } else if !repo.has_object(remote_id) {
+                        Some(Mode::RejectedFetchFirst)
+                    } else if !(is_commit(remote_id)? && is_commit(new_id)?) {
+                        Some(Mode::RejectedNeedsForce)
+                    } else if !oracle.is_ancestor(remote_id, new_id)? {
+                        Some(Mode::RejectedNonFastForward)
+                    } else {
+                        None
+                    };
+                    match rejection {
+                        None => Mode::FastForward,
+                        Some(_) if force => Mode::Forced,
+                        Some(rejection) => rejection,
+                    }
+                }
+            };
+            out.push(mode);
+        }
+        Ok(out)
+    }
+
+    /// Return the id of the remote-tracking reference of `remote_ref`, if there is one.
+    fn remote_tracking_id(&self, remote_ref: &BStr) -> Result<Option<ObjectId>, check::Error> {
+        let null = self.repo.object_hash().null();
+        let item = gix_refspec::match_group::Item {
+            full_ref_name: remote_ref,
+            target: &null,
+            object: None,
+        };
+        let group = gix_refspec::MatchGroup::from_fetch_specs(
+            self.refspecs(crate::remote::Direction::Fetch)
+                .iter()
+                .map(gix_refspec::RefSpec::to_ref),
+        );
+        let Some(local_name) = group
+            .match_remotes(Some(item).into_iter())
+            .mappings
+            .into_iter()
+            .find_map(|m| m.rhs)
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .repo
+            .try_find_reference(local_name.as_ref())?
+            .and_then(|r| r.try_id().map(crate::Id::detach)))
+    }
+}
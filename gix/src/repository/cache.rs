@@ -49,4 +49,20 @@ impl crate::Repository {
         self.objects.enable_object_memory();
         self
     }
+
+    /// Write all objects that were kept in memory so far into the object database, and return the amount of objects written.
+    ///
+    /// This allows to make objects written speculatively, for instance by a test merge, permanent.
+    /// Objects written after this call will still be kept in memory.
+    pub fn persist_object_memory(&self) -> Result<usize, crate::object::write::Error> {
+        self.objects.persist_object_memory().map_err(Into::into)
+    }
+
+    /// Drop all objects that were kept in memory so far, and return the amount of dropped objects.
+    ///
+    /// This undoes all object writes since [object memory was enabled](Self::with_object_memory()), or since it was last
+    /// [persisted](Self::persist_object_memory()).
+    pub fn discard_object_memory(&self) -> usize {
+        self.objects.reset_object_memory().map_or(0, |objects| objects.len())
+    }
 }
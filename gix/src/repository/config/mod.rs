@@ -24,11 +24,37 @@ impl crate::Repository {
 
     /// Return filesystem options as retrieved from the repository configuration.
     ///
-    /// Note that these values have not been [probed](gix_fs::Capabilities::probe()).
+    /// Note that these values have not been [probed](gix_fs::Capabilities::probe()),
+    /// use [`filesystem_capabilities()`](Self::filesystem_capabilities()) for that.
     pub fn filesystem_options(&self) -> Result<gix_fs::Capabilities, config::boolean::Error> {
         self.config.fs_capabilities()
     }
 
+    /// Return filesystem options as retrieved from the repository configuration, but limited to what the filesystem
+    /// of the worktree actually supports.
+    ///
+    /// This way, worktrees on filesystems without support for symlinks or the executable bit, like FAT or some network mounts,
+    /// behave as if `core.symlinks=false` or `core.fileMode=false` were configured, and case-insensitive or unicode-decomposing
+    /// filesystems are treated as such even if `core.ignoreCase` or `core.precomposeUnicode` aren't set.
+    /// The worktree is [probed](gix_fs::Capabilities::probe()) only once per repository instance, and for bare repositories
+    /// this is the same as [`filesystem_options()`](Self::filesystem_options()).
+    pub fn filesystem_capabilities(&self) -> Result<gix_fs::Capabilities, config::boolean::Error> {
+        let configured = self.config.fs_capabilities()?;
+        let Some(work_dir) = self.work_dir() else {
+            return Ok(configured);
+        };
+        let probed = self
+            .config
+            .worktree_fs_capabilities
+            .get_or_init(|| gix_fs::Capabilities::probe(work_dir));
+        Ok(gix_fs::Capabilities {
+            precompose_unicode: configured.precompose_unicode || probed.precompose_unicode,
+            ignore_case: configured.ignore_case || probed.ignore_case,
+            executable_bit: configured.executable_bit && probed.executable_bit,
+            symlink: configured.symlink && probed.symlink,
+        })
+    }
+
     /// Return filesystem options on how to perform stat-checks, typically in relation to the index.
     ///
     /// Note that these values have not been [probed](gix_fs::Capabilities::probe()).
@@ -120,6 +146,11 @@ impl crate::Repository {
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.config.object_hash
     }
+
+    /// Return the format version and extensions of the repository as seen when it was opened.
+    pub fn repository_format(&self) -> &config::RepositoryFormat {
+        &self.config.repository_format
+    }
 }
 
 mod branch;
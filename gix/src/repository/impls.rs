@@ -2,6 +2,7 @@ impl Clone for crate::Repository {
     fn clone(&self) -> Self {
         crate::Repository::from_refs_and_objects(
             self.refs.clone(),
+            self.reftable.clone(),
             self.objects.clone(),
             self.work_tree.clone(),
             self.common_dir.clone(),
@@ -38,6 +39,7 @@ impl From<&crate::ThreadSafeRepository> for crate::Repository {
     fn from(repo: &crate::ThreadSafeRepository) -> Self {
         crate::Repository::from_refs_and_objects(
             repo.refs.clone(),
+            repo.reftable.clone(),
             gix_odb::memory::Proxy::from(gix_odb::Cache::from(repo.objects.to_handle())).with_write_passthrough(),
             repo.work_tree.clone(),
             repo.common_dir.clone(),
@@ -56,6 +58,7 @@ impl From<crate::ThreadSafeRepository> for crate::Repository {
     fn from(repo: crate::ThreadSafeRepository) -> Self {
         crate::Repository::from_refs_and_objects(
             repo.refs,
+            repo.reftable,
             gix_odb::memory::Proxy::from(gix_odb::Cache::from(repo.objects.to_handle())).with_write_passthrough(),
             repo.work_tree,
             repo.common_dir,
@@ -74,6 +77,7 @@ impl From<crate::Repository> for crate::ThreadSafeRepository {
     fn from(r: crate::Repository) -> Self {
         crate::ThreadSafeRepository {
             refs: r.refs,
+            reftable: r.reftable,
             objects: r.objects.into_inner().store(),
             work_tree: r.work_tree,
             common_dir: r.common_dir,
@@ -1,5 +1,8 @@
 use crate::{
-    config::cache::util::ApplyLeniencyDefault, config::tree::Index, worktree, worktree::IndexPersistedOrInMemory,
+    config::cache::util::ApplyLeniencyDefault,
+    config::tree::{Core, Index},
+    worktree,
+    worktree::IndexPersistedOrInMemory,
 };
 
 /// Index access
@@ -124,6 +127,38 @@ impl crate::Repository {
             self.git_dir().join("index"),
         ))
     }
+
+    /// Return the directories included by the sparse checkout of this worktree if `core.sparseCheckout` and
+    /// `core.sparseCheckoutCone` are enabled, or `None` if everything is checked out or the patterns aren't in cone mode.
+    ///
+    /// The patterns are read from `$GIT_DIR/info/sparse-checkout`. Like `git`, we fall back to `None` if the file doesn't exist
+    /// or if it doesn't contain cone patterns.
+    /// Use the returned [`Cone`](gix_index::sparse::Cone) to [collapse](gix_index::State::collapse_sparse_directories())
+    /// the directories outside of it, which should be done before writing the index if `index.sparse` is enabled.
+    pub fn sparse_checkout_cone(
+        &self,
+    ) -> Result<Option<gix_index::sparse::Cone>, crate::repository::sparse_checkout_cone::Error> {
+        let config = &self.config.resolved;
+        for key in [&Core::SPARSE_CHECKOUT, &Core::SPARSE_CHECKOUT_CONE] {
+            let is_enabled = config
+                .boolean(key)
+                .map(|res| key.enrich_error(res))
+                .transpose()
+                .with_lenient_default(self.config.lenient_config)?
+                .unwrap_or_default();
+            if !is_enabled {
+                return Ok(None);
+            }
+        }
+
+        let path = self.git_dir().join("info").join("sparse-checkout");
+        let patterns = match std::fs::read(&path) {
+            Ok(patterns) => patterns,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(crate::repository::sparse_checkout_cone::Error::Io { source, path }),
+        };
+        Ok(gix_index::sparse::Cone::from_bytes(&patterns).ok())
+    }
 }
 
 impl std::ops::Deref for IndexPersistedOrInMemory {
@@ -4,6 +4,7 @@ impl crate::Repository {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn from_refs_and_objects(
         refs: crate::RefStore,
+        reftable: Option<gix_ref::reftable::Store>,
         mut objects: crate::OdbHandle,
         work_tree: Option<std::path::PathBuf>,
         common_dir: Option<std::path::PathBuf>,
@@ -20,6 +21,7 @@ impl crate::Repository {
             common_dir,
             objects,
             refs,
+            reftable,
             config,
             options: linked_worktree_options,
             #[cfg(feature = "index")]
@@ -0,0 +1,492 @@
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_features::progress::{Count, DynNestedProgress, Progress};
+use gix_hash::ObjectId;
+use gix_hashtable::ObjectIdSet;
+
+use crate::{
+    maintenance::{auto, due, prune, reachable, run, Task},
+    Repository,
+};
+
+/// Maintenance
+impl Repository {
+    /// Return all maintenance tasks that are due according to their thresholds, or an empty list if `maintenance.auto` is `false`.
+    ///
+    /// Tasks which [delete objects](Task::deletes_objects()) are never due if `extensions.preciousObjects` is set.
+    pub fn maintenance_tasks_due(&self) -> Result<Vec<Task>, due::Error> {
+        if !self.config.may_run_maintenance_automatically()? {
+            return Ok(Vec::new());
+        }
+        let mut out = Vec::new();
+        for task in Task::ALL {
+            if self.is_maintenance_task_due(*task)? {
+                out.push(*task);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Run all maintenance tasks that are due, similar to `git maintenance run --auto`, and return the tasks that were run.
+    ///
+    /// Each task is checked right before it would run, to allow previous tasks to affect its threshold.
+    /// This is meant to be called after operations that add objects to the repository, like fetches or the creation of commits.
+    /// Nothing is done if `maintenance.auto` is `false`.
+    pub fn maintenance_auto(
+        &self,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Vec<Task>, auto::Error> {
+        let mut out = Vec::new();
+        if !self
+            .config
+            .may_run_maintenance_automatically()
+            .map_err(due::Error::from)?
+        {
+            return Ok(out);
+        }
+        for task in Task::ALL {
+            if self.is_maintenance_task_due(*task)? {
+                self.maintenance_run(*task, progress, should_interrupt)
+                    .map_err(|source| auto::Error::Run { task: *task, source })?;
+                out.push(*task);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Run the maintenance `task` unconditionally, similar to `git maintenance run --task=<task>`.
+    ///
+    /// Tasks which [delete objects](Task::deletes_objects()) fail if `extensions.preciousObjects` is set.
+    pub fn maintenance_run(
+        &self,
+        task: Task,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<(), run::Error> {
+        if task.deletes_objects() && self.config.repository_format.precious_objects {
+            return Err(run::Error::PreciousObjects { task });
+        }
+        match task {
+            Task::LooseObjects => self.pack_loose_objects(progress, should_interrupt),
+            Task::IncrementalRepack => self.write_multi_pack_index(progress, should_interrupt),
+            Task::CommitGraph => self.write_commit_graph(progress, should_interrupt),
+        }
+    }
+
+    /// Delete unreachable loose objects which are older than `gc.pruneExpire`, similar to `git prune --expire=<gc.pruneExpire>`.
+    ///
+    /// Objects are reachable from references, `HEAD`, their reflogs and the index. The grace period, `2.weeks.ago` by default,
+    /// protects objects which are about to become reachable by a concurrent operation, which is also why writing an object
+    /// that already exists updates the modification time of its loose object or pack.
+    /// Nothing is pruned while another operation appears to be adding objects, as indicated by quarantine directories like
+    /// `objects/incoming-*` or temporary files in the object database that are younger than the grace period.
+    /// If `extensions.preciousObjects` is set, an error is returned instead.
+    ///
+    /// ### Deviation
+    ///
+    /// Unreachable objects in packs are never pruned as this requires repacking.
+    pub fn prune_unreachable_objects(
+        &self,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<prune::Outcome, prune::Error> {
+        if self.config.repository_format.precious_objects {
+            return Err(prune::Error::PreciousObjects);
+        }
+        let Some(expiry) = self.config.prune_expiry_date(SystemTime::now())? else {
+            return Ok(prune::Outcome::NeverExpires);
+        };
+        let expiry = expiry.seconds;
+        if let Some(marker) = self.running_operation_marker(expiry)? {
+            return Ok(prune::Outcome::OperationInProgress { marker });
+        }
+
+        let reachable = self.reachable_objects(progress, should_interrupt)?;
+        let loose = self.loose_objects();
+        let mut objects = 0;
+        let mut fan_out_dirs = BTreeSet::new();
+        for id in loose.iter() {
+            let id = id?;
+            if reachable.contains(&id) {
+                continue;
+            }
+            let path = loose.object_path(&id);
+            match std::fs::metadata(&path).and_then(|meta| meta.modified()) {
+                Ok(modified) if seconds_since_epoch(modified) > expiry => continue,
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => objects += 1,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+            fan_out_dirs.extend(path.parent().map(ToOwned::to_owned));
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(prune::Error::Interrupted);
+            }
+        }
+        for dir in fan_out_dirs {
+            std::fs::remove_dir(dir).ok();
+        }
+        Ok(prune::Outcome::Pruned { objects })
+    }
+}
+
+/// Utilities
+impl Repository {
+    fn is_maintenance_task_due(&self, task: Task) -> Result<bool, due::Error> {
+        let (enabled, threshold) = self.config.maintenance_task(task)?;
+        if !enabled || threshold == 0 || (task.deletes_objects() && self.config.repository_format.precious_objects) {
+            return Ok(false);
+        }
+        if task == Task::CommitGraph && (self.is_shallow() || !self.config.may_use_commit_graph()?) {
+            return Ok(false);
+        }
+        let Ok(threshold) = usize::try_from(threshold) else {
+            return Ok(true);
+        };
+        let count = match task {
+            Task::LooseObjects => {
+                let mut count = 0;
+                for id in self.loose_objects().iter().take(threshold) {
+                    id?;
+                    count += 1;
+                }
+                count
+            }
+            Task::IncrementalRepack => {
+                let in_multi_index: std::collections::HashSet<_> =
+                    gix_pack::multi_index::File::at(self.packs_dir().join("multi-pack-index"))
+                        .map(|multi_index| multi_index.index_names().to_owned())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .collect();
+                self.pack_index_paths()?
+                    .iter()
+                    .filter(|path| {
+                        path.file_name()
+                            .map_or(true, |name| !in_multi_index.contains(std::path::Path::new(name)))
+                    })
+                    .count()
+            }
+            Task::CommitGraph => {
+                let graph = self.commit_graph().ok();
+                let mut seen = ObjectIdSet::default();
+                let mut queue = self.reachable_commit_tips()?;
+                let mut count = 0;
+                while let Some(id) = queue.pop() {
+                    if count == threshold {
+                        break;
+                    }
+                    if !seen.insert(id) || graph.as_ref().map_or(false, |graph| graph.lookup(id).is_some()) {
+                        continue;
+                    }
+                    count += 1;
+                    queue.extend(self.find_commit(id)?.parent_ids().map(crate::Id::detach));
+                }
+                count
+            }
+        };
+        Ok(count >= threshold)
+    }
+
+    fn pack_loose_objects(
+        &self,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<(), run::Error> {
+        use gix_pack::data::output;
+
+        let loose = self.loose_objects();
+        let ids = loose.iter().collect::<Result<Vec<_>, _>>()?;
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let db = self.objects.clone().into_arc()?.into_inner();
+        let (counts, _) = output::count::objects_unthreaded(
+            &db,
+            &mut ids.iter().copied().map(Ok),
+            &progress.add_child("counting".into()),
+            should_interrupt,
+            output::count::objects::ObjectExpansion::AsIs,
+        )?;
+        let num_objects = counts.len() as u32;
+        let mut entries = gix_features::parallel::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            db,
+            Box::new(progress.add_child("creating entries".into())),
+            output::entry::iter_from_counts::Options::default(),
+        ));
+        let mut pack = Vec::new();
+        for written in output::bytes::FromEntriesIter::new(
+            entries.by_ref(),
+            &mut pack,
+            num_objects,
+            gix_pack::data::Version::V2,
+            self.object_hash(),
+        ) {
+            written?;
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(run::Error::Interrupted);
+            }
+        }
+
+        gix_pack::Bundle::write_to_directory(
+            &mut pack.as_slice(),
+            Some(&self.packs_dir()),
+            progress,
+            should_interrupt,
+            None::<gix_object::find::Never>,
+            gix_pack::bundle::write::Options {
+                object_hash: self.object_hash(),
+                fsync_pack: self.config.fsync.method_for(gix_fs::fsync::Component::Pack),
+                fsync_pack_metadata: self.config.fsync.method_for(gix_fs::fsync::Component::PackMetadata),
+                ..Default::default()
+            },
+        )?;
+
+        let mut fan_out_dirs = BTreeSet::new();
+        for id in &ids {
+            let path = loose.object_path(id);
+            match std::fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+            fan_out_dirs.extend(path.parent().map(ToOwned::to_owned));
+        }
+        for dir in fan_out_dirs {
+            // It's OK if a directory isn't empty as loose objects were added in the meantime.
+            std::fs::remove_dir(dir).ok();
+        }
+        Ok(())
+    }
+
+    fn write_multi_pack_index(
+        &self,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<(), run::Error> {
+        let index_paths = self.pack_index_paths()?;
+        if index_paths.is_empty() {
+            return Ok(());
+        }
+        let mut lock = gix_lock::File::acquire_to_update_resource(
+            self.packs_dir().join("multi-pack-index"),
+            gix_lock::acquire::Fail::Immediately,
+            None,
+        )?;
+        gix_pack::multi_index::File::write_from_index_paths(
+            index_paths,
+            &mut lock,
+            progress,
+            should_interrupt,
+            gix_pack::multi_index::write::Options {
+                object_hash: self.object_hash(),
+            },
+        )?;
+        lock.commit()?;
+        Ok(())
+    }
+
+    fn write_commit_graph(
+        &self,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<(), run::Error> {
+        let mut commits_progress = progress.add_child("collecting commits".into());
+        commits_progress.init(None, gix_features::progress::count("commits"));
+
+        let info_dir = self.objects.store_ref().path().join("info");
+        // Commits in the chain are skipped along with their ancestors, so only new commits have to be traversed.
+        let chain = gix_commitgraph::Graph::from_commit_graphs_dir(&info_dir.join("commit-graphs")).ok();
+        let mut commits = Vec::new();
+        let mut seen = ObjectIdSet::default();
+        let mut queue = self.reachable_commit_tips()?;
+        while let Some(id) = queue.pop() {
+            if !seen.insert(id) || chain.as_ref().map_or(false, |chain| chain.lookup(id).is_some()) {
+                continue;
+            }
+            let commit = self.find_commit(id)?;
+            let commit = commit.decode()?;
+            let parents: Vec<_> = commit.parents().collect();
+            queue.extend(parents.iter().copied());
+            commits.push(gix_commitgraph::file::write::Commit {
+                id,
+                tree: commit.tree(),
+                parents,
+                commit_time: commit.committer.time.seconds.max(0) as u64,
+            });
+            commits_progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(run::Error::Interrupted);
+            }
+        }
+
+        drop(chain);
+        gix_commitgraph::Graph::write_chain(&info_dir, commits, self.object_hash(), Default::default())?;
+        Ok(())
+    }
+
+    /// Return the ids of all commits that references point to, after peeling them.
+    fn reachable_commit_tips(&self) -> Result<Vec<ObjectId>, reachable::Error> {
+        let mut out = Vec::new();
+        for reference in self.references()?.all()?.peeled()? {
+            if let Some(id) = reference?.try_id() {
+                if self.find_header(id)?.kind() == gix_object::Kind::Commit {
+                    out.push(id.detach());
+                }
+            }
+        }
+        if let Ok(id) = self.head_id() {
+            out.push(id.detach());
+        }
+        Ok(out)
+    }
+
+    /// Return the ids of all objects reachable from references, `HEAD`, their reflogs and the index.
+    fn reachable_objects(
+        &self,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<ObjectIdSet, prune::Error> {
+        fn extend_with_reflog(
+            platform: &mut gix_ref::file::log::iter::Platform<'_, '_>,
+            out: &mut Vec<ObjectId>,
+        ) -> Result<(), prune::Error> {
+            if let Some(log) = platform.all()? {
+                for line in log {
+                    let line = line?;
+                    out.extend([line.previous_oid(), line.new_oid()]);
+                }
+            }
+            Ok(())
+        }
+
+        let mut queue = Vec::new();
+        for reference in self.references()?.all()? {
+            let reference = reference?;
+            queue.extend(reference.target().try_id().map(ToOwned::to_owned));
+            extend_with_reflog(&mut reference.log_iter(), &mut queue)?;
+        }
+        let head = self.head()?;
+        queue.extend(head.id().map(crate::Id::detach));
+        extend_with_reflog(&mut head.log_iter(), &mut queue)?;
+        queue.extend(
+            self.index_or_empty()?
+                .entries()
+                .iter()
+                .filter(|entry| entry.mode != gix_index::entry::Mode::COMMIT)
+                .map(|entry| entry.id),
+        );
+
+        let mut progress = progress.add_child("traversing reachable objects".into());
+        progress.init(None, gix_features::progress::count("objects"));
+        let mut seen = ObjectIdSet::default();
+        let mut buf = Vec::new();
+        while let Some(id) = queue.pop() {
+            if id.is_null() || !seen.insert(id) {
+                continue;
+            }
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(prune::Error::Interrupted);
+            }
+            // Objects may legitimately be missing, for instance in shallow repositories.
+            let Some(data) =
+                gix_object::Find::try_find(&self.objects, &id, &mut buf).map_err(prune::Error::FindObject)?
+            else {
+                continue;
+            };
+            match data.decode()? {
+                gix_object::ObjectRef::Commit(commit) => {
+                    queue.push(commit.tree());
+                    queue.extend(commit.parents());
+                }
+                gix_object::ObjectRef::Tag(tag) => queue.push(tag.target()),
+                gix_object::ObjectRef::Tree(tree) => {
+                    for entry in tree.entries {
+                        if entry.mode.is_tree() {
+                            queue.push(entry.oid.to_owned());
+                        } else if !entry.mode.is_commit() {
+                            seen.insert(entry.oid.to_owned());
+                        }
+                    }
+                }
+                gix_object::ObjectRef::Blob(_) => {}
+            }
+        }
+        Ok(seen)
+    }
+
+    /// Return the path to a quarantine directory or temporary file in the object database which is younger than `expiry`,
+    /// indicating that another operation may currently be adding objects.
+    fn running_operation_marker(&self, expiry: gix_date::SecondsSinceUnixEpoch) -> std::io::Result<Option<PathBuf>> {
+        for dir in [self.objects.store_ref().path().to_owned(), self.packs_dir()] {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            for entry in entries {
+                let entry = entry?;
+                let name = entry.file_name();
+                let Some(name) = name.to_str() else {
+                    continue;
+                };
+                if !["incoming-", "tmp_", ".tmp"]
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+                {
+                    continue;
+                }
+                match entry.metadata().and_then(|meta| meta.modified()) {
+                    Ok(modified) if seconds_since_epoch(modified) > expiry => return Ok(Some(entry.path())),
+                    Ok(_) => {}
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn loose_objects(&self) -> gix_odb::loose::Store {
+        gix_odb::loose::Store::at(self.objects.store_ref().path(), self.object_hash())
+    }
+
+    fn packs_dir(&self) -> PathBuf {
+        self.objects.store_ref().path().join("pack")
+    }
+
+    fn pack_index_paths(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut out = Vec::new();
+        let entries = match std::fs::read_dir(self.packs_dir()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(out),
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "idx") {
+                out.push(path);
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+}
+
+/// Like `git`, compare modification times with a granularity of seconds.
+fn seconds_since_epoch(time: SystemTime) -> gix_date::SecondsSinceUnixEpoch {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as gix_date::SecondsSinceUnixEpoch)
+}
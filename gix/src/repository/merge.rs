@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use gix_hash::ObjectId;
+use gix_odb::Write;
+
+use crate::{
+    bstr::BString,
+    merge::{
+        blob::Labels,
+        tree::{Entry, Error, Outcome},
+    },
+};
+
+impl crate::Repository {
+    /// Merge the changes from `base` to `ours` with the changes from `base` to `theirs`, all of which are objects that
+    /// peel to trees, and write the merged tree along with all merged blobs into the object database.
+    ///
+    /// If `base` is `None`, the sides are merged as if they started out empty, which is what `git` does for unrelated histories.
+    /// Changes to different files are combined, and so are changes to different regions of the same text file.
+    /// All other changes conflict, and conflicting text regions are surrounded by conflict markers using `labels`.
+    ///
+    /// Note that renames aren't detected, and that a single `base` has to be chosen by the caller if there are multiple merge bases.
+    pub fn merge_trees(
+        &self,
+        base: Option<ObjectId>,
+        ours: impl Into<ObjectId>,
+        theirs: impl Into<ObjectId>,
+        labels: Labels<'_>,
+    ) -> Result<Outcome, Error> {
+        let base = base.map(|id| self.tree_id_of(id)).transpose()?;
+        let ours = self.tree_id_of(ours.into())?;
+        let theirs = self.tree_id_of(theirs.into())?;
+        Ok(gix_merge::tree(
+            base.as_deref(),
+            &ours,
+            &theirs,
+            labels,
+            &self.objects,
+            |object| -> Result<_, gix_odb::write::Error> {
+                let mut buf = Vec::new();
+                object.write_to(&mut buf)?;
+                self.objects.write_buf(object.kind(), &buf)
+            },
+        )?)
+    }
+
+    /// Return all non-tree entries of the tree `id` peels to, by their path.
+    pub(crate) fn entries_by_path(&self, id: ObjectId) -> Result<BTreeMap<BString, Entry>, Error> {
+        Ok(gix_merge::tree::entries_by_path(&self.tree_id_of(id)?, &self.objects)?)
+    }
+
+    fn tree_id_of(&self, id: ObjectId) -> Result<ObjectId, Error> {
+        Ok(self.find_object(id)?.peel_to_tree()?.id)
+    }
+}
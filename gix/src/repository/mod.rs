@@ -59,13 +59,25 @@ mod kind;
 mod location;
 #[cfg(feature = "mailmap")]
 mod mailmap;
+#[cfg(feature = "maintenance")]
+mod maintenance;
+#[cfg(feature = "merge")]
+mod merge;
 mod object;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(all(feature = "merge", feature = "blocking-network-client"))]
+mod pull;
+#[cfg(feature = "merge")]
+mod rebase;
 mod reference;
 mod remote;
 mod revision;
+#[cfg(feature = "tree-editor")]
+mod rewrite;
 mod shallow;
+#[cfg(all(feature = "merge", feature = "status"))]
+mod stash;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
@@ -122,6 +134,41 @@ pub mod merge_base_with_graph {
     }
 }
 
+///
+#[cfg(feature = "revision")]
+pub mod references_reachability {
+    /// The error returned by [Repository::references_containing()](crate::Repository::references_containing()) and
+    /// [Repository::references_merged_into()](crate::Repository::references_merged_into()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenCache(#[from] crate::repository::commit_graph_if_enabled::Error),
+        #[error(transparent)]
+        PeelReference(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        Reachability(#[from] gix_revision::reachability::Error),
+    }
+}
+
+///
+#[cfg(feature = "merge")]
+pub mod rebase_todo {
+    /// The error returned by [Repository::rebase_todo()](crate::Repository::rebase_todo()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenCache(#[from] crate::repository::commit_graph_if_enabled::Error),
+        #[error(transparent)]
+        Reachability(#[from] gix_revision::reachability::Error),
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+    }
+}
+
 ///
 pub mod commit_graph_if_enabled {
     /// The error returned by [Repository::commit_graph_if_enabled()](crate::Repository::commit_graph_if_enabled()).
@@ -152,6 +199,23 @@ pub mod index_from_tree {
     }
 }
 
+///
+#[cfg(feature = "index")]
+pub mod sparse_checkout_cone {
+    /// The error returned by [Repository::sparse_checkout_cone()](crate::Repository::sparse_checkout_cone).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        BooleanConfig(#[from] crate::config::boolean::Error),
+        #[error("Could not read the sparse-checkout patterns at {path:?}")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+    }
+}
+
 ///
 pub mod branch_remote_ref_name {
 
@@ -184,6 +248,22 @@ pub mod branch_remote_tracking_ref_name {
     }
 }
 
+///
+pub mod state_signature {
+    /// The error returned by [`Repository::state_signature()`](crate::Repository::state_signature()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Head(#[from] crate::reference::find::existing::Error),
+        #[error("Could not obtain the version of {path:?}")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+    }
+}
+
 ///
 #[cfg(feature = "attributes")]
 pub mod pathspec_defaults_ignore_case {
@@ -174,6 +174,7 @@ impl crate::Repository {
     ///
     /// Note that we hash the object in memory to avoid storing objects that are already present. That way,
     /// we avoid writing duplicate objects using slow disks that will eventually have to be garbage collected.
+    /// Instead, existing objects are freshened to protect them from being pruned while they become reachable.
     pub fn write_object(&self, object: impl gix_object::WriteTo) -> Result<Id<'_>, object::write::Error> {
         let mut buf = self.shared_empty_buf();
         object.write_to(buf.deref_mut()).expect("write to memory works");
@@ -182,11 +183,6 @@ impl crate::Repository {
     }
 
     fn write_object_inner(&self, buf: &[u8], kind: gix_object::Kind) -> Result<Id<'_>, object::write::Error> {
-        let oid = gix_object::compute_hash(self.object_hash(), kind, buf);
-        if self.objects.exists(&oid) {
-            return Ok(oid.attach(self));
-        }
-
         self.objects
             .write_buf(kind, buf)
             .map(|oid| oid.attach(self))
@@ -199,10 +195,6 @@ impl crate::Repository {
     /// pre-hashing the data, and checking if the object is already present.
     pub fn write_blob(&self, bytes: impl AsRef<[u8]>) -> Result<Id<'_>, object::write::Error> {
         let bytes = bytes.as_ref();
-        let oid = gix_object::compute_hash(self.object_hash(), gix_object::Kind::Blob, bytes);
-        if self.objects.exists(&oid) {
-            return Ok(oid.attach(self));
-        }
         self.objects
             .write_buf(gix_object::Kind::Blob, bytes)
             .map_err(Into::into)
@@ -226,11 +218,6 @@ impl crate::Repository {
     }
 
     fn write_blob_stream_inner(&self, buf: &[u8]) -> Result<Id<'_>, object::write::Error> {
-        let oid = gix_object::compute_hash(self.object_hash(), gix_object::Kind::Blob, buf);
-        if self.objects.exists(&oid) {
-            return Ok(oid.attach(self));
-        }
-
         self.objects
             .write_buf(gix_object::Kind::Blob, buf)
             .map_err(Into::into)
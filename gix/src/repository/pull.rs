@@ -0,0 +1,228 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::BString,
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Branch, Pull},
+    },
+    merge::blob::Labels,
+    pull::{Error, FastForward, Options, Outcome, Rebase, Strategy},
+    rebase::todo::Instruction,
+    remote,
+};
+
+impl crate::Repository {
+    /// Fetch from the remote of the current branch and integrate the fetched commit into it by fast-forwarding,
+    /// merging or rebasing, similar to what `git pull` does, while providing `progress` and checking `should_interrupt`
+    /// during the fetch.
+    ///
+    /// The remote is the one configured in `branch.<name>.remote`, or `origin`, and the commit to integrate is the one
+    /// marked for merging in `FETCH_HEAD`, typically the remote branch configured in `branch.<name>.merge`.
+    /// Then `branch.<name>.rebase` or `pull.rebase`, and `pull.ff` decide how it's integrated unless overridden by `options`.
+    ///
+    /// Note that merges and rebases stop at the first conflict without changing the current branch, and that only the
+    /// current branch is updated, but neither the index nor the worktree. These can be updated by checking out
+    /// [`Outcome::new`], knowing that they were at [`Outcome::previous`] before.
+    pub fn pull<P>(&self, mut progress: P, should_interrupt: &AtomicBool, options: Options) -> Result<Outcome, Error>
+    where
+        P: crate::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        self.pull_inner(&mut progress, should_interrupt, options)
+    }
+
+    fn pull_inner(
+        &self,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        Options { rebase, fast_forward }: Options,
+    ) -> Result<Outcome, Error> {
+        let head = self.head()?;
+        let reference = head.referent_name().ok_or(Error::DetachedHead)?.to_owned();
+        let previous = head.id().map(crate::Id::detach);
+        let short_name = reference.shorten().to_owned();
+
+        let remote = head
+            .into_remote(remote::Direction::Fetch)
+            .or_else(|| self.find_default_remote(remote::Direction::Fetch))
+            .ok_or(Error::NoRemote)??;
+        let fetch = remote
+            .connect(remote::Direction::Fetch)?
+            .prepare_fetch(&mut *progress, Default::default())?
+            .with_write_fetch_head(true)
+            .with_reflog_message(remote::fetch::RefLogMessage::Prefixed { action: "pull".into() })
+            .receive_inner(progress, should_interrupt)?;
+
+        let mut candidates = self
+            .refs
+            .read_fetch_head()?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| entry.for_merge);
+        let upstream = match (candidates.next(), candidates.count()) {
+            (Some(entry), 0) => entry,
+            (None, _) => return Err(Error::NoMergeCandidate { branch: short_name }),
+            (Some(_), more) => return Err(Error::MultipleMergeCandidates { count: more + 1 }),
+        };
+
+        let config = &self.config.resolved;
+        let rebase = match rebase {
+            Some(rebase) => rebase,
+            None => config
+                .string_by("branch", Some(short_name.as_ref()), Branch::REBASE.name)
+                .map(|value| Branch::REBASE.try_into_rebase(value))
+                .or_else(|| {
+                    config
+                        .string(Pull::REBASE)
+                        .map(|value| Pull::REBASE.try_into_rebase(value))
+                })
+                .transpose()
+                .with_leniency(self.config.lenient_config)?
+                .unwrap_or_default(),
+        };
+        let fast_forward = match fast_forward {
+            Some(fast_forward) => fast_forward,
+            None => config
+                .string(Pull::FF)
+                .map(|value| Pull::FF.try_into_fast_forward(value))
+                .transpose()
+                .with_leniency(self.config.lenient_config)?
+                .unwrap_or_default(),
+        };
+
+        let mut outcome = Outcome {
+            fetch,
+            upstream: upstream.id,
+            strategy: Strategy::UpToDate,
+            reference,
+            previous,
+            new: upstream.id,
+        };
+        let Some(previous) = previous else {
+            outcome.strategy = Strategy::FastForward;
+            self.update_pulled_branch(&outcome, "pull: Fast-forward".into())?;
+            return Ok(outcome);
+        };
+        let base = match self.merge_base(previous, upstream.id) {
+            Ok(base) => Some(base.detach()),
+            Err(crate::repository::merge_base::Error::NotFound { .. }) => None,
+            Err(err) => return Err(err.into()),
+        };
+        if base == Some(upstream.id) {
+            outcome.new = previous;
+            return Ok(outcome);
+        }
+        let is_rebase = rebase != Rebase::False;
+        if base == Some(previous) && (is_rebase || fast_forward != FastForward::Never) {
+            outcome.strategy = Strategy::FastForward;
+            self.update_pulled_branch(&outcome, "pull: Fast-forward".into())?;
+            return Ok(outcome);
+        }
+        if fast_forward == FastForward::Only {
+            return Err(Error::NotFastForward);
+        }
+
+        let (strategy, new, message) = match rebase {
+            Rebase::Interactive => return Err(Error::InteractiveRebase),
+            Rebase::True | Rebase::Merges => {
+                let (new, strategy) = self.rebase_onto(previous, upstream.id, rebase)?;
+                let message = format!(
+                    "pull --rebase (finish): {} onto {}",
+                    outcome.reference.as_bstr(),
+                    upstream.id
+                );
+                (strategy, new, message.into())
+            }
+            Rebase::False => {
+                let merge = self.merge_trees(
+                    base,
+                    previous,
+                    upstream.id,
+                    Labels {
+                        ours: "HEAD".into(),
+                        theirs: upstream.description.as_ref(),
+                    },
+                )?;
+                if merge.has_conflicts() {
+                    return Err(Error::MergeConflict {
+                        conflicts: merge.conflicts,
+                    });
+                }
+                let mut message: BString = format!("Merge {}", upstream.description).into();
+                if short_name != "main" && short_name != "master" {
+                    message.extend_from_slice(b" into ");
+                    message.extend_from_slice(&short_name);
+                }
+                let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+                let author = self.author().ok_or(crate::commit::Error::AuthorMissing)??;
+                let commit = gix_object::Commit {
+                    tree: merge.tree,
+                    parents: [previous, upstream.id].into_iter().collect(),
+                    author: author.into(),
+                    committer: committer.into(),
+                    encoding: None,
+                    message,
+                    extra_headers: Vec::new(),
+                };
+                let new = self.write_object(&commit)?.detach();
+                (Strategy::Merge, new, "pull: Merge made by a three-way merge.".into())
+            }
+        };
+        outcome.strategy = strategy;
+        outcome.new = new;
+        self.update_pulled_branch(&outcome, message)?;
+        Ok(outcome)
+    }
+
+    /// Recreate all commits reachable from `tip` but not from `onto` on top of `onto`, and return the new tip along with
+    /// the [rebase strategy](Strategy::Rebase) listing all recreated and skipped commits.
+    fn rebase_onto(&self, tip: ObjectId, onto: ObjectId, rebase: Rebase) -> Result<(ObjectId, Strategy), Error> {
+        let (commits, mut skipped) = self.commits_to_rebase(onto, tip)?;
+        if let (Rebase::Merges, Some(commit)) = (rebase, skipped.first()) {
+            return Err(Error::RebaseMerges { commit: *commit });
+        }
+        let todo = commits
+            .into_iter()
+            .map(|commit| Instruction::Pick {
+                commit: commit.into(),
+                summary: Default::default(),
+            })
+            .collect();
+        let out = self.rebase(onto, todo, &mut crate::rebase::NonInteractive)?;
+        skipped.extend(out.dropped);
+        Ok((
+            out.head.unwrap_or(onto),
+            Strategy::Rebase {
+                rebased: out.rewritten,
+                skipped,
+            },
+        ))
+    }
+
+    fn update_pulled_branch(&self, outcome: &Outcome, message: BString) -> Result<(), Error> {
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message,
+                },
+                expected: match outcome.previous {
+                    Some(previous) => PreviousValue::MustExistAndMatch(Target::Object(previous)),
+                    None => PreviousValue::MustNotExist,
+                },
+                new: Target::Object(outcome.new),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: true,
+        })?;
+        Ok(())
+    }
+}
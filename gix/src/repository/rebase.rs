@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+
+use gix_hash::{ObjectId, Prefix};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    merge::blob::Labels,
+    rebase::{
+        todo::{FixupMessage, Instruction, List},
+        Delegate, Error, MessageKind, Outcome, Stop,
+    },
+    repository::rebase_todo,
+};
+
+impl crate::Repository {
+    /// Return the todo list for rebasing all commits reachable from `tip` but not from `upstream`, which picks them
+    /// parents first just like `git rebase -i <upstream>` would.
+    ///
+    /// Merge commits are left out as their changes are contained in the commits that are picked.
+    pub fn rebase_todo(
+        &self,
+        upstream: impl Into<ObjectId>,
+        tip: impl Into<ObjectId>,
+    ) -> Result<List, rebase_todo::Error> {
+        let (commits, _merges) = self.commits_to_rebase(upstream.into(), tip.into())?;
+        commits
+            .into_iter()
+            .map(|id| {
+                let commit = self.find_commit(id)?;
+                let summary = commit.message_raw()?.lines().next().unwrap_or_default().into();
+                Ok(Instruction::Pick {
+                    commit: commit.id().shorten_or_id(),
+                    summary,
+                })
+            })
+            .collect()
+    }
+
+    /// Perform all instructions of `todo`, starting with `onto` as the current commit, and use `delegate` for all interactions
+    /// with the user, which includes the editing of `todo` before the first instruction is performed.
+    ///
+    /// Picked commits whose changes are already present in the current commit are dropped, and commits are reused
+    /// if their parent is the current commit.
+    /// Labels and commits named by `reset` and `merge` are resolved like revisions, with the `onto` label being predefined.
+    ///
+    /// Note that the rebase stops with an error at the first conflict, and that neither references, nor the index or the
+    /// worktree are changed, which is left to the caller based on the returned [`Outcome`].
+    pub fn rebase(
+        &self,
+        onto: impl Into<ObjectId>,
+        mut todo: List,
+        delegate: &mut dyn Delegate,
+    ) -> Result<Outcome, Error> {
+        delegate.edit_todo(&mut todo).map_err(Error::Delegate)?;
+        let instructions: Vec<_> = todo.instructions().cloned().collect();
+        let onto = onto.into();
+        let committer: gix_actor::Signature = self.committer().ok_or(crate::commit::Error::CommitterMissing)??.into();
+
+        let mut labels = HashMap::<BString, Option<ObjectId>>::from([("onto".into(), Some(onto))]);
+        let mut head = Some(onto);
+        let mut out = Outcome {
+            head,
+            rewritten: Vec::new(),
+            dropped: Vec::new(),
+            update_refs: Vec::new(),
+        };
+        let mut fold_needs_edit = false;
+        for (idx, instruction) in instructions.iter().enumerate() {
+            match instruction {
+                Instruction::Pick { commit, .. }
+                | Instruction::Reword { commit, .. }
+                | Instruction::Edit { commit, .. } => {
+                    let id = self.resolve_todo_commit(commit)?;
+                    let Some(new) = self.pick_commit(head, id, &committer)? else {
+                        out.dropped.push(id);
+                        continue;
+                    };
+                    let new = match instruction {
+                        Instruction::Reword { .. } => {
+                            Some(self.edit_commit_message(new, MessageKind::Reword, &committer, delegate)?)
+                        }
+                        Instruction::Edit { .. } => delegate
+                            .stop(Stop::Edit { commit: id }, Some(new))
+                            .map_err(Error::Delegate)?,
+                        _ => Some(new),
+                    };
+                    head = new;
+                    if let Some(new) = new {
+                        out.rewritten.push((id, new));
+                    }
+                }
+                Instruction::Squash { commit, .. } | Instruction::Fixup { commit, .. } => {
+                    let current = head.ok_or(Error::NoCurrentCommit {
+                        instruction: instruction.name(),
+                    })?;
+                    let id = self.resolve_todo_commit(commit)?;
+                    let (message, needs_edit) = match instruction {
+                        Instruction::Fixup {
+                            message: FixupMessage::Discard,
+                            ..
+                        } => (None, false),
+                        Instruction::Fixup {
+                            message: FixupMessage::Use,
+                            ..
+                        } => (Some(false), false),
+                        Instruction::Fixup {
+                            message: FixupMessage::Edit,
+                            ..
+                        } => (Some(false), true),
+                        _ => (Some(true), true),
+                    };
+                    let new = self.fold_commit(current, id, message, &committer)?;
+                    fold_needs_edit |= needs_edit;
+                    let is_last_fold = !matches!(
+                        instructions.get(idx + 1),
+                        Some(Instruction::Squash { .. } | Instruction::Fixup { .. })
+                    );
+                    let new = if is_last_fold && fold_needs_edit {
+                        fold_needs_edit = false;
+                        self.edit_commit_message(new, MessageKind::Squash, &committer, delegate)?
+                    } else {
+                        new
+                    };
+                    out.rewritten.push((id, new));
+                    for (_, rewritten) in &mut out.rewritten {
+                        if *rewritten == current {
+                            *rewritten = new;
+                        }
+                    }
+                    head = Some(new);
+                }
+                Instruction::Drop { commit, .. } => out.dropped.push(self.resolve_todo_commit(commit)?),
+                Instruction::Exec { command } => {
+                    head = delegate.exec(command.as_ref(), head).map_err(Error::Delegate)?;
+                }
+                Instruction::Break => head = delegate.stop(Stop::Break, head).map_err(Error::Delegate)?,
+                Instruction::Label { name } => {
+                    labels.insert(name.clone(), head);
+                }
+                Instruction::Reset { name } => {
+                    head = if name == "[new root]" {
+                        None
+                    } else {
+                        Some(self.resolve_todo_label(&labels, name.as_ref())?)
+                    };
+                }
+                Instruction::Merge {
+                    commit,
+                    edit_message,
+                    labels: to_merge,
+                    ..
+                } => {
+                    let current = head.ok_or(Error::NoCurrentCommit {
+                        instruction: instruction.name(),
+                    })?;
+                    let [label] = to_merge.as_slice() else {
+                        return Err(Error::OctopusMerge {
+                            count: to_merge.len() + 1,
+                        });
+                    };
+                    let other = self.resolve_todo_label(&labels, label.as_ref())?;
+                    let original = commit.as_ref().map(|c| self.resolve_todo_commit(c)).transpose()?;
+                    let new = self.merge_commit(
+                        current,
+                        other,
+                        label.as_ref(),
+                        original,
+                        *edit_message,
+                        &committer,
+                        delegate,
+                    )?;
+                    if let Some(original) = original {
+                        out.rewritten.push((original, new));
+                    }
+                    head = Some(new);
+                }
+                Instruction::UpdateRef { name } => {
+                    let target = head.ok_or(Error::NoCurrentCommit {
+                        instruction: instruction.name(),
+                    })?;
+                    out.update_refs.push((name.clone().try_into()?, target));
+                }
+                Instruction::Noop => {}
+            }
+        }
+        out.head = head;
+        Ok(out)
+    }
+
+    /// Return all commits reachable from `tip` but not from `upstream` in topological order, parents first, along with
+    /// all merge commits among them, which aren't part of the returned commits.
+    pub(crate) fn commits_to_rebase(
+        &self,
+        upstream: ObjectId,
+        tip: ObjectId,
+    ) -> Result<(Vec<ObjectId>, Vec<ObjectId>), rebase_todo::Error> {
+        let commit_graph = self.commit_graph_if_enabled()?;
+        let mut oracle = gix_revision::reachability::Oracle::new(&self.objects, commit_graph.as_ref());
+        let mut commits = Vec::new();
+        let mut merges = Vec::new();
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut stack = vec![(tip, false)];
+        while let Some((id, parents_visited)) = stack.pop() {
+            if parents_visited {
+                commits.push(id);
+                continue;
+            }
+            if !seen.insert(id) || oracle.is_ancestor(id, upstream)? {
+                continue;
+            }
+            let commit = self.find_commit(id)?;
+            let parents: Vec<_> = commit.parent_ids().map(crate::Id::detach).collect();
+            if parents.len() > 1 {
+                merges.push(id);
+            } else {
+                stack.push((id, true));
+            }
+            stack.extend(parents.into_iter().map(|parent| (parent, false)));
+        }
+        Ok((commits, merges))
+    }
+
+    fn resolve_todo_commit(&self, prefix: &Prefix) -> Result<ObjectId, Error> {
+        if prefix.hex_len() == prefix.as_oid().kind().len_in_hex() {
+            return Ok(prefix.as_oid().to_owned());
+        }
+        match self.objects.lookup_prefix(*prefix, None)? {
+            Some(Ok(id)) => Ok(id),
+            Some(Err(())) => Err(Error::Ambiguous { prefix: *prefix }),
+            None => Err(Error::NotFound { prefix: *prefix }),
+        }
+    }
+
+    fn resolve_todo_label(&self, labels: &HashMap<BString, Option<ObjectId>>, name: &BStr) -> Result<ObjectId, Error> {
+        if let Some(Some(id)) = labels.get(name) {
+            return Ok(*id);
+        }
+        self.rev_parse_single(name)
+            .map(crate::Id::detach)
+            .map_err(|source| Error::ResolveLabel {
+                name: name.to_owned(),
+                source,
+            })
+    }
+
+    /// Apply the changes of `id` on top of `head`, or return `None` if all of its changes are already present.
+    fn pick_commit(
+        &self,
+        head: Option<ObjectId>,
+        id: ObjectId,
+        committer: &gix_actor::Signature,
+    ) -> Result<Option<ObjectId>, Error> {
+        let commit = self.find_commit(id)?;
+        let commit = commit.decode()?;
+        if commit.parents.len() > 1 {
+            return Err(Error::PickMerge { commit: id });
+        }
+        let parent = commit.parents().next();
+        if parent == head {
+            return Ok(Some(id));
+        }
+        let head_tree = self.tree_of_commit(head)?;
+        let merge = self.merge_trees(
+            parent,
+            head_tree,
+            id,
+            Labels {
+                ours: "HEAD".into(),
+                theirs: commit.message_summary().as_ref(),
+            },
+        )?;
+        if merge.has_conflicts() {
+            return Err(Error::Conflict {
+                commit: id,
+                conflicts: merge.conflicts,
+            });
+        }
+        if merge.tree == head_tree && commit.tree() != self.tree_of_commit(parent)? {
+            return Ok(None);
+        }
+        let new_commit = gix_object::Commit {
+            tree: merge.tree,
+            parents: head.into_iter().collect(),
+            author: commit.author.into(),
+            committer: committer.clone(),
+            encoding: commit.encoding.map(ToOwned::to_owned),
+            message: commit.message.to_owned(),
+            extra_headers: Vec::new(),
+        };
+        Ok(Some(self.write_object(&new_commit)?.detach()))
+    }
+
+    /// Amend `current` with the changes of `id`. If `combine_message` is `None`, the message of `current` is kept,
+    /// otherwise the message of `id` is appended to it if `true`, or replaces it if `false`.
+    fn fold_commit(
+        &self,
+        current: ObjectId,
+        id: ObjectId,
+        combine_message: Option<bool>,
+        committer: &gix_actor::Signature,
+    ) -> Result<ObjectId, Error> {
+        let commit = self.find_commit(id)?;
+        let commit = commit.decode()?;
+        let merge = self.merge_trees(
+            commit.parents().next(),
+            current,
+            id,
+            Labels {
+                ours: "HEAD".into(),
+                theirs: commit.message_summary().as_ref(),
+            },
+        )?;
+        if merge.has_conflicts() {
+            return Err(Error::Conflict {
+                commit: id,
+                conflicts: merge.conflicts,
+            });
+        }
+        let mut new_commit: gix_object::Commit = self.find_commit(current)?.decode()?.into();
+        new_commit.tree = merge.tree;
+        new_commit.committer = committer.clone();
+        match combine_message {
+            None => {}
+            Some(true) => {
+                while !new_commit.message.ends_with(b"\n\n") {
+                    new_commit.message.push_byte(b'\n');
+                }
+                new_commit.message.push_str(commit.message);
+            }
+            Some(false) => new_commit.message = commit.message.to_owned(),
+        }
+        Ok(self.write_object(&new_commit)?.detach())
+    }
+
+    /// Let `delegate` edit the message of `id`, and write a new commit with the changed message if it changed.
+    fn edit_commit_message(
+        &self,
+        id: ObjectId,
+        kind: MessageKind,
+        committer: &gix_actor::Signature,
+        delegate: &mut dyn Delegate,
+    ) -> Result<ObjectId, Error> {
+        let mut commit: gix_object::Commit = self.find_commit(id)?.decode()?.into();
+        let previous = commit.message.clone();
+        delegate
+            .edit_message(kind, &mut commit.message)
+            .map_err(Error::Delegate)?;
+        if commit.message == previous {
+            return Ok(id);
+        }
+        commit.committer = committer.clone();
+        Ok(self.write_object(&commit)?.detach())
+    }
+
+    /// Merge `other` into `current` and use the message of `original`, or a generated one that mentions `label`.
+    #[allow(clippy::too_many_arguments)]
+    fn merge_commit(
+        &self,
+        current: ObjectId,
+        other: ObjectId,
+        label: &BStr,
+        original: Option<ObjectId>,
+        edit_message: bool,
+        committer: &gix_actor::Signature,
+        delegate: &mut dyn Delegate,
+    ) -> Result<ObjectId, Error> {
+        let original_id = original;
+        let original = original.map(|id| self.find_commit(id)).transpose()?;
+        let original = original.as_ref().map(crate::Commit::decode).transpose()?;
+        if let Some((id, original)) = original_id.zip(original.as_ref()) {
+            if !edit_message && original.parents().eq([current, other]) {
+                return Ok(id);
+            }
+        }
+        let base = match self.merge_base(current, other) {
+            Ok(base) => Some(base.detach()),
+            Err(crate::repository::merge_base::Error::NotFound { .. }) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let merge = self.merge_trees(
+            base,
+            current,
+            other,
+            Labels {
+                ours: "HEAD".into(),
+                theirs: label,
+            },
+        )?;
+        if merge.has_conflicts() {
+            return Err(Error::Conflict {
+                commit: other,
+                conflicts: merge.conflicts,
+            });
+        }
+        let (author, mut message) = match &original {
+            Some(original) => (original.author.into(), original.message.to_owned()),
+            None => (committer.clone(), format!("Merge branch '{label}'").into()),
+        };
+        if edit_message {
+            delegate
+                .edit_message(MessageKind::Merge, &mut message)
+                .map_err(Error::Delegate)?;
+        }
+        let commit = gix_object::Commit {
+            tree: merge.tree,
+            parents: [current, other].into_iter().collect(),
+            author,
+            committer: committer.clone(),
+            encoding: original.and_then(|c| c.encoding.map(ToOwned::to_owned)),
+            message,
+            extra_headers: Vec::new(),
+        };
+        Ok(self.write_object(&commit)?.detach())
+    }
+
+    fn tree_of_commit(&self, id: Option<ObjectId>) -> Result<ObjectId, Error> {
+        Ok(match id {
+            Some(id) => self.find_commit(id)?.tree_id()?.detach(),
+            None => ObjectId::empty_tree(self.object_hash()),
+        })
+    }
+}
@@ -49,6 +49,9 @@ impl crate::Repository {
 
     /// Remove the currently set reference namespace and return it, affecting only this `Easy`.
     pub fn clear_namespace(&mut self) -> Option<gix_ref::Namespace> {
+        if let Some(reftable) = self.reftable.as_mut() {
+            reftable.namespace = None;
+        }
         self.refs.namespace.take()
     }
 
@@ -64,6 +67,9 @@ impl crate::Repository {
         gix_validate::reference::name::Error: From<E>,
     {
         let namespace = gix_ref::namespace::expand(namespace)?;
+        if let Some(reftable) = self.reftable.as_mut() {
+            reftable.namespace = Some(namespace.clone());
+        }
         Ok(self.refs.namespace.replace(namespace))
     }
 
@@ -143,12 +149,73 @@ impl crate::Repository {
         &self,
         edits: impl IntoIterator<Item = RefEdit>,
     ) -> Result<Vec<RefEdit>, reference::edit::Error> {
+        self.prepare_reference_edits(edits)?.commit()
+    }
+
+    /// Lock all references affected by `edits` and verify their expectations, but leave it to the caller to
+    /// [commit](reference::transaction::Prepared::commit()) or [roll back](reference::transaction::Prepared::rollback())
+    /// the edits once other work that must happen before the references change was done.
+    ///
+    /// Dropping the returned instance rolls back all edits.
+    ///
+    /// Note that the `reference-transaction` hook isn't run if references are kept in a reftable, and that the lock
+    /// on the stack of tables is acquired with the timeout of `core.filesRefLockTimeout`.
+    pub fn prepare_reference_edits(
+        &self,
+        edits: impl IntoIterator<Item = RefEdit>,
+    ) -> Result<reference::transaction::Prepared<'_>, reference::edit::Error> {
         let (file_lock_fail, packed_refs_lock_fail) = self.config.lock_timeout()?;
-        self.refs
-            .transaction()
-            .prepare(edits, file_lock_fail, packed_refs_lock_fail)?
-            .commit(self.committer().transpose()?)
-            .map_err(Into::into)
+        let transaction = match &self.reftable {
+            Some(store) => reference::transaction::Transaction::Reftable(
+                store.transaction().prepare(edits, file_lock_fail)?,
+            ),
+            None => reference::transaction::Transaction::File(
+                self.ref_transaction()?
+                    .prepare(edits, file_lock_fail, packed_refs_lock_fail)?,
+            ),
+        };
+        Ok(reference::transaction::Prepared {
+            repo: self,
+            transaction,
+        })
+    }
+
+    /// Create a new transaction on the reference store which runs the `reference-transaction` hook if there is one
+    /// and if [hooks may be run](crate::open::Options::run_hooks()).
+    pub(crate) fn ref_transaction(&self) -> Result<gix_ref::file::Transaction<'_, '_>, reference::edit::Error> {
+        let transaction = self.refs.transaction();
+        #[cfg(feature = "command")]
+        if let Some(hook) = self.hook_path("reference-transaction")? {
+            return Ok(transaction.hook(move |state, updates| {
+                reference::transaction::hook::run(self, &hook, state, updates).map_err(Into::into)
+            }));
+        }
+        Ok(transaction)
+    }
+
+    /// Return the path to the executable hook named `name`, like `pre-receive`, or `None` if it doesn't exist
+    /// or if [hooks must not be run](crate::open::Options::run_hooks()).
+    ///
+    /// Like `git`, a relative `core.hooksPath` is relative to the working tree, or the `.git` directory in bare repositories.
+    #[cfg(feature = "command")]
+    pub fn hook_path(&self, name: &str) -> Result<Option<std::path::PathBuf>, gix_config::path::interpolate::Error> {
+        if !self.options.run_hooks || self.git_dir_trust() != gix_sec::Trust::Full {
+            return Ok(None);
+        }
+        let hooks_dir = match self
+            .config
+            .trusted_file_path(&crate::config::tree::Core::HOOKS_PATH)
+            .transpose()?
+        {
+            Some(path) => self.work_dir().unwrap_or(self.git_dir()).join(path),
+            None => self.common_dir().join("hooks"),
+        };
+        let path = hooks_dir.join(name);
+        Ok(std::fs::metadata(&path)
+            .map_or(false, |md| {
+                md.is_file() && (cfg!(windows) || gix_fs::is_executable(&md))
+            })
+            .then_some(path))
     }
 
     /// Return the repository head, an abstraction to help dealing with the `HEAD` reference.
@@ -235,7 +302,10 @@ impl crate::Repository {
     /// references.
     pub fn references(&self) -> Result<reference::iter::Platform<'_>, reference::iter::Error> {
         Ok(reference::iter::Platform {
-            platform: self.refs.iter()?,
+            platform: match &self.reftable {
+                Some(store) => reference::iter::PlatformInner::Reftable(store),
+                None => reference::iter::PlatformInner::Loose(self.refs.iter()?),
+            },
             repo: self,
         })
     }
@@ -249,13 +319,13 @@ impl crate::Repository {
         Name: TryInto<&'a PartialNameRef, Error = E>,
         gix_ref::file::find::Error: From<E>,
     {
-        let state = self;
-        match state.refs.try_find(name) {
-            Ok(r) => match r {
-                Some(r) => Ok(Some(Reference::from_ref(r, self))),
-                None => Ok(None),
-            },
-            Err(err) => Err(err.into()),
-        }
+        let reference = match &self.reftable {
+            Some(store) => {
+                let name = name.try_into().map_err(gix_ref::file::find::Error::from)?;
+                store.try_find(name)?
+            }
+            None => self.refs.try_find(name)?,
+        };
+        Ok(reference.map(|r| Reference::from_ref(r, self)))
     }
 }
@@ -104,6 +104,67 @@ impl crate::Repository {
             .collect())
     }
 
+    /// Return all `references` which contain `commit`, i.e. whose commit can reach `commit` by following its parents,
+    /// similar to `git branch --contains <commit>` or `git tag --contains <commit>`.
+    ///
+    /// References are peeled to their commit first, and those which don't point to a commit are never included.
+    ///
+    /// # Performance
+    /// Generation numbers of the commit-graph, if enabled, are used to avoid traversing commits that can't
+    /// reach `commit` anyway.
+    #[cfg(feature = "revision")]
+    pub fn references_containing<'repo>(
+        &'repo self,
+        commit: impl Into<gix_hash::ObjectId>,
+        references: impl IntoIterator<Item = crate::Reference<'repo>>,
+    ) -> Result<Vec<crate::Reference<'repo>>, super::references_reachability::Error> {
+        let commit = commit.into();
+        self.filter_references_by_reachability(references, |oracle, reference_id| {
+            oracle.is_ancestor(commit, reference_id)
+        })
+    }
+
+    /// Return all `references` which are fully merged into `target`, i.e. whose commit can be reached from `target`
+    /// by following its parents, similar to `git branch --merged <target>`.
+    ///
+    /// References are peeled to their commit first, and those which don't point to a commit are never included.
+    ///
+    /// # Performance
+    /// Generation numbers of the commit-graph, if enabled, are used to avoid traversing commits that can't
+    /// reach the reference commits anyway.
+    #[cfg(feature = "revision")]
+    pub fn references_merged_into<'repo>(
+        &'repo self,
+        target: impl Into<gix_hash::ObjectId>,
+        references: impl IntoIterator<Item = crate::Reference<'repo>>,
+    ) -> Result<Vec<crate::Reference<'repo>>, super::references_reachability::Error> {
+        let target = target.into();
+        self.filter_references_by_reachability(references, |oracle, reference_id| {
+            oracle.is_ancestor(reference_id, target)
+        })
+    }
+
+    #[cfg(feature = "revision")]
+    fn filter_references_by_reachability<'repo>(
+        &'repo self,
+        references: impl IntoIterator<Item = crate::Reference<'repo>>,
+        mut keep: impl FnMut(
+            &mut gix_revision::reachability::Oracle<'_, '_>,
+            gix_hash::ObjectId,
+        ) -> Result<bool, gix_revision::reachability::Error>,
+    ) -> Result<Vec<crate::Reference<'repo>>, super::references_reachability::Error> {
+        let cache = self.commit_graph_if_enabled()?;
+        let mut oracle = gix_revision::reachability::Oracle::new(&self.objects, cache.as_ref());
+        let mut out = Vec::new();
+        for reference in references {
+            let id = reference.clone().peel_to_id_in_place()?.detach();
+            if keep(&mut oracle, id)? {
+                out.push(reference);
+            }
+        }
+        Ok(out)
+    }
+
     /// Create the baseline for a revision walk by initializing it with the `tips` to start iterating on.
     ///
     /// It can be configured further before starting the actual walk.
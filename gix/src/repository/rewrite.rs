@@ -0,0 +1,223 @@
+use gix_hash::ObjectId;
+use gix_hashtable::{HashMap, HashSet};
+use gix_object::FindExt;
+use gix_odb::HeaderExt;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::rewrite::{history::Error, Action, Delegate, Options, Outcome};
+
+/// A reference to rewrite, along with the tag object it points to, if any.
+struct Tip {
+    name: FullName,
+    target: ObjectId,
+    commit: ObjectId,
+    tag: Option<gix_object::Tag>,
+}
+
+/// History rewriting
+impl crate::Repository {
+    /// Rewrite all commits reachable from references that point to commits, or annotated tags of commits, by passing each
+    /// of them to `delegate`, parents first, and update the references to point to the rewritten commits.
+    ///
+    /// New objects are collected in memory and written into packs once [enough of them](Options::objects_in_memory_limit)
+    /// were created, which is much faster than writing loose objects.
+    /// Commits that don't change keep their id, and references that don't change aren't touched.
+    ///
+    /// Note that symbolic references like `HEAD` follow their targets, and that references whose commits were dropped
+    /// along with all of their ancestors are deleted.
+    pub fn rewrite_history(&self, delegate: &mut dyn Delegate, options: Options) -> Result<Outcome, Error> {
+        let mut repo = self.clone();
+        repo.objects.enable_object_memory();
+        let writer = gix_odb::bulk::Writer::new(
+            self.objects.store_ref().path(),
+            self.object_hash(),
+            gix_odb::bulk::Options::default(),
+        );
+        let tips = self.rewrite_tips()?;
+
+        let mut buf = Vec::new();
+        let mut parents_by_commit = HashMap::<ObjectId, (ObjectId, Vec<ObjectId>)>::default();
+        let mut commits_in_order = Vec::new();
+        {
+            let mut seen = HashSet::default();
+            let mut stack: Vec<_> = tips.iter().rev().map(|tip| (tip.commit, false)).collect();
+            while let Some((id, parents_are_done)) = stack.pop() {
+                if parents_are_done {
+                    commits_in_order.push(id);
+                    continue;
+                }
+                if !seen.insert(id) {
+                    continue;
+                }
+                let commit = self.objects.find_commit(&id, &mut buf)?;
+                let parents: Vec<_> = commit.parents().collect();
+                stack.push((id, true));
+                stack.extend(
+                    parents
+                        .iter()
+                        .rev()
+                        .filter(|parent| !seen.contains(*parent))
+                        .map(|parent| (*parent, false)),
+                );
+                parents_by_commit.insert(id, (commit.tree(), parents));
+            }
+        }
+
+        let empty_tree = ObjectId::empty_tree(self.object_hash());
+        let mut commits = HashMap::<ObjectId, Option<ObjectId>>::default();
+        let mut new_trees = HashMap::<ObjectId, ObjectId>::default();
+        let mut rewritten_trees = HashMap::<ObjectId, ObjectId>::default();
+        for id in commits_in_order {
+            let mut commit: gix_object::Commit = repo.objects.find_commit(&id, &mut buf)?.into();
+            let (original_tree, original_parents) = &parents_by_commit[&id];
+            let mut parents = Vec::with_capacity(original_parents.len());
+            for new_parent in original_parents.iter().filter_map(|parent| commits[parent]) {
+                if !parents.contains(&new_parent) {
+                    parents.push(new_parent);
+                }
+            }
+            commit.parents = parents.into();
+            commit.tree = match rewritten_trees.get(original_tree) {
+                Some(tree) => *tree,
+                None => {
+                    let mut editor = repo.edit_tree(*original_tree)?;
+                    delegate.rewrite_tree(&mut editor).map_err(Error::Delegate)?;
+                    let tree = editor.write()?.detach();
+                    rewritten_trees.insert(*original_tree, tree);
+                    tree
+                }
+            };
+
+            let action = delegate.rewrite_commit(&id, &mut commit).map_err(Error::Delegate)?;
+            let first_parent = commit.parents.first().copied();
+            let became_empty = options.prune_empty && commit.parents.len() <= 1 && {
+                let parent_tree = match first_parent {
+                    Some(parent) => match new_trees.get(&parent) {
+                        Some(tree) => *tree,
+                        None => repo.objects.find_commit(&parent, &mut buf)?.tree(),
+                    },
+                    None => empty_tree,
+                };
+                let original_parent_tree = original_parents
+                    .first()
+                    .map_or(empty_tree, |parent| parents_by_commit[parent].0);
+                commit.tree == parent_tree && *original_tree != original_parent_tree
+            };
+            if action == Action::Drop || became_empty {
+                commits.insert(id, first_parent);
+                continue;
+            }
+
+            let tree = commit.tree;
+            let new_id = repo.write_object(commit)?.detach();
+            new_trees.insert(new_id, tree);
+            commits.insert(id, Some(new_id));
+            if repo.objects.num_objects_in_memory() >= options.objects_in_memory_limit {
+                write_object_memory(&mut repo, &writer)?;
+            }
+        }
+
+        let mut ref_edits = Vec::new();
+        for tip in tips {
+            let new_commit = commits[&tip.commit];
+            let new_target = match (new_commit, tip.tag) {
+                (Some(new_commit), Some(mut tag)) if new_commit != tip.commit => {
+                    tag.target = new_commit;
+                    Some(repo.write_object(tag)?.detach())
+                }
+                (Some(new_commit), None) => Some(new_commit),
+                (Some(_unchanged), Some(_tag)) => Some(tip.target),
+                (None, _) => None,
+            };
+            if new_target == Some(tip.target) {
+                continue;
+            }
+            let expected = PreviousValue::MustExistAndMatch(Target::Object(tip.target));
+            ref_edits.push(RefEdit {
+                change: match new_target {
+                    Some(new) => Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: "rewrite history".into(),
+                        },
+                        expected,
+                        new: Target::Object(new),
+                    },
+                    None => Change::Delete {
+                        expected,
+                        log: RefLog::AndReference,
+                    },
+                },
+                name: tip.name,
+                deref: false,
+            });
+        }
+        write_object_memory(&mut repo, &writer)?;
+
+        let ref_edits = self.edit_references(ref_edits)?;
+        let packs = writer.take_written_packs();
+        for keep_path in packs.iter().filter_map(|pack| pack.keep_path.as_deref()) {
+            std::fs::remove_file(keep_path).map_err(|source| Error::RemoveKeepFile {
+                path: keep_path.to_owned(),
+                source,
+            })?;
+        }
+        Ok(Outcome {
+            commits,
+            ref_edits,
+            packs,
+        })
+    }
+
+    /// Return all references that directly point to commits or to annotated tags pointing to commits.
+    fn rewrite_tips(&self) -> Result<Vec<Tip>, Error> {
+        let mut tips = Vec::new();
+        let mut buf = Vec::new();
+        for reference in self.references()?.all()? {
+            let reference = reference.map_err(Error::IterReferences)?.detach();
+            let target = match reference.target {
+                Target::Object(id) => id,
+                Target::Symbolic(_) => continue,
+            };
+            match self.objects.header(target)?.kind() {
+                gix_object::Kind::Commit => tips.push(Tip {
+                    name: reference.name,
+                    target,
+                    commit: target,
+                    tag: None,
+                }),
+                gix_object::Kind::Tag => {
+                    let tag: gix_object::Tag = self.objects.find_tag(&target, &mut buf)?.into();
+                    if tag.target_kind == gix_object::Kind::Commit {
+                        tips.push(Tip {
+                            name: reference.name,
+                            target,
+                            commit: tag.target,
+                            tag: Some(tag),
+                        });
+                    }
+                }
+                gix_object::Kind::Tree | gix_object::Kind::Blob => {}
+            }
+        }
+        Ok(tips)
+    }
+}
+
+/// Move all objects from the memory of `repo` into a new pack, so they remain accessible to `repo`.
+fn write_object_memory(repo: &mut crate::Repository, writer: &gix_odb::bulk::Writer) -> Result<(), Error> {
+    let Some(mut objects) = repo.objects.take_object_memory() else {
+        return Ok(());
+    };
+    repo.objects.enable_object_memory();
+    if objects.is_empty() {
+        return Ok(());
+    }
+    writer.write_many(objects.drain().map(|(_id, object)| object).collect())?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,459 @@
+use std::{collections::BTreeMap, path::Path, sync::atomic::AtomicBool};
+
+use gix_dir::{
+    entry::{Kind, Status},
+    walk::EmissionMode,
+};
+
+use gix_hash::ObjectId;
+use gix_object::tree::EntryKind;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+use gix_status::index_as_worktree::{self, EntryStatus};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Merge, Rebase},
+    },
+    merge::{blob::Labels, tree::Entry},
+    stash::{AutoStash, Error, Operation},
+    status::index_worktree::iter::Item,
+};
+
+impl crate::Repository {
+    /// Store the changes of the index compared to `HEAD` and the changes of all tracked files in the worktree compared to
+    /// the index in a new stash commit, and return it, or `None` if there are no changes. This is what `git stash create` does.
+    ///
+    /// If `message` is set, it describes the changes instead of the summary of the `HEAD` commit.
+    /// Note that the index, the worktree and all references remain unchanged, and that untracked files are never stashed.
+    pub fn stash_create(&self, message: Option<&BStr>) -> Result<Option<ObjectId>, Error> {
+        self.stash_create_opts(message, Default::default())
+    }
+
+    /// Like [`stash_create()`](Self::stash_create()), but configurable with `options`, which allows to also stash
+    /// untracked files in a commit that is the third parent of the stash, just like `git stash push --include-untracked` does.
+    ///
+    /// Note that untracked files also remain unchanged.
+    pub fn stash_create_opts(
+        &self,
+        message: Option<&BStr>,
+        options: crate::stash::create::Options,
+    ) -> Result<Option<ObjectId>, Error> {
+        let workdir = self.work_dir().ok_or(Error::MissingWorkDir)?;
+        let head = self.head()?;
+        if head.is_unborn() {
+            return Err(Error::UnbornHead);
+        }
+        let branch: BString = head
+            .referent_name()
+            .map_or_else(|| "(no branch)".into(), |name| name.shorten().to_owned());
+        let head = self.head_commit()?;
+        let head_tree = head.tree_id()?.detach();
+        let description = format!(
+            "{}: {} {}",
+            branch,
+            head.id().shorten_or_id(),
+            head.message()?.summary()
+        );
+
+        let index = self.index_or_empty()?;
+        let mut editor = gix_object::tree::Editor::new(Default::default(), &self.objects, self.object_hash());
+        for entry in index.entries() {
+            let path = entry.path(&index);
+            if entry.stage_raw() != 0 {
+                return Err(Error::Unmerged { path: path.to_owned() });
+            }
+            let Some(mode) = entry.mode.to_tree_entry_mode() else {
+                continue;
+            };
+            if !entry.flags.contains(gix_index::entry::Flags::INTENT_TO_ADD) {
+                editor.upsert(path.split_str("/"), mode.kind(), entry.id)?;
+            }
+        }
+        let index_tree = editor.write(|tree| self.write_object(tree).map(crate::Id::detach))?;
+
+        let (mut pipeline, _) = self.filter_pipeline(None)?;
+        let executable_bit = self.config.fs_capabilities()?.executable_bit;
+        for item in self
+            .status(gix_features::progress::Discard)?
+            .index(crate::worktree::IndexPersistedOrInMemory::Persisted(index.clone()))
+            .index_worktree_rewrites(None)
+            .index_worktree_submodules(None)
+            .index_worktree_options_mut(|opts| {
+                opts.dirwalk_options = None;
+            })
+            .into_index_worktree_iter(Vec::new())?
+        {
+            let Item::Modification {
+                entry,
+                rela_path,
+                status,
+                ..
+            } = item?
+            else {
+                continue;
+            };
+            let executable_bit_changed = match status {
+                EntryStatus::Change(index_as_worktree::Change::Removed) => {
+                    editor.remove(rela_path.split_str("/"))?;
+                    continue;
+                }
+                EntryStatus::Change(index_as_worktree::Change::Modification {
+                    executable_bit_changed, ..
+                }) => executable_bit_changed,
+                EntryStatus::Change(index_as_worktree::Change::Type) | EntryStatus::IntentToAdd => false,
+                _ => continue,
+            };
+            let was_executable = entry.mode == gix_index::entry::Mode::FILE_EXECUTABLE;
+            let (kind, id) = self.worktree_blob(&mut pipeline, &index, workdir, rela_path.as_bstr(), |_| {
+                executable_bit && (was_executable != executable_bit_changed)
+            })?;
+            editor.upsert(rela_path.split_str("/"), kind, id)?;
+        }
+        let worktree_tree = editor.write(|tree| self.write_object(tree).map(crate::Id::detach))?;
+
+        let mut untracked_tree = None;
+        if options.include_untracked {
+            let mut collect = gix_dir::walk::delegate::Collect::default();
+            self.dirwalk(
+                &index,
+                None::<&str>,
+                &AtomicBool::default(),
+                self.dirwalk_options()?.emit_untracked(EmissionMode::Matching),
+                &mut collect,
+            )?;
+            let mut editor = gix_object::tree::Editor::new(Default::default(), &self.objects, self.object_hash());
+            let mut has_untracked = false;
+            for (entry, _) in collect.into_entries_by_path() {
+                if entry.status != Status::Untracked || !matches!(entry.disk_kind, Some(Kind::File | Kind::Symlink)) {
+                    continue;
+                }
+                let (kind, id) =
+                    self.worktree_blob(&mut pipeline, &index, workdir, entry.rela_path.as_bstr(), |meta| {
+                        executable_bit && gix_fs::is_executable(meta)
+                    })?;
+                editor.upsert(entry.rela_path.split_str("/"), kind, id)?;
+                has_untracked = true;
+            }
+            if has_untracked {
+                untracked_tree = Some(editor.write(|tree| self.write_object(tree).map(crate::Id::detach))?);
+            }
+        }
+        if index_tree == head_tree && worktree_tree == index_tree && untracked_tree.is_none() {
+            return Ok(None);
+        }
+
+        let head_id = head.id;
+        let index_commit = self.write_stash_commit(index_tree, &[head_id], format!("index on {description}\n"))?;
+        let mut parents = vec![head_id, index_commit];
+        if let Some(tree) = untracked_tree {
+            parents.push(self.write_stash_commit(tree, &[], format!("untracked files on {description}\n"))?);
+        }
+        let message = match message {
+            Some(message) => format!("On {branch}: {message}\n"),
+            None => format!("WIP on {description}\n"),
+        };
+        self.write_stash_commit(worktree_tree, &parents, message).map(Some)
+    }
+
+    /// Add `stash` to the stash list by pointing `refs/stash` to it, with `message` in its reflog.
+    /// This is what `git stash store` does.
+    pub fn stash_store(&self, stash: impl Into<ObjectId>, message: impl Into<BString>) -> Result<(), Error> {
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: true,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Object(stash.into()),
+            },
+            name: "refs/stash".try_into().expect("valid"),
+            deref: false,
+        })?;
+        Ok(())
+    }
+
+    /// Apply the changes stored in `stash` onto `HEAD` and write the result to the worktree, which is what
+    /// `git stash apply` does.
+    ///
+    /// Like `git`, the changes aren't added to the index, except for files that are new compared to `HEAD`.
+    /// Untracked files stored in the stash are restored as untracked files, and none of them may exist in the worktree.
+    /// If the changes conflict with `HEAD`, nothing is changed and [`Error::Conflict`] is returned.
+    ///
+    /// Note that the index and worktree must not have any changes, and that the worktree is expected to match either
+    /// the tree of `HEAD` or the tree of the commit that was checked out when the stash was created.
+    pub fn stash_apply(&self, stash: impl Into<ObjectId>) -> Result<(), Error> {
+        let stash = stash.into();
+        let parents: Vec<_> = self.find_commit(stash)?.parent_ids().map(crate::Id::detach).collect();
+        let base = *parents.first().ok_or(Error::NotAStash { id: stash })?;
+        if self.is_dirty()? {
+            return Err(Error::Dirty);
+        }
+        let workdir = self.work_dir().ok_or(Error::MissingWorkDir)?;
+        let untracked = match parents.get(2) {
+            Some(untracked) => self.entries_by_path(self.find_commit(*untracked)?.tree_id()?.detach())?,
+            None => Default::default(),
+        };
+        if let Some(path) = untracked.keys().find(|path| {
+            workdir
+                .join(gix_path::from_bstr(path.as_bstr()))
+                .symlink_metadata()
+                .is_ok()
+        }) {
+            return Err(Error::UntrackedExists { path: path.clone() });
+        }
+        let head = self.head_commit()?;
+        let merge = self.merge_trees(
+            Some(base),
+            head.id,
+            stash,
+            Labels {
+                ours: "Updated upstream".into(),
+                theirs: "Stashed changes".into(),
+            },
+        )?;
+        if merge.has_conflicts() {
+            return Err(Error::Conflict {
+                conflicts: merge.conflicts,
+            });
+        }
+        let written = self.checkout_tree_changes(self.find_commit(base)?.tree_id()?.detach(), merge.tree)?;
+
+        let head_tree = head.tree_id()?.detach();
+        let head_entries = self.entries_by_path(head_tree)?;
+        let mut editor = gix_object::tree::Editor::new(
+            self.find_tree(head_tree)?.decode()?.into(),
+            &self.objects,
+            self.object_hash(),
+        );
+        for (path, entry) in self.entries_by_path(merge.tree)? {
+            if !head_entries.contains_key(&path) {
+                editor.upsert(path.split_str("/"), entry.mode.kind(), entry.id)?;
+            }
+        }
+        let index_tree = editor.write(|tree| self.write_object(tree).map(crate::Id::detach))?;
+        self.reset_index(index_tree, &written)?;
+
+        if !untracked.is_empty() {
+            let (mut pipeline, _) = self.filter_pipeline(None)?;
+            let executable_bit = self.config.fs_capabilities()?.executable_bit;
+            for (path, entry) in &untracked {
+                self.checkout_entry(&mut pipeline, workdir, path.as_bstr(), entry, executable_bit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stash all local changes if this is enabled for `operation` by configuration, or if `enabled` is `Some(true)`, and
+    /// reset the index and worktree to `HEAD` so that `operation` can be performed.
+    ///
+    /// Return `None` if nothing was stashed, or the stash to [reapply](AutoStash::apply()) once `operation` is done,
+    /// whether it succeeded or not. This is what `git` does with `--autostash`.
+    pub fn autostash(&self, operation: Operation, enabled: Option<bool>) -> Result<Option<AutoStash<'_>>, Error> {
+        let enabled = match (enabled, operation) {
+            (Some(enabled), _) => enabled,
+            (None, Operation::Checkout) => false,
+            (None, Operation::Rebase | Operation::Merge) => {
+                let key = match operation {
+                    Operation::Rebase => &Rebase::AUTO_STASH,
+                    _ => &Merge::AUTO_STASH,
+                };
+                self.config
+                    .resolved
+                    .boolean(key)
+                    .map(|res| key.enrich_error(res))
+                    .transpose()
+                    .with_leniency(self.config.lenient_config)?
+                    .unwrap_or_default()
+            }
+        };
+        if !enabled {
+            return Ok(None);
+        }
+        let Some(id) = self.stash_create(Some("autostash".into()))? else {
+            return Ok(None);
+        };
+        let stash = AutoStash {
+            repo: self,
+            id: Some(id),
+        };
+        let commit = self.find_commit(id)?;
+        let base = commit.parent_ids().next().ok_or(Error::NotAStash { id })?;
+        let base_tree = self.find_commit(base)?.tree_id()?.detach();
+        let written = self.checkout_tree_changes(commit.tree_id()?.detach(), base_tree)?;
+        self.reset_index(base_tree, &written)?;
+        Ok(Some(stash))
+    }
+
+    fn write_stash_commit(&self, tree: ObjectId, parents: &[ObjectId], message: String) -> Result<ObjectId, Error> {
+        let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+        let author = self.author().ok_or(crate::commit::Error::AuthorMissing)??;
+        let commit = gix_object::Commit {
+            tree,
+            parents: parents.iter().copied().collect(),
+            author: author.into(),
+            committer: committer.into(),
+            encoding: None,
+            message: message.into(),
+            extra_headers: Vec::new(),
+        };
+        Ok(self.write_object(&commit)?.detach())
+    }
+
+    /// Write the file or symlink at `rela_path` as blob after converting it with `pipeline`, and return it along with
+    /// its kind. `is_executable` determines if a file is executable, given its metadata.
+    fn worktree_blob(
+        &self,
+        pipeline: &mut crate::filter::Pipeline<'_>,
+        index: &gix_index::State,
+        workdir: &Path,
+        rela_path: &BStr,
+        is_executable: impl FnOnce(&std::fs::Metadata) -> bool,
+    ) -> Result<(EntryKind, ObjectId), Error> {
+        let path = workdir.join(gix_path::from_bstr(rela_path));
+        let io_err = |source| Error::Io {
+            path: rela_path.to_owned(),
+            source,
+        };
+        let meta = std::fs::symlink_metadata(&path).map_err(io_err)?;
+        let (kind, data) = if meta.is_symlink() {
+            let target = std::fs::read_link(&path).map_err(io_err)?;
+            let target = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(target)).into_owned();
+            (EntryKind::Link, target.into())
+        } else {
+            let file = std::fs::File::open(&path).map_err(io_err)?;
+            let mut data = Vec::new();
+            std::io::copy(
+                &mut pipeline.convert_to_git(file, gix_path::from_bstr(rela_path).as_ref(), index)?,
+                &mut data,
+            )
+            .map_err(io_err)?;
+            let kind = if is_executable(&meta) {
+                EntryKind::BlobExecutable
+            } else {
+                EntryKind::Blob
+            };
+            (kind, data)
+        };
+        Ok((kind, self.write_blob(data)?.detach()))
+    }
+
+    /// Change all tracked files in the worktree which differ between the trees `from` and `to` to match `to`,
+    /// assuming that the worktree currently matches `from`, and return the entries that were written.
+    fn checkout_tree_changes(&self, from: ObjectId, to: ObjectId) -> Result<BTreeMap<BString, Entry>, Error> {
+        let workdir = self.work_dir().ok_or(Error::MissingWorkDir)?;
+        let from = self.entries_by_path(from)?;
+        let to = self.entries_by_path(to)?;
+        // Delete first so that files can take the place of directories that became empty.
+        for path in from.keys().filter(|path| !to.contains_key(*path)) {
+            let file = workdir.join(gix_path::from_bstr(path.as_bstr()));
+            match std::fs::remove_file(&file) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(Error::Io {
+                        path: path.clone(),
+                        source: err,
+                    })
+                }
+                _ => {}
+            }
+            if let Some(parent) = file.parent() {
+                gix_fs::dir::remove::empty_upward_until_boundary(parent, workdir).ok();
+            }
+        }
+
+        let (mut pipeline, _) = self.filter_pipeline(None)?;
+        let executable_bit = self.config.fs_capabilities()?.executable_bit;
+        let mut written = to;
+        written.retain(|path, entry| from.get(path) != Some(entry));
+        for (path, entry) in &written {
+            self.checkout_entry(&mut pipeline, workdir, path.as_bstr(), entry, executable_bit)?;
+        }
+        Ok(written)
+    }
+
+    fn checkout_entry(
+        &self,
+        pipeline: &mut crate::filter::Pipeline<'_>,
+        workdir: &Path,
+        rela_path: &BStr,
+        entry: &Entry,
+        executable_bit: bool,
+    ) -> Result<(), Error> {
+        let kind = entry.mode.kind();
+        if matches!(kind, EntryKind::Commit | EntryKind::Tree) {
+            // Submodules are left as they are.
+            return Ok(());
+        }
+        let path = workdir.join(gix_path::from_bstr(rela_path));
+        let io_err = |source| Error::Io {
+            path: rela_path.to_owned(),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) if !meta.is_dir() => std::fs::remove_file(&path).map_err(io_err)?,
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(io_err(err)),
+            _ => {}
+        }
+        let blob = self.find_blob(entry.id)?;
+        if kind == EntryKind::Link {
+            let target = gix_path::try_from_byte_slice(&blob.data).map_err(|_| {
+                io_err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "symlink target isn't a valid path",
+                ))
+            })?;
+            return gix_fs::symlink::create(target, &path).map_err(io_err);
+        }
+        let mut data = pipeline.convert_to_worktree(&blob.data, rela_path, gix_filter::driver::apply::Delay::Forbid)?;
+        let mut file = std::fs::File::create(&path).map_err(io_err)?;
+        std::io::copy(&mut data, &mut file).map_err(io_err)?;
+        #[cfg(unix)]
+        if executable_bit && kind == EntryKind::BlobExecutable {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o755))
+                .map_err(io_err)?;
+        }
+        #[cfg(not(unix))]
+        let _ = executable_bit;
+        Ok(())
+    }
+
+    /// Write an index that matches `tree`, keeping the filesystem information of entries that didn't change to avoid
+    /// having to read their files again.
+    ///
+    /// Entries that were `written` to the worktree get the filesystem information of their file if it matches them,
+    /// as it may differ from what was seen before even if the entry didn't change, for instance due to filters.
+    fn reset_index(&self, tree: ObjectId, written: &BTreeMap<BString, Entry>) -> Result<(), Error> {
+        let workdir = self.work_dir().ok_or(Error::MissingWorkDir)?;
+        let previous = self.index_or_empty()?;
+        let mut index = self.index_from_tree(&tree)?;
+        for (entry, path) in index.entries_mut_with_paths() {
+            if let Some(written) = written.get(path) {
+                if written.id == entry.id {
+                    let meta = gix_index::fs::Metadata::from_path_no_follow(&workdir.join(gix_path::from_bstr(path)))
+                        .map_err(|source| Error::Io {
+                        path: path.to_owned(),
+                        source,
+                    })?;
+                    entry.stat = gix_index::entry::Stat::from_fs(&meta).unwrap_or_default();
+                }
+            } else if let Some(previous) = previous
+                .entry_by_path(path)
+                .filter(|previous| previous.id == entry.id && previous.mode == entry.mode)
+            {
+                entry.stat = previous.stat;
+            }
+        }
+        index.write(Default::default())?;
+        Ok(())
+    }
+}
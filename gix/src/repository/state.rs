@@ -1,4 +1,9 @@
-use crate::state;
+use std::{
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::{repository::state_signature, state};
 
 impl crate::Repository {
     /// Returns the status of an in progress operation on a repository or [`None`]
@@ -42,3 +47,56 @@ impl crate::Repository {
         }
     }
 }
+
+impl crate::Repository {
+    /// Return a cheap [signature](state::Signature) of `HEAD`, the branch it points to, the index and `packed-refs`
+    /// which can be compared to a previous one to learn if any of these may have changed.
+    ///
+    /// Only the end of the index is read to obtain its checksum, and `packed-refs` is only checked for its modification
+    /// time and size, which makes this suitable for frequent polling.
+    /// See the [type documentation](state::Signature) for what is and isn't covered.
+    pub fn state_signature(&self) -> Result<state::Signature, state_signature::Error> {
+        let head = self.head()?;
+        Ok(state::Signature {
+            head_name: head.referent_name().map(ToOwned::to_owned),
+            head_id: head.id().map(crate::Id::detach),
+            index: file_version(&self.index_path(), Some(self.object_hash()))?,
+            packed_refs: file_version(&self.refs.packed_refs_path(), None)?,
+        })
+    }
+}
+
+/// Return the version of the file at `path`, using its trailing checksum if `object_hash` is set and it has one.
+fn file_version(
+    path: &Path,
+    object_hash: Option<gix_hash::Kind>,
+) -> Result<Option<state::Version>, state_signature::Error> {
+    let io_err = |err| state_signature::Error::Io {
+        source: err,
+        path: path.to_owned(),
+    };
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_err(err)),
+    };
+    let metadata = file.metadata().map_err(io_err)?;
+    let len = metadata.len();
+    if let Some(object_hash) = object_hash {
+        let hash_len = object_hash.len_in_bytes() as u64;
+        const HEADER_LEN: u64 = 12;
+        if len >= HEADER_LEN + hash_len {
+            let mut checksum = object_hash.null();
+            file.seek(SeekFrom::Start(len - hash_len)).map_err(io_err)?;
+            file.read_exact(checksum.as_mut_slice()).map_err(io_err)?;
+            // The checksum is null if writing it was skipped with `index.skipHash`.
+            if !checksum.is_null() {
+                return Ok(Some(state::Version::Checksum(checksum)));
+            }
+        }
+    }
+    Ok(Some(state::Version::Stamp {
+        modified: metadata.modified().ok(),
+        len,
+    }))
+}
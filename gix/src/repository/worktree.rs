@@ -51,6 +51,19 @@ impl crate::Repository {
         self.config.is_bare && self.work_dir().is_none()
     }
 
+    /// Collect everything needed to checkout files into the worktree with [`gix_worktree_state::checkout()`](crate::worktree::state::checkout()),
+    /// using `attributes_source` to control where `.gitattributes` files are read from.
+    ///
+    /// Note that the returned options never overwrite existing files and assume the destination isn't empty,
+    /// which callers may have to adjust depending on their use-case.
+    #[cfg(feature = "worktree-mutation")]
+    pub fn checkout_options(
+        &self,
+        attributes_source: gix_worktree::stack::state::attributes::Source,
+    ) -> Result<gix_worktree_state::checkout::Options, crate::config::checkout_options::Error> {
+        self.config.checkout_options(self, attributes_source)
+    }
+
     /// If `id` points to a tree, produce a stream that yields one worktree entry after the other. The index of the tree at `id`
     /// is returned as well as it is an intermediate byproduct that might be useful to callers.
     ///
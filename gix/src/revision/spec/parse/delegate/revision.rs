@@ -19,7 +19,7 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         if !self.err.is_empty() && self.refs[self.idx].is_some() {
             return None;
         }
-        match self.repo.refs.find(name) {
+        match self.find_reference(name) {
             Ok(r) => {
                 assert!(self.refs[self.idx].is_none(), "BUG: cannot set the same ref twice");
                 self.refs[self.idx] = Some(r);
@@ -75,7 +75,7 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                         Some(())
                     }
                     RefsHint::PreferRef | RefsHint::PreferObjectOnFullLengthHexShaUseRefOtherwise | RefsHint::Fail => {
-                        match self.repo.refs.find(&prefix.to_string()) {
+                        match self.find_reference(prefix.to_string().as_str().into()) {
                             Ok(ref_) => {
                                 assert!(self.refs[self.idx].is_none(), "BUG: cannot set the same ref twice");
                                 if self.opts.refs_hint == RefsHint::Fail {
@@ -105,60 +105,92 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
 
     fn reflog(&mut self, query: ReflogLookup) -> Option<()> {
         self.unset_disambiguate_call();
+        let r = match &mut self.refs[self.idx] {
+            Some(r) => r.clone().attach(self.repo),
+            val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
+                Ok(Some(r)) => {
+                    *val = Some(r.clone().detach());
+                    r
+                }
+                Ok(None) => {
+                    self.err.push(Error::UnbornHeadsHaveNoRefLog);
+                    return None;
+                }
+                Err(err) => {
+                    self.err.push(err.into());
+                    return None;
+                }
+            },
+        };
+        let mut platform = r.log_iter();
         match query {
-            ReflogLookup::Date(_date) => {
-                // TODO: actually do this - this should be possible now despite incomplete date parsing
-                self.err.push(Error::Planned {
-                    dependency: "remote handling and ref-specs are fleshed out more",
-                });
-                None
-            }
-            ReflogLookup::Entry(no) => {
-                let r = match &mut self.refs[self.idx] {
-                    Some(r) => r.clone().attach(self.repo),
-                    val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
-                        Ok(Some(r)) => {
-                            *val = Some(r.clone().detach());
-                            r
-                        }
-                        Ok(None) => {
-                            self.err.push(Error::UnbornHeadsHaveNoRefLog);
-                            return None;
-                        }
-                        Err(err) => {
-                            self.err.push(err.into());
-                            return None;
-                        }
-                    },
-                };
-                let mut platform = r.log_iter();
-                match platform.rev().ok().flatten() {
-                    Some(mut it) => match it.nth(no).and_then(Result::ok) {
-                        Some(line) => {
+            ReflogLookup::Date(date) => match platform.rev().ok().flatten() {
+                Some(it) => {
+                    let mut oldest = None;
+                    for line in it.filter_map(Result::ok) {
+                        if line.signature.time.seconds <= date.seconds {
                             self.objs[self.idx]
                                 .get_or_insert_with(HashSet::default)
                                 .insert(line.new_oid);
+                            return Some(());
+                        }
+                        oldest = Some(line);
+                    }
+                    match oldest {
+                        Some(line) => {
+                            // Like git, use the state before the oldest entry if the date predates the whole log.
+                            let id = if line.previous_oid.is_null() {
+                                line.new_oid
+                            } else {
+                                line.previous_oid
+                            };
+                            self.objs[self.idx].get_or_insert_with(HashSet::default).insert(id);
                             Some(())
                         }
                         None => {
-                            let available = platform.rev().ok().flatten().map_or(0, Iterator::count);
                             self.err.push(Error::RefLogEntryOutOfRange {
                                 reference: r.detach(),
-                                desired: no,
-                                available,
+                                desired: 0,
+                                available: 0,
                             });
                             None
                         }
-                    },
+                    }
+                }
+                None => {
+                    self.err.push(Error::MissingRefLog {
+                        reference: r.name().as_bstr().into(),
+                        action: "lookup entry by date",
+                    });
+                    None
+                }
+            },
+            ReflogLookup::Entry(no) => match platform.rev().ok().flatten() {
+                Some(mut it) => match it.nth(no).and_then(Result::ok) {
+                    Some(line) => {
+                        self.objs[self.idx]
+                            .get_or_insert_with(HashSet::default)
+                            .insert(line.new_oid);
+                        Some(())
+                    }
                     None => {
-                        self.err.push(Error::MissingRefLog {
-                            reference: r.name().as_bstr().into(),
-                            action: "lookup entry",
+                        let available = platform.rev().ok().flatten().map_or(0, Iterator::count);
+                        self.err.push(Error::RefLogEntryOutOfRange {
+                            reference: r.detach(),
+                            desired: no,
+                            available,
                         });
                         None
                     }
+                },
+                None => {
+                    self.err.push(Error::MissingRefLog {
+                        reference: r.name().as_bstr().into(),
+                        action: "lookup entry",
+                    });
+                    None
                 }
-            }
+            },
         }
     }
 
@@ -265,3 +297,13 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         None
     }
 }
+
+impl<'repo> Delegate<'repo> {
+    /// Find the reference partially named `name` in whichever store the repository keeps its references.
+    fn find_reference(&self, name: &BStr) -> Result<gix_ref::Reference, Error> {
+        Ok(match &self.repo.reftable {
+            Some(store) => store.find(name)?,
+            None => self.repo.refs.find(name)?,
+        })
+    }
+}
@@ -156,6 +156,8 @@ pub enum Error {
     #[error(transparent)]
     FindReference(#[from] gix_ref::file::find::existing::Error),
     #[error(transparent)]
+    FindReftableReference(#[from] gix_ref::reftable::find::existing::Error),
+    #[error(transparent)]
     FindObject(#[from] object::find::existing::Error),
     #[error(transparent)]
     LookupPrefix(#[from] gix_odb::store::prefix::lookup::Error),
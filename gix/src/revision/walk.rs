@@ -155,6 +155,7 @@ pub struct Platform<'repo> {
     pub(crate) parents: gix_traverse::commit::Parents,
     pub(crate) use_commit_graph: Option<bool>,
     pub(crate) commit_graph: Option<gix_commitgraph::Graph>,
+    pub(crate) max_count: Option<usize>,
 }
 
 impl<'repo> Platform<'repo> {
@@ -167,6 +168,7 @@ impl<'repo> Platform<'repo> {
             use_commit_graph: None,
             commit_graph: None,
             prune: Vec::new(),
+            max_count: None,
         }
     }
 }
@@ -180,11 +182,22 @@ impl<'repo> Platform<'repo> {
     }
 
     /// Only traverse the first parent of the commit graph.
+    ///
+    /// Note that a cutoff date configured with [sorting](Self::sorting()) is still respected.
     pub fn first_parent_only(mut self) -> Self {
         self.parents = gix_traverse::commit::Parents::First;
         self
     }
 
+    /// Stop the traversal once `count` commits were returned, or set it to `None` to return all commits (the default).
+    ///
+    /// This is similar to `git log --max-count`. Combine it with [`ByCommitTimeNewestFirstCutoffOlderThan`](Sorting::ByCommitTimeNewestFirstCutoffOlderThan)
+    /// to obtain recent history quickly, as no more commits than needed will be looked at.
+    pub fn max_count(mut self, count: impl Into<Option<usize>>) -> Self {
+        self.max_count = count.into();
+        self
+    }
+
     /// Allow using the commitgraph, if present, if `toggle` is `true`, or disallow it with `false`. Set it to `None` to leave
     /// control over this to the configuration of `core.commitGraph` (the default).
     ///
@@ -255,6 +268,7 @@ impl<'repo> Platform<'repo> {
             use_commit_graph,
             commit_graph,
             mut prune,
+            max_count,
         } = self;
         prune.sort();
         Ok(revision::Walk {
@@ -300,7 +314,8 @@ impl<'repo> Platform<'repo> {
                         .then(|| self.repo.commit_graph().ok())
                         .flatten()),
                 )
-                .map(|res| res.map_err(iter::Error::from)),
+                .map(|res| res.map_err(iter::Error::from))
+                .take(max_count.unwrap_or(usize::MAX)),
             ),
         })
     }
@@ -0,0 +1,124 @@
+//! Rewrite the history of all references, similar to `git filter-repo`.
+//!
+//! Use [`Repository::rewrite_history()`](crate::Repository::rewrite_history()) with a [`Delegate`] to change trees,
+//! messages or signatures of each commit, or to drop commits entirely.
+#![allow(clippy::empty_docs)]
+
+use gix_hash::ObjectId;
+
+/// The error type used in [`Delegate`] methods.
+pub type DelegateError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// What to do with a commit after it was presented to [`Delegate::rewrite_commit()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Action {
+    /// Write the possibly changed commit.
+    Keep,
+    /// Drop the commit, so that its children use its first parent instead.
+    Drop,
+}
+
+/// The callbacks to use when [rewriting history](crate::Repository::rewrite_history()).
+pub trait Delegate {
+    /// Change the tree of a commit using `editor`, which starts out with the original tree of the commit.
+    ///
+    /// Note that the result is reused for all commits with the same original tree, so the changes must only depend on the tree
+    /// itself.
+    fn rewrite_tree(&mut self, _editor: &mut crate::object::tree::Editor<'_>) -> Result<(), DelegateError> {
+        Ok(())
+    }
+
+    /// Change `commit`, the decoded original commit with `id`, with its tree and parents already rewritten.
+    ///
+    /// Return [`Action::Drop`] to remove it from history.
+    fn rewrite_commit(
+        &mut self,
+        _id: &gix_hash::oid,
+        _commit: &mut gix_object::Commit,
+    ) -> Result<Action, DelegateError> {
+        Ok(Action::Keep)
+    }
+}
+
+/// Options for use in [`Repository::rewrite_history()`](crate::Repository::rewrite_history()).
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// If `true`, non-merge commits that didn't change anything after rewriting their tree are dropped, unless they
+    /// didn't change anything originally either.
+    pub prune_empty: bool,
+    /// The amount of newly written objects to keep in memory until they are written into a pack.
+    pub objects_in_memory_limit: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            prune_empty: true,
+            objects_in_memory_limit: 100_000,
+        }
+    }
+}
+
+/// The result of [`Repository::rewrite_history()`](crate::Repository::rewrite_history()).
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// A mapping of each original commit to its rewritten version, or to `None` if it was dropped along with all of its ancestors.
+    pub commits: gix_hashtable::HashMap<ObjectId, Option<ObjectId>>,
+    /// The edits of all references that were changed, as performed at the end of the operation.
+    pub ref_edits: Vec<gix_ref::transaction::RefEdit>,
+    /// The packs that were written to hold all new objects.
+    pub packs: Vec<gix_odb::pack::bundle::write::Outcome>,
+}
+
+impl Outcome {
+    /// Write a mapping of original commits to their rewritten versions to `out`, in the format of the `commit-map` file
+    /// of `git filter-repo`.
+    ///
+    /// Dropped commits are mapped to the null id.
+    pub fn write_commit_map(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+        let mut commits: Vec<_> = self.commits.iter().collect();
+        commits.sort_by_key(|(old, _)| **old);
+        out.write_all(b"old new\n")?;
+        for (old, new) in commits {
+            let new = new.unwrap_or_else(|| old.kind().null());
+            writeln!(out, "{old} {new}")?;
+        }
+        Ok(())
+    }
+}
+
+///
+pub mod history {
+    /// The error returned by [`Repository::rewrite_history()`](crate::Repository::rewrite_history()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenReferences(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        IterReferencesInit(#[from] crate::reference::iter::init::Error),
+        #[error("Could not read a reference")]
+        IterReferences(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        FindObject(#[from] gix_object::find::existing_object::Error),
+        #[error(transparent)]
+        FindHeader(#[from] gix_object::find::existing::Error),
+        #[error(transparent)]
+        EditTree(#[from] crate::repository::edit_tree::Error),
+        #[error(transparent)]
+        WriteTree(#[from] crate::object::tree::editor::write::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        WritePack(#[from] gix_odb::bulk::Error),
+        #[error(transparent)]
+        Delegate(crate::rewrite::DelegateError),
+        #[error(transparent)]
+        EditReferences(#[from] crate::reference::edit::Error),
+        #[error("Could not remove keep-file at '{}'", path.display())]
+        RemoveKeepFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+    }
+}
@@ -0,0 +1,166 @@
+//! Stash changes of the index and worktree in commits to restore them later, which is also used to automatically
+//! stash local changes around operations that move `HEAD`.
+//!
+//! A stash is a commit whose tree contains the tracked files of the worktree, with the commit that was checked out
+//! as its first parent and a commit containing the tree of the index as its second parent, just like `git stash` creates them.
+//! Use [`Repository::stash_create()`](crate::Repository::stash_create()) to create one, and
+//! [`Repository::stash_apply()`](crate::Repository::stash_apply()) to apply it to the index and worktree.
+use gix_hash::ObjectId;
+
+use crate::Repository;
+
+/// The operation around which local changes may be stashed automatically, which determines the configuration to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// A rebase, controlled by `rebase.autoStash`.
+    Rebase,
+    /// A merge, controlled by `merge.autoStash`.
+    Merge,
+    /// A checkout, for which changes are only stashed if enabled explicitly as there is no configuration for it.
+    Checkout,
+}
+
+/// Local changes that were stashed [automatically](Repository::autostash()) before an operation, and that are
+/// to be [reapplied](AutoStash::apply()) afterwards.
+///
+/// If dropped without being applied, the stash is stored in the stash list as `refs/stash` so that the changes aren't lost.
+#[derive(Debug)]
+pub struct AutoStash<'repo> {
+    pub(crate) repo: &'repo Repository,
+    pub(crate) id: Option<ObjectId>,
+}
+
+impl AutoStash<'_> {
+    /// The commit containing the stashed changes.
+    pub fn id(&self) -> ObjectId {
+        self.id.expect("only unset when applied or dropped")
+    }
+
+    /// Apply the stashed changes onto the current `HEAD`, or store them in the stash list as `refs/stash` if that
+    /// would cause conflicts, like `git` does after rebasing or merging.
+    ///
+    /// The index and worktree are expected to either match the tree of `HEAD` or the tree of the commit that was checked out
+    /// when the changes were stashed, the latter being the case if the operation doesn't update the worktree.
+    /// Either way, the index and worktree will match the tree of `HEAD` with the stashed changes applied to it.
+    pub fn apply(mut self) -> Result<Reapplied, Error> {
+        let id = self.id.take().expect("only unset when applied or dropped");
+        match self.repo.stash_apply(id) {
+            Ok(()) => Ok(Reapplied::Applied),
+            Err(Error::Conflict { conflicts }) => {
+                self.repo.stash_store(id, "autostash")?;
+                Ok(Reapplied::Stored { conflicts })
+            }
+            Err(err) => {
+                self.id = Some(id);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl Drop for AutoStash<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            self.repo.stash_store(id, "autostash").ok();
+        }
+    }
+}
+
+/// The outcome of [`AutoStash::apply()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reapplied {
+    /// The stashed changes were applied to the index and worktree.
+    Applied,
+    /// The stashed changes would have conflicted with the changes made by the operation, and were stored in the stash list
+    /// for the user to apply them later. The index and worktree weren't changed.
+    Stored {
+        /// The conflicts that applying the stashed changes would have caused.
+        conflicts: Vec<crate::merge::tree::Conflict>,
+    },
+}
+
+///
+pub mod create {
+    /// Options for [`Repository::stash_create_opts()`](crate::Repository::stash_create_opts()).
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Options {
+        /// If `true`, untracked files that aren't ignored are stored in a commit that becomes the third parent of the stash.
+        pub include_untracked: bool,
+    }
+}
+
+///
+pub mod error {
+    use gix_hash::ObjectId;
+
+    use crate::{bstr::BString, merge::tree::Conflict};
+
+    /// The error returned by [`Repository::stash_create()`](crate::Repository::stash_create()) and related methods.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot stash changes without a worktree")]
+        MissingWorkDir,
+        #[error("Cannot apply a stash while there are local changes")]
+        Dirty,
+        #[error("Cannot stash changes without an initial commit")]
+        UnbornHead,
+        #[error("Cannot stash changes while {path:?} is unmerged")]
+        Unmerged { path: BString },
+        #[error("Cannot restore the untracked file {path:?} from the stash as it already exists")]
+        UntrackedExists { path: BString },
+        #[error("{id} is not a stash as it has no parent")]
+        NotAStash { id: ObjectId },
+        #[error("Applying the stash resulted in {} conflict(s)", conflicts.len())]
+        Conflict { conflicts: Vec<Conflict> },
+        #[error("Could not access {path:?} in the worktree")]
+        Io { path: BString, source: std::io::Error },
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        HeadCommit(#[from] crate::reference::head_commit::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::with_conversion::Error),
+        #[error(transparent)]
+        TreeId(#[from] crate::object::commit::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        IndexFromTree(#[from] crate::repository::index_from_tree::Error),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+        #[error(transparent)]
+        Status(#[from] crate::status::Error),
+        #[error(transparent)]
+        StatusIter(#[from] crate::status::index_worktree::iter::Error),
+        #[error(transparent)]
+        StatusItem(#[from] crate::status::index_worktree::Error),
+        #[error(transparent)]
+        IsDirty(#[from] crate::status::is_dirty::Error),
+        #[error(transparent)]
+        Dirwalk(#[from] crate::dirwalk::Error),
+        #[error(transparent)]
+        FilterPipeline(#[from] crate::repository::filter::pipeline::Error),
+        #[error(transparent)]
+        ConvertToGit(#[from] crate::filter::pipeline::convert_to_git::Error),
+        #[error(transparent)]
+        ConvertToWorktree(#[from] crate::filter::pipeline::convert_to_worktree::Error),
+        #[error(transparent)]
+        EditTree(#[from] gix_object::tree::editor::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        MergeTrees(#[from] crate::merge::tree::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+        #[error(transparent)]
+        ParseTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        Config(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        EditReference(#[from] crate::reference::edit::Error),
+    }
+}
+pub use error::Error;
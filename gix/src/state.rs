@@ -23,3 +23,43 @@ pub enum InProgress {
     /// A revert operation with multiple commits pending.
     RevertSequence,
 }
+
+/// A cheap token describing the state of `HEAD`, `packed-refs` and the index, as obtained by
+/// [`Repository::state_signature()`](crate::Repository::state_signature()).
+///
+/// Comparing it to a previously obtained signature tells if something may have changed, without reading the index or
+/// all references.
+///
+/// ### Invalidation
+///
+/// * If two signatures differ, something was written, even though the effective state may be the same, for instance
+///   when the index was refreshed or `packed-refs` was rewritten with the same references.
+/// * If they are equal, `HEAD`, the reference it points to, the index and `packed-refs` are assumed to be unchanged.
+///   Changes to other loose references, to the worktree, to the configuration or to the object database aren't covered.
+/// * `packed-refs`, and the index if it's written without checksum, are tracked by modification time and size,
+///   so rewriting them with the same size within the timestamp resolution of the filesystem may go unnoticed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signature {
+    /// The name of the branch `HEAD` points to, or `None` if `HEAD` is detached.
+    pub head_name: Option<gix_ref::FullName>,
+    /// The object `HEAD` points to, or `None` if the branch it points to is unborn.
+    pub head_id: Option<gix_hash::ObjectId>,
+    /// The version of the index, or `None` if it doesn't exist.
+    pub index: Option<Version>,
+    /// The version of the `packed-refs` file, or `None` if it doesn't exist.
+    pub packed_refs: Option<Version>,
+}
+
+/// The version of a file as used in a state [`Signature`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// The checksum stored at the end of the file, which changes whenever its content changes.
+    Checksum(gix_hash::ObjectId),
+    /// The modification time and size of the file, for use if it has no checksum.
+    Stamp {
+        /// The time of the last modification of the file, if the platform supports it.
+        modified: Option<std::time::SystemTime>,
+        /// The size of the file in bytes.
+        len: u64,
+    },
+}
@@ -123,7 +123,7 @@ impl Repository {
 
         let cwd = self.current_dir();
         let git_dir_realpath = crate::path::realpath_opts(self.git_dir(), cwd, crate::path::realpath::MAX_SYMLINKS)?;
-        let fs_caps = self.filesystem_options()?;
+        let fs_caps = self.filesystem_capabilities()?;
         let accelerate_lookup = fs_caps.ignore_case.then(|| index.prepare_icase_backing());
         let resource_cache = crate::diff::resource_cache(
             self,
@@ -327,6 +327,7 @@ pub mod iter {
         /// The index that was used for the operation.
         pub index: crate::worktree::IndexPersistedOrInMemory,
         skip_hash: bool,
+        fsync: Option<gix_fs::fsync::Method>,
         changes: Option<Vec<(usize, iter::ApplyChange)>>,
     }
 
@@ -365,6 +366,7 @@ pub mod iter {
             Some(index.write(crate::index::write::Options {
                 extensions: Default::default(),
                 skip_hash: self.skip_hash,
+                fsync: self.fsync,
             }))
         }
     }
@@ -637,6 +639,7 @@ pub mod iter {
                 .transpose()
                 .with_lenient_default(self.repo.config.lenient_config)?
                 .unwrap_or_default();
+            let fsync = self.repo.config.fsync.method_for(gix_fs::fsync::Component::Index);
             let should_interrupt = self.should_interrupt.clone().unwrap_or_default();
             let submodule = BuiltinSubmoduleStatus::new(self.repo.clone().into_sync(), self.submodules)?;
             #[cfg(feature = "parallel")]
@@ -668,6 +671,7 @@ pub mod iter {
                                 index,
                                 changes: None,
                                 skip_hash,
+                                fsync,
                             })
                         }
                     })
@@ -703,6 +707,7 @@ pub mod iter {
                     index,
                     changes: None,
                     skip_hash,
+                    fsync,
                 };
                 let mut iter = super::Iter {
                     items: Vec::new().into_iter(),
@@ -43,18 +43,32 @@ pub enum Submodule {
 /// How untracked files should be handled.
 #[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum UntrackedFiles {
-    /// Do not show any untracked files.
+    /// Do not show any untracked files, like `--untracked-files=no`.
     ///
     /// This can mean no directory walk is performed.
     None,
     /// If possible, collapse files into their parent folders to reduce the amount of
-    /// emitted untracked files.
+    /// emitted untracked files, like `--untracked-files=normal`.
     #[default]
     Collapsed,
-    /// Show each individual untracked file or directory (if empty directories are emitted) that the dirwalk encountered .
+    /// Show each individual untracked file or directory (if empty directories are emitted) that the dirwalk encountered,
+    /// like `--untracked-files=all`.
     Files,
 }
 
+/// How ignored files should be handled.
+#[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Ignored {
+    /// Do not show any ignored files, like `--ignored=no`.
+    #[default]
+    None,
+    /// If possible, collapse ignored files into their parent folders to reduce the amount of
+    /// emitted ignored files, like `--ignored=traditional`.
+    Collapsed,
+    /// Show each ignored file or directory as it was matched by an ignore pattern, like `--ignored=matching`.
+    Matching,
+}
+
 impl Default for Submodule {
     fn default() -> Self {
         Submodule::AsConfigured { check_dirty: false }
@@ -1,4 +1,4 @@
-use crate::status::{index_worktree, OwnedOrStaticAtomicBool, Platform, Submodule, UntrackedFiles};
+use crate::status::{index_worktree, Ignored, OwnedOrStaticAtomicBool, Platform, Submodule, UntrackedFiles};
 use std::sync::atomic::AtomicBool;
 
 /// Builder
@@ -40,6 +40,19 @@ where
         self.dirwalk_options(|cb| cb.emit_untracked(mode))
     }
 
+    /// A simple way to explicitly set the desired way of listing `ignored` files, which aren't listed by default.
+    ///
+    /// Note that if the directory walk was disabled, for instance with [`untracked_files(UntrackedFiles::None)`](Self::untracked_files()),
+    /// this call has no effect.
+    pub fn ignored(self, ignored: Ignored) -> Self {
+        let mode = match ignored {
+            Ignored::None => None,
+            Ignored::Collapsed => Some(gix_dir::walk::EmissionMode::CollapseDirectory),
+            Ignored::Matching => Some(gix_dir::walk::EmissionMode::Matching),
+        };
+        self.dirwalk_options(|cb| cb.emit_ignored(mode))
+    }
+
     /// Set the interrupt flag to `should_interrupt`, which typically is an application-wide flag
     /// that is ultimately controlled by user interrupts.
     ///
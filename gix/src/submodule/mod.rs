@@ -312,6 +312,9 @@ pub mod status {
         /// If `check_dirty` is `true`, the computation will stop once the first in a ladder operations
         /// ordered from cheap to expensive shows that the submodule is dirty.
         /// Thus, submodules that are clean will still impose the complete set of computation, as given.
+        ///
+        /// Submodules of the submodule are checked recursively according to their own configuration, and changes in them
+        /// are reflected by [`Status::has_modified_content()`] and [`Status::has_untracked_content()`].
         #[doc(alias = "submodule_status", alias = "git2")]
         pub fn status(
             &self,
@@ -417,6 +420,52 @@ pub mod status {
                 self.checked_out_head_id != self.index_id || self.changes.as_ref().map_or(false, |c| !c.is_empty());
             Some(is_dirty)
         }
+
+        /// Return `true` if the submodule is checked out at a commit other than the one recorded in the superproject's index,
+        /// which is shown as `new commits` by `git status`.
+        pub fn has_new_commits(&self) -> bool {
+            self.checked_out_head_id.is_some() && self.checked_out_head_id != self.index_id
+        }
+
+        /// Return `true` if tracked files in the submodule worktree were changed, or if one of its own submodules
+        /// has new commits or modified content, which is shown as `modified content` by `git status`.
+        ///
+        /// Note that this is always `false` if the [changes](Self::changes) weren't computed.
+        pub fn has_modified_content(&self) -> bool {
+            use crate::status::index_worktree::iter::{Item, RewriteSource};
+            use gix_status::index_as_worktree::{Change, EntryStatus};
+            self.changes.iter().flatten().any(|change| match change {
+                Item::Modification { status, .. } => match status {
+                    EntryStatus::Change(Change::SubmoduleModification(status)) => {
+                        status.has_new_commits() || status.has_modified_content()
+                    }
+                    EntryStatus::NeedsUpdate(_) => false,
+                    EntryStatus::Conflict(_) | EntryStatus::Change(_) | EntryStatus::IntentToAdd => true,
+                },
+                Item::Rewrite { source, copy, .. } => !copy && matches!(source, RewriteSource::RewriteFromIndex { .. }),
+                Item::DirectoryContents { .. } => false,
+            })
+        }
+
+        /// Return `true` if there are untracked files in the submodule worktree, or in the worktree of one of its own
+        /// submodules, which is shown as `untracked content` by `git status`.
+        ///
+        /// Note that this is always `false` if the [changes](Self::changes) weren't computed, or if untracked files
+        /// were ignored.
+        pub fn has_untracked_content(&self) -> bool {
+            use crate::status::index_worktree::iter::Item;
+            use gix_status::index_as_worktree::{Change, EntryStatus};
+            self.changes.iter().flatten().any(|change| match change {
+                Item::Modification {
+                    status: EntryStatus::Change(Change::SubmoduleModification(status)),
+                    ..
+                } => status.has_untracked_content(),
+                Item::DirectoryContents { entry, .. } => entry.status == gix_dir::entry::Status::Untracked,
+                // The destination of a rewrite is always an untracked file.
+                Item::Rewrite { .. } => true,
+                Item::Modification { .. } => false,
+            })
+        }
     }
 
     pub(super) mod types {
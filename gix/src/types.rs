@@ -143,7 +143,12 @@ pub struct Reference<'r> {
 /// those who experiment with workloads to get speed boosts of 2x or more.
 pub struct Repository {
     /// A ref store with shared ownership (or the equivalent of it).
+    ///
+    /// Note that it doesn't see any reference if the repository keeps them in a reftable, so prefer using the methods
+    /// of this type to find, iterate and edit references.
     pub refs: crate::RefStore,
+    /// The store to use instead of `refs` if the repository keeps its references in a reftable.
+    pub(crate) reftable: Option<gix_ref::reftable::Store>,
     /// A way to access objects.
     pub objects: crate::OdbHandle,
 
@@ -177,7 +182,11 @@ pub struct Repository {
 #[derive(Clone)]
 pub struct ThreadSafeRepository {
     /// A store for references to point at objects
+    ///
+    /// Note that it doesn't see any reference if the repository keeps them in a reftable.
     pub refs: crate::RefStore,
+    /// The store to use instead of `refs` if the repository keeps its references in a reftable.
+    pub(crate) reftable: Option<gix_ref::reftable::Store>,
     /// A store for objects that contain data
     pub objects: gix_features::threading::OwnShared<gix_odb::Store>,
     /// The path to the worktree at which to find checked out files
@@ -203,6 +203,50 @@ mod push {
     }
 }
 
+mod pull {
+    use crate::config::tree::bcow;
+    use gix::config::tree::{Branch, Key, Pull};
+    use gix::pull;
+
+    #[test]
+    fn ff() -> crate::Result {
+        for (actual, expected) in [
+            ("only", pull::FastForward::Only),
+            ("true", pull::FastForward::Allow),
+            ("false", pull::FastForward::Never),
+        ] {
+            assert_eq!(Pull::FF.try_into_fast_forward(bcow(actual))?, expected);
+            assert!(Pull::FF.validate(actual.into()).is_ok());
+        }
+        assert_eq!(
+            Pull::FF.try_into_fast_forward(bcow("always")).unwrap_err().to_string(),
+            "The key \"pull.ff=always\" was invalid"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rebase() -> crate::Result {
+        for (actual, expected) in [
+            ("true", pull::Rebase::True),
+            ("false", pull::Rebase::False),
+            ("merges", pull::Rebase::Merges),
+            ("m", pull::Rebase::Merges),
+            ("interactive", pull::Rebase::Interactive),
+            ("i", pull::Rebase::Interactive),
+        ] {
+            assert_eq!(Pull::REBASE.try_into_rebase(bcow(actual))?, expected);
+            assert_eq!(Branch::REBASE.try_into_rebase(bcow(actual))?, expected);
+        }
+        assert_eq!(
+            Pull::REBASE.try_into_rebase(bcow("preserve")).unwrap_err().to_string(),
+            "The key \"pull.rebase=preserve\" was invalid"
+        );
+        assert!(Branch::REBASE.validate("interactive".into()).is_ok());
+        Ok(())
+    }
+}
+
 mod fetch {
 
     #[test]
@@ -530,6 +574,66 @@ mod core {
         Ok(())
     }
 
+    #[test]
+    fn fsync() -> crate::Result {
+        use gix::fs::fsync::{Component::*, Components, Method};
+        for (value, expected) in [
+            ("", Components::default()),
+            ("none", Components::NONE),
+            ("all", Components::all()),
+            ("none,reference", [Reference].into_iter().collect()),
+            (
+                "reference, index",
+                [Pack, PackMetadata, CommitGraph, Reference, Index]
+                    .into_iter()
+                    .collect(),
+            ),
+            ("-pack,-commit-graph", [PackMetadata].into_iter().collect()),
+            (
+                "committed",
+                [LooseObject, Pack, PackMetadata, CommitGraph, Reference]
+                    .into_iter()
+                    .collect(),
+            ),
+            (
+                "none,added,-objects",
+                [LooseObject, Pack, Reference, Index].into_iter().collect(),
+            ),
+            (
+                "-loose-object,loose-object",
+                [LooseObject, Pack, PackMetadata, CommitGraph].into_iter().collect(),
+            ),
+        ] {
+            assert_eq!(Core::FSYNC.try_into_components(bcow(value))?, expected, "{value}");
+            assert!(Core::FSYNC.validate(value.into()).is_ok());
+        }
+        assert_eq!(
+            Core::FSYNC
+                .try_into_components(bcow("pack,refs"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.fsync=pack,refs\" was invalid"
+        );
+        assert!(Core::FSYNC.validate("objects,-foo".into()).is_err());
+
+        for (value, expected) in [
+            ("fsync", Method::Fsync),
+            ("writeout-only", Method::WriteoutOnly),
+            ("batch", Method::Batch),
+        ] {
+            assert_eq!(Core::FSYNC_METHOD.try_into_fsync_method(bcow(value))?, expected);
+            assert!(Core::FSYNC_METHOD.validate(value.into()).is_ok());
+        }
+        assert_eq!(
+            Core::FSYNC_METHOD
+                .try_into_fsync_method(bcow("Batch"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.fsyncMethod=Batch\" was invalid"
+        );
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "attributes")]
     fn safecrlf() -> crate::Result {
@@ -678,6 +782,23 @@ mod extensions {
         assert!(Extensions::OBJECT_FORMAT.validate("invalid".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn ref_storage() -> crate::Result {
+        Extensions::REF_STORAGE.try_into_ref_storage(bcow("files"))?;
+        Extensions::REF_STORAGE.try_into_ref_storage(bcow("reftable"))?;
+        assert_eq!(
+            Extensions::REF_STORAGE
+                .try_into_ref_storage(bcow("unknown"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.refStorage=unknown\" was invalid"
+        );
+        assert!(Extensions::REF_STORAGE.validate("files".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("reftable".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("unknown".into()).is_err());
+        Ok(())
+    }
 }
 
 mod checkout {
@@ -700,6 +821,45 @@ mod checkout {
     }
 }
 
+mod gc {
+    use gix::config::tree::{Gc, Key};
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn prune_expire() -> crate::Result {
+        let now = std::time::UNIX_EPOCH + std::time::Duration::from_secs(14 * 24 * 60 * 60 + 42);
+        assert_eq!(Gc::PRUNE_EXPIRE.try_into_expiry_date(bcow("never"), now)?, None);
+        assert_eq!(
+            Gc::PRUNE_EXPIRE.try_into_expiry_date(bcow("now"), now)?,
+            Some(gix_date::Time::new(14 * 24 * 60 * 60 + 42, 0))
+        );
+        assert_eq!(
+            Gc::PRUNE_EXPIRE
+                .try_into_expiry_date(bcow("2.weeks.ago"), now)?
+                .map(|time| time.seconds),
+            Some(42),
+            "dots may separate the components of relative dates"
+        );
+        assert_eq!(
+            Gc::PRUNE_EXPIRE
+                .try_into_expiry_date(bcow("2 weeks ago"), now)?
+                .map(|time| time.seconds),
+            Some(42)
+        );
+        assert!(Gc::PRUNE_EXPIRE.validate("2.weeks.ago".into()).is_ok());
+        assert_eq!(
+            Gc::PRUNE_EXPIRE
+                .try_into_expiry_date(bcow("whenever"), now)
+                .unwrap_err()
+                .to_string(),
+            "The date format at key \"gc.pruneExpire\" was invalid"
+        );
+        assert!(Gc::PRUNE_EXPIRE.validate("whenever".into()).is_err());
+        Ok(())
+    }
+}
+
 mod pack {
     use gix::config::tree::{Key, Pack};
 
@@ -0,0 +1,9 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q --ref-format=reftable
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git tag -a -m "annotated" annotated
+git branch other
@@ -0,0 +1,20 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+echo a > a
+echo secret > secret
+git add a secret
+git commit -q -m c1
+git branch other
+
+echo b >> a
+git commit -q -am c2
+
+echo more >> secret
+git commit -q -am "c3 only changes the secret"
+git tag -m "annotated" v1
+
+git commit -q --allow-empty -m "c4 was empty already"
@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+
+echo a > a
+echo b > b
+echo c > c
+git add .
+git commit -q -m "initial"
+
+echo "a changed" > a
+rm b
+echo new > new
+git add new
+echo "c staged" > c
+git add c
+echo "c changed after staging" > c
+
+git rev-parse "$(git stash create)^{tree}" > .git/stash-worktree-tree
+git rev-parse "$(git stash create)^2^{tree}" > .git/stash-index-tree
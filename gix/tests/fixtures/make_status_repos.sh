@@ -13,3 +13,14 @@ git init -q untracked-only
   mkdir new
   touch new/untracked subdir/untracked
 )
+
+git init -q untracked-and-ignored
+(cd untracked-and-ignored
+  printf '*.o\nbuild/\n' >.gitignore
+  touch tracked
+  git add .
+  git commit -q -m init
+
+  mkdir build new objs
+  touch build/a.o build/b tracked.o new/untracked new/ignored.o objs/a.o
+)
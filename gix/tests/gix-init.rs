@@ -253,6 +253,108 @@ mod with_overrides {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn object_directory_and_alternates_from_environment() -> gix_testtools::Result {
+        let (repo, _tmp) = crate::util::basic_rw_repo()?;
+        let objects_dir = repo.objects.store_ref().path().to_owned();
+        let quarantine_dir = repo.git_dir().join("quarantine");
+        std::fs::create_dir(&quarantine_dir)?;
+
+        let _env = Env::new()
+            .set("GIT_OBJECT_DIRECTORY", quarantine_dir.to_str().expect("valid UTF-8"))
+            .set(
+                "GIT_ALTERNATE_OBJECT_DIRECTORIES",
+                format!(
+                    "\"{}\"{}does-not-matter",
+                    objects_dir.to_str().expect("valid UTF-8"),
+                    if cfg!(windows) { ";" } else { ":" }
+                ),
+            );
+        let mut opts = gix::open::Options::isolated();
+        opts.permissions.env.objects = Permission::Allow;
+        let repo = gix::open_opts(repo.git_dir(), opts)?;
+        assert_eq!(
+            repo.objects.store_ref().path(),
+            quarantine_dir,
+            "objects are written to the object directory from the environment"
+        );
+        assert_eq!(
+            repo.objects.store_ref().alternates(),
+            [objects_dir.clone(), "does-not-matter".into()],
+            "the original object directory is used as alternate so existing objects can be found"
+        );
+        let head_id = repo.head_id()?;
+        assert!(repo.has_object(head_id), "objects are found in the alternate");
+
+        let blob_id = repo.write_blob(b"new object")?;
+        let loose_path = |dir: &std::path::Path| {
+            let hex = blob_id.to_hex().to_string();
+            dir.join(&hex[..2]).join(&hex[2..])
+        };
+        assert!(
+            loose_path(&quarantine_dir).is_file(),
+            "new objects go to the quarantine"
+        );
+        assert!(!loose_path(&objects_dir).exists());
+
+        let mut opts = gix::open::Options::isolated();
+        opts.permissions.env.objects = Permission::Deny;
+        let repo = gix::open_opts(repo.git_dir(), opts)?;
+        assert_eq!(
+            repo.objects.store_ref().path(),
+            objects_dir,
+            "without permission, the environment isn't used"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn isolated_options_ignore_global_configuration_and_environment() -> gix_testtools::Result {
+        let (repo, _tmp) = crate::util::basic_rw_repo()?;
+        let home = gix_testtools::tempfile::TempDir::new()?;
+        std::fs::write(home.path().join(".gitconfig"), b"[user]\n\tname = from-home\n")?;
+        let _env = Env::new()
+            .set("HOME", home.path().to_str().expect("valid UTF-8"))
+            .set(
+                "XDG_CONFIG_HOME",
+                home.path().join("xdg").to_str().expect("valid UTF-8"),
+            )
+            .set("GIT_CONFIG_NOSYSTEM", "1")
+            .set("GIT_CONFIG_COUNT", "1")
+            .set("GIT_CONFIG_KEY_0", "a.from-env")
+            .set("GIT_CONFIG_VALUE_0", "value")
+            .set("GIT_COMMITTER_NAME", "committer from env");
+
+        let repo = gix::open_opts(repo.git_dir(), gix::open::Options::isolated())?;
+        let config = repo.config_snapshot();
+        assert_eq!(config.string("user.name"), None, "the user configuration isn't loaded");
+        assert_eq!(
+            config.string("a.from-env"),
+            None,
+            "configuration from the environment is ignored"
+        );
+        assert_eq!(
+            config.string("gitoxide.committer.nameFallback"),
+            None,
+            "environment variables aren't read"
+        );
+
+        let repo = gix::open_opts(repo.git_dir(), gix::open::Options::default())?;
+        let config = repo.config_snapshot();
+        assert_eq!(
+            config.string("user.name").as_deref(),
+            Some(cow_bstr("from-home").as_ref())
+        );
+        assert_eq!(config.string("a.from-env").as_deref(), Some(cow_bstr("value").as_ref()));
+        assert_eq!(
+            config.string("gitoxide.committer.nameFallback").as_deref(),
+            Some(cow_bstr("committer from env").as_ref())
+        );
+        Ok(())
+    }
+
     fn cow_bstr(s: &str) -> Cow<BStr> {
         Cow::Borrowed(s.into())
     }
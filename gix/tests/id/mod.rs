@@ -154,6 +154,48 @@ mod ancestors {
         Ok(())
     }
 
+    #[test]
+    fn max_count_and_cutoff() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let head = repo.head()?.into_peeled_id()?;
+
+        for use_commit_graph in [false, true] {
+            let commits = head
+                .ancestors()
+                .sorting(gix::revision::walk::Sorting::ByCommitTimeNewestFirst)
+                .max_count(2)
+                .use_commit_graph(use_commit_graph)
+                .all()?
+                .map(|c| c.map(|c| c.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(
+                commits,
+                &[
+                    hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170"),
+                    hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac")
+                ],
+                "the walk stops after the given amount of commits"
+            );
+
+            let commits = head
+                .ancestors()
+                .sorting(gix::revision::walk::Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
+                    seconds: 978393600, // =2001-01-02 00:00:00 +0000
+                })
+                .first_parent_only()
+                .use_commit_graph(use_commit_graph)
+                .all()?
+                .map(|c| c.map(|c| c.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(
+                commits,
+                &[hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170")],
+                "the cutoff is respected when following only the first parent"
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn filtered() -> crate::Result {
         let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
@@ -112,6 +112,264 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_with_connectivity_check() -> crate::Result {
+        let (repo, _tmp) = repo_rw("two-origins");
+        let out = repo
+            .find_remote("changes-on-top-of-origin")?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .with_connectivity_check(true)
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(
+            matches!(out.status, Status::Change { .. }),
+            "the pack is complete, so the check passes"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "blocking-network-client", feature = "maintenance"))]
+    fn fetch_with_auto_maintenance() -> crate::Result {
+        let (mut repo, _tmp) = repo_rw("two-origins");
+        repo.config_snapshot_mut().set_subsection_value(
+            &gix::config::tree::Maintenance::TASK_AUTO,
+            "commit-graph",
+            "1",
+        )?;
+        assert!(repo.commit_graph().is_err(), "there is no commit-graph yet");
+        let out = repo
+            .find_remote("changes-on-top-of-origin")?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .with_auto_maintenance(true)
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        let Status::Change { update_refs, .. } = out.status else {
+            panic!("a pack was received")
+        };
+        let graph = repo.commit_graph()?;
+        for edit in update_refs.edits {
+            let id = repo.find_reference(edit.name.as_ref())?.into_fully_peeled_id()?;
+            assert!(
+                graph.lookup(id).is_some(),
+                "the fetched commits are part of the commit-graph written after the fetch"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_head_marks_the_merge_branch_of_the_current_branch() -> crate::Result {
+        let (mut repo, _tmp) = repo_rw("two-origins");
+        repo.find_remote("changes-on-top-of-origin")?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        let entries = repo.refs.read_fetch_head()?.expect("written by default");
+        assert!(
+            entries.iter().all(|e| !e.for_merge),
+            "the current branch merges from 'origin', and the first refspec is a pattern"
+        );
+        let url = repo
+            .find_remote("changes-on-top-of-origin")?
+            .url(Fetch)
+            .expect("present")
+            .to_bstring();
+        assert!(entries
+            .iter()
+            .any(|e| e.description == format!("branch 'main' of {url}")));
+        assert!(entries.iter().any(|e| e.description == format!("tag 'v1.0' of {url}")));
+
+        repo.config_snapshot_mut().set_subsection_value(
+            &gix::config::tree::Branch::REMOTE,
+            "main",
+            "changes-on-top-of-origin",
+        )?;
+        repo.find_remote("changes-on-top-of-origin")?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        let entries = repo.refs.read_fetch_head()?.expect("rewritten");
+        let first = &entries[0];
+        assert!(first.for_merge, "objects to merge come first");
+        assert_eq!(first.description, format!("branch 'main' of {url}"));
+        assert_eq!(
+            entries.iter().filter(|e| e.for_merge).count(),
+            1,
+            "only the branch configured in `branch.main.merge` is to be merged"
+        );
+        assert_eq!(
+            repo.find_reference("refs/remotes/changes-on-top-of-origin/main")?.id(),
+            first.id,
+            "the merge-candidate is the one that was fetched"
+        );
+
+        std::fs::remove_file(repo.path().join("FETCH_HEAD"))?;
+        repo.find_remote("changes-on-top-of-origin")?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .with_write_fetch_head(false)
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(repo.refs.read_fetch_head()?, None, "writing can be disabled");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "blocking-network-client", feature = "merge"))]
+    fn pull_fast_forwards_merges_or_rebases_the_current_branch() -> crate::Result {
+        use gix::pull::{FastForward, Options, Rebase, Strategy};
+
+        fn repo_with_local_commit() -> crate::Result<(gix::Repository, TempDir, gix::ObjectId)> {
+            let (mut repo, tmp) = repo_rw("two-origins");
+            repo.config_snapshot_mut().set_subsection_value(
+                &gix::config::tree::Branch::REMOTE,
+                "main",
+                "changes-on-top-of-origin",
+            )?;
+            let head = repo.head_commit()?;
+            let local = repo
+                .commit("HEAD", "local change", head.tree_id()?, Some(head.id))?
+                .detach();
+            drop(head);
+            Ok((repo, tmp, local))
+        }
+        let pull = |repo: &gix::Repository, options: Options| {
+            repo.pull(gix::progress::Discard, &AtomicBool::default(), options)
+        };
+
+        let (mut repo, _tmp) = repo_rw("two-origins");
+        repo.config_snapshot_mut().set_subsection_value(
+            &gix::config::tree::Branch::REMOTE,
+            "main",
+            "changes-on-top-of-origin",
+        )?;
+        let previous = repo.head_id()?.detach();
+        let out = pull(&repo, Default::default())?;
+        assert_eq!(out.strategy, Strategy::FastForward);
+        assert_eq!(out.reference.as_bstr(), "refs/heads/main");
+        assert_eq!(out.previous, Some(previous));
+        assert_eq!(
+            out.new,
+            repo.find_reference("refs/remotes/changes-on-top-of-origin/main")?.id(),
+            "the branch now points to what was fetched"
+        );
+        assert_eq!(out.upstream, out.new);
+        assert_eq!(repo.head_id()?, out.new);
+        assert_eq!(
+            pull(&repo, Default::default())?.strategy,
+            Strategy::UpToDate,
+            "nothing changes on the second pull"
+        );
+
+        let (repo, _tmp, local) = repo_with_local_commit()?;
+        assert!(matches!(
+            pull(
+                &repo,
+                Options {
+                    fast_forward: Some(FastForward::Only),
+                    ..Default::default()
+                }
+            ),
+            Err(gix::pull::Error::NotFastForward)
+        ));
+        assert_eq!(repo.head_id()?, local, "nothing changed");
+
+        let out = pull(&repo, Default::default())?;
+        assert_eq!(out.strategy, Strategy::Merge);
+        let merge = repo.find_commit(out.new)?;
+        assert_eq!(
+            merge.parent_ids().map(gix::Id::detach).collect::<Vec<_>>(),
+            [local, out.upstream]
+        );
+        assert!(merge.message_raw()?.starts_with(b"Merge branch 'main' of "));
+        assert_eq!(repo.head_id()?, out.new);
+
+        let (mut repo, _tmp, local) = repo_with_local_commit()?;
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Pull::REBASE, "true")?;
+        let out = pull(&repo, Default::default())?;
+        let Strategy::Rebase { rebased, skipped } = &out.strategy else {
+            panic!("expected a rebase, got {:?}", out.strategy)
+        };
+        assert!(skipped.is_empty());
+        assert_eq!(rebased.len(), 1);
+        assert_eq!(rebased[0].0, local);
+        assert_eq!(rebased[0].1, out.new);
+        let rebased = repo.find_commit(out.new)?;
+        assert_eq!(
+            rebased.parent_ids().map(gix::Id::detach).collect::<Vec<_>>(),
+            [out.upstream]
+        );
+        assert_eq!(rebased.message_raw()?, "local change");
+
+        let (repo, _tmp, _local) = repo_with_local_commit()?;
+        assert!(matches!(
+            pull(
+                &repo,
+                Options {
+                    rebase: Some(Rebase::Interactive),
+                    ..Default::default()
+                }
+            ),
+            Err(gix::pull::Error::InteractiveRebase)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn connectivity_check_detects_missing_objects_before_updating_refs() -> crate::Result {
+        let (remote_repo, _remote_tmp) = crate::util::basic_rw_repo()?;
+        let tip = remote_repo.head_commit()?;
+        let tmp = TempDir::new()?;
+        let repo = gix::init_bare(tmp.path())?;
+        assert_eq!(
+            gix::odb::Write::write_buf(&repo.objects, gix::object::Kind::Commit, &tip.data)?,
+            tip.id,
+            "the tip commit is present locally, but nothing it refers to"
+        );
+
+        let remote = repo
+            .remote_at(remote_repo.git_dir())?
+            .with_refspecs(Some("refs/heads/main:refs/remotes/origin/main"), Fetch)?;
+        let err = remote
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .with_connectivity_check(true)
+            .receive(gix::progress::Discard, &AtomicBool::default())
+            .expect_err("the tree and parent of the tip are missing");
+        match err {
+            fetch::Error::Connectivity(fetch::connectivity::Error::MissingObjects { missing }) => {
+                let tip = tip.decode()?;
+                let mut expected = vec![
+                    (tip.tree(), gix::object::Kind::Tree),
+                    (tip.parents().next().expect("one parent"), gix::object::Kind::Commit),
+                ];
+                expected.sort();
+                let mut missing = missing;
+                missing.sort();
+                assert_eq!(missing, expected);
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        assert!(
+            repo.try_find_reference("refs/remotes/origin/main")?.is_none(),
+            "references aren't updated if objects are missing"
+        );
+
+        remote
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(
+            repo.try_find_reference("refs/remotes/origin/main")?.is_some(),
+            "without the check, the reference is updated as the tip object is present"
+        );
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "blocking-network-client")]
     fn fetch_with_alternates_adds_tips_from_alternates() -> crate::Result<()> {
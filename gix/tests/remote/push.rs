@@ -0,0 +1,121 @@
+use gix::remote::{
+    push::{Lease, Mode, Update},
+    Direction,
+};
+use gix_hash::ObjectId;
+
+fn update(remote_id: Option<ObjectId>, new_id: Option<ObjectId>) -> Update {
+    Update {
+        remote_ref: "refs/heads/main".try_into().expect("valid"),
+        remote_id,
+        new_id,
+        force: false,
+        lease: None,
+    }
+}
+
+#[test]
+fn fast_forwards_are_allowed_and_everything_else_needs_force() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let head = repo.head_id()?.detach();
+    let parent = repo.find_commit(head)?.parent_ids().next().expect("parent").detach();
+    let remote = repo.remote_at("https://example.com/repo")?;
+
+    let tag = update(Some(parent), Some(head));
+    let tag = Update {
+        remote_ref: "refs/tags/v1".try_into()?,
+        ..tag
+    };
+    let unknown = repo.object_hash().null();
+    let updates = [
+        update(Some(parent), Some(head)),
+        update(Some(head), Some(parent)),
+        update(Some(head), Some(head)),
+        update(None, Some(head)),
+        update(Some(head), None),
+        update(Some(unknown), Some(head)),
+        update(Some(head), Some(repo.head_tree_id()?.detach())),
+        tag.clone(),
+    ];
+    assert_eq!(
+        remote.check_push_updates(&updates)?,
+        [
+            Mode::FastForward,
+            Mode::RejectedNonFastForward,
+            Mode::NoChangeNeeded,
+            Mode::New,
+            Mode::Delete,
+            Mode::RejectedFetchFirst,
+            Mode::RejectedNeedsForce,
+            Mode::RejectedTagUpdate,
+        ]
+    );
+
+    let forced: Vec<_> = updates
+        .into_iter()
+        .map(|update| Update { force: true, ..update })
+        .collect();
+    assert_eq!(
+        remote.check_push_updates(&forced)?,
+        [
+            Mode::FastForward,
+            Mode::Forced,
+            Mode::NoChangeNeeded,
+            Mode::New,
+            Mode::Delete,
+            Mode::Forced,
+            Mode::Forced,
+            Mode::Forced,
+        ],
+        "force allows everything"
+    );
+    Ok(())
+}
+
+#[test]
+fn force_with_lease() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let head = repo.head_id()?.detach();
+    let parent = repo.find_commit(head)?.parent_ids().next().expect("parent").detach();
+    let remote = repo
+        .remote_at("https://example.com/repo")?
+        .with_refspecs(Some("+refs/heads/*:refs/remotes/origin/*"), Direction::Fetch)?;
+
+    let with_lease = |remote_id, lease| Update {
+        lease: Some(lease),
+        ..update(remote_id, Some(parent))
+    };
+    assert_eq!(
+        remote.check_push_updates(&[
+            with_lease(Some(head), Lease::Expect(Some(head))),
+            with_lease(Some(head), Lease::Expect(Some(parent))),
+            with_lease(Some(head), Lease::Expect(None)),
+            with_lease(None, Lease::Expect(None)),
+            with_lease(Some(head), Lease::RemoteTracking),
+        ])?,
+        [
+            Mode::Forced,
+            Mode::RejectedStale { expected: Some(parent) },
+            Mode::RejectedStale { expected: None },
+            Mode::New,
+            Mode::RejectedStale { expected: None },
+        ],
+        "a lease that holds allows non-fast-forwards, and without remote-tracking branch the remote ref must not exist"
+    );
+
+    repo.reference(
+        "refs/remotes/origin/main",
+        head,
+        gix::refs::transaction::PreviousValue::MustNotExist,
+        "remote-tracking branch",
+    )?;
+    assert_eq!(
+        remote.check_push_updates(&[
+            with_lease(Some(head), Lease::RemoteTracking),
+            with_lease(Some(parent), Lease::RemoteTracking),
+        ])?,
+        [Mode::Forced, Mode::RejectedStale { expected: Some(head) }],
+        "the remote-tracking branch is found with the fetch refspecs"
+    );
+    Ok(())
+}
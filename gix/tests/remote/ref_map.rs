@@ -86,3 +86,75 @@ mod blocking_and_async_io {
         Ok(())
     }
 }
+
+#[cfg(feature = "blocking-network-client")]
+mod blocking_io {
+    use gix::remote::{ref_map, Direction::Fetch};
+    use gix_features::progress;
+
+    #[test]
+    fn cache_is_used_until_it_expires_and_is_updated_by_fetches() -> crate::Result {
+        let (repo, _tmp) = crate::util::basic_rw_repo()?;
+        let remote = repo
+            .remote_at(repo.git_dir())?
+            .with_refspecs(Some("refs/heads/*:refs/remotes/origin/*"), Fetch)?;
+        let cache = ref_map::Cache::new(std::time::Duration::from_secs(3600));
+        let options = || ref_map::Options {
+            cache: Some(cache.clone()),
+            ..Default::default()
+        };
+        let list = |options: ref_map::Options| -> crate::Result<usize> {
+            Ok(remote
+                .connect(Fetch)?
+                .ref_map(progress::Discard, options)?
+                .remote_refs
+                .len())
+        };
+        let num_refs = list(options())?;
+
+        let head = repo.head_id()?;
+        repo.reference(
+            "refs/heads/new",
+            head,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+            "new branch",
+        )?;
+        assert_eq!(list(options())?, num_refs, "the cached refs are used");
+        assert_eq!(
+            list(Default::default())?,
+            num_refs + 1,
+            "without cache, the remote is contacted"
+        );
+
+        remote.connect(Fetch)?.prepare_fetch(progress::Discard, options())?;
+        assert_eq!(
+            list(options())?,
+            num_refs + 1,
+            "fetches contact the remote and update the cache"
+        );
+
+        repo.reference(
+            "refs/heads/other",
+            head,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+            "new branch",
+        )?;
+        cache.clear();
+        assert_eq!(list(options())?, num_refs + 2, "a cleared cache contacts the remote");
+
+        let expired = ref_map::Options {
+            cache: Some(ref_map::Cache::new(std::time::Duration::ZERO)),
+            ..Default::default()
+        };
+        assert_eq!(list(expired.clone())?, num_refs + 2);
+        repo.reference(
+            "refs/heads/third",
+            head,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+            "new branch",
+        )?;
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        assert_eq!(list(expired)?, num_refs + 3, "expired entries are refreshed");
+        Ok(())
+    }
+}
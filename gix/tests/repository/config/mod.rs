@@ -2,6 +2,83 @@ mod config_snapshot;
 mod identity;
 mod remote;
 
+mod filesystem_capabilities {
+    use gix::config::tree::Core;
+
+    #[test]
+    fn configuration_is_limited_by_what_the_worktree_supports() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut repo = gix::init(tmp.path())?;
+        let probed = gix::fs::Capabilities::probe(repo.work_dir().expect("non-bare"));
+        {
+            let mut config = repo.config_snapshot_mut();
+            config.set_raw_value(&Core::SYMLINKS, "false")?;
+            config.set_raw_value(&Core::FILE_MODE, "true")?;
+            config.set_raw_value(&Core::IGNORE_CASE, "false")?;
+        }
+
+        let caps = repo.filesystem_capabilities()?;
+        assert!(
+            !caps.symlink,
+            "the configuration can turn capabilities off even if the filesystem supports them"
+        );
+        assert_eq!(
+            caps.executable_bit, probed.executable_bit,
+            "the configuration can't turn on capabilities that the filesystem lacks"
+        );
+        assert_eq!(
+            caps.ignore_case, probed.ignore_case,
+            "case-insensitive filesystems are detected even if not configured"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bare_repositories_only_use_the_configuration() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let repo = gix::init_bare(tmp.path())?;
+        assert_eq!(repo.filesystem_capabilities()?, repo.filesystem_options()?);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "index")]
+mod stat_options {
+    use gix::config::tree::{gitoxide, Core};
+
+    #[test]
+    fn defaults_compare_all_stat_fields_but_nanoseconds() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let repo = gix::init(tmp.path())?;
+        assert_eq!(repo.stat_options()?, gix::index::entry::stat::Options::default());
+        Ok(())
+    }
+
+    #[test]
+    fn configuration_controls_which_fields_are_compared() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut repo = gix::init(tmp.path())?;
+        {
+            let mut config = repo.config_snapshot_mut();
+            config.set_raw_value(&Core::TRUST_C_TIME, "false")?;
+            config.set_raw_value(&Core::CHECK_STAT, "minimal")?;
+            config.set_raw_value(&gitoxide::Core::USE_NSEC, "true")?;
+            config.set_raw_value(&gitoxide::Core::USE_STDEV, "true")?;
+        }
+        assert_eq!(
+            repo.stat_options()?,
+            gix::index::entry::stat::Options {
+                trust_ctime: false,
+                check_stat: false,
+                use_nsec: true,
+                use_stdev: true,
+            }
+        );
+
+        Ok(())
+    }
+}
+
 #[cfg(feature = "blocking-network-client")]
 mod ssh_options {
     use std::ffi::OsStr;
@@ -60,3 +60,26 @@ fn pipeline_with_autocrlf() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+fn audit_eol_with_autocrlf() -> crate::Result {
+    let repo = named_repo("make_config_repo.sh")?;
+    let (mut pipe, _index) = repo.filter_pipeline(None)?;
+
+    let out = pipe.audit_eol("file".into(), Some(b"hi\r\n"), Some(b"hi\n"))?;
+    assert_eq!(out.attributes_digest, None, "there are no attributes");
+    assert_eq!(out.digest, gix_filter::eol::AttributesDigest::TextAutoCrlf);
+    assert!(out.sources.is_empty());
+    assert_eq!(
+        out.changes,
+        [
+            gix_filter::eol::audit::Change::Renormalize,
+            gix_filter::eol::audit::Change::ConvertOnCheckout {
+                worktree: gix_filter::eol::audit::LineEndings::Lf,
+                checkout: gix_filter::eol::audit::LineEndings::CrLf,
+            }
+        ],
+        "the index contains CRLF, which is kept on checkout as it is auto-text"
+    );
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::{config::tree::Maintenance, maintenance::Task};
+
+fn set_thresholds(repo: &mut gix::Repository, threshold: &str) -> crate::Result {
+    let mut config = repo.config_snapshot_mut();
+    for task in Task::ALL {
+        config.set_subsection_value(&Maintenance::TASK_AUTO, task.as_str(), threshold)?;
+    }
+    Ok(())
+}
+
+fn num_loose_objects(repo: &gix::Repository) -> usize {
+    gix::odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash())
+        .iter()
+        .count()
+}
+
+#[test]
+fn auto_runs_due_tasks_until_nothing_is_due() -> crate::Result {
+    let (mut repo, _tmp) = crate::util::basic_rw_repo()?;
+    assert_eq!(
+        repo.maintenance_tasks_due()?,
+        [],
+        "the default thresholds aren't reached in this small repository"
+    );
+
+    set_thresholds(&mut repo, "1")?;
+    assert_eq!(
+        repo.maintenance_tasks_due()?,
+        [Task::LooseObjects, Task::CommitGraph],
+        "there are no packs yet, so the multi-pack-index isn't due"
+    );
+    let num_loose = num_loose_objects(&repo);
+    assert_ne!(num_loose, 0);
+
+    let ran = repo.maintenance_auto(&mut gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(
+        ran,
+        Task::ALL,
+        "the pack written by the first task makes the multi-pack-index due"
+    );
+    assert_eq!(num_loose_objects(&repo), 0, "all loose objects were packed");
+    assert!(repo.objects.store_ref().path().join("pack/multi-pack-index").is_file());
+
+    assert!(
+        repo.objects
+            .store_ref()
+            .path()
+            .join("info/commit-graphs/commit-graph-chain")
+            .is_file(),
+        "commits are added to a split commit-graph"
+    );
+    let graph = repo.commit_graph()?;
+    let head = repo.head_commit()?;
+    assert_eq!(graph.num_commits(), 2, "all commits are in the commit-graph");
+    let graph_commit = graph.commit_by_id(head.id).expect("head commit is present");
+    assert_eq!(graph_commit.generation(), 2);
+    assert_eq!(graph_commit.root_tree_id(), head.tree_id()?.as_ref());
+
+    assert_eq!(repo.maintenance_tasks_due()?, [], "nothing is due after maintenance");
+
+    let repo = gix::open_opts(repo.path(), crate::restricted())?;
+    assert_eq!(
+        repo.head_commit()?.tree()?.iter().count(),
+        1,
+        "all objects are still accessible from the new pack"
+    );
+    Ok(())
+}
+
+fn with_precious_objects(repo: gix::Repository) -> crate::Result<gix::Repository> {
+    let config_path = repo.git_dir().join("config");
+    let mut config = std::fs::read(&config_path)?;
+    config.extend_from_slice(b"[extensions]\n\tpreciousObjects = true\n[gc]\n\tpruneExpire = now\n");
+    std::fs::write(config_path, config)?;
+    Ok(gix::open_opts(repo.git_dir(), crate::restricted())?)
+}
+
+#[test]
+fn tasks_deleting_objects_are_skipped_with_precious_objects() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let mut repo = with_precious_objects(repo)?;
+    set_thresholds(&mut repo, "1")?;
+    assert_eq!(
+        repo.maintenance_tasks_due()?,
+        [Task::CommitGraph],
+        "packing loose objects deletes them, which isn't allowed"
+    );
+    assert!(matches!(
+        repo.maintenance_run(Task::LooseObjects, &mut gix::progress::Discard, &AtomicBool::default())
+            .unwrap_err(),
+        gix::maintenance::run::Error::PreciousObjects {
+            task: Task::LooseObjects
+        }
+    ));
+
+    let num_loose = num_loose_objects(&repo);
+    assert_eq!(
+        repo.maintenance_auto(&mut gix::progress::Discard, &AtomicBool::default())?,
+        [Task::CommitGraph]
+    );
+    assert_eq!(num_loose_objects(&repo), num_loose, "no object was deleted");
+    Ok(())
+}
+
+#[test]
+fn thresholds_can_force_or_disable_tasks() -> crate::Result {
+    let (mut repo, _tmp) = crate::util::basic_rw_repo()?;
+    set_thresholds(&mut repo, "-1")?;
+    assert_eq!(
+        repo.maintenance_tasks_due()?,
+        Task::ALL,
+        "negative thresholds always make tasks due"
+    );
+
+    repo.config_snapshot_mut()
+        .set_subsection_value(&Maintenance::TASK_ENABLED, "loose-objects", "false")?;
+    assert_eq!(
+        repo.maintenance_tasks_due()?,
+        [Task::IncrementalRepack, Task::CommitGraph]
+    );
+
+    set_thresholds(&mut repo, "0")?;
+    assert_eq!(
+        repo.maintenance_tasks_due()?,
+        [],
+        "0 disables the automatic run of tasks"
+    );
+
+    set_thresholds(&mut repo, "-1")?;
+    repo.config_snapshot_mut().set_value(&Maintenance::AUTO, "false")?;
+    assert_eq!(repo.maintenance_tasks_due()?, [], "automatic maintenance is disabled");
+    assert_eq!(
+        repo.maintenance_auto(&mut gix::progress::Discard, &AtomicBool::default())?,
+        []
+    );
+    Ok(())
+}
+
+mod prune {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::{config::tree::Gc, maintenance::prune::Outcome};
+
+    fn prune(repo: &gix::Repository) -> Result<Outcome, gix::maintenance::prune::Error> {
+        repo.prune_unreachable_objects(&mut gix::progress::Discard, &AtomicBool::default())
+    }
+
+    #[test]
+    fn unreachable_loose_objects_older_than_the_grace_period() -> crate::Result {
+        let (mut repo, _tmp) = crate::util::basic_rw_repo()?;
+        let unreachable = repo.write_blob("unreachable")?.detach();
+        let num_loose = super::num_loose_objects(&repo);
+
+        assert_eq!(
+            prune(&repo)?,
+            Outcome::Pruned { objects: 0 },
+            "by default, objects are kept for two weeks"
+        );
+
+        repo.config_snapshot_mut().set_value(&Gc::PRUNE_EXPIRE, "never")?;
+        assert_eq!(prune(&repo)?, Outcome::NeverExpires);
+
+        repo.config_snapshot_mut().set_value(&Gc::PRUNE_EXPIRE, "1.hour.ago")?;
+        let quarantine = repo.objects.store_ref().path().join("incoming-123");
+        std::fs::create_dir(&quarantine)?;
+        assert_eq!(
+            prune(&repo)?,
+            Outcome::OperationInProgress {
+                marker: quarantine.clone()
+            },
+            "a quarantine directory that is younger than the grace period prevents pruning"
+        );
+        std::fs::remove_dir(quarantine)?;
+
+        repo.config_snapshot_mut().set_value(&Gc::PRUNE_EXPIRE, "now")?;
+
+        assert_eq!(prune(&repo)?, Outcome::Pruned { objects: 1 });
+        assert!(!repo.has_object(unreachable));
+        assert_eq!(
+            super::num_loose_objects(&repo),
+            num_loose - 1,
+            "reachable objects are kept"
+        );
+        assert_eq!(repo.head_commit()?.tree()?.iter().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn writing_existing_objects_protects_them_from_pruning() -> crate::Result {
+        let (mut repo, _tmp) = crate::util::basic_rw_repo()?;
+        let id = repo.write_blob("about to become reachable")?.detach();
+        let path = gix::odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash()).object_path(&id);
+        filetime::set_file_mtime(&path, filetime::FileTime::from_unix_time(1000, 0))?;
+        repo.config_snapshot_mut().set_value(&Gc::PRUNE_EXPIRE, "1.hour.ago")?;
+
+        assert_eq!(repo.write_blob("about to become reachable")?, id);
+        assert_eq!(
+            prune(&repo)?,
+            Outcome::Pruned { objects: 0 },
+            "the object was freshened and is now within the grace period"
+        );
+
+        filetime::set_file_mtime(&path, filetime::FileTime::from_unix_time(1000, 0))?;
+        assert_eq!(prune(&repo)?, Outcome::Pruned { objects: 1 });
+        Ok(())
+    }
+
+    #[test]
+    fn precious_objects_are_never_pruned() -> crate::Result {
+        let (repo, _tmp) = crate::util::basic_rw_repo()?;
+        let repo = super::with_precious_objects(repo)?;
+        repo.write_blob("unreachable")?;
+        assert!(matches!(
+            prune(&repo).unwrap_err(),
+            gix::maintenance::prune::Error::PreciousObjects
+        ));
+        Ok(())
+    }
+}
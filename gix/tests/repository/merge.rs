@@ -0,0 +1,103 @@
+use gix::{
+    bstr::ByteSlice,
+    merge::{blob::Labels, tree::ConflictKind},
+    objs::tree::EntryKind,
+    ObjectId,
+};
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ours: "ours".into(),
+        theirs: "theirs".into(),
+    }
+}
+
+fn tree(repo: &gix::Repository, files: &[(&str, &str)]) -> crate::Result<ObjectId> {
+    let mut editor = repo.empty_tree().edit()?;
+    for (path, content) in files {
+        let blob = repo.write_blob(content)?;
+        editor.upsert(*path, EntryKind::Blob, blob)?;
+    }
+    Ok(editor.write()?.detach())
+}
+
+fn content(repo: &gix::Repository, tree: ObjectId, path: &str) -> crate::Result<String> {
+    let entry = repo
+        .find_tree(tree)?
+        .peel_to_entry_by_path(path)?
+        .expect("path exists in merged tree");
+    Ok(entry.object()?.data.to_str()?.to_owned())
+}
+
+#[test]
+fn changes_to_different_files_and_regions_are_combined() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let base = tree(&repo, &[("a", "1\n2\n3\n4\n5\n"), ("dir/b", "b\n"), ("c", "c\n")])?;
+    let ours = tree(&repo, &[("a", "one\n2\n3\n4\n5\n"), ("dir/b", "ours\n"), ("c", "c\n")])?;
+    let theirs = tree(
+        &repo,
+        &[("a", "1\n2\n3\n4\nfive\n"), ("dir/b", "b\n"), ("new", "new\n")],
+    )?;
+
+    let outcome = repo.merge_trees(Some(base), ours, theirs, labels())?;
+    assert!(!outcome.has_conflicts());
+    assert_eq!(content(&repo, outcome.tree, "a")?, "one\n2\n3\n4\nfive\n");
+    assert_eq!(content(&repo, outcome.tree, "dir/b")?, "ours\n");
+    assert_eq!(content(&repo, outcome.tree, "new")?, "new\n");
+    assert!(
+        repo.find_tree(outcome.tree)?.peel_to_entry_by_path("c")?.is_none(),
+        "their deletion is applied"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicting_changes_are_reported_and_marked() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let base = tree(&repo, &[("a", "a\nb\nc\n"), ("deleted", "d\n")])?;
+    let ours = tree(&repo, &[("a", "a\nours\nc\n"), ("deleted", "modified\n")])?;
+    let theirs = tree(&repo, &[("a", "a\ntheirs\nc\n")])?;
+
+    let outcome = repo.merge_trees(Some(base), ours, theirs, labels())?;
+    assert_eq!(
+        outcome
+            .conflicts
+            .iter()
+            .map(|conflict| (conflict.path.to_string(), conflict.kind))
+            .collect::<Vec<_>>(),
+        [
+            ("a".to_string(), ConflictKind::Content),
+            ("deleted".into(), ConflictKind::ModifyDelete)
+        ]
+    );
+    assert_eq!(
+        content(&repo, outcome.tree, "a")?,
+        "a\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\nc\n"
+    );
+    assert_eq!(
+        content(&repo, outcome.tree, "deleted")?,
+        "modified\n",
+        "the modified side is kept"
+    );
+    let deleted = &outcome.conflicts[1];
+    assert!(deleted.base.is_some() && deleted.ours.is_some() && deleted.theirs.is_none());
+    Ok(())
+}
+
+#[test]
+fn without_base_identical_additions_merge_cleanly() -> crate::Result {
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let ours = tree(&repo, &[("same", "same\n"), ("ours", "ours\n")])?;
+    let theirs = tree(&repo, &[("same", "same\n"), ("theirs", "theirs\n")])?;
+
+    let outcome = repo.merge_trees(None, ours, theirs, labels())?;
+    assert!(!outcome.has_conflicts());
+    assert_eq!(
+        repo.find_tree(outcome.tree)?
+            .iter()
+            .map(|entry| entry.map(|entry| entry.filename().to_string()))
+            .collect::<Result<Vec<_>, _>>()?,
+        ["ours", "same", "theirs"]
+    );
+    Ok(())
+}
@@ -5,13 +5,25 @@ mod config;
 mod excludes;
 #[cfg(feature = "attributes")]
 mod filter;
+#[cfg(feature = "maintenance")]
+mod maintenance;
+#[cfg(feature = "merge")]
+mod merge;
 mod object;
 mod open;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(feature = "merge")]
+mod rebase;
 mod reference;
 mod remote;
+#[cfg(feature = "revision")]
+mod revision;
+#[cfg(feature = "tree-editor")]
+mod rewrite;
 mod shallow;
+#[cfg(all(feature = "merge", feature = "status"))]
+mod stash;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
@@ -275,6 +275,31 @@ mod write_blob {
     }
 }
 
+mod object_memory {
+    use crate::repository::object::empty_bare_repo;
+
+    #[test]
+    fn persist_and_discard() -> crate::Result {
+        let (_tmp, repo) = empty_bare_repo()?;
+        let disk_repo = repo.clone();
+        let repo = repo.with_object_memory();
+
+        let persisted_id = repo.write_blob("speculative, but kept")?.detach();
+        assert!(!disk_repo.has_object(persisted_id), "the object is only in memory…");
+        assert_eq!(repo.persist_object_memory()?, 1);
+        assert!(disk_repo.has_object(persisted_id), "…until it's persisted");
+
+        let discarded_id = repo.write_blob("speculative, and dropped")?.detach();
+        assert_eq!(repo.discard_object_memory(), 1);
+        assert!(!repo.has_object(discarded_id), "discarded objects are gone…");
+        assert!(!disk_repo.has_object(discarded_id), "…and were never written");
+        assert!(repo.has_object(persisted_id));
+
+        assert_eq!(repo.persist_object_memory()?, 0, "nothing is left to persist");
+        Ok(())
+    }
+}
+
 #[test]
 fn writes_avoid_io_using_duplicate_check() -> crate::Result {
     let repo = crate::named_repo("make_packed_and_loose.sh")?;
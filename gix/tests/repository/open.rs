@@ -130,6 +130,128 @@ fn non_bare_split_worktree_invalid_worktree_path_empty() -> crate::Result {
     Ok(())
 }
 
+mod repository_format {
+    use gix::config::RepositoryFormat;
+
+    fn open_with_config(
+        config: &str,
+    ) -> crate::Result<(
+        Result<gix::Repository, gix::open::Error>,
+        gix_testtools::tempfile::TempDir,
+    )> {
+        open_with_config_opts(config, gix::open::Options::isolated().strict_config(true))
+    }
+
+    fn open_with_config_opts(
+        config: &str,
+        opts: gix::open::Options,
+    ) -> crate::Result<(
+        Result<gix::Repository, gix::open::Error>,
+        gix_testtools::tempfile::TempDir,
+    )> {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let repo = gix::init_bare(tmp.path())?;
+        let config_path = repo.git_dir().join("config");
+        let mut buf = std::fs::read(&config_path)?;
+        buf.extend_from_slice(config.as_bytes());
+        std::fs::write(config_path, buf)?;
+        Ok((gix::open_opts(tmp.path(), opts), tmp))
+    }
+
+    #[test]
+    fn extensions_are_exposed() -> crate::Result {
+        let (repo, _tmp) = open_with_config(
+            "[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\tobjectFormat = sha1\n\trefStorage = files\n\tpreciousObjects = true\n\tpartialClone = origin\n\tnoop\n\tnoop-v1\n",
+        )?;
+        assert_eq!(
+            *repo?.repository_format(),
+            RepositoryFormat {
+                version: 1,
+                object_hash: gix_hash::Kind::Sha1,
+                worktree_config: false,
+                precious_objects: true,
+                partial_clone: Some("origin".into()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_extensions_are_ignored_in_version_0() -> crate::Result {
+        let (repo, _tmp) = open_with_config(
+            "[core]\n\trepositoryFormatVersion = 0\n[extensions]\n\tunknown = true\n\trefStorage = reftable\n",
+        )?;
+        assert_eq!(repo?.repository_format().version, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_extensions_are_rejected_in_version_1() -> crate::Result {
+        let (repo, _tmp) = open_with_config("[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\tunknown = true\n")?;
+        assert!(matches!(
+            repo.unwrap_err(),
+            gix::open::Error::Config(gix::config::Error::UnknownExtension { name }) if name == "unknown"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_extensions_are_ignored_in_lenient_mode() -> crate::Result {
+        let (repo, _tmp) = open_with_config_opts(
+            "[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\tunknown = true\n",
+            gix::open::Options::isolated().strict_config(false),
+        )?;
+        assert_eq!(repo?.repository_format().version, 1, "the extension is merely reported");
+        Ok(())
+    }
+
+    #[test]
+    fn known_but_unsupported_extensions_are_rejected_in_version_1() -> crate::Result {
+        let (repo, _tmp) =
+            open_with_config("[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\tcompatObjectFormat = sha256\n")?;
+        assert!(matches!(
+            repo.unwrap_err(),
+            gix::open::Error::Config(gix::config::Error::UnsupportedExtension { name }) if name == "compatObjectFormat"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn known_but_unsupported_extensions_are_ignored_in_lenient_mode() -> crate::Result {
+        let (repo, _tmp) = open_with_config_opts(
+            "[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\tcompatObjectFormat = sha256\n",
+            gix::open::Options::isolated().strict_config(false),
+        )?;
+        assert_eq!(
+            repo?.object_hash(),
+            gix_hash::Kind::Sha1,
+            "the compatibility format is ignored"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_ref_storage_is_rejected() -> crate::Result {
+        let (repo, _tmp) =
+            open_with_config("[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\trefStorage = unknown\n")?;
+        assert!(matches!(
+            repo.unwrap_err(),
+            gix::open::Error::Config(gix::config::Error::ConfigTypedString(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() -> crate::Result {
+        let (repo, _tmp) = open_with_config("[core]\n\trepositoryFormatVersion = 2\n")?;
+        assert!(matches!(
+            repo.unwrap_err(),
+            gix::open::Error::Config(gix::config::Error::UnsupportedRepositoryFormatVersion { version: 2 })
+        ));
+        Ok(())
+    }
+}
+
 mod missing_config_file {
 
     use crate::util::named_subrepo_opts;
@@ -0,0 +1,303 @@
+use gix::{
+    bstr::{BStr, BString},
+    objs::tree::EntryKind,
+    rebase::{
+        todo::{Instruction, List},
+        DelegateError, MessageKind, NonInteractive, Stop,
+    },
+    ObjectId,
+};
+
+fn commit(
+    repo: &gix::Repository,
+    reference: &str,
+    message: &str,
+    files: &[(&str, &str)],
+    parents: &[ObjectId],
+) -> crate::Result<ObjectId> {
+    let mut editor = repo.empty_tree().edit()?;
+    for (path, content) in files {
+        let blob = repo.write_blob(content)?;
+        editor.upsert(*path, EntryKind::Blob, blob)?;
+    }
+    let tree = editor.write()?;
+    Ok(repo.commit(reference, message, tree, parents.iter().copied())?.detach())
+}
+
+fn paths(repo: &gix::Repository, id: ObjectId) -> crate::Result<Vec<BString>> {
+    let tree = repo.find_commit(id)?.tree()?;
+    Ok(tree
+        .decode()?
+        .entries
+        .iter()
+        .map(|entry| entry.filename.to_owned())
+        .collect())
+}
+
+fn parents(repo: &gix::Repository, id: ObjectId) -> crate::Result<Vec<ObjectId>> {
+    Ok(repo.find_commit(id)?.parent_ids().map(gix::Id::detach).collect())
+}
+
+fn message(repo: &gix::Repository, id: ObjectId) -> crate::Result<BString> {
+    Ok(repo.find_commit(id)?.message_raw()?.to_owned())
+}
+
+/// Return a repository with `base`, `upstream` on top of `base`, and three commits on top of `base` which each add a file.
+fn repo_with_topic() -> crate::Result<(
+    gix::Repository,
+    gix_testtools::tempfile::TempDir,
+    ObjectId,
+    ObjectId,
+    Vec<ObjectId>,
+)> {
+    let (repo, tmp) = crate::util::basic_rw_repo()?;
+    let base = commit(&repo, "refs/heads/base", "base", &[("a", "a\n")], &[])?;
+    let upstream = commit(&repo, "refs/heads/upstream", "upstream", &[("a", "A\n")], &[base])?;
+    let mut topic = Vec::new();
+    let mut files = vec![("a", "a\n")];
+    for name in ["one", "two", "three"] {
+        files.push((name, name));
+        let parent = topic.last().copied().unwrap_or(base);
+        topic.push(commit(&repo, "refs/heads/topic", name, &files, &[parent])?);
+    }
+    Ok((repo, tmp, base, upstream, topic))
+}
+
+#[test]
+fn todo_picks_commits_parents_first() -> crate::Result {
+    let (repo, _tmp, _base, upstream, topic) = repo_with_topic()?;
+    let todo = repo.rebase_todo(upstream, topic[2])?;
+    let instructions: Vec<_> = todo.instructions().collect();
+    assert_eq!(instructions.len(), 3);
+    for ((instruction, expected), summary) in instructions.iter().zip(&topic).zip(["one", "two", "three"]) {
+        let Instruction::Pick {
+            commit,
+            summary: actual,
+        } = instruction
+        else {
+            panic!("only picks are expected")
+        };
+        assert_eq!(commit.cmp_oid(expected), std::cmp::Ordering::Equal);
+        assert!(commit.hex_len() < expected.kind().len_in_hex(), "ids are abbreviated");
+        assert_eq!(actual, summary);
+    }
+    assert_eq!(repo.rebase_todo(topic[2], upstream)?.lines.len(), 1, "only upstream");
+    Ok(())
+}
+
+#[test]
+fn picks_are_applied_onto_the_new_base() -> crate::Result {
+    let (repo, _tmp, base, upstream, topic) = repo_with_topic()?;
+    let todo = repo.rebase_todo(upstream, topic[2])?;
+    let mut with_upstream = todo.clone();
+    with_upstream
+        .lines
+        .push(gix::rebase::todo::Line::Instruction(Instruction::Pick {
+            commit: upstream.into(),
+            summary: "already applied".into(),
+        }));
+    let out = repo.rebase(upstream, with_upstream, &mut NonInteractive)?;
+    let head = out.head.expect("commits were picked");
+    assert_eq!(paths(&repo, head)?, ["a", "one", "three", "two"]);
+    assert_eq!(
+        repo.find_commit(head)?
+            .tree()?
+            .find_entry("a")
+            .expect("present")
+            .object()?
+            .data,
+        b"A\n",
+        "the change of upstream is kept"
+    );
+    assert_eq!(out.dropped, [upstream], "changes that are already present are dropped");
+    assert_eq!(
+        out.rewritten.iter().map(|(original, _)| *original).collect::<Vec<_>>(),
+        topic
+    );
+    assert_eq!(parents(&repo, out.rewritten[0].1)?, [upstream]);
+    assert_eq!(message(&repo, out.rewritten[2].1)?, "three");
+    assert_eq!(out.rewritten[2].1, head);
+
+    let out = repo.rebase(base, todo, &mut NonInteractive)?;
+    assert_eq!(out.head, Some(topic[2]), "commits that don't need to change are reused");
+    assert!(out.rewritten.iter().all(|(original, new)| original == new));
+    Ok(())
+}
+
+#[derive(Default)]
+struct Recorder {
+    edit_todo: Option<List>,
+    messages: Vec<(MessageKind, BString)>,
+    stops: Vec<Stop>,
+    commands: Vec<BString>,
+}
+
+impl gix::rebase::Delegate for Recorder {
+    fn edit_todo(&mut self, list: &mut List) -> Result<(), DelegateError> {
+        if let Some(edited) = self.edit_todo.take() {
+            *list = edited;
+        }
+        Ok(())
+    }
+
+    fn edit_message(&mut self, kind: MessageKind, message: &mut BString) -> Result<(), DelegateError> {
+        self.messages.push((kind, message.clone()));
+        *message = format!("edited {kind:?}").into();
+        Ok(())
+    }
+
+    fn stop(&mut self, stop: Stop, head: Option<ObjectId>) -> Result<Option<ObjectId>, DelegateError> {
+        self.stops.push(stop);
+        Ok(head)
+    }
+
+    fn exec(&mut self, command: &BStr, head: Option<ObjectId>) -> Result<Option<ObjectId>, DelegateError> {
+        self.commands.push(command.to_owned());
+        Ok(head)
+    }
+}
+
+#[test]
+fn the_delegate_edits_the_todo_list_and_messages() -> crate::Result {
+    let (repo, _tmp, _base, upstream, topic) = repo_with_topic()?;
+    let todo = format!(
+        "reword {}\nsquash {}\n# comment\nexec make test\nbreak\nedit {}\nfixup -C {upstream}\ndrop {}\n",
+        topic[0], topic[1], topic[2], topic[1]
+    );
+    let mut delegate = Recorder {
+        edit_todo: Some(List::from_bytes(todo.as_bytes(), b'#')?),
+        ..Default::default()
+    };
+    let out = repo.rebase(upstream, repo.rebase_todo(upstream, topic[2])?, &mut delegate)?;
+
+    assert_eq!(
+        delegate.messages,
+        [
+            (MessageKind::Reword, "one".into()),
+            (MessageKind::Squash, "edited Reword\n\ntwo".into())
+        ],
+        "`fixup -C` replaces the message without editing it"
+    );
+    assert_eq!(delegate.commands, ["make test"]);
+    assert_eq!(delegate.stops, [Stop::Break, Stop::Edit { commit: topic[2] }]);
+
+    let squashed = out.rewritten[0].1;
+    assert_eq!(message(&repo, squashed)?, "edited Squash");
+    assert_eq!(parents(&repo, squashed)?, [upstream]);
+    assert_eq!(paths(&repo, squashed)?, ["a", "one", "two"]);
+    assert_eq!(
+        out.rewritten[..2],
+        [(topic[0], squashed), (topic[1], squashed)],
+        "folded commits are contained in the same commit"
+    );
+
+    let head = out.head.expect("present");
+    assert_eq!(out.rewritten[2..], [(topic[2], head), (upstream, head)]);
+    assert_eq!(message(&repo, head)?, "upstream");
+    assert_eq!(parents(&repo, head)?, [squashed]);
+    assert_eq!(paths(&repo, head)?, ["a", "one", "three", "two"]);
+    assert_eq!(out.dropped, [topic[1]]);
+    Ok(())
+}
+
+#[test]
+fn merges_with_labels_and_ref_updates() -> crate::Result {
+    let (repo, _tmp, base, upstream, topic) = repo_with_topic()?;
+    let side = commit(
+        &repo,
+        "refs/heads/side",
+        "side",
+        &[("a", "a\n"), ("side", "side")],
+        &[base],
+    )?;
+    let merge = commit(
+        &repo,
+        "refs/heads/merged",
+        "Merge branch 'side'",
+        &[("a", "a\n"), ("one", "one"), ("side", "side")],
+        &[topic[0], side],
+    )?;
+    let todo = format!(
+        "label onto\nreset onto\npick {side}\nlabel side\nreset onto\npick {}\nmerge -C {merge} side # Merge branch 'side'\nupdate-ref refs/heads/side\n",
+        topic[0]
+    );
+    let todo = List::from_bytes(todo.as_bytes(), b'#')?;
+
+    let out = repo.rebase(upstream, todo.clone(), &mut NonInteractive)?;
+    let head = out.head.expect("merge commit");
+    let new_parents = parents(&repo, head)?;
+    assert_eq!(new_parents.len(), 2);
+    assert_eq!(parents(&repo, new_parents[0])?, [upstream]);
+    assert_eq!(parents(&repo, new_parents[1])?, [upstream]);
+    assert_eq!(message(&repo, head)?, "Merge branch 'side'");
+    assert_eq!(paths(&repo, head)?, ["a", "one", "side"]);
+    assert_eq!(out.rewritten.last(), Some(&(merge, head)));
+    assert_eq!(
+        out.update_refs,
+        [("refs/heads/side".try_into()?, head)],
+        "refs to update are only reported"
+    );
+    assert_eq!(
+        repo.find_reference("refs/heads/side")?.id(),
+        side,
+        "references aren't changed"
+    );
+
+    let out = repo.rebase(base, todo, &mut NonInteractive)?;
+    assert_eq!(
+        out.head,
+        Some(merge),
+        "merges are reused if their parents didn't change"
+    );
+
+    let mut delegate = Recorder::default();
+    let todo = List::from_bytes(
+        format!("pick {side}\nlabel side\nreset onto\nmerge side").as_bytes(),
+        b'#',
+    )?;
+    let out = repo.rebase(upstream, todo, &mut delegate)?;
+    assert_eq!(
+        delegate.messages,
+        [(MessageKind::Merge, "Merge branch 'side'".into())],
+        "without a commit, the message is generated and edited"
+    );
+    assert_eq!(message(&repo, out.head.expect("merge"))?, "edited Merge");
+    Ok(())
+}
+
+#[test]
+fn errors() -> crate::Result {
+    let (repo, _tmp, base, upstream, topic) = repo_with_topic()?;
+    let conflicting = commit(
+        &repo,
+        "refs/heads/conflict",
+        "conflict",
+        &[("a", "conflict\n")],
+        &[base],
+    )?;
+    for (todo, expected) in [
+        ("exec make".to_string(), "The rebase was aborted"),
+        (
+            format!("pick {conflicting}"),
+            "Applying commit {conflicting} resulted in 1 conflict(s)",
+        ),
+        (
+            format!("reset [new root]\nsquash {}", topic[0]),
+            "Cannot 'squash' without a current commit",
+        ),
+        ("merge a b".into(), "Octopus merges of 3 commits aren't supported"),
+        (
+            "reset does-not-exist".into(),
+            "Could not resolve label \"does-not-exist\"",
+        ),
+        ("pick 0000000".into(), "The commit 0000000 doesn't exist"),
+    ] {
+        let todo = List::from_bytes(todo.as_bytes(), b'#')?;
+        let err = repo.rebase(upstream, todo, &mut NonInteractive).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            expected.replace("{conflicting}", &conflicting.to_string())
+        );
+    }
+    Ok(())
+}
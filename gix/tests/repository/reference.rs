@@ -80,6 +80,176 @@ mod set_namespace {
     }
 }
 
+mod prepare_reference_edits {
+    use gix::refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit},
+        Target,
+    };
+
+    fn create(name: &str) -> RefEdit {
+        RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::MustNotExist,
+                new: Target::Object(gix::ObjectId::empty_tree(gix::hash::Kind::Sha1)),
+            },
+            name: name.try_into().expect("valid"),
+            deref: false,
+        }
+    }
+
+    #[test]
+    fn locks_are_held_until_commit_or_rollback() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_references_repo.sh")?;
+        let prepared = repo.prepare_reference_edits(Some(create("refs/heads/new")))?;
+        assert_eq!(
+            prepared.edits().map(|edit| edit.name.as_bstr()).collect::<Vec<_>>(),
+            ["refs/heads/new"]
+        );
+        assert!(
+            repo.try_find_reference("refs/heads/new")?.is_none(),
+            "nothing is written before committing"
+        );
+        assert!(
+            repo.prepare_reference_edits(Some(create("refs/heads/new"))).is_err(),
+            "the reference is locked"
+        );
+
+        let edits = prepared.rollback();
+        assert_eq!(edits.len(), 1);
+        assert!(repo.try_find_reference("refs/heads/new")?.is_none());
+
+        let prepared = repo.prepare_reference_edits(Some(create("refs/heads/new")))?;
+        drop(prepared);
+        let prepared = repo.prepare_reference_edits(Some(create("refs/heads/new")))?;
+        assert!(
+            repo.try_find_reference("refs/heads/new")?.is_none(),
+            "dropping rolls back"
+        );
+
+        let edits = prepared.commit()?;
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            repo.find_reference("refs/heads/new")?.id(),
+            gix::ObjectId::empty_tree(gix::hash::Kind::Sha1)
+        );
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, feature = "command"))]
+mod reference_transaction_hook {
+    use std::os::unix::fs::PermissionsExt;
+
+    use gix::refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit},
+        Target,
+    };
+
+    fn install_hook(repo: &gix::Repository) -> crate::Result {
+        let path = repo.git_dir().join("hooks").join("reference-transaction");
+        std::fs::create_dir_all(path.parent().expect("hooks dir"))?;
+        std::fs::write(
+            &path,
+            "#!/bin/sh\n{ echo \"$1\"; cat; } >>\"$GIT_DIR/hook.log\"\ntest \"$1\" != prepared || test ! -f reject\n",
+        )?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    fn repo_rw_with_hooks() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+        crate::util::repo_rw_opts("make_basic_repo.sh", crate::util::restricted().run_hooks(true))
+    }
+
+    fn hook_log(repo: &gix::Repository) -> String {
+        std::fs::read_to_string(repo.git_dir().join("hook.log")).unwrap_or_default()
+    }
+
+    fn update(previous: gix::ObjectId, new: gix::ObjectId) -> RefEdit {
+        RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::MustExistAndMatch(Target::Object(previous)),
+                new: Target::Object(new),
+            },
+            name: "refs/heads/main".try_into().expect("valid"),
+            deref: false,
+        }
+    }
+
+    fn empty_tree() -> gix::ObjectId {
+        gix::ObjectId::empty_tree(gix::hash::Kind::Sha1)
+    }
+
+    #[test]
+    fn is_run_for_each_state_with_updates_on_stdin() -> crate::Result {
+        let (repo, _keep) = repo_rw_with_hooks()?;
+        install_hook(&repo)?;
+        let previous = repo.head_id()?.detach();
+        repo.edit_reference(update(previous, empty_tree()))?;
+        let line = format!("{previous} {} refs/heads/main", empty_tree());
+        assert_eq!(hook_log(&repo), format!("prepared\n{line}\ncommitted\n{line}\n"));
+
+        std::fs::remove_file(repo.git_dir().join("hook.log"))?;
+        std::fs::write(repo.work_dir().expect("non-bare").join("reject"), "")?;
+        let err = repo.edit_reference(update(empty_tree(), previous)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The transaction was rejected by its hook",
+            "the hook runs in the worktree and rejects the transaction"
+        );
+        assert_eq!(
+            repo.find_reference("refs/heads/main")?.id(),
+            empty_tree(),
+            "nothing changed"
+        );
+        let line = format!("{} {previous} refs/heads/main", empty_tree());
+        assert_eq!(hook_log(&repo), format!("prepared\n{line}\naborted\n{line}\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn is_not_run_if_disabled() -> crate::Result {
+        let (repo, _keep) =
+            crate::util::repo_rw_opts("make_basic_repo.sh", crate::util::restricted().run_hooks(false))?;
+        install_hook(&repo)?;
+        repo.edit_reference(update(repo.head_id()?.detach(), empty_tree()))?;
+        assert_eq!(hook_log(&repo), "");
+        Ok(())
+    }
+
+    #[test]
+    fn is_not_run_in_isolated_repositories() -> crate::Result {
+        let (repo, _keep) = crate::util::repo_rw_opts(
+            "make_basic_repo.sh",
+            gix::open::Options::isolated().config_overrides(["user.name=gitoxide", "user.email=gitoxide@localhost"]),
+        )?;
+        install_hook(&repo)?;
+        repo.edit_reference(update(repo.head_id()?.detach(), empty_tree()))?;
+        assert_eq!(hook_log(&repo), "", "isolated repositories don't run their own hooks");
+        Ok(())
+    }
+
+    #[test]
+    fn is_found_in_core_hooks_path() -> crate::Result {
+        let (mut repo, _keep) = repo_rw_with_hooks()?;
+        install_hook(&repo)?;
+        std::fs::rename(repo.git_dir().join("hooks"), repo.git_dir().join("custom-hooks"))?;
+        let previous = repo.head_id()?.detach();
+        repo.edit_reference(update(previous, empty_tree()))?;
+        assert_eq!(hook_log(&repo), "", "there is no hook in the default location");
+
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Core::HOOKS_PATH, ".git/custom-hooks")?;
+        repo.edit_reference(update(empty_tree(), previous))?;
+        assert!(
+            hook_log(&repo).starts_with("prepared\n"),
+            "relative paths are relative to the worktree"
+        );
+        Ok(())
+    }
+}
+
 mod iter_references {
 
     use crate::util::hex_to_id;
@@ -226,3 +396,117 @@ mod head {
         Ok(())
     }
 }
+
+mod reftable {
+    use gix::refs::transaction::PreviousValue;
+    use gix_testtools::tempfile;
+
+    /// Turn a new repository into one that keeps its references in a reftable, like `git init --ref-format=reftable` would.
+    fn reftable_repo() -> crate::Result<(gix::Repository, tempfile::TempDir)> {
+        let dir = tempfile::tempdir()?;
+        let git_dir = gix::init(dir.path())?.git_dir().to_owned();
+        let mut config = std::fs::OpenOptions::new().append(true).open(git_dir.join("config"))?;
+        std::io::Write::write_all(
+            &mut config,
+            b"[core]\n\trepositoryFormatVersion = 1\n[extensions]\n\trefStorage = reftable\n",
+        )?;
+        std::fs::create_dir(git_dir.join("reftable"))?;
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/.invalid\n")?;
+        gix::refs::reftable::Store::at(git_dir, Default::default())
+            .transaction()
+            .prepare(
+                [gix::refs::transaction::RefEdit {
+                    change: gix::refs::transaction::Change::Update {
+                        log: Default::default(),
+                        expected: PreviousValue::MustNotExist,
+                        new: gix::refs::Target::Symbolic("refs/heads/main".try_into()?),
+                    },
+                    name: "HEAD".try_into()?,
+                    deref: false,
+                }],
+                gix::lock::acquire::Fail::Immediately,
+            )?
+            .commit(None)?;
+        let repo = gix::open_opts(dir.path(), crate::restricted())?;
+        Ok((repo, dir))
+    }
+
+    #[test]
+    fn open_read_and_write() -> crate::Result {
+        let (mut repo, _keep) = reftable_repo()?;
+        assert_eq!(
+            repo.head_name()?.expect("symbolic").as_bstr(),
+            "refs/heads/main",
+            "HEAD is read from the reftable, not from the placeholder file"
+        );
+        assert!(repo.head()?.is_unborn());
+
+        let tree = repo.write_object(gix::objs::Tree::empty())?.detach();
+        let commit = repo
+            .commit("HEAD", "initial", tree, gix::commit::NO_PARENT_IDS)?
+            .detach();
+        assert_eq!(
+            repo.head_id()?,
+            commit,
+            "HEAD is dereferenced and updated in the reftable"
+        );
+        assert!(
+            !repo.git_dir().join("refs/heads/main").exists(),
+            "no loose reference was written"
+        );
+
+        repo.tag_reference("v1", commit, PreviousValue::MustNotExist)?;
+        repo.reference("refs/heads/other", commit, PreviousValue::MustNotExist, "create")?;
+        assert_eq!(
+            repo.references()?
+                .all()?
+                .map(|r| r.map(|r| r.name().as_bstr().to_owned()))
+                .collect::<Result<Vec<_>, _>>()?,
+            ["refs/heads/main", "refs/heads/other", "refs/tags/v1"]
+        );
+        assert_eq!(repo.references()?.tags()?.count(), 1);
+        assert_eq!(repo.find_reference("v1")?.peel_to_id_in_place()?, commit);
+        assert_eq!(repo.rev_parse_single("other")?, commit);
+
+        repo.find_reference("other")?.delete()?;
+        assert!(repo.try_find_reference("other")?.is_none());
+
+        repo.set_namespace("ns")?;
+        repo.reference("refs/heads/namespaced", commit, PreviousValue::MustNotExist, "create")?;
+        assert_eq!(
+            repo.references()?.all()?.count(),
+            1,
+            "only the namespaced reference is visible"
+        );
+        repo.clear_namespace();
+        assert!(repo
+            .try_find_reference("refs/namespaces/ns/refs/heads/namespaced")?
+            .is_some());
+
+        let reopened = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        assert_eq!(reopened.head_id()?, commit, "all changes are persisted");
+        Ok(())
+    }
+
+    #[test]
+    fn repositories_created_by_git_can_be_read_and_written() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 45, 0) {
+            return Ok(());
+        }
+        let (repo, dir) = crate::repo_rw("make_reftable_repo.sh")?;
+        let head_id = repo.head_id()?.detach();
+        assert_eq!(repo.head_name()?.expect("symbolic").as_bstr(), "refs/heads/main");
+        assert_eq!(repo.references()?.all()?.count(), 3);
+        assert_eq!(repo.find_reference("annotated")?.peel_to_id_in_place()?, head_id);
+        assert_eq!(repo.rev_parse_single("annotated^{commit}")?, head_id);
+
+        repo.reference("refs/heads/new", head_id, PreviousValue::MustNotExist, "created by gix")?;
+        let out = std::process::Command::new("git")
+            .args(["rev-parse", "refs/heads/new"])
+            .current_dir(dir.path())
+            .output()?;
+        assert!(out.status.success(), "git can read the table written by gix");
+        assert_eq!(out.stdout, format!("{head_id}\n").as_bytes());
+        Ok(())
+    }
+}
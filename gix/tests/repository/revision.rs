@@ -0,0 +1,69 @@
+use crate::util::hex_to_id;
+
+fn branch_names(references: Vec<gix::Reference<'_>>) -> Vec<String> {
+    references.iter().map(|r| r.name().shorten().to_string()).collect()
+}
+
+fn branches<'a>(platform: &'a gix::reference::iter::Platform<'_>) -> crate::Result<Vec<gix::Reference<'a>>> {
+    platform.local_branches()?.collect()
+}
+
+#[test]
+fn references_containing() -> crate::Result {
+    for use_commit_graph in [false, true] {
+        let repo = crate::named_subrepo_opts(
+            "make_repo_with_fork_and_dates.sh",
+            "",
+            gix::open::Options::isolated().config_overrides([format!("core.commitGraph={use_commit_graph}")]),
+        )?;
+        let refs = repo.references()?;
+        let c1 = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+        let b1c1 = hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac");
+        let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+
+        assert_eq!(
+            branch_names(repo.references_containing(c1, branches(&refs)?)?),
+            ["branch1", "main"],
+            "the root commit is contained in all branches"
+        );
+        assert_eq!(
+            branch_names(repo.references_containing(b1c1, branches(&refs)?)?),
+            ["branch1", "main"],
+            "branch1 was merged into main"
+        );
+        assert_eq!(
+            branch_names(repo.references_containing(c2, branches(&refs)?)?),
+            ["main"]
+        );
+        assert_eq!(
+            branch_names(repo.references_containing(repo.object_hash().null(), branches(&refs)?)?),
+            Vec::<String>::new(),
+            "missing commits are contained nowhere"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn references_merged_into() -> crate::Result {
+    let repo = crate::named_repo("make_repo_with_fork_and_dates.sh")?;
+    let refs = repo.references()?;
+    let main = repo.rev_parse_single("main")?;
+    let branch1 = repo.rev_parse_single("branch1")?;
+    let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+
+    assert_eq!(
+        branch_names(repo.references_merged_into(main, branches(&refs)?)?),
+        ["branch1", "main"]
+    );
+    assert_eq!(
+        branch_names(repo.references_merged_into(branch1, branches(&refs)?)?),
+        ["branch1"]
+    );
+    assert_eq!(
+        branch_names(repo.references_merged_into(c2, branches(&refs)?)?),
+        Vec::<String>::new(),
+        "neither branch is merged into a commit that predates both of their tips"
+    );
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use gix::bstr::ByteVec;
+use gix::rewrite::{Action, Delegate, DelegateError};
+
+struct RemoveSecret;
+
+impl Delegate for RemoveSecret {
+    fn rewrite_tree(&mut self, editor: &mut gix::object::tree::Editor<'_>) -> Result<(), DelegateError> {
+        editor.remove("secret")?;
+        Ok(())
+    }
+
+    fn rewrite_commit(
+        &mut self,
+        _id: &gix_hash::oid,
+        commit: &mut gix_object::Commit,
+    ) -> Result<Action, DelegateError> {
+        commit.message.insert_str(0, "rewritten ");
+        Ok(Action::Keep)
+    }
+}
+
+fn messages_and_trees(repo: &gix::Repository, tip: gix_hash::ObjectId) -> crate::Result<Vec<(String, Vec<String>)>> {
+    let mut out = Vec::new();
+    for info in repo.rev_walk(Some(tip)).all()? {
+        let commit = info?.object()?;
+        let files = commit
+            .tree()?
+            .decode()?
+            .entries
+            .iter()
+            .map(|entry| entry.filename.to_string())
+            .collect();
+        out.push((commit.message_raw_sloppy().to_string(), files));
+    }
+    Ok(out)
+}
+
+#[test]
+fn remove_file_from_all_commits_and_prune_empty_ones() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_rewrite_repo.sh")?;
+    let original_other = repo.find_reference("refs/heads/other")?.id().detach();
+    let original_tag = repo.find_reference("refs/tags/v1")?.id().detach();
+
+    let outcome = repo.rewrite_history(&mut RemoveSecret, Default::default())?;
+    assert_eq!(outcome.commits.len(), 4);
+    assert_eq!(
+        outcome.commits.values().filter(|new| new.is_some()).count(),
+        4,
+        "the dropped commit is mapped to its parent"
+    );
+    assert_eq!(outcome.ref_edits.len(), 3, "all refs changed");
+    assert_eq!(outcome.packs.len(), 1, "all objects fit into a single pack");
+    assert!(
+        outcome
+            .packs
+            .iter()
+            .all(|pack| pack.keep_path.as_ref().is_some_and(|p| !p.exists())),
+        "keep-files are removed once refs point to the new objects"
+    );
+
+    let main = repo.find_reference("refs/heads/main")?.id().detach();
+    assert_eq!(
+        messages_and_trees(&repo, main)?,
+        vec![
+            ("rewritten c4 was empty already\n".into(), vec!["a".into()]),
+            ("rewritten c2\n".into(), vec!["a".into()]),
+            ("rewritten c1\n".into(), vec!["a".into()]),
+        ],
+        "c3 became empty and was pruned, while c4 was empty before and is kept"
+    );
+    let other = repo.find_reference("refs/heads/other")?.id().detach();
+    assert_ne!(other, original_other);
+    assert_eq!(outcome.commits[&original_other], Some(other));
+
+    let tag = repo.find_reference("refs/tags/v1")?.id();
+    assert_ne!(tag, original_tag);
+    let tag = tag.object()?.into_tag();
+    let tag = tag.decode()?;
+    assert_eq!(tag.message, "annotated\n", "the tag itself is unchanged…");
+    assert_eq!(
+        messages_and_trees(&repo, tag.target())?[0].0,
+        "rewritten c2\n",
+        "…but points to the replacement of the pruned commit"
+    );
+
+    let mut map = Vec::new();
+    outcome.write_commit_map(&mut map)?;
+    assert_eq!(
+        map.iter().filter(|b| **b == b'\n').count(),
+        5,
+        "header and one line per commit"
+    );
+    Ok(())
+}
+
+#[test]
+fn unchanged_history_keeps_all_ids() -> crate::Result {
+    struct Noop;
+    impl Delegate for Noop {}
+
+    let (repo, _tmp) = crate::repo_rw("make_rewrite_repo.sh")?;
+    let main = repo.find_reference("refs/heads/main")?.id().detach();
+    let outcome = repo.rewrite_history(&mut Noop, Default::default())?;
+    assert!(outcome.commits.iter().all(|(old, new)| Some(*old) == *new));
+    assert!(outcome.ref_edits.is_empty());
+    assert!(outcome.packs.is_empty(), "no new object had to be written");
+    assert_eq!(repo.find_reference("refs/heads/main")?.id(), main);
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use gix::{
+    bstr::ByteSlice,
+    objs::tree::EntryKind,
+    stash::{Operation, Reapplied},
+    ObjectId,
+};
+
+fn file(repo: &gix::Repository, path: &str) -> Option<String> {
+    std::fs::read_to_string(repo.work_dir().expect("non-bare").join(path)).ok()
+}
+
+fn tree_id_from_git(repo: &gix::Repository, name: &str) -> crate::Result<ObjectId> {
+    let hex = std::fs::read(repo.git_dir().join(name))?;
+    Ok(ObjectId::from_hex(hex.trim())?)
+}
+
+/// Commit `content` to `path` on top of `HEAD`, without touching the index or worktree.
+fn commit_file(repo: &gix::Repository, path: &str, content: &str) -> crate::Result<ObjectId> {
+    let head = repo.head_commit()?;
+    let blob = repo.write_blob(content)?;
+    let tree = head.tree()?.edit()?.upsert(path, EntryKind::Blob, blob)?.write()?;
+    Ok(repo.commit("HEAD", "change", tree, Some(head.id))?.detach())
+}
+
+#[test]
+fn create_stores_index_and_worktree_like_git() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    let id = repo.stash_create(None)?.expect("there are changes");
+    let stash = repo.find_commit(id)?;
+    let head = repo.head_id()?;
+    let parents: Vec<_> = stash.parent_ids().collect();
+    assert_eq!(parents.len(), 2);
+    assert_eq!(parents[0], head);
+    assert_eq!(stash.tree_id()?, tree_id_from_git(&repo, "stash-worktree-tree")?);
+    let index_commit = repo.find_commit(parents[1])?;
+    assert_eq!(index_commit.parent_ids().collect::<Vec<_>>(), [head]);
+    assert_eq!(index_commit.tree_id()?, tree_id_from_git(&repo, "stash-index-tree")?);
+    assert_eq!(
+        stash.message_raw()?,
+        format!("WIP on main: {} initial\n", head.shorten_or_id())
+    );
+    assert_eq!(
+        index_commit.message_raw()?,
+        format!("index on main: {} initial\n", head.shorten_or_id())
+    );
+    assert_eq!(
+        repo.find_commit(repo.stash_create(Some("message".into()))?.expect("changes"))?
+            .message_raw()?,
+        "On main: message\n"
+    );
+
+    assert_eq!(file(&repo, "a").as_deref(), Some("a changed\n"), "nothing is changed");
+    assert_eq!(file(&repo, "b"), None);
+    assert!(repo.try_find_reference("refs/stash")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn autostash_resets_and_reapplies_local_changes_onto_head() -> crate::Result {
+    let (mut repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    assert!(
+        repo.autostash(Operation::Merge, None)?.is_none(),
+        "`merge.autoStash` isn't set"
+    );
+    assert!(repo.autostash(Operation::Checkout, None)?.is_none());
+    repo.config_snapshot_mut()
+        .set_value(&gix::config::tree::Merge::AUTO_STASH, "true")?;
+
+    let stash = repo.autostash(Operation::Merge, None)?.expect("there are changes");
+    assert!(!repo.is_dirty()?, "the index and worktree are reset to HEAD");
+    assert_eq!(file(&repo, "a").as_deref(), Some("a\n"));
+    assert_eq!(file(&repo, "b").as_deref(), Some("b\n"));
+    assert_eq!(file(&repo, "new"), None);
+    assert!(repo.stash_create(None)?.is_none());
+
+    commit_file(&repo, "d", "d from upstream\n")?;
+    assert_eq!(stash.apply()?, Reapplied::Applied);
+    assert_eq!(file(&repo, "a").as_deref(), Some("a changed\n"));
+    assert_eq!(file(&repo, "b"), None);
+    assert_eq!(file(&repo, "d").as_deref(), Some("d from upstream\n"));
+    assert_eq!(file(&repo, "c").as_deref(), Some("c changed after staging\n"));
+    assert_eq!(file(&repo, "new").as_deref(), Some("new\n"));
+    let index = repo.open_index()?;
+    assert!(
+        index.entry_by_path("new".into()).is_some(),
+        "new files are added to the index"
+    );
+    assert_eq!(
+        index.entry_by_path("c".into()).expect("present").id,
+        repo.head_commit()?
+            .tree()?
+            .find_entry("c")
+            .expect("present")
+            .object_id(),
+        "other changes are only applied to the worktree"
+    );
+    assert!(repo.try_find_reference("refs/stash")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn autostash_is_stored_on_conflict() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    let stash = repo.autostash(Operation::Rebase, Some(true))?.expect("changes");
+    let id = stash.id();
+    commit_file(&repo, "a", "a from upstream\n")?;
+    let Reapplied::Stored { conflicts } = stash.apply()? else {
+        panic!("`a` was changed by both")
+    };
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].path, "a");
+    assert_eq!(repo.find_reference("refs/stash")?.id(), id);
+    assert_eq!(file(&repo, "a").as_deref(), Some("a\n"), "nothing was applied");
+    Ok(())
+}
+
+#[test]
+fn dropped_autostash_is_stored() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    let id = repo.autostash(Operation::Rebase, Some(true))?.expect("changes").id();
+    let stash = repo.find_reference("refs/stash")?;
+    assert_eq!(stash.id(), id);
+    assert_eq!(
+        stash
+            .log_iter()
+            .all()?
+            .expect("present")
+            .next()
+            .expect("one entry")?
+            .message,
+        "autostash"
+    );
+    repo.stash_apply(id)?;
+    assert_eq!(file(&repo, "a").as_deref(), Some("a changed\n"));
+    Ok(())
+}
+
+#[test]
+fn filtered_files_are_stashed_like_git_stores_them_and_restored_for_the_worktree() -> crate::Result {
+    let (mut repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    repo.config_snapshot_mut()
+        .set_value(&gix::config::tree::Core::AUTO_CRLF, "true")?;
+    std::fs::write(repo.work_dir().expect("non-bare").join("a"), "a\r\nfiltered\r\n")?;
+
+    let stash = repo.autostash(Operation::Rebase, Some(true))?.expect("changes");
+    let tree = repo.find_commit(stash.id())?.tree()?;
+    let a = tree.find_entry("a").expect("present").object()?;
+    assert_eq!(a.data.as_bstr(), "a\nfiltered\n", "the clean conversion is applied");
+    assert_eq!(
+        file(&repo, "a").as_deref(),
+        Some("a\r\n"),
+        "the smudge conversion is applied"
+    );
+
+    assert_eq!(stash.apply()?, Reapplied::Applied);
+    assert_eq!(
+        file(&repo, "a").as_deref(),
+        Some("a\r\nfiltered\r\n"),
+        "the file round-trips"
+    );
+    assert!(
+        repo.stash_create(None)?.is_some(),
+        "the other changes are restored as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn untracked_files_can_be_stashed_and_restored() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    let workdir = repo.work_dir().expect("non-bare").to_owned();
+    let _ = repo.autostash(Operation::Rebase, Some(true))?.expect("changes").id();
+    std::fs::create_dir(workdir.join("dir"))?;
+    std::fs::write(workdir.join("dir").join("untracked"), "untracked\n")?;
+    assert!(
+        repo.stash_create(None)?.is_none(),
+        "untracked files aren't stashed by default"
+    );
+
+    let id = repo
+        .stash_create_opts(
+            None,
+            gix::stash::create::Options {
+                include_untracked: true,
+            },
+        )?
+        .expect("untracked files are changes");
+    let parents: Vec<_> = repo.find_commit(id)?.parent_ids().collect();
+    assert_eq!(parents.len(), 3, "untracked files are stored in the third parent");
+    let untracked = repo.find_commit(parents[2])?;
+    assert_eq!(untracked.parent_ids().count(), 0);
+    assert_eq!(
+        untracked.message_raw()?,
+        format!("untracked files on main: {} initial\n", parents[0].shorten_or_id())
+    );
+    assert_eq!(
+        untracked
+            .tree()?
+            .peel_to_entry_by_path("dir/untracked")?
+            .expect("present")
+            .object()?
+            .data
+            .as_bstr(),
+        "untracked\n"
+    );
+    assert_eq!(
+        file(&repo, "dir/untracked").as_deref(),
+        Some("untracked\n"),
+        "nothing is changed"
+    );
+
+    assert!(matches!(
+        repo.stash_apply(id),
+        Err(gix::stash::Error::UntrackedExists { path }) if path == "dir/untracked"
+    ));
+    std::fs::remove_dir_all(workdir.join("dir"))?;
+    repo.stash_apply(id)?;
+    assert_eq!(file(&repo, "dir/untracked").as_deref(), Some("untracked\n"));
+    assert!(
+        repo.open_index()?.entry_by_path("dir/untracked".into()).is_none(),
+        "untracked files remain untracked"
+    );
+    Ok(())
+}
+
+#[test]
+fn commits_without_parents_are_not_stashes() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_stash_repo.sh")?;
+    let id = repo.head_id()?.detach();
+    assert!(matches!(
+        repo.stash_apply(id),
+        Err(gix::stash::Error::NotAStash { id: actual }) if actual == id
+    ));
+    Ok(())
+}
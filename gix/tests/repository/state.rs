@@ -77,3 +77,52 @@ fn revert_sequence() -> Result {
 
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "index")]
+fn signature_tracks_head_index_and_packed_refs() -> Result {
+    use gix::state::Version;
+
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let head_id = repo.head_id()?.detach();
+    let signature = repo.state_signature()?;
+    assert_eq!(
+        signature.head_name.as_ref().map(gix::refs::FullName::as_bstr),
+        Some("refs/heads/main".into())
+    );
+    assert_eq!(signature.head_id, Some(head_id));
+    let mut index = repo.open_index()?;
+    assert_eq!(
+        signature.index,
+        Some(Version::Checksum(index.checksum().expect("read from disk"))),
+        "the checksum is read from the end of the index file"
+    );
+    assert_eq!(signature.packed_refs, None, "there are no packed refs yet");
+    assert_eq!(repo.state_signature()?, signature, "nothing changed");
+
+    index.remove_entries(|_, _, _| true);
+    index.write(Default::default())?;
+    let changed = repo.state_signature()?;
+    assert_ne!(changed.index, signature.index, "the index content changed");
+    assert_eq!(
+        changed.index,
+        Some(Version::Checksum(index.checksum().expect("set when written")))
+    );
+    assert_eq!(changed.head_id, signature.head_id);
+
+    let parent_id = repo.head_commit()?.parent_ids().next().expect("a parent").detach();
+    std::fs::write(repo.path().join("refs/heads/main"), format!("{parent_id}\n"))?;
+    let changed = repo.state_signature()?;
+    assert_eq!(changed.head_name, signature.head_name);
+    assert_eq!(changed.head_id, Some(parent_id), "the branch HEAD points to is tracked");
+
+    std::fs::write(
+        repo.path().join("packed-refs"),
+        "# pack-refs with: peeled fully-peeled sorted \n",
+    )?;
+    assert!(matches!(
+        repo.state_signature()?.packed_refs,
+        Some(Version::Stamp { len: 46, .. })
+    ));
+    Ok(())
+}
@@ -336,3 +336,38 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         );
     }
 }
+
+#[cfg(feature = "index")]
+mod sparse_checkout_cone {
+    use gix::{config::tree::Core, index::sparse::Cone};
+
+    #[test]
+    fn is_only_available_if_enabled_and_in_cone_mode() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut repo = gix::init(tmp.path())?;
+        assert_eq!(repo.sparse_checkout_cone()?, None, "disabled by default");
+
+        let info_dir = repo.git_dir().join("info");
+        std::fs::create_dir_all(&info_dir)?;
+        std::fs::write(info_dir.join("sparse-checkout"), "/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n")?;
+        repo.config_snapshot_mut()
+            .set_raw_value(&Core::SPARSE_CHECKOUT, "true")?;
+        assert_eq!(
+            repo.sparse_checkout_cone()?,
+            None,
+            "without cone mode, the patterns are like the ones in .gitignore"
+        );
+
+        repo.config_snapshot_mut()
+            .set_raw_value(&Core::SPARSE_CHECKOUT_CONE, "true")?;
+        assert_eq!(repo.sparse_checkout_cone()?, Some(Cone::from_directories(["a/b"])));
+
+        std::fs::write(info_dir.join("sparse-checkout"), "/*\n!/*/\n*.txt\n")?;
+        assert_eq!(
+            repo.sparse_checkout_cone()?,
+            None,
+            "like git, patterns that aren't in cone mode disable cone mode"
+        );
+        Ok(())
+    }
+}
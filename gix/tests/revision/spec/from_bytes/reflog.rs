@@ -1,7 +1,4 @@
-use gix::{
-    prelude::ObjectIdExt,
-    revision::{spec::parse::Error, Spec},
-};
+use gix::{prelude::ObjectIdExt, revision::Spec};
 
 use crate::{
     revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, repo},
@@ -78,10 +75,29 @@ fn by_index() {
 }
 
 #[test]
-fn by_date_is_planned_until_git_date_crate_is_implements_parsing() {
-    let repo = repo("complex_graph").unwrap();
-    assert!(matches!(
-        parse_spec_no_baseline("main@{1979-02-26 18:30:00}", &repo).unwrap_err(),
-        Error::Planned { .. }
-    ));
+fn by_date() {
+    let repo = &repo("complex_graph").unwrap();
+    for (spec, expected) in [
+        (
+            "main@{2005-04-07 15:16:40 -0700}",
+            "a8fbd8e0c8753ef07ccdbfa93da030d53b68f0bc",
+        ),
+        (
+            "main@{2100-01-01 00:00:00 +0000}",
+            "55e825ebe8fd2ff78cad3826afb696b96b576a7e",
+        ),
+        (
+            "@{2005-04-07 15:21:00 -0700}",
+            "5b3f9e24965d0b28780b7ce5daf2b5b7f7e0459f",
+        ),
+    ] {
+        let parsed = parse_spec_no_baseline(spec, repo).unwrap_or_else(|err| panic!("{spec}: {err}"));
+        assert_eq!(parsed, Spec::from_id(hex_to_id(expected).attach(repo)), "{spec}");
+    }
+
+    assert_eq!(
+        parse_spec_no_baseline("main@{1979-02-26 18:30:00}", repo).unwrap(),
+        Spec::from_id(hex_to_id("9f9eac6bd1cd4b4cc6a494f044b28c985a22972b").attach(repo)),
+        "dates before the first entry resolve to the oldest known state, like git does"
+    );
 }
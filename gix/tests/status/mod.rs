@@ -112,6 +112,81 @@ mod index_worktree {
             Ok(())
         }
 
+        #[test]
+        fn untracked_and_ignored_files_modes() -> crate::Result {
+            use gix::status::{Ignored, UntrackedFiles};
+            use gix_dir::entry::Status::{Ignored as IgnoredStatus, Untracked};
+            type PathAndStatus = (gix::bstr::BString, gix_dir::entry::Status);
+
+            let repo = repo("untracked-and-ignored")?;
+            let paths_and_status = |untracked_files: UntrackedFiles,
+                                    ignored: Ignored|
+             -> crate::Result<Vec<PathAndStatus>> {
+                Ok(repo
+                    .status(gix::progress::Discard)?
+                    .untracked_files(untracked_files)
+                    .ignored(ignored)
+                    .index_worktree_options_mut(|opts| {
+                        opts.sorting =
+                            Some(gix::status::plumbing::index_as_worktree_with_renames::Sorting::ByPathCaseSensitive);
+                    })
+                    .into_index_worktree_iter(Vec::new())?
+                    .filter_map(Result::ok)
+                    .map(|item| match item {
+                        Item::DirectoryContents { entry, .. } => (entry.rela_path, entry.status),
+                        _ => unreachable!("there are only untracked and ignored files"),
+                    })
+                    .collect())
+            };
+
+            let ignored_file = IgnoredStatus(gix_ignore::Kind::Expendable);
+            assert_eq!(
+                paths_and_status(UntrackedFiles::Collapsed, Ignored::None)?,
+                [("new".into(), Untracked)],
+                "ignored files aren't shown by default"
+            );
+            assert_eq!(
+                paths_and_status(UntrackedFiles::Files, Ignored::None)?,
+                [("new/untracked".into(), Untracked)]
+            );
+            assert_eq!(
+                paths_and_status(UntrackedFiles::Collapsed, Ignored::Collapsed)?,
+                [
+                    ("build".into(), ignored_file),
+                    ("new".into(), Untracked),
+                    ("objs".into(), ignored_file),
+                    ("tracked.o".into(), ignored_file),
+                ],
+                "directories with only ignored files are collapsed, and ignored files in untracked directories are hidden"
+            );
+            assert_eq!(
+                paths_and_status(UntrackedFiles::Collapsed, Ignored::Matching)?,
+                [
+                    ("build".into(), ignored_file),
+                    ("new".into(), Untracked),
+                    ("objs/a.o".into(), ignored_file),
+                    ("tracked.o".into(), ignored_file),
+                ],
+                "only directories that match an ignore pattern themselves are shown as directory"
+            );
+            assert_eq!(
+                paths_and_status(UntrackedFiles::Files, Ignored::Matching)?,
+                [
+                    ("build".into(), ignored_file),
+                    ("new/ignored.o".into(), ignored_file),
+                    ("new/untracked".into(), Untracked),
+                    ("objs/a.o".into(), ignored_file),
+                    ("tracked.o".into(), ignored_file),
+                ]
+            );
+            assert_eq!(
+                paths_and_status(UntrackedFiles::None, Ignored::Matching)?,
+                [],
+                "without directory walk, there are no ignored files either"
+            );
+            Ok(())
+        }
+
         #[test]
         fn early_drop_for_is_dirty_emulation() -> crate::Result {
             let repo = submodule_repo("modified-untracked-and-submodule-head-changed-and-modified")?;
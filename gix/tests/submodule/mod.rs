@@ -154,6 +154,9 @@ mod open {
                 Some(Vec::new()),
                 "the status check ran, but there were no changes"
             );
+            assert!(status.has_new_commits());
+            assert!(!status.has_modified_content());
+            assert!(!status.has_untracked_content());
             // make it easier to compare this as baseline
             status.changes.take();
 
@@ -214,6 +217,9 @@ mod open {
                 2,
                 "1 modified, 1 untracked"
             );
+            assert!(!status.has_new_commits());
+            assert!(status.has_modified_content());
+            assert!(status.has_untracked_content());
 
             let status_with_dirty_check = sm.status_opts(
                 gix::submodule::config::Ignore::None,
@@ -237,6 +243,11 @@ mod open {
             );
             assert_eq!(status.index_id, status.checked_out_head_id, "the head didn't change");
             assert_eq!(status.changes.as_ref().into_iter().flatten().count(), 1, "1 modified");
+            assert!(status.has_modified_content());
+            assert!(
+                !status.has_untracked_content(),
+                "untracked files are ignored, so they aren't detected"
+            );
 
             Ok(())
         }
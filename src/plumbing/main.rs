@@ -153,14 +153,473 @@ pub fn main() -> Result<()> {
             },
         ),
         Subcommands::Worktree(crate::plumbing::options::worktree::Platform { cmd }) => match cmd {
-            crate::plumbing::options::worktree::SubCommands::List => prepare_and_run(
+            crate::plumbing::options::worktree::SubCommands::List { porcelain } => prepare_and_run(
                 "worktree-list",
                 trace,
                 verbose,
                 progress,
                 progress_keep_open,
                 None,
-                move |_progress, out, _err| core::repository::worktree::list(repository(Mode::Lenient)?, out, format),
+                move |_progress, out, _err| {
+                    core::repository::worktree::list(repository(Mode::Lenient)?, out, format, porcelain)
+                },
+            ),
+            crate::plumbing::options::worktree::SubCommands::Add {
+                new_branch,
+                detach,
+                force,
+                lock,
+                reason,
+                path,
+                commit_ish,
+            } => prepare_and_run(
+                "worktree-add",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::worktree::add(
+                        repository(Mode::Strict)?,
+                        &path,
+                        commit_ish.as_deref(),
+                        out,
+                        &gix::interrupt::IS_INTERRUPTED,
+                        core::repository::worktree::add::Options {
+                            new_branch,
+                            detach,
+                            force,
+                            lock: lock.then_some(reason),
+                        },
+                    )
+                },
+            ),
+            crate::plumbing::options::worktree::SubCommands::Lock { reason, worktree } => prepare_and_run(
+                "worktree-lock",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| {
+                    core::repository::worktree::lock(repository(Mode::Lenient)?, &worktree, reason.as_deref())
+                },
+            ),
+            crate::plumbing::options::worktree::SubCommands::Unlock { worktree } => prepare_and_run(
+                "worktree-unlock",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| core::repository::worktree::unlock(repository(Mode::Lenient)?, &worktree),
+            ),
+            crate::plumbing::options::worktree::SubCommands::Move { worktree, new_path } => prepare_and_run(
+                "worktree-move",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| {
+                    core::repository::worktree::move_to(repository(Mode::Lenient)?, &worktree, &new_path)
+                },
+            ),
+            crate::plumbing::options::worktree::SubCommands::Remove { force, worktree } => prepare_and_run(
+                "worktree-remove",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| {
+                    core::repository::worktree::remove(repository(Mode::Lenient)?, &worktree, force)
+                },
+            ),
+            crate::plumbing::options::worktree::SubCommands::Prune { dry_run } => prepare_and_run(
+                "worktree-prune",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::worktree::prune(
+                        repository(Mode::Lenient)?,
+                        out,
+                        core::repository::worktree::prune::Options { dry_run, verbose },
+                    )
+                },
+            ),
+        },
+        Subcommands::Branch(crate::plumbing::options::branch::Platform { cmd }) => match cmd {
+            crate::plumbing::options::branch::Subcommands::List {
+                all,
+                contains,
+                merged,
+                verbose: branch_verbose,
+            } => prepare_and_run(
+                "branch-list",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::branch::list(
+                        repository(Mode::Lenient)?,
+                        out,
+                        format,
+                        core::repository::branch::list::Options {
+                            all,
+                            contains,
+                            merged,
+                            verbose: branch_verbose,
+                        },
+                    )
+                },
+            ),
+            crate::plumbing::options::branch::Subcommands::Create {
+                force,
+                name,
+                start_point,
+            } => prepare_and_run(
+                "branch-create",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| {
+                    core::repository::branch::create(
+                        repository(Mode::Strict)?,
+                        &name,
+                        start_point.as_deref(),
+                        core::repository::branch::create::Options { force },
+                    )
+                },
+            ),
+            crate::plumbing::options::branch::Subcommands::Delete { force, names } => prepare_and_run(
+                "branch-delete",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::branch::delete(
+                        repository(Mode::Strict)?,
+                        &names,
+                        out,
+                        core::repository::branch::delete::Options { force },
+                    )
+                },
+            ),
+            crate::plumbing::options::branch::Subcommands::Rename { force, old_or_new, new } => prepare_and_run(
+                "branch-rename",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| {
+                    let (old, new) = match new {
+                        Some(new) => (Some(old_or_new), new),
+                        None => (None, old_or_new),
+                    };
+                    core::repository::branch::rename(
+                        repository(Mode::Strict)?,
+                        old.as_deref(),
+                        &new,
+                        core::repository::branch::rename::Options { force },
+                    )
+                },
+            ),
+            crate::plumbing::options::branch::Subcommands::Upstream { cmd } => match cmd {
+                crate::plumbing::options::branch::upstream::Subcommands::Set { upstream, branch } => prepare_and_run(
+                    "branch-upstream-set",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, _out, _err| {
+                        core::repository::branch::set_upstream(repository(Mode::Strict)?, branch.as_deref(), &upstream)
+                    },
+                ),
+                crate::plumbing::options::branch::upstream::Subcommands::Unset { branch } => prepare_and_run(
+                    "branch-upstream-unset",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, _out, _err| {
+                        core::repository::branch::unset_upstream(repository(Mode::Strict)?, branch.as_deref())
+                    },
+                ),
+            },
+        },
+        Subcommands::Switch(crate::plumbing::options::checkout::Switch {
+            create,
+            force_create,
+            detach,
+            force,
+            target,
+        }) => prepare_and_run(
+            "switch",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, _out, err| {
+                core::repository::checkout::switch(
+                    repository(Mode::Strict)?,
+                    target.as_deref(),
+                    err,
+                    &gix::interrupt::IS_INTERRUPTED,
+                    core::repository::checkout::switch::Options {
+                        force_new_branch: force_create.is_some(),
+                        new_branch: create.or(force_create),
+                        detach,
+                        force,
+                    },
+                )
+            },
+        ),
+        Subcommands::Checkout(crate::plumbing::options::checkout::Checkout {
+            create,
+            force_create,
+            detach,
+            force,
+            target,
+            paths,
+        }) => prepare_and_run(
+            "checkout",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, _out, err| {
+                core::repository::checkout::checkout(
+                    repository(Mode::Strict)?,
+                    target.as_deref(),
+                    paths,
+                    err,
+                    &gix::interrupt::IS_INTERRUPTED,
+                    core::repository::checkout::switch::Options {
+                        force_new_branch: force_create.is_some(),
+                        new_branch: create.or(force_create),
+                        detach,
+                        force,
+                    },
+                )
+            },
+        ),
+        Subcommands::Merge(crate::plumbing::options::merge::Platform {
+            ff_only,
+            no_ff,
+            abort,
+            message,
+            rev,
+        }) => prepare_and_run(
+            "merge",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::merge::merge(
+                    repository(Mode::Strict)?,
+                    rev.as_deref(),
+                    out,
+                    &gix::interrupt::IS_INTERRUPTED,
+                    core::repository::merge::Options {
+                        ff_only,
+                        no_ff,
+                        abort,
+                        message,
+                    },
+                )
+            },
+        ),
+        Subcommands::Stash(crate::plumbing::options::stash::Platform { cmd }) => {
+            match cmd.unwrap_or(crate::plumbing::options::stash::Subcommands::Push {
+                include_untracked: false,
+                message: None,
+            }) {
+                crate::plumbing::options::stash::Subcommands::Push {
+                    include_untracked,
+                    message,
+                } => prepare_and_run(
+                    "stash-push",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| {
+                        core::repository::stash::push(
+                            repository(Mode::Strict)?,
+                            out,
+                            &gix::interrupt::IS_INTERRUPTED,
+                            core::repository::stash::push::Options {
+                                include_untracked,
+                                message,
+                            },
+                        )
+                    },
+                ),
+                crate::plumbing::options::stash::Subcommands::List => prepare_and_run(
+                    "stash-list",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| core::repository::stash::list(repository(Mode::Lenient)?, out),
+                ),
+                crate::plumbing::options::stash::Subcommands::Show { stash } => prepare_and_run(
+                    "stash-show",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| {
+                        core::repository::stash::show(repository(Mode::Lenient)?, stash.as_deref(), out)
+                    },
+                ),
+                crate::plumbing::options::stash::Subcommands::Apply { stash } => prepare_and_run(
+                    "stash-apply",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| {
+                        core::repository::stash::apply(repository(Mode::Strict)?, stash.as_deref(), out)
+                    },
+                ),
+                crate::plumbing::options::stash::Subcommands::Pop { stash } => prepare_and_run(
+                    "stash-pop",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| {
+                        core::repository::stash::pop(repository(Mode::Strict)?, stash.as_deref(), out)
+                    },
+                ),
+                crate::plumbing::options::stash::Subcommands::Drop { stash } => prepare_and_run(
+                    "stash-drop",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| {
+                        core::repository::stash::drop(repository(Mode::Strict)?, stash.as_deref(), out)
+                    },
+                ),
+            }
+        }
+        Subcommands::Show(crate::plumbing::options::show::Platform {
+            oneline,
+            no_patch,
+            stat,
+            spec,
+        }) => prepare_and_run(
+            "show",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::show::show(
+                    repository(Mode::Lenient)?,
+                    &spec,
+                    out,
+                    core::repository::show::Options {
+                        oneline,
+                        no_patch,
+                        stat,
+                    },
+                )
+            },
+        ),
+        Subcommands::Tag(crate::plumbing::options::tag::Platform { cmd }) => match cmd {
+            crate::plumbing::options::tag::Subcommands::List { sort, patterns } => prepare_and_run(
+                "tag-list",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::tag::list(
+                        repository(Mode::Lenient)?,
+                        out,
+                        format,
+                        core::repository::tag::list::Options { patterns, sort },
+                    )
+                },
+            ),
+            crate::plumbing::options::tag::Subcommands::Create {
+                annotate,
+                sign,
+                local_user,
+                message,
+                file,
+                force,
+                name,
+                target,
+            } => prepare_and_run(
+                "tag-create",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| {
+                    core::repository::tag::create(
+                        repository(Mode::Strict)?,
+                        &name,
+                        target.as_deref(),
+                        core::repository::tag::create::Options {
+                            message,
+                            file,
+                            annotate,
+                            sign,
+                            local_user,
+                            force,
+                        },
+                    )
+                },
+            ),
+            crate::plumbing::options::tag::Subcommands::Delete { names } => prepare_and_run(
+                "tag-delete",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::tag::delete(repository(Mode::Strict)?, &names, out),
+            ),
+            crate::plumbing::options::tag::Subcommands::Verify { names } => prepare_and_run(
+                "tag-verify",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| core::repository::tag::verify(repository(Mode::Lenient)?, &names),
             ),
         },
         Subcommands::IsClean | Subcommands::IsChanged => {
@@ -297,6 +756,67 @@ pub fn main() -> Result<()> {
                     )
                 },
             ),
+            crate::plumbing::options::submodule::Subcommands::Status { recursive, paths } => prepare_and_run(
+                "submodule-status",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::submodule::status(
+                        repository(Mode::Lenient)?,
+                        &paths,
+                        out,
+                        format,
+                        core::repository::submodule::status::Options { recursive },
+                    )
+                },
+            ),
+            crate::plumbing::options::submodule::Subcommands::Init { paths } => prepare_and_run(
+                "submodule-init",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::submodule::init(repository(Mode::Lenient)?, &paths, out),
+            ),
+            #[cfg(feature = "gitoxide-core-blocking-client")]
+            crate::plumbing::options::submodule::Subcommands::Update {
+                init,
+                recursive,
+                jobs,
+                paths,
+            } => prepare_and_run(
+                "submodule-update",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::submodule::update::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::submodule::update(
+                        repository(Mode::Lenient)?,
+                        &paths,
+                        progress,
+                        out,
+                        &gix::interrupt::IS_INTERRUPTED,
+                        core::repository::submodule::update::Options { init, recursive, jobs },
+                    )
+                },
+            ),
+            crate::plumbing::options::submodule::Subcommands::Foreach { recursive, command } => prepare_and_run(
+                "submodule-foreach",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::submodule::foreach(repository(Mode::Lenient)?, command, recursive, out)
+                },
+            ),
         },
         #[cfg(feature = "gitoxide-core-tools-archive")]
         Subcommands::Archive(crate::plumbing::options::archive::Platform {
@@ -445,6 +965,72 @@ pub fn main() -> Result<()> {
             )
         }
         #[cfg(feature = "gitoxide-core-blocking-client")]
+        Subcommands::Serve(crate::plumbing::options::serve::Platform {
+            base_path,
+            export_all,
+            enable_receive_pack,
+            listen,
+            stdio,
+            http,
+        }) => {
+            let opts = core::serve::Options {
+                base_path,
+                export_all,
+                enable_receive_pack,
+            };
+            if stdio {
+                // Standard output carries the protocol, so it must not be used for anything else.
+                prepare_and_run("serve", trace, false, false, false, None, move |progress, out, _err| {
+                    core::serve::stdio(out, progress, &gix::interrupt::IS_INTERRUPTED, opts)
+                })
+            } else {
+                prepare_and_run(
+                    "serve",
+                    trace,
+                    auto_verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |progress, _out, _err| {
+                        if http {
+                            let listen = listen.as_deref().unwrap_or(core::serve::http::DEFAULT_LISTEN_ADDRESS);
+                            core::serve::http::tcp(listen, progress, &gix::interrupt::IS_INTERRUPTED, opts)
+                        } else {
+                            let listen = listen.as_deref().unwrap_or(core::serve::DEFAULT_LISTEN_ADDRESS);
+                            core::serve::tcp(listen, progress, &gix::interrupt::IS_INTERRUPTED, opts)
+                        }
+                    },
+                )
+            }
+        }
+        #[cfg(feature = "gitoxide-core-blocking-client")]
+        Subcommands::RemoteHelper(crate::plumbing::options::remote_helper::Platform { remote: _, bundle_path }) => {
+            // `git` tells remote helpers which repository they are working for.
+            let repo = match std::env::var_os("GIT_DIR") {
+                Some(git_dir) => gix::open(git_dir)?,
+                None => repository(Mode::Lenient)?,
+            };
+            // Standard output carries the protocol, so it must not be used for anything else.
+            prepare_and_run(
+                "remote-helper",
+                trace,
+                false,
+                false,
+                false,
+                None,
+                move |mut progress, out, _err| {
+                    core::repository::remote_helper(
+                        repo,
+                        &mut std::io::stdin().lock(),
+                        &mut { out },
+                        &mut progress,
+                        &gix::interrupt::IS_INTERRUPTED,
+                        core::repository::remote_helper::Options { bundle_path },
+                    )
+                },
+            )
+        }
+        #[cfg(feature = "gitoxide-core-blocking-client")]
         Subcommands::Fetch(crate::plumbing::options::fetch::Platform {
             dry_run,
             handshake_info,
@@ -476,6 +1062,51 @@ pub fn main() -> Result<()> {
                 },
             )
         }
+        #[cfg(feature = "gitoxide-core-blocking-client")]
+        Subcommands::Mirror(crate::plumbing::options::mirror::Platform {
+            dry_run,
+            prune,
+            push_to,
+            limit_rate,
+            remote,
+        }) => {
+            let opts = core::repository::mirror::Options {
+                format,
+                dry_run,
+                remote,
+                prune,
+                push_to,
+                max_bytes_per_second: limit_rate,
+            };
+            prepare_and_run(
+                "mirror",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::mirror::PROGRESS_RANGE,
+                move |progress, out, err| {
+                    core::repository::mirror(repository(Mode::LenientWithGitInstallConfig)?, progress, out, err, opts)
+                },
+            )
+        }
+        Subcommands::Analyze(crate::plumbing::options::analyze::Platform { limit }) => prepare_and_run(
+            "analyze",
+            trace,
+            auto_verbose,
+            progress,
+            progress_keep_open,
+            core::repository::analyze::PROGRESS_RANGE,
+            move |progress, out, err| {
+                core::repository::analyze(
+                    repository(Mode::Strict)?,
+                    progress,
+                    out,
+                    err,
+                    core::repository::analyze::Options { format, limit },
+                )
+            },
+        ),
         Subcommands::ConfigTree => show_progress(),
         Subcommands::Credential(cmd) => core::repository::credential(
             repository(Mode::StrictWithGitInstallConfig)?,
@@ -503,6 +1134,7 @@ pub fn main() -> Result<()> {
                             ref_specs: ref_spec,
                             show_unmapped_remote_refs,
                         },
+                        _ => unreachable!("BUG: other subcommands are handled separately"),
                     };
                     let context = core::repository::remote::refs::Options {
                         name_or_url: name,
@@ -548,6 +1180,142 @@ pub fn main() -> Result<()> {
                         ))
                     }
                 }
+                remote::Subcommands::Show => {
+                    #[cfg(feature = "gitoxide-core-blocking-client")]
+                    {
+                        prepare_and_run(
+                            "remote-show",
+                            trace,
+                            auto_verbose,
+                            progress,
+                            progress_keep_open,
+                            core::repository::remote::show::PROGRESS_RANGE,
+                            move |progress, out, _err| {
+                                core::repository::remote::show(
+                                    repository(Mode::LenientWithGitInstallConfig)?,
+                                    name.as_deref(),
+                                    progress,
+                                    out,
+                                )
+                            },
+                        )
+                    }
+                    #[cfg(feature = "gitoxide-core-async-client")]
+                    {
+                        let (_handle, progress) = async_util::prepare(
+                            auto_verbose,
+                            trace,
+                            "remote-show",
+                            Some(core::repository::remote::show::PROGRESS_RANGE),
+                        );
+                        futures_lite::future::block_on(core::repository::remote::show(
+                            repository(Mode::LenientWithGitInstallConfig)?,
+                            name.as_deref(),
+                            progress,
+                            std::io::stdout(),
+                        ))
+                    }
+                }
+                remote::Subcommands::Prune { dry_run } => {
+                    let opts = core::repository::remote::prune::Options { dry_run };
+                    #[cfg(feature = "gitoxide-core-blocking-client")]
+                    {
+                        prepare_and_run(
+                            "remote-prune",
+                            trace,
+                            auto_verbose,
+                            progress,
+                            progress_keep_open,
+                            core::repository::remote::prune::PROGRESS_RANGE,
+                            move |progress, out, _err| {
+                                core::repository::remote::prune(
+                                    repository(Mode::LenientWithGitInstallConfig)?,
+                                    name.as_deref(),
+                                    progress,
+                                    out,
+                                    opts,
+                                )
+                            },
+                        )
+                    }
+                    #[cfg(feature = "gitoxide-core-async-client")]
+                    {
+                        let (_handle, progress) = async_util::prepare(
+                            auto_verbose,
+                            trace,
+                            "remote-prune",
+                            Some(core::repository::remote::prune::PROGRESS_RANGE),
+                        );
+                        futures_lite::future::block_on(core::repository::remote::prune(
+                            repository(Mode::LenientWithGitInstallConfig)?,
+                            name.as_deref(),
+                            progress,
+                            std::io::stdout(),
+                            opts,
+                        ))
+                    }
+                }
+                remote::Subcommands::Add {
+                    track,
+                    tags,
+                    no_tags,
+                    remote,
+                    url,
+                } => prepare_and_run(
+                    "remote-add",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, _out, _err| {
+                        core::repository::remote::add(
+                            repository(Mode::Lenient)?,
+                            &remote,
+                            &url,
+                            core::repository::remote::add::Options {
+                                track,
+                                tags: (tags || no_tags).then_some(tags),
+                            },
+                        )
+                    },
+                ),
+                remote::Subcommands::Remove { remote } => prepare_and_run(
+                    "remote-remove",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, _out, _err| core::repository::remote::remove(repository(Mode::Lenient)?, &remote),
+                ),
+                remote::Subcommands::Rename { old, new } => prepare_and_run(
+                    "remote-rename",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, _out, _err| {
+                        core::repository::remote::rename(repository(Mode::Lenient)?, &old, &new)
+                    },
+                ),
+                remote::Subcommands::SetUrl { push, add, remote, url } => prepare_and_run(
+                    "remote-set-url",
+                    trace,
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, _out, _err| {
+                        core::repository::remote::set_url(
+                            repository(Mode::Lenient)?,
+                            &remote,
+                            &url,
+                            core::repository::remote::set_url::Options { push, add },
+                        )
+                    },
+                ),
             }
         }
         Subcommands::Config(config::Platform { filter }) => prepare_and_run(
@@ -987,6 +1755,35 @@ pub fn main() -> Result<()> {
                 )
             },
         ),
+        Subcommands::RevParse(crate::plumbing::options::revision::RevParse {
+            verify,
+            abbrev_ref,
+            symbolic_full_name,
+            show_toplevel,
+            git_dir,
+            specs,
+        }) => prepare_and_run(
+            "rev-parse",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::revision::rev_parse(
+                    repository(Mode::Strict)?,
+                    specs,
+                    out,
+                    core::repository::revision::rev_parse::Options {
+                        verify,
+                        abbrev_ref,
+                        symbolic_full_name,
+                        show_toplevel,
+                        git_dir,
+                    },
+                )
+            },
+        ),
         Subcommands::Revision(cmd) => match cmd {
             revision::Subcommands::List { spec, svg, limit } => prepare_and_run(
                 "revision-list",
@@ -1271,6 +2068,23 @@ pub fn main() -> Result<()> {
                     )
                 },
             ),
+            attributes::Subcommands::CheckEol { pathspec } => prepare_and_run(
+                "attributes-check-eol",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, err| {
+                    core::repository::attributes::check_eol(
+                        repository(Mode::Strict)?,
+                        pathspec,
+                        out,
+                        err,
+                        core::repository::attributes::check_eol::Options { format },
+                    )
+                },
+            ),
             attributes::Subcommands::ValidateBaseline { statistics, no_ignore } => prepare_and_run(
                 "attributes-validate-baseline",
                 trace,
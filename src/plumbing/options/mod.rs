@@ -89,6 +89,8 @@ pub enum Subcommands {
     /// Interact with the object database.
     #[clap(subcommand)]
     Odb(odb::Subcommands),
+    /// Find the paths and objects that contribute most to the size of the repository, and show how it grew over time.
+    Analyze(analyze::Platform),
     /// Check for missing objects.
     Fsck(fsck::Platform),
     /// Interact with tree objects.
@@ -105,6 +107,8 @@ pub enum Subcommands {
     /// Query and obtain information about revisions.
     #[clap(subcommand)]
     Revision(revision::Subcommands),
+    /// Resolve revisions to the objects they refer to, or print information about the repository, like `git rev-parse`.
+    RevParse(revision::RevParse),
     /// A program just like `git credential`.
     #[clap(subcommand)]
     Credential(credential::Subcommands),
@@ -113,6 +117,19 @@ pub enum Subcommands {
     Fetch(fetch::Platform),
     #[cfg(feature = "gitoxide-core-blocking-client")]
     Clone(clone::Platform),
+    /// Make all references match those of a remote, and optionally push them all to another remote, like
+    /// `git fetch --mirror` and `git push --mirror` do.
+    #[cfg(feature = "gitoxide-core-blocking-client")]
+    Mirror(mirror::Platform),
+    /// Serve repositories to `git` clients over the `git://` protocol like `git daemon`, or over smart HTTP.
+    #[cfg(feature = "gitoxide-core-blocking-client")]
+    Serve(serve::Platform),
+    /// Act as remote helper for `git` with a bundle file as remote, like `git remote-<transport>` programs do.
+    ///
+    /// Install a `git-remote-gix` program that runs `gix remote-helper "$@"` to `fetch`, `clone` and `push`
+    /// with urls like `gix::path/to/file.bundle`.
+    #[cfg(feature = "gitoxide-core-blocking-client")]
+    RemoteHelper(remote_helper::Platform),
     /// Interact with the mailmap.
     #[clap(subcommand)]
     Mailmap(mailmap::Subcommands),
@@ -140,6 +157,17 @@ pub enum Subcommands {
     Corpus(corpus::Platform),
     MergeBase(merge_base::Command),
     Worktree(worktree::Platform),
+    Branch(branch::Platform),
+    Tag(tag::Platform),
+    /// Switch to a branch, or to a commit with a detached `HEAD`, updating the index and the worktree.
+    Switch(checkout::Switch),
+    /// Switch to a branch or revision, or restore paths from a revision or the index.
+    Checkout(checkout::Checkout),
+    /// Merge a revision into `HEAD`, or abort the merge in progress.
+    Merge(merge::Platform),
+    Stash(stash::Platform),
+    /// Show commits with the changes they introduce, tags with the object they point to, trees and blobs.
+    Show(show::Platform),
     /// Subcommands that need no git repository to run.
     #[clap(subcommand)]
     Free(free::Subcommands),
@@ -281,6 +309,8 @@ pub mod merge_base {
 }
 
 pub mod worktree {
+    use std::path::PathBuf;
+
     #[derive(Debug, clap::Parser)]
     #[command(about = "Commands for handling worktrees")]
     pub struct Platform {
@@ -291,7 +321,332 @@ pub mod worktree {
     #[derive(Debug, clap::Subcommand)]
     pub enum SubCommands {
         /// List all worktrees, along with some accompanying information
+        List {
+            /// Print one attribute per line in the stable format of `git worktree list --porcelain`.
+            #[clap(long)]
+            porcelain: bool,
+        },
+        /// Create a new linked worktree at the given path and check out a branch or commit into it.
+        Add {
+            /// Create a new branch with this name and check it out in the new worktree.
+            #[clap(short = 'b', value_name = "NEW-BRANCH", conflicts_with = "detach")]
+            new_branch: Option<String>,
+            /// Detach `HEAD` in the new worktree, even if a branch is given.
+            #[clap(long, short = 'd')]
+            detach: bool,
+            /// Check out the branch even if it's already checked out in another worktree.
+            #[clap(long, short = 'f')]
+            force: bool,
+            /// Keep the new worktree locked after creation.
+            #[clap(long)]
+            lock: bool,
+            /// The reason for locking the new worktree.
+            #[clap(long, requires = "lock")]
+            reason: Option<String>,
+            /// The directory to create the worktree in.
+            path: PathBuf,
+            /// The branch or revision to check out, defaulting to a new branch named after the directory.
+            commit_ish: Option<String>,
+        },
+        /// Lock a linked worktree to prevent it from being moved, removed or pruned.
+        Lock {
+            /// The reason for locking the worktree.
+            #[clap(long)]
+            reason: Option<String>,
+            /// The path or name of the linked worktree.
+            worktree: PathBuf,
+        },
+        /// Unlock a linked worktree.
+        Unlock {
+            /// The path or name of the linked worktree.
+            worktree: PathBuf,
+        },
+        /// Move a linked worktree to a new location.
+        Move {
+            /// The path or name of the linked worktree.
+            worktree: PathBuf,
+            /// The new location, or the directory to move the worktree into if it exists.
+            new_path: PathBuf,
+        },
+        /// Remove a linked worktree and its administrative files.
+        Remove {
+            /// Remove a worktree with local modifications, and pass twice to remove a locked worktree.
+            #[clap(long, short = 'f', action = clap::ArgAction::Count)]
+            force: u8,
+            /// The path or name of the linked worktree.
+            worktree: PathBuf,
+        },
+        /// Remove the administrative files of worktrees whose directory doesn't exist anymore.
+        Prune {
+            /// Only print what would be removed.
+            #[clap(long, short = 'n')]
+            dry_run: bool,
+        },
+    }
+}
+
+pub mod branch {
+    #[derive(Debug, clap::Parser)]
+    #[command(about = "Commands for handling branches")]
+    pub struct Platform {
+        #[clap(subcommand)]
+        pub cmd: Subcommands,
+    }
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// List all local branches, optionally limited to those that contain or are merged into a revision.
+        List {
+            /// Also list remote-tracking branches.
+            #[clap(long, short = 'a')]
+            all: bool,
+            /// Only list branches that contain the commit of the given revspec.
+            #[clap(long, value_name = "REV")]
+            contains: Option<String>,
+            /// Only list branches whose commits are reachable from the given revspec.
+            #[clap(long, value_name = "REV")]
+            merged: Option<String>,
+            /// Show the commit of each branch, and if given twice, also its upstream branch and how far it is ahead or behind.
+            #[clap(long, short = 'v', action = clap::ArgAction::Count)]
+            verbose: u8,
+        },
+        /// Create a new branch.
+        Create {
+            /// Reset the branch to the start point if it already exists.
+            #[clap(long, short = 'f')]
+            force: bool,
+            /// The name of the branch to create.
+            name: String,
+            /// The revspec of the commit the branch should point to, defaulting to `HEAD`.
+            start_point: Option<String>,
+        },
+        /// Delete branches, which by default have to be fully merged into their upstream branch or `HEAD`.
+        Delete {
+            /// Delete branches even if they are not fully merged, like `git branch -D`.
+            #[clap(long, short = 'f')]
+            force: bool,
+            /// The names of the branches to delete.
+            #[clap(required = true)]
+            names: Vec<String>,
+        },
+        /// Rename a branch along with its configuration.
+        Rename {
+            /// Overwrite the branch with the new name if it already exists.
+            #[clap(long, short = 'f')]
+            force: bool,
+            /// The branch to rename if `new` is given as well, or the new name of the current branch otherwise.
+            old_or_new: String,
+            /// The new name of the branch.
+            new: Option<String>,
+        },
+        /// Configure or remove the upstream branch of a branch.
+        Upstream {
+            #[clap(subcommand)]
+            cmd: upstream::Subcommands,
+        },
+    }
+
+    pub mod upstream {
+        #[derive(Debug, clap::Subcommand)]
+        pub enum Subcommands {
+            /// Set the upstream of a branch to a remote tracking branch like `origin/main`, or a local branch.
+            Set {
+                /// The upstream branch, like `origin/main`.
+                upstream: String,
+                /// The branch to configure, defaulting to the current branch.
+                branch: Option<String>,
+            },
+            /// Remove the upstream configuration of a branch.
+            Unset {
+                /// The branch to configure, defaulting to the current branch.
+                branch: Option<String>,
+            },
+        }
+    }
+}
+
+pub mod checkout {
+    use gix::bstr::BString;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Switch {
+        /// Create a new branch of the given name at the target commit, defaulting to `HEAD`, and switch to it.
+        #[clap(long, short = 'c', value_name = "NAME", conflicts_with = "force_create")]
+        pub create: Option<String>,
+        /// Like --create, but reset the branch if it already exists.
+        #[clap(long, short = 'C', value_name = "NAME")]
+        pub force_create: Option<String>,
+        /// Switch to the commit of the target with a detached `HEAD`.
+        #[clap(long, short = 'd')]
+        pub detach: bool,
+        /// Discard local modifications and overwrite untracked files that are in the way.
+        #[clap(long, short = 'f')]
+        pub force: bool,
+        /// The branch to switch to, or the commit to start the new branch at.
+        pub target: Option<String>,
+    }
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Checkout {
+        /// Create a new branch of the given name at the target commit, defaulting to `HEAD`, and switch to it.
+        #[clap(short = 'b', value_name = "NAME", conflicts_with = "force_create")]
+        pub create: Option<String>,
+        /// Like -b, but reset the branch if it already exists.
+        #[clap(short = 'B', value_name = "NAME")]
+        pub force_create: Option<String>,
+        /// Switch to the commit of the target with a detached `HEAD`, even if it is a branch.
+        #[clap(long, short = 'd')]
+        pub detach: bool,
+        /// Discard local modifications and overwrite untracked files that are in the way.
+        #[clap(long, short = 'f')]
+        pub force: bool,
+        /// The branch or revision to switch to, or the revision to restore paths from.
+        pub target: Option<String>,
+        /// The paths to restore from `target`, or from the index if there is no target.
+        #[clap(last = true, value_parser = crate::shared::AsBString)]
+        pub paths: Vec<BString>,
+    }
+}
+
+pub mod merge {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Refuse to merge unless `HEAD` can be fast-forwarded to the revision.
+        #[clap(long, conflicts_with_all = ["no_ff", "abort"])]
+        pub ff_only: bool,
+        /// Create a merge commit even if `HEAD` could be fast-forwarded to the revision.
+        #[clap(long, conflicts_with = "abort")]
+        pub no_ff: bool,
+        /// Abort the merge in progress, resetting the index and the worktree to `HEAD`.
+        #[clap(long, conflicts_with = "rev")]
+        pub abort: bool,
+        /// The message of the merge commit.
+        #[clap(long, short = 'm', conflicts_with = "abort")]
+        pub message: Option<String>,
+        /// The revision to merge into `HEAD`.
+        #[clap(required_unless_present = "abort")]
+        pub rev: Option<String>,
+    }
+}
+
+pub mod stash {
+    #[derive(Debug, clap::Parser)]
+    #[command(about = "Commands for stashing local modifications away and restoring them")]
+    pub struct Platform {
+        /// The subcommand to run, defaulting to `push`.
+        #[clap(subcommand)]
+        pub cmd: Option<Subcommands>,
+    }
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Save local modifications as a new stash entry and reset the index and the worktree to `HEAD`.
+        Push {
+            /// Also stash untracked files and remove them from the worktree.
+            #[clap(long, short = 'u')]
+            include_untracked: bool,
+            /// The message to describe the stash entry with.
+            #[clap(long, short = 'm')]
+            message: Option<String>,
+        },
+        /// List all stash entries, newest first.
         List,
+        /// Show the status of each path changed by a stash entry.
+        Show {
+            /// The stash entry to show, like `stash@{1}`, defaulting to the newest one.
+            stash: Option<String>,
+        },
+        /// Apply the changes of a stash entry to the worktree.
+        Apply {
+            /// The stash entry to apply, like `stash@{1}`, defaulting to the newest one.
+            stash: Option<String>,
+        },
+        /// Apply the changes of a stash entry to the worktree and drop it unless there were conflicts.
+        Pop {
+            /// The stash entry to pop, like `stash@{1}`, defaulting to the newest one.
+            stash: Option<String>,
+        },
+        /// Remove a stash entry.
+        Drop {
+            /// The stash entry to drop, like `stash@{1}`, defaulting to the newest one.
+            stash: Option<String>,
+        },
+    }
+}
+
+pub mod show {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Show commits as a single line with their abbreviated id and their subject.
+        #[clap(long)]
+        pub oneline: bool,
+        /// Don't show the changes introduced by commits.
+        #[clap(long, short = 's', conflicts_with = "stat")]
+        pub no_patch: bool,
+        /// Show the amount of changed lines per file instead of the changes themselves.
+        #[clap(long)]
+        pub stat: bool,
+        /// The revision specification of the object to show.
+        #[clap(default_value = "HEAD")]
+        pub spec: String,
+    }
+}
+
+pub mod tag {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Parser)]
+    #[command(about = "Commands for handling tags")]
+    pub struct Platform {
+        #[clap(subcommand)]
+        pub cmd: Subcommands,
+    }
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// List all tags, optionally limited to those matching the given patterns.
+        List {
+            /// The key to sort by, one of `refname`, `version:refname` or `creatordate`, prefixed with `-` to reverse.
+            #[clap(long, value_name = "KEY")]
+            sort: Option<String>,
+            /// Wildmatch patterns that short tag names have to match, like `v1.*`.
+            patterns: Vec<String>,
+        },
+        /// Create a lightweight tag, or an annotated tag if a message is provided or requested.
+        Create {
+            /// Create an annotated tag, opening an editor for the message unless it's provided otherwise.
+            #[clap(long, short = 'a')]
+            annotate: bool,
+            /// Create a signed annotated tag using `gpg.program` or `gpg`.
+            #[clap(long, short = 's')]
+            sign: bool,
+            /// Create a signed annotated tag using the given key.
+            #[clap(long, short = 'u', value_name = "KEY-ID")]
+            local_user: Option<String>,
+            /// The message of the annotated tag.
+            #[clap(long, short = 'm', conflicts_with = "file")]
+            message: Option<String>,
+            /// Read the message of the annotated tag from the given file, or from stdin if it is `-`.
+            #[clap(long, short = 'F')]
+            file: Option<PathBuf>,
+            /// Replace an existing tag of the same name.
+            #[clap(long, short = 'f')]
+            force: bool,
+            /// The name of the tag, like `v1.0`.
+            name: String,
+            /// The revspec of the object to tag, defaulting to `HEAD`.
+            target: Option<String>,
+        },
+        /// Delete the given tags.
+        Delete {
+            #[clap(required = true)]
+            names: Vec<String>,
+        },
+        /// Verify the PGP signature of the given annotated tags.
+        Verify {
+            #[clap(required = true)]
+            names: Vec<String>,
+        },
     }
 }
 
@@ -437,6 +792,82 @@ pub mod fetch {
 }
 
 #[cfg(feature = "gitoxide-core-blocking-client")]
+pub mod mirror {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Don't change the local repository or the push remote, but otherwise try to be as accurate as possible.
+        #[clap(long, short = 'n')]
+        pub dry_run: bool,
+
+        /// Delete local references that don't exist on the remote anymore.
+        #[clap(long, short = 'p')]
+        pub prune: bool,
+
+        /// The name or url of a remote to push all references to after fetching, deleting those that don't exist locally.
+        #[clap(long, value_name = "REMOTE")]
+        pub push_to: Option<String>,
+
+        /// Limit the rate at which data is transferred to the given amount of bytes per second.
+        #[clap(long, value_name = "BYTES")]
+        pub limit_rate: Option<u64>,
+
+        /// The name of the remote to mirror, or the url of the remote to connect to directly.
+        ///
+        /// If unset, the current branch will determine the remote.
+        pub remote: Option<String>,
+    }
+}
+
+#[cfg(feature = "gitoxide-core-blocking-client")]
+pub mod serve {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The directory containing the repositories to serve, with all requested paths being relative to it.
+        #[clap(long, default_value = ".")]
+        pub base_path: PathBuf,
+
+        /// Serve all repositories, not only those containing a `git-daemon-export-ok` file.
+        #[clap(long)]
+        pub export_all: bool,
+
+        /// Allow clients to push to the repositories we serve.
+        #[clap(long)]
+        pub enable_receive_pack: bool,
+
+        /// The address to listen on for TCP connections, defaulting to port 9418, or 8080 with `--http`.
+        #[clap(long, conflicts_with = "stdio")]
+        pub listen: Option<String>,
+
+        /// Serve a single connection over standard input and output, like `git daemon --inetd`.
+        ///
+        /// If `SSH_ORIGINAL_COMMAND` is set, as it is for forced commands of `ssh`, the request is taken from it instead.
+        #[clap(long)]
+        pub stdio: bool,
+
+        /// Speak the smart HTTP protocol instead of the `git://` protocol, like `git http-backend`.
+        ///
+        /// Repositories are served from the root of the URL, like `http://localhost:8080/project.git`.
+        #[clap(long, conflicts_with = "stdio")]
+        pub http: bool,
+    }
+}
+
+#[cfg(feature = "gitoxide-core-blocking-client")]
+pub mod remote_helper {
+    use std::{ffi::OsString, path::PathBuf};
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The name of the remote as configured in `git`, or the url if the remote isn't named.
+        pub remote: OsString,
+
+        /// The path to the bundle file to fetch from or push to.
+        pub bundle_path: PathBuf,
+    }
+}
+
 pub mod clone {
     use std::{ffi::OsString, num::NonZeroU32, path::PathBuf};
 
@@ -536,6 +967,56 @@ pub mod remote {
             #[clap(value_parser = crate::shared::AsBString)]
             ref_spec: Vec<gix::bstr::BString>,
         },
+        /// Add a new remote with the given name that fetches from the given URL.
+        Add {
+            /// Only track the given branch instead of all branches, can be specified multiple times.
+            #[clap(long = "track", short = 't', value_name = "BRANCH")]
+            track: Vec<String>,
+            /// Fetch all tags from the remote.
+            #[clap(long, conflicts_with = "no_tags")]
+            tags: bool,
+            /// Don't fetch any tags from the remote.
+            #[clap(long)]
+            no_tags: bool,
+            /// The name of the new remote.
+            remote: String,
+            /// The URL to fetch from.
+            url: String,
+        },
+        /// Remove a remote along with its remote tracking branches and configuration.
+        #[clap(visible_alias = "rm")]
+        Remove {
+            /// The name of the remote to remove.
+            remote: String,
+        },
+        /// Rename a remote along with its remote tracking branches and configuration.
+        Rename {
+            /// The current name of the remote.
+            old: String,
+            /// The new name of the remote.
+            new: String,
+        },
+        /// Show information about the remote, connecting to it to learn about new and stale branches.
+        Show,
+        /// Change the URL of a remote.
+        SetUrl {
+            /// Change the push URL instead of the fetch URL.
+            #[clap(long)]
+            push: bool,
+            /// Add the URL instead of replacing the existing one.
+            #[clap(long)]
+            add: bool,
+            /// The name of the remote to change.
+            remote: String,
+            /// The new URL.
+            url: String,
+        },
+        /// Delete remote tracking branches whose counterpart doesn't exist on the remote anymore.
+        Prune {
+            /// Only print which remote tracking branches would be deleted.
+            #[clap(long, short = 'n')]
+            dry_run: bool,
+        },
     }
 }
 
@@ -619,6 +1100,15 @@ pub mod clean {
     }
 }
 
+pub mod analyze {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The amount of paths and blobs to list, largest first.
+        #[clap(long, short = 'l', default_value_t = 20)]
+        pub limit: usize,
+    }
+}
+
 pub mod odb {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -825,6 +1315,27 @@ pub mod revision {
         #[clap(visible_alias = "prev")]
         PreviousBranches,
     }
+
+    #[derive(Debug, clap::Parser)]
+    pub struct RevParse {
+        /// Require exactly one rev-spec that resolves to a single object, and print its full hash.
+        #[clap(long)]
+        pub verify: bool,
+        /// Print the short name of the reference each rev-spec refers to, like `main` for `HEAD`.
+        #[clap(long, conflicts_with_all = ["verify", "symbolic_full_name"])]
+        pub abbrev_ref: bool,
+        /// Print the full name of the reference each rev-spec refers to, like `refs/heads/main` for `HEAD`.
+        #[clap(long, conflicts_with = "verify")]
+        pub symbolic_full_name: bool,
+        /// Print the absolute path to the root of the worktree.
+        #[clap(long)]
+        pub show_toplevel: bool,
+        /// Print the path to the git directory.
+        #[clap(long)]
+        pub git_dir: bool,
+        /// rev-specs like `@`, `main..feature` or `HEAD^{tree}`.
+        pub specs: Vec<std::ffi::OsString>,
+    }
 }
 
 pub mod attributes {
@@ -852,6 +1363,15 @@ pub mod attributes {
             #[clap(value_parser = CheckPathSpec)]
             pathspec: Vec<BString>,
         },
+        /// Check all files in the index for line endings that would change the next time they are added or checked out,
+        /// similar to `git diff --check`, and show the attributes that configure the conversion.
+        ///
+        /// Exits with an error if there is at least one such file.
+        CheckEol {
+            /// The git path specifications of the files to check, or unset to check all files in the index.
+            #[clap(value_parser = CheckPathSpec)]
+            pathspec: Vec<BString>,
+        },
     }
 }
 
@@ -962,6 +1482,48 @@ pub mod submodule {
             #[clap(short = 'd', long)]
             dirty_suffix: Option<Option<String>>,
         },
+        /// Print the commit checked out in each submodule, prefixed with `-` if uninitialized and `+` if it doesn't match the index.
+        Status {
+            /// Also print the status of nested submodules.
+            #[clap(long)]
+            recursive: bool,
+            /// Limit the output to submodules at or below these paths.
+            #[clap(value_parser = crate::shared::AsBString)]
+            paths: Vec<gix::bstr::BString>,
+        },
+        /// Copy the url and update strategy of submodules from `.gitmodules` into the local configuration.
+        Init {
+            /// Only initialize submodules at or below these paths.
+            #[clap(value_parser = crate::shared::AsBString)]
+            paths: Vec<gix::bstr::BString>,
+        },
+        /// Clone or fetch submodules and check out the commit recorded in the superproject.
+        #[cfg(feature = "gitoxide-core-blocking-client")]
+        Update {
+            /// Initialize submodules that aren't yet initialized before updating them.
+            #[clap(long)]
+            init: bool,
+            /// Also update nested submodules, initializing them as needed.
+            #[clap(long)]
+            recursive: bool,
+            /// The amount of submodules to fetch in parallel. Defaults to `submodule.fetchJobs` or the amount of cores.
+            #[clap(long, short = 'j')]
+            jobs: Option<usize>,
+            /// Only update submodules at or below these paths.
+            #[clap(value_parser = crate::shared::AsBString)]
+            paths: Vec<gix::bstr::BString>,
+        },
+        /// Run a shell command in each checked out submodule.
+        ///
+        /// The variables `$name`, `$sm_path`, `$displaypath`, `$sha1` and `$toplevel` are available to the command.
+        Foreach {
+            /// Also run the command in nested submodules.
+            #[clap(long)]
+            recursive: bool,
+            /// The command to run, along with its arguments.
+            #[clap(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+            command: Vec<std::ffi::OsString>,
+        },
     }
 }
 
@@ -94,14 +94,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.looseCompression",
         usage: Planned("")
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned("We want to support huge repos and be the fastest in doing so")
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned("This is a nice improvement over 'sparseCheckout' alone and should one day be available too")
-    },
     Record {
         config: "core.gitProxy",
         usage: NotPlanned("The transport mechanism works differently enough to not support it for now, but of course it's possible to add support if there is demand")
@@ -142,10 +134,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "commitGraph.readChangedPaths",
         usage: NotPlanned("Can be considered when the underlying feature is actually used or needed")
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned("We can read sparse indices and support for it will be added early on")
-    },
     Record {
         config: "merge.renormalize",
         usage: Planned("Once merging is being implemented, renormalization should be respected")
@@ -187,14 +175,6 @@ static GIT_CONFIG: &[Record] = &[
         usage: NotPlanned("No plan to implement 'format-patch' or 'request-pull' summary")
 
     },
-    Record {
-        config: "core.fsync",
-        usage: Planned("More safety for disk write operations is a good thing, definitely on the server")
-    },
-    Record {
-        config: "core.fsyncMethod",
-        usage: Planned("Needed to support 'core.fsync'")
-    },
     Record {
         config: "core.sharedRepository",
         usage: NotPlanned("On demand")
@@ -237,14 +217,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "fetch.unpackLimit",
         usage: Planned("")
     },
-    Record {
-        config: "fetch.prune",
-        usage: Planned("")
-    },
-    Record {
-        config: "fetch.pruneTags",
-        usage: Planned("")
-    },
     Record {
         config: "fetch.writeCommitGraph",
         usage: Planned("")
@@ -270,14 +242,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "advice.updateSparsePath",
         usage: NotApplicable("gitoxide does not yet have an 'advice' system")
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned("Together with 'index.sparse' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not")
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned("Non-cone mode is deprecated but should still fail gracefully if encountered")
-    },
     Record {
         config: "core.splitIndex",
         usage: NotPlanned("We don't want to be able to create split indices, but we will read them. It's (somewhat) superseded by sparse indices")
@@ -290,10 +254,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "splitIndex.sharedIndexExpire",
         usage: NotPlanned("Seems like it's superseded by sparse indices")
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned("Together with 'core.sparseCheckout' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not")
-    },
     Record {
         config: "index.version",
         usage: Planned("Once V4 indices can be written, we need to be able to set a desired version. For now we write the smallest possible index version only.")
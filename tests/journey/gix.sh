@@ -155,6 +155,758 @@ title "gix (with repository)"
       )
     )
   )
+
+  title "gix stash"
+  (when "running 'stash'"
+    snapshot="$snapshot/stash"
+    (small-repo-in-sandbox
+      (with "a file that is converted by filters and an untracked file"
+        git config core.autocrlf true
+        printf 'changed\r\n' > b
+        echo untracked > untracked
+        it "stashes them" && {
+          WITH_SNAPSHOT="$snapshot/push" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose stash push --include-untracked -m filtered
+        }
+        it "stores the file like git would store it" && {
+          WITH_SNAPSHOT="$snapshot/stashed-blob" \
+          expect_run $SUCCESSFULLY git cat-file -p 'stash@{0}:b'
+        }
+        it "leaves a clean worktree" && {
+          expect_run $SUCCESSFULLY git diff --quiet &&
+          expect_run $WITH_FAILURE test -e untracked
+        }
+        it "lists the stash entry" && {
+          WITH_SNAPSHOT="$snapshot/list" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose stash list
+        }
+        it "restores the changes when popping the entry" && {
+          WITH_SNAPSHOT="$snapshot/pop" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose stash pop
+        }
+        it "restores the file as it was in the worktree" && {
+          printf 'changed\r\n' > expected &&
+          expect_run $SUCCESSFULLY cmp b expected &&
+          rm expected
+        }
+        it "restores the untracked file" && {
+          WITH_SNAPSHOT="$snapshot/status-after-pop" \
+          expect_run $SUCCESSFULLY git status --porcelain
+        }
+      )
+      (with "changes that conflict with the stash entry"
+        git reset -q --hard && rm untracked
+        echo stashed > b
+        "$exe_plumbing" --no-verbose stash push &>/dev/null
+        echo upstream > b && git commit -qam upstream
+        it "refuses to apply it" && {
+          WITH_SNAPSHOT="$snapshot/fail-conflict" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose stash apply
+        }
+        it "changes nothing and keeps the stash entry" && {
+          expect_run $SUCCESSFULLY git diff --quiet &&
+          expect_run $SUCCESSFULLY git rev-parse --verify -q 'stash@{0}'
+        }
+      )
+    )
+  )
+
+  if [[ "$kind" != "small" && "$kind" != "async" ]]; then
+  title "gix serve"
+  (when "running 'serve'"
+    snapshot="$snapshot/serve"
+    (with "the --stdio flag and a proc-receive hook"
+      (small-repo-in-sandbox
+        {
+          git init --bare server.git
+          git -C server.git config receive.procReceiveRefs refs/for
+          cat >server.git/hooks/proc-receive <<'EOF'
+#!/bin/sh
+pkt() { printf '%04x%s\n' $((${#1} + 5)) "$1"; }
+pkt version=1
+printf 0000
+cat >/dev/null &
+pkt "ok refs/for/main/topic"
+pkt "option refname refs/pull/1/head"
+pkt "option old-oid 0000000000000000000000000000000000000000"
+printf 0000
+EOF
+          chmod +x server.git/hooks/proc-receive
+          cat >ssh <<EOF
+#!/bin/sh
+shift
+SSH_ORIGINAL_COMMAND="\$*" exec "$exe_plumbing" --no-verbose serve --stdio --base-path "$PWD" --export-all --enable-receive-pack
+EOF
+          chmod +x ssh
+        } &>/dev/null
+        it "reports the references rewritten by the hook using report-status-v2" && {
+          WITH_SNAPSHOT="$snapshot/proc-receive-report-status-v2" \
+          GIT_SSH="$PWD/ssh" GIT_SSH_VARIANT=simple \
+          expect_run $SUCCESSFULLY git push --porcelain ssh://localhost/server.git HEAD:refs/for/main/topic
+        }
+        it "doesn't create the pushed reference" && {
+          expect_run $WITH_FAILURE git -C server.git rev-parse --verify --quiet refs/for/main/topic
+        }
+      )
+    )
+    (with "the --http flag"
+      (small-repo-in-sandbox
+        port=9419
+        "$exe_plumbing" --no-verbose serve --http --listen "127.0.0.1:$port" --base-path . --export-all --enable-receive-pack &>/dev/null &
+        server_pid=$!
+        trap 'kill $server_pid' EXIT
+        while ! nc -z 127.0.0.1 $port; do
+          sleep 0.1
+        done
+        url="http://127.0.0.1:$port/"
+        it "lists references" && {
+          WITH_SNAPSHOT="$snapshot/http-ls-remote" \
+          expect_run $SUCCESSFULLY git ls-remote "$url"
+        }
+        it "clones the repository" && {
+          expect_run $SUCCESSFULLY git clone --quiet "$url" clone
+        }
+        (with "a new commit in the clone"
+          {
+            cd clone
+            git commit --allow-empty -m "pushed"
+          } &>/dev/null
+          it "pushes it" && {
+            expect_run $SUCCESSFULLY git push --quiet origin HEAD:refs/heads/pushed
+          }
+          it "updated the served repository" && {
+            expect_equals "$(git -C .. rev-parse pushed)" "$(git rev-parse HEAD)"
+          }
+        )
+      )
+    )
+  )
+  fi
+
+  title "gix analyze"
+  (with "the 'analyze' sub-command"
+    snapshot="$snapshot/analyze"
+    (small-repo-in-sandbox
+      (with 'human output format'
+        it "generates correct output" && {
+          WITH_SNAPSHOT="$snapshot/success-format-human" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose analyze
+        }
+      )
+      (with 'a limit'
+        it "lists only the largest paths and blobs" && {
+          WITH_SNAPSHOT="$snapshot/success-limit-1" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose analyze --limit 1
+        }
+      )
+      if test "$kind" = "max" || test "$kind" = "max-pure"; then
+      (with "--format json"
+        it "generates the correct output in JSON format" && {
+          WITH_SNAPSHOT="$snapshot/success-format-json" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose --format json analyze
+        }
+      )
+      fi
+    )
+  )
+
+  title "gix tag"
+  (when "running 'tag'"
+    snapshot="$snapshot/tag"
+    (small-repo-in-sandbox
+      (with "the 'list' sub-command"
+        it "lists all tags" && {
+          WITH_SNAPSHOT="$snapshot/list" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose tag list
+        }
+        it "lists matching tags in the given order" && {
+          WITH_SNAPSHOT="$snapshot/list-sorted-with-pattern" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose tag list --sort=-refname '*annotated'
+        }
+      )
+      (with "the 'create' sub-command"
+        it "creates a lightweight tag" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose tag create lightweight HEAD~1
+        }
+        it "creates an annotated tag with a message" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose tag create -m "a message" with-message
+        }
+        it "wrote the expected tags" && {
+          WITH_SNAPSHOT="$snapshot/create-result" \
+          expect_run_sh $SUCCESSFULLY "git rev-parse lightweight && git cat-file -p with-message"
+        }
+        it "fails if the tag exists already" && {
+          WITH_SNAPSHOT="$snapshot/create-fail-exists" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose tag create annotated
+        }
+        it "replaces an existing tag when forced" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose tag create --force lightweight
+        }
+      )
+      (with "the 'delete' sub-command"
+        it "deletes the tag" && {
+          WITH_SNAPSHOT="$snapshot/delete" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose tag delete lightweight
+        }
+        it "fails if the tag doesn't exist" && {
+          WITH_SNAPSHOT="$snapshot/delete-fail-missing" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose tag delete lightweight
+        }
+      )
+      (with "the 'verify' sub-command"
+        it "fails for tags without signature" && {
+          WITH_SNAPSHOT="$snapshot/verify-fail-unsigned" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose tag verify annotated
+        }
+      )
+    )
+  )
+
+  title "gix branch"
+  (when "running 'branch'"
+    snapshot="$snapshot/branch"
+    (small-repo-in-sandbox
+      (with "the 'create' sub-command"
+        it "creates a branch at the given start point" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch create feature HEAD~1
+        }
+        it "fails if the branch exists already" && {
+          WITH_SNAPSHOT="$snapshot/create-fail-exists" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose branch create feature
+        }
+        it "resets the branch when forced" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch create --force feature
+        }
+      )
+      (with "the 'rename' sub-command"
+        it "renames the branch" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch rename feature renamed
+        }
+      )
+      (with "the 'upstream' sub-command"
+        it "sets the upstream of the branch" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch upstream set main renamed
+        }
+        it "lists the branches with their upstream" && {
+          WITH_SNAPSHOT="$snapshot/list-with-upstream" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch list -vv
+        }
+        it "removes the upstream of the branch" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch upstream unset renamed
+        }
+        it "lists the branches without upstream" && {
+          WITH_SNAPSHOT="$snapshot/list-without-upstream" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch list -vv
+        }
+      )
+      (with "the 'delete' sub-command"
+        {
+          git checkout -b unmerged
+          git commit --allow-empty -m "unmerged"
+          git checkout main
+        } &>/dev/null
+        it "deletes a merged branch" && {
+          WITH_SNAPSHOT="$snapshot/delete" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch delete renamed
+        }
+        it "refuses to delete a branch that isn't merged" && {
+          WITH_SNAPSHOT="$snapshot/delete-fail-unmerged" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose branch delete unmerged
+        }
+        it "deletes a branch that isn't merged when forced" && {
+          WITH_SNAPSHOT="$snapshot/delete-forced" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose branch delete --force unmerged
+        }
+      )
+    )
+  )
+
+  title "gix switch and gix checkout"
+  (when "running 'switch'"
+    snapshot="$snapshot/switch"
+    (small-repo-in-sandbox
+      it "switches to a branch" && {
+        WITH_SNAPSHOT="$snapshot/branch" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose switch dev
+      }
+      (with "local modifications"
+        echo "local" > b
+        it "refuses to overwrite them" && {
+          WITH_SNAPSHOT="$snapshot/fail-local-modifications" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose switch main
+        }
+        it "discards them when forced" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose switch --force main
+        }
+        it "leaves a clean worktree" && {
+          expect_run $SUCCESSFULLY git diff --quiet
+        }
+      )
+      it "creates a new branch and switches to it" && {
+        WITH_SNAPSHOT="$snapshot/create" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose switch -c topic HEAD~1
+      }
+      it "detaches HEAD" && {
+        WITH_SNAPSHOT="$snapshot/detach" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose switch --detach main
+      }
+      it "refuses to switch to a tag without --detach" && {
+        WITH_SNAPSHOT="$snapshot/fail-not-a-branch" \
+        expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose switch unannotated
+      }
+    )
+  )
+  (when "running 'checkout'"
+    snapshot="$snapshot/checkout"
+    (small-repo-in-sandbox
+      it "creates a new branch and switches to it" && {
+        WITH_SNAPSHOT="$snapshot/create" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose checkout -b topic dev
+      }
+      it "switches to a branch" && {
+        WITH_SNAPSHOT="$snapshot/branch" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose checkout main
+      }
+      (with "paths"
+        echo "change" > b
+        it "restores them from the index" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose checkout -- b
+        }
+        it "leaves a clean worktree" && {
+          expect_run $SUCCESSFULLY git diff --quiet
+        }
+        it "restores them from a revision" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose checkout dev -- b
+        }
+        it "updates the index and the worktree" && {
+          WITH_SNAPSHOT="$snapshot/paths-from-revision-status" \
+          expect_run $SUCCESSFULLY git status --porcelain
+        }
+      )
+    )
+  )
+
+  title "gix rev-parse"
+  (with "the 'rev-parse' sub-command"
+    snapshot="$snapshot/rev-parse"
+    (small-repo-in-sandbox
+      it "resolves rev-specs to object ids" && {
+        WITH_SNAPSHOT="$snapshot/specs" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose rev-parse HEAD main~1 'HEAD^{tree}' main..dev
+      }
+      (with "--verify"
+        it "prints the full hash of a single object" && {
+          WITH_SNAPSHOT="$snapshot/verify" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose rev-parse --verify HEAD
+        }
+        it "fails for more than one rev-spec" && {
+          WITH_SNAPSHOT="$snapshot/verify-fail-multiple" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose rev-parse --verify HEAD main
+        }
+        it "fails for rev-specs that don't exist" && {
+          WITH_SNAPSHOT="$snapshot/verify-fail-missing" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose rev-parse --verify does-not-exist
+        }
+      )
+      (with "--abbrev-ref"
+        it "prints the short names of references" && {
+          WITH_SNAPSHOT="$snapshot/abbrev-ref" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose rev-parse --abbrev-ref HEAD annotated
+        }
+      )
+      (with "--symbolic-full-name"
+        it "prints the full names of references" && {
+          WITH_SNAPSHOT="$snapshot/symbolic-full-name" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose rev-parse --symbolic-full-name HEAD dev
+        }
+      )
+      (with "--git-dir"
+        it "prints the relative path to the git directory" && {
+          WITH_SNAPSHOT="$snapshot/git-dir" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose rev-parse --git-dir
+        }
+      )
+      (with "--show-toplevel in a sub-directory"
+        mkdir sub
+        toplevel="$PWD"
+        cd sub
+        it "prints the path to the worktree root" && {
+          expect_equals "$("$exe_plumbing" --no-verbose rev-parse --show-toplevel)" "$toplevel"
+        }
+      )
+    )
+  )
+
+  title "gix show"
+  (with "the 'show' sub-command"
+    snapshot="$snapshot/show"
+    (small-repo-in-sandbox
+      it "shows the HEAD commit with its patch" && {
+        WITH_SNAPSHOT="$snapshot/commit" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose show
+      }
+      (with "--oneline"
+        it "shows the commit in a single line along with its patch" && {
+          WITH_SNAPSHOT="$snapshot/commit-oneline" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose show --oneline HEAD~1
+        }
+      )
+      (with "--stat"
+        it "shows the amount of changed lines per file" && {
+          WITH_SNAPSHOT="$snapshot/commit-stat" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose show --stat
+        }
+      )
+      (with "--no-patch"
+        it "shows an annotated tag and the commit it points to" && {
+          WITH_SNAPSHOT="$snapshot/tag-no-patch" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose show --no-patch annotated
+        }
+      )
+      it "shows the entries of a tree" && {
+        WITH_SNAPSHOT="$snapshot/tree" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose show 'HEAD^{tree}'
+      }
+      it "shows the content of a blob" && {
+        WITH_SNAPSHOT="$snapshot/blob" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose show HEAD:b
+      }
+      it "fails for revisions that don't exist" && {
+        WITH_SNAPSHOT="$snapshot/fail-missing" \
+        expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose show does-not-exist
+      }
+    )
+  )
+
+  title "gix worktree"
+  (when "running 'worktree'"
+    snapshot="$snapshot/worktree"
+    (small-repo-in-sandbox
+      toplevel="$(pwd -P)"
+      (with "the 'add' sub-command"
+        it "creates a worktree with a new branch named after the directory" && {
+          WITH_SNAPSHOT="$snapshot/add" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree add wt-feature
+        }
+        it "creates a worktree with a new branch at the given revision" && {
+          WITH_SNAPSHOT="$snapshot/add-new-branch" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree add -b topic wt-topic HEAD~1
+        }
+        it "creates a worktree with detached HEAD" && {
+          WITH_SNAPSHOT="$snapshot/add-detached" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree add --detach wt-detached
+        }
+        it "refuses to check out a branch that is checked out already" && {
+          WITH_SNAPSHOT="$snapshot/add-fail-branch-in-use" \
+          expect_run_sh $WITH_FAILURE "'$exe_plumbing' --no-verbose worktree add wt-main main 2>&1 | sed 's#$toplevel#<toplevel>#g'"
+        }
+      )
+      (with "the 'lock' sub-command"
+        it "locks the worktree" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree lock --reason "on a removable drive" wt-topic
+        }
+        it "lists the lock in porcelain format" && {
+          WITH_SNAPSHOT="$snapshot/list-porcelain" \
+          expect_run_sh $SUCCESSFULLY "'$exe_plumbing' --no-verbose worktree list --porcelain | sed 's#$toplevel#<toplevel>#g'"
+        }
+        it "prevents the worktree from being removed" && {
+          WITH_SNAPSHOT="$snapshot/remove-fail-locked" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose worktree remove wt-topic
+        }
+        it "prevents the worktree from being moved" && {
+          WITH_SNAPSHOT="$snapshot/move-fail-locked" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose worktree move wt-topic wt-moved
+        }
+      )
+      (with "the 'unlock' and 'move' sub-commands"
+        it "unlocks the worktree" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree unlock wt-topic
+        }
+        it "moves the worktree" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree move wt-topic wt-moved
+        }
+        it "keeps the worktree usable" && {
+          expect_run $SUCCESSFULLY git -C wt-moved status
+        }
+      )
+      (with "the 'remove' sub-command"
+        echo "modified" > wt-moved/a
+        it "refuses to remove a worktree with modifications" && {
+          WITH_SNAPSHOT="$snapshot/remove-fail-modified" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose worktree remove wt-moved
+        }
+        it "removes it when forced" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree remove --force wt-moved
+        }
+        it "removed the worktree directory" && {
+          expect_run $WITH_FAILURE test -e wt-moved
+        }
+      )
+      (with "the 'prune' sub-command"
+        rm -Rf wt-detached
+        it "prints what would be pruned" && {
+          WITH_SNAPSHOT="$snapshot/prune-dry-run" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree prune --dry-run
+        }
+        it "prunes worktrees whose directory is gone" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose worktree prune
+        }
+        it "lists only the remaining worktrees" && {
+          WITH_SNAPSHOT="$snapshot/list-after-prune" \
+          expect_run_sh $SUCCESSFULLY "'$exe_plumbing' --no-verbose worktree list | sed 's#$toplevel#<toplevel>#g'"
+        }
+      )
+    )
+  )
+
+  if [[ "$kind" != "small" ]]; then
+  title "gix remote (configuration)"
+  (when "running 'remote' to change remotes"
+    snapshot="$snapshot/remote"
+    (small-repo-in-sandbox
+      {
+        git branch gone
+        git clone . clone
+        git -C clone remote set-url origin ..
+        git branch -D gone
+      } &>/dev/null
+      cd clone
+      (with "the 'add' sub-command"
+        it "adds a remote tracking only the given branches" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote add --track main --no-tags other ..
+        }
+        it "writes its configuration" && {
+          WITH_SNAPSHOT="$snapshot/add-config" \
+          expect_run $SUCCESSFULLY git config --get-regexp '^remote\.other\.'
+        }
+        it "fails if the remote exists already" && {
+          WITH_SNAPSHOT="$snapshot/add-fail-exists" \
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose remote add other ..
+        }
+      )
+      if [[ "$kind" != "async" ]]; then
+      (with "the 'show' sub-command"
+        it "shows tracked and stale branches" && {
+          WITH_SNAPSHOT="$snapshot/show" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote -n origin show
+        }
+      )
+      (with "the 'prune' sub-command"
+        it "prints which branches would be pruned" && {
+          WITH_SNAPSHOT="$snapshot/prune-dry-run" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote -n origin prune --dry-run
+        }
+        it "prunes stale remote tracking branches" && {
+          WITH_SNAPSHOT="$snapshot/prune" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote -n origin prune
+        }
+        it "deleted the remote tracking branch" && {
+          expect_run $WITH_FAILURE git rev-parse --verify --quiet refs/remotes/origin/gone
+        }
+      )
+      fi
+      (with "the 'rename' and 'set-url' sub-commands"
+        git fetch other &>/dev/null
+        it "renames the remote" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote rename other renamed
+        }
+        it "adds a push URL" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote set-url --push --add renamed ../push
+        }
+        it "rewrote the configuration and the remote tracking branches" && {
+          WITH_SNAPSHOT="$snapshot/rename-config-and-refs" \
+          expect_run_sh $SUCCESSFULLY "git config --get-regexp '^remote\.renamed\.' && git for-each-ref --format='%(refname)' refs/remotes/renamed"
+        }
+      )
+      (with "the 'remove' sub-command"
+        it "removes the remote" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose remote remove renamed
+        }
+        it "removed its configuration" && {
+          expect_run $WITH_FAILURE git config --get-regexp '^remote\.renamed\.'
+        }
+        it "removed its remote tracking branches" && {
+          WITH_SNAPSHOT="$snapshot/remove-refs" \
+          expect_run $SUCCESSFULLY git for-each-ref --format='%(refname)' refs/remotes
+        }
+      )
+    )
+  )
+  fi
+
+  title "gix submodule"
+  (when "running 'submodule'"
+    snapshot="$snapshot/submodule"
+    (sandbox
+      {
+        git init sub
+        git -C sub checkout -b main
+        git -C sub commit --allow-empty -m "first"
+        git -C sub tag first
+        git -C sub commit --allow-empty -m "second"
+        git init super
+        git -C super -c protocol.file.allow=always submodule add ../sub sub
+        git -C super commit -m "add submodule"
+        git clone super clone
+      } &>/dev/null
+      cd clone
+      sandbox_root="$(cd .. && pwd -P)"
+      it "shows uninitialized submodules" && {
+        WITH_SNAPSHOT="$snapshot/status-uninitialized" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose submodule status
+      }
+      (with "the 'init' sub-command"
+        it "registers the submodules" && {
+          WITH_SNAPSHOT="$snapshot/init" \
+          expect_run_sh $SUCCESSFULLY "'$exe_plumbing' --no-verbose submodule init | sed 's#$sandbox_root#<sandbox>#g'"
+        }
+        it "writes the local configuration" && {
+          WITH_SNAPSHOT="$snapshot/init-config" \
+          expect_run_sh $SUCCESSFULLY "git config --get-regexp '^submodule\.' | sed 's#$sandbox_root#<sandbox>#g'"
+        }
+      )
+      if [[ "$kind" != "small" && "$kind" != "async" ]]; then
+      (with "the 'update' sub-command"
+        it "clones and checks out the submodules" && {
+          WITH_SNAPSHOT="$snapshot/update" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose submodule update
+        }
+        it "shows the checked out commit" && {
+          WITH_SNAPSHOT="$snapshot/status" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose submodule status
+        }
+        (with "a submodule that doesn't match the index"
+          git -C sub checkout HEAD~1 &>/dev/null
+          it "shows the mismatch" && {
+            WITH_SNAPSHOT="$snapshot/status-modified" \
+            expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose submodule status
+          }
+          it "checks out the recorded commit again" && {
+            WITH_SNAPSHOT="$snapshot/update" \
+            expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose submodule update
+          }
+        )
+        (with "the 'foreach' sub-command"
+          it "runs the command in each submodule" && {
+            WITH_SNAPSHOT="$snapshot/foreach" \
+            expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose submodule foreach 'echo $name $sm_path $sha1 $displaypath'
+          }
+        )
+      )
+      fi
+    )
+  )
+
+  if [[ "$kind" != "small" && "$kind" != "async" ]]; then
+  title "gix mirror"
+  (with "the 'mirror' sub-command"
+    snapshot="$snapshot/mirror"
+    (small-repo-in-sandbox
+      {
+        git init --bare local.git
+        git init --bare push.git
+        git -C local.git remote add origin ..
+      } &>/dev/null
+      cd local.git
+      (with "--dry-run"
+        it "prints what would be mirrored" && {
+          WITH_SNAPSHOT="$snapshot/dry-run" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose mirror --dry-run origin
+        }
+        it "doesn't create any reference" && {
+          expect_run $SUCCESSFULLY test -z "$(git for-each-ref)"
+        }
+      )
+      it "mirrors all references" && {
+        WITH_SNAPSHOT="$snapshot/initial" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose mirror origin
+      }
+      (with "references that were deleted on the remote or only exist locally"
+        {
+          git -C .. branch -D dev
+          git -C .. branch new main~1
+          git update-ref refs/heads/local-only main
+        } &>/dev/null
+        it "keeps them without --prune" && {
+          WITH_SNAPSHOT="$snapshot/update" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose mirror origin
+        }
+        it "deletes them with --prune" && {
+          WITH_SNAPSHOT="$snapshot/prune" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose mirror --prune --limit-rate 1000000 origin
+        }
+        it "leaves only the references of the remote" && {
+          WITH_SNAPSHOT="$snapshot/prune-refs" \
+          expect_run $SUCCESSFULLY git for-each-ref --format='%(refname)'
+        }
+      )
+      (with "--push-to"
+        it "pushes all references to the other remote" && {
+          WITH_SNAPSHOT="$snapshot/push-to" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose mirror --push-to ../push.git origin
+        }
+        it "made the other remote a mirror" && {
+          WITH_SNAPSHOT="$snapshot/prune-refs" \
+          expect_run $SUCCESSFULLY git -C ../push.git for-each-ref --format='%(refname)'
+        }
+      )
+    )
+  )
+  fi
+
+  if [[ "$kind" != "small" && "$kind" != "async" ]]; then
+  title "gix remote-helper"
+  (when "running 'remote-helper'"
+    snapshot="$snapshot/remote-helper"
+    (small-repo-in-sandbox
+      {
+        mkdir bin
+        printf '#!/bin/sh\nexec "%s" --no-verbose remote-helper "$@"\n' "$exe_plumbing" > bin/git-remote-gix
+        chmod +x bin/git-remote-gix
+        git bundle create repo.bundle --all
+      } &>/dev/null
+      export PATH="$PWD/bin:$PATH"
+      it "clones from a bundle created by git" && {
+        expect_run $SUCCESSFULLY git clone -q "gix::$PWD/repo.bundle" clone
+      }
+      it "checks out the branch HEAD pointed to" && {
+        WITH_SNAPSHOT="$snapshot/clone-log" \
+        expect_run $SUCCESSFULLY git -C clone log --oneline --decorate
+      }
+      (with "a new commit in the clone"
+        {
+          echo new > clone/new
+          git -C clone add new
+          git -C clone commit -m "fourth"
+        } &>/dev/null
+        it "pushes it into the bundle" && {
+          expect_run $SUCCESSFULLY git -C clone push -q origin main main:refs/heads/new
+        }
+        it "rewrites the bundle with all previous and pushed references" && {
+          WITH_SNAPSHOT="$snapshot/pushed-heads" \
+          expect_run $SUCCESSFULLY git bundle list-heads repo.bundle
+        }
+        it "rejects pushes that aren't fast-forwards" && {
+          expect_run $WITH_FAILURE git -C clone push -q origin main~2:main
+        }
+        it "can still be cloned by git" && {
+          expect_run $SUCCESSFULLY git clone -q repo.bundle clone-by-git
+        }
+      )
+      (with "an incremental bundle with prerequisites"
+        {
+          echo more >> a
+          git commit -am "fifth"
+          git bundle create incremental.bundle main~1..main
+        } &>/dev/null
+        it "fetches it when the prerequisites are present" && {
+          expect_run $SUCCESSFULLY git -C clone-by-git fetch -q "gix::$PWD/incremental.bundle" main &&
+          expect_run $SUCCESSFULLY test "$(git -C clone-by-git rev-parse FETCH_HEAD)" = "$(git rev-parse main)"
+        }
+      )
+    )
+  )
+  fi
 )
 
 title "gix attributes"